@@ -0,0 +1,141 @@
+//! First-run detection and environment bootstrap
+//!
+//! The first time xcargo runs on a machine (no `~/.xcargo` state), it checks
+//! whether any cross-compilation route is set up. If none is, the CLI offers a
+//! guided setup. A marker file in the state directory ensures the offer is
+//! only made once.
+
+use crate::error::{Error, Result};
+use crate::toolchain::zig::ZigToolchain;
+use crate::toolchain::ToolchainManager;
+use std::fs;
+use std::path::{Path, PathBuf};
+use which::which;
+
+/// Environment variable that disables the first-run bootstrap
+pub const NO_FIRST_RUN_ENV: &str = "XCARGO_NO_FIRST_RUN";
+
+/// Marker file written once the first run has been handled
+const FIRST_RUN_MARKER: &str = "first-run-complete";
+
+/// Directory holding xcargo's per-user state (`~/.xcargo`)
+#[must_use]
+pub fn state_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".xcargo"))
+}
+
+/// Whether this is the first xcargo invocation for the current user
+#[must_use]
+pub fn is_first_run() -> bool {
+    state_dir().is_some_and(|dir| is_first_run_in(&dir))
+}
+
+/// Record that the first run has been handled, so the bootstrap is not offered again
+pub fn mark_first_run_complete() -> Result<()> {
+    let dir = state_dir()
+        .ok_or_else(|| Error::Config("Could not determine home directory".to_string()))?;
+    mark_first_run_complete_in(&dir)
+}
+
+fn is_first_run_in(dir: &Path) -> bool {
+    !dir.join(FIRST_RUN_MARKER).exists()
+}
+
+fn mark_first_run_complete_in(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(FIRST_RUN_MARKER), env!("CARGO_PKG_VERSION"))?;
+    Ok(())
+}
+
+/// Which cross-compilation routes are available on this machine
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvironmentStatus {
+    /// Number of rustup targets installed besides the host
+    pub extra_targets: usize,
+
+    /// Container runtime found on PATH (docker or podman)
+    pub container_runtime: Option<String>,
+
+    /// Whether Zig is available
+    pub zig: bool,
+}
+
+impl EnvironmentStatus {
+    /// Detect the current environment
+    #[must_use]
+    pub fn detect() -> Self {
+        let extra_targets = ToolchainManager::new()
+            .and_then(|manager| {
+                let toolchain = manager
+                    .get_default_toolchain()?
+                    .map_or_else(|| "stable".to_string(), |tc| tc.name);
+                manager.list_targets(&toolchain)
+            })
+            .map_or(0, |targets| targets.len().saturating_sub(1));
+
+        let container_runtime = ["docker", "podman"]
+            .into_iter()
+            .find(|runtime| which(runtime).is_ok())
+            .map(str::to_string);
+
+        let zig = ZigToolchain::detect().ok().flatten().is_some();
+
+        Self {
+            extra_targets,
+            container_runtime,
+            zig,
+        }
+    }
+
+    /// Whether no cross-compilation route is set up at all
+    #[must_use]
+    pub fn is_incomplete(&self) -> bool {
+        self.extra_targets == 0 && self.container_runtime.is_none() && !self.zig
+    }
+
+    /// Descriptions of the missing pieces, for display
+    #[must_use]
+    pub fn missing(&self) -> Vec<&'static str> {
+        let mut missing = Vec::new();
+        if self.extra_targets == 0 {
+            missing.push("No rustup targets installed besides the host");
+        }
+        if self.container_runtime.is_none() {
+            missing.push("No container runtime (Docker or Podman) found");
+        }
+        if !self.zig {
+            missing.push("Zig not found");
+        }
+        missing
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_first_run_marker() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join(".xcargo");
+
+        assert!(is_first_run_in(&dir));
+        mark_first_run_complete_in(&dir).unwrap();
+        assert!(!is_first_run_in(&dir));
+    }
+
+    #[test]
+    fn test_environment_status_incomplete() {
+        let empty = EnvironmentStatus::default();
+        assert!(empty.is_incomplete());
+        assert_eq!(empty.missing().len(), 3);
+
+        let with_zig = EnvironmentStatus {
+            zig: true,
+            ..EnvironmentStatus::default()
+        };
+        assert!(!with_zig.is_incomplete());
+        assert_eq!(with_zig.missing().len(), 2);
+    }
+}
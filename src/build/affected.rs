@@ -0,0 +1,545 @@
+//! Change detection for incremental multi-target builds
+//!
+//! `xcargo build --all` in a large workspace rebuilds every member for every
+//! target. Using `cargo metadata` and `git diff`, this module works out which
+//! members a change can affect, including members that depend on a changed
+//! one through a dependency that applies to the target being built.
+
+use super::args::CargoArgs;
+use crate::error::{Error, Result};
+use crate::target::Target;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Files at the workspace root that affect every member when changed
+const GLOBAL_FILES: &[&str] = &[
+    "Cargo.toml",
+    "Cargo.lock",
+    "xcargo.toml",
+    "rust-toolchain",
+    "rust-toolchain.toml",
+];
+
+/// Members of a workspace affected by a set of changes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Affected {
+    /// A workspace-wide file changed; everything must be rebuilt
+    All,
+    /// Only these members need to be rebuilt (may be empty)
+    Members(Vec<String>),
+}
+
+/// A dependency of one workspace member on another
+#[derive(Debug, Clone)]
+struct MemberDep {
+    name: String,
+    /// Platform the dependency is limited to (`cfg(...)` or a triple)
+    platform: Option<String>,
+}
+
+/// A workspace member package
+#[derive(Debug, Clone)]
+struct Member {
+    name: String,
+    dir: PathBuf,
+    deps: Vec<MemberDep>,
+}
+
+/// Workspace members and the path dependencies between them
+#[derive(Debug, Clone)]
+pub struct WorkspaceGraph {
+    root: PathBuf,
+    members: Vec<Member>,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<MetadataPackage>,
+    workspace_members: Vec<String>,
+    workspace_root: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct MetadataPackage {
+    id: String,
+    name: String,
+    manifest_path: PathBuf,
+    #[serde(default)]
+    dependencies: Vec<MetadataDependency>,
+}
+
+#[derive(Deserialize)]
+struct MetadataDependency {
+    name: String,
+    #[serde(default)]
+    target: Option<String>,
+    #[serde(default)]
+    path: Option<PathBuf>,
+}
+
+impl WorkspaceGraph {
+    /// Load the graph of the workspace containing the current directory
+    pub fn load() -> Result<Self> {
+        let output = Command::new("cargo")
+            .args(["metadata", "--no-deps", "--format-version", "1"])
+            .output()
+            .map_err(|e| Error::Build(format!("Failed to run cargo metadata: {e}")))?;
+
+        if !output.status.success() {
+            return Err(Error::Build(format!(
+                "cargo metadata failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Self::from_metadata(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// Build the graph from `cargo metadata --format-version 1` JSON output
+    pub fn from_metadata(json: &str) -> Result<Self> {
+        let metadata: Metadata = serde_json::from_str(json)
+            .map_err(|e| Error::Build(format!("Invalid cargo metadata output: {e}")))?;
+
+        let members: Vec<&MetadataPackage> = metadata
+            .packages
+            .iter()
+            .filter(|p| metadata.workspace_members.contains(&p.id))
+            .collect();
+        let names: BTreeSet<&str> = members.iter().map(|p| p.name.as_str()).collect();
+
+        let members = members
+            .iter()
+            .map(|package| Member {
+                name: package.name.clone(),
+                dir: package
+                    .manifest_path
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .unwrap_or_default(),
+                deps: package
+                    .dependencies
+                    .iter()
+                    // `name` is the package name even when the dependency is renamed
+                    .filter(|d| d.path.is_some() && names.contains(d.name.as_str()))
+                    .map(|d| MemberDep {
+                        name: d.name.clone(),
+                        platform: d.target.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Self {
+            root: metadata.workspace_root,
+            members,
+        })
+    }
+
+    /// Names of all workspace members
+    #[must_use]
+    pub fn member_names(&self) -> Vec<String> {
+        self.members.iter().map(|m| m.name.clone()).collect()
+    }
+
+    /// Whether the root manifest is a virtual manifest (no root package)
+    #[must_use]
+    pub fn is_virtual(&self) -> bool {
+        !self.members.iter().any(|m| m.dir == self.root)
+    }
+
+    /// Compute the members affected by `changed` files when building for `target`
+    ///
+    /// Paths may be absolute or relative to the workspace root. A member is
+    /// affected if one of its files changed, or if it depends (through a
+    /// dependency that applies to `target`) on an affected member.
+    #[must_use]
+    pub fn affected(&self, changed: &[PathBuf], target: &Target) -> Affected {
+        let mut affected = BTreeSet::new();
+
+        for path in changed {
+            let path = if path.is_absolute() {
+                path.clone()
+            } else {
+                self.root.join(path)
+            };
+
+            if self.is_global_file(&path) {
+                return Affected::All;
+            }
+
+            // The deepest member directory owns the file (members can be nested)
+            if let Some(member) = self
+                .members
+                .iter()
+                .filter(|m| path.starts_with(&m.dir))
+                .max_by_key(|m| m.dir.components().count())
+            {
+                affected.insert(member.name.clone());
+            }
+        }
+
+        // Propagate to dependents until nothing changes
+        loop {
+            let before = affected.len();
+            for member in &self.members {
+                if affected.contains(&member.name) {
+                    continue;
+                }
+                let depends_on_affected = member.deps.iter().any(|dep| {
+                    affected.contains(&dep.name)
+                        && dep
+                            .platform
+                            .as_deref()
+                            .map_or(true, |spec| platform_matches(spec, target))
+                });
+                if depends_on_affected {
+                    affected.insert(member.name.clone());
+                }
+            }
+            if affected.len() == before {
+                break;
+            }
+        }
+
+        Affected::Members(affected.into_iter().collect())
+    }
+
+    fn is_global_file(&self, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(&self.root) else {
+            return false;
+        };
+
+        relative.starts_with(".cargo")
+            || GLOBAL_FILES.iter().any(|file| relative == Path::new(file))
+    }
+}
+
+/// Per-target package selection for an incremental multi-target build
+#[derive(Debug, Clone)]
+pub struct IncrementalPlan {
+    graph: WorkspaceGraph,
+    changed: Vec<PathBuf>,
+    exclude: Vec<String>,
+}
+
+impl IncrementalPlan {
+    /// Plan an incremental build of changes since the git ref `since`
+    ///
+    /// Returns `None` when the build does not cover the whole workspace (a
+    /// non-virtual root without `--workspace`, or packages selected with
+    /// `-p`), since there is nothing to narrow down.
+    pub fn new(since: &str, args: &CargoArgs) -> Result<Option<Self>> {
+        if args.selects_packages() {
+            return Ok(None);
+        }
+
+        let graph = WorkspaceGraph::load()?;
+        if !args.workspace && !graph.is_virtual() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            graph,
+            changed: changed_files(since)?,
+            exclude: args.exclude.clone(),
+        }))
+    }
+
+    /// Members to build for `target`, or `None` if everything must be built
+    ///
+    /// An empty list means no member is affected and the target can be skipped.
+    /// Members excluded with `--exclude` are never returned.
+    #[must_use]
+    pub fn members_for(&self, target: &str) -> Option<Vec<String>> {
+        let target = Target::from_triple(target).ok()?;
+
+        match self.graph.affected(&self.changed, &target) {
+            Affected::All => None,
+            Affected::Members(members) => Some(
+                members
+                    .into_iter()
+                    .filter(|m| !self.exclude.contains(m))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Files changed since `since`, including uncommitted and untracked files
+///
+/// Changes are measured from the merge base of `since` and `HEAD`, so on a
+/// pull request branch only the branch's own changes are counted. Returned
+/// paths are absolute.
+pub fn changed_files(since: &str) -> Result<Vec<PathBuf>> {
+    let toplevel = git(&["rev-parse", "--show-toplevel"])?;
+    let toplevel = PathBuf::from(toplevel.trim());
+    let base = git(&["merge-base", since, "HEAD"])?;
+
+    let mut files = BTreeSet::new();
+    for list in [
+        git(&["diff", "--name-only", base.trim()])?,
+        git(&["ls-files", "--others", "--exclude-standard", "--full-name"])?,
+    ] {
+        files.extend(
+            list.lines()
+                .filter(|l| !l.is_empty())
+                .map(|l| toplevel.join(l)),
+        );
+    }
+
+    Ok(files.into_iter().collect())
+}
+
+fn git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .map_err(|e| Error::Build(format!("Failed to run git: {e}")))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(Error::Build(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Whether a dependency's platform spec applies to `target`
+///
+/// Predicates that cannot be evaluated from the triple alone (features,
+/// pointer width, ...) are treated as matching, so a dependency is never
+/// wrongly ignored.
+fn platform_matches(spec: &str, target: &Target) -> bool {
+    match spec.strip_prefix("cfg(").and_then(|s| s.strip_suffix(')')) {
+        Some(expr) => {
+            let tokens = tokenize(expr);
+            let mut pos = 0;
+            eval_cfg(&tokens, &mut pos, target).unwrap_or(true)
+        }
+        None => spec == target.triple,
+    }
+}
+
+fn tokenize(expr: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '(' | ')' | ',' | '=' => tokens.push(c.to_string()),
+            '"' => {
+                let value: String = chars.by_ref().take_while(|&c| c != '"').collect();
+                tokens.push(format!("\"{value}\""));
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut ident = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_alphanumeric() || next == '_' {
+                        ident.push(next);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(ident);
+            }
+        }
+    }
+
+    tokens
+}
+
+/// Evaluate one cfg predicate; `None` means it cannot be decided
+fn eval_cfg(tokens: &[String], pos: &mut usize, target: &Target) -> Option<bool> {
+    let ident = tokens.get(*pos)?.clone();
+    *pos += 1;
+
+    match ident.as_str() {
+        "all" | "any" | "not" => {
+            let mut results = Vec::new();
+            *pos += 1; // (
+            while tokens.get(*pos).is_some_and(|t| t != ")") {
+                results.push(eval_cfg(tokens, pos, target));
+                if tokens.get(*pos).is_some_and(|t| t == ",") {
+                    *pos += 1;
+                }
+            }
+            *pos += 1; // )
+
+            match ident.as_str() {
+                "not" => results.first().copied().flatten().map(|r| !r),
+                "all" if results.contains(&Some(false)) => Some(false),
+                "any" if results.contains(&Some(true)) => Some(true),
+                _ if results.contains(&None) => None,
+                "all" => Some(true),
+                _ => Some(false),
+            }
+        }
+        key => {
+            let value = if tokens.get(*pos).is_some_and(|t| t == "=") {
+                let value = tokens.get(*pos + 1)?.trim_matches('"').to_string();
+                *pos += 2;
+                Some(value)
+            } else {
+                None
+            };
+
+            match (key, value) {
+                ("windows" | "unix", None) => cfg_value("target_family", target).map(|f| f == key),
+                (key, Some(value)) => cfg_value(key, target).map(|actual| actual == value),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Value of a `target_*` cfg key derived from the triple, if known
+fn cfg_value(key: &str, target: &Target) -> Option<String> {
+    let triple = target.triple.as_str();
+    let os = [
+        ("windows", "windows"),
+        ("android", "android"),
+        ("linux", "linux"),
+        ("darwin", "macos"),
+        ("apple-ios", "ios"),
+        ("freebsd", "freebsd"),
+        ("wasi", "wasi"),
+    ]
+    .into_iter()
+    .find(|(pattern, _)| triple.contains(pattern))
+    .map(|(_, os)| os);
+
+    match key {
+        "target_os" => os.map(str::to_string),
+        "target_family" => os.map(|os| {
+            match os {
+                "windows" => "windows",
+                "wasi" => "wasm",
+                _ => "unix",
+            }
+            .to_string()
+        }),
+        "target_arch" => arch_name(&target.arch),
+        "target_vendor" => Some(target.vendor.clone()),
+        "target_env" => {
+            let env = target.env.as_deref().unwrap_or_default();
+            ["gnu", "musl", "msvc"]
+                .into_iter()
+                .find(|e| env.starts_with(e))
+                .map(str::to_string)
+        }
+        _ => None,
+    }
+}
+
+fn arch_name(arch: &str) -> Option<String> {
+    let name = match arch {
+        "x86_64" | "aarch64" | "wasm32" | "riscv64" | "powerpc64" => arch,
+        a if a.starts_with("riscv64") => "riscv64",
+        a if a.starts_with("powerpc64") => "powerpc64",
+        "i686" | "i586" | "i386" => "x86",
+        a if a.starts_with("arm") || a.starts_with("thumb") => "arm",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const METADATA: &str = r#"{
+        "workspace_root": "/ws",
+        "workspace_members": ["core 0.1.0", "cli 0.1.0", "win 0.1.0", "docs 0.1.0"],
+        "packages": [
+            {"id": "core 0.1.0", "name": "core", "manifest_path": "/ws/crates/core/Cargo.toml",
+             "dependencies": [{"name": "serde"}]},
+            {"id": "cli 0.1.0", "name": "cli", "manifest_path": "/ws/crates/cli/Cargo.toml",
+             "dependencies": [{"name": "core", "path": "/ws/crates/core"}]},
+            {"id": "win 0.1.0", "name": "win", "manifest_path": "/ws/crates/win/Cargo.toml",
+             "dependencies": [{"name": "core", "path": "/ws/crates/core", "target": "cfg(windows)"}]},
+            {"id": "docs 0.1.0", "name": "docs", "manifest_path": "/ws/docs/Cargo.toml"}
+        ]
+    }"#;
+
+    fn graph() -> WorkspaceGraph {
+        WorkspaceGraph::from_metadata(METADATA).unwrap()
+    }
+
+    fn target(triple: &str) -> Target {
+        Target::from_triple(triple).unwrap()
+    }
+
+    fn members(names: &[&str]) -> Affected {
+        Affected::Members(names.iter().map(|s| (*s).to_string()).collect())
+    }
+
+    #[test]
+    fn test_leaf_change_affects_only_that_member() {
+        let changed = vec![PathBuf::from("crates/cli/src/main.rs")];
+        assert_eq!(
+            graph().affected(&changed, &target("x86_64-unknown-linux-gnu")),
+            members(&["cli"])
+        );
+    }
+
+    #[test]
+    fn test_change_propagates_to_dependents_per_target() {
+        let changed = vec![PathBuf::from("/ws/crates/core/src/lib.rs")];
+        let graph = graph();
+
+        assert_eq!(
+            graph.affected(&changed, &target("x86_64-unknown-linux-gnu")),
+            members(&["cli", "core"])
+        );
+        assert_eq!(
+            graph.affected(&changed, &target("x86_64-pc-windows-gnu")),
+            members(&["cli", "core", "win"])
+        );
+    }
+
+    #[test]
+    fn test_global_files_affect_everything() {
+        for file in ["Cargo.lock", "Cargo.toml", ".cargo/config.toml"] {
+            assert_eq!(
+                graph().affected(&[PathBuf::from(file)], &target("x86_64-unknown-linux-gnu")),
+                Affected::All
+            );
+        }
+    }
+
+    #[test]
+    fn test_unrelated_files_affect_nothing() {
+        let changed = vec![PathBuf::from("README.md")];
+        assert_eq!(
+            graph().affected(&changed, &target("x86_64-unknown-linux-gnu")),
+            members(&[])
+        );
+        assert!(graph().is_virtual());
+    }
+
+    #[test]
+    fn test_platform_matches() {
+        let linux = target("aarch64-unknown-linux-musl");
+        assert!(platform_matches("cfg(unix)", &linux));
+        assert!(!platform_matches("cfg(windows)", &linux));
+        assert!(platform_matches(
+            r#"cfg(all(target_os = "linux", target_env = "musl"))"#,
+            &linux
+        ));
+        assert!(!platform_matches(
+            r#"cfg(not(target_arch = "aarch64"))"#,
+            &linux
+        ));
+        assert!(platform_matches("aarch64-unknown-linux-musl", &linux));
+        assert!(!platform_matches("x86_64-pc-windows-msvc", &linux));
+        // Undecidable predicates count as matching
+        assert!(platform_matches(r#"cfg(feature = "x")"#, &linux));
+    }
+}
@@ -0,0 +1,323 @@
+//! Validation and normalization of passthrough cargo arguments
+//!
+//! Arguments after `--` are forwarded to cargo, but a few of them change what
+//! xcargo itself is doing (which packages are built, which targets are
+//! injected). Those are recognized here so the build plan and summaries can
+//! reflect them instead of passing them through blindly.
+
+use crate::error::{Error, Result};
+
+/// Cargo arguments split into recognized scope flags and plain passthrough
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CargoArgs {
+    /// Build every workspace member (`--workspace`, or cargo's deprecated `--all`)
+    pub workspace: bool,
+
+    /// Workspace members excluded from the build (`--exclude <spec>`)
+    pub exclude: Vec<String>,
+
+    /// Build all cargo targets (lib, bins, tests, benches, examples)
+    pub all_targets: bool,
+
+    /// Remaining arguments, forwarded to cargo unchanged
+    pub passthrough: Vec<String>,
+}
+
+impl CargoArgs {
+    /// Parse and validate passthrough cargo arguments
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::CargoArgs;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let args = CargoArgs::parse(&[
+    ///     "--all".to_string(),
+    ///     "--exclude".to_string(),
+    ///     "xtask".to_string(),
+    /// ])?;
+    /// assert!(args.workspace);
+    /// assert_eq!(args.exclude, vec!["xtask"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the arguments select a target (xcargo injects
+    /// `--target` itself) or use `--exclude` without `--workspace`.
+    pub fn parse(args: &[String]) -> Result<Self> {
+        let mut parsed = Self::default();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                // Everything after a bare `--` belongs to rustc or the test harness
+                "--" => {
+                    parsed.passthrough.push(arg.clone());
+                    parsed.passthrough.extend(iter.by_ref().cloned());
+                }
+                "--workspace" | "--all" => parsed.workspace = true,
+                "--all-targets" => parsed.all_targets = true,
+                "--exclude" => {
+                    let spec = iter.next().ok_or_else(|| {
+                        Error::Config("--exclude requires a package name".to_string())
+                    })?;
+                    parsed.add_exclude(spec);
+                }
+                "--target" => {
+                    return Err(Self::target_conflict(iter.next().map(String::as_str)));
+                }
+                other => {
+                    if let Some(spec) = other.strip_prefix("--exclude=") {
+                        parsed.add_exclude(spec);
+                    } else if let Some(triple) = other.strip_prefix("--target=") {
+                        return Err(Self::target_conflict(Some(triple)));
+                    } else {
+                        parsed.passthrough.push(arg.clone());
+                    }
+                }
+            }
+        }
+
+        if !parsed.exclude.is_empty() && !parsed.workspace {
+            return Err(Error::Config(
+                "--exclude can only be used together with --workspace".to_string(),
+            ));
+        }
+
+        Ok(parsed)
+    }
+
+    fn add_exclude(&mut self, spec: &str) {
+        if !self.exclude.iter().any(|e| e == spec) {
+            self.exclude.push(spec.to_string());
+        }
+    }
+
+    fn target_conflict(triple: Option<&str>) -> Error {
+        let hint = triple.map_or_else(
+            || "use 'xcargo build --target <triple>'".to_string(),
+            |t| format!("use 'xcargo build --target {t}'"),
+        );
+        Error::Config(format!(
+            "--target cannot be passed through to cargo because xcargo selects the target itself; {hint} instead"
+        ))
+    }
+
+    /// Render the arguments back into the normalized form passed to cargo
+    #[must_use]
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        if self.workspace {
+            args.push("--workspace".to_string());
+        }
+        for spec in &self.exclude {
+            args.push("--exclude".to_string());
+            args.push(spec.clone());
+        }
+        if self.all_targets {
+            args.push("--all-targets".to_string());
+        }
+        args.extend(self.passthrough.iter().cloned());
+
+        args
+    }
+
+    /// Whether packages are selected explicitly with `-p`/`--package`
+    #[must_use]
+    pub fn selects_packages(&self) -> bool {
+        self.passthrough
+            .iter()
+            .take_while(|arg| *arg != "--")
+            .any(|arg| arg == "--package" || arg.starts_with("--package=") || arg.starts_with("-p"))
+    }
+
+    /// Whether the number of cargo jobs is set with `-j`/`--jobs`
+    #[must_use]
+    pub fn sets_jobs(&self) -> bool {
+        self.passthrough
+            .iter()
+            .take_while(|arg| *arg != "--")
+            .any(|arg| arg == "--jobs" || arg.starts_with("--jobs=") || arg.starts_with("-j"))
+    }
+
+    /// Whether cargo's output format is chosen with `--message-format`
+    #[must_use]
+    pub fn sets_message_format(&self) -> bool {
+        self.passthrough
+            .iter()
+            .take_while(|arg| *arg != "--")
+            .any(|arg| arg == "--message-format" || arg.starts_with("--message-format="))
+    }
+
+    /// Whether the standard library is rebuilt with `-Z build-std`, which needs `rust-src`
+    #[must_use]
+    pub fn uses_build_std(&self) -> bool {
+        let args: Vec<&String> = self
+            .passthrough
+            .iter()
+            .take_while(|arg| *arg != "--")
+            .collect();
+
+        args.iter().any(|arg| arg.starts_with("-Zbuild-std"))
+            || args
+                .windows(2)
+                .any(|pair| pair[0] == "-Z" && pair[1].starts_with("build-std"))
+    }
+
+    /// Render the arguments with the workspace scope replaced by explicit packages
+    #[must_use]
+    pub fn with_packages(&self, packages: &[String]) -> Vec<String> {
+        let mut args = Vec::new();
+
+        for package in packages {
+            args.push("--package".to_string());
+            args.push(package.clone());
+        }
+        if self.all_targets {
+            args.push("--all-targets".to_string());
+        }
+        args.extend(self.passthrough.iter().cloned());
+
+        args
+    }
+
+    /// Human-readable description of the build scope, if it is not the default
+    #[must_use]
+    pub fn scope_description(&self) -> Option<String> {
+        let mut parts = Vec::new();
+
+        if self.workspace {
+            if self.exclude.is_empty() {
+                parts.push("entire workspace".to_string());
+            } else {
+                parts.push(format!(
+                    "entire workspace excluding {}",
+                    self.exclude.join(", ")
+                ));
+            }
+        }
+        if self.all_targets {
+            parts.push("all cargo targets".to_string());
+        }
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_plain_passthrough() {
+        let parsed = CargoArgs::parse(&args(&["--features", "full"])).unwrap();
+        assert!(!parsed.workspace);
+        assert_eq!(parsed.passthrough, args(&["--features", "full"]));
+        assert_eq!(parsed.scope_description(), None);
+    }
+
+    #[test]
+    fn test_parse_normalizes_all_to_workspace() {
+        let parsed = CargoArgs::parse(&args(&["--all", "--all-targets"])).unwrap();
+        assert!(parsed.workspace);
+        assert!(parsed.all_targets);
+        assert_eq!(parsed.to_args(), args(&["--workspace", "--all-targets"]));
+    }
+
+    #[test]
+    fn test_parse_exclude_forms() {
+        let parsed = CargoArgs::parse(&args(&[
+            "--workspace",
+            "--exclude",
+            "xtask",
+            "--exclude=bench",
+            "--exclude",
+            "xtask",
+        ]))
+        .unwrap();
+        assert_eq!(parsed.exclude, args(&["xtask", "bench"]));
+        assert_eq!(
+            parsed.scope_description().unwrap(),
+            "entire workspace excluding xtask, bench"
+        );
+    }
+
+    #[test]
+    fn test_exclude_requires_workspace() {
+        assert!(CargoArgs::parse(&args(&["--exclude", "xtask"])).is_err());
+        assert!(CargoArgs::parse(&args(&["--exclude"])).is_err());
+    }
+
+    #[test]
+    fn test_target_passthrough_rejected() {
+        assert!(CargoArgs::parse(&args(&["--target", "x86_64-pc-windows-gnu"])).is_err());
+        assert!(CargoArgs::parse(&args(&["--target=x86_64-pc-windows-gnu"])).is_err());
+    }
+
+    #[test]
+    fn test_sets_jobs() {
+        assert!(CargoArgs::parse(&args(&["-j", "4"])).unwrap().sets_jobs());
+        assert!(CargoArgs::parse(&args(&["--jobs=4"])).unwrap().sets_jobs());
+        assert!(!CargoArgs::parse(&args(&["--", "-j", "4"]))
+            .unwrap()
+            .sets_jobs());
+    }
+
+    #[test]
+    fn test_sets_message_format() {
+        assert!(CargoArgs::parse(&args(&["--message-format", "short"]))
+            .unwrap()
+            .sets_message_format());
+        assert!(CargoArgs::parse(&args(&["--message-format=json"]))
+            .unwrap()
+            .sets_message_format());
+        assert!(!CargoArgs::parse(&args(&["--", "--message-format=json"]))
+            .unwrap()
+            .sets_message_format());
+    }
+
+    #[test]
+    fn test_with_packages_replaces_workspace_scope() {
+        let parsed =
+            CargoArgs::parse(&args(&["--workspace", "--exclude", "xtask", "--locked"])).unwrap();
+        assert!(!parsed.selects_packages());
+        assert_eq!(
+            parsed.with_packages(&args(&["core", "cli"])),
+            args(&["--package", "core", "--package", "cli", "--locked"])
+        );
+        assert!(CargoArgs::parse(&args(&["-p", "core"]))
+            .unwrap()
+            .selects_packages());
+    }
+
+    #[test]
+    fn test_arguments_after_separator_untouched() {
+        let parsed = CargoArgs::parse(&args(&["--", "--target", "--all"])).unwrap();
+        assert!(!parsed.workspace);
+        assert_eq!(parsed.to_args(), args(&["--", "--target", "--all"]));
+    }
+
+    #[test]
+    fn test_uses_build_std() {
+        let parsed = CargoArgs::parse(&args(&["-Z", "build-std=core,alloc"])).unwrap();
+        assert!(parsed.uses_build_std());
+
+        let parsed = CargoArgs::parse(&args(&["-Zbuild-std"])).unwrap();
+        assert!(parsed.uses_build_std());
+
+        let parsed = CargoArgs::parse(&args(&["--", "-Zbuild-std"])).unwrap();
+        assert!(!parsed.uses_build_std());
+    }
+}
@@ -0,0 +1,221 @@
+//! Output files of a build, from cargo's JSON messages
+//!
+//! Cargo reports every file it produces in a `compiler-artifact` message,
+//! with absolute paths that already account for `--target-dir`,
+//! `CARGO_TARGET_DIR` and `build.target-dir`. The binaries, shared and static
+//! libraries and wasm modules among them are recorded per target and profile
+//! in `.xcargo/artifacts.json`, where [`Builder::artifact_paths`] finds them
+//! after the build.
+
+use crate::error::{Error, Result};
+use crate::target::Target;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::attest::artifacts;
+use super::executor::Builder;
+use super::summary::target_dir_for;
+
+/// Artifacts of the latest builds, relative to the project root
+pub const ARTIFACTS_FILE: &str = ".xcargo/artifacts.json";
+
+/// Kinds of cargo targets whose files, rather than an executable, are the output
+const LIBRARY_KINDS: [&str; 3] = ["cdylib", "dylib", "staticlib"];
+
+/// Files cargo reports for libraries that only other Rust crates consume
+const INTERMEDIATE_EXTENSIONS: [&str; 2] = ["rlib", "rmeta"];
+
+/// Output files listed in cargo's `--message-format=json` output, in the
+/// order cargo reported them
+///
+/// Test binaries, build scripts and proc macros are left out.
+pub(super) fn parse_artifacts(cargo_json: &str) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = Vec::new();
+    let messages = cargo_json
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| {
+            message["reason"] == "compiler-artifact" && message["profile"]["test"] != true
+        });
+
+    for message in messages {
+        let kinds: Vec<&str> = message["target"]["kind"]
+            .as_array()
+            .map(|kinds| kinds.iter().filter_map(|kind| kind.as_str()).collect())
+            .unwrap_or_default();
+
+        let found: Vec<PathBuf> = if let Some(executable) = message["executable"].as_str() {
+            vec![PathBuf::from(executable)]
+        } else if kinds.iter().any(|kind| LIBRARY_KINDS.contains(kind)) {
+            message["filenames"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|filename| filename.as_str())
+                .map(PathBuf::from)
+                .filter(|path| {
+                    !path.extension().is_some_and(|ext| {
+                        INTERMEDIATE_EXTENSIONS.iter().any(|known| ext == *known)
+                    })
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        for path in found {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+/// Artifacts of the latest build of each target and profile
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct ArtifactIndex {
+    /// Paths keyed by "<triple>/<profile>", e.g. "x86_64-pc-windows-gnu/release"
+    #[serde(default)]
+    pub targets: BTreeMap<String, Vec<PathBuf>>,
+}
+
+impl ArtifactIndex {
+    /// Load the index, or an empty one if the file is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the index, creating its directory if needed
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Build(format!("Failed to serialize artifact index: {e}")))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Artifacts recorded for a target and profile
+    pub fn get(&self, target: &str, profile: &str) -> Option<&[PathBuf]> {
+        self.targets
+            .get(&format!("{target}/{profile}"))
+            .map(Vec::as_slice)
+    }
+
+    /// Replace the artifacts of a target and profile
+    pub fn set(&mut self, target: &str, profile: &str, paths: Vec<PathBuf>) {
+        self.targets.insert(format!("{target}/{profile}"), paths);
+    }
+}
+
+impl Builder {
+    /// Output files of the latest build of `target` with `profile` ("debug"
+    /// or "release"): binaries, shared and static libraries, and wasm modules
+    ///
+    /// The paths are the ones cargo reported, so they are right for custom
+    /// target directories. Builds cargo did not report (container builds, or
+    /// ones that passed `--message-format` through) fall back to the files
+    /// in `target/<triple>/<profile>/`. Files that no longer exist are left
+    /// out.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xcargo::build::Builder;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let builder = Builder::new()?;
+    /// for path in builder.artifact_paths("x86_64-pc-windows-gnu", "release") {
+    ///     println!("{}", path.display());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn artifact_paths(&self, target: &str, profile: &str) -> Vec<PathBuf> {
+        // cargo writes x86_64-unknown-linux-gnu.2.17 builds under the plain triple
+        let triple = Target::from_triple(target).map_or_else(|_| target.to_string(), |t| t.triple);
+
+        let index = ArtifactIndex::load(Path::new(ARTIFACTS_FILE));
+        match index.get(&triple, profile) {
+            Some(paths) => paths
+                .iter()
+                .filter(|path| path.is_file())
+                .cloned()
+                .collect(),
+            None => artifacts(&target_dir_for(&triple), &triple, profile == "release"),
+        }
+    }
+}
+
+/// Record the artifacts of a build in [`ARTIFACTS_FILE`]
+///
+/// # Errors
+/// Returns an error if the index cannot be written
+pub(super) fn record_artifacts(target: &str, release: bool, paths: &[PathBuf]) -> Result<()> {
+    let path = Path::new(ARTIFACTS_FILE);
+    let mut index = ArtifactIndex::load(path);
+    index.set(
+        target,
+        if release { "release" } else { "debug" },
+        paths.to_vec(),
+    );
+    index.save(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CARGO_JSON: &str = r#"{"reason":"compiler-artifact","package_id":"registry+https://github.com/rust-lang/crates.io-index#serde@1.0.0","target":{"kind":["lib"],"name":"serde"},"profile":{"test":false},"filenames":["/out/x86_64-pc-windows-gnu/release/deps/libserde-1a2b.rlib"],"executable":null}
+{"reason":"compiler-artifact","package_id":"path+file:///src/app#0.1.0","target":{"kind":["custom-build"],"name":"build-script-build"},"profile":{"test":false},"filenames":["/out/release/build/app-3c4d/build-script-build"],"executable":null}
+{"reason":"compiler-artifact","package_id":"path+file:///src/app#0.1.0","target":{"kind":["lib","cdylib"],"name":"app"},"profile":{"test":false},"filenames":["/out/x86_64-pc-windows-gnu/release/libapp.rlib","/out/x86_64-pc-windows-gnu/release/app.dll","/out/x86_64-pc-windows-gnu/release/libapp.dll.a"],"executable":null}
+{"reason":"compiler-artifact","package_id":"path+file:///src/app#0.1.0","target":{"kind":["bin"],"name":"app"},"profile":{"test":false},"filenames":["/out/x86_64-pc-windows-gnu/release/app.exe"],"executable":"/out/x86_64-pc-windows-gnu/release/app.exe"}
+{"reason":"compiler-artifact","package_id":"path+file:///src/app#0.1.0","target":{"kind":["bin"],"name":"app"},"profile":{"test":true},"filenames":[],"executable":"/out/x86_64-pc-windows-gnu/release/deps/app-5e6f.exe"}
+{"reason":"build-finished","success":true}"#;
+
+    #[test]
+    fn test_parse_artifacts() {
+        assert_eq!(
+            parse_artifacts(CARGO_JSON),
+            [
+                PathBuf::from("/out/x86_64-pc-windows-gnu/release/app.dll"),
+                PathBuf::from("/out/x86_64-pc-windows-gnu/release/libapp.dll.a"),
+                PathBuf::from("/out/x86_64-pc-windows-gnu/release/app.exe"),
+            ]
+        );
+        assert!(parse_artifacts("not json").is_empty());
+    }
+
+    #[test]
+    fn test_artifact_index_roundtrip() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(ARTIFACTS_FILE);
+        assert_eq!(ArtifactIndex::load(&path), ArtifactIndex::default());
+
+        let mut index = ArtifactIndex::default();
+        index.set(
+            "wasm32-wasip1",
+            "debug",
+            vec![PathBuf::from("/out/app.wasm")],
+        );
+        index.save(&path).unwrap();
+
+        let loaded = ArtifactIndex::load(&path);
+        assert_eq!(
+            loaded.get("wasm32-wasip1", "debug"),
+            Some(&[PathBuf::from("/out/app.wasm")][..])
+        );
+        assert_eq!(loaded.get("wasm32-wasip1", "release"), None);
+    }
+}
@@ -0,0 +1,400 @@
+//! Provenance records for built artifacts
+//!
+//! With `--attest` (or `attest.enabled`), every artifact of a build gets a
+//! `<artifact>.sha256` checksum and a `<artifact>.provenance.json` describing
+//! how it was built: the exact rustc, the Zig version or container image
+//! digest, rustflags, the Cargo.lock hash, and the host. The provenance file
+//! can be signed with minisign or cosign, so a binary can be traced back to
+//! the build that produced it.
+
+use crate::build::LockedTarget;
+use crate::cache::sha256_file;
+use crate::config::AttestConfig;
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::postprocess::file_size;
+use super::summary::{find_cargo_lock, target_dir_for};
+
+/// Extensions of the files attested in a profile directory: executables,
+/// libraries, WebAssembly modules, and objcopy images
+const ARTIFACT_EXTENSIONS: [&str; 9] = [
+    "exe", "wasm", "so", "dylib", "dll", "a", "lib", "bin", "hex",
+];
+
+/// How an artifact was built, as written to `<artifact>.provenance.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct Provenance {
+    /// File name of the artifact
+    pub artifact: String,
+
+    /// SHA-256 of the artifact, hex encoded
+    pub sha256: String,
+
+    /// Size in bytes
+    pub size: u64,
+
+    /// Target triple, with its glibc version if one was requested
+    pub target: String,
+
+    /// Cargo profile, "debug" or "release"
+    pub profile: String,
+
+    /// `rustc --version` of the toolchain; unset for container builds,
+    /// where the image digest identifies the compiler
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rustc: Option<String>,
+
+    /// Strategy, Rust release, Zig version, and image digest, as in xcargo.lock
+    #[serde(flatten)]
+    pub environment: LockedTarget,
+
+    /// Rustflags the build was run with
+    pub rustflags: Vec<String>,
+
+    /// SHA-256 of the project's Cargo.lock
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cargo_lock_sha256: Option<String>,
+
+    /// Machine the build ran on
+    pub host: BuildHost,
+
+    /// Version of xcargo that ran the build
+    pub xcargo: String,
+
+    /// Unix time of the build, or `SOURCE_DATE_EPOCH` if set
+    pub built_at: u64,
+}
+
+/// Machine a build ran on
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BuildHost {
+    /// Host target triple
+    pub triple: String,
+
+    /// CI service, e.g. "github-actions", when built in CI
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ci: Option<String>,
+
+    /// Link to the CI run, where the service provides one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ci_run: Option<String>,
+}
+
+impl BuildHost {
+    /// Describe this machine, reading CI details from the environment
+    #[must_use]
+    pub fn detect(triple: &str) -> Self {
+        Self::from_env(triple, |name| {
+            std::env::var(name).ok().filter(|value| !value.is_empty())
+        })
+    }
+
+    fn from_env(triple: &str, var: impl Fn(&str) -> Option<String>) -> Self {
+        let (ci, ci_run) = if var("GITHUB_ACTIONS").is_some() {
+            let run = match (
+                var("GITHUB_SERVER_URL"),
+                var("GITHUB_REPOSITORY"),
+                var("GITHUB_RUN_ID"),
+            ) {
+                (Some(server), Some(repo), Some(run)) => {
+                    Some(format!("{server}/{repo}/actions/runs/{run}"))
+                }
+                _ => None,
+            };
+            (Some("github-actions".to_string()), run)
+        } else if var("GITLAB_CI").is_some() {
+            (Some("gitlab-ci".to_string()), var("CI_JOB_URL"))
+        } else if var("CI").is_some() {
+            (Some("ci".to_string()), None)
+        } else {
+            (None, None)
+        };
+        Self {
+            triple: triple.to_string(),
+            ci,
+            ci_run,
+        }
+    }
+}
+
+impl Provenance {
+    /// Provenance shared by every artifact of one target's build; the
+    /// artifact fields are filled in per file
+    #[must_use]
+    pub fn for_build(
+        target: &str,
+        release: bool,
+        rustc: Option<String>,
+        environment: LockedTarget,
+        rustflags: Vec<String>,
+        host: BuildHost,
+    ) -> Self {
+        Self {
+            target: target.to_string(),
+            profile: if release { "release" } else { "debug" }.to_string(),
+            rustc,
+            environment,
+            rustflags,
+            cargo_lock_sha256: find_cargo_lock().and_then(|lock| sha256_file(&lock).ok()),
+            host,
+            xcargo: env!("CARGO_PKG_VERSION").to_string(),
+            built_at: build_time(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Write checksums and provenance for the artifacts of a target's build, or
+/// only the `selected` ones if any, signing the provenance if configured
+///
+/// # Errors
+/// Returns an error if `attest.sign` names an unknown signer, or if writing
+/// or signing a file fails
+pub(super) fn attest_artifacts(
+    triple: &str,
+    build: &Provenance,
+    release: bool,
+    selected: &[PathBuf],
+    config: &AttestConfig,
+) -> Result<()> {
+    let signer = config.sign.as_deref().map(Signer::from_name).transpose()?;
+    let artifacts = if selected.is_empty() {
+        artifacts(&target_dir_for(triple), triple, release)
+    } else {
+        selected
+            .iter()
+            .filter(|path| path.is_file())
+            .cloned()
+            .collect()
+    };
+    if artifacts.is_empty() {
+        helpers::warning(format!("No artifacts to attest for {triple}"));
+        return Ok(());
+    }
+
+    for artifact in &artifacts {
+        let name = artifact
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let sha256 = sha256_file(artifact)?;
+        std::fs::write(sibling(artifact, "sha256"), format!("{sha256}  {name}\n"))?;
+
+        let provenance = Provenance {
+            artifact: name.clone(),
+            sha256,
+            size: file_size(artifact),
+            ..build.clone()
+        };
+        let path = sibling(artifact, "provenance.json");
+        let json = serde_json::to_string_pretty(&provenance)
+            .map_err(|e| Error::Build(format!("Failed to serialize provenance: {e}")))?;
+        std::fs::write(&path, json + "\n")?;
+
+        match signer {
+            Some(signer) => {
+                let signature = signer.sign(&path, config.key.as_deref())?;
+                helpers::success(format!(
+                    "Attested {name} ({})",
+                    signature.file_name().unwrap_or_default().to_string_lossy()
+                ));
+            }
+            None => helpers::success(format!("Attested {name}")),
+        }
+    }
+    Ok(())
+}
+
+/// Tool that signs provenance files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Signer {
+    Minisign,
+    Cosign,
+}
+
+impl Signer {
+    fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "minisign" => Ok(Self::Minisign),
+            "cosign" => Ok(Self::Cosign),
+            other => Err(Error::Config(format!(
+                "attest.sign must be \"minisign\" or \"cosign\", not \"{other}\""
+            ))),
+        }
+    }
+
+    /// Sign `path`, returning the signature file; the tools may prompt for
+    /// a key password or a sigstore login, so they keep the terminal
+    fn sign(self, path: &Path, key: Option<&str>) -> Result<PathBuf> {
+        let (program, signature) = match self {
+            Self::Minisign => ("minisign", sibling(path, "minisig")),
+            Self::Cosign => ("cosign", sibling(path, "sigstore.json")),
+        };
+        let mut cmd = Command::new(program);
+        match self {
+            Self::Minisign => {
+                cmd.arg("-S");
+                if let Some(key) = key {
+                    cmd.arg("-s").arg(key);
+                }
+                cmd.arg("-m").arg(path);
+            }
+            Self::Cosign => {
+                cmd.args(["sign-blob", "--yes"]);
+                if let Some(key) = key {
+                    cmd.arg("--key").arg(key);
+                }
+                cmd.arg("--bundle").arg(&signature).arg(path);
+            }
+        }
+
+        let status = cmd.status().map_err(|e| {
+            Error::Build(format!(
+                "Failed to run {program} to sign provenance (attest.sign = \"{program}\"): {e}"
+            ))
+        })?;
+        if status.success() {
+            Ok(signature)
+        } else {
+            Err(Error::Build(format!(
+                "{program} failed to sign {}",
+                path.display()
+            )))
+        }
+    }
+}
+
+/// Whether a file is a checksum, provenance record, or signature written by attest
+pub(super) fn is_attestation(name: &str) -> bool {
+    [".sha256", ".provenance.json", ".minisig", ".sigstore.json"]
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+}
+
+/// `app` → `app.<suffix>`, keeping any extension: `app.exe.<suffix>`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// The artifacts cargo placed in the profile directory for `target`
+pub(super) fn artifacts(target_dir: &Path, target: &str, release: bool) -> Vec<PathBuf> {
+    let profile = if release { "release" } else { "debug" };
+    let Ok(entries) = std::fs::read_dir(target_dir.join(target).join(profile)) else {
+        return Vec::new();
+    };
+    let mut artifacts: Vec<PathBuf> = entries
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            let artifact = match path.extension() {
+                None => true,
+                Some(ext) => ARTIFACT_EXTENSIONS.iter().any(|known| ext == *known),
+            };
+            !hidden && artifact
+        })
+        .collect();
+    artifacts.sort();
+    artifacts
+}
+
+/// `SOURCE_DATE_EPOCH` for reproducible builds, else the current time
+fn build_time() -> u64 {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_artifacts() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("x86_64-unknown-linux-gnu/release");
+        std::fs::create_dir_all(dir.join("deps")).unwrap();
+        for file in ["app", "app.d", "libapp.so", "libapp.rlib", ".cargo-lock"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+        std::fs::write(dir.join("app.provenance.json"), "{}").unwrap();
+
+        assert_eq!(
+            artifacts(temp.path(), "x86_64-unknown-linux-gnu", true),
+            [dir.join("app"), dir.join("libapp.so")]
+        );
+    }
+
+    #[test]
+    fn test_sibling() {
+        assert_eq!(
+            sibling(Path::new("target/app.exe"), "provenance.json"),
+            Path::new("target/app.exe.provenance.json")
+        );
+        assert!(is_attestation("app.exe.provenance.json.minisig"));
+        assert!(!is_attestation("app.exe"));
+    }
+
+    #[test]
+    fn test_build_host_ci() {
+        let github = BuildHost::from_env("x86_64-unknown-linux-gnu", |name| {
+            match name {
+                "GITHUB_ACTIONS" | "CI" => Some("true"),
+                "GITHUB_SERVER_URL" => Some("https://github.com"),
+                "GITHUB_REPOSITORY" => Some("acme/app"),
+                "GITHUB_RUN_ID" => Some("42"),
+                _ => None,
+            }
+            .map(str::to_string)
+        });
+        assert_eq!(github.ci.as_deref(), Some("github-actions"));
+        assert_eq!(
+            github.ci_run.as_deref(),
+            Some("https://github.com/acme/app/actions/runs/42")
+        );
+
+        let local = BuildHost::from_env("x86_64-unknown-linux-gnu", |_| None);
+        assert_eq!(local.ci, None);
+    }
+
+    #[test]
+    fn test_provenance_json() {
+        let provenance = Provenance {
+            artifact: "app".to_string(),
+            environment: LockedTarget {
+                strategy: "zig".to_string(),
+                zig: Some("0.13.0".to_string()),
+                ..LockedTarget::default()
+            },
+            ..Provenance::default()
+        };
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&provenance).unwrap()).unwrap();
+        assert_eq!(json["strategy"], "zig");
+        assert_eq!(json["zig"], "0.13.0");
+        assert!(json.get("image_digest").is_none());
+    }
+
+    #[test]
+    fn test_unknown_signer() {
+        let err = Signer::from_name("gpg").unwrap_err();
+        assert!(err.to_string().contains("\"minisign\" or \"cosign\""));
+    }
+}
@@ -0,0 +1,533 @@
+//! Dependency audits for cross-compilation
+//!
+//! `xcargo deps audit` resolves the dependency graph with
+//! `cargo metadata --filter-platform <triple>`, so `[target.'cfg(...)'.dependencies]`
+//! apply as they would in a build, and walks it from the workspace members
+//! along normal dependencies. Build dependencies and proc macros run on the
+//! host and are left out, except to tell what a build script does.
+//!
+//! Each crate found is checked against a built-in list of crates with known
+//! cross-compilation problems, and crates whose build scripts compile or
+//! link native code are reported, since those need a C toolchain or
+//! libraries for the target.
+
+use crate::error::{Error, Result};
+use crate::target::Target;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::process::Command;
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Known to fail or misbehave for the target
+    Problem,
+    /// Builds native code, so it needs a C toolchain or libraries for the target
+    Native,
+}
+
+impl Severity {
+    /// Name used in reports
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Problem => "problem",
+            Severity::Native => "native",
+        }
+    }
+}
+
+/// A dependency that may get in the way of building for a target
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    /// Crate name
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+
+    /// Crate version
+    pub version: String,
+
+    /// Whether it is a known problem or native code to provide for
+    pub severity: Severity,
+
+    /// What goes wrong for the target
+    pub problem: String,
+
+    /// How to get around it
+    pub advice: String,
+
+    /// How the crate is pulled in, from a workspace member to the crate
+    pub path: Vec<String>,
+}
+
+/// Findings for the dependencies of one target
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyAudit {
+    /// Target triple the graph was resolved for
+    pub target: String,
+
+    /// Number of crates compiled for the target, workspace members included
+    pub crates: usize,
+
+    /// Problems first, then native builds, each by crate name
+    pub findings: Vec<Finding>,
+}
+
+/// Targets a known issue applies to
+#[derive(Debug, Clone, Copy)]
+enum Applies {
+    /// Every target
+    All,
+    /// wasm32-unknown-unknown and other WebAssembly targets without an OS
+    BareWasm,
+    /// Targets with an OS whose C library has to be provided
+    Native,
+    /// MSVC and WebAssembly targets
+    MsvcOrWasm,
+}
+
+impl Applies {
+    fn matches(self, target: &Target) -> bool {
+        let bare_wasm = target.is_wasm() && target.os == "unknown";
+        match self {
+            Applies::All => true,
+            Applies::BareWasm => bare_wasm,
+            Applies::Native => !target.is_wasm(),
+            Applies::MsvcOrWasm => target.is_wasm() || target.env.as_deref() == Some("msvc"),
+        }
+    }
+}
+
+/// A crate with a known cross-compilation problem
+struct KnownIssue {
+    name: &'static str,
+    applies: Applies,
+    /// Enabled features that avoid the problem
+    unless_features: &'static [&'static str],
+    problem: &'static str,
+    advice: &'static str,
+}
+
+/// Crates with known cross-compilation problems
+const KNOWN_ISSUES: &[KnownIssue] = &[
+    KnownIssue {
+        name: "openssl-sys",
+        applies: Applies::Native,
+        unless_features: &["vendored"],
+        problem: "links the system OpenSSL, which has to be installed for the target",
+        advice: "enable the `vendored` feature of openssl, or use rustls instead of native-tls",
+    },
+    KnownIssue {
+        name: "openssl-sys",
+        applies: Applies::BareWasm,
+        unless_features: &[],
+        problem: "OpenSSL does not build for WebAssembly",
+        advice: "use a pure-Rust TLS or crypto crate for this target",
+    },
+    KnownIssue {
+        name: "aws-lc-sys",
+        applies: Applies::Native,
+        unless_features: &[],
+        problem: "builds AWS-LC with CMake, and needs NASM for Windows targets",
+        advice: "install cmake (and nasm for Windows), or select rustls' `ring` provider",
+    },
+    KnownIssue {
+        name: "getrandom",
+        applies: Applies::BareWasm,
+        unless_features: &["js", "wasm_js"],
+        problem: "has no source of randomness on wasm32-unknown-unknown",
+        advice: "enable the `js` feature (getrandom 0.2) or `wasm_js` with \
+                 --cfg getrandom_backend=\"wasm_js\" (getrandom 0.3)",
+    },
+    KnownIssue {
+        name: "mio",
+        applies: Applies::BareWasm,
+        unless_features: &[],
+        problem: "has no sockets or event loop on wasm32-unknown-unknown",
+        advice: "leave tokio's `net` and `rt-multi-thread` features out for this target, \
+                 or build for wasm32-wasip1",
+    },
+    KnownIssue {
+        name: "socket2",
+        applies: Applies::BareWasm,
+        unless_features: &[],
+        problem: "does not support wasm32-unknown-unknown",
+        advice: "make the networking dependencies conditional on not(target_family = \"wasm\")",
+    },
+    KnownIssue {
+        name: "tikv-jemalloc-sys",
+        applies: Applies::MsvcOrWasm,
+        unless_features: &[],
+        problem: "jemalloc does not build for MSVC or WebAssembly",
+        advice: "use the system allocator on these targets with a cfg'd #[global_allocator]",
+    },
+    KnownIssue {
+        name: "jemalloc-sys",
+        applies: Applies::MsvcOrWasm,
+        unless_features: &[],
+        problem: "jemalloc does not build for MSVC or WebAssembly",
+        advice: "use the system allocator on these targets with a cfg'd #[global_allocator]",
+    },
+    KnownIssue {
+        name: "libsqlite3-sys",
+        applies: Applies::All,
+        unless_features: &[
+            "bundled",
+            "bundled-sqlcipher",
+            "bundled-sqlcipher-vendored-openssl",
+        ],
+        problem: "links the system SQLite, which has to be installed for the target",
+        advice: "enable the `bundled` feature (rusqlite's `bundled`)",
+    },
+    KnownIssue {
+        name: "pq-sys",
+        applies: Applies::All,
+        unless_features: &["bundled"],
+        problem: "links libpq, which has to be installed for the target",
+        advice: "enable the `bundled` feature, or use a pure-Rust driver such as tokio-postgres",
+    },
+    KnownIssue {
+        name: "curl-sys",
+        applies: Applies::Native,
+        unless_features: &["static-curl"],
+        problem: "links the system libcurl, which has to be installed for the target",
+        advice: "enable the `static-curl` feature, or use a pure-Rust HTTP client",
+    },
+    KnownIssue {
+        name: "alsa-sys",
+        applies: Applies::Native,
+        unless_features: &[],
+        problem: "finds libasound with pkg-config, which needs the target's sysroot",
+        advice: "set PKG_CONFIG_SYSROOT_DIR to the target's sysroot, or build with --container",
+    },
+    KnownIssue {
+        name: "libudev-sys",
+        applies: Applies::Native,
+        unless_features: &[],
+        problem: "finds libudev with pkg-config, which needs the target's sysroot",
+        advice: "set PKG_CONFIG_SYSROOT_DIR to the target's sysroot, or build with --container",
+    },
+    KnownIssue {
+        name: "libdbus-sys",
+        applies: Applies::Native,
+        unless_features: &["vendored"],
+        problem: "finds libdbus with pkg-config, which needs the target's sysroot",
+        advice: "enable the `vendored` feature, or build with --container",
+    },
+];
+
+/// Build-script helpers and what using them means for a target
+const NATIVE_BUILD_TOOLS: &[(&str, &str)] = &[
+    ("cc", "compiles C or C++ with the target's C compiler"),
+    (
+        "cmake",
+        "builds native code with CMake and the target's C compiler",
+    ),
+    (
+        "bindgen",
+        "generates bindings with bindgen, which needs libclang",
+    ),
+    ("pkg-config", "looks for a system library with pkg-config"),
+    ("vcpkg", "looks for a system library with vcpkg"),
+];
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<Package>,
+    workspace_members: Vec<String>,
+    resolve: Option<Resolve>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    id: String,
+    name: String,
+    version: String,
+    #[serde(default)]
+    links: Option<String>,
+    #[serde(default)]
+    targets: Vec<PackageTarget>,
+}
+
+#[derive(Deserialize)]
+struct PackageTarget {
+    kind: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Resolve {
+    nodes: Vec<Node>,
+}
+
+#[derive(Deserialize)]
+struct Node {
+    id: String,
+    #[serde(default)]
+    deps: Vec<NodeDep>,
+    #[serde(default)]
+    features: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct NodeDep {
+    pkg: String,
+    #[serde(default)]
+    dep_kinds: Vec<DepKind>,
+}
+
+#[derive(Deserialize)]
+struct DepKind {
+    kind: Option<String>,
+}
+
+impl NodeDep {
+    fn is_kind(&self, kind: Option<&str>) -> bool {
+        self.dep_kinds.iter().any(|k| k.kind.as_deref() == kind)
+    }
+}
+
+impl DependencyAudit {
+    /// Resolve the dependencies of the workspace in the current directory for
+    /// `target` and audit them
+    ///
+    /// `cargo_args` are passed to `cargo metadata`, e.g. `--features` or
+    /// `--offline`.
+    ///
+    /// # Errors
+    /// Returns an error if the target is invalid or `cargo metadata` fails
+    pub fn run(target: &str, cargo_args: &[String]) -> Result<Self> {
+        let target = Target::from_triple(target)?;
+        let output = Command::new("cargo")
+            .args(["metadata", "--format-version", "1", "--filter-platform"])
+            .arg(&target.triple)
+            .args(cargo_args)
+            .output()
+            .map_err(|e| Error::Build(format!("Failed to run cargo metadata: {e}")))?;
+        if !output.status.success() {
+            return Err(Error::Build(format!(
+                "cargo metadata failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Self::from_metadata(&String::from_utf8_lossy(&output.stdout), &target)
+    }
+
+    /// Audit `cargo metadata --filter-platform` JSON output for `target`
+    ///
+    /// # Errors
+    /// Returns an error if the output cannot be parsed or has no resolved graph
+    pub fn from_metadata(json: &str, target: &Target) -> Result<Self> {
+        let metadata: Metadata = serde_json::from_str(json)
+            .map_err(|e| Error::Build(format!("Invalid cargo metadata output: {e}")))?;
+        let resolve = metadata.resolve.ok_or_else(|| {
+            Error::Build("cargo metadata did not resolve the dependencies".to_string())
+        })?;
+        let packages: HashMap<&str, &Package> = metadata
+            .packages
+            .iter()
+            .map(|package| (package.id.as_str(), package))
+            .collect();
+        let nodes: HashMap<&str, &Node> = resolve
+            .nodes
+            .iter()
+            .map(|node| (node.id.as_str(), node))
+            .collect();
+
+        // Breadth first from the members, so each crate keeps a shortest path
+        let mut parents: BTreeMap<&str, Option<&str>> = BTreeMap::new();
+        let mut queue = VecDeque::new();
+        for member in &metadata.workspace_members {
+            parents.insert(member.as_str(), None);
+            queue.push_back(member.as_str());
+        }
+        while let Some(id) = queue.pop_front() {
+            let Some(node) = nodes.get(id) else { continue };
+            for dep in node.deps.iter().filter(|dep| dep.is_kind(None)) {
+                let is_proc_macro = packages.get(dep.pkg.as_str()).is_some_and(|package| {
+                    package
+                        .targets
+                        .iter()
+                        .any(|t| t.kind.iter().any(|k| k == "proc-macro"))
+                });
+                if !is_proc_macro && !parents.contains_key(dep.pkg.as_str()) {
+                    parents.insert(dep.pkg.as_str(), Some(id));
+                    queue.push_back(dep.pkg.as_str());
+                }
+            }
+        }
+
+        let mut findings = Vec::new();
+        for &id in parents.keys() {
+            let (Some(package), Some(node)) = (packages.get(id), nodes.get(id)) else {
+                continue;
+            };
+            let path = || {
+                let mut path = Vec::new();
+                let mut current = Some(id);
+                while let Some(id) = current {
+                    path.push(packages.get(id).map_or(id, |p| p.name.as_str()).to_string());
+                    current = parents.get(id).copied().flatten();
+                }
+                path.reverse();
+                path
+            };
+            let finding = |severity, problem: &str, advice: &str| Finding {
+                crate_name: package.name.clone(),
+                version: package.version.clone(),
+                severity,
+                problem: problem.to_string(),
+                advice: advice.to_string(),
+                path: path(),
+            };
+
+            let known: Vec<&KnownIssue> = KNOWN_ISSUES
+                .iter()
+                .filter(|issue| issue.name == package.name && issue.applies.matches(target))
+                .filter(|issue| {
+                    !issue
+                        .unless_features
+                        .iter()
+                        .any(|feature| node.features.iter().any(|f| f == feature))
+                })
+                .collect();
+            for issue in &known {
+                findings.push(finding(Severity::Problem, issue.problem, issue.advice));
+            }
+            if known.is_empty() {
+                if let Some(problem) = native_build(package, node, &packages) {
+                    let advice = if target.is_wasm() {
+                        "needs a clang with the WebAssembly backend, and C code that builds without libc"
+                    } else {
+                        "build with --zig or --container, or install a C cross toolchain"
+                    };
+                    findings.push(finding(Severity::Native, &problem, advice));
+                }
+            }
+        }
+        findings.sort_by(|a, b| (a.severity, &a.crate_name).cmp(&(b.severity, &b.crate_name)));
+
+        Ok(Self {
+            target: target.triple.clone(),
+            crates: parents.len(),
+            findings,
+        })
+    }
+
+    /// Findings with [`Severity::Problem`]
+    #[must_use]
+    pub fn problems(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.severity == Severity::Problem)
+            .count()
+    }
+}
+
+/// What a package's build script does natively, if anything: the native
+/// build tools among its build dependencies, or the library a `-sys` crate
+/// links
+fn native_build(
+    package: &Package,
+    node: &Node,
+    packages: &HashMap<&str, &Package>,
+) -> Option<String> {
+    let has_build_script = package
+        .targets
+        .iter()
+        .any(|t| t.kind.iter().any(|k| k == "custom-build"));
+    if !has_build_script {
+        return None;
+    }
+    let tools: Vec<&str> = node
+        .deps
+        .iter()
+        .filter(|dep| dep.is_kind(Some("build")))
+        .filter_map(|dep| packages.get(dep.pkg.as_str()))
+        .filter_map(|dep| {
+            NATIVE_BUILD_TOOLS
+                .iter()
+                .find(|(name, _)| *name == dep.name)
+                .map(|(_, what)| *what)
+        })
+        .collect();
+    match (tools.is_empty(), &package.links) {
+        (false, _) => Some(format!("build script {}", tools.join("; "))),
+        // Other crates set `links` only to keep a single version in the graph
+        (true, Some(links)) if package.name.ends_with("-sys") => {
+            Some(format!("links the native library `{links}`"))
+        }
+        (true, _) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Metadata of an app depending on getrandom and, through a build
+    /// dependency-only helper, on cc
+    const METADATA: &str = r#"{
+        "packages": [
+            {"id": "app", "name": "app", "version": "0.1.0", "targets": [{"kind": ["bin"]}]},
+            {"id": "getrandom", "name": "getrandom", "version": "0.2.15", "targets": [{"kind": ["lib"]}]},
+            {"id": "zstd-sys", "name": "zstd-sys", "version": "2.0.13", "links": "zstd",
+             "targets": [{"kind": ["lib"]}, {"kind": ["custom-build"]}]},
+            {"id": "cc", "name": "cc", "version": "1.1.0", "targets": [{"kind": ["lib"]}]},
+            {"id": "derive", "name": "derive", "version": "1.0.0", "targets": [{"kind": ["proc-macro"]}]},
+            {"id": "openssl-sys", "name": "openssl-sys", "version": "0.9.0", "targets": [{"kind": ["lib"]}]}
+        ],
+        "workspace_members": ["app"],
+        "resolve": {"nodes": [
+            {"id": "app", "features": [], "deps": [
+                {"pkg": "getrandom", "dep_kinds": [{"kind": null, "target": null}]},
+                {"pkg": "zstd-sys", "dep_kinds": [{"kind": null, "target": null}]},
+                {"pkg": "derive", "dep_kinds": [{"kind": null, "target": null}]}
+            ]},
+            {"id": "getrandom", "features": ["std"], "deps": []},
+            {"id": "zstd-sys", "features": [], "deps": [
+                {"pkg": "cc", "dep_kinds": [{"kind": "build", "target": null}]}
+            ]},
+            {"id": "cc", "features": [], "deps": []},
+            {"id": "derive", "features": [], "deps": [
+                {"pkg": "openssl-sys", "dep_kinds": [{"kind": null, "target": null}]}
+            ]},
+            {"id": "openssl-sys", "features": [], "deps": []}
+        ]}
+    }"#;
+
+    fn audit(triple: &str) -> DependencyAudit {
+        DependencyAudit::from_metadata(METADATA, &Target::from_triple(triple).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_audit_wasm() {
+        let audit = audit("wasm32-unknown-unknown");
+        // cc is a build dependency and derive a proc macro, both for the host
+        assert_eq!(audit.crates, 3);
+        assert_eq!(audit.problems(), 1);
+
+        let getrandom = &audit.findings[0];
+        assert_eq!(getrandom.crate_name, "getrandom");
+        assert_eq!(getrandom.severity, Severity::Problem);
+        assert_eq!(getrandom.path, vec!["app", "getrandom"]);
+
+        let zstd = &audit.findings[1];
+        assert_eq!(zstd.severity, Severity::Native);
+        assert!(zstd.problem.contains("C compiler"), "{}", zstd.problem);
+    }
+
+    #[test]
+    fn test_audit_native_target() {
+        let audit = audit("aarch64-unknown-linux-gnu");
+        assert_eq!(audit.problems(), 0);
+        assert_eq!(audit.findings.len(), 1);
+        assert_eq!(audit.findings[0].crate_name, "zstd-sys");
+
+        // getrandom's js feature makes it fine on the web
+        let json = METADATA.replace(r#"["std"]"#, r#"["std", "js"]"#);
+        let target = Target::from_triple("wasm32-unknown-unknown").unwrap();
+        let audit = DependencyAudit::from_metadata(&json, &target).unwrap();
+        assert_eq!(audit.problems(), 0);
+    }
+}
@@ -0,0 +1,227 @@
+//! Benchmark result collection and cross-target comparison
+//!
+//! `cargo bench` output is scanned for libtest (`bench: N ns/iter`) and
+//! Criterion (`time: [low mid high]`) result lines so results from several
+//! targets can be shown side by side.
+
+use crate::output::table::align_columns;
+use std::collections::BTreeMap;
+
+/// A single benchmark measurement
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    /// Benchmark name
+    pub name: String,
+
+    /// Time per iteration in nanoseconds
+    pub ns_per_iter: f64,
+}
+
+/// Parse benchmark results from `cargo bench` output
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::build::parse_bench_output;
+///
+/// let results = parse_bench_output("test hot_loop ... bench:       1,250 ns/iter (+/- 40)");
+/// assert_eq!(results[0].name, "hot_loop");
+/// assert_eq!(results[0].ns_per_iter, 1250.0);
+/// ```
+#[must_use]
+pub fn parse_bench_output(output: &str) -> Vec<BenchResult> {
+    let mut results = Vec::new();
+    let mut previous = "";
+
+    for line in output.lines() {
+        if let Some(result) =
+            parse_libtest_line(line).or_else(|| parse_criterion_line(line, previous))
+        {
+            results.push(result);
+        }
+        if !line.trim().is_empty() {
+            previous = line;
+        }
+    }
+
+    results
+}
+
+/// `test name ... bench:       1,234 ns/iter (+/- 56)`
+fn parse_libtest_line(line: &str) -> Option<BenchResult> {
+    let rest = line.trim().strip_prefix("test ")?;
+    let (name, measurement) = rest.split_once(" ... bench:")?;
+    let value = measurement.split_whitespace().next()?;
+
+    Some(BenchResult {
+        name: name.trim().to_string(),
+        ns_per_iter: value.replace(',', "").parse().ok()?,
+    })
+}
+
+/// `name   time:   [1.2 ms 1.3 ms 1.4 ms]`, where long names are printed on
+/// the preceding line
+fn parse_criterion_line(line: &str, previous: &str) -> Option<BenchResult> {
+    let (name, rest) = line.split_once("time:")?;
+    let estimates = rest.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let parts: Vec<&str> = estimates.split_whitespace().collect();
+
+    // [low unit mid unit high unit]; use the middle estimate
+    let (value, unit) = (parts.get(2)?, parts.get(3)?);
+    let factor = match *unit {
+        "ps" => 0.001,
+        "ns" => 1.0,
+        "µs" | "us" => 1_000.0,
+        "ms" => 1_000_000.0,
+        "s" => 1_000_000_000.0,
+        _ => return None,
+    };
+
+    let name = if name.trim().is_empty() {
+        previous.trim()
+    } else {
+        name.trim()
+    };
+
+    Some(BenchResult {
+        name: name.to_string(),
+        ns_per_iter: value.parse::<f64>().ok()? * factor,
+    })
+}
+
+/// Format a duration in nanoseconds with a readable unit
+fn format_duration_ns(ns: f64) -> String {
+    if ns >= 1_000_000_000.0 {
+        format!("{:.2} s", ns / 1_000_000_000.0)
+    } else if ns >= 1_000_000.0 {
+        format!("{:.2} ms", ns / 1_000_000.0)
+    } else if ns >= 1_000.0 {
+        format!("{:.2} µs", ns / 1_000.0)
+    } else {
+        format!("{ns:.2} ns")
+    }
+}
+
+/// Benchmark results from several targets, for side-by-side comparison
+#[derive(Debug, Clone, Default)]
+pub struct BenchTable {
+    targets: Vec<String>,
+    results: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl BenchTable {
+    /// Create an empty table
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the results of one target
+    pub fn add(&mut self, target: &str, results: &[BenchResult]) {
+        if !self.targets.iter().any(|t| t == target) {
+            self.targets.push(target.to_string());
+        }
+        for result in results {
+            self.results
+                .entry(result.name.clone())
+                .or_default()
+                .insert(target.to_string(), result.ns_per_iter);
+        }
+    }
+
+    /// Whether any results were collected
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Render the table; times after the first target column are shown
+    /// with their ratio to the first target
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut rows = vec![{
+            let mut header = vec!["benchmark".to_string()];
+            header.extend(self.targets.iter().cloned());
+            header
+        }];
+
+        for (name, by_target) in &self.results {
+            let baseline = self.targets.first().and_then(|t| by_target.get(t));
+            let mut row = vec![name.clone()];
+            for (idx, target) in self.targets.iter().enumerate() {
+                let cell = match (by_target.get(target), baseline) {
+                    (None, _) => "-".to_string(),
+                    (Some(ns), Some(base)) if idx > 0 && *base > 0.0 => {
+                        format!("{} ({:.2}x)", format_duration_ns(*ns), ns / base)
+                    }
+                    (Some(ns), _) => format_duration_ns(*ns),
+                };
+                row.push(cell);
+            }
+            rows.push(row);
+        }
+
+        align_columns(&rows).join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_libtest_output() {
+        let output = "\
+running 2 tests
+test bench_add  ... bench:         120 ns/iter (+/- 3)
+test bench_hash ... bench:       1,234.50 ns/iter (+/- 56.10)
+
+test result: ok. 0 passed; 0 failed; 0 ignored; 2 measured";
+
+        let results = parse_bench_output(output);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "bench_add");
+        assert!((results[0].ns_per_iter - 120.0).abs() < f64::EPSILON);
+        assert!((results[1].ns_per_iter - 1234.5).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_parse_criterion_output() {
+        let output = "\
+fib 20                  time:   [24.512 µs 24.634 µs 24.771 µs]
+a_very_long_benchmark_name_that_wraps
+                        time:   [1.5000 ms 2.0000 ms 2.5000 ms]
+                        change: [-1.2% +0.3% +1.9%] (p = 0.70 > 0.05)";
+
+        let results = parse_bench_output(output);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "fib 20");
+        assert!((results[0].ns_per_iter - 24_634.0).abs() < 1e-6);
+        assert_eq!(results[1].name, "a_very_long_benchmark_name_that_wraps");
+        assert!((results[1].ns_per_iter - 2_000_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_bench_table_render() {
+        let mut table = BenchTable::new();
+        table.add(
+            "x86_64-unknown-linux-gnu",
+            &[BenchResult {
+                name: "hot_loop".to_string(),
+                ns_per_iter: 1_000.0,
+            }],
+        );
+        table.add(
+            "aarch64-unknown-linux-gnu",
+            &[BenchResult {
+                name: "hot_loop".to_string(),
+                ns_per_iter: 1_500.0,
+            }],
+        );
+
+        let rendered = table.render();
+        assert!(rendered.starts_with("benchmark"));
+        assert!(rendered.contains("1.00 µs"));
+        assert!(rendered.contains("1.50 µs (1.50x)"));
+    }
+}
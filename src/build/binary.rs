@@ -0,0 +1,812 @@
+//! Section and symbol tables of built binaries
+//!
+//! Just enough of ELF, PE/COFF, 64-bit Mach-O and WebAssembly is read to
+//! tell how big each section is and which symbols take up the space, without
+//! needing binutils for every target. Formats that give no symbol sizes
+//! (Mach-O, COFF) are sized by the distance to the next symbol in the same
+//! section, the way `nm --size-sort` estimates them.
+
+/// Reads integers of a file's class and byte order
+pub(super) struct Reader<'a> {
+    pub bytes: &'a [u8],
+    /// Whether address-sized fields are 8 bytes (ELF64)
+    pub wide: bool,
+    pub big_endian: bool,
+}
+
+impl<'a> Reader<'a> {
+    /// A little-endian reader of 4-byte words
+    pub fn le(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            wide: false,
+            big_endian: false,
+        }
+    }
+
+    pub fn u8(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(offset).copied()
+    }
+
+    pub fn u16(&self, offset: usize) -> Option<u16> {
+        let bytes: [u8; 2] = self
+            .bytes
+            .get(offset..offset.checked_add(2)?)?
+            .try_into()
+            .ok()?;
+        Some(if self.big_endian {
+            u16::from_be_bytes(bytes)
+        } else {
+            u16::from_le_bytes(bytes)
+        })
+    }
+
+    pub fn u32(&self, offset: usize) -> Option<u32> {
+        let bytes: [u8; 4] = self
+            .bytes
+            .get(offset..offset.checked_add(4)?)?
+            .try_into()
+            .ok()?;
+        Some(if self.big_endian {
+            u32::from_be_bytes(bytes)
+        } else {
+            u32::from_le_bytes(bytes)
+        })
+    }
+
+    pub fn u64(&self, offset: usize) -> Option<u64> {
+        let bytes: [u8; 8] = self
+            .bytes
+            .get(offset..offset.checked_add(8)?)?
+            .try_into()
+            .ok()?;
+        Some(if self.big_endian {
+            u64::from_be_bytes(bytes)
+        } else {
+            u64::from_le_bytes(bytes)
+        })
+    }
+
+    /// An address-sized field: 8 bytes in ELF64, 4 in ELF32
+    pub fn word(&self, offset: usize) -> Option<u64> {
+        if self.wide {
+            self.u64(offset)
+        } else {
+            self.u32(offset).map(u64::from)
+        }
+    }
+
+    pub fn usize(&self, offset: usize) -> Option<usize> {
+        usize::try_from(self.word(offset)?).ok()
+    }
+
+    /// The NUL-terminated string at `offset`
+    pub fn str(&self, offset: usize) -> Option<String> {
+        let rest = self.bytes.get(offset..)?;
+        let end = rest.iter().position(|&b| b == 0)?;
+        Some(String::from_utf8_lossy(&rest[..end]).into_owned())
+    }
+
+    /// A fixed-size name field padded with NULs, e.g. a Mach-O section name
+    fn name(&self, offset: usize, len: usize) -> Option<String> {
+        let field = self.bytes.get(offset..offset.checked_add(len)?)?;
+        let end = field.iter().position(|&b| b == 0).unwrap_or(len);
+        Some(String::from_utf8_lossy(&field[..end]).into_owned())
+    }
+
+    /// An unsigned LEB128 number and the offset after it
+    fn leb(&self, mut offset: usize) -> Option<(u64, usize)> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.u8(offset)?;
+            offset += 1;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some((value, offset));
+            }
+        }
+        None
+    }
+
+    /// A LEB128 length-prefixed string and the offset after it
+    fn leb_str(&self, offset: usize) -> Option<(String, usize)> {
+        let (len, start) = self.leb(offset)?;
+        let end = start.checked_add(usize::try_from(len).ok()?)?;
+        let bytes = self.bytes.get(start..end)?;
+        Some((String::from_utf8_lossy(bytes).into_owned(), end))
+    }
+}
+
+/// What a section holds, for the code/data/debug totals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum SectionKind {
+    Code,
+    Data,
+    Debug,
+    Other,
+}
+
+/// A section and the bytes it takes in the file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Section {
+    pub name: String,
+    pub size: u64,
+    pub kind: SectionKind,
+}
+
+/// A function or data symbol with its (possibly mangled) name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Symbol {
+    pub name: String,
+    pub size: u64,
+}
+
+/// The sections and sized symbols of a binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Layout {
+    /// "ELF", "PE", "Mach-O" or "wasm"
+    pub format: &'static str,
+    pub sections: Vec<Section>,
+    pub symbols: Vec<Symbol>,
+}
+
+/// Read the layout of a binary; `None` for formats that aren't understood,
+/// such as static libraries and 32-bit Mach-O
+pub(super) fn parse(bytes: &[u8]) -> Option<Layout> {
+    match bytes.get(..4)? {
+        b"\x7fELF" => parse_elf(bytes),
+        b"\0asm" => parse_wasm(bytes),
+        [0xcf, 0xfa, 0xed, 0xfe] => parse_macho(bytes),
+        [b'M', b'Z', ..] => parse_pe(bytes),
+        _ => None,
+    }
+}
+
+/// Symbols at known addresses, sized by the distance to the next one
+/// (or the end of the section)
+struct Placed {
+    name: String,
+    section: usize,
+    addr: u64,
+}
+
+fn size_by_address(mut placed: Vec<Placed>, section_ends: &[u64]) -> Vec<Symbol> {
+    placed.sort_by_key(|p| (p.section, p.addr));
+    let mut symbols = Vec::with_capacity(placed.len());
+    for (idx, symbol) in placed.iter().enumerate() {
+        let end = placed
+            .get(idx + 1)
+            .filter(|next| next.section == symbol.section)
+            .map(|next| next.addr)
+            .or_else(|| section_ends.get(symbol.section).copied())
+            .unwrap_or(symbol.addr);
+        let size = end.saturating_sub(symbol.addr);
+        if size > 0 {
+            symbols.push(Symbol {
+                name: symbol.name.clone(),
+                size,
+            });
+        }
+    }
+    symbols
+}
+
+const SHT_SYMTAB: u32 = 2;
+const SHT_NOBITS: u32 = 8;
+const SHT_DYNSYM: u32 = 11;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+const STT_OBJECT: u8 = 1;
+const STT_FUNC: u8 = 2;
+
+fn parse_elf(bytes: &[u8]) -> Option<Layout> {
+    let elf = Reader {
+        bytes,
+        wide: bytes.get(4) == Some(&2),
+        big_endian: bytes.get(5) == Some(&2),
+    };
+    let (shoff, shentsize, shnum, shstrndx) = if elf.wide {
+        (
+            elf.usize(0x28)?,
+            elf.u16(0x3A)?,
+            elf.u16(0x3C)?,
+            elf.u16(0x3E)?,
+        )
+    } else {
+        (
+            elf.usize(0x20)?,
+            elf.u16(0x2E)?,
+            elf.u16(0x30)?,
+            elf.u16(0x32)?,
+        )
+    };
+    // (name, type, flags, offset, size, link)
+    let headers: Vec<(u32, u32, u64, usize, u64, u32)> = (0..usize::from(shnum))
+        .filter_map(|idx| {
+            let sh = shoff.checked_add(idx * usize::from(shentsize))?;
+            if elf.wide {
+                Some((
+                    elf.u32(sh)?,
+                    elf.u32(sh + 4)?,
+                    elf.u64(sh + 8)?,
+                    elf.usize(sh + 24)?,
+                    elf.u64(sh + 32)?,
+                    elf.u32(sh + 40)?,
+                ))
+            } else {
+                Some((
+                    elf.u32(sh)?,
+                    elf.u32(sh + 4)?,
+                    elf.word(sh + 8)?,
+                    elf.usize(sh + 16)?,
+                    elf.word(sh + 20)?,
+                    elf.u32(sh + 24)?,
+                ))
+            }
+        })
+        .collect();
+    let names = headers.get(usize::from(shstrndx)).map(|h| h.3);
+
+    let mut sections = Vec::new();
+    for &(name, kind, flags, _, size, _) in headers.iter().skip(1) {
+        if kind == SHT_NOBITS || size == 0 {
+            continue;
+        }
+        let name = names
+            .and_then(|table| elf.str(table.checked_add(name as usize)?))
+            .unwrap_or_default();
+        let kind = if name.starts_with(".debug") || name.starts_with(".zdebug") {
+            SectionKind::Debug
+        } else if flags & SHF_EXECINSTR != 0 {
+            SectionKind::Code
+        } else if flags & SHF_ALLOC != 0 {
+            SectionKind::Data
+        } else {
+            SectionKind::Other
+        };
+        sections.push(Section { name, size, kind });
+    }
+
+    // The full symbol table, or the exported symbols of a stripped binary
+    let table = headers
+        .iter()
+        .find(|h| h.1 == SHT_SYMTAB)
+        .or_else(|| headers.iter().find(|h| h.1 == SHT_DYNSYM));
+    let mut symbols = Vec::new();
+    if let Some(&(_, _, _, offset, size, link)) = table {
+        let strings = headers.get(link as usize).map_or(0, |h| h.3);
+        let entry = if elf.wide { 24 } else { 16 };
+        let end = offset.saturating_add(usize::try_from(size).unwrap_or(0));
+        for sym in (offset..end).step_by(entry) {
+            let (name, info, shndx, size) = if elf.wide {
+                (
+                    elf.u32(sym),
+                    elf.u8(sym + 4),
+                    elf.u16(sym + 6),
+                    elf.u64(sym + 16),
+                )
+            } else {
+                (
+                    elf.u32(sym),
+                    elf.u8(sym + 12),
+                    elf.u16(sym + 14),
+                    elf.word(sym + 8),
+                )
+            };
+            let (Some(name), Some(info), Some(shndx), Some(size)) = (name, info, shndx, size)
+            else {
+                break;
+            };
+            if shndx == 0 || size == 0 || !matches!(info & 0xf, STT_FUNC | STT_OBJECT) {
+                continue;
+            }
+            if let Some(name) = strings
+                .checked_add(name as usize)
+                .and_then(|offset| elf.str(offset))
+            {
+                symbols.push(Symbol { name, size });
+            }
+        }
+    }
+
+    Some(Layout {
+        format: "ELF",
+        sections,
+        symbols,
+    })
+}
+
+const IMAGE_SCN_CNT_CODE: u32 = 0x20;
+const IMAGE_SCN_CNT_INITIALIZED_DATA: u32 = 0x40;
+const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+const IMAGE_SYM_CLASS_EXTERNAL: u8 = 2;
+const IMAGE_SYM_CLASS_STATIC: u8 = 3;
+
+fn parse_pe(bytes: &[u8]) -> Option<Layout> {
+    let pe = Reader::le(bytes);
+    let header = pe.u32(0x3C)? as usize;
+    if bytes.get(header..header + 4)? != b"PE\0\0" {
+        return None;
+    }
+    let coff = header + 4;
+    let nsections = usize::from(pe.u16(coff + 2)?);
+    let symtab = pe.u32(coff + 8)? as usize;
+    let nsyms = pe.u32(coff + 12)? as usize;
+    let table = coff + 20 + usize::from(pe.u16(coff + 16)?);
+    // COFF string table, right after the symbols (MinGW binaries only)
+    let strings = if symtab == 0 {
+        None
+    } else {
+        nsyms
+            .checked_mul(18)
+            .and_then(|size| symtab.checked_add(size))
+    };
+    let long_name = |field: &str| {
+        let offset: usize = field.strip_prefix('/')?.parse().ok()?;
+        pe.str(strings?.checked_add(offset)?)
+    };
+
+    let mut sections = Vec::new();
+    let mut ranges = Vec::new();
+    for idx in 0..nsections {
+        let sh = table.checked_add(idx.checked_mul(40)?)?;
+        let short = pe.name(sh, 8)?;
+        let name = long_name(&short).unwrap_or(short);
+        let (vaddr, vsize) = (pe.u32(sh + 12)?, pe.u32(sh + 8)?);
+        let size = u64::from(pe.u32(sh + 16)?);
+        let flags = pe.u32(sh + 36)?;
+        ranges.push(u64::from(vaddr) + u64::from(vsize));
+        if size == 0 {
+            continue;
+        }
+        let kind = if name.starts_with(".debug") {
+            SectionKind::Debug
+        } else if flags & (IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE) != 0 {
+            SectionKind::Code
+        } else if flags & IMAGE_SCN_CNT_INITIALIZED_DATA != 0 {
+            SectionKind::Data
+        } else {
+            SectionKind::Other
+        };
+        sections.push(Section { name, size, kind });
+    }
+
+    let mut placed = Vec::new();
+    let mut idx = 0;
+    while symtab != 0 && idx < nsyms {
+        let Some(sym) = idx.checked_mul(18).and_then(|at| symtab.checked_add(at)) else {
+            break;
+        };
+        let (Some(value), Some(section), Some(class), Some(aux)) = (
+            pe.u32(sym + 8),
+            pe.u16(sym + 12),
+            pe.u8(sym + 16),
+            pe.u8(sym + 17),
+        ) else {
+            break;
+        };
+        idx += 1 + usize::from(aux);
+        // Section numbers are 1-based; 0 is undefined, and 0xFFFx are special
+        let section = usize::from(section);
+        if section == 0
+            || section > nsections
+            || !matches!(class, IMAGE_SYM_CLASS_EXTERNAL | IMAGE_SYM_CLASS_STATIC)
+        {
+            continue;
+        }
+        let name = if pe.u32(sym) == Some(0) {
+            strings.and_then(|table| pe.str(table + pe.u32(sym + 4)? as usize))
+        } else {
+            pe.name(sym, 8)
+        };
+        let Some(name) = name.filter(|n| !n.is_empty() && !n.starts_with('.')) else {
+            continue;
+        };
+        let vaddr = pe.u32(table.checked_add((section - 1) * 40 + 12)?)?;
+        placed.push(Placed {
+            name,
+            section: section - 1,
+            addr: u64::from(vaddr) + u64::from(value),
+        });
+    }
+
+    Some(Layout {
+        format: "PE",
+        sections,
+        symbols: size_by_address(placed, &ranges),
+    })
+}
+
+const LC_SYMTAB: u32 = 0x2;
+const LC_SEGMENT_64: u32 = 0x19;
+const S_ATTR_PURE_INSTRUCTIONS: u32 = 0x8000_0000;
+const S_ATTR_SOME_INSTRUCTIONS: u32 = 0x400;
+/// Section types that take no space in the file
+const ZEROFILL_TYPES: [u32; 3] = [0x1, 0xC, 0x12];
+const N_STAB: u8 = 0xe0;
+const N_TYPE: u8 = 0x0e;
+const N_SECT: u8 = 0x0e;
+
+fn parse_macho(bytes: &[u8]) -> Option<Layout> {
+    let macho = Reader::le(bytes);
+    let ncmds = macho.u32(16)?;
+    let mut sections = Vec::new();
+    // Start and end address of each section, in n_sect order
+    let mut ranges = Vec::new();
+    let mut symtab = None;
+
+    let mut cmd = 32;
+    for _ in 0..ncmds {
+        let (kind, size) = (macho.u32(cmd)?, macho.u32(cmd + 4)? as usize);
+        if kind == LC_SEGMENT_64 {
+            let nsects = macho.u32(cmd + 64)? as usize;
+            for idx in 0..nsects {
+                let sh = cmd.checked_add(72 + idx.checked_mul(80)?)?;
+                let (sectname, segname) = (macho.name(sh, 16)?, macho.name(sh + 16, 16)?);
+                let (addr, size) = (macho.u64(sh + 32)?, macho.u64(sh + 40)?);
+                let flags = macho.u32(sh + 64)?;
+                ranges.push(addr.saturating_add(size));
+                if size == 0 || ZEROFILL_TYPES.contains(&(flags & 0xff)) {
+                    continue;
+                }
+                let kind = if segname == "__DWARF" {
+                    SectionKind::Debug
+                } else if flags & (S_ATTR_PURE_INSTRUCTIONS | S_ATTR_SOME_INSTRUCTIONS) != 0 {
+                    SectionKind::Code
+                } else if segname.starts_with("__TEXT") || segname.starts_with("__DATA") {
+                    SectionKind::Data
+                } else {
+                    SectionKind::Other
+                };
+                sections.push(Section {
+                    name: format!("{segname},{sectname}"),
+                    size,
+                    kind,
+                });
+            }
+        } else if kind == LC_SYMTAB {
+            symtab = Some((
+                macho.u32(cmd + 8)? as usize,
+                macho.u32(cmd + 12)? as usize,
+                macho.u32(cmd + 16)? as usize,
+            ));
+        }
+        if size == 0 {
+            break;
+        }
+        cmd = cmd.checked_add(size)?;
+    }
+
+    let mut placed = Vec::new();
+    if let Some((symoff, nsyms, stroff)) = symtab {
+        for idx in 0..nsyms {
+            let Some(sym) = idx.checked_mul(16).and_then(|at| symoff.checked_add(at)) else {
+                break;
+            };
+            let (Some(strx), Some(kind), Some(section), Some(addr)) = (
+                macho.u32(sym),
+                macho.u8(sym + 4),
+                macho.u8(sym + 5),
+                macho.u64(sym + 8),
+            ) else {
+                break;
+            };
+            if kind & N_STAB != 0 || kind & N_TYPE != N_SECT || section == 0 {
+                continue;
+            }
+            let Some(name) = stroff
+                .checked_add(strx as usize)
+                .and_then(|offset| macho.str(offset))
+            else {
+                continue;
+            };
+            // C symbol names carry a leading underscore on Apple platforms
+            let name = name.strip_prefix('_').map_or(name.clone(), String::from);
+            placed.push(Placed {
+                name,
+                section: usize::from(section) - 1,
+                addr,
+            });
+        }
+    }
+
+    Some(Layout {
+        format: "Mach-O",
+        sections,
+        symbols: size_by_address(placed, &ranges),
+    })
+}
+
+const WASM_SECTIONS: [&str; 14] = [
+    "custom",
+    "type",
+    "import",
+    "function",
+    "table",
+    "memory",
+    "global",
+    "export",
+    "start",
+    "element",
+    "code",
+    "data",
+    "datacount",
+    "tag",
+];
+
+fn parse_wasm(bytes: &[u8]) -> Option<Layout> {
+    let wasm = Reader::le(bytes);
+    let mut sections = Vec::new();
+    let mut imported_functions = 0u64;
+    let mut bodies = Vec::new();
+    let mut names = std::collections::HashMap::new();
+
+    let mut offset = 8;
+    while offset < bytes.len() {
+        let id = wasm.u8(offset)?;
+        let (size, start) = wasm.leb(offset + 1)?;
+        let end = start.checked_add(usize::try_from(size).ok()?)?;
+        let mut name = WASM_SECTIONS
+            .get(usize::from(id))
+            .map_or_else(|| format!("section {id}"), ToString::to_string);
+        match id {
+            0 => {
+                let (custom, payload) = wasm.leb_str(start)?;
+                if custom == "name" {
+                    read_wasm_names(&wasm, payload, end, &mut names);
+                }
+                name = custom;
+            }
+            2 => imported_functions = count_imported_functions(&wasm, start)?,
+            10 => {
+                let (count, mut body) = wasm.leb(start)?;
+                for _ in 0..count {
+                    let (len, code) = wasm.leb(body)?;
+                    bodies.push(len.checked_add((code - body) as u64)?);
+                    body = code.checked_add(usize::try_from(len).ok()?)?;
+                }
+            }
+            _ => {}
+        }
+        let kind = match id {
+            10 => SectionKind::Code,
+            11 => SectionKind::Data,
+            0 if name.starts_with(".debug") => SectionKind::Debug,
+            _ => SectionKind::Other,
+        };
+        sections.push(Section {
+            name,
+            size: (end - offset) as u64,
+            kind,
+        });
+        offset = end;
+    }
+
+    let symbols = bodies
+        .into_iter()
+        .enumerate()
+        .map(|(idx, size)| {
+            let index = imported_functions + idx as u64;
+            Symbol {
+                name: names
+                    .remove(&index)
+                    .unwrap_or_else(|| format!("function[{index}]")),
+                size,
+            }
+        })
+        .collect();
+    Some(Layout {
+        format: "wasm",
+        sections,
+        symbols,
+    })
+}
+
+/// Functions the import section brings in, which come first in the index space
+fn count_imported_functions(wasm: &Reader<'_>, start: usize) -> Option<u64> {
+    let (count, mut offset) = wasm.leb(start)?;
+    let mut functions = 0;
+    for _ in 0..count {
+        let (_, after_module) = wasm.leb_str(offset)?;
+        let (_, after_field) = wasm.leb_str(after_module)?;
+        let kind = wasm.u8(after_field)?;
+        offset = after_field + 1;
+        offset = match kind {
+            // function: type index; tag: attribute and type index
+            0 => wasm.leb(offset)?.1,
+            4 => wasm.leb(offset + 1)?.1,
+            // table: element type, then limits
+            1 => skip_limits(wasm, offset + 1)?,
+            2 => skip_limits(wasm, offset)?,
+            // global: value type and mutability
+            3 => offset + 2,
+            _ => return None,
+        };
+        functions += u64::from(kind == 0);
+    }
+    Some(functions)
+}
+
+fn skip_limits(wasm: &Reader<'_>, offset: usize) -> Option<usize> {
+    let (flags, offset) = wasm.leb(offset)?;
+    let (_, offset) = wasm.leb(offset)?;
+    if flags & 1 == 0 {
+        Some(offset)
+    } else {
+        Some(wasm.leb(offset)?.1)
+    }
+}
+
+/// Function names from the `name` custom section
+fn read_wasm_names(
+    wasm: &Reader<'_>,
+    mut offset: usize,
+    end: usize,
+    names: &mut std::collections::HashMap<u64, String>,
+) {
+    while offset < end {
+        let (Some(id), Some((size, start))) = (wasm.u8(offset), wasm.leb(offset + 1)) else {
+            return;
+        };
+        let Some(next) = usize::try_from(size)
+            .ok()
+            .and_then(|s| start.checked_add(s))
+        else {
+            return;
+        };
+        if id == 1 {
+            let Some((count, mut entry)) = wasm.leb(start) else {
+                return;
+            };
+            for _ in 0..count {
+                let Some((index, name)) = wasm.leb(entry) else {
+                    return;
+                };
+                let Some((name, after)) = wasm.leb_str(name) else {
+                    return;
+                };
+                names.insert(index, name);
+                entry = after;
+            }
+        }
+        offset = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_elf_sections_and_symbols() {
+        // .text (code), .shstrtab, .symtab, .strtab
+        let shstrtab = b"\0.text\0.shstrtab\0.symtab\0.strtab\0";
+        let strtab = b"\0main\0DATA\0undef\0";
+        let mut symtab = vec![0u8; 24];
+        for (name, info, shndx, size) in [(1u32, 2u8, 1u16, 48u64), (6, 1, 1, 16), (11, 2, 0, 8)] {
+            let mut sym = vec![0u8; 24];
+            sym[..4].copy_from_slice(&name.to_le_bytes());
+            sym[4] = info;
+            sym[6..8].copy_from_slice(&shndx.to_le_bytes());
+            sym[16..24].copy_from_slice(&size.to_le_bytes());
+            symtab.extend(sym);
+        }
+
+        let mut bytes = vec![0u8; 64];
+        bytes[..4].copy_from_slice(b"\x7fELF");
+        bytes[4] = 2;
+        bytes[5] = 1;
+        let mut headers = vec![[0u8; 64]];
+        let mut add =
+            |bytes: &mut Vec<u8>, name: u32, kind: u32, flags: u64, data: &[u8], link: u32| {
+                let mut sh = [0u8; 64];
+                sh[..4].copy_from_slice(&name.to_le_bytes());
+                sh[4..8].copy_from_slice(&kind.to_le_bytes());
+                sh[8..16].copy_from_slice(&flags.to_le_bytes());
+                sh[24..32].copy_from_slice(&(bytes.len() as u64).to_le_bytes());
+                sh[32..40].copy_from_slice(&(data.len() as u64).to_le_bytes());
+                sh[40..44].copy_from_slice(&link.to_le_bytes());
+                bytes.extend_from_slice(data);
+                headers.push(sh);
+            };
+        add(&mut bytes, 1, 1, SHF_ALLOC | SHF_EXECINSTR, &[0x90; 64], 0);
+        add(&mut bytes, 7, 3, 0, shstrtab, 0);
+        add(&mut bytes, 17, SHT_SYMTAB, 0, &symtab, 4);
+        add(&mut bytes, 25, 3, 0, strtab, 0);
+        let shoff = bytes.len() as u64;
+        for sh in &headers {
+            bytes.extend_from_slice(sh);
+        }
+        bytes[0x28..0x30].copy_from_slice(&shoff.to_le_bytes());
+        bytes[0x3A..0x3C].copy_from_slice(&64u16.to_le_bytes());
+        bytes[0x3C..0x3E].copy_from_slice(&u16::try_from(headers.len()).unwrap().to_le_bytes());
+        bytes[0x3E..0x40].copy_from_slice(&2u16.to_le_bytes());
+
+        let layout = parse(&bytes).unwrap();
+        assert_eq!(layout.format, "ELF");
+        assert_eq!(
+            layout.sections[0],
+            Section {
+                name: ".text".to_string(),
+                size: 64,
+                kind: SectionKind::Code,
+            }
+        );
+        assert_eq!(layout.sections.len(), 4);
+        // The undefined symbol is left out
+        assert_eq!(
+            layout.symbols,
+            vec![
+                Symbol {
+                    name: "main".to_string(),
+                    size: 48
+                },
+                Symbol {
+                    name: "DATA".to_string(),
+                    size: 16
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_wasm_functions() {
+        let mut bytes = b"\0asm\x01\0\0\0".to_vec();
+        // import section: one function import "env" "f"
+        bytes.extend([2, 9, 1, 3, b'e', b'n', b'v', 1, b'f', 0, 0]);
+        // code section: two bodies of 3 and 5 bytes
+        bytes.extend([10, 11, 2, 3, 0, 0, 0x0b, 5, 0, 0, 0, 0, 0x0b]);
+        // name section naming function 1 (the first defined one)
+        let mut names = vec![4, b'n', b'a', b'm', b'e', 1, 7, 1, 1, 4];
+        names.extend(b"main");
+        bytes.push(0);
+        bytes.push(u8::try_from(names.len()).unwrap());
+        bytes.extend(names);
+
+        let layout = parse(&bytes).unwrap();
+        assert_eq!(layout.format, "wasm");
+        let names: Vec<&str> = layout.sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["import", "code", "name"]);
+        assert_eq!(layout.sections[1].kind, SectionKind::Code);
+        assert_eq!(layout.sections[1].size, 13);
+        assert_eq!(
+            layout.symbols,
+            vec![
+                Symbol {
+                    name: "main".to_string(),
+                    size: 4
+                },
+                Symbol {
+                    name: "function[2]".to_string(),
+                    size: 6
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_size_by_address() {
+        let placed = vec![
+            Placed {
+                name: "b".to_string(),
+                section: 0,
+                addr: 0x1040,
+            },
+            Placed {
+                name: "a".to_string(),
+                section: 0,
+                addr: 0x1000,
+            },
+            Placed {
+                name: "c".to_string(),
+                section: 1,
+                addr: 0x2000,
+            },
+        ];
+        let symbols = size_by_address(placed, &[0x1100, 0x2010]);
+        let sizes: Vec<(&str, u64)> = symbols.iter().map(|s| (s.name.as_str(), s.size)).collect();
+        assert_eq!(sizes, [("a", 0x40), ("b", 0xC0), ("c", 0x10)]);
+
+        assert_eq!(parse(b"!<arch>\n"), None);
+    }
+}
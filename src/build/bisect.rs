@@ -0,0 +1,339 @@
+//! Finding the commit that broke a target with `git bisect`
+//!
+//! `xcargo bisect-target` runs `git bisect run` with the hidden
+//! `xcargo __bisect-check` command as the test. Each commit is first checked
+//! for the host, and commits that don't even build there are skipped, so the
+//! search only blames commits for breakage specific to the target.
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use crate::target::Target;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use super::executor::Builder;
+use super::options::{BuildOptions, CargoOperation};
+
+/// Hidden subcommand `git bisect run` invokes for each commit
+pub const BISECT_CHECK_COMMAND: &str = "__bisect-check";
+
+/// Result of checking one commit, reported to `git bisect run` as an exit code
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BisectVerdict {
+    /// The target builds
+    Good,
+    /// The host builds but the target does not
+    Bad,
+    /// The commit cannot be tested (no usable config, or the host fails too)
+    Skip,
+    /// The environment is broken; stop bisecting
+    Abort,
+}
+
+impl BisectVerdict {
+    /// Exit code understood by `git bisect run`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::BisectVerdict;
+    ///
+    /// assert_eq!(BisectVerdict::Skip.exit_code(), 125);
+    /// ```
+    #[must_use]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Good => 0,
+            Self::Bad => 1,
+            Self::Skip => 125,
+            Self::Abort => 128,
+        }
+    }
+}
+
+/// First commit where the target stopped building
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FirstBadCommit {
+    /// Full commit hash
+    pub hash: String,
+
+    /// Subject line of the commit message
+    pub summary: String,
+}
+
+/// Check the checked-out commit for `target`
+///
+/// `options` carries the toolchain, Zig preference, and cargo arguments;
+/// its target and operation are replaced. The host is checked first unless
+/// it is the target itself.
+#[must_use]
+pub fn check_revision(target: &str, options: &BuildOptions) -> BisectVerdict {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            helpers::warning(format!("Skipping commit, configuration unusable: {e}"));
+            return BisectVerdict::Skip;
+        }
+    };
+    let builder = match Builder::with_config(config) {
+        Ok(builder) => builder,
+        Err(e) => {
+            helpers::error(format!("Cannot check commits: {e}"));
+            return BisectVerdict::Abort;
+        }
+    };
+    let host = match Target::detect_host() {
+        Ok(host) => host.triple,
+        Err(e) => {
+            helpers::error(format!("Cannot detect host target: {e}"));
+            return BisectVerdict::Abort;
+        }
+    };
+
+    let check = |triple: &str| {
+        builder.build(&BuildOptions {
+            target: Some(triple.to_string()),
+            operation: CargoOperation::Check,
+            ..options.clone()
+        })
+    };
+
+    if target != host && check(&host).is_err() {
+        helpers::warning(format!(
+            "Skipping commit, it does not build for the host ({host})"
+        ));
+        return BisectVerdict::Skip;
+    }
+
+    match check(target) {
+        Ok(()) => BisectVerdict::Good,
+        Err(_) => BisectVerdict::Bad,
+    }
+}
+
+/// Arguments for `xcargo __bisect-check` reproducing `options` for `target`
+#[must_use]
+pub fn bisect_check_args(target: &str, options: &BuildOptions) -> Vec<String> {
+    let mut args = vec![
+        BISECT_CHECK_COMMAND.to_string(),
+        "--target".to_string(),
+        target.to_string(),
+    ];
+    if let Some(toolchain) = &options.toolchain {
+        args.extend(["--toolchain".to_string(), toolchain.clone()]);
+    }
+    match options.use_zig {
+        Some(true) => args.push("--zig".to_string()),
+        Some(false) => args.push("--no-zig".to_string()),
+        None => {}
+    }
+    if !options.cargo_args.is_empty() || !options.selection.is_empty() {
+        args.push("--".to_string());
+        args.extend(options.selection.to_args());
+        args.extend(options.cargo_args.iter().cloned());
+    }
+    args
+}
+
+/// Bisect the commits between `good` and `bad` in the repository at `repo`
+///
+/// `check_command` is run at every commit, with `git bisect run` exit-code
+/// semantics. The repository is returned to its original commit afterwards.
+/// Returns `None` if git could not single out one commit (e.g. because
+/// the candidates were skipped).
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::path::Path;
+/// use xcargo::build::bisect;
+///
+/// # fn example() -> xcargo::Result<()> {
+/// let check = vec!["sh".to_string(), "-c".to_string(), "cargo check".to_string()];
+/// if let Some(commit) = bisect(Path::new("."), "v1.0.0", "HEAD", &check)? {
+///     println!("first bad commit: {} {}", commit.hash, commit.summary);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn bisect(
+    repo: &Path,
+    good: &str,
+    bad: &str,
+    check_command: &[String],
+) -> Result<Option<FirstBadCommit>> {
+    if !git(repo, &["status", "--porcelain", "--untracked-files=no"])?
+        .trim()
+        .is_empty()
+    {
+        return Err(Error::Build(
+            "Working tree has uncommitted changes; commit or stash them before bisecting"
+                .to_string(),
+        ));
+    }
+
+    git(repo, &["bisect", "start", bad, good, "--"])?;
+    let result = bisect_run(repo, check_command);
+    if let Err(e) = git(repo, &["bisect", "reset"]) {
+        helpers::warning(format!("Could not reset bisect state: {e}"));
+    }
+
+    let Some(hash) = result? else {
+        return Ok(None);
+    };
+    let summary = git(repo, &["log", "-1", "--format=%s", &hash])?
+        .trim()
+        .to_string();
+    Ok(Some(FirstBadCommit { hash, summary }))
+}
+
+/// Run `git bisect run`, echoing its output, and return the first bad commit
+fn bisect_run(repo: &Path, check_command: &[String]) -> Result<Option<String>> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["bisect", "run"])
+        .args(check_command)
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::Build(format!("Failed to run git: {e}")))?;
+
+    let mut first_bad = None;
+    let mut only_skipped = false;
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout)
+            .lines()
+            .map_while(std::io::Result::ok)
+        {
+            println!("{line}");
+            if let Some(hash) = line.strip_suffix(" is the first bad commit") {
+                first_bad = Some(hash.trim().to_string());
+            }
+            only_skipped |= line.contains("only 'skip'ped commits left");
+        }
+    }
+
+    let status = child
+        .wait()
+        .map_err(|e| Error::Build(format!("Failed to wait for git: {e}")))?;
+    if !status.success() && first_bad.is_none() && !only_skipped {
+        return Err(Error::Build(format!("git bisect run stopped ({status})")));
+    }
+    Ok(first_bad)
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|e| Error::Build(format!("Failed to run git: {e}")))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    } else {
+        Err(Error::Build(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn commit(repo: &Path, message: &str) {
+        git(
+            repo,
+            &[
+                "-c",
+                "user.name=xcargo",
+                "-c",
+                "user.email=xcargo@example.com",
+                "commit",
+                "-q",
+                "--allow-empty",
+                "-m",
+                message,
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_bisect_check_args() {
+        let options = BuildOptions {
+            toolchain: Some("nightly".to_string()),
+            use_zig: Some(false),
+            cargo_args: vec!["--features".to_string(), "simd".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            bisect_check_args("aarch64-unknown-linux-gnu", &options).join(" "),
+            "__bisect-check --target aarch64-unknown-linux-gnu --toolchain nightly --no-zig -- --features simd"
+        );
+        assert_eq!(
+            bisect_check_args("wasm32-unknown-unknown", &BuildOptions::default()).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_bisect_finds_first_bad_commit() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path();
+        git(repo, &["init", "-q"]).unwrap();
+
+        commit(repo, "good 1");
+        git(repo, &["tag", "good"]).unwrap();
+        commit(repo, "good 2");
+        std::fs::write(repo.join("broken"), "").unwrap();
+        git(repo, &["add", "broken"]).unwrap();
+        commit(repo, "break the target");
+        commit(repo, "bad 2");
+        let head = git(repo, &["rev-parse", "HEAD"]).unwrap();
+
+        let check = ["sh", "-c", "test ! -f broken"].map(String::from);
+        let found = bisect(repo, "good", "HEAD", &check).unwrap().unwrap();
+        assert_eq!(found.summary, "break the target");
+
+        // The repository is back where it started
+        assert_eq!(git(repo, &["rev-parse", "HEAD"]).unwrap(), head);
+    }
+
+    #[test]
+    fn test_bisect_all_skipped() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path();
+        git(repo, &["init", "-q"]).unwrap();
+        for message in ["one", "two", "three", "four"] {
+            commit(repo, message);
+        }
+
+        let skip = ["sh", "-c", "exit 125"].map(String::from);
+        assert!(bisect(repo, "HEAD~3", "HEAD", &skip).unwrap().is_none());
+
+        let abort = ["sh", "-c", "exit 128"].map(String::from);
+        assert!(bisect(repo, "HEAD~3", "HEAD", &abort).is_err());
+    }
+
+    #[test]
+    fn test_bisect_requires_clean_tree() {
+        let temp = TempDir::new().unwrap();
+        let repo = temp.path();
+        git(repo, &["init", "-q"]).unwrap();
+        std::fs::write(repo.join("file"), "").unwrap();
+        git(repo, &["add", "file"]).unwrap();
+        commit(repo, "add file");
+        std::fs::write(repo.join("file"), "changed").unwrap();
+
+        let check = ["true"].map(String::from);
+        assert!(bisect(repo, "HEAD", "HEAD", &check).is_err());
+    }
+}
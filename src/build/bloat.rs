@@ -0,0 +1,389 @@
+//! Binary size analysis and cross-target comparison
+//!
+//! Built binaries are read directly (see `binary.rs`), so a size report works
+//! for any target without its binutils. Symbols are attributed to crates by
+//! their demangled names the way cargo-bloat does it: the first path component
+//! of a Rust symbol is its crate, and everything else (C code, the linker's
+//! own symbols) counts as `[unknown]`.
+
+use crate::error::{Error, Result};
+use crate::output::table::align_columns;
+use crate::toolchain::format_size;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::binary::{self, SectionKind};
+
+/// Crate name for symbols that don't come from Rust code
+const UNKNOWN_CRATE: &str = "[unknown]";
+
+/// Longest symbol name shown in the table; JSON output has the full names
+const MAX_NAME_WIDTH: usize = 100;
+
+/// Size of a section of a binary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SectionSize {
+    /// Section name, e.g. `.text` or `__TEXT,__text`
+    pub name: String,
+
+    /// "code", "data", "debug" or "other"
+    pub kind: &'static str,
+
+    /// Bytes the section takes in the file
+    pub size: u64,
+}
+
+/// Bytes attributed to one crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrateSize {
+    /// Crate name, or `[unknown]` for non-Rust symbols
+    pub name: String,
+
+    /// Total size of the crate's symbols
+    pub size: u64,
+}
+
+/// A function or static and its size
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SymbolSize {
+    /// Demangled name
+    pub name: String,
+
+    /// Crate the symbol belongs to
+    #[serde(rename = "crate")]
+    pub crate_name: String,
+
+    /// Size in bytes
+    pub size: u64,
+}
+
+/// Where the bytes of a built binary go
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SizeReport {
+    /// Target triple the binary was built for
+    pub target: String,
+
+    /// File name of the binary
+    pub binary: String,
+
+    /// Path of the binary
+    pub path: PathBuf,
+
+    /// "ELF", "PE", "Mach-O" or "wasm"
+    pub format: &'static str,
+
+    /// Size of the file
+    pub file_size: u64,
+
+    /// Total size of the sections holding code
+    pub code_size: u64,
+
+    /// Total size of the sections holding constants and initialized data
+    pub data_size: u64,
+
+    /// Total size of debug information
+    pub debug_size: u64,
+
+    /// Sections, largest first
+    pub sections: Vec<SectionSize>,
+
+    /// The largest crates
+    pub crates: Vec<CrateSize>,
+
+    /// The largest symbols
+    pub symbols: Vec<SymbolSize>,
+}
+
+impl SizeReport {
+    /// Analyze a built binary, keeping the `top` largest crates and symbols
+    ///
+    /// Returns `None` for files that aren't an ELF, PE, 64-bit Mach-O or
+    /// WebAssembly binary, such as static libraries.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read
+    pub fn analyze(target: &str, path: &Path, top: usize) -> Result<Option<Self>> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| Error::Build(format!("Failed to read {}: {e}", path.display())))?;
+        let Some(layout) = binary::parse(&bytes) else {
+            return Ok(None);
+        };
+
+        let total = |kind: SectionKind| {
+            layout
+                .sections
+                .iter()
+                .filter(|s| s.kind == kind)
+                .map(|s| s.size)
+                .sum()
+        };
+        let mut sections: Vec<SectionSize> = layout
+            .sections
+            .iter()
+            .map(|s| SectionSize {
+                name: s.name.clone(),
+                kind: match s.kind {
+                    SectionKind::Code => "code",
+                    SectionKind::Data => "data",
+                    SectionKind::Debug => "debug",
+                    SectionKind::Other => "other",
+                },
+                size: s.size,
+            })
+            .collect();
+        sections.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+
+        let mut symbols: Vec<SymbolSize> = layout
+            .symbols
+            .iter()
+            .map(|symbol| SymbolSize {
+                name: demangle(&symbol.name),
+                crate_name: crate_name(&symbol.name).unwrap_or_else(|| UNKNOWN_CRATE.to_string()),
+                size: symbol.size,
+            })
+            .collect();
+
+        let mut by_crate: BTreeMap<&str, u64> = BTreeMap::new();
+        for symbol in &symbols {
+            *by_crate.entry(&symbol.crate_name).or_default() += symbol.size;
+        }
+        let mut crates: Vec<CrateSize> = by_crate
+            .into_iter()
+            .map(|(name, size)| CrateSize {
+                name: name.to_string(),
+                size,
+            })
+            .collect();
+        crates.sort_by_key(|c| std::cmp::Reverse(c.size));
+        crates.truncate(top);
+
+        symbols.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        symbols.truncate(top);
+
+        Ok(Some(Self {
+            target: target.to_string(),
+            binary: path
+                .file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().into_owned()),
+            path: path.to_path_buf(),
+            format: layout.format,
+            file_size: bytes.len() as u64,
+            code_size: total(SectionKind::Code),
+            data_size: total(SectionKind::Data),
+            debug_size: total(SectionKind::Debug),
+            sections,
+            crates,
+            symbols,
+        }))
+    }
+
+    /// Render the sections, crates and symbols, with their share of the file
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn render(&self, top: usize) -> String {
+        let percent = |size: u64| {
+            if self.file_size == 0 {
+                String::new()
+            } else {
+                format!("{:.1}%", size as f64 * 100.0 / self.file_size as f64)
+            }
+        };
+        let mut out = vec![format!(
+            "{}: {} ({}, {})",
+            self.target,
+            self.binary,
+            self.format,
+            format_size(self.file_size)
+        )];
+
+        out.push("  Sections".to_string());
+        let rows: Vec<Vec<String>> = self
+            .sections
+            .iter()
+            .take(top)
+            .map(|s| vec![format_size(s.size), percent(s.size), s.name.clone()])
+            .collect();
+        out.extend(
+            align_columns(&rows)
+                .into_iter()
+                .map(|row| format!("    {row}")),
+        );
+
+        if self.symbols.is_empty() {
+            out.push("  No symbols (the binary is stripped)".to_string());
+            return out.join("\n");
+        }
+
+        out.push("  Crates".to_string());
+        let rows: Vec<Vec<String>> = self
+            .crates
+            .iter()
+            .map(|c| vec![format_size(c.size), percent(c.size), c.name.clone()])
+            .collect();
+        out.extend(
+            align_columns(&rows)
+                .into_iter()
+                .map(|row| format!("    {row}")),
+        );
+
+        out.push("  Largest symbols".to_string());
+        let rows: Vec<Vec<String>> = self
+            .symbols
+            .iter()
+            .map(|s| {
+                vec![
+                    format_size(s.size),
+                    percent(s.size),
+                    s.crate_name.clone(),
+                    shorten(&s.name),
+                ]
+            })
+            .collect();
+        out.extend(
+            align_columns(&rows)
+                .into_iter()
+                .map(|row| format!("    {row}")),
+        );
+        out.join("\n")
+    }
+}
+
+/// Render one row per binary with its code, data and debug sizes; file sizes
+/// after the first row are shown with their ratio to the first
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn render_size_comparison(reports: &[SizeReport]) -> String {
+    let mut rows = vec![["target", "binary", "size", "code", "data", "debug"]
+        .map(String::from)
+        .to_vec()];
+    let baseline = reports.first().map(|r| r.file_size);
+    for (idx, report) in reports.iter().enumerate() {
+        let size = match baseline {
+            Some(base) if idx > 0 && base > 0 => format!(
+                "{} ({:.2}x)",
+                format_size(report.file_size),
+                report.file_size as f64 / base as f64
+            ),
+            _ => format_size(report.file_size),
+        };
+        rows.push(vec![
+            report.target.clone(),
+            report.binary.clone(),
+            size,
+            format_size(report.code_size),
+            format_size(report.data_size),
+            format_size(report.debug_size),
+        ]);
+    }
+    align_columns(&rows).join("\n")
+}
+
+/// Cut a generic-heavy symbol name down to [`MAX_NAME_WIDTH`] characters
+fn shorten(name: &str) -> String {
+    if name.chars().count() <= MAX_NAME_WIDTH {
+        return name.to_string();
+    }
+    let mut short: String = name.chars().take(MAX_NAME_WIDTH - 1).collect();
+    short.push('…');
+    short
+}
+
+/// Demangle a Rust symbol without its hash; other names are kept
+fn demangle(name: &str) -> String {
+    rustc_demangle::try_demangle(name).map_or_else(|_| name.to_string(), |d| format!("{d:#}"))
+}
+
+/// Crate a Rust symbol belongs to; `None` for other symbols
+///
+/// Trait impls (`<alloc::string::String as core::fmt::Display>::fmt`) count
+/// toward the crate of the implementing type.
+fn crate_name(name: &str) -> Option<String> {
+    let demangled = format!("{:#}", rustc_demangle::try_demangle(name).ok()?);
+    let path = demangled
+        .trim_start_matches(|c: char| "<&*[( ".contains(c))
+        .trim_start_matches("mut ")
+        .trim_start_matches("const ")
+        .trim_start_matches("dyn ");
+    let end = path
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(path.len());
+    (end > 0).then(|| path[..end].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_demangle() {
+        assert_eq!(
+            demangle("_ZN3std2rt19lang_start_internal17h0123456789abcdefE"),
+            "std::rt::lang_start_internal"
+        );
+        assert_eq!(
+            demangle("_ZN60_$LT$alloc..string..String$u20$as$u20$core..fmt..Display$GT$3fmt17h0123456789abcdefE"),
+            "<alloc::string::String as core::fmt::Display>::fmt"
+        );
+        assert_eq!(
+            demangle(
+                "_RNvMs_NtNtNtCsjrHSEGnQ3l9_3std12backtrace_rs9symbolize5gimliNtB4_7Context3new"
+            ),
+            "<std::backtrace_rs::symbolize::gimli::Context>::new"
+        );
+        assert_eq!(demangle("memcpy"), "memcpy");
+    }
+
+    #[test]
+    fn test_crate_name() {
+        assert_eq!(
+            crate_name("_ZN5serde2de5Error6custom17h0123456789abcdefE").as_deref(),
+            Some("serde")
+        );
+        // Mach-O and 32-bit Windows keep an extra underscore
+        assert_eq!(
+            crate_name("__ZN3std2io5stdio6_print17h0123456789abcdefE").as_deref(),
+            Some("std")
+        );
+        assert_eq!(
+            crate_name("_ZN60_$LT$alloc..string..String$u20$as$u20$core..fmt..Display$GT$3fmt17h0123456789abcdefE")
+                .as_deref(),
+            Some("alloc")
+        );
+        assert_eq!(
+            crate_name("_RNvCs1234_5myapp4main").as_deref(),
+            Some("myapp")
+        );
+        assert_eq!(
+            crate_name("_RNvNtNtCsfEOYDRpO4Ta_11miniz_oxide7inflate4core10decompress").as_deref(),
+            Some("miniz_oxide")
+        );
+        assert_eq!(crate_name("memcpy"), None);
+    }
+
+    #[test]
+    fn test_render_size_comparison() {
+        let report = |target: &str, file_size| SizeReport {
+            target: target.to_string(),
+            binary: "app".to_string(),
+            path: PathBuf::from("app"),
+            format: "ELF",
+            file_size,
+            code_size: 1024,
+            data_size: 512,
+            debug_size: 0,
+            sections: Vec::new(),
+            crates: Vec::new(),
+            symbols: Vec::new(),
+        };
+        let table = render_size_comparison(&[
+            report("x86_64-unknown-linux-musl", 2048),
+            report("aarch64-unknown-linux-musl", 3072),
+        ]);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("target"));
+        assert!(lines[1].contains("2.0 KiB"));
+        assert!(lines[2].contains("3.0 KiB (1.50x)"));
+    }
+}
@@ -0,0 +1,536 @@
+//! Async, cancellable builds for embedding xcargo in services
+//!
+//! [`Builder::build_async`] runs a target's build on tokio's blocking pool and
+//! kills cargo when its [`CancellationToken`] is cancelled, so an orchestration
+//! service can stop a stuck build without stopping the whole process. A
+//! builder created with [`Builder::with_concurrency_limit`] runs at most that
+//! many async builds at a time.
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::output::log::{self, Verbosity};
+use crate::output::progress::BuildProgress;
+use crate::output::terminal;
+use std::io::{BufRead, BufReader, IsTerminal};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+use super::cargo_progress::{force_progress, forward_stderr, StderrTail};
+use super::executor::Builder;
+use super::options::BuildOptions;
+use super::report::BuildReport;
+use super::target_output::TargetOutput;
+
+/// How often a running cargo is checked for cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How cargo runs for one target: killed when the build is cancelled or
+/// times out, and followed on the target's progress bar
+#[derive(Clone, Copy)]
+pub(super) struct CargoProcess<'a> {
+    /// Token that cancels the build, if it can be cancelled
+    token: Option<&'a CancellationToken>,
+
+    /// Time cargo may run before it is killed
+    timeout: Option<Duration>,
+
+    /// Target being built, for the error
+    target: &'a str,
+
+    /// Progress bar driven by cargo's progress, if one is shown
+    progress: Option<&'a BuildProgress>,
+
+    /// Where the end of cargo's stderr is kept, if anywhere
+    stderr_tail: Option<&'a StderrTail>,
+
+    /// Where cargo's output goes in a parallel build, if not straight to
+    /// the terminal
+    output: Option<&'a TargetOutput>,
+}
+
+impl<'a> CargoProcess<'a> {
+    pub(super) fn new(token: Option<&'a CancellationToken>, target: &'a str) -> Self {
+        Self {
+            token,
+            timeout: None,
+            target,
+            progress: None,
+            stderr_tail: None,
+            output: None,
+        }
+    }
+
+    /// Kill cargo, and the test binaries and runners it started, after `timeout`
+    pub(super) fn with_timeout(self, timeout: Option<Duration>) -> Self {
+        Self { timeout, ..self }
+    }
+
+    /// Whether cargo has to be watched while it runs
+    fn watched(&self) -> bool {
+        self.token.is_some() || self.timeout.is_some()
+    }
+
+    /// Show cargo's progress on `progress`, when the terminal can show it
+    pub(super) fn with_progress(self, progress: &'a BuildProgress) -> Self {
+        let visible = std::io::stderr().is_terminal()
+            && !crate::output::is_plain()
+            && log::enabled(Verbosity::Normal);
+        Self {
+            progress: Some(progress).filter(|_| visible),
+            ..self
+        }
+    }
+
+    /// Keep the end of cargo's stderr in `tail` while printing it
+    pub(super) fn capturing_stderr(self, tail: &'a StderrTail) -> Self {
+        Self {
+            stderr_tail: Some(tail),
+            ..self
+        }
+    }
+
+    /// Keep `text` with cargo's stderr, e.g. a diagnostic read from stdout
+    pub(super) fn capture(&self, text: &str) {
+        if let Some(tail) = self.stderr_tail {
+            tail.push(text);
+        }
+    }
+
+    /// Tag or group cargo's output with `output`'s target
+    pub(super) fn with_output(self, output: Option<&'a TargetOutput>) -> Self {
+        Self { output, ..self }
+    }
+
+    /// Run `print` with the progress bar out of the way
+    fn print(&self, print: impl FnOnce()) {
+        match self.progress {
+            Some(progress) => progress.suspend(print),
+            None => print(),
+        }
+    }
+
+    /// Print `text` as cargo printed it to stdout
+    pub(super) fn print_stdout(&self, text: &str) {
+        match self.output {
+            Some(output) => output.stdout(text, self.progress),
+            None => self.print(|| println!("{text}")),
+        }
+    }
+
+    /// Print `text` as cargo printed it to stderr
+    pub(super) fn print_stderr(&self, text: &str) {
+        match self.output {
+            Some(output) => output.stderr(text, self.progress),
+            None => self.print(|| eprint!("{text}")),
+        }
+    }
+
+    /// Fail if the build was cancelled
+    pub(super) fn check(&self) -> Result<()> {
+        if self.token.is_some_and(CancellationToken::is_cancelled) {
+            return Err(Error::Cancelled {
+                target: self.target.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Run `cmd` to completion, killing it if the build is cancelled
+    pub(super) fn run(&self, cmd: &mut Command) -> Result<ExitStatus> {
+        if self.output.is_some() {
+            return self.run_lines(cmd, |line| self.print_stdout(&line));
+        }
+        let running = self.spawn(cmd)?;
+        self.wait(running)
+    }
+
+    /// Start `cmd`, reading cargo's progress from its stderr if a progress
+    /// bar is shown, and keeping its stderr if asked to
+    fn spawn(&self, cmd: &mut Command) -> Result<Running> {
+        self.check()?;
+        if self.progress.is_some() {
+            force_progress(cmd);
+            cmd.stderr(Stdio::piped());
+        } else if self.stderr_tail.is_some() || self.output.is_some() {
+            // Read through a pipe, cargo would no longer see the terminal's colors
+            let colors = std::io::stderr().is_terminal() && terminal::colors_enabled();
+            if colors && std::env::var_os("CARGO_TERM_COLOR").is_none() {
+                cmd.env("CARGO_TERM_COLOR", "always");
+            }
+            cmd.stderr(Stdio::piped());
+        } else {
+            cmd.stderr(Stdio::inherit());
+        }
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| Error::Build(format!("Failed to execute cargo: {e}")))?;
+        let stderr = child.stderr.take().map(|stderr| {
+            forward_stderr(
+                stderr,
+                self.progress.cloned(),
+                self.stderr_tail.cloned(),
+                self.output.cloned(),
+            )
+        });
+        Ok(Running {
+            child,
+            stderr,
+            started: Instant::now(),
+        })
+    }
+
+    /// Run `cmd`, passing each line of its stdout to `on_line`, and kill it
+    /// if the build is cancelled or times out
+    pub(super) fn run_lines(
+        &self,
+        cmd: &mut Command,
+        mut on_line: impl FnMut(String),
+    ) -> Result<ExitStatus> {
+        let mut running = self.spawn(cmd.stdout(Stdio::piped()))?;
+        let Some(stdout) = running.child.stdout.take() else {
+            return self.wait(running);
+        };
+
+        if !self.watched() {
+            for line in BufReader::new(stdout)
+                .lines()
+                .map_while(std::io::Result::ok)
+            {
+                on_line(line);
+            }
+            return self.wait(running);
+        }
+
+        // Read on another thread so a silent cargo can still be stopped
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout)
+                .lines()
+                .map_while(std::io::Result::ok)
+            {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        loop {
+            match receiver.recv_timeout(POLL_INTERVAL) {
+                Ok(line) => on_line(line),
+                Err(RecvTimeoutError::Timeout) => self.kill_if_stopped(&mut running)?,
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        self.wait(running)
+    }
+
+    /// Wait for cargo to exit and for its stderr to be printed
+    fn wait(&self, mut running: Running) -> Result<ExitStatus> {
+        let status = if self.watched() {
+            loop {
+                let exited = running
+                    .child
+                    .try_wait()
+                    .map_err(|e| Error::Build(format!("Failed to wait for cargo: {e}")))?;
+                if let Some(status) = exited {
+                    break status;
+                }
+                self.kill_if_stopped(&mut running)?;
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        } else {
+            running
+                .child
+                .wait()
+                .map_err(|e| Error::Build(format!("Failed to wait for cargo: {e}")))?
+        };
+        if let Some(stderr) = running.stderr {
+            let _ = stderr.join();
+        }
+        Ok(status)
+    }
+
+    /// Kill cargo if the build was cancelled or has run out of time
+    fn kill_if_stopped(&self, running: &mut Running) -> Result<()> {
+        let stopped = self.check().and_then(|()| match self.timeout {
+            Some(timeout) if running.started.elapsed() >= timeout => Err(Error::BuildTimeout {
+                target: self.target.to_string(),
+                timeout,
+            }),
+            _ => Ok(()),
+        });
+        if stopped.is_err() {
+            kill_tree(&mut running.child);
+        }
+        stopped
+    }
+}
+
+/// Kill `child` and every process it started, such as test binaries and the
+/// emulators running them
+fn kill_tree(child: &mut Child) {
+    #[cfg(unix)]
+    kill_descendants(child.id());
+    #[cfg(windows)]
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &child.id().to_string()])
+        .output();
+
+    // The process may have exited in the meantime
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Kill the descendants of `pid`, deepest first
+#[cfg(unix)]
+fn kill_descendants(pid: u32) {
+    let Ok(output) = Command::new("pgrep")
+        .arg("-P")
+        .arg(pid.to_string())
+        .output()
+    else {
+        return;
+    };
+    let children = String::from_utf8_lossy(&output.stdout);
+    for child in children
+        .split_whitespace()
+        .filter_map(|pid| pid.parse().ok())
+    {
+        kill_descendants(child);
+        let _ = Command::new("kill")
+            .arg("-KILL")
+            .arg(child.to_string())
+            .output();
+    }
+}
+
+/// A started cargo and the thread printing its stderr
+struct Running {
+    child: Child,
+    stderr: Option<JoinHandle<()>>,
+    started: Instant,
+}
+
+impl Builder {
+    /// Limit how many [`build_async`](Self::build_async) calls run cargo at
+    /// the same time; further calls wait for a slot
+    #[must_use]
+    pub fn with_concurrency_limit(self, max_concurrent: usize) -> Self {
+        self.with_concurrency(Arc::new(Semaphore::new(max_concurrent.max(1))))
+    }
+
+    /// Run the cargo operation for a single target without blocking the
+    /// async runtime
+    ///
+    /// Cancelling `cancel` stops a build that is waiting for a slot and kills
+    /// cargo if it is running; the build then fails with
+    /// [`Error::Cancelled`]. A container build can only be cancelled before
+    /// the container starts.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xcargo::build::{Builder, BuildOptions, CancellationToken};
+    ///
+    /// # async fn example() -> xcargo::Result<()> {
+    /// let builder = Builder::new()?.with_concurrency_limit(2);
+    ///
+    /// // Calling `stop.cancel()` elsewhere kills the build
+    /// let cancel = CancellationToken::new();
+    /// let stop = cancel.clone();
+    /// # drop(stop);
+    ///
+    /// let options = BuildOptions {
+    ///     target: Some("aarch64-unknown-linux-gnu".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let report = builder.build_async(&options, &cancel).await?;
+    /// println!("{} built in {:?}", report.target, report.duration);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Builder::build`], or
+    /// [`Error::Cancelled`] if the build was cancelled.
+    pub async fn build_async(
+        &self,
+        options: &BuildOptions,
+        cancel: &CancellationToken,
+    ) -> Result<BuildReport> {
+        build_owned(
+            self.config().clone(),
+            self.concurrency().cloned(),
+            options.clone(),
+            cancel.clone(),
+        )
+        .await
+    }
+
+    /// Run the cargo operation for several targets concurrently, within the
+    /// builder's concurrency limit
+    ///
+    /// Without a concurrency limit, every target builds at once. Returns each
+    /// target's result, in the order of `targets`. Cancelling `cancel` cancels
+    /// every build that has not finished.
+    pub async fn build_all_async(
+        &self,
+        targets: &[String],
+        options: &BuildOptions,
+        cancel: &CancellationToken,
+    ) -> Vec<Result<BuildReport>> {
+        let handles: Vec<_> = targets
+            .iter()
+            .map(|target| {
+                let options = BuildOptions {
+                    target: Some(target.clone()),
+                    ..options.clone()
+                };
+                tokio::spawn(build_owned(
+                    self.config().clone(),
+                    self.concurrency().cloned(),
+                    options,
+                    cancel.clone(),
+                ))
+            })
+            .collect();
+
+        let mut results = Vec::with_capacity(handles.len());
+        for handle in handles {
+            results.push(
+                handle
+                    .await
+                    .unwrap_or_else(|e| Err(Error::Build(format!("Task join error: {e}")))),
+            );
+        }
+        results
+    }
+}
+
+/// Build one target on the blocking pool once a slot is free
+async fn build_owned(
+    config: Config,
+    concurrency: Option<Arc<Semaphore>>,
+    options: BuildOptions,
+    cancel: CancellationToken,
+) -> Result<BuildReport> {
+    let cancelled = || Error::Cancelled {
+        target: options.target.clone().unwrap_or_default(),
+    };
+
+    // Hold a slot until the build finishes
+    let _permit = match concurrency {
+        Some(semaphore) => tokio::select! {
+            permit = semaphore.acquire_owned() => Some(
+                permit.map_err(|e| Error::Build(format!("Build slots closed: {e}")))?,
+            ),
+            () = cancel.cancelled() => return Err(cancelled()),
+        },
+        None => None,
+    };
+    if cancel.is_cancelled() {
+        return Err(cancelled());
+    }
+
+    tokio::task::spawn_blocking(move || {
+        Builder::with_config(config)?
+            .with_cancellation(cancel)
+            .build_with_report(&options)
+    })
+    .await
+    .map_err(|e| Error::Build(format!("Task join error: {e}")))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_lines_without_token() {
+        let mut lines = Vec::new();
+        let status = CargoProcess::new(None, "x86_64-unknown-linux-gnu")
+            .run_lines(
+                Command::new("sh").args(["-c", "echo one; echo two"]),
+                |line| {
+                    lines.push(line);
+                },
+            )
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(lines, ["one", "two"]);
+    }
+
+    #[test]
+    fn test_cancel_kills_process() {
+        let token = CancellationToken::new();
+        let cancel = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            cancel.cancel();
+        });
+
+        let started = Instant::now();
+        let result = CargoProcess::new(Some(&token), "x86_64-unknown-linux-gnu").run_lines(
+            Command::new("sh").args(["-c", "echo started; sleep 30"]),
+            |_| {},
+        );
+        assert!(
+            matches!(result, Err(Error::Cancelled { target }) if target == "x86_64-unknown-linux-gnu")
+        );
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        // Already cancelled: nothing is started
+        let result = CargoProcess::new(Some(&token), "x86_64-unknown-linux-gnu")
+            .run(&mut Command::new("false"));
+        assert!(matches!(result, Err(Error::Cancelled { .. })));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_kills_process_tree() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let pid_file = temp.path().join("pid");
+        let script = format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+
+        let started = Instant::now();
+        let result = CargoProcess::new(None, "aarch64-unknown-linux-gnu")
+            .with_timeout(Some(Duration::from_millis(300)))
+            .run(Command::new("sh").args(["-c", &script]));
+        assert!(matches!(
+            result,
+            Err(Error::BuildTimeout { target, .. }) if target == "aarch64-unknown-linux-gnu"
+        ));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        // The background sleep was killed with the shell
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let alive = Command::new("kill")
+            .args(["-0", pid.trim()])
+            .output()
+            .unwrap();
+        assert!(!alive.status.success());
+    }
+
+    #[tokio::test]
+    async fn test_build_async_cancelled_while_waiting_for_slot() {
+        let config = Config::default();
+        let slots = Arc::new(Semaphore::new(1));
+        let _busy = Arc::clone(&slots).acquire_owned().await.unwrap();
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let options = BuildOptions {
+            target: Some("wasm32-unknown-unknown".to_string()),
+            ..Default::default()
+        };
+        let result = build_owned(config, Some(slots), options, cancel).await;
+        assert!(
+            matches!(result, Err(Error::Cancelled { target }) if target == "wasm32-unknown-unknown")
+        );
+    }
+}
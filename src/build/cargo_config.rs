@@ -0,0 +1,280 @@
+//! Cargo configuration files (`.cargo/config.toml`) a build runs with
+//!
+//! Cargo merges every `.cargo/config.toml` from the working directory up to
+//! the filesystem root, then `$CARGO_HOME/config.toml`, nearer files winning.
+//! Native builds read them as they are. A container only sees the files
+//! inside the mounted project, so the others are merged into one file that is
+//! mounted at the container's root, where cargo finds it the same way.
+
+use std::path::{Path, PathBuf};
+use toml::Table;
+#[cfg(feature = "container")]
+use toml::Value;
+
+/// A parsed cargo configuration file
+#[derive(Debug, Clone)]
+pub(super) struct CargoConfigFile {
+    /// Where the file is
+    pub path: PathBuf,
+
+    /// Its contents
+    pub table: Table,
+}
+
+#[cfg(feature = "container")]
+impl CargoConfigFile {
+    /// Directory cargo resolves the file's relative paths against: the
+    /// parent of its `.cargo` directory
+    fn base_dir(&self) -> &Path {
+        self.path
+            .parent()
+            .and_then(Path::parent)
+            .unwrap_or_else(|| Path::new("/"))
+    }
+}
+
+/// Cargo configuration files that apply to a build in `dir`, nearest first
+///
+/// Files that cannot be read are skipped; cargo reports them itself.
+#[must_use]
+pub(super) fn cargo_configs(dir: &Path) -> Vec<CargoConfigFile> {
+    let cargo_home = crate::toolchain::cargo_home();
+    let candidates = dir
+        .ancestors()
+        .map(|dir| dir.join(".cargo"))
+        .filter(|cargo_dir| Some(cargo_dir) != cargo_home.as_ref())
+        .chain(cargo_home.clone());
+
+    let mut configs = Vec::new();
+    for cargo_dir in candidates {
+        // `config` is the older name; cargo prefers `config.toml`
+        let Some(path) = [cargo_dir.join("config.toml"), cargo_dir.join("config")]
+            .into_iter()
+            .find(|path| path.is_file())
+        else {
+            continue;
+        };
+        let table = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|contents| contents.parse::<Table>().ok());
+        if let Some(table) = table {
+            configs.push(CargoConfigFile { path, table });
+        }
+    }
+    configs
+}
+
+/// Linker cargo would use for `triple` from `target.<triple>.linker`, and the
+/// file that sets it
+#[must_use]
+pub(super) fn configured_linker<'a>(
+    configs: &'a [CargoConfigFile],
+    triple: &str,
+) -> Option<(&'a str, &'a Path)> {
+    configs.iter().find_map(|config| {
+        let linker = config
+            .table
+            .get("target")?
+            .get(triple)?
+            .get("linker")?
+            .as_str()?;
+        Some((linker, config.path.as_path()))
+    })
+}
+
+/// The files outside `project_dir`, merged the way cargo merges them, with
+/// their relative paths made absolute; `None` if there are none
+#[cfg(feature = "container")]
+#[must_use]
+pub(super) fn merge_outside(configs: &[CargoConfigFile], project_dir: &Path) -> Option<Table> {
+    let outside: Vec<&CargoConfigFile> = configs
+        .iter()
+        .filter(|config| !config.path.starts_with(project_dir))
+        .collect();
+    if outside.is_empty() {
+        return None;
+    }
+
+    // Farthest first, so nearer files override
+    let mut merged = Table::new();
+    for config in outside.into_iter().rev() {
+        let mut table = config.table.clone();
+        let base_dir = config.base_dir();
+        for_each_path(&mut table, &mut |path| {
+            let absolute = base_dir.join(&*path);
+            *path = absolute.to_string_lossy().to_string();
+        });
+        merge(&mut merged, table);
+    }
+    Some(merged)
+}
+
+/// Point the host paths in `table` at where `volumes` mount them inside the
+/// container; returns the paths that are not mounted
+#[cfg(feature = "container")]
+pub(super) fn relocate(table: &mut Table, volumes: &[(String, String)]) -> Vec<String> {
+    let mut unmounted = Vec::new();
+    for_each_path(table, &mut |path| {
+        let mount = volumes.iter().find_map(|(host, container)| {
+            let rest = Path::new(path.as_str()).strip_prefix(host).ok()?;
+            Some(if rest.as_os_str().is_empty() {
+                PathBuf::from(container)
+            } else {
+                Path::new(container).join(rest)
+            })
+        });
+        match mount {
+            Some(container_path) => *path = container_path.to_string_lossy().to_string(),
+            None => unmounted.push(path.clone()),
+        }
+    });
+    unmounted
+}
+
+/// Merge `overlay` into `base` like cargo: tables merge key by key, arrays
+/// are joined, and other values are replaced
+#[cfg(feature = "container")]
+fn merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overlay)) => merge(base, overlay),
+            (Some(Value::Array(base)), Value::Array(overlay)) => base.extend(overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Call `f` with every filesystem path in a cargo configuration: `paths`,
+/// `[patch]` path dependencies, and directory and local registry sources
+#[cfg(feature = "container")]
+fn for_each_path(table: &mut Table, f: &mut impl FnMut(&mut String)) {
+    if let Some(Value::Array(paths)) = table.get_mut("paths") {
+        for path in paths {
+            if let Value::String(path) = path {
+                f(path);
+            }
+        }
+    }
+    if let Some(Value::Table(registries)) = table.get_mut("patch") {
+        for (_, patches) in registries.iter_mut() {
+            let Value::Table(patches) = patches else {
+                continue;
+            };
+            for (_, patch) in patches.iter_mut() {
+                if let Some(Value::String(path)) = patch.get_mut("path") {
+                    f(path);
+                }
+            }
+        }
+    }
+    if let Some(Value::Table(sources)) = table.get_mut("source") {
+        for (_, source) in sources.iter_mut() {
+            for key in ["directory", "local-registry"] {
+                if let Some(Value::String(path)) = source.get_mut(key) {
+                    f(path);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(path: &str, contents: &str) -> CargoConfigFile {
+        CargoConfigFile {
+            path: PathBuf::from(path),
+            table: contents.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_configured_linker_nearest_wins() {
+        let configs = [
+            config(
+                "/work/app/.cargo/config.toml",
+                "[target.aarch64-unknown-linux-gnu]\nlinker = \"aarch64-linux-gnu-gcc\"\n",
+            ),
+            config(
+                "/work/.cargo/config.toml",
+                "[target.aarch64-unknown-linux-gnu]\nlinker = \"clang\"\n",
+            ),
+        ];
+        assert_eq!(
+            configured_linker(&configs, "aarch64-unknown-linux-gnu"),
+            Some((
+                "aarch64-linux-gnu-gcc",
+                Path::new("/work/app/.cargo/config.toml")
+            ))
+        );
+        assert_eq!(configured_linker(&configs, "x86_64-pc-windows-gnu"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "container")]
+    fn test_merge_outside_project() {
+        let configs = [
+            config("/work/app/.cargo/config.toml", "[build]\njobs = 2\n"),
+            config(
+                "/work/.cargo/config.toml",
+                r#"
+                [patch.crates-io]
+                serde = { path = "serde" }
+
+                [net]
+                retry = 5
+                "#,
+            ),
+            config(
+                "/home/me/.cargo/config.toml",
+                r#"
+                [source.vendored]
+                directory = "/srv/vendor"
+
+                [net]
+                retry = 2
+                git-fetch-with-cli = true
+                "#,
+            ),
+        ];
+
+        let merged = merge_outside(&configs, Path::new("/work/app")).unwrap();
+        assert!(merged.get("build").is_none());
+        assert_eq!(merged["net"]["retry"].as_integer(), Some(5));
+        assert_eq!(merged["net"]["git-fetch-with-cli"].as_bool(), Some(true));
+        assert_eq!(
+            merged["patch"]["crates-io"]["serde"]["path"].as_str(),
+            Some("/work/serde")
+        );
+
+        let mut merged = merged;
+        let unmounted = relocate(
+            &mut merged,
+            &[("/work/serde".to_string(), "/serde".to_string())],
+        );
+        assert_eq!(
+            merged["patch"]["crates-io"]["serde"]["path"].as_str(),
+            Some("/serde")
+        );
+        assert_eq!(unmounted, ["/srv/vendor"]);
+
+        assert!(merge_outside(&configs[..1], Path::new("/work/app")).is_none());
+    }
+
+    #[test]
+    fn test_cargo_configs_nearest_first() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let app = temp_dir.path().join("app");
+        std::fs::create_dir_all(app.join(".cargo")).unwrap();
+        std::fs::create_dir_all(temp_dir.path().join(".cargo")).unwrap();
+        std::fs::write(app.join(".cargo/config.toml"), "[build]\njobs = 2\n").unwrap();
+        std::fs::write(temp_dir.path().join(".cargo/config"), "[net]\nretry = 5\n").unwrap();
+
+        let configs = cargo_configs(&app);
+        assert_eq!(configs[0].path, app.join(".cargo/config.toml"));
+        assert_eq!(configs[1].path, temp_dir.path().join(".cargo/config"));
+    }
+}
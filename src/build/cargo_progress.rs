@@ -0,0 +1,201 @@
+//! Cargo's own progress, read from its stderr
+//!
+//! When its progress bar is forced on, cargo redraws a line like
+//! `Building [=====>     ] 12/57: serde, syn(build)` as it compiles. xcargo
+//! reads those lines to drive its own progress bar with the real number of
+//! compiled units, and passes everything else cargo prints through. The end
+//! of what it printed is kept, so a failure's cause can be recognized.
+
+use crate::output::progress::BuildProgress;
+use crate::output::terminal::{read_segment, strip_ansi};
+use std::io::{BufReader, Read};
+use std::process::Command;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::JoinHandle;
+
+use super::target_output::TargetOutput;
+
+/// Width cargo draws its progress bar with; only the counts are read
+const PROGRESS_WIDTH: &str = "80";
+
+/// Bytes of cargo's stderr kept by a [`StderrTail`]
+const TAIL_BYTES: usize = 64 * 1024;
+
+/// The last lines cargo printed, without colors, shared with the thread
+/// reading them
+#[derive(Debug, Clone, Default)]
+pub(super) struct StderrTail(Arc<Mutex<String>>);
+
+impl StderrTail {
+    /// Keep `text`, dropping the oldest lines past [`TAIL_BYTES`]
+    pub(super) fn push(&self, text: &str) {
+        let mut tail = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        tail.push_str(&strip_ansi(text));
+        if !tail.ends_with('\n') {
+            tail.push('\n');
+        }
+        if tail.len() > TAIL_BYTES {
+            let excess = tail.len() - TAIL_BYTES;
+            let cut = tail[excess..]
+                .find('\n')
+                .map_or(tail.len(), |n| excess + n + 1);
+            tail.drain(..cut);
+        }
+    }
+
+    /// Everything kept so far
+    pub(super) fn text(&self) -> String {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+}
+
+/// Units cargo has compiled so far
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitProgress {
+    /// Units finished
+    pub done: u64,
+
+    /// Units in the build
+    pub total: u64,
+
+    /// Units compiling now, e.g. "serde, syn(build)"
+    pub current: String,
+}
+
+impl UnitProgress {
+    /// Parse one redraw of cargo's progress bar
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::UnitProgress;
+    ///
+    /// let progress = UnitProgress::parse("    Building [=====>      ] 12/57: serde, syn(build)   ").unwrap();
+    /// assert_eq!((progress.done, progress.total), (12, 57));
+    /// assert_eq!(progress.current, "serde, syn(build)");
+    /// assert_eq!(UnitProgress::parse("   Compiling serde v1.0.200"), None);
+    /// ```
+    #[must_use]
+    pub fn parse(line: &str) -> Option<Self> {
+        let rest = line.trim_start().strip_prefix("Building [")?;
+        let (_, counts) = rest.split_once("] ")?;
+        let (counts, current) = counts.split_once(':').unwrap_or((counts, ""));
+        let (done, total) = counts.trim().split_once('/')?;
+        Some(Self {
+            done: done.parse().ok()?,
+            total: total.parse().ok()?,
+            current: current.trim().to_string(),
+        })
+    }
+}
+
+/// Make cargo draw its progress bar into a pipe, in color if the terminal
+/// has colors
+pub(super) fn force_progress(cmd: &mut Command) {
+    cmd.env("CARGO_TERM_PROGRESS_WHEN", "always")
+        .env("CARGO_TERM_PROGRESS_WIDTH", PROGRESS_WIDTH);
+    if std::env::var_os("CARGO_TERM_COLOR").is_none() {
+        cmd.env("CARGO_TERM_COLOR", "always");
+    }
+}
+
+/// Read cargo's stderr on another thread, moving `progress` along with
+/// cargo's progress bar and printing every other line, through `output` if
+/// given, which is also kept in `tail`
+pub(super) fn forward_stderr(
+    stderr: impl Read + Send + 'static,
+    progress: Option<BuildProgress>,
+    tail: Option<StderrTail>,
+    output: Option<TargetOutput>,
+) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        // Progress redraws end in '\r', everything else in '\n'
+        let mut reader = BufReader::new(stderr);
+        let mut segment = Vec::new();
+        loop {
+            segment.clear();
+            let Ok(read) = read_segment(&mut reader, &mut segment) else {
+                break;
+            };
+            if read == 0 {
+                break;
+            }
+            let text = String::from_utf8_lossy(&segment);
+            let line = text.trim_end_matches(['\r', '\n']);
+            if let Some(progress) = &progress {
+                if let Some(units) = UnitProgress::parse(&strip_ansi(line)) {
+                    progress.set_units(units.done, units.total, &units.current);
+                    continue;
+                }
+            }
+            // Other redraws (fetching, downloading) are dropped
+            if text.ends_with('\r') {
+                continue;
+            }
+            if let Some(tail) = &tail {
+                tail.push(line);
+            }
+            match (&output, &progress) {
+                (Some(output), progress) => output.stderr(line, progress.as_ref()),
+                (None, Some(progress)) => progress.eprintln(line),
+                (None, None) => eprintln!("{line}"),
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_progress() {
+        let progress =
+            UnitProgress::parse("    Building [>    ] 1/16: proc-macro2(build)  ").unwrap();
+        assert_eq!(
+            progress,
+            UnitProgress {
+                done: 1,
+                total: 16,
+                current: "proc-macro2(build)".to_string(),
+            }
+        );
+        // Nothing compiling while cargo waits on a build script
+        assert_eq!(
+            UnitProgress::parse("    Building [==>  ] 3/16")
+                .unwrap()
+                .current,
+            ""
+        );
+        assert_eq!(
+            UnitProgress::parse("       Fetch [==>  ] 2 complete; 2 pending"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_colored_progress() {
+        let line = "\x1b[K\x1b[1m\x1b[96m    Building\x1b[0m [=====>  ] 14/16: serde   ";
+        assert_eq!(UnitProgress::parse(&strip_ansi(line)).unwrap().done, 14);
+    }
+
+    #[test]
+    fn test_stderr_tail_keeps_the_end() {
+        let tail = StderrTail::default();
+        tail.push("\x1b[1m\x1b[31merror\x1b[0m: linking with `cc` failed");
+        assert_eq!(tail.text(), "error: linking with `cc` failed\n");
+
+        let line = "x".repeat(1000);
+        for _ in 0..100 {
+            tail.push(&line);
+        }
+        tail.push("cannot find -lssl");
+        let text = tail.text();
+        assert!(text.len() <= TAIL_BYTES);
+        assert!(text.starts_with('x'));
+        assert!(text.ends_with("cannot find -lssl\n"));
+    }
+}
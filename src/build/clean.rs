@@ -0,0 +1,156 @@
+//! Selective cleaning of build output and xcargo's caches
+//!
+//! `cargo clean` removes the whole target directory, which throws away every
+//! other target's build along with the one that needs a fresh start.
+//! `xcargo clean` removes `target/<triple>/` for chosen targets only, and
+//! optionally the Zig wrapper scripts and xcargo's build cache.
+
+use crate::error::{Error, Result};
+use crate::toolchain::dir_size;
+use std::path::{Path, PathBuf};
+
+use super::summary::{isolated_target_dir, BuildHistory};
+
+/// A directory `xcargo clean` removes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CleanItem {
+    /// What the directory holds, e.g. a target triple or "Zig wrappers"
+    pub label: String,
+
+    /// Path to the directory
+    pub path: PathBuf,
+
+    /// Total size of the directory, in bytes
+    pub size_bytes: u64,
+
+    /// Target whose build output this is, if any
+    pub target: Option<String>,
+}
+
+impl CleanItem {
+    /// Item for a directory, or `None` if it doesn't exist
+    #[must_use]
+    pub fn for_dir(label: impl Into<String>, path: PathBuf) -> Option<Self> {
+        path.is_dir().then(|| Self {
+            label: label.into(),
+            size_bytes: dir_size(&path),
+            path,
+            target: None,
+        })
+    }
+}
+
+/// Build output of `targets` in `target_dir`, for the targets that have any
+///
+/// A target built with `build.isolate_targets` also has its own target
+/// directory under `isolated/`, which is removed along with `<triple>/`.
+#[must_use]
+pub fn target_clean_items(target_dir: &Path, targets: &[String]) -> Vec<CleanItem> {
+    targets
+        .iter()
+        .flat_map(|target| {
+            let shared = CleanItem::for_dir(target.clone(), target_dir.join(target));
+            let isolated = CleanItem::for_dir(
+                format!("{target} (isolated)"),
+                isolated_target_dir(target_dir, target),
+            );
+            shared.into_iter().chain(isolated).map(|item| CleanItem {
+                target: Some(target.clone()),
+                ..item
+            })
+        })
+        .collect()
+}
+
+/// Remove `items`, and the build history of the targets among them
+///
+/// # Errors
+/// Returns an error if a directory cannot be removed
+pub fn remove_clean_items(items: &[CleanItem], history: &mut BuildHistory) -> Result<()> {
+    for item in items {
+        std::fs::remove_dir_all(&item.path)
+            .map_err(|e| Error::Build(format!("Failed to remove {}: {e}", item.path.display())))?;
+        if let Some(target) = &item.target {
+            let prefix = format!("{target}/");
+            history.targets.retain(|key, _| !key.starts_with(&prefix));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::HistoryEntry;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_clean_selected_targets() {
+        let temp = TempDir::new().unwrap();
+        let target_dir = temp.path();
+        for triple in ["aarch64-unknown-linux-gnu", "x86_64-pc-windows-gnu"] {
+            let profile_dir = target_dir.join(triple).join("release");
+            std::fs::create_dir_all(&profile_dir).unwrap();
+            std::fs::write(profile_dir.join("app"), vec![0u8; 100]).unwrap();
+        }
+
+        let mut history = BuildHistory::default();
+        for key in [
+            "aarch64-unknown-linux-gnu/release",
+            "x86_64-pc-windows-gnu/release",
+        ] {
+            history.targets.insert(
+                key.to_string(),
+                HistoryEntry {
+                    size_bytes: 100,
+                    duration_ms: 1000,
+                    built_at: None,
+                },
+            );
+        }
+
+        // Targets that were never built have nothing to clean
+        let items = target_clean_items(
+            target_dir,
+            &[
+                "aarch64-unknown-linux-gnu".to_string(),
+                "riscv64gc-unknown-linux-gnu".to_string(),
+            ],
+        );
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].size_bytes, 100);
+        assert_eq!(
+            items[0].target.as_deref(),
+            Some("aarch64-unknown-linux-gnu")
+        );
+
+        remove_clean_items(&items, &mut history).unwrap();
+        assert!(!target_dir.join("aarch64-unknown-linux-gnu").exists());
+        assert!(target_dir.join("x86_64-pc-windows-gnu").exists());
+        assert_eq!(
+            history.targets.keys().collect::<Vec<_>>(),
+            ["x86_64-pc-windows-gnu/release"]
+        );
+    }
+
+    #[test]
+    fn test_clean_isolated_target() {
+        let temp = TempDir::new().unwrap();
+        let target = "aarch64-unknown-linux-gnu";
+        let isolated = isolated_target_dir(temp.path(), target);
+        std::fs::create_dir_all(isolated.join(target).join("debug")).unwrap();
+
+        let items = target_clean_items(temp.path(), &[target.to_string()]);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].path, isolated);
+        assert_eq!(items[0].target.as_deref(), Some(target));
+    }
+
+    #[test]
+    fn test_clean_item_for_missing_dir() {
+        let temp = TempDir::new().unwrap();
+        assert!(CleanItem::for_dir("Zig wrappers", temp.path().join("zig-wrappers")).is_none());
+        let item = CleanItem::for_dir("build cache", temp.path().to_path_buf()).unwrap();
+        assert_eq!(item.target, None);
+    }
+}
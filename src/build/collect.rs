@@ -0,0 +1,344 @@
+//! Test binaries built in one place and run in another
+//!
+//! `xcargo test --no-run --collect <dir>` cross-compiles the test binaries
+//! for a target and copies them, with a manifest, into `<dir>/<target>/`.
+//! The directory can then be moved to real hardware or a later CI stage,
+//! where `xcargo test --from-collected <dir>` runs the binaries directly or
+//! through the target's configured runner.
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use crate::target::Target;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::executor::Builder;
+use super::runner::{run_with_policy, RunnerPolicy};
+
+/// Manifest written next to collected test binaries
+pub const TEST_MANIFEST: &str = "xcargo-tests.json";
+
+/// A collected test binary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollectedTest {
+    /// Package the test belongs to
+    pub package: String,
+
+    /// Name of the test target, e.g. the integration test file's name
+    pub name: String,
+
+    /// Kind of target the tests are in, e.g. "lib", "bin", or "test"
+    pub kind: String,
+
+    /// File name of the binary, relative to the manifest
+    pub file: String,
+}
+
+/// Test binaries collected for one target
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestManifest {
+    /// Target triple the binaries were built for
+    pub target: String,
+
+    /// Whether the binaries were built in release mode
+    pub release: bool,
+
+    /// The collected binaries
+    pub tests: Vec<CollectedTest>,
+}
+
+impl TestManifest {
+    /// Load the manifest in `dir`
+    ///
+    /// # Errors
+    /// Returns an error if the manifest cannot be read or parsed
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(TEST_MANIFEST);
+        let contents = std::fs::read_to_string(&path)
+            .map_err(|e| Error::Config(format!("Failed to read {}: {e}", path.display())))?;
+        serde_json::from_str(&contents)
+            .map_err(|e| Error::Config(format!("Failed to parse {}: {e}", path.display())))
+    }
+
+    /// Write the manifest into `dir`
+    ///
+    /// # Errors
+    /// Returns an error if the manifest cannot be written
+    pub fn save(&self, dir: &Path) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Config(format!("Failed to serialize {TEST_MANIFEST}: {e}")))?;
+        std::fs::write(dir.join(TEST_MANIFEST), contents)?;
+        Ok(())
+    }
+}
+
+/// Test binaries cargo reported in `--message-format=json` output
+pub(super) fn parse_test_artifacts(cargo_json: &str) -> Vec<(CollectedTest, PathBuf)> {
+    cargo_json
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line).ok())
+        .filter(|message| {
+            message["reason"] == "compiler-artifact" && message["profile"]["test"] == true
+        })
+        .filter_map(|message| {
+            let executable = PathBuf::from(message["executable"].as_str()?);
+            let package = package_name(message["package_id"].as_str().unwrap_or_default());
+            let test = CollectedTest {
+                package,
+                name: message["target"]["name"].as_str()?.to_string(),
+                kind: message["target"]["kind"][0]
+                    .as_str()
+                    .unwrap_or("test")
+                    .to_string(),
+                file: executable.file_name()?.to_string_lossy().into_owned(),
+            };
+            Some((test, executable))
+        })
+        .collect()
+}
+
+/// Package name in a cargo package ID, e.g. `path+file:///src/app#0.1.0`,
+/// `registry+https://...#serde@1.0.0`, or the older `app 0.1.0 (...)`
+fn package_name(package_id: &str) -> String {
+    let name = match package_id.rsplit_once('#') {
+        Some((_, fragment)) if fragment.contains('@') => fragment.split('@').next(),
+        Some((source, _)) => source.rsplit('/').next(),
+        None => package_id.split(' ').next(),
+    };
+    name.unwrap_or_default().to_string()
+}
+
+/// Copy test binaries into `dir/<target>/` and write their manifest there
+///
+/// # Errors
+/// Returns an error if a binary cannot be copied or the manifest written
+pub(super) fn collect_tests(
+    artifacts: Vec<(CollectedTest, PathBuf)>,
+    dir: &Path,
+    target: &str,
+    release: bool,
+) -> Result<(PathBuf, TestManifest)> {
+    let target_dir = dir.join(target);
+    std::fs::create_dir_all(&target_dir)?;
+
+    let mut tests = Vec::new();
+    for (test, executable) in artifacts {
+        let dest = target_dir.join(&test.file);
+        std::fs::copy(&executable, &dest).map_err(|e| {
+            Error::Build(format!(
+                "Failed to copy {} to {}: {e}",
+                executable.display(),
+                dest.display()
+            ))
+        })?;
+        tests.push(test);
+    }
+    tests.sort_by(|a, b| (&a.package, &a.name).cmp(&(&b.package, &b.name)));
+
+    let manifest = TestManifest {
+        target: target.to_string(),
+        release,
+        tests,
+    };
+    manifest.save(&target_dir)?;
+    Ok((target_dir, manifest))
+}
+
+/// Manifests in a collection directory: `dir` itself, or each target
+/// directory inside it
+///
+/// # Errors
+/// Returns an error if `dir` holds no collected tests or a manifest is invalid
+pub fn collected_manifests(dir: &Path) -> Result<Vec<(PathBuf, TestManifest)>> {
+    if dir.join(TEST_MANIFEST).is_file() {
+        return Ok(vec![(dir.to_path_buf(), TestManifest::load(dir)?)]);
+    }
+
+    let mut manifests = Vec::new();
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        let mut dirs: Vec<PathBuf> = entries
+            .filter_map(std::result::Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.join(TEST_MANIFEST).is_file())
+            .collect();
+        dirs.sort();
+        for target_dir in dirs {
+            let manifest = TestManifest::load(&target_dir)?;
+            manifests.push((target_dir, manifest));
+        }
+    }
+    if manifests.is_empty() {
+        return Err(Error::Config(format!(
+            "No collected tests in {} (expected {TEST_MANIFEST} files from `xcargo test --no-run --collect`)",
+            dir.display()
+        )));
+    }
+    Ok(manifests)
+}
+
+/// Run collected test binaries, directly or through each target's runner
+///
+/// `target` restricts the run to one collected target; `test_args` are
+/// passed to every test binary. Needs no Rust toolchain, so it also works
+/// on the hardware the tests were built for.
+///
+/// # Errors
+/// Returns an error if nothing was collected for the target or a test binary fails
+pub fn run_collected(
+    config: &Config,
+    dir: &Path,
+    target: Option<&str>,
+    test_args: &[String],
+) -> Result<()> {
+    let manifests: Vec<_> = collected_manifests(dir)?
+        .into_iter()
+        .filter(|(_, manifest)| target.map_or(true, |t| manifest.target == t))
+        .collect();
+    if manifests.is_empty() {
+        return Err(Error::Config(format!(
+            "No tests for {} collected in {}",
+            target.unwrap_or_default(),
+            dir.display()
+        )));
+    }
+
+    let mut passed = 0;
+    let mut failed = Vec::new();
+    for (target_dir, manifest) in &manifests {
+        let target = Target::from_triple(&manifest.target)?;
+        let runner = match config
+            .get_target_config(&target.triple)
+            .and_then(|c| c.runner.clone())
+        {
+            Some(runner) => Some(runner),
+            None => Builder::default_runner(&target)?,
+        };
+        if runner.is_none() && !Builder::host_can_run(&target)? {
+            helpers::warning(format!(
+                "No runner configured for {} and this host can't execute its binaries; skipping",
+                target.triple
+            ));
+            helpers::tip(format!(
+                "Configure one in xcargo.toml: [targets.\"{}\"] runner = \"{}\"",
+                target.triple,
+                Builder::suggested_runner(&target)
+            ));
+            continue;
+        }
+        let policy = config
+            .runners
+            .get(&target.triple)
+            .map(RunnerPolicy::from_config)
+            .transpose()?
+            .unwrap_or_default();
+
+        helpers::progress(format!(
+            "Running {} test binar{} for {}",
+            manifest.tests.len(),
+            if manifest.tests.len() == 1 {
+                "y"
+            } else {
+                "ies"
+            },
+            target.triple
+        ));
+        for test in &manifest.tests {
+            let mut command: Vec<String> = runner
+                .as_deref()
+                .map(|r| r.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_default();
+            let runner_len = command.len();
+            command.push(target_dir.join(&test.file).display().to_string());
+            command.extend(test_args.iter().cloned());
+
+            helpers::info(format!("{} {} ({})", test.package, test.name, test.kind));
+            if run_with_policy(&command, runner_len, &policy, None)? == 0 {
+                passed += 1;
+            } else {
+                failed.push(format!("{}: {} {}", target.triple, test.package, test.name));
+            }
+        }
+    }
+
+    helpers::line("");
+    if failed.is_empty() {
+        helpers::success(format!(
+            "{passed} test binar{} passed",
+            if passed == 1 { "y" } else { "ies" }
+        ));
+        return Ok(());
+    }
+    for failure in &failed {
+        helpers::error(format!("Failed: {failure}"));
+    }
+    Err(Error::Build(format!(
+        "{} of {} test binaries failed",
+        failed.len(),
+        failed.len() + passed
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CARGO_JSON: &str = r#"{"reason":"compiler-artifact","package_id":"path+file:///src/app#0.1.0","target":{"kind":["lib"],"name":"app"},"profile":{"test":true},"executable":"/src/app/target/aarch64-unknown-linux-gnu/debug/deps/app-1a2b"}
+{"reason":"compiler-artifact","package_id":"path+file:///src/app#0.1.0","target":{"kind":["lib"],"name":"app"},"profile":{"test":false},"executable":null}
+{"reason":"compiler-artifact","package_id":"app 0.1.0 (path+file:///src/app)","target":{"kind":["test"],"name":"cli"},"profile":{"test":true},"executable":"/src/app/target/aarch64-unknown-linux-gnu/debug/deps/cli-3c4d"}
+{"reason":"build-finished","success":true}"#;
+
+    #[test]
+    fn test_parse_test_artifacts() {
+        let artifacts = parse_test_artifacts(CARGO_JSON);
+        assert_eq!(artifacts.len(), 2);
+        assert_eq!(
+            artifacts[0].0,
+            CollectedTest {
+                package: "app".to_string(),
+                name: "app".to_string(),
+                kind: "lib".to_string(),
+                file: "app-1a2b".to_string(),
+            }
+        );
+        assert_eq!(artifacts[1].0.package, "app");
+        assert_eq!(artifacts[1].0.kind, "test");
+        assert_eq!(
+            package_name("registry+https://github.com/rust-lang/crates.io-index#serde@1.0.200"),
+            "serde"
+        );
+    }
+
+    #[test]
+    fn test_collect_and_find_manifests() {
+        let temp = TempDir::new().unwrap();
+        let binary = temp.path().join("cli-3c4d");
+        std::fs::write(&binary, "binary").unwrap();
+        let test = CollectedTest {
+            package: "app".to_string(),
+            name: "cli".to_string(),
+            kind: "test".to_string(),
+            file: "cli-3c4d".to_string(),
+        };
+
+        let out = temp.path().join("collected");
+        let (target_dir, manifest) = collect_tests(
+            vec![(test, binary)],
+            &out,
+            "aarch64-unknown-linux-gnu",
+            false,
+        )
+        .unwrap();
+        assert!(target_dir.join("cli-3c4d").is_file());
+
+        // Both the collection root and a target directory can be given
+        let found = collected_manifests(&out).unwrap();
+        assert_eq!(found, [(target_dir.clone(), manifest.clone())]);
+        assert_eq!(
+            collected_manifests(&target_dir).unwrap(),
+            [(target_dir, manifest)]
+        );
+        assert!(collected_manifests(temp.path()).is_err());
+    }
+}
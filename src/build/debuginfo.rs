@@ -0,0 +1,432 @@
+//! Split debug info and symbol archives
+//!
+//! With `split_debuginfo = true`, a target's binaries are shipped without
+//! their debug info, and the debug info is kept in a symbol archive so crash
+//! reports from production can be symbolicated later. How the debug info is
+//! split depends on the binary format:
+//!
+//! - ELF, and PE built by MinGW: `objcopy --only-keep-debug` writes
+//!   `<binary>.debug`, and the binary keeps a `.gnu_debuglink` to it; a
+//!   `.dwp` package from `split-debuginfo = "packed"` is archived too
+//! - Mach-O: `dsymutil` collects the debug info into `<binary>.dSYM`
+//! - PE built by MSVC: the linker already wrote a `.pdb`
+//!
+//! Symbol files are archived under `target/symbols/<triple>/` in the layout
+//! of a symbol server: `<file>/<build ID>/<file>`. The build ID is the ELF
+//! `NT_GNU_BUILD_ID` note, the Mach-O `LC_UUID`, or the GUID and age a PE
+//! binary records for its PDB, which is what debuggers and crash reporters
+//! look symbols up by.
+
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::binary::{self, Reader, SectionKind};
+use super::doc::copy_dir;
+use super::postprocess::{run_tool, Tool};
+use super::summary::target_dir;
+
+/// Directory under the target directory symbol files are archived in
+pub const SYMBOLS_DIR: &str = "symbols";
+
+const PT_NOTE: u32 = 4;
+/// The note holding the linker's build ID
+const NT_GNU_BUILD_ID: u32 = 3;
+const LC_UUID: u32 = 0x1b;
+const IMAGE_DEBUG_TYPE_CODEVIEW: u32 = 2;
+
+/// Build ID of an ELF, Mach-O or PE binary, as symbol servers spell it:
+/// lowercase hex for ELF, uppercase hex for Mach-O UUIDs and for the PDB
+/// GUID of PE binaries, followed by the PDB's age
+pub(super) fn build_id(bytes: &[u8]) -> Option<String> {
+    match bytes.get(..4)? {
+        b"\x7fELF" => elf_build_id(bytes),
+        [0xcf, 0xfa, 0xed, 0xfe] => macho_uuid(bytes),
+        [b'M', b'Z', ..] => codeview(bytes).map(|(id, _)| id),
+        _ => None,
+    }
+}
+
+fn elf_build_id(bytes: &[u8]) -> Option<String> {
+    let elf = Reader {
+        bytes,
+        wide: *bytes.get(4)? == 2,
+        big_endian: *bytes.get(5)? == 2,
+    };
+    let (phoff, phentsize, phnum) = if elf.wide {
+        (elf.usize(0x20)?, elf.u16(0x36)?, elf.u16(0x38)?)
+    } else {
+        (elf.usize(0x1C)?, elf.u16(0x2A)?, elf.u16(0x2C)?)
+    };
+    for idx in 0..usize::from(phnum) {
+        let ph = phoff.checked_add(idx * usize::from(phentsize))?;
+        if elf.u32(ph)? != PT_NOTE {
+            continue;
+        }
+        let (offset, filesz, align) = if elf.wide {
+            (elf.usize(ph + 8)?, elf.usize(ph + 32)?, elf.usize(ph + 48)?)
+        } else {
+            (elf.usize(ph + 4)?, elf.usize(ph + 16)?, elf.usize(ph + 28)?)
+        };
+        let align = if align == 8 { 8 } else { 4 };
+        let pad = |n: usize| (n + align - 1) / align * align;
+        let mut note = offset;
+        while note + 12 <= offset + filesz {
+            let (namesz, descsz) = (elf.u32(note)? as usize, elf.u32(note + 4)? as usize);
+            let name = note + 12;
+            let desc = name + pad(namesz);
+            if elf.u32(note + 8)? == NT_GNU_BUILD_ID
+                && elf.bytes.get(name..name + 4) == Some(b"GNU\0")
+            {
+                return Some(hex(elf.bytes.get(desc..desc + descsz)?, false));
+            }
+            note = desc + pad(descsz);
+        }
+    }
+    None
+}
+
+fn macho_uuid(bytes: &[u8]) -> Option<String> {
+    let macho = Reader::le(bytes);
+    let mut cmd = 32;
+    for _ in 0..macho.u32(16)? {
+        let (kind, size) = (macho.u32(cmd)?, macho.u32(cmd + 4)? as usize);
+        if kind == LC_UUID {
+            return Some(hex(bytes.get(cmd + 8..cmd + 24)?, true));
+        }
+        cmd += size;
+    }
+    None
+}
+
+/// The `CodeView` record of a PE binary: its PDB's ID and file name
+fn codeview(bytes: &[u8]) -> Option<(String, String)> {
+    let pe = Reader::le(bytes);
+    let header = pe.u32(0x3C)? as usize;
+    if bytes.get(header..header + 4)? != b"PE\0\0" {
+        return None;
+    }
+    let coff = header + 4;
+    let optional = coff + 20;
+    let directories = match pe.u16(optional)? {
+        0x10b => optional + 96,
+        0x20b => optional + 112,
+        _ => return None,
+    };
+    // Data directory 6 is the debug directory
+    let (debug_rva, debug_size) = (pe.u32(directories + 48)?, pe.u32(directories + 52)?);
+    let table = optional + usize::from(pe.u16(coff + 16)?);
+    let file_offset = |rva: u32| {
+        (0..usize::from(pe.u16(coff + 2)?)).find_map(|idx| {
+            let sh = table + idx * 40;
+            let (vaddr, raw_size, raw) = (pe.u32(sh + 12)?, pe.u32(sh + 16)?, pe.u32(sh + 20)?);
+            (rva >= vaddr && rva - vaddr < raw_size).then(|| (raw + rva - vaddr) as usize)
+        })
+    };
+
+    let directory = file_offset(debug_rva)?;
+    for entry in (directory..directory + debug_size as usize).step_by(28) {
+        if pe.u32(entry + 12)? != IMAGE_DEBUG_TYPE_CODEVIEW {
+            continue;
+        }
+        let record = pe.u32(entry + 24)? as usize;
+        if bytes.get(record..record + 4)? != b"RSDS" {
+            continue;
+        }
+        let guid = record + 4;
+        let mut id = format!(
+            "{:08X}{:04X}{:04X}",
+            pe.u32(guid)?,
+            pe.u16(guid + 4)?,
+            pe.u16(guid + 6)?
+        );
+        id.push_str(&hex(bytes.get(guid + 8..guid + 16)?, true));
+        let _ = write!(id, "{:X}", pe.u32(guid + 16)?);
+        let path = pe.str(guid + 20)?;
+        let name = path.rsplit(['\\', '/']).next().unwrap_or(&path).to_string();
+        return Some((id, name));
+    }
+    None
+}
+
+fn hex(bytes: &[u8], upper: bool) -> String {
+    bytes.iter().fold(String::new(), |mut out, byte| {
+        let _ = if upper {
+            write!(out, "{byte:02X}")
+        } else {
+            write!(out, "{byte:02x}")
+        };
+        out
+    })
+}
+
+/// Cargo variable that keeps debug info in a profile's builds, unless
+/// `manifest` or the environment already configures it
+///
+/// Release builds have no debug info by default, which would leave nothing
+/// to split.
+pub(super) fn profile_debug_env(release: bool, manifest: &Path) -> Option<(String, String)> {
+    let (profile, name) = if release {
+        ("release", "RELEASE")
+    } else {
+        ("dev", "DEV")
+    };
+    let key = format!("CARGO_PROFILE_{name}_DEBUG");
+    if std::env::var_os(&key).is_some() {
+        return None;
+    }
+    let manifest: Option<toml::Table> = std::fs::read_to_string(manifest)
+        .ok()
+        .and_then(|text| text.parse().ok());
+    let configured = manifest
+        .as_ref()
+        .and_then(|manifest| manifest.get("profile")?.get(profile)?.get("debug"))
+        .is_some();
+    (!configured).then(|| (key, "true".to_string()))
+}
+
+/// Split the debug info out of `binary` and archive it by build ID
+///
+/// `objcopy` must understand the binary's format; `dsymutil` is looked up
+/// for Mach-O binaries.
+pub(super) fn split_debuginfo(binary: &Path, objcopy: Option<&Tool>, triple: &str) -> Result<()> {
+    let name = binary.file_name().unwrap_or_default().to_string_lossy();
+    let bytes = std::fs::read(binary)?;
+    let Some(layout) = binary::parse(&bytes) else {
+        return Ok(());
+    };
+    let has_debug = layout
+        .sections
+        .iter()
+        .any(|section| section.kind == SectionKind::Debug);
+
+    let mut symbols = Vec::new();
+    match layout.format {
+        "Mach-O" => {
+            let dsym = sibling(binary, "dSYM");
+            if !dsym.exists() {
+                let dsymutil = ["dsymutil", "llvm-dsymutil"]
+                    .iter()
+                    .find_map(|tool| which::which(tool).ok())
+                    .ok_or_else(|| {
+                        helpers::tip("dsymutil comes with Xcode; on other hosts install LLVM");
+                        Error::Build(format!(
+                            "No dsymutil available to split the debug info of {name}"
+                        ))
+                    })?;
+                let mut cmd = Command::new(dsymutil);
+                cmd.arg(binary).arg("-o").arg(&dsym);
+                run_tool(cmd, "dsymutil", binary)?;
+            }
+            symbols.push(dsym);
+        }
+        "PE" if !has_debug => {
+            // MSVC keeps the debug info in a PDB next to the executable
+            let pdb = codeview(&bytes)
+                .map(|(_, pdb)| binary.with_file_name(pdb))
+                .filter(|pdb| pdb.is_file());
+            symbols.extend(pdb);
+        }
+        "ELF" | "PE" if has_debug => {
+            let Some(objcopy) = objcopy else {
+                helpers::tip("Install the target's cross binutils, or: rustup component add llvm-tools-preview");
+                return Err(Error::Build(format!(
+                    "No objcopy available to split the debug info of {name} for {triple}"
+                )));
+            };
+            let debug = sibling(binary, "debug");
+            let mut cmd = objcopy.command();
+            cmd.arg("--only-keep-debug").arg(binary).arg(&debug);
+            run_tool(cmd, &objcopy.display(), binary)?;
+            let mut cmd = objcopy.command();
+            cmd.arg("--strip-debug")
+                .arg(format!("--add-gnu-debuglink={}", debug.display()))
+                .arg(binary);
+            run_tool(cmd, &objcopy.display(), binary)?;
+            symbols.push(debug);
+        }
+        _ => {}
+    }
+    symbols.extend(Some(binary.with_extension("dwp")).filter(|dwp| dwp.is_file()));
+    if symbols.is_empty() {
+        helpers::warning(format!(
+            "{name} has no debug info to split; set debug = true under [profile.release] in Cargo.toml"
+        ));
+        return Ok(());
+    }
+
+    // Binaries without a build ID are archived by a hash of their contents
+    let id = build_id(&bytes).unwrap_or_else(|| {
+        crate::cache::sha256_file(binary)
+            .map_or_else(|_| "unknown".to_string(), |hash| hash[..32].to_string())
+    });
+    let archive = target_dir().join(SYMBOLS_DIR).join(triple);
+    for symbol in &symbols {
+        archive_symbols(symbol, &archive, &id)?;
+    }
+    helpers::success(format!(
+        "Split debug info of {name} (build ID {id}) into {}",
+        archive.display()
+    ));
+    Ok(())
+}
+
+/// Copy a symbol file or `.dSYM` bundle to `<archive>/<file>/<id>/<file>`
+fn archive_symbols(symbols: &Path, archive: &Path, id: &str) -> Result<PathBuf> {
+    let name = symbols.file_name().unwrap_or_default();
+    let dest = archive.join(name).join(id).join(name);
+    if dest.exists() {
+        if dest.is_dir() {
+            std::fs::remove_dir_all(&dest)?;
+        } else {
+            std::fs::remove_file(&dest)?;
+        }
+    }
+    if symbols.is_dir() {
+        copy_dir(symbols, &dest)?;
+    } else {
+        std::fs::create_dir_all(dest.parent().unwrap_or(archive))?;
+        std::fs::copy(symbols, &dest)?;
+    }
+    Ok(dest)
+}
+
+/// `app` → `app.<suffix>`, keeping any extension: `app.exe.<suffix>`
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A 64-bit little-endian ELF file with one `PT_NOTE` segment
+    fn elf_with_note(kind: u32, desc: &[u8]) -> Vec<u8> {
+        let mut note = Vec::new();
+        for field in [4, u32::try_from(desc.len()).unwrap(), kind] {
+            note.extend_from_slice(&field.to_le_bytes());
+        }
+        note.extend_from_slice(b"GNU\0");
+        note.extend_from_slice(desc);
+
+        let mut bytes = vec![0u8; 64 + 56];
+        bytes[..4].copy_from_slice(b"\x7fELF");
+        bytes[4] = 2;
+        bytes[5] = 1;
+        bytes[0x20..0x28].copy_from_slice(&64u64.to_le_bytes());
+        bytes[0x36..0x38].copy_from_slice(&56u16.to_le_bytes());
+        bytes[0x38..0x3A].copy_from_slice(&1u16.to_le_bytes());
+        let ph = 64;
+        bytes[ph..ph + 4].copy_from_slice(&PT_NOTE.to_le_bytes());
+        bytes[ph + 8..ph + 16].copy_from_slice(&120u64.to_le_bytes());
+        bytes[ph + 32..ph + 40].copy_from_slice(&(note.len() as u64).to_le_bytes());
+        bytes[ph + 48..ph + 56].copy_from_slice(&4u64.to_le_bytes());
+        bytes.extend(note);
+        bytes
+    }
+
+    #[test]
+    fn test_elf_build_id() {
+        let binary = elf_with_note(NT_GNU_BUILD_ID, &[0xab, 0xcd, 0x01, 0x23]);
+        assert_eq!(build_id(&binary).as_deref(), Some("abcd0123"));
+        // glibc's ABI tag is not a build ID
+        assert_eq!(build_id(&elf_with_note(1, &[0; 16])), None);
+    }
+
+    #[test]
+    fn test_macho_uuid() {
+        let mut bytes = vec![0u8; 32];
+        bytes[..4].copy_from_slice(&[0xcf, 0xfa, 0xed, 0xfe]);
+        bytes[16..20].copy_from_slice(&1u32.to_le_bytes());
+        bytes.extend_from_slice(&LC_UUID.to_le_bytes());
+        bytes.extend_from_slice(&24u32.to_le_bytes());
+        bytes.extend((0..16).map(|b| b * 0x11));
+        assert_eq!(
+            build_id(&bytes).as_deref(),
+            Some("00112233445566778899AABBCCDDEEFF")
+        );
+    }
+
+    #[test]
+    fn test_pe_codeview() {
+        // MZ header, PE32+ optional header, one section holding the debug
+        // directory and its CodeView record
+        let mut bytes = vec![0u8; 0x400];
+        bytes[..2].copy_from_slice(b"MZ");
+        bytes[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        bytes[0x80..0x84].copy_from_slice(b"PE\0\0");
+        let coff = 0x84;
+        bytes[coff + 2..coff + 4].copy_from_slice(&1u16.to_le_bytes());
+        bytes[coff + 16..coff + 18].copy_from_slice(&240u16.to_le_bytes());
+        let optional = coff + 20;
+        bytes[optional..optional + 2].copy_from_slice(&0x20bu16.to_le_bytes());
+        let debug_dir = optional + 112 + 48;
+        bytes[debug_dir..debug_dir + 4].copy_from_slice(&0x1000u32.to_le_bytes());
+        bytes[debug_dir + 4..debug_dir + 8].copy_from_slice(&28u32.to_le_bytes());
+        let sh = optional + 240;
+        bytes[sh + 12..sh + 16].copy_from_slice(&0x1000u32.to_le_bytes());
+        bytes[sh + 16..sh + 20].copy_from_slice(&0x200u32.to_le_bytes());
+        bytes[sh + 20..sh + 24].copy_from_slice(&0x200u32.to_le_bytes());
+
+        let entry = 0x200;
+        bytes[entry + 12..entry + 16].copy_from_slice(&IMAGE_DEBUG_TYPE_CODEVIEW.to_le_bytes());
+        bytes[entry + 24..entry + 28].copy_from_slice(&0x240u32.to_le_bytes());
+        let record = 0x240;
+        bytes[record..record + 4].copy_from_slice(b"RSDS");
+        bytes[record + 4..record + 8].copy_from_slice(&0x1234_5678u32.to_le_bytes());
+        bytes[record + 8..record + 10].copy_from_slice(&0x9abcu16.to_le_bytes());
+        bytes[record + 10..record + 12].copy_from_slice(&0xdef0u16.to_le_bytes());
+        bytes[record + 12..record + 20].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        bytes[record + 20..record + 24].copy_from_slice(&3u32.to_le_bytes());
+        let path = b"C:\\build\\target\\release\\deps\\my_app.pdb\0";
+        bytes[record + 24..record + 24 + path.len()].copy_from_slice(path);
+
+        assert_eq!(
+            codeview(&bytes),
+            Some((
+                "123456789ABCDEF001020304050607083".to_string(),
+                "my_app.pdb".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_profile_debug_env() {
+        let temp = TempDir::new().unwrap();
+        let manifest = temp.path().join("Cargo.toml");
+        std::fs::write(&manifest, "[package]\nname = \"app\"\n").unwrap();
+        assert_eq!(
+            profile_debug_env(true, &manifest),
+            Some((
+                "CARGO_PROFILE_RELEASE_DEBUG".to_string(),
+                "true".to_string()
+            ))
+        );
+
+        std::fs::write(
+            &manifest,
+            "[profile.release]\ndebug = \"line-tables-only\"\n",
+        )
+        .unwrap();
+        assert_eq!(profile_debug_env(true, &manifest), None);
+        assert!(profile_debug_env(false, &manifest).is_some());
+    }
+
+    #[test]
+    fn test_archive_symbols() {
+        let temp = TempDir::new().unwrap();
+        let debug = temp.path().join("app.debug");
+        std::fs::write(&debug, "dwarf").unwrap();
+        let archive = temp.path().join("symbols/x86_64-unknown-linux-gnu");
+
+        let dest = archive_symbols(&debug, &archive, "abcd").unwrap();
+        assert_eq!(dest, archive.join("app.debug/abcd/app.debug"));
+        assert_eq!(std::fs::read_to_string(dest).unwrap(), "dwarf");
+    }
+}
@@ -0,0 +1,317 @@
+//! Copying a build to a remote machine
+//!
+//! `xcargo deploy` builds a target and copies its binaries to a host reachable
+//! over SSH, such as a Raspberry Pi on the local network, with `scp` or
+//! `rsync`. When a systemd service runs the binary, it is stopped before the
+//! copy (a running executable cannot be overwritten) and started again
+//! afterwards; a `restart` command is run on the host after the copy.
+//!
+//! Hosts, paths and commands are configured under `[deploy]`, or per target
+//! under `[deploy."<triple>"]`.
+
+use crate::config::DeployMethod;
+use crate::error::{Error, Result};
+use crate::output::{helpers, log};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::executor::Builder;
+use super::options::BuildOptions;
+
+/// Where and how to deploy a build
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeployRequest {
+    /// SSH destination, e.g. "pi@192.168.1.10"
+    pub host: String,
+
+    /// Directory on the host the binaries are copied into; the login
+    /// directory when unset
+    pub path: Option<String>,
+
+    /// How the binaries are copied
+    pub method: DeployMethod,
+
+    /// systemd service restarted around the copy
+    pub service: Option<String>,
+
+    /// Command run on the host after the copy
+    pub restart: Option<String>,
+
+    /// Binary to copy; every binary of the build when unset
+    pub bin: Option<String>,
+
+    /// Build the target before copying
+    pub build: bool,
+
+    /// Print the commands copying the latest build instead of building and
+    /// running them
+    pub dry_run: bool,
+}
+
+impl DeployRequest {
+    /// `scp` or `rsync` invocation copying `files` to the host
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::PathBuf;
+    /// use xcargo::build::DeployRequest;
+    /// use xcargo::config::DeployMethod;
+    ///
+    /// let request = DeployRequest {
+    ///     host: "pi@192.168.1.10".to_string(),
+    ///     path: Some("/opt/app".to_string()),
+    ///     method: DeployMethod::Scp,
+    ///     service: None,
+    ///     restart: None,
+    ///     bin: None,
+    ///     build: true,
+    ///     dry_run: false,
+    /// };
+    /// assert_eq!(
+    ///     request.copy_args(&[PathBuf::from("target/app")]),
+    ///     ["scp", "-p", "target/app", "pi@192.168.1.10:/opt/app"]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn copy_args(&self, files: &[PathBuf]) -> Vec<String> {
+        let mut args = match self.method {
+            DeployMethod::Scp => vec!["scp".to_string(), "-p".to_string()],
+            DeployMethod::Rsync => vec!["rsync".to_string(), "-az".to_string()],
+        };
+        args.extend(files.iter().map(|file| file.display().to_string()));
+        args.push(format!(
+            "{}:{}",
+            self.host,
+            self.path.as_deref().unwrap_or("")
+        ));
+        args
+    }
+
+    /// `ssh` invocation running `systemctl <action>` for the service, if
+    /// there is one
+    #[must_use]
+    pub fn systemctl_args(&self, action: &str) -> Option<Vec<String>> {
+        let service = self.service.as_ref()?;
+        Some(
+            ["ssh", &self.host, "sudo", "systemctl", action, service]
+                .map(str::to_string)
+                .to_vec(),
+        )
+    }
+
+    /// `ssh` invocation running the restart command, if there is one
+    #[must_use]
+    pub fn restart_args(&self) -> Option<Vec<String>> {
+        let restart = self.restart.as_ref()?;
+        Some(vec!["ssh".to_string(), self.host.clone(), restart.clone()])
+    }
+}
+
+impl Builder {
+    /// Build a target and copy its binaries to a remote host
+    ///
+    /// `options.target` selects the target and `options.release` the
+    /// profile. Returns the local paths of the copied binaries.
+    ///
+    /// # Errors
+    /// Returns an error if no target is given, the build fails, there are no
+    /// binaries to copy, or copying or a command on the host fails
+    pub fn deploy(&self, request: &DeployRequest, options: &BuildOptions) -> Result<Vec<PathBuf>> {
+        let Some(target) = options.target.as_deref() else {
+            return Err(Error::Config(
+                "xcargo deploy needs a target, e.g. --target aarch64-unknown-linux-gnu, or deploy.target in xcargo.toml"
+                    .to_string(),
+            ));
+        };
+        if request.build && !request.dry_run {
+            self.build(options)?;
+        }
+
+        let profile = if options.release { "release" } else { "debug" };
+        let binaries = select_binaries(
+            &self.artifact_paths(target, profile),
+            request.bin.as_deref(),
+        );
+        if binaries.is_empty() {
+            let what = request
+                .bin
+                .as_ref()
+                .map_or_else(|| "binaries".to_string(), |bin| format!("binary '{bin}'"));
+            return Err(Error::Build(format!(
+                "No {what} in the {profile} build of {target}; build it with 'xcargo build --target {target}{}'",
+                if options.release { " --release" } else { "" }
+            )));
+        }
+
+        helpers::section(format!("Deploying to {}", request.host));
+        let stop = request.systemctl_args("stop");
+        let copy = request.copy_args(&binaries);
+        let start = request.systemctl_args("start");
+        let restart = request.restart_args();
+        if request.dry_run {
+            let steps = stop.iter().chain([&copy]).chain(&start).chain(&restart);
+            for args in steps {
+                println!("{}", log::command_line(&command(args)));
+            }
+            return Ok(binaries);
+        }
+
+        if let Some(stop) = &stop {
+            run_remote(stop)?;
+        }
+        let copied = run_remote(&copy);
+        // Bring the service back even if the copy failed
+        if let Some(start) = &start {
+            run_remote(start)?;
+        }
+        copied?;
+        if let Some(restart) = &restart {
+            run_remote(restart)?;
+        }
+
+        for binary in &binaries {
+            helpers::success(format!(
+                "Copied {} to {}:{}",
+                binary.display(),
+                request.host,
+                request.path.as_deref().unwrap_or("~")
+            ));
+        }
+        if let Some(service) = &request.service {
+            helpers::success(format!("Restarted {service}"));
+        }
+        if let Some(restart) = &request.restart {
+            helpers::success(format!("Ran '{restart}' on {}", request.host));
+        }
+        Ok(binaries)
+    }
+}
+
+fn command(args: &[String]) -> Command {
+    let mut cmd = Command::new(&args[0]);
+    cmd.args(&args[1..]);
+    cmd
+}
+
+/// Run `scp`, `rsync` or `ssh`, which print their own errors and prompts
+fn run_remote(args: &[String]) -> Result<()> {
+    let mut cmd = command(args);
+    log::command(&cmd);
+    let status = cmd.status().map_err(|e| {
+        let install = match args[0].as_str() {
+            "rsync" => "Install rsync on this machine and the host",
+            _ => "Install an OpenSSH client",
+        };
+        Error::Build(format!(
+            "Failed to run {}: {e}. {install} to deploy",
+            args[0]
+        ))
+    })?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Build(format!(
+            "{} failed ({status})",
+            log::command_line(&cmd)
+        )))
+    }
+}
+
+/// Executables among a build's artifacts, or the one named `bin`
+pub(super) fn select_binaries(artifacts: &[PathBuf], bin: Option<&str>) -> Vec<PathBuf> {
+    artifacts
+        .iter()
+        .filter(|path| match path.extension() {
+            None => true,
+            Some(ext) => ext == "exe",
+        })
+        .filter(|path| bin.map_or(true, |bin| file_stem(path) == Some(bin)))
+        .cloned()
+        .collect()
+}
+
+fn file_stem(path: &Path) -> Option<&str> {
+    path.file_stem().and_then(|stem| stem.to_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(service: Option<&str>) -> DeployRequest {
+        DeployRequest {
+            host: "pi@raspberrypi.local".to_string(),
+            path: None,
+            method: DeployMethod::Scp,
+            service: service.map(str::to_string),
+            restart: None,
+            bin: None,
+            build: true,
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn test_deploy_commands() {
+        let request = request(Some("sensor"));
+        assert_eq!(
+            request.copy_args(&[PathBuf::from("a"), PathBuf::from("b")]),
+            ["scp", "-p", "a", "b", "pi@raspberrypi.local:"]
+        );
+        assert_eq!(
+            request.systemctl_args("stop").unwrap(),
+            [
+                "ssh",
+                "pi@raspberrypi.local",
+                "sudo",
+                "systemctl",
+                "stop",
+                "sensor"
+            ]
+        );
+        assert_eq!(self::request(None).systemctl_args("start"), None);
+        assert_eq!(request.restart_args(), None);
+
+        let request = DeployRequest {
+            path: Some("/opt/sensor/".to_string()),
+            method: DeployMethod::Rsync,
+            restart: Some("sudo systemctl restart sensor".to_string()),
+            ..request
+        };
+        assert_eq!(
+            request.copy_args(&[PathBuf::from("a")]),
+            ["rsync", "-az", "a", "pi@raspberrypi.local:/opt/sensor/"]
+        );
+        assert_eq!(
+            request.restart_args().unwrap(),
+            [
+                "ssh",
+                "pi@raspberrypi.local",
+                "sudo systemctl restart sensor"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_binaries() {
+        let artifacts = [
+            PathBuf::from("target/release/sensor"),
+            PathBuf::from("target/release/tool"),
+            PathBuf::from("target/release/libsensor.so"),
+            PathBuf::from("target/release/libsensor.rlib"),
+        ];
+        assert_eq!(
+            select_binaries(&artifacts, None),
+            [
+                PathBuf::from("target/release/sensor"),
+                PathBuf::from("target/release/tool")
+            ]
+        );
+        assert_eq!(
+            select_binaries(&artifacts, Some("tool")),
+            [PathBuf::from("target/release/tool")]
+        );
+        assert!(select_binaries(&artifacts, Some("missing")).is_empty());
+    }
+}
@@ -0,0 +1,294 @@
+//! Compiler diagnostics from cargo's JSON messages
+//!
+//! xcargo runs cargo with `--message-format=json-diagnostic-rendered-ansi`,
+//! prints each diagnostic as rustc rendered it, and keeps a count of the
+//! warnings and errors per target. Multi-target summaries then show the first
+//! few errors of each failed target instead of the whole interleaved output.
+
+use std::fmt;
+
+/// Errors listed per failed target in build summaries
+pub const MAX_REPORTED_ERRORS: usize = 3;
+
+/// Severity of a compiler diagnostic
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiagnosticLevel {
+    /// A compile error (`error`, or an internal compiler error)
+    Error,
+    /// A warning
+    Warning,
+}
+
+/// A warning or error reported by rustc
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// Severity
+    pub level: DiagnosticLevel,
+
+    /// Main message, e.g. "mismatched types"
+    pub message: String,
+
+    /// Error code, e.g. "E0308"
+    pub code: Option<String>,
+
+    /// Primary location as `file:line:column`
+    pub location: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+    /// One line in rustc's style: `error[E0308]: mismatched types (src/main.rs:4:5)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let level = match self.level {
+            DiagnosticLevel::Error => "error",
+            DiagnosticLevel::Warning => "warning",
+        };
+        write!(f, "{level}")?;
+        if let Some(code) = &self.code {
+            write!(f, "[{code}]")?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(location) = &self.location {
+            write!(f, " ({location})")?;
+        }
+        Ok(())
+    }
+}
+
+/// A line of cargo's JSON output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CargoMessage {
+    /// A compiler diagnostic and rustc's rendering of it
+    Diagnostic {
+        /// The diagnostic, or `None` for rustc's closing summaries like
+        /// "aborting due to 2 previous errors"
+        diagnostic: Option<Diagnostic>,
+        /// Rendered text to show the user
+        rendered: Option<String>,
+    },
+    /// Any other cargo message (artifacts, build scripts, build finished)
+    Json(String),
+    /// Output that is not a cargo message, e.g. from a test harness
+    Text(String),
+}
+
+impl CargoMessage {
+    /// Classify one line of cargo's `--message-format=json*` output
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::{CargoMessage, DiagnosticLevel};
+    ///
+    /// let line = r#"{"reason":"compiler-message","message":{"level":"warning","message":"unused variable: `x`","code":{"code":"unused_variables"},"spans":[{"file_name":"src/main.rs","line_start":2,"column_start":9,"is_primary":true}],"rendered":"warning: unused variable: `x`\n"}}"#;
+    /// let CargoMessage::Diagnostic { diagnostic: Some(diagnostic), .. } = CargoMessage::parse(line) else {
+    ///     panic!("not a diagnostic");
+    /// };
+    /// assert_eq!(diagnostic.level, DiagnosticLevel::Warning);
+    /// assert_eq!(diagnostic.location.as_deref(), Some("src/main.rs:2:9"));
+    /// ```
+    #[must_use]
+    pub fn parse(line: &str) -> Self {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+            return Self::Text(line.to_string());
+        };
+        if value["reason"] != "compiler-message" {
+            return Self::Json(line.to_string());
+        }
+
+        let message = &value["message"];
+        Self::Diagnostic {
+            diagnostic: parse_diagnostic(message),
+            rendered: message["rendered"].as_str().map(String::from),
+        }
+    }
+}
+
+/// The diagnostic in a `compiler-message`, skipping notes and rustc's summaries
+fn parse_diagnostic(message: &serde_json::Value) -> Option<Diagnostic> {
+    let level = match message["level"].as_str()? {
+        "error" | "error: internal compiler error" => DiagnosticLevel::Error,
+        "warning" => DiagnosticLevel::Warning,
+        _ => return None,
+    };
+    let text = message["message"].as_str()?;
+    // rustc closes with "aborting due to 2 previous errors" or "3 warnings emitted"
+    let has_spans = message["spans"]
+        .as_array()
+        .is_some_and(|spans| !spans.is_empty());
+    if !has_spans && (text.starts_with("aborting due to") || text.ends_with("emitted")) {
+        return None;
+    }
+
+    let location = message["spans"].as_array().and_then(|spans| {
+        let span = spans
+            .iter()
+            .find(|span| span["is_primary"] == true)
+            .or_else(|| spans.first())?;
+        Some(format!(
+            "{}:{}:{}",
+            span["file_name"].as_str()?,
+            span["line_start"],
+            span["column_start"]
+        ))
+    });
+
+    Some(Diagnostic {
+        level,
+        message: text.to_string(),
+        code: message["code"]["code"].as_str().map(String::from),
+        location,
+    })
+}
+
+/// Error and warning counts for display, e.g. "2 errors, 1 warning"
+#[must_use]
+pub fn format_counts(errors: usize, warnings: usize) -> String {
+    let plural =
+        |count: usize, noun: &str| format!("{count} {noun}{}", if count == 1 { "" } else { "s" });
+    match (errors, warnings) {
+        (0, 0) => "-".to_string(),
+        (0, warnings) => plural(warnings, "warning"),
+        (errors, 0) => plural(errors, "error"),
+        (errors, warnings) => format!(
+            "{}, {}",
+            plural(errors, "error"),
+            plural(warnings, "warning")
+        ),
+    }
+}
+
+/// Warnings and errors reported while building one target
+///
+/// Diagnostics rustc reports more than once, e.g. for a library and its unit
+/// tests, are counted once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    /// Errors, in the order rustc reported them
+    pub errors: Vec<Diagnostic>,
+
+    /// Warnings, in the order rustc reported them
+    pub warnings: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    /// Record a diagnostic unless it was already reported
+    pub fn record(&mut self, diagnostic: Diagnostic) {
+        let list = match diagnostic.level {
+            DiagnosticLevel::Error => &mut self.errors,
+            DiagnosticLevel::Warning => &mut self.warnings,
+        };
+        if !list.contains(&diagnostic) {
+            list.push(diagnostic);
+        }
+    }
+
+    /// Whether nothing was reported
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.warnings.is_empty()
+    }
+
+    /// The first `limit` errors, one per line, and how many were left out
+    ///
+    /// Returns `None` if no errors were reported.
+    #[must_use]
+    pub fn error_summary(&self, limit: usize) -> Option<String> {
+        if self.errors.is_empty() {
+            return None;
+        }
+        let mut lines: Vec<String> = self
+            .errors
+            .iter()
+            .take(limit)
+            .map(ToString::to_string)
+            .collect();
+        if self.errors.len() > limit {
+            lines.push(format!("... and {} more", self.errors.len() - limit));
+        }
+        Some(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(level: &str, text: &str, spans: &str) -> String {
+        format!(
+            r#"{{"reason":"compiler-message","package_id":"path+file:///src/app#0.1.0","message":{{"level":"{level}","message":"{text}","code":null,"spans":{spans},"rendered":"{level}: {text}\n"}}}}"#
+        )
+    }
+
+    fn diagnostic(line: &str) -> Option<Diagnostic> {
+        match CargoMessage::parse(line) {
+            CargoMessage::Diagnostic { diagnostic, .. } => diagnostic,
+            other => panic!("not a diagnostic: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_error_with_code() {
+        let line = r#"{"reason":"compiler-message","message":{"level":"error","message":"mismatched types","code":{"code":"E0308","explanation":null},"spans":[{"file_name":"src/lib.rs","line_start":1,"column_start":1,"is_primary":false},{"file_name":"src/main.rs","line_start":4,"column_start":18,"is_primary":true}],"rendered":"error[E0308]: mismatched types\n"}}"#;
+        let diagnostic = diagnostic(line).unwrap();
+        assert_eq!(
+            diagnostic.to_string(),
+            "error[E0308]: mismatched types (src/main.rs:4:18)"
+        );
+    }
+
+    #[test]
+    fn test_parse_skips_summaries() {
+        assert_eq!(
+            diagnostic(&message("error", "aborting due to 2 previous errors", "[]")),
+            None
+        );
+        assert_eq!(
+            diagnostic(&message("warning", "1 warning emitted", "[]")),
+            None
+        );
+        assert_eq!(diagnostic(&message("note", "see issue #1", "[]")), None);
+        // Errors without a location still count, e.g. from the linker
+        assert!(diagnostic(&message("error", "linking with `cc` failed", "[]")).is_some());
+    }
+
+    #[test]
+    fn test_parse_other_lines() {
+        assert!(matches!(
+            CargoMessage::parse(r#"{"reason":"build-finished","success":true}"#),
+            CargoMessage::Json(_)
+        ));
+        assert_eq!(
+            CargoMessage::parse("test tests::it_works ... ok"),
+            CargoMessage::Text("test tests::it_works ... ok".to_string())
+        );
+    }
+
+    #[test]
+    fn test_format_counts() {
+        assert_eq!(format_counts(0, 0), "-");
+        assert_eq!(format_counts(0, 2), "2 warnings");
+        assert_eq!(format_counts(1, 0), "1 error");
+        assert_eq!(format_counts(5, 1), "5 errors, 1 warning");
+    }
+
+    #[test]
+    fn test_diagnostics_dedup_and_summary() {
+        let mut diagnostics = Diagnostics::default();
+        assert_eq!(diagnostics.error_summary(MAX_REPORTED_ERRORS), None);
+
+        let warning = diagnostic(&message("warning", "unused import", "[]")).unwrap();
+        diagnostics.record(warning.clone());
+        // Reported again for the unit tests of the same crate
+        diagnostics.record(warning);
+        for i in 0..5 {
+            diagnostics.record(diagnostic(&message("error", &format!("error {i}"), "[]")).unwrap());
+        }
+
+        assert_eq!(diagnostics.errors.len(), 5);
+        assert_eq!(diagnostics.warnings.len(), 1);
+        assert_eq!(
+            diagnostics.error_summary(2).unwrap(),
+            "error: error 0\nerror: error 1\n... and 3 more"
+        );
+    }
+}
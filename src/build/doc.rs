@@ -0,0 +1,204 @@
+//! Documentation for several targets
+//!
+//! `cargo doc --target <triple>` documents the items compiled for that
+//! target, so unix-only modules appear in the Linux docs and Windows-only
+//! ones in the Windows docs. `xcargo doc --merge` copies each target's docs
+//! into one directory, `<dir>/<triple>/`, with an index page that switches
+//! between them and keeps the page being read where the browser allows it.
+
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::summary::{target_dir, target_dir_for};
+
+/// Directory under the target directory that merged docs go to by default
+pub const MERGED_DOC_DIR: &str = "doc-merged";
+
+/// Where `cargo doc --target <triple>` writes the docs
+#[must_use]
+pub fn doc_dir(triple: &str) -> PathBuf {
+    target_dir_for(triple).join(triple).join("doc")
+}
+
+/// Default directory for merged docs: `<target_dir>/doc-merged`
+#[must_use]
+pub fn merged_doc_dir() -> PathBuf {
+    target_dir().join(MERGED_DOC_DIR)
+}
+
+/// Docs of several targets merged into one directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergedDocs {
+    /// The index page with the target switcher
+    pub index: PathBuf,
+
+    /// Documented crates per target, for targets that had docs
+    pub crates: BTreeMap<String, Vec<String>>,
+
+    /// Targets without docs in their target directory
+    pub missing: Vec<String>,
+}
+
+/// Copy the docs of `targets` into `out` and write its index page
+///
+/// The docs of each target replace an earlier copy in `out/<triple>/`.
+/// The index page opens the crate of the package in the current directory
+/// when a target documents it.
+///
+/// # Errors
+/// Returns an error if no target has docs, or copying or writing fails
+pub fn merge_docs(targets: &[String], out: &Path) -> Result<MergedDocs> {
+    let mut crates = BTreeMap::new();
+    let mut missing = Vec::new();
+    for triple in targets {
+        let source = doc_dir(triple);
+        let documented = std::fs::read_to_string(source.join("crates.js"))
+            .map(|listing| documented_crates(&listing))
+            .unwrap_or_default();
+        if documented.is_empty() {
+            missing.push(triple.clone());
+            continue;
+        }
+        let dest = out.join(triple);
+        if dest.exists() {
+            std::fs::remove_dir_all(&dest)?;
+        }
+        copy_dir(&source, &dest)?;
+        crates.insert(triple.clone(), documented);
+    }
+    if crates.is_empty() {
+        return Err(Error::Build(format!(
+            "No docs to merge; expected them in {}",
+            doc_dir(&targets[0]).display()
+        )));
+    }
+
+    let index = out.join("index.html");
+    let default_crate = package_crate(Path::new("Cargo.toml"));
+    std::fs::write(&index, index_page(&crates, default_crate.as_deref()))?;
+    Ok(MergedDocs {
+        index,
+        crates,
+        missing,
+    })
+}
+
+/// Crate names listed in rustdoc's `crates.js`:
+/// `window.ALL_CRATES = ["app","serde"];`
+fn documented_crates(listing: &str) -> Vec<String> {
+    let list = listing
+        .find('[')
+        .zip(listing.find(']'))
+        .and_then(|(start, end)| listing.get(start..=end));
+    list.and_then(|list| serde_json::from_str(list).ok())
+        .unwrap_or_default()
+}
+
+/// Crate name of the package in `manifest`, as rustdoc writes it
+fn package_crate(manifest: &Path) -> Option<String> {
+    let manifest: toml::Table = std::fs::read_to_string(manifest).ok()?.parse().ok()?;
+    let name = manifest.get("package")?.get("name")?.as_str()?;
+    Some(name.replace('-', "_"))
+}
+
+pub(super) fn copy_dir(source: &Path, dest: &Path) -> Result<()> {
+    std::fs::create_dir_all(dest)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            copy_dir(&path, &dest.join(entry.file_name()))?;
+        } else {
+            std::fs::copy(&path, dest.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+/// Index page with a target and crate switcher above the selected docs
+///
+/// The selection is kept in the URL fragment (`#<triple>/<crate>`), so
+/// links to a target's docs can be shared.
+fn index_page(crates: &BTreeMap<String, Vec<String>>, default_crate: Option<&str>) -> String {
+    let title = default_crate.unwrap_or("Crate");
+    let crates_json = serde_json::to_string(crates).unwrap_or_else(|_| "{}".to_string());
+    let default_json = serde_json::to_string(&default_crate).unwrap_or_else(|_| "null".to_string());
+    format!(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{title} documentation</title>
+<style>
+body {{ margin: 0; height: 100vh; display: flex; flex-direction: column; font-family: sans-serif; }}
+nav {{ display: flex; gap: 16px; align-items: center; padding: 8px 16px; border-bottom: 1px solid #ccc; }}
+iframe {{ flex: 1; width: 100%; border: 0; }}
+</style>
+</head>
+<body>
+<nav>
+<label>Target <select id="target"></select></label>
+<label>Crate <select id="crate"></select></label>
+</nav>
+<iframe id="docs" title="Documentation"></iframe>
+<script>
+const CRATES = {crates_json};
+const DEFAULT_CRATE = {default_json};
+const targetSelect = document.getElementById("target");
+const crateSelect = document.getElementById("crate");
+const frame = document.getElementById("docs");
+let shownTarget = null;
+
+function options(select, values, selected) {{
+  select.replaceChildren(...values.map((value) => new Option(value, value, false, value === selected)));
+}}
+
+// The page open in the frame, relative to its target's directory
+function currentPage() {{
+  try {{
+    const marker = "/" + shownTarget + "/";
+    const path = frame.contentWindow.location.pathname;
+    const at = path.lastIndexOf(marker);
+    return at < 0 ? null : path.slice(at + marker.length) + frame.contentWindow.location.hash;
+  }} catch (e) {{
+    return null;
+  }}
+}}
+
+function show(target, crate, page) {{
+  const crates = CRATES[target];
+  if (!crates.includes(crate)) {{
+    crate = crates.includes(DEFAULT_CRATE) ? DEFAULT_CRATE : crates[0];
+    page = null;
+  }}
+  options(targetSelect, Object.keys(CRATES), target);
+  options(crateSelect, crates, crate);
+  frame.src = target + "/" + (page || crate + "/index.html");
+  history.replaceState(null, "", "#" + target + "/" + crate);
+  shownTarget = target;
+}}
+
+targetSelect.addEventListener("change", () => show(targetSelect.value, crateSelect.value, currentPage()));
+crateSelect.addEventListener("change", () => show(targetSelect.value, crateSelect.value, null));
+
+const [target, crate] = location.hash.slice(1).split("/");
+show(target in CRATES ? target : Object.keys(CRATES)[0], crate || DEFAULT_CRATE, null);
+</script>
+</body>
+</html>
+"##
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_documented_crates() {
+        let listing = "window.ALL_CRATES = [\"app\",\"serde\"];\n//{\"start\":21}\n";
+        assert_eq!(documented_crates(listing), vec!["app", "serde"]);
+        assert!(documented_crates("").is_empty());
+    }
+}
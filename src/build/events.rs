@@ -0,0 +1,245 @@
+//! Build progress events for editor integrations
+//!
+//! With `--progress-socket <path>`, xcargo connects to a Unix domain socket
+//! (a named pipe on Windows) that an editor extension listens on, and writes
+//! one JSON object per line as builds and targets start and finish.
+
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use serde::Serialize;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+
+use super::summary::{TargetResult, TargetStatus};
+
+/// Progress event, serialized as `{"event": "<name>", ...}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BuildEvent {
+    /// A multi-target build started
+    BuildStarted {
+        /// Cargo subcommand (build, check, test, bench)
+        operation: String,
+        /// Targets in build order
+        targets: Vec<String>,
+    },
+    /// A target started building
+    TargetStarted {
+        /// Target triple
+        target: String,
+    },
+    /// A target finished or was skipped
+    TargetFinished {
+        /// Target triple
+        target: String,
+        /// How the build ended
+        status: TargetStatus,
+        /// Wall time in milliseconds
+        duration_ms: u64,
+        /// Combined artifact size, for successful builds
+        artifact_bytes: Option<u64>,
+    },
+    /// A multi-target build finished
+    BuildFinished {
+        /// Targets that built successfully
+        succeeded: usize,
+        /// Targets that failed
+        failed: usize,
+        /// Targets that were skipped
+        skipped: usize,
+        /// Targets left unbuilt after a failure with `build.fail_fast`
+        not_built: usize,
+    },
+}
+
+impl BuildEvent {
+    /// Event for a finished target
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::{BuildEvent, TargetResult};
+    ///
+    /// let event = BuildEvent::target_finished(&TargetResult::skipped("wasm32-unknown-unknown"));
+    /// assert_eq!(
+    ///     serde_json::to_string(&event).unwrap(),
+    ///     r#"{"event":"target_finished","target":"wasm32-unknown-unknown","status":"skipped","duration_ms":0,"artifact_bytes":null}"#
+    /// );
+    /// ```
+    #[must_use]
+    pub fn target_finished(result: &TargetResult) -> Self {
+        Self::TargetFinished {
+            target: result.target.clone(),
+            status: result.status,
+            duration_ms: u64::try_from(result.duration.as_millis()).unwrap_or(u64::MAX),
+            artifact_bytes: result.artifact_bytes,
+        }
+    }
+
+    /// Event for the end of a multi-target build
+    #[must_use]
+    pub fn build_finished(results: &[TargetResult]) -> Self {
+        let count = |status| results.iter().filter(|r| r.status == status).count();
+        Self::BuildFinished {
+            succeeded: count(TargetStatus::Success),
+            failed: count(TargetStatus::Failed),
+            skipped: count(TargetStatus::Skipped),
+            not_built: count(TargetStatus::NotBuilt),
+        }
+    }
+}
+
+/// Connection events are written to, if any
+static SINK: Mutex<Option<Box<dyn Write + Send>>> = Mutex::new(None);
+
+/// Send all further events to the socket or named pipe at `path`
+pub fn connect(path: &Path) -> Result<()> {
+    let writer = open(path).map_err(|e| {
+        Error::Io(io::Error::new(
+            e.kind(),
+            format!("Cannot connect to progress socket {}: {e}", path.display()),
+        ))
+    })?;
+    *SINK.lock().unwrap_or_else(PoisonError::into_inner) = Some(writer);
+    Ok(())
+}
+
+/// Whether events are being sent anywhere
+#[must_use]
+pub fn is_connected() -> bool {
+    SINK.lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .is_some()
+}
+
+/// Send `event` to the progress socket, if connected
+///
+/// If the listener goes away, a warning is printed once and events are
+/// dropped from then on; the build itself carries on.
+pub fn emit(event: &BuildEvent) {
+    let mut sink = SINK.lock().unwrap_or_else(PoisonError::into_inner);
+    let Some(writer) = sink.as_mut() else {
+        return;
+    };
+    let Ok(mut line) = serde_json::to_string(event) else {
+        return;
+    };
+    line.push('\n');
+
+    if let Err(e) = writer
+        .write_all(line.as_bytes())
+        .and_then(|()| writer.flush())
+    {
+        *sink = None;
+        helpers::warning(format!(
+            "Progress socket closed, no longer sending events: {e}"
+        ));
+    }
+}
+
+#[cfg(unix)]
+fn open(path: &Path) -> io::Result<Box<dyn Write + Send>> {
+    Ok(Box::new(std::os::unix::net::UnixStream::connect(path)?))
+}
+
+#[cfg(windows)]
+fn open(path: &Path) -> io::Result<Box<dyn Write + Send>> {
+    // Named pipes (\\.\pipe\name) are opened like files
+    Ok(Box::new(
+        std::fs::OpenOptions::new().write(true).open(path)?,
+    ))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn open(_path: &Path) -> io::Result<Box<dyn Write + Send>> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "progress sockets are not supported on this platform",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_event_json() {
+        let event = BuildEvent::BuildStarted {
+            operation: "build".to_string(),
+            targets: vec!["x86_64-pc-windows-gnu".to_string()],
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"build_started","operation":"build","targets":["x86_64-pc-windows-gnu"]}"#
+        );
+
+        let result = TargetResult {
+            target: "aarch64-unknown-linux-gnu".to_string(),
+            status: TargetStatus::Success,
+            duration: Duration::from_millis(1500),
+            artifact_bytes: Some(4096),
+            errors: 0,
+            warnings: 0,
+            strategy: None,
+            fallback: false,
+        };
+        let json = serde_json::to_value(BuildEvent::target_finished(&result)).unwrap();
+        assert_eq!(json["status"], "success");
+        assert_eq!(json["duration_ms"], 1500);
+        assert_eq!(json["artifact_bytes"], 4096);
+    }
+
+    #[test]
+    fn test_build_finished_counts() {
+        let results = [
+            TargetResult::skipped("wasm32-unknown-unknown"),
+            TargetResult {
+                status: TargetStatus::Failed,
+                ..TargetResult::skipped("x86_64-pc-windows-gnu")
+            },
+            TargetResult::not_built("aarch64-apple-darwin"),
+        ];
+        assert_eq!(
+            BuildEvent::build_finished(&results),
+            BuildEvent::BuildFinished {
+                succeeded: 0,
+                failed: 1,
+                skipped: 1,
+                not_built: 1
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_events_stream_over_socket() {
+        use std::io::{BufRead, BufReader};
+        use std::os::unix::net::UnixListener;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("progress.sock");
+        let listener = UnixListener::bind(&path).unwrap();
+
+        connect(&path).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        emit(&BuildEvent::TargetStarted {
+            target: "x86_64-unknown-linux-musl".to_string(),
+        });
+        *SINK.lock().unwrap() = None;
+
+        let line = BufReader::new(stream).lines().next().unwrap().unwrap();
+        assert_eq!(
+            line,
+            r#"{"event":"target_started","target":"x86_64-unknown-linux-musl"}"#
+        );
+    }
+
+    #[test]
+    fn test_connect_missing_socket() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let err = connect(&temp.path().join("missing.sock")).unwrap_err();
+        assert!(err.to_string().contains("progress socket"));
+    }
+}
@@ -1,16 +1,63 @@
 //! Build execution and orchestration
 
-use crate::config::Config;
-use crate::error::{Error, Result};
+use crate::config::{Config, TargetCustomConfig};
+use crate::error::{missing_package_suggestions, Error, Result};
+use crate::output::log::{self, Verbosity};
+use crate::output::progress::{BuildProgress, MultiTargetProgress};
+use crate::output::terminal::{self, ColorChoice};
 use crate::output::{helpers, tips};
-use crate::target::Target;
+use crate::plugin::PluginHook;
+use crate::target::{normalize_triple, RequirementDatabase, Target};
 use crate::toolchain::zig::ZigToolchain;
-use crate::toolchain::ToolchainManager;
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::process::Command;
-
-use super::options::{BuildOptions, CargoOperation};
+use crate::toolchain::{
+    bindgen_clang_args, cc_var, find_rust_lld, CcToolchain, LldSupport, ToolchainManager,
+    ToolchainSelection, ToolchainSource, RUST_LLD,
+};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+use super::affected::IncrementalPlan;
+use super::args::CargoArgs;
+use super::artifacts::{self, ARTIFACTS_FILE};
+use super::attest::{self, BuildHost, Provenance};
+use super::bench::{parse_bench_output, BenchResult, BenchTable};
+use super::cancel::CargoProcess;
+use super::cargo_config;
+use super::cargo_progress::StderrTail;
+use super::collect::{collect_tests, parse_test_artifacts};
+use super::debuginfo;
+use super::diagnostics::{CargoMessage, Diagnostics, MAX_REPORTED_ERRORS};
+use super::doc;
+use super::events::{self, BuildEvent};
+use super::hooks::{run_hooks, HookContext};
+#[cfg(feature = "container")]
+use super::install::{mount_install_root, CONTAINER_INSTALL_ROOT};
+#[cfg(feature = "container")]
+use super::libtest::{parse_test_output, TestCase};
+use super::linkage;
+use super::lock::{lock_path, LockFile, LockedTarget, LOCK_FILE};
+use super::options::{BuildOptions, BuildStrategy, CargoOperation};
+use super::postprocess::post_process;
+use super::report::BuildReport;
+use super::runner::{events_path, read_events, RunnerPolicy, RunnerReport, RUNNER_EVENTS_ENV};
+use super::rustflags;
+use super::sign;
+use super::stats::{BuildRecord, STATS_FILE};
+use super::summary::{
+    render_summary, target_dir, target_dir_for, use_target_dir_config, BuildHistory, TargetResult,
+    TargetStatus, HISTORY_FILE,
+};
+use super::target_output::TargetOutput;
+use super::wasm;
+
+/// Where a target directory outside the project is mounted in container builds
+#[cfg(feature = "container")]
+const CONTAINER_TARGET_DIR: &str = "/xcargo-target";
 
 /// Build executor
 pub struct Builder {
@@ -22,6 +69,15 @@ pub struct Builder {
 
     /// Zig toolchain (if available)
     zig_toolchain: Option<ZigToolchain>,
+
+    /// Token that kills cargo when cancelled (async builds)
+    cancel: Option<CancellationToken>,
+
+    /// Slots shared by the builder's async builds
+    concurrency: Option<Arc<Semaphore>>,
+
+    /// Display shared with the other targets of a parallel build
+    multi_progress: Option<MultiTargetProgress>,
 }
 
 impl Builder {
@@ -39,30 +95,94 @@ impl Builder {
     /// ```
     pub fn new() -> Result<Self> {
         let toolchain_manager = ToolchainManager::new()?;
-        let config = Config::discover()?.map(|(c, _)| c).unwrap_or_default();
+        let config = Config::load()?;
 
         // Try to detect Zig for cross-compilation
-        let zig_toolchain = ZigToolchain::detect().ok().flatten();
+        let zig_toolchain = Self::find_zig(&config);
 
         Ok(Self {
             toolchain_manager,
             config,
             zig_toolchain,
+            cancel: None,
+            concurrency: None,
+            multi_progress: None,
         })
     }
 
     /// Create a builder with a specific configuration
     pub fn with_config(config: Config) -> Result<Self> {
         let toolchain_manager = ToolchainManager::new()?;
-        let zig_toolchain = ZigToolchain::detect().ok().flatten();
+        let zig_toolchain = Self::find_zig(&config);
+        use_target_dir_config(&config.build);
 
         Ok(Self {
             toolchain_manager,
             config,
             zig_toolchain,
+            cancel: None,
+            concurrency: None,
+            multi_progress: None,
+        })
+    }
+
+    /// Kill cargo when `cancel` is cancelled
+    pub(super) fn with_cancellation(self, cancel: CancellationToken) -> Self {
+        Self {
+            cancel: Some(cancel),
+            ..self
+        }
+    }
+
+    /// Share `slots` between the builder's async builds
+    pub(super) fn with_concurrency(self, slots: Arc<Semaphore>) -> Self {
+        Self {
+            concurrency: Some(slots),
+            ..self
+        }
+    }
+
+    /// Slots limiting the builder's async builds, if limited
+    pub(super) fn concurrency(&self) -> Option<&Arc<Semaphore>> {
+        self.concurrency.as_ref()
+    }
+
+    /// How cargo runs for `target`
+    fn cargo_process<'a>(&'a self, target: &'a str) -> CargoProcess<'a> {
+        CargoProcess::new(self.cancel.as_ref(), target)
+    }
+
+    /// Show each target's progress below the other targets' in `progress`
+    pub(super) fn with_multi_progress(self, progress: MultiTargetProgress) -> Self {
+        Self {
+            multi_progress: Some(progress),
+            ..self
+        }
+    }
+
+    /// The project's Zig, installing a pinned release; builds go on without Zig if that fails
+    fn find_zig(config: &Config) -> Option<ZigToolchain> {
+        ZigToolchain::for_config(config).unwrap_or_else(|e| {
+            helpers::warning(format!("{e}; building without Zig"));
+            None
         })
     }
 
+    /// Configuration the builder was created with
+    pub(super) fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Toolchain manager the builder runs cargo through
+    pub(super) fn toolchain_manager(&self) -> &ToolchainManager {
+        &self.toolchain_manager
+    }
+
+    /// Zig found for cross-compilation, if any
+    pub(super) fn zig(&self) -> Option<&ZigToolchain> {
+        self.zig_toolchain.as_ref()
+    }
+
     /// Check if a Cargo.toml exists in current directory or parent directories
     fn has_cargo_toml() -> bool {
         let mut current_dir = std::env::current_dir().ok();
@@ -98,10 +218,95 @@ impl Builder {
     /// # }
     /// ```
     pub fn build(&self, options: &BuildOptions) -> Result<()> {
+        self.build_with_report(options).map(|_| ())
+    }
+
+    /// Run benchmarks for a single target and collect their results
+    ///
+    /// Benchmarks for targets the host cannot execute are only compiled
+    /// unless a runner is configured for the target.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xcargo::build::{Builder, BuildOptions};
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let builder = Builder::new()?;
+    /// let options = BuildOptions {
+    ///     target: Some("aarch64-unknown-linux-gnu".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// for result in builder.bench(&options)? {
+    ///     println!("{}: {} ns/iter", result.name, result.ns_per_iter);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bench(&self, options: &BuildOptions) -> Result<Vec<BenchResult>> {
+        let options = BuildOptions {
+            operation: CargoOperation::Bench,
+            ..options.clone()
+        };
+        self.execute(&options, &mut BuildReport::default())
+    }
+
+    /// Run the cargo operation, reporting it to the progress socket and the
+    /// build statistics, and recording the target, strategy, and rustc's
+    /// diagnostics in `report`
+    pub(super) fn execute(
+        &self,
+        options: &BuildOptions,
+        report: &mut BuildReport,
+    ) -> Result<Vec<BenchResult>> {
+        let target = options
+            .target
+            .clone()
+            .or_else(|| self.config.targets.default.first().cloned())
+            .or_else(|| Target::detect_host().ok().map(|host| host.triple))
+            .unwrap_or_default();
+        events::emit(&BuildEvent::TargetStarted {
+            target: target.clone(),
+        });
+
+        let started = Instant::now();
+        let outcome = self.execute_with_fallback(options, report);
+        let result = TargetResult::measure(&target, outcome.is_ok(), started.elapsed(), options);
+        events::emit(&BuildEvent::target_finished(&result));
+        if options.operation != CargoOperation::Install {
+            Self::record_build(&result.with_strategy(report), options);
+        }
+        outcome
+    }
+
+    /// Add a finished target to the build statistics in [`STATS_FILE`]
+    fn record_build(result: &TargetResult, options: &BuildOptions) {
+        let record = BuildRecord::new(result, options.operation.as_str(), options.release);
+        if let Err(e) = record.append(Path::new(STATS_FILE)) {
+            log::write("Stats", &format!("Could not update {STATS_FILE}: {e}"));
+        }
+    }
+
+    /// Run the cargo operation, returning benchmark results for `cargo bench`
+    ///
+    /// With a `forced` strategy, the build fails unless the target can be
+    /// built with it, instead of choosing one.
+    pub(super) fn execute_target(
+        &self,
+        options: &BuildOptions,
+        report: &mut BuildReport,
+        forced: Option<BuildStrategy>,
+    ) -> Result<Vec<BenchResult>> {
         helpers::section(format!("xcargo {}", options.operation.as_str()));
 
+        // Validate passthrough arguments before doing any work
+        let cargo_args = CargoArgs::parse(&options.cargo_args)?;
+
+        // cargo install builds a crate from elsewhere, not the current project
+        let installing = options.operation == CargoOperation::Install;
+
         // Check for Cargo.toml early to provide helpful error
-        if !Self::has_cargo_toml() {
+        if !installing && !Self::has_cargo_toml() {
             helpers::error("No Cargo.toml found in current directory or parent directories");
             helpers::tip("Initialize a new Rust project with: cargo init");
             helpers::tip("Or navigate to an existing Rust project directory");
@@ -124,43 +329,166 @@ impl Builder {
             host.triple
         };
 
+        // Determine toolchain: --toolchain, xcargo.toml, rust-toolchain.toml, rustup default
+        let selection = self.toolchain_manager.resolve_toolchain(
+            options.toolchain.as_deref(),
+            self.config.toolchain.channel.as_deref(),
+        )?;
+        if let Some(conflict) = &selection.conflict {
+            helpers::warning(conflict);
+        }
+        if options.verbose {
+            helpers::info(format!(
+                "Using toolchain {} (from {})",
+                selection.name, selection.source
+            ));
+        }
+
+        // Build with the versions locked in xcargo.lock until `xcargo update-lock`;
+        // the lock describes the project's builds, not installed crates
+        let locked = if installing || !options.lock {
+            None
+        } else {
+            LockFile::load(&lock_path())?.and_then(|mut lock| lock.targets.remove(&target_triple))
+        };
+        let (toolchain, toolchain_override, lock_warning) =
+            self.locked_toolchain(&selection, locked.as_ref())?;
+        if let Some(warning) = lock_warning {
+            helpers::warning(warning);
+        } else if options.verbose && toolchain != selection.name {
+            helpers::info(format!("Using Rust {toolchain} from {LOCK_FILE}"));
+        }
+        let rust_release = self.toolchain_manager.rustc_release(&toolchain);
+
+        // Translate renamed targets (wasm32-wasi/wasm32-wasip1) for this toolchain
+        let normalized = normalize_triple(&target_triple, rust_release.as_deref());
+        if let Some(warning) = &normalized.warning {
+            helpers::warning(warning);
+        }
+
         // Parse target
-        let target = Target::from_triple(&target_triple)?;
+        let target = Target::from_triple(&normalized.triple)?;
+        report.target.clone_from(&target.triple);
+        if target.is_wasm() {
+            wasm::check_wasm_options(&target, options)?;
+        }
+        if let Some(glibc) = &target.glibc {
+            if options.use_container || options.use_zig == Some(false) {
+                return Err(Error::Config(format!(
+                    "{target_triple} targets glibc {glibc}, which needs a Zig build; drop --container and --no-zig"
+                )));
+            }
+        }
         helpers::progress(format!(
             "{} for target: {}",
             options.operation.description(),
-            target.triple
+            target.versioned_triple()
         ));
+        if let Some(scope) = cargo_args.scope_description() {
+            helpers::info(format!("Scope: {scope}"));
+        }
+        if let Some(selection) = options.selection.description() {
+            helpers::info(format!("Selected: {selection}"));
+        }
+        self.run_build_hooks(PluginHook::PreBuild, &target, options)?;
+
+        // Link with the toolchain's rust-lld before falling back to a container;
+        // a specific glibc version can only be linked against with Zig
+        let rust_lld = match forced {
+            _ if target.glibc.is_some() => None,
+            None | Some(BuildStrategy::RustLld) => {
+                self.rust_lld_fallback(&target, &toolchain, options)?
+            }
+            Some(_) => None,
+        };
 
         // Check if we should use container build
-        let should_use_container =
-            options.use_container || self.should_use_container_for_target(&target)?;
+        let should_use_container = rust_lld.is_none()
+            && target.glibc.is_none()
+            && match forced {
+                Some(strategy) => strategy == BuildStrategy::Container,
+                None => options.use_container || self.should_use_container_for_target(&target)?,
+            };
 
         if should_use_container {
-            return self.build_with_container(&target, options);
+            Self::check_forced_strategy(forced, BuildStrategy::Container, &target)?;
+            self.check_release_policy(BuildStrategy::Container, options)?;
+            report.strategy = Some(BuildStrategy::Container);
+            self.cargo_process(&target.triple).check()?;
+            Self::warn_locked_strategy(locked.as_ref(), BuildStrategy::Container);
+            let entry = self.build_with_container(&target, options, locked.as_ref())?;
+            if !installing && options.lock {
+                Self::record_lock(&target_triple, locked.as_ref(), entry.clone());
+            }
+            self.post_process_target(&target, &toolchain, options)?;
+            self.sign_target(&target, options)?;
+            let rustflags = rustflags::configured_rustflags(&self.config, &target.triple);
+            self.attest_target(&target, None, entry, rustflags, &[], options)?;
+            self.run_build_hooks(PluginHook::PostBuild, &target, options)?;
+            return Ok(Vec::new());
         }
 
         // Check if Zig can handle this cross-compilation
-        let zig_env = self.try_zig_cross_compilation(&target, options)?;
+        let zig_env = if rust_lld.is_some() || forced.is_some_and(|s| s != BuildStrategy::Zig) {
+            None
+        } else {
+            self.try_zig_cross_compilation(&target, options)?
+        };
         let using_zig = zig_env.is_some();
 
-        // Determine toolchain
-        let toolchain = if let Some(tc) = &options.toolchain {
-            tc.clone()
+        let strategy = if using_zig {
+            BuildStrategy::Zig
+        } else if rust_lld.is_some() {
+            BuildStrategy::RustLld
         } else {
-            "stable".to_string()
+            BuildStrategy::Native
         };
+        Self::check_forced_strategy(forced, strategy, &target)?;
+        self.check_release_policy(strategy, options)?;
+        Self::warn_locked_strategy(locked.as_ref(), strategy);
+        report.strategy = Some(strategy);
+
+        let zig_version = self
+            .zig_toolchain
+            .as_ref()
+            .filter(|_| using_zig)
+            .map(|zig| zig.version().to_string());
+        if let (Some(locked_zig), Some(zig_version)) =
+            (locked.as_ref().and_then(|l| l.zig.as_deref()), &zig_version)
+        {
+            if locked_zig != zig_version {
+                return Err(Error::Toolchain(format!(
+                    "{LOCK_FILE} locks Zig {locked_zig} for {target_triple}, but Zig {zig_version} is installed. \
+                     Install Zig {locked_zig}, or run `xcargo update-lock {target_triple}` to lock Zig {zig_version}"
+                )));
+            }
+        }
 
-        // Ensure target is installed
+        // Components requested for this target, plus rust-src for -Z build-std
+        let mut components = self
+            .config
+            .get_target_config(&target.triple)
+            .and_then(|tc| tc.components.clone())
+            .unwrap_or_default();
+        if cargo_args.uses_build_std() && !components.iter().any(|c| c == "rust-src") {
+            components.push("rust-src".to_string());
+        }
+
+        // Ensure target and components are installed
         helpers::progress("Checking toolchain and target...".to_string());
-        self.toolchain_manager.prepare_target(&toolchain, &target)?;
+        self.toolchain_manager.prepare_target_with_components(
+            &toolchain,
+            &target,
+            &components,
+            options.assume_yes,
+        )?;
         helpers::success("Toolchain and target ready");
 
         // Show tips based on target
-        if target.os != Target::detect_host()?.os {
+        if !target.is_wasm() && target.os != Target::detect_host()?.os {
             if using_zig {
                 helpers::tip("Cross-compiling using Zig toolchain");
-            } else {
+            } else if rust_lld.is_none() {
                 helpers::tip("Cross-compiling to a different OS");
                 if self.config.container.use_when == "target.os != host.os" {
                     helpers::hint("Container builds not yet implemented - using native toolchain");
@@ -174,6 +502,8 @@ impl Builder {
         // Check linker configuration and availability (skip if using Zig)
         let linker = if using_zig {
             None // Zig provides its own linker
+        } else if rust_lld.is_some() {
+            Some(RUST_LLD.to_string())
         } else if let Some(config) = target_config {
             config.linker.clone()
         } else {
@@ -181,8 +511,8 @@ impl Builder {
             requirements.linker
         };
 
-        // Verify linker exists if specified (and not using Zig)
-        if !using_zig {
+        // Verify linker exists if specified (rustc finds rust-lld in its own sysroot)
+        if !using_zig && rust_lld.is_none() {
             if let Some(ref linker_path) = linker {
                 if let Ok(path) = which::which(linker_path) {
                     if options.verbose {
@@ -233,15 +563,16 @@ impl Builder {
         }
 
         // Build cargo command with progress tracking
-        use crate::output::progress::BuildProgress;
-
-        let progress = match options.operation {
-            super::options::CargoOperation::Build => BuildProgress::compiling(&target.triple),
-            super::options::CargoOperation::Check => BuildProgress::checking(&target.triple),
-            super::options::CargoOperation::Test => BuildProgress::testing(&target.triple),
+        let operation = match options.operation {
+            CargoOperation::Build => "Compiling",
+            operation => operation.description(),
+        };
+        let progress = match &self.multi_progress {
+            Some(multi) => multi.add_build(&target.triple, operation),
+            None => BuildProgress::new(&target.triple, operation),
         };
 
-        let mut cmd = Command::new("cargo");
+        let mut cmd = Command::new(self.toolchain_manager.cargo_program());
 
         // Apply Zig environment if using Zig for cross-compilation
         if let Some(ref env) = zig_env {
@@ -270,6 +601,92 @@ impl Builder {
             }
         }
 
+        // Compilers and flags for C and C++ dependencies built with the cc
+        // crate, unless the environment already sets them
+        let cc_compiler = match &zig_env {
+            Some(env) => env
+                .get(&cc_var("CC", &target.triple))
+                .map(|path| path.display().to_string()),
+            None => linker.clone().or_else(|| target.get_requirements().linker),
+        };
+        for (key, value) in
+            Self::cc_environment(target_config, &target, strategy, cc_compiler.as_deref())
+        {
+            if std::env::var_os(&key).is_some() {
+                continue;
+            }
+            if options.verbose {
+                helpers::info(format!("Setting {key}={value}"));
+            }
+            cmd.env(key, value);
+        }
+
+        // Keep debug info in the build, for split_debuginfo to move it out
+        if let Some((key, value)) = Self::split_debuginfo_env(target_config, options) {
+            if options.verbose {
+                helpers::info(format!("Setting {key}={value}"));
+            }
+            cmd.env(key, value);
+        }
+
+        // The linker xcargo sets wins over the cargo configuration's
+        let linker_var = format!(
+            "CARGO_TARGET_{}_LINKER",
+            target.triple.to_uppercase().replace('-', "_")
+        );
+        let xcargo_linker = cmd
+            .get_envs()
+            .find(|(key, _)| *key == linker_var.as_str())
+            .and_then(|(_, value)| value)
+            .map(|value| value.to_string_lossy().to_string());
+        if let Some(xcargo_linker) = xcargo_linker {
+            let cargo_configs = cargo_config::cargo_configs(&std::env::current_dir()?);
+            if let Some((linker, path)) =
+                cargo_config::configured_linker(&cargo_configs, &target.triple)
+            {
+                if linker != xcargo_linker {
+                    helpers::warning(format!(
+                        "Linking {} with {xcargo_linker} instead of {linker} from {}",
+                        target.triple,
+                        path.display()
+                    ));
+                }
+            }
+        }
+
+        // Run foreign test and bench binaries through the configured runner (e.g. QEMU)
+        let runner_var = format!(
+            "CARGO_TARGET_{}_RUNNER",
+            target.triple.to_uppercase().replace('-', "_")
+        );
+        let runner = match target_config.and_then(|c| c.runner.clone()) {
+            Some(runner) => Some(runner),
+            None => Self::default_runner(&target)?,
+        };
+        if let Some(runner) = &runner {
+            cmd.env(&runner_var, runner);
+            if options.verbose {
+                helpers::info(format!("Setting {runner_var}={runner}"));
+            }
+        }
+
+        // Apply [runners.<triple>] timeouts and retries to each test binary
+        let runner_events = if options.operation == CargoOperation::Test {
+            let runner = runner.clone().or_else(|| std::env::var(&runner_var).ok());
+            if runner.is_none() && target.is_wasm() {
+                return Err(wasm::missing_runner(&target));
+            }
+            self.wrap_test_runner(
+                &mut cmd,
+                &target,
+                &runner_var,
+                runner.as_deref(),
+                options.verbose,
+            )?
+        } else {
+            None
+        };
+
         // Add custom environment variables from target config
         if let Some(config) = target_config {
             for (key, value) in &config.env {
@@ -278,41 +695,100 @@ impl Builder {
                     helpers::info(format!("Setting {key}={value}"));
                 }
             }
+        }
 
-            // Add custom rustflags if specified
-            if let Some(ref rustflags) = config.rustflags {
-                let rustflags_str = rustflags.join(" ");
-                cmd.env("RUSTFLAGS", &rustflags_str);
+        // [build] and target rustflags, then the strategy's, then the
+        // environment's; rust-lld needs its linker flavor, and musl targets
+        // Rust's own C runtime
+        let strategy_flags = rust_lld.map(LldSupport::rustflags).unwrap_or_default();
+        let merged_rustflags =
+            rustflags::merged_rustflags(&self.config, &target.triple, &strategy_flags);
+        if options.operation == CargoOperation::Doc {
+            let rustflags = merged_rustflags
+                .clone()
+                .unwrap_or_else(rustflags::ambient_rustflags);
+            let doc_flags = rustflags::rustdoc_flags(&rustflags);
+            if !doc_flags.is_empty() {
+                cmd.env("CARGO_ENCODED_RUSTDOCFLAGS", rustflags::encode(&doc_flags));
+                cmd.env_remove("RUSTDOCFLAGS");
                 if options.verbose {
-                    helpers::info(format!("Setting RUSTFLAGS={rustflags_str}"));
+                    helpers::info(format!("Setting rustdoc flags: {}", doc_flags.join(" ")));
+                }
+            }
+        }
+        if let Some(rustflags) = merged_rustflags {
+            if rustflags::ambient_rustflags().is_empty() {
+                let cwd = std::env::current_dir()?;
+                if let Some(cargo_config) = rustflags::shadowed_cargo_config(&cwd, &target.triple) {
+                    helpers::warning(format!(
+                        "Cargo ignores the rustflags in {} while xcargo sets its own",
+                        cargo_config.display()
+                    ));
+                    helpers::tip("Move them to build.rustflags in xcargo.toml");
                 }
             }
+            // The encoded form keeps flags with spaces, e.g. paths, in one piece;
+            // the environment's flags are merged in
+            cmd.env("CARGO_ENCODED_RUSTFLAGS", rustflags::encode(&rustflags));
+            cmd.env_remove("RUSTFLAGS");
+            if options.verbose {
+                helpers::info(format!("Setting rustflags: {}", rustflags.join(" ")));
+            }
         }
 
-        // Add toolchain override if specified
-        if options.toolchain.is_some() {
+        // Add toolchain override unless rustup already selects it; a
+        // standalone toolchain has no rustup proxy to pass it to
+        if toolchain_override && self.toolchain_manager.uses_rustup() {
             cmd.arg(format!("+{toolchain}"));
         }
 
         cmd.arg(options.operation.as_str());
 
+        // With build.isolate_targets, each triple has a target directory of its own
+        let triple_target_dir = target_dir_for(&target.triple);
+        if triple_target_dir != target_dir() {
+            cmd.env("CARGO_TARGET_DIR", &triple_target_dir);
+        }
+
         // Add target
         cmd.arg("--target").arg(&target.triple);
 
-        // Add release flag
-        if options.release {
+        // Add release flag; cargo install builds in release mode by default
+        if options.release && !installing {
             cmd.arg("--release");
         }
 
-        // Add verbose flag
-        if options.verbose
+        // Benchmarks the host cannot execute are only compiled
+        if options.operation == CargoOperation::Bench {
+            let has_runner = runner.is_some() || std::env::var_os(&runner_var).is_some();
+            if !has_runner && !Self::host_can_run(&target)? {
+                helpers::warning(format!(
+                    "No runner configured for {}; compiling benchmarks without running them",
+                    target.triple
+                ));
+                helpers::tip(format!(
+                    "Configure one in xcargo.toml: [targets.\"{}\"] runner = \"{}\"",
+                    target.triple,
+                    Self::suggested_runner(&target)
+                ));
+                cmd.arg("--no-run");
+            }
+        }
+
+        // Add verbose flag; -vv also shows build script output, -q quiets cargo
+        let verbose = options.verbose
             || self
                 .config
                 .build
                 .cargo_flags
-                .contains(&"--verbose".to_string())
-        {
+                .contains(&"--verbose".to_string());
+        if verbose {
             cmd.arg("--verbose");
+            if log::enabled(Verbosity::Debug) {
+                cmd.arg("--verbose");
+            }
+        } else if !log::enabled(Verbosity::Normal) {
+            cmd.arg("--quiet");
         }
 
         // Add additional cargo flags from config
@@ -322,36 +798,157 @@ impl Builder {
             }
         }
 
-        // Add additional args from options
-        for arg in &options.cargo_args {
-            cmd.arg(arg);
+        // Limit cargo to build.jobs unless jobs were passed through
+        if let Some(jobs) = self.config.build.jobs.filter(|_| !cargo_args.sets_jobs()) {
+            cmd.arg("--jobs").arg(jobs.to_string());
         }
 
-        if options.verbose {
-            helpers::info(format!("Executing: {cmd:?}"));
+        // Add per-target features from config; they belong to the project's crates
+        let feature_args = if installing {
+            Vec::new()
+        } else {
+            Self::target_feature_args(target_config, &cargo_args)
+        };
+        if options.verbose && !feature_args.is_empty() {
+            helpers::info(format!("Target features: {}", feature_args.join(" ")));
+        }
+        cmd.args(feature_args);
+
+        // Test binaries to collect are listed in cargo's JSON messages
+        let collect_dir = options
+            .collect_tests
+            .as_deref()
+            .filter(|_| options.operation == CargoOperation::Test);
+
+        // Diagnostics are read from cargo's JSON messages unless a format was passed through
+        let read_diagnostics =
+            options.operation != CargoOperation::Bench && !cargo_args.sets_message_format();
+        if read_diagnostics {
+            cmd.arg(Self::diagnostic_message_format());
+        } else if collect_dir.is_some() {
+            cmd.arg("--message-format=json-render-diagnostics");
+        }
+
+        // Add the selected packages and cargo targets, then additional args from options
+        cmd.args(options.selection.to_args());
+        cmd.args(cargo_args.to_args());
+
+        log::command(&cmd);
+
+        // In a parallel build, cargo's output is tagged or grouped by target;
+        // messages in a format passed through are left as cargo prints them
+        let target_output = self
+            .multi_progress
+            .as_ref()
+            .filter(|_| !cargo_args.sets_message_format())
+            .map(|_| {
+                let mode = options.output.unwrap_or(self.config.build.output);
+                TargetOutput::new(&target.triple, mode)
+            });
+
+        // Execute build; benchmark output is captured so results can be compared
+        let mut cargo_messages = String::new();
+        let stderr_tail = StderrTail::default();
+        let process = self
+            .cargo_process(&target.triple)
+            .with_timeout(self.config.build_timeout(&target.triple)?)
+            .with_progress(&progress)
+            .capturing_stderr(&stderr_tail)
+            .with_output(target_output.as_ref());
+        let run = if options.operation == CargoOperation::Bench {
+            Self::run_capturing_bench(&mut cmd, process)
+        } else if read_diagnostics {
+            Self::run_reading_diagnostics(&mut cmd, process, &mut report.diagnostics).map(
+                |(status, messages)| {
+                    cargo_messages = messages;
+                    (status, Vec::new())
+                },
+            )
+        } else if collect_dir.is_some() {
+            process
+                .run_lines(&mut cmd, |line| {
+                    cargo_messages.push_str(&line);
+                    cargo_messages.push('\n');
+                })
+                .map(|status| (status, Vec::new()))
+        } else {
+            process.run(&mut cmd).map(|status| (status, Vec::new()))
+        };
+        // Grouped output is shown once cargo is done, even if it was killed
+        if let Some(output) = &target_output {
+            output.flush(&progress);
         }
+        let (status, bench_results) = run?;
 
-        // Execute build
-        let status = cmd
-            .status()
-            .map_err(|e| Error::Build(format!("Failed to execute cargo: {e}")))?;
+        if let Some(events) = &runner_events {
+            Self::report_runner_events(events);
+        }
 
         if status.success() {
             progress.finish_success();
+            if installing {
+                return Ok(bench_results);
+            }
+            let entry = LockedTarget {
+                strategy: strategy.as_str().to_string(),
+                rust: rust_release,
+                zig: zig_version,
+                ..LockedTarget::default()
+            };
+            if options.lock {
+                Self::record_lock(&target_triple, locked.as_ref(), entry.clone());
+            }
+            if options.operation == CargoOperation::Build {
+                report.artifacts = artifacts::parse_artifacts(&cargo_messages);
+            }
+            if !report.artifacts.is_empty() {
+                if let Err(e) =
+                    artifacts::record_artifacts(&target.triple, options.release, &report.artifacts)
+                {
+                    log::write(
+                        "Artifacts",
+                        &format!("Could not update {ARTIFACTS_FILE}: {e}"),
+                    );
+                }
+            }
+            self.post_process_target(&target, &toolchain, options)?;
+            self.sign_target(&target, options)?;
+            self.attest_target(
+                &target,
+                Some(&toolchain),
+                entry,
+                Self::command_rustflags(&cmd),
+                &report.artifacts,
+                options,
+            )?;
+            self.run_build_hooks(PluginHook::PostBuild, &target, options)?;
+            if let Some(dir) = collect_dir {
+                let (collected_dir, manifest) = collect_tests(
+                    parse_test_artifacts(&cargo_messages),
+                    dir,
+                    &target.triple,
+                    options.release,
+                )?;
+                let count = manifest.tests.len();
+                helpers::success(format!(
+                    "Collected {count} test binar{} in {}",
+                    if count == 1 { "y" } else { "ies" },
+                    collected_dir.display()
+                ));
+                helpers::tip(format!(
+                    "Run them with: xcargo test --from-collected {}",
+                    dir.display()
+                ));
+            }
 
             // Show helpful tips (only for build/test, not check)
-            if options.operation != CargoOperation::Check {
-                if options.release {
-                    helpers::tip(format!(
-                        "Release build artifacts are in target/{}/release/",
-                        target.triple
-                    ));
-                } else {
-                    helpers::tip(format!(
-                        "Debug build artifacts are in target/{}/debug/",
-                        target.triple
-                    ));
-                }
+            if options.operation == CargoOperation::Doc {
+                helpers::tip(format!(
+                    "Documentation is in {}/",
+                    doc::doc_dir(&target.triple).display()
+                ));
+            } else if options.operation != CargoOperation::Check {
+                Self::show_artifact_location(&target, options, &report.artifacts);
 
                 // Additional tips based on target
                 if target.os == "windows" && Target::detect_host()?.os != "windows" {
@@ -368,7 +965,7 @@ impl Builder {
                 }
             }
 
-            Ok(())
+            Ok(bench_results)
         } else {
             progress.finish_error("build failed");
 
@@ -376,68 +973,34 @@ impl Builder {
             if linker.is_none() {
                 let requirements = target.get_requirements();
                 if let Some(suggested_linker) = requirements.linker {
-                    println!();
+                    helpers::line("");
                     helpers::hint("This target requires a cross-compilation linker");
                     helpers::tip(format!("Install the linker: {suggested_linker}"));
                     helpers::tip("Then configure it in xcargo.toml:".to_string());
-                    println!("\n  [targets.\"{}\"]", target.triple);
-                    println!("  linker = \"{suggested_linker}\"");
+                    helpers::line(format!("\n  [targets.\"{}\"]", target.triple));
+                    helpers::line(format!("  linker = \"{suggested_linker}\""));
 
                     if !requirements.tools.is_empty() {
-                        println!();
+                        helpers::line("");
                         helpers::hint(format!(
                             "Additional required tools: {}",
                             requirements.tools.join(", ")
                         ));
                     }
 
-                    // Provide OS-specific installation instructions
-                    let host_os = Target::detect_host()?.os;
-                    println!();
-                    helpers::section("Installation Instructions");
-
-                    match (host_os.as_str(), target.os.as_str()) {
-                        ("macos", "linux") => {
-                            helpers::tip(
-                                "Install via Homebrew: brew install SomeLinuxCrossCompiler"
-                                    .to_string(),
-                            );
-                            helpers::tip(
-                                "Or use a container-based build (coming soon)".to_string(),
-                            );
-                        }
-                        ("macos", "windows") => {
-                            helpers::tip(
-                                "Install via Homebrew: brew install mingw-w64".to_string(),
-                            );
-                            helpers::tip("Then set: [targets.\"x86_64-pc-windows-gnu\"] linker = \"x86_64-w64-mingw32-gcc\"".to_string());
-                        }
-                        ("linux", "windows") => {
-                            helpers::tip(
-                                "Install via package manager: sudo apt install mingw-w64"
-                                    .to_string(),
-                            );
-                            helpers::tip("Then set: [targets.\"x86_64-pc-windows-gnu\"] linker = \"x86_64-w64-mingw32-gcc\"".to_string());
-                        }
-                        ("linux", "macos") => {
-                            helpers::tip(
-                                "macOS cross-compilation from Linux requires osxcross".to_string(),
-                            );
-                            helpers::tip(
-                                "See: https://github.com/tpoechtrager/osxcross".to_string(),
-                            );
-                        }
-                        (_, _) => {
-                            helpers::tip(format!(
-                                "Cross-compiling from {} to {} may require specific toolchains",
-                                host_os, target.os
-                            ));
+                    // Installation instructions for this host from the target database
+                    let instructions = target.get_install_instructions();
+                    if !instructions.is_empty() {
+                        helpers::line("");
+                        helpers::section("Installation Instructions");
+                        for instruction in instructions {
+                            helpers::line(format!("  {instruction}"));
                         }
                     }
                 }
             } else if let Some(ref linker_path) = linker {
                 if which::which(linker_path).is_err() {
-                    println!();
+                    helpers::line("");
                     helpers::hint(format!(
                         "The configured linker '{linker_path}' is not in your PATH"
                     ));
@@ -445,9 +1008,31 @@ impl Builder {
                 }
             }
 
-            println!();
+            // Failures caused by missing system libraries or tools; without
+            // the host, the build's own error is reported without suggestions
+            let missing = Target::detect_host()
+                .map(|host| {
+                    missing_package_suggestions(&stderr_tail.text(), &target.triple, &host.triple)
+                })
+                .unwrap_or_default();
+            if !missing.is_empty() {
+                return Err(Error::BuildFailed {
+                    target: target.triple,
+                    exit_code: status.code(),
+                    suggestion: Some(missing.join("\n")),
+                });
+            }
+
+            helpers::line("");
             helpers::tip("Run with --verbose to see detailed error output".to_string());
 
+            if let Some(errors) = report.diagnostics.error_summary(MAX_REPORTED_ERRORS) {
+                return Err(Error::BuildFailed {
+                    target: target.triple,
+                    exit_code: status.code(),
+                    suggestion: Some(format!("Fix the compile errors first:\n{errors}")),
+                });
+            }
             Err(Error::Build(format!(
                 "{} failed for target {}",
                 options.operation.description(),
@@ -468,84 +1053,735 @@ impl Builder {
             targets.len()
         ));
 
+        // Reject invalid passthrough arguments once instead of failing every target
+        let cargo_args = CargoArgs::parse(&options.cargo_args)?;
+        if let Some(scope) = cargo_args.scope_description() {
+            helpers::info(format!("Scope: {scope}"));
+        }
+
+        let plan = Self::incremental_plan(&cargo_args, options);
+        events::emit(&BuildEvent::BuildStarted {
+            operation: options.operation.as_str().to_string(),
+            targets: targets.to_vec(),
+        });
+
         let mut successes = Vec::new();
         let mut failures = Vec::new();
+        let mut skipped = Vec::new();
+        let mut bench_table = BenchTable::new();
+        let mut results = Vec::new();
+        let fail_fast = options.fail_fast.unwrap_or(self.config.build.fail_fast);
 
         for (idx, target) in targets.iter().enumerate() {
-            println!("\n[{}/{}] Target: {}", idx + 1, targets.len(), target);
-            println!("{}", "─".repeat(50));
+            if fail_fast && !failures.is_empty() {
+                let result = TargetResult::not_built(target);
+                events::emit(&BuildEvent::target_finished(&result));
+                results.push(result);
+                continue;
+            }
+
+            helpers::line(format!(
+                "\n[{}/{}] Target: {}",
+                idx + 1,
+                targets.len(),
+                target
+            ));
+            helpers::rule();
 
             let mut target_options = options.clone();
             target_options.target = Some(target.clone());
 
-            match self.build(&target_options) {
-                Ok(()) => successes.push(target.clone()),
+            if let Some(members) = plan.as_ref().and_then(|p| p.members_for(target)) {
+                if members.is_empty() {
+                    helpers::info(format!(
+                        "No affected workspace members for {target}, skipping"
+                    ));
+                    skipped.push(target.clone());
+                    results.push(TargetResult::skipped(target));
+                    events::emit(&BuildEvent::target_finished(&TargetResult::skipped(target)));
+                    continue;
+                }
+                helpers::info(format!("Affected members: {}", members.join(", ")));
+                target_options.cargo_args = cargo_args.with_packages(&members);
+            }
+
+            let started = Instant::now();
+            let mut report = BuildReport::default();
+            let outcome = self.execute(&target_options, &mut report);
+            results.push(
+                TargetResult::measure(target, outcome.is_ok(), started.elapsed(), options)
+                    .with_diagnostics(&report.diagnostics)
+                    .with_strategy(&report),
+            );
+            match outcome {
+                Ok(bench_results) => {
+                    bench_table.add(target, &bench_results);
+                    successes.push(target.clone());
+                }
                 Err(e) => {
                     helpers::error(format!("Failed to build {target}: {e}"));
-                    failures.push(target.clone());
+                    failures.push((
+                        target.clone(),
+                        report.diagnostics.error_summary(MAX_REPORTED_ERRORS),
+                    ));
                 }
             }
         }
 
-        println!("\n");
+        helpers::line("\n");
         helpers::section("Build Summary");
         helpers::success(format!("{} target(s) built successfully", successes.len()));
+        if !skipped.is_empty() {
+            helpers::info(format!(
+                "{} target(s) skipped (no affected members)",
+                skipped.len()
+            ));
+        }
+        helpers::line("");
+        Self::report_target_summary(&results, options);
+
+        if !bench_table.is_empty() {
+            helpers::line("");
+            helpers::section("Benchmark Comparison");
+            helpers::line(bench_table.render());
+        }
 
         if !failures.is_empty() {
             helpers::error(format!("{} target(s) failed", failures.len()));
-            for target in &failures {
-                helpers::error(format!("  - {target}"));
-            }
-            return Err(Error::Build("Some targets failed to build".to_string()));
+            Self::report_failures(&failures);
+            Self::report_not_built(&results);
+            return Err(Error::TargetsFailed {
+                failed: failures.into_iter().map(|(target, _)| target).collect(),
+                succeeded: successes.len(),
+            });
         }
 
-        helpers::tip(tips::PARALLEL_BUILDS);
+        // Plain output builds one target at a time even with parallel = true
+        if !self.config.build.parallel {
+            helpers::tip(tips::PARALLEL_BUILDS);
+        }
         Ok(())
     }
 
-    /// Try to use Zig for cross-compilation if available and supported
+    /// Print the per-target summary table, record artifact sizes in the
+    /// build history, and report the end of the build to the progress socket
+    pub(super) fn report_target_summary(results: &[TargetResult], options: &BuildOptions) {
+        events::emit(&BuildEvent::build_finished(results));
+
+        let history_path = Path::new(HISTORY_FILE);
+        let mut history = BuildHistory::load(history_path);
+
+        helpers::line(render_summary(results, &history, options.release));
+
+        if results.iter().any(|r| r.artifact_bytes.is_some()) {
+            history.record(results, options.release);
+            if let Err(e) = history.save(history_path) {
+                helpers::warning(format!("Could not update {HISTORY_FILE}: {e}"));
+            }
+        }
+    }
+
+    /// List failed targets with the first compile errors of each
+    pub(super) fn report_failures(failures: &[(String, Option<String>)]) {
+        for (target, errors) in failures {
+            helpers::error(format!("  - {target}"));
+            // Shown with -q too, like the error above
+            for line in errors.iter().flat_map(|errors| errors.lines()) {
+                log::write("Error", line);
+                println!("      {line}");
+            }
+        }
+    }
+
+    /// Mention the targets a fail-fast build stopped before
+    pub(super) fn report_not_built(results: &[TargetResult]) {
+        let not_built: Vec<&str> = results
+            .iter()
+            .filter(|result| result.status == TargetStatus::NotBuilt)
+            .map(|result| result.target.as_str())
+            .collect();
+        if !not_built.is_empty() {
+            helpers::info(format!(
+                "Not built after the failure (--fail-fast): {}",
+                not_built.join(", ")
+            ));
+            helpers::hint("Build every target regardless of failures with --keep-going");
+        }
+    }
+
+    /// Run test binaries through `xcargo __run-test` when `[runners.<triple>]`
+    /// sets a timeout or retries
     ///
-    /// Returns Some(env) if Zig can handle this cross-compilation, None otherwise.
-    /// Respects the `use_zig` option: None = auto, Some(true) = force, Some(false) = disable
-    fn try_zig_cross_compilation(
+    /// Returns the file the runner records timeouts and retries in.
+    fn wrap_test_runner(
         &self,
+        cmd: &mut Command,
         target: &Target,
-        options: &BuildOptions,
-    ) -> Result<Option<HashMap<String, PathBuf>>> {
-        // Check if Zig is explicitly disabled
-        if options.use_zig == Some(false) {
-            if options.verbose {
-                helpers::info("Zig disabled via --no-zig flag");
-            }
+        runner_var: &str,
+        runner: Option<&str>,
+        verbose: bool,
+    ) -> Result<Option<PathBuf>> {
+        let Some(runner_config) = self.config.get_runner_config(&target.triple) else {
+            return Ok(None);
+        };
+        let policy = RunnerPolicy::from_config(runner_config)?;
+        if !policy.is_active() {
+            return Ok(None);
+        }
+
+        // Cargo re-invokes the running executable, which must be the xcargo CLI
+        let xcargo = std::env::current_exe()?;
+        if xcargo.file_stem().map_or(true, |stem| stem != "xcargo") {
+            helpers::warning(format!(
+                "runners.\"{}\" test_timeout and retries only apply when running the xcargo CLI",
+                target.triple
+            ));
             return Ok(None);
         }
 
-        // Check if Zig is explicitly forced
-        let force_zig = options.use_zig == Some(true);
+        let wrapper = policy.wrapper_command(&xcargo, runner);
+        if verbose {
+            helpers::info(format!("Setting {runner_var}={wrapper}"));
+        }
 
-        // Determine if we're cross-compiling to a different OS
+        let events = events_path(&target.triple);
+        let _ = std::fs::remove_file(&events);
+        cmd.env(runner_var, wrapper).env(RUNNER_EVENTS_ENV, &events);
+        Ok(Some(events))
+    }
+
+    /// Report timeouts and retries recorded by the test runner
+    fn report_runner_events(events: &Path) {
+        let report = RunnerReport::from_events(&read_events(events));
+        let _ = std::fs::remove_file(events);
+        if report.is_empty() {
+            return;
+        }
+
+        helpers::section("Test Runner Events");
+        if report.timeouts > 0 {
+            helpers::warning(format!("{} test binary run(s) timed out", report.timeouts));
+        }
+        if report.retries > 0 {
+            helpers::warning(format!("{} failed run(s) retried", report.retries));
+        }
+        if !report.recovered.is_empty() {
+            helpers::warning(format!(
+                "Passed after retry (possibly flaky): {}",
+                report.recovered.join(", ")
+            ));
+        }
+        helpers::hint("These are runner events; only the final run of each test binary counts as a test result");
+    }
+
+    /// Feature arguments configured for a target
+    ///
+    /// `--no-default-features` is left out if it was already passed through,
+    /// since cargo rejects the flag being given twice.
+    fn target_feature_args(
+        target_config: Option<&TargetCustomConfig>,
+        cargo_args: &CargoArgs,
+    ) -> Vec<String> {
+        let Some(config) = target_config else {
+            return Vec::new();
+        };
+
+        let passed_through = cargo_args
+            .passthrough
+            .iter()
+            .take_while(|arg| *arg != "--")
+            .any(|arg| arg == "--no-default-features");
+
+        config
+            .feature_args()
+            .into_iter()
+            .filter(|arg| !(passed_through && arg == "--no-default-features"))
+            .collect()
+    }
+
+    /// Whether binaries built for `target` can be executed on the host
+    pub(super) fn host_can_run(target: &Target) -> Result<bool> {
         let host = Target::detect_host()?;
-        let is_cross_os = target.os != host.os;
+        Ok(target.triple == host.triple || (target.arch == host.arch && target.os == host.os))
+    }
 
-        // For auto mode, only attempt Zig for cross-compilation (different OS)
-        if !force_zig && !is_cross_os {
+    /// Runner for test binaries when none is configured: a WebAssembly
+    /// runtime, or the target database's emulator (e.g. QEMU) on hosts that
+    /// can't execute the target's binaries, if it is installed
+    pub(super) fn default_runner(target: &Target) -> Result<Option<String>> {
+        if target.is_wasm() {
+            return Ok(wasm::default_runner(target));
+        }
+        if Self::host_can_run(target)? {
             return Ok(None);
         }
+        Ok(RequirementDatabase::global()
+            .lookup(&target.triple)
+            .runner
+            .filter(|runner| {
+                runner
+                    .split_whitespace()
+                    .next()
+                    .is_some_and(|program| which::which(program).is_ok())
+            }))
+    }
 
-        // Check if Zig is available and supports this target
-        if let Some(ref zig) = self.zig_toolchain {
-            if zig.supports_target(target) {
-                helpers::info(format!(
+    /// Runner to suggest configuring for `target`, e.g. "qemu-s390x -L /usr/s390x-linux-gnu"
+    pub(super) fn suggested_runner(target: &Target) -> String {
+        RequirementDatabase::global()
+            .lookup(&target.triple)
+            .runner
+            .unwrap_or_else(|| format!("qemu-{}", target.arch))
+    }
+
+    /// `--message-format` that makes cargo report diagnostics as JSON with
+    /// rustc's rendering, in color when colors are on and stderr is a terminal
+    fn diagnostic_message_format() -> &'static str {
+        use std::io::IsTerminal;
+
+        let colors = terminal::colors_enabled()
+            && (std::io::stderr().is_terminal() || terminal::color_choice() == ColorChoice::Always);
+        if colors {
+            "--message-format=json-diagnostic-rendered-ansi"
+        } else {
+            "--message-format=json"
+        }
+    }
+
+    /// Run cargo with JSON messages, printing diagnostics as rustc rendered
+    /// them and recording them in `diagnostics`
+    ///
+    /// Returns cargo's other JSON messages, one per line.
+    fn run_reading_diagnostics(
+        cmd: &mut Command,
+        process: CargoProcess<'_>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<(ExitStatus, String)> {
+        let mut messages = String::new();
+        let status = process.run_lines(cmd, |line| {
+            match CargoMessage::parse(&line) {
+                CargoMessage::Diagnostic {
+                    diagnostic,
+                    rendered,
+                } => {
+                    if let Some(rendered) = rendered {
+                        process.print_stderr(&rendered);
+                        process.capture(&rendered);
+                    }
+                    if let Some(diagnostic) = diagnostic {
+                        diagnostics.record(diagnostic);
+                    }
+                }
+                CargoMessage::Json(message) => {
+                    messages.push_str(&message);
+                    messages.push('\n');
+                }
+                // Test harnesses and `cargo run`-style runners print to stdout
+                CargoMessage::Text(text) => process.print_stdout(&text),
+            }
+        })?;
+
+        Ok((status, messages))
+    }
+
+    /// Run cargo, echoing its stdout while collecting benchmark results
+    fn run_capturing_bench(
+        cmd: &mut Command,
+        process: CargoProcess<'_>,
+    ) -> Result<(ExitStatus, Vec<BenchResult>)> {
+        let mut output = String::new();
+        let status = process.run_lines(cmd, |line| {
+            process.print_stdout(&line);
+            output.push_str(&line);
+            output.push('\n');
+        })?;
+
+        Ok((status, parse_bench_output(&output)))
+    }
+
+    /// Plan an incremental build when `options.affected_since` is set
+    ///
+    /// Falls back to building everything if the workspace or git history
+    /// cannot be inspected.
+    pub(super) fn incremental_plan(
+        cargo_args: &CargoArgs,
+        options: &BuildOptions,
+    ) -> Option<IncrementalPlan> {
+        let since = options.affected_since.as_deref()?;
+        // Explicitly selected packages are built whether they changed or not
+        if !options.selection.packages.is_empty() {
+            return None;
+        }
+
+        match IncrementalPlan::new(since, cargo_args) {
+            Ok(Some(plan)) => {
+                helpers::info(format!(
+                    "Incremental build: only members changed since {since}"
+                ));
+                Some(plan)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                helpers::warning(format!(
+                    "Could not determine changed members, building everything: {e}"
+                ));
+                None
+            }
+        }
+    }
+
+    /// Tell where the built artifacts are: the ones cargo reported, else the
+    /// selected binaries and examples, else the profile directory
+    fn show_artifact_location(target: &Target, options: &BuildOptions, reported: &[PathBuf]) {
+        let target_dir = target_dir_for(&target.triple);
+        let paths = if reported.is_empty() {
+            options
+                .selection
+                .artifact_paths(&target_dir, target, options.release)
+        } else {
+            reported.to_vec()
+        };
+        let profile = if options.release { "release" } else { "debug" };
+
+        if options.operation == CargoOperation::Build && !paths.is_empty() {
+            let cwd = std::env::current_dir().unwrap_or_default();
+            for path in &paths {
+                let shown = path.strip_prefix(&cwd).unwrap_or(path);
+                helpers::tip(format!("Built {}", shown.display()));
+            }
+        } else {
+            helpers::tip(format!(
+                "{} build artifacts are in {}/",
+                if options.release { "Release" } else { "Debug" },
+                target_dir.join(&target.triple).join(profile).display()
+            ));
+        }
+    }
+
+    /// Enforce `policy.release_requires` for release builds
+    /// Toolchain to build with, honoring the Rust release locked in xcargo.lock
+    ///
+    /// Returns the toolchain name, whether it must be passed to cargo as
+    /// `+toolchain`, and a warning if an explicit `--toolchain` wins over the
+    /// lock.
+    pub(super) fn locked_toolchain(
+        &self,
+        selection: &ToolchainSelection,
+        locked: Option<&LockedTarget>,
+    ) -> Result<(String, bool, Option<String>)> {
+        let selected = (selection.name.clone(), selection.needs_override(), None);
+        let Some(locked_rust) = locked.and_then(|l| l.rust.as_deref()) else {
+            return Ok(selected);
+        };
+        let current = self.toolchain_manager.rustc_release(&selection.name);
+        if current.as_deref() == Some(locked_rust) {
+            return Ok(selected);
+        }
+
+        if selection.source == ToolchainSource::CommandLine {
+            let warning = format!(
+                "{LOCK_FILE} locks Rust {locked_rust}; building with --toolchain {} instead",
+                selection.name
+            );
+            return Ok((selected.0, selected.1, Some(warning)));
+        }
+
+        // Stable releases are also rustup toolchain names
+        let is_release = locked_rust
+            .split('.')
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+        if is_release && self.toolchain_manager.uses_rustup() {
+            return Ok((locked_rust.to_string(), true, None));
+        }
+
+        Err(Error::Toolchain(format!(
+            "{LOCK_FILE} locks Rust {locked_rust}, but toolchain {} is Rust {}. \
+             Run `xcargo update-lock` to lock the current toolchain",
+            selection.name,
+            current.as_deref().unwrap_or("unknown")
+        )))
+    }
+
+    /// Warn when a target is built differently than when it was locked
+    fn warn_locked_strategy(locked: Option<&LockedTarget>, strategy: BuildStrategy) {
+        if let Some(warning) = Self::locked_strategy_warning(locked, strategy) {
+            helpers::warning(warning);
+        }
+    }
+
+    /// Describe a difference between the locked and the chosen strategy
+    pub(super) fn locked_strategy_warning(
+        locked: Option<&LockedTarget>,
+        strategy: BuildStrategy,
+    ) -> Option<String> {
+        locked
+            .filter(|l| l.strategy != strategy.as_str())
+            .map(|locked| {
+                format!(
+                    "{LOCK_FILE} was recorded with a {} build, building with {strategy}",
+                    locked.strategy
+                )
+            })
+    }
+
+    /// Run the target's post-build pipeline (split debug info, strip,
+    /// objcopy, upx) after a build
+    fn post_process_target(
+        &self,
+        target: &Target,
+        toolchain: &str,
+        options: &BuildOptions,
+    ) -> Result<()> {
+        if options.operation != CargoOperation::Build {
+            return Ok(());
+        }
+        if target.is_wasm() {
+            let wasm_config = self
+                .config
+                .get_target_config(&target.triple)
+                .and_then(|c| c.wasm.as_ref());
+            wasm::wasm_post_build(target, wasm_config, options.release)?;
+        }
+        if target
+            .env
+            .as_deref()
+            .is_some_and(|env| env.starts_with("musl"))
+        {
+            // -crt-static asks for a dynamically linked musl binary on purpose
+            let rustflags = [
+                rustflags::configured_rustflags(&self.config, &target.triple),
+                rustflags::ambient_rustflags(),
+            ]
+            .concat();
+            if !rustflags.iter().any(|flag| flag.ends_with("-crt-static")) {
+                linkage::check_static(target, options.release);
+            }
+        }
+        let Some(config) = self.config.get_target_config(&target.triple) else {
+            return Ok(());
+        };
+        post_process(
+            target,
+            config,
+            options.release,
+            self.toolchain_manager.sysroot(toolchain).as_deref(),
+            self.zig_toolchain.as_ref().map(ZigToolchain::path),
+        )
+    }
+
+    /// Cargo variable keeping debug info in builds whose debug info is split
+    fn split_debuginfo_env(
+        config: Option<&TargetCustomConfig>,
+        options: &BuildOptions,
+    ) -> Option<(String, String)> {
+        let split = config.and_then(|c| c.split_debuginfo) == Some(true);
+        if !split || options.operation != CargoOperation::Build {
+            return None;
+        }
+        debuginfo::profile_debug_env(options.release, Path::new("Cargo.toml"))
+    }
+
+    /// Sign a release build's binaries, if `[signing]` applies to the target
+    fn sign_target(&self, target: &Target, options: &BuildOptions) -> Result<()> {
+        if options.operation != CargoOperation::Build {
+            return Ok(());
+        }
+        sign::sign_artifacts(target, &self.config.signing, options.release)
+    }
+
+    /// Write provenance for a built target's artifacts, if requested
+    fn attest_target(
+        &self,
+        target: &Target,
+        toolchain: Option<&str>,
+        environment: LockedTarget,
+        rustflags: Vec<String>,
+        reported: &[PathBuf],
+        options: &BuildOptions,
+    ) -> Result<()> {
+        let enabled = options.attest || self.config.attest.enabled == Some(true);
+        if !enabled || options.operation != CargoOperation::Build {
+            return Ok(());
+        }
+        let rustc = toolchain.and_then(|t| self.toolchain_manager.rustc_version(t));
+        let build = Provenance::for_build(
+            &target.versioned_triple(),
+            options.release,
+            rustc,
+            environment,
+            rustflags,
+            BuildHost::detect(&Target::detect_host()?.triple),
+        );
+        let selected = if reported.is_empty() {
+            options.selection.artifact_paths(
+                &target_dir_for(&target.triple),
+                target,
+                options.release,
+            )
+        } else {
+            reported.to_vec()
+        };
+        attest::attest_artifacts(
+            &target.triple,
+            &build,
+            options.release,
+            &selected,
+            &self.config.attest,
+        )
+    }
+
+    /// Rustflags a cargo command runs with: the ones xcargo set, else the
+    /// inherited ones
+    fn command_rustflags(cmd: &Command) -> Vec<String> {
+        cmd.get_envs()
+            .find(|(key, _)| *key == "CARGO_ENCODED_RUSTFLAGS")
+            .and_then(|(_, value)| value)
+            .map_or_else(rustflags::ambient_rustflags, |value| {
+                rustflags::decode(&value.to_string_lossy())
+            })
+    }
+
+    /// Run the project's pre- or post-build hook commands for a target
+    fn run_build_hooks(
+        &self,
+        hook: PluginHook,
+        target: &Target,
+        options: &BuildOptions,
+    ) -> Result<()> {
+        // cargo install builds someone else's crate
+        if options.operation == CargoOperation::Install {
+            return Ok(());
+        }
+        let context = HookContext {
+            target: &target.triple,
+            profile: if options.release { "release" } else { "debug" },
+            operation: options.operation.as_str(),
+        };
+        run_hooks(&self.config, hook, &context)
+    }
+
+    /// Lock a successfully built target's environment if it isn't locked yet
+    fn record_lock(target: &str, locked: Option<&LockedTarget>, entry: LockedTarget) {
+        if locked.is_some() {
+            return;
+        }
+        match LockFile::record(&lock_path(), target, entry) {
+            Ok(true) => helpers::info(format!("Locked the {target} environment in {LOCK_FILE}")),
+            Ok(false) => {}
+            Err(e) => helpers::warning(format!("Could not update {LOCK_FILE}: {e}")),
+        }
+    }
+
+    pub(super) fn check_release_policy(
+        &self,
+        strategy: BuildStrategy,
+        options: &BuildOptions,
+    ) -> Result<()> {
+        if !options.release {
+            return Ok(());
+        }
+
+        let Some(required) = &self.config.policy.release_requires else {
+            return Ok(());
+        };
+
+        if BuildStrategy::from_name(required) == Some(strategy) {
+            return Ok(());
+        }
+
+        Err(Error::PolicyViolation {
+            policy: "release_requires".to_string(),
+            required: required.clone(),
+            actual: strategy.to_string(),
+        })
+    }
+
+    /// The `cc` crate's and bindgen's variables for compiling C and C++
+    /// code for `target`
+    ///
+    /// `compiler` is the C compiler the strategy links with: a GCC cross
+    /// linker, whose toolchain native builds use, or Zig's cc wrapper, whose
+    /// variables the Zig environment already has. Both tell bindgen where the
+    /// target's headers are. Container images come with compilers of their
+    /// own, so they only get the flags.
+    pub(super) fn cc_environment(
+        config: Option<&TargetCustomConfig>,
+        target: &Target,
+        strategy: BuildStrategy,
+        compiler: Option<&str>,
+    ) -> BTreeMap<String, String> {
+        let mut toolchain = compiler
+            .filter(|_| strategy == BuildStrategy::Native)
+            .and_then(CcToolchain::gnu)
+            .unwrap_or_default();
+        let headers_from = match strategy {
+            BuildStrategy::Native => toolchain.cc.as_deref(),
+            BuildStrategy::Zig => compiler,
+            _ => None,
+        };
+        toolchain.bindgen_args = headers_from.map(bindgen_clang_args).unwrap_or_default();
+        if let Some(config) = config {
+            toolchain.cflags = config.cflags.clone().unwrap_or_default();
+            toolchain.cxxflags = config.cxxflags.clone().unwrap_or_default();
+        }
+        toolchain.env(&target.triple)
+    }
+
+    /// Try to use Zig for cross-compilation if available and supported
+    ///
+    /// Returns Some(env) if Zig can handle this cross-compilation, None otherwise.
+    /// Respects the `use_zig` option: None = auto, Some(true) = force, Some(false) = disable
+    fn try_zig_cross_compilation(
+        &self,
+        target: &Target,
+        options: &BuildOptions,
+    ) -> Result<Option<HashMap<String, PathBuf>>> {
+        // Check if Zig is explicitly disabled
+        if options.use_zig == Some(false) {
+            if options.verbose {
+                helpers::info("Zig disabled via --no-zig flag");
+            }
+            return Ok(None);
+        }
+
+        // Check if Zig is explicitly forced; only Zig can target a glibc version
+        let force_zig = options.use_zig == Some(true) || target.glibc.is_some();
+
+        // Determine if we're cross-compiling to a different OS
+        let host = Target::detect_host()?;
+        let is_cross_os = target.os != host.os;
+
+        // For auto mode, only attempt Zig for cross-compilation (different OS)
+        if !force_zig && !is_cross_os {
+            return Ok(None);
+        }
+
+        // Check if Zig is available and supports this target
+        if let Some(ref zig) = self.zig_toolchain {
+            if zig.supports_target(target) {
+                helpers::info(format!(
                     "Zig {} detected, using for cross-compilation",
                     zig.version()
                 ));
                 let env = zig.environment_for_target(target)?;
                 return Ok(Some(env));
             } else if force_zig {
-                return Err(Error::Toolchain(format!(
-                    "Zig does not support target '{}'. Supported targets: x86_64-linux-gnu, aarch64-linux-gnu, armv7-linux-gnueabihf",
-                    target.triple
-                )));
+                return Err(Error::Toolchain(
+                    match ZigToolchain::required_version(&target.triple) {
+                        Some(required) => format!(
+                            "Zig {} is too old for target '{}'; it needs Zig {required} or newer",
+                            zig.version(),
+                            target.triple
+                        ),
+                        None => format!(
+                            "Zig does not support target '{}'. Supported targets: {}",
+                            target.triple,
+                            ZigToolchain::supported_target_names()
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    },
+                ));
             }
             // Zig available but doesn't support this target - not an error in auto mode
             if options.verbose {
@@ -556,7 +1792,11 @@ impl Builder {
             }
         } else {
             // Zig not available
-            if force_zig {
+            if let Some(glibc) = &target.glibc {
+                return Err(Error::Toolchain(format!(
+                    "Zig not found. Targeting glibc {glibc} needs Zig: https://ziglang.org/download/"
+                )));
+            } else if force_zig {
                 return Err(Error::Toolchain(
                     "Zig not found. Install Zig to use --zig flag: brew install zig (macOS) or scoop install zig (Windows)".to_string()
                 ));
@@ -576,6 +1816,78 @@ impl Builder {
         Ok(None)
     }
 
+    /// Link with the toolchain's bundled rust-lld if the target supports it
+    /// and no system linker is available
+    ///
+    /// Returns `None` when a container or Zig was requested, a linker is
+    /// configured for the target, the target is not in the rust-lld
+    /// compatibility table, or the system linker is installed.
+    fn rust_lld_fallback(
+        &self,
+        target: &Target,
+        toolchain: &str,
+        options: &BuildOptions,
+    ) -> Result<Option<LldSupport>> {
+        let Some((support, lld)) = self.rust_lld_choice(target, toolchain, options)? else {
+            return Ok(None);
+        };
+
+        helpers::info(format!(
+            "No system linker for {}, linking with the toolchain's rust-lld",
+            target.triple
+        ));
+        if options.verbose {
+            helpers::info(format!("Using rust-lld: {}", lld.display()));
+        }
+        if support == LldSupport::SelfContained {
+            helpers::hint("Crates with C dependencies still need a C compiler for this target");
+        }
+        Ok(Some(support))
+    }
+
+    /// The toolchain's rust-lld, if a target links with it: it must support
+    /// the target, and no system linker or other strategy may be configured
+    pub(super) fn rust_lld_choice(
+        &self,
+        target: &Target,
+        toolchain: &str,
+        options: &BuildOptions,
+    ) -> Result<Option<(LldSupport, PathBuf)>> {
+        if options.use_container
+            || options.use_zig == Some(true)
+            || self.config.container.use_when == "always"
+        {
+            return Ok(None);
+        }
+        if self
+            .config
+            .get_target_config(&target.triple)
+            .is_some_and(|c| c.linker.is_some())
+        {
+            return Ok(None);
+        }
+        let Some(support) = LldSupport::for_target(&target.triple) else {
+            return Ok(None);
+        };
+
+        let host = Target::detect_host()?;
+        let linker_missing = match target.get_requirements().linker {
+            Some(linker) => which::which(linker).is_err(),
+            // Without a cross linker only the host's C toolchain is left,
+            // which cannot link for another OS
+            None => target.os != host.os,
+        };
+        if !linker_missing {
+            return Ok(None);
+        }
+
+        Ok(self
+            .toolchain_manager
+            .sysroot(toolchain)
+            .and_then(|sysroot| find_rust_lld(&sysroot, &host.triple))
+            .map(|lld| (support, lld)))
+    }
+
     /// Suggest platform-specific installation instructions for a linker
     fn suggest_linker_installation(&self, host: &Target, target: &Target) {
         let host_os = host.os.as_str();
@@ -608,14 +1920,15 @@ impl Builder {
             }
             (_, _) => {
                 helpers::tip(format!(
-                    "Install cross-compilation tools for {host_os} → {target_os}"
+                    "Install cross-compilation tools for {host_os} {} {target_os}",
+                    terminal::symbol("→", "->")
                 ));
             }
         }
     }
 
     /// Determine if a container build should be used for this target
-    fn should_use_container_for_target(&self, target: &Target) -> Result<bool> {
+    pub(super) fn should_use_container_for_target(&self, target: &Target) -> Result<bool> {
         #[cfg(not(feature = "container"))]
         {
             let _ = target; // Suppress unused warning
@@ -624,22 +1937,36 @@ impl Builder {
 
         #[cfg(feature = "container")]
         {
+            // WebAssembly never needs a foreign toolchain
+            if target.is_wasm() {
+                return Ok(false);
+            }
             let host = Target::detect_host()?;
 
-            // Check config's use_when condition
-            match self.config.container.use_when.as_str() {
-                "always" => Ok(true),
-                "never" => Ok(false),
-                "target.os != host.os" => Ok(target.os != host.os),
-                _ => Ok(false),
-            }
+            // Check config's use_when condition; `xcargo config validate` reports unreadable ones
+            Ok(self
+                .config
+                .container
+                .use_when
+                .parse::<crate::config::UseWhen>()
+                .is_ok_and(|condition| condition.matches(target, &host)))
         }
     }
 
     /// Build using a container
+    ///
+    /// Returns the environment to lock: the image and the digest it resolved to.
     #[cfg(feature = "container")]
-    fn build_with_container(&self, target: &Target, options: &BuildOptions) -> Result<()> {
-        use crate::container::{ContainerBuilder, ContainerConfig, RuntimeType};
+    fn build_with_container(
+        &self,
+        target: &Target,
+        options: &BuildOptions,
+        locked: Option<&LockedTarget>,
+    ) -> Result<LockedTarget> {
+        use crate::config::VolumeMount;
+        use crate::container::{
+            CargoCache, ContainerBuilder, ContainerConfig, ImageSelector, PullPolicy, RuntimeType,
+        };
 
         helpers::section("xcargo container build");
         helpers::info(format!("Building {} using container", target.triple));
@@ -650,6 +1977,7 @@ impl Builder {
 
         // Create container builder
         let container_builder = ContainerBuilder::new(runtime_type)
+            .map(|b| b.with_image_selector(ImageSelector::from_config(&self.config.container)))
             .map_err(|e| {
                 helpers::error(format!("Failed to initialize container runtime: {e}"));
                 helpers::hint("Make sure Docker or Podman is installed and running");
@@ -707,18 +2035,71 @@ impl Builder {
                     helpers::tip("Run without --container flag");
                 } else {
                     helpers::hint("This target may not have a pre-built container image");
-                    helpers::tip("You can specify a custom image in xcargo.toml");
+                    helpers::tip(format!(
+                        "Set an image for it in xcargo.toml: [container.images] \"{}\" = \"<image>\"",
+                        target.triple
+                    ));
                 }
 
                 e
             })?;
 
-        helpers::info(format!("Using image: {}", image.full_name()));
+        // Use the digest locked for the image, so the tag can't move under us
+        let image_name = image.full_name();
+        let run_image = match locked.filter(|l| l.image.is_some()) {
+            Some(locked) if locked.image.as_deref() != Some(image_name.as_str()) => {
+                return Err(Error::Container(format!(
+                    "{LOCK_FILE} locks image {} for {}, but {image_name} is selected. \
+                     Run `xcargo update-lock {}` to lock the new image",
+                    locked.image.as_deref().unwrap_or_default(),
+                    target.triple,
+                    target.triple
+                )));
+            }
+            Some(locked) => locked.pinned_image().unwrap_or_else(|| image_name.clone()),
+            None => image_name.clone(),
+        };
+        helpers::info(format!("Using image: {run_image}"));
 
         // Build container config
         let mut container_config = ContainerConfig::default();
         container_config.runtime = runtime_type;
-        container_config.image = image.full_name();
+        container_config.image = run_image.clone();
+        container_config.map_user = self.config.container.map_user;
+        container_config.pull_policy =
+            PullPolicy::from_str(&self.config.container.pull_policy).unwrap_or_default();
+        container_config.cargo_cache =
+            CargoCache::from_str(&self.config.container.cargo_cache).unwrap_or_default();
+        container_config.timeout = self.config.build_timeout(&target.triple)?;
+        if let Some(toolchain) = options
+            .toolchain
+            .as_ref()
+            .or(self.config.toolchain.channel.as_ref())
+        {
+            container_config.toolchain.clone_from(toolchain);
+        }
+
+        // Build into the host's target directory when it isn't the project's
+        // own target/, which the container sees through the project mount
+        let project_dir = std::env::current_dir()?;
+        let host_target_dir = project_dir.join(target_dir_for(&target.triple));
+        if host_target_dir != project_dir.join("target") {
+            std::fs::create_dir_all(&host_target_dir)?;
+            container_config.volumes.push((
+                host_target_dir.display().to_string(),
+                CONTAINER_TARGET_DIR.to_string(),
+            ));
+            container_config.env.push((
+                "CARGO_TARGET_DIR".to_string(),
+                CONTAINER_TARGET_DIR.to_string(),
+            ));
+        }
+
+        // Extra volumes from xcargo.toml, relative to the project
+        for volume in &self.config.container.volumes {
+            let mount: VolumeMount = volume.parse().map_err(Error::Config)?;
+            container_config.volumes.push(mount.resolve(&project_dir));
+        }
 
         // Add custom environment variables from target config
         if let Some(target_config) = self.config.get_target_config(&target.triple) {
@@ -727,41 +2108,276 @@ impl Builder {
             }
         }
 
+        if let Some(env) =
+            Self::split_debuginfo_env(self.config.get_target_config(&target.triple), options)
+        {
+            container_config.env.push(env);
+        }
+
+        // The image's compilers build C dependencies; pass along the configured flags
+        container_config.env.extend(Self::cc_environment(
+            self.config.get_target_config(&target.triple),
+            target,
+            BuildStrategy::Container,
+            None,
+        ));
+
+        // The host's environment stays outside, so only configured rustflags apply
+        let rustflags = rustflags::configured_rustflags(&self.config, &target.triple);
+        if !rustflags.is_empty() {
+            container_config.env.push((
+                "CARGO_ENCODED_RUSTFLAGS".to_string(),
+                rustflags::encode(&rustflags),
+            ));
+        }
+        if !rustflags::ambient_rustflags().is_empty() {
+            helpers::warning("RUSTFLAGS from the environment is not passed into the container");
+            helpers::tip("Set build.rustflags in xcargo.toml to use the flags in container builds");
+        }
+
+        // Mount patches and path dependencies outside the project, and
+        // forward git credentials if the credentials policy allows it
+        let installing = options.operation == CargoOperation::Install;
+        let source_mounts = Self::external_source_mounts(
+            &self.config.container.credentials,
+            &container_config.workdir,
+            !installing,
+        )?;
+        for issue in &source_mounts.unresolved {
+            helpers::warning(issue);
+        }
+        container_config.volumes.extend(source_mounts.volumes);
+        container_config.env.extend(source_mounts.env);
+
+        // Registry tokens and netrc, for private registries and HTTPS git
+        let credential_mounts = crate::container::cargo_credentials(
+            self.config.container.forward_registry_tokens,
+            self.config.container.forward_netrc,
+            container_config.map_user,
+        );
+        for issue in &credential_mounts.unresolved {
+            helpers::warning(issue);
+        }
+        container_config.volumes.extend(credential_mounts.volumes);
+        for (key, value) in credential_mounts.env {
+            if !container_config.env.iter().any(|(set, _)| *set == key) {
+                container_config.env.push((key, value));
+            }
+        }
+        container_config.secrets.extend(credential_mounts.secrets);
+        Self::forward_cargo_config(&mut container_config)?;
+
         // Execute container build
         helpers::progress("Pulling container image...");
 
-        let mut cargo_args = options.cargo_args.clone();
-        if options.release {
+        let parsed_args = CargoArgs::parse(&options.cargo_args)?;
+        let mut cargo_args = if installing {
+            Vec::new()
+        } else {
+            Self::target_feature_args(self.config.get_target_config(&target.triple), &parsed_args)
+        };
+        cargo_args.extend(options.selection.to_args());
+        cargo_args.extend(parsed_args.to_args());
+        if options.release && !installing {
             cargo_args.insert(0, "--release".to_string());
         }
+
+        // cargo install writes to a root on the host, mounted into the container
+        container_config.cargo_command = options.operation.as_str().to_string();
+        if installing {
+            if let Some(root) = mount_install_root(&mut cargo_args) {
+                container_config
+                    .volumes
+                    .push((root, CONTAINER_INSTALL_ROOT.to_string()));
+            }
+        }
         if options.verbose {
             cargo_args.insert(0, "--verbose".to_string());
         }
 
-        container_builder.build(&target.triple, &cargo_args, &container_config)?;
+        let explain_failure = |e: Error| {
+            if let Some(first) = source_mounts.unresolved.first() {
+                helpers::hint(format!("The build may have failed because {first}"));
+                if self.config.container.credentials == "none" {
+                    helpers::tip(
+                        "Set container.credentials = \"ssh-agent\" in xcargo.toml to forward SSH credentials",
+                    );
+                }
+            }
+            e
+        };
 
-        println!(); // Empty line for spacing
-        helpers::success(format!("Container build completed for {}", target.triple));
+        // Test output is captured so failing tests can be reported by name
+        if options.operation == CargoOperation::Test {
+            let run =
+                container_builder.run_captured(&target.triple, &cargo_args, &container_config)?;
+            let fail_fast = !cargo_args.iter().any(|arg| arg == "--no-fail-fast");
+            Self::report_container_tests(target, &run, fail_fast).map_err(explain_failure)?;
+        } else {
+            container_builder
+                .build(&target.triple, &cargo_args, &container_config)
+                .map_err(explain_failure)?;
+        }
 
-        // Show helpful tips
-        if options.release {
-            helpers::tip(format!(
-                "Release build artifacts are in target/{}/release/",
+        helpers::line(""); // Empty line for spacing
+        helpers::success(format!(
+            "Container {} completed for {}",
+            options.operation.as_str(),
+            target.triple
+        ));
+
+        // Show helpful tips; `Builder::install` reports the installed binaries
+        if installing {
+            return Ok(LockedTarget::default());
+        }
+        Self::show_artifact_location(target, options, &[]);
+
+        Ok(LockedTarget {
+            strategy: BuildStrategy::Container.as_str().to_string(),
+            image_digest: container_builder.image_digest(&run_image),
+            image: Some(image_name),
+            ..LockedTarget::default()
+        })
+    }
+
+    /// Report the results of a container test run, failing with the names
+    /// of the tests that failed
+    #[cfg(feature = "container")]
+    fn report_container_tests(
+        target: &Target,
+        run: &crate::container::CapturedRun,
+        fail_fast: bool,
+    ) -> Result<()> {
+        let report = parse_test_output(&run.stdout);
+        if !report.is_empty() {
+            helpers::line("");
+            helpers::info(format!("{}: {}", target.triple, report.summary()));
+        }
+        if run.success {
+            return Ok(());
+        }
+
+        let failed: Vec<&TestCase> = report.failed().collect();
+        if failed.is_empty() {
+            // Nothing ran, e.g. the tests didn't compile
+            return Err(Error::Container(format!(
+                "Container test run failed for {}",
                 target.triple
-            ));
+            )));
+        }
+        for test in &failed {
+            helpers::error(format!("{} failed", test.name));
+        }
+        if fail_fast {
+            helpers::hint("cargo stops at the first test binary with failures");
+            helpers::tip("Pass -- --no-fail-fast to run every test binary");
+        }
+
+        let names: Vec<&str> = failed.iter().map(|t| t.name.as_str()).collect();
+        Err(Error::Build(format!(
+            "{} test{} failed for {}: {}",
+            failed.len(),
+            if failed.len() == 1 { "" } else { "s" },
+            target.triple,
+            names.join(", ")
+        )))
+    }
+
+    /// Extra mounts and credentials needed for patches and dependencies
+    /// outside the project directory; with `scan_sources` false only the
+    /// credentials are forwarded
+    #[cfg(feature = "container")]
+    fn external_source_mounts(
+        credentials: &str,
+        container_root: &str,
+        scan_sources: bool,
+    ) -> Result<crate::container::SourceMounts> {
+        use crate::container::{find_external_sources, plan_mounts, CredentialsPolicy};
+
+        let credentials: CredentialsPolicy = credentials.parse()?;
+        let host_root = std::env::current_dir()?;
+
+        let sources = if scan_sources {
+            match find_external_sources(&host_root) {
+                Ok(sources) => sources,
+                Err(e) => {
+                    helpers::warning(format!("Could not inspect cargo patches: {e}"));
+                    Vec::new()
+                }
+            }
         } else {
-            helpers::tip(format!(
-                "Debug build artifacts are in target/{}/debug/",
-                target.triple
-            ));
+            Vec::new()
+        };
+
+        let mounts = plan_mounts(&sources, &host_root, container_root, credentials)?;
+        for (host, container) in &mounts.volumes {
+            helpers::info(format!("Mounting {host} at {container}"));
+        }
+        Ok(mounts)
+    }
+
+    /// Mount the cargo configuration from outside the project, which the
+    /// container would not see, merged into one file at the container's root
+    #[cfg(feature = "container")]
+    fn forward_cargo_config(
+        container_config: &mut crate::container::ContainerConfig,
+    ) -> Result<()> {
+        let project_dir = std::env::current_dir()?;
+        let configs = cargo_config::cargo_configs(&project_dir);
+        let Some(mut merged) = cargo_config::merge_outside(&configs, &project_dir) else {
+            return Ok(());
+        };
+
+        // Paths in the configuration must point at where they are mounted
+        let mut volumes = container_config.volumes.clone();
+        volumes.push((
+            project_dir.to_string_lossy().to_string(),
+            container_config.workdir.clone(),
+        ));
+        for path in cargo_config::relocate(&mut merged, &volumes) {
+            if cfg!(windows) || !Path::new(&path).exists() {
+                helpers::warning(format!(
+                    "{path} from the cargo configuration is not available in the container"
+                ));
+            } else {
+                container_config
+                    .volumes
+                    .push((path.clone(), format!("{path}:ro")));
+            }
         }
 
+        let file = project_dir.join(".xcargo").join("cargo-config.toml");
+        let contents = toml::to_string(&merged)
+            .map_err(|e| Error::Config(format!("Failed to write {}: {e}", file.display())))?;
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&file, contents)?;
+        container_config.volumes.push((
+            file.to_string_lossy().to_string(),
+            "/.cargo/config.toml:ro".to_string(),
+        ));
+
+        let sources: Vec<String> = configs
+            .iter()
+            .filter(|config| !config.path.starts_with(&project_dir))
+            .map(|config| config.path.display().to_string())
+            .collect();
+        helpers::info(format!(
+            "Forwarding cargo configuration from {}",
+            sources.join(", ")
+        ));
         Ok(())
     }
 
     /// Build using a container (fallback when feature not enabled)
     #[cfg(not(feature = "container"))]
-    fn build_with_container(&self, _target: &Target, _options: &BuildOptions) -> Result<()> {
+    fn build_with_container(
+        &self,
+        _target: &Target,
+        _options: &BuildOptions,
+        _locked: Option<&LockedTarget>,
+    ) -> Result<LockedTarget> {
         helpers::error("Container support not enabled");
         helpers::hint("Rebuild xcargo with: cargo install xcargo --features container");
         helpers::tip("Or use native build without --container flag");
@@ -775,6 +2391,41 @@ impl Builder {
 mod tests {
     use super::*;
 
+    fn release_options() -> BuildOptions {
+        BuildOptions {
+            release: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_release_policy_denies_other_strategies() {
+        let mut config = Config::default();
+        config.policy.release_requires = Some("container".to_string());
+        let Ok(builder) = Builder::with_config(config) else {
+            return;
+        };
+
+        let result = builder.check_release_policy(BuildStrategy::Native, &release_options());
+        assert!(matches!(result, Err(Error::PolicyViolation { .. })));
+        assert!(builder
+            .check_release_policy(BuildStrategy::Container, &release_options())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_release_policy_ignores_debug_builds() {
+        let mut config = Config::default();
+        config.policy.release_requires = Some("container".to_string());
+        let Ok(builder) = Builder::with_config(config) else {
+            return;
+        };
+
+        assert!(builder
+            .check_release_policy(BuildStrategy::Native, &BuildOptions::default())
+            .is_ok());
+    }
+
     #[test]
     fn test_builder_new() {
         // This test will succeed if rustup is installed
@@ -785,4 +2436,54 @@ mod tests {
         }
         assert!(builder.is_ok());
     }
+
+    #[test]
+    fn test_target_feature_args_skip_duplicate_no_default() {
+        let config = TargetCustomConfig {
+            linker: None,
+            force_container: None,
+            env: HashMap::new(),
+            rustflags: None,
+            cflags: None,
+            cxxflags: None,
+            runner: None,
+            features: Some(vec!["web".to_string()]),
+            no_default_features: Some(true),
+            components: None,
+            strip: None,
+            objcopy: None,
+            upx: None,
+            split_debuginfo: None,
+            hooks: None,
+            wasm: None,
+            strategies: None,
+            timeout: None,
+        };
+
+        let plain = CargoArgs::default();
+        assert_eq!(
+            Builder::target_feature_args(Some(&config), &plain),
+            vec!["--no-default-features", "--features", "web"]
+        );
+
+        let passed = CargoArgs::parse(&["--no-default-features".to_string()]).unwrap();
+        assert_eq!(
+            Builder::target_feature_args(Some(&config), &passed),
+            vec!["--features", "web"]
+        );
+        assert!(Builder::target_feature_args(None, &plain).is_empty());
+    }
+
+    #[test]
+    fn test_suggested_runner() {
+        let target = |triple| Target::from_triple(triple).unwrap();
+        assert_eq!(
+            Builder::suggested_runner(&target("s390x-unknown-linux-gnu")),
+            "qemu-s390x -L /usr/s390x-linux-gnu"
+        );
+        assert_eq!(
+            Builder::suggested_runner(&target("aarch64-unknown-linux-gnu")),
+            "qemu-aarch64"
+        );
+    }
 }
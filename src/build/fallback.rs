@@ -0,0 +1,214 @@
+//! Falling back to other strategies when a target's build fails
+//!
+//! With `build.fallback = true`, a build that fails with the strategy xcargo
+//! chose is retried with the next strategy of the target's chain: Zig, then
+//! the native toolchain, then a container, unless the target lists its own
+//! `strategies`. Every attempt starts over with a fresh report, so nothing
+//! from a failed strategy carries over into the next one.
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use crate::target::Target;
+
+use super::bench::BenchResult;
+use super::executor::Builder;
+use super::options::{BuildOptions, BuildStrategy};
+use super::report::BuildReport;
+
+/// Chain of targets without their own `strategies`
+const DEFAULT_CHAIN: [BuildStrategy; 3] = [
+    BuildStrategy::Zig,
+    BuildStrategy::Native,
+    BuildStrategy::Container,
+];
+
+/// Strategies a failed build of `target` falls back through, in order
+pub(super) fn fallback_chain(config: &Config, target: &str) -> Vec<BuildStrategy> {
+    config
+        .get_target_config(target)
+        .and_then(|tc| tc.strategies.as_ref())
+        .map_or_else(
+            || DEFAULT_CHAIN.to_vec(),
+            |names| {
+                names
+                    .iter()
+                    .filter_map(|name| BuildStrategy::from_name(name))
+                    .collect()
+            },
+        )
+}
+
+/// First strategy of `chain` that was not `tried` yet
+pub(super) fn next_strategy(
+    chain: &[BuildStrategy],
+    tried: &[BuildStrategy],
+) -> Option<BuildStrategy> {
+    chain
+        .iter()
+        .copied()
+        .find(|strategy| !tried.contains(strategy))
+}
+
+/// Whether another strategy could succeed where this attempt failed
+///
+/// Cancelled builds stay cancelled, a build that timed out has used up its
+/// time, and compile errors fail with any strategy.
+fn can_fall_back(error: &Error, report: &BuildReport) -> bool {
+    !matches!(error, Error::Cancelled { .. } | Error::BuildTimeout { .. })
+        && report.diagnostics.errors.is_empty()
+}
+
+impl Builder {
+    /// Run the cargo operation, retrying a failed build with the target's
+    /// fallback strategies when `build.fallback` is set
+    ///
+    /// On success `report` describes the attempt that worked; when every
+    /// strategy fails, it describes the first attempt and its error is returned.
+    pub(super) fn execute_with_fallback(
+        &self,
+        options: &BuildOptions,
+        report: &mut BuildReport,
+    ) -> Result<Vec<BenchResult>> {
+        let error = match self.execute_target(options, report, None) {
+            Ok(results) => return Ok(results),
+            Err(e) => e,
+        };
+        // Failures before a strategy was chosen are not the strategy's fault
+        let first = match report.strategy {
+            Some(strategy) if self.config().build.fallback && can_fall_back(&error, report) => {
+                strategy
+            }
+            _ => return Err(error),
+        };
+
+        let chain = fallback_chain(self.config(), &report.target);
+        let mut tried = vec![first];
+        let mut last_error = error.to_string();
+        while let Some(next) = next_strategy(&chain, &tried) {
+            helpers::warning(format!(
+                "{} build of {} failed ({last_error}), falling back to {next}",
+                tried[tried.len() - 1],
+                report.target
+            ));
+            let fallback_options = BuildOptions {
+                use_zig: Some(next == BuildStrategy::Zig),
+                use_container: next == BuildStrategy::Container,
+                ..options.clone()
+            };
+            let mut attempt = BuildReport {
+                fallback_from: tried.clone(),
+                ..BuildReport::default()
+            };
+            match self.execute_target(&fallback_options, &mut attempt, Some(next)) {
+                Ok(results) => {
+                    helpers::success(format!("Built {} with {next}", attempt.target));
+                    *report = attempt;
+                    return Ok(results);
+                }
+                Err(e) if !can_fall_back(&e, &attempt) => {
+                    *report = attempt;
+                    return Err(e);
+                }
+                Err(e) => {
+                    tried.push(next);
+                    last_error = e.to_string();
+                }
+            }
+        }
+
+        helpers::warning(format!(
+            "{} failed with every strategy tried: {}",
+            report.target,
+            tried
+                .iter()
+                .map(BuildStrategy::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        Err(error)
+    }
+
+    /// Fail if the build was `forced` to a strategy other than the one the
+    /// target would be built with
+    pub(super) fn check_forced_strategy(
+        forced: Option<BuildStrategy>,
+        strategy: BuildStrategy,
+        target: &Target,
+    ) -> Result<()> {
+        match forced {
+            Some(forced) if forced != strategy => Err(Error::Build(format!(
+                "{} cannot be built with {forced}, it needs {strategy}",
+                target.triple
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_chain() {
+        let config = Config::from_str(
+            r#"
+            [targets."aarch64-unknown-linux-gnu"]
+            strategies = ["native", "container"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            fallback_chain(&config, "aarch64-unknown-linux-gnu"),
+            [BuildStrategy::Native, BuildStrategy::Container]
+        );
+        assert_eq!(
+            fallback_chain(&config, "x86_64-pc-windows-gnu"),
+            DEFAULT_CHAIN
+        );
+    }
+
+    #[test]
+    fn test_next_strategy() {
+        use BuildStrategy::{Container, Native, RustLld, Zig};
+
+        assert_eq!(next_strategy(&DEFAULT_CHAIN, &[Zig]), Some(Native));
+        assert_eq!(
+            next_strategy(&DEFAULT_CHAIN, &[Zig, Native]),
+            Some(Container)
+        );
+        assert_eq!(
+            next_strategy(&DEFAULT_CHAIN, &[Zig, Native, Container]),
+            None
+        );
+
+        // The first attempt's strategy may come from anywhere in the chain
+        assert_eq!(next_strategy(&DEFAULT_CHAIN, &[Native]), Some(Zig));
+        assert_eq!(next_strategy(&DEFAULT_CHAIN, &[RustLld]), Some(Zig));
+        assert_eq!(next_strategy(&[Container, Zig], &[Zig]), Some(Container));
+    }
+
+    #[test]
+    fn test_can_fall_back() {
+        let report = BuildReport::default();
+        assert!(can_fall_back(
+            &Error::Build("linker failed".to_string()),
+            &report
+        ));
+        assert!(!can_fall_back(
+            &Error::Cancelled {
+                target: "x86_64-pc-windows-gnu".to_string()
+            },
+            &report
+        ));
+        assert!(!can_fall_back(
+            &Error::BuildTimeout {
+                target: "x86_64-pc-windows-gnu".to_string(),
+                timeout: std::time::Duration::from_secs(60)
+            },
+            &report
+        ));
+    }
+}
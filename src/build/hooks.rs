@@ -0,0 +1,162 @@
+//! Pre- and post-build commands from `[hooks]`
+//!
+//! Hooks are the command-line side of the plugin hook points: instead of
+//! implementing [`Plugin`](crate::plugin::Plugin), a project lists shell
+//! commands to run before and after each target is built.
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::output::{helpers, log};
+use crate::plugin::PluginHook;
+use std::process::Command;
+
+/// What a hook is told about the build it runs around
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct HookContext<'a> {
+    /// Target triple being built
+    pub target: &'a str,
+
+    /// Cargo profile, "release" or "debug"
+    pub profile: &'a str,
+
+    /// Cargo operation, e.g. "build" or "test"
+    pub operation: &'a str,
+}
+
+impl HookContext<'_> {
+    /// Environment variables passed to hook commands
+    fn env(&self, hook: PluginHook) -> [(&'static str, String); 4] {
+        [
+            ("XCARGO_TARGET", self.target.to_string()),
+            ("XCARGO_PROFILE", self.profile.to_string()),
+            ("XCARGO_OPERATION", self.operation.to_string()),
+            ("XCARGO_HOOK", hook.as_str().to_string()),
+        ]
+    }
+}
+
+/// Commands configured for a hook: the `[hooks]` ones, then the target's
+fn hook_commands<'a>(config: &'a Config, target: &str, hook: PluginHook) -> Vec<&'a str> {
+    let target_hooks = config
+        .get_target_config(target)
+        .and_then(|c| c.hooks.as_ref());
+    [Some(&config.hooks), target_hooks]
+        .into_iter()
+        .flatten()
+        .flat_map(|hooks| match hook {
+            PluginHook::PreBuild => hooks.pre_build.as_slice(),
+            PluginHook::PostBuild => hooks.post_build.as_slice(),
+            _ => &[],
+        })
+        .map(String::as_str)
+        .collect()
+}
+
+/// Run the commands configured for `hook`, stopping at the first failure
+///
+/// # Errors
+/// Returns an error if a command cannot be started or exits unsuccessfully
+pub(super) fn run_hooks(config: &Config, hook: PluginHook, context: &HookContext) -> Result<()> {
+    for command in hook_commands(config, context.target, hook) {
+        helpers::progress(format!("Running {} hook: {command}", hook.as_str()));
+        let mut cmd = shell(command);
+        cmd.envs(context.env(hook));
+        log::command(&cmd);
+        let status = cmd.status().map_err(|e| {
+            Error::Build(format!(
+                "Failed to run {} hook '{command}': {e}",
+                hook.as_str()
+            ))
+        })?;
+        if !status.success() {
+            return Err(Error::Build(format!(
+                "{} hook '{command}' failed for {} ({status})",
+                hook.as_str(),
+                context.target
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// A command line run through the platform's shell
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", command]);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command]);
+        cmd
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> HookContext<'static> {
+        HookContext {
+            target: "aarch64-unknown-linux-gnu",
+            profile: "release",
+            operation: "build",
+        }
+    }
+
+    #[test]
+    fn test_hook_commands_order() {
+        let config = Config::from_str(
+            r#"
+            [hooks]
+            pre_build = ["./gen.sh"]
+
+            [targets."aarch64-unknown-linux-gnu".hooks]
+            pre_build = ["./gen-arm.sh"]
+            post_build = ["./upload.sh"]
+            "#,
+        )
+        .unwrap();
+
+        let target = "aarch64-unknown-linux-gnu";
+        assert_eq!(
+            hook_commands(&config, target, PluginHook::PreBuild),
+            ["./gen.sh", "./gen-arm.sh"]
+        );
+        assert_eq!(
+            hook_commands(&config, target, PluginHook::PostBuild),
+            ["./upload.sh"]
+        );
+        assert_eq!(
+            hook_commands(&config, "x86_64-unknown-linux-gnu", PluginHook::PreBuild),
+            ["./gen.sh"]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_hooks_env_and_failure() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let out = temp.path().join("hook.txt");
+        let config = Config::from_str(&format!(
+            r#"
+            [hooks]
+            pre_build = ["echo $XCARGO_HOOK $XCARGO_TARGET $XCARGO_PROFILE > {}"]
+            post_build = ["exit 3", "touch {}.never"]
+            "#,
+            out.display(),
+            out.display()
+        ))
+        .unwrap();
+
+        run_hooks(&config, PluginHook::PreBuild, &context()).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "pre-build aarch64-unknown-linux-gnu release\n"
+        );
+
+        let err = run_hooks(&config, PluginHook::PostBuild, &context()).unwrap_err();
+        assert!(err.to_string().contains("post-build hook 'exit 3' failed"));
+        assert!(!temp.path().join("hook.txt.never").exists());
+    }
+}
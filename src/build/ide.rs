@@ -0,0 +1,158 @@
+//! rust-analyzer settings for working on a cross target
+//!
+//! `xcargo ide setup` points rust-analyzer at a target: the code is analyzed
+//! with the target's cfgs, check-on-save runs `xcargo check` so diagnostics
+//! come from the target's build, and build scripts get the compilers and
+//! flags xcargo would set.
+
+use crate::error::{Error, Result};
+use crate::target::Target;
+use serde_json::{Map, Value};
+
+use super::plan::TargetPlan;
+
+/// VS Code's settings file for the workspace
+pub const VSCODE_SETTINGS: &str = ".vscode/settings.json";
+
+/// rust-analyzer settings for checking the target of `plan`, keyed as in
+/// VS Code's settings
+///
+/// Bare-metal targets have no test harness, so only their libraries and
+/// binaries are checked. A container build's environment doesn't apply to
+/// rust-analyzer, which runs on the host, and is left out.
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::build::{rust_analyzer_settings, TargetPlan};
+/// use xcargo::target::Target;
+///
+/// let target = Target::from_triple("aarch64-unknown-linux-gnu").unwrap();
+/// let plan = TargetPlan {
+///     target: target.triple.clone(),
+///     strategy: "zig".to_string(),
+///     ..TargetPlan::default()
+/// };
+/// let settings = rust_analyzer_settings(&target, &plan, None);
+/// assert_eq!(settings["rust-analyzer.cargo.target"], "aarch64-unknown-linux-gnu");
+/// assert_eq!(settings["rust-analyzer.check.overrideCommand"][0], "xcargo");
+/// ```
+#[must_use]
+pub fn rust_analyzer_settings(
+    target: &Target,
+    plan: &TargetPlan,
+    profile: Option<&str>,
+) -> Map<String, Value> {
+    let mut command = vec!["xcargo", "check", "--target", plan.target.as_str()];
+    if let Some(profile) = profile {
+        command.extend(["--profile", profile]);
+    }
+    command.extend(["--message-format=json", "--", "--workspace"]);
+    if !target.is_bare_metal() {
+        command.push("--all-targets");
+    }
+
+    let mut settings = Map::new();
+    settings.insert(
+        "rust-analyzer.cargo.target".to_string(),
+        Value::from(target.triple.as_str()),
+    );
+    settings.insert(
+        "rust-analyzer.check.overrideCommand".to_string(),
+        Value::from(command),
+    );
+    if plan.strategy != "container" {
+        let env = plan
+            .full_env()
+            .into_iter()
+            .map(|(key, value)| (key, Value::from(value)))
+            .collect();
+        settings.insert(
+            "rust-analyzer.cargo.extraEnv".to_string(),
+            Value::Object(env),
+        );
+    }
+    settings
+}
+
+/// `settings` merged into the JSON settings file `existing`, keeping its
+/// other settings
+///
+/// # Errors
+/// Returns an error if `existing` is not a JSON object. VS Code also accepts
+/// comments and trailing commas, which are not supported.
+pub fn merge_settings(existing: &str, settings: &Map<String, Value>) -> Result<String> {
+    let mut merged = if existing.trim().is_empty() {
+        Map::new()
+    } else {
+        match serde_json::from_str(existing) {
+            Ok(Value::Object(object)) => object,
+            Ok(_) => {
+                return Err(Error::Config(
+                    "The settings file does not hold a JSON object".to_string(),
+                ))
+            }
+            Err(e) => {
+                return Err(Error::Config(format!(
+                    "The settings file is not plain JSON ({e}); comments and trailing commas are not supported"
+                )))
+            }
+        }
+    };
+    merged.extend(settings.clone());
+
+    let mut contents = serde_json::to_string_pretty(&Value::Object(merged))
+        .map_err(|e| Error::Config(format!("Failed to serialize settings: {e}")))?;
+    contents.push('\n');
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_for_bare_metal_and_container() {
+        let target = Target::from_triple("thumbv7em-none-eabihf").unwrap();
+        let plan = TargetPlan {
+            target: target.triple.clone(),
+            strategy: "container".to_string(),
+            ..TargetPlan::default()
+        };
+        let settings = rust_analyzer_settings(&target, &plan, Some("embedded"));
+        assert_eq!(
+            settings["rust-analyzer.check.overrideCommand"],
+            serde_json::json!([
+                "xcargo",
+                "check",
+                "--target",
+                "thumbv7em-none-eabihf",
+                "--profile",
+                "embedded",
+                "--message-format=json",
+                "--",
+                "--workspace"
+            ])
+        );
+        assert!(!settings.contains_key("rust-analyzer.cargo.extraEnv"));
+    }
+
+    #[test]
+    fn test_merge_settings_keeps_other_settings() {
+        let mut settings = Map::new();
+        settings.insert("rust-analyzer.cargo.target".to_string(), Value::from("x"));
+
+        let merged = merge_settings(
+            r#"{"editor.formatOnSave": true, "rust-analyzer.cargo.target": "old"}"#,
+            &settings,
+        )
+        .unwrap();
+        let merged: Value = serde_json::from_str(&merged).unwrap();
+        assert_eq!(merged["editor.formatOnSave"], true);
+        assert_eq!(merged["rust-analyzer.cargo.target"], "x");
+
+        assert!(merge_settings("", &settings).is_ok());
+        assert!(merge_settings("// comment\n{}", &settings).is_err());
+        assert!(merge_settings("[]", &settings).is_err());
+    }
+}
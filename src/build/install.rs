@@ -0,0 +1,191 @@
+//! Cross-compiled `cargo install`
+//!
+//! `cargo install` puts binaries in `~/.cargo/bin`, which only makes sense for
+//! the host. `xcargo install` builds a crate for a foreign target with the same
+//! toolchain, Zig, and container handling as `xcargo build`, installs it into a
+//! scratch root, and copies the binaries into a directory of the user's
+//! choosing.
+
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use std::path::{Path, PathBuf};
+
+use super::executor::Builder;
+use super::options::{BuildOptions, CargoOperation};
+
+/// Where the install root is mounted in container builds
+pub(super) const CONTAINER_INSTALL_ROOT: &str = "/xcargo-install";
+
+/// A crate to cross-compile and install
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallRequest {
+    /// Crate name on crates.io
+    pub krate: String,
+
+    /// Version requirement, e.g. "1.2" or "=1.2.3"
+    pub version: Option<String>,
+
+    /// Directory the binaries are copied into
+    pub out_dir: PathBuf,
+}
+
+impl InstallRequest {
+    /// Arguments for `cargo install` into `root`, before any passthrough arguments
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::{Path, PathBuf};
+    /// use xcargo::build::InstallRequest;
+    ///
+    /// let request = InstallRequest {
+    ///     krate: "ripgrep".to_string(),
+    ///     version: Some("14".to_string()),
+    ///     out_dir: PathBuf::from("dist"),
+    /// };
+    /// assert_eq!(
+    ///     request.cargo_args(Path::new("/tmp/root")),
+    ///     ["ripgrep", "--version", "14", "--root", "/tmp/root", "--no-track"]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn cargo_args(&self, root: &Path) -> Vec<String> {
+        let mut args = vec![self.krate.clone()];
+        if let Some(version) = &self.version {
+            args.extend(["--version".to_string(), version.clone()]);
+        }
+        args.extend([
+            "--root".to_string(),
+            root.display().to_string(),
+            // The scratch root is thrown away, so there is nothing to track
+            "--no-track".to_string(),
+        ]);
+        args
+    }
+}
+
+impl Builder {
+    /// Cross-compile a crate and copy its binaries to the requested directory
+    ///
+    /// `options.target` selects the target; `options.cargo_args` are passed
+    /// on to `cargo install`. Returns the paths of the copied binaries.
+    ///
+    /// # Errors
+    /// Returns an error if no target is given, the build fails, or the
+    /// binaries cannot be copied
+    pub fn install(
+        &self,
+        request: &InstallRequest,
+        options: &BuildOptions,
+    ) -> Result<Vec<PathBuf>> {
+        if options.target.is_none() {
+            return Err(Error::Config(
+                "xcargo install needs a target, e.g. --target aarch64-unknown-linux-musl"
+                    .to_string(),
+            ));
+        }
+
+        let root = std::env::temp_dir().join(format!("xcargo-install-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root)?;
+
+        let mut cargo_args = request.cargo_args(&root);
+        cargo_args.extend(options.cargo_args.iter().cloned());
+        let install_options = BuildOptions {
+            operation: CargoOperation::Install,
+            release: true,
+            cargo_args,
+            ..options.clone()
+        };
+
+        let installed = self
+            .build(&install_options)
+            .and_then(|()| copy_binaries(&root.join("bin"), &request.out_dir));
+        let _ = std::fs::remove_dir_all(&root);
+
+        let installed = installed?;
+        if installed.is_empty() {
+            return Err(Error::Build(format!(
+                "{} has no binaries to install",
+                request.krate
+            )));
+        }
+        for path in &installed {
+            helpers::success(format!("Installed {}", path.display()));
+        }
+        Ok(installed)
+    }
+}
+
+/// Point a `--root` argument at the container mount
+///
+/// Returns the host path it pointed to, which the caller mounts at
+/// [`CONTAINER_INSTALL_ROOT`].
+#[cfg_attr(not(feature = "container"), allow(dead_code))]
+pub(super) fn mount_install_root(cargo_args: &mut [String]) -> Option<String> {
+    let idx = cargo_args.iter().position(|arg| arg == "--root")? + 1;
+    let root = cargo_args.get_mut(idx)?;
+    Some(std::mem::replace(root, CONTAINER_INSTALL_ROOT.to_string()))
+}
+
+/// Copy every file in `bin_dir` to `out_dir`, keeping permissions
+fn copy_binaries(bin_dir: &Path, out_dir: &Path) -> Result<Vec<PathBuf>> {
+    let Ok(entries) = std::fs::read_dir(bin_dir) else {
+        return Ok(Vec::new());
+    };
+    std::fs::create_dir_all(out_dir)?;
+
+    let mut copied = Vec::new();
+    for entry in entries.filter_map(std::result::Result::ok) {
+        if !entry.path().is_file() {
+            continue;
+        }
+        let dest = out_dir.join(entry.file_name());
+        std::fs::copy(entry.path(), &dest).map_err(|e| {
+            Error::Build(format!("Failed to copy binary to {}: {e}", dest.display()))
+        })?;
+        copied.push(dest);
+    }
+    copied.sort();
+    Ok(copied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_mount_install_root() {
+        let mut args = vec![
+            "bat".to_string(),
+            "--root".to_string(),
+            "/tmp/xcargo-install-1".to_string(),
+            "--locked".to_string(),
+        ];
+        assert_eq!(
+            mount_install_root(&mut args).as_deref(),
+            Some("/tmp/xcargo-install-1")
+        );
+        assert_eq!(args[2], CONTAINER_INSTALL_ROOT);
+        assert_eq!(mount_install_root(&mut ["bat".to_string()]), None);
+    }
+
+    #[test]
+    fn test_copy_binaries() {
+        let temp = TempDir::new().unwrap();
+        let bin = temp.path().join("root/bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(bin.join("rg"), "binary").unwrap();
+
+        let out_dir = temp.path().join("dist/arm");
+        let copied = copy_binaries(&bin, &out_dir).unwrap();
+        assert_eq!(copied, [out_dir.join("rg")]);
+        assert_eq!(std::fs::read_to_string(&copied[0]).unwrap(), "binary");
+
+        // Nothing installed, nothing copied
+        assert!(copy_binaries(&temp.path().join("missing"), &out_dir)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+//! Test result parsing
+//!
+//! Test binary output is scanned for libtest's per-test results, in both the
+//! default format (`test name ... FAILED`) and the `--format json` event
+//! stream, so failing tests can be reported by name instead of as a single
+//! exit code.
+
+use serde_json::Value;
+
+/// How a single test ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestOutcome {
+    /// The test passed
+    Passed,
+    /// The test failed or timed out
+    Failed,
+    /// The test was ignored
+    Ignored,
+}
+
+/// Result of one test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestCase {
+    /// Full test path, e.g. `parser::tests::empty_input`
+    pub name: String,
+
+    /// How the test ended
+    pub outcome: TestOutcome,
+
+    /// Captured output of a failed test, if libtest printed it
+    pub output: Option<String>,
+}
+
+/// Results of every test in a test run
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TestReport {
+    /// Tests in the order they finished
+    pub tests: Vec<TestCase>,
+}
+
+impl TestReport {
+    /// Whether no test results were found
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.tests.is_empty()
+    }
+
+    /// Number of tests that ended with `outcome`
+    #[must_use]
+    pub fn count(&self, outcome: TestOutcome) -> usize {
+        self.tests.iter().filter(|t| t.outcome == outcome).count()
+    }
+
+    /// Tests that failed
+    pub fn failed(&self) -> impl Iterator<Item = &TestCase> {
+        self.tests
+            .iter()
+            .filter(|t| t.outcome == TestOutcome::Failed)
+    }
+
+    /// One-line summary, e.g. "41 passed, 1 failed, 2 ignored"
+    #[must_use]
+    pub fn summary(&self) -> String {
+        format!(
+            "{} passed, {} failed, {} ignored",
+            self.count(TestOutcome::Passed),
+            self.count(TestOutcome::Failed),
+            self.count(TestOutcome::Ignored)
+        )
+    }
+}
+
+/// Parse test results from the output of `cargo test`
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::build::{parse_test_output, TestOutcome};
+///
+/// let report = parse_test_output("test tests::it_works ... ok\ntest tests::edge ... FAILED\n");
+/// assert_eq!(report.count(TestOutcome::Passed), 1);
+/// assert_eq!(report.failed().next().unwrap().name, "tests::edge");
+/// ```
+#[must_use]
+pub fn parse_test_output(output: &str) -> TestReport {
+    let mut report = TestReport::default();
+    // Index of the failed test whose captured output is being read
+    let mut reading: Option<usize> = None;
+
+    for line in output.lines() {
+        if let Some(case) = parse_json_event(line).or_else(|| parse_result_line(line)) {
+            report.tests.push(case);
+            reading = None;
+            continue;
+        }
+
+        // Failed tests' output follows under "---- name stdout ----"
+        if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            reading = report
+                .tests
+                .iter()
+                .rposition(|t| t.name == name && t.outcome == TestOutcome::Failed);
+            continue;
+        }
+        if line == "failures:" || line.starts_with("test result:") {
+            reading = None;
+            continue;
+        }
+        if let Some(index) = reading {
+            let output = report.tests[index].output.get_or_insert_with(String::new);
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+
+    for case in &mut report.tests {
+        if let Some(output) = &mut case.output {
+            let trimmed = output.trim_end().len();
+            output.truncate(trimmed);
+        }
+    }
+    report
+}
+
+/// `test name ... ok`, `test name ... FAILED` or `test name ... ignored[, reason]`
+fn parse_result_line(line: &str) -> Option<TestCase> {
+    let rest = line.strip_prefix("test ")?;
+    let (name, result) = rest.rsplit_once(" ... ")?;
+    let outcome = match result.trim() {
+        "ok" => TestOutcome::Passed,
+        "FAILED" => TestOutcome::Failed,
+        ignored if ignored.starts_with("ignored") => TestOutcome::Ignored,
+        _ => return None,
+    };
+    Some(TestCase {
+        name: name.to_string(),
+        outcome,
+        output: None,
+    })
+}
+
+/// `{ "type": "test", "event": "failed", "name": "...", "stdout": "..." }`
+fn parse_json_event(line: &str) -> Option<TestCase> {
+    if !line.starts_with('{') {
+        return None;
+    }
+    let event: Value = serde_json::from_str(line).ok()?;
+    if event.get("type")?.as_str()? != "test" {
+        return None;
+    }
+    let outcome = match event.get("event")?.as_str()? {
+        "ok" => TestOutcome::Passed,
+        "failed" | "timeout" => TestOutcome::Failed,
+        "ignored" => TestOutcome::Ignored,
+        _ => return None,
+    };
+    Some(TestCase {
+        name: event.get("name")?.as_str()?.to_string(),
+        outcome,
+        output: event
+            .get("stdout")
+            .and_then(Value::as_str)
+            .map(|s| s.trim_end().to_string())
+            .filter(|s| !s.is_empty()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRETTY: &str = "\
+running 4 tests
+test config::tests::defaults ... ok
+test parser::tests::empty ... FAILED
+test net::tests::online ... ignored, needs network
+test parser::tests::nested ... FAILED
+
+failures:
+
+---- parser::tests::empty stdout ----
+thread 'parser::tests::empty' panicked at src/parser.rs:10:5:
+assertion failed: tokens.is_empty()
+
+---- parser::tests::nested stdout ----
+thread 'parser::tests::nested' panicked at src/parser.rs:20:5:
+not yet implemented
+
+failures:
+    parser::tests::empty
+    parser::tests::nested
+
+test result: FAILED. 1 passed; 2 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.01s
+";
+
+    #[test]
+    fn test_parse_pretty_output() {
+        let report = parse_test_output(PRETTY);
+        assert_eq!(report.summary(), "1 passed, 2 failed, 1 ignored");
+
+        let failed: Vec<&TestCase> = report.failed().collect();
+        assert_eq!(failed[0].name, "parser::tests::empty");
+        assert_eq!(
+            failed[0].output.as_deref(),
+            Some(
+                "thread 'parser::tests::empty' panicked at src/parser.rs:10:5:\n\
+                 assertion failed: tokens.is_empty()"
+            )
+        );
+        assert_eq!(failed[1].name, "parser::tests::nested");
+        assert!(failed[1]
+            .output
+            .as_deref()
+            .unwrap()
+            .ends_with("not yet implemented"));
+    }
+
+    #[test]
+    fn test_parse_json_output() {
+        let output = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "a" }
+{ "type": "test", "name": "a", "event": "ok" }
+{ "type": "test", "name": "b", "event": "failed", "stdout": "thread 'b' panicked\n" }
+{ "type": "test", "name": "c", "event": "ignored" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1, "ignored": 1 }
+"#;
+        let report = parse_test_output(output);
+        assert_eq!(report.summary(), "1 passed, 1 failed, 1 ignored");
+        let failed = report.failed().next().unwrap();
+        assert_eq!(failed.name, "b");
+        assert_eq!(failed.output.as_deref(), Some("thread 'b' panicked"));
+    }
+
+    #[test]
+    fn test_parse_ignores_other_output() {
+        let report = parse_test_output("running 0 tests\nhello from a test\n{not json}\n");
+        assert!(report.is_empty());
+    }
+}
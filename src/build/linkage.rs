@@ -0,0 +1,349 @@
+//! Static linking checks for musl binaries
+//!
+//! musl targets are built to get self-contained executables, but a wrong
+//! linker (the host's `cc`, or Zig picking up the host's libraries) quietly
+//! produces binaries that need a dynamic loader or glibc at run time. After a
+//! musl build each executable's ELF program headers are read, and anything
+//! that ties it to a dynamic loader or glibc is reported.
+
+use crate::output::helpers;
+use crate::target::Target;
+
+use super::binary::Reader;
+use super::postprocess::target_binaries;
+use super::summary::target_dir_for;
+
+const PT_LOAD: u32 = 1;
+const PT_DYNAMIC: u32 = 2;
+const PT_INTERP: u32 = 3;
+const PT_NOTE: u32 = 4;
+const DT_NULL: u64 = 0;
+const DT_NEEDED: u64 = 1;
+const DT_STRTAB: u64 = 5;
+const DT_STRSZ: u64 = 10;
+/// The `.note.ABI-tag` glibc's startup files put in every program
+const NT_GNU_ABI_TAG: u32 = 1;
+
+/// What an ELF executable needs from the system at run time
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct Linkage {
+    /// The dynamic loader in `PT_INTERP`, e.g. `/lib/ld-musl-x86_64.so.1`
+    pub interpreter: Option<String>,
+    /// Shared libraries from the `DT_NEEDED` entries
+    pub needed: Vec<String>,
+    /// glibc symbol versions the binary references, e.g. `GLIBC_2.34`
+    pub glibc_versions: Vec<String>,
+    /// Whether glibc's startup code was linked in
+    pub glibc_startup: bool,
+}
+
+impl Linkage {
+    /// Whether the binary runs without a loader, shared libraries or glibc
+    ///
+    /// Static PIE executables have a dynamic section for their relocations,
+    /// but no interpreter and no libraries, so they count as static.
+    pub fn is_static(&self) -> bool {
+        self.interpreter.is_none()
+            && self.needed.is_empty()
+            && self.glibc_versions.is_empty()
+            && !self.glibc_startup
+    }
+
+    /// One line per thing that keeps the binary from being static
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Some(interpreter) = &self.interpreter {
+            problems.push(format!("needs the dynamic loader {interpreter}"));
+        }
+        if !self.needed.is_empty() {
+            problems.push(format!(
+                "links shared libraries: {}",
+                self.needed.join(", ")
+            ));
+        }
+        if !self.glibc_versions.is_empty() {
+            let shown = self.glibc_versions.len().min(3);
+            let more = match self.glibc_versions.len() - shown {
+                0 => String::new(),
+                n => format!(" and {n} more"),
+            };
+            problems.push(format!(
+                "references glibc symbols ({}{more})",
+                self.glibc_versions[..shown].join(", ")
+            ));
+        }
+        if self.glibc_startup {
+            problems.push("contains glibc's startup code".to_string());
+        }
+        problems
+    }
+}
+
+/// A program header's fields the check needs
+struct Segment {
+    kind: u32,
+    offset: usize,
+    vaddr: u64,
+    filesz: usize,
+    align: usize,
+}
+
+/// Read the linkage of an ELF executable; `None` if `bytes` isn't ELF
+pub(super) fn inspect(bytes: &[u8]) -> Option<Linkage> {
+    if bytes.get(..4)? != b"\x7fELF" {
+        return None;
+    }
+    let elf = Reader {
+        bytes,
+        wide: *bytes.get(4)? == 2,
+        big_endian: *bytes.get(5)? == 2,
+    };
+    let (phoff, phentsize, phnum) = if elf.wide {
+        (elf.usize(0x20)?, elf.u16(0x36)?, elf.u16(0x38)?)
+    } else {
+        (elf.usize(0x1C)?, elf.u16(0x2A)?, elf.u16(0x2C)?)
+    };
+
+    let segments: Vec<Segment> = (0..usize::from(phnum))
+        .filter_map(|idx| {
+            let ph = phoff.checked_add(idx * usize::from(phentsize))?;
+            let segment = if elf.wide {
+                Segment {
+                    kind: elf.u32(ph)?,
+                    offset: elf.usize(ph + 8)?,
+                    vaddr: elf.u64(ph + 16)?,
+                    filesz: elf.usize(ph + 32)?,
+                    align: elf.usize(ph + 48)?,
+                }
+            } else {
+                Segment {
+                    kind: elf.u32(ph)?,
+                    offset: elf.usize(ph + 4)?,
+                    vaddr: elf.word(ph + 8)?,
+                    filesz: elf.usize(ph + 16)?,
+                    align: elf.usize(ph + 28)?,
+                }
+            };
+            Some(segment)
+        })
+        .collect();
+
+    let mut linkage = Linkage::default();
+    for segment in &segments {
+        match segment.kind {
+            PT_INTERP => linkage.interpreter = elf.str(segment.offset),
+            PT_DYNAMIC => read_dynamic(&elf, segment, &segments, &mut linkage),
+            PT_NOTE => linkage.glibc_startup |= has_abi_tag(&elf, segment),
+            _ => {}
+        }
+    }
+    Some(linkage)
+}
+
+/// Collect `DT_NEEDED` libraries and glibc versions from the dynamic section
+fn read_dynamic(elf: &Reader<'_>, dynamic: &Segment, segments: &[Segment], linkage: &mut Linkage) {
+    let entry = if elf.wide { 16 } else { 8 };
+    let mut needed = Vec::new();
+    let (mut strtab, mut strsz) = (None, 0);
+    for offset in (dynamic.offset..dynamic.offset + dynamic.filesz).step_by(entry) {
+        let (Some(tag), Some(value)) = (elf.word(offset), elf.word(offset + entry / 2)) else {
+            break;
+        };
+        match tag {
+            DT_NULL => break,
+            DT_NEEDED => needed.push(value),
+            DT_STRTAB => strtab = Some(value),
+            DT_STRSZ => strsz = usize::try_from(value).unwrap_or(0),
+            _ => {}
+        }
+    }
+    // DT_STRTAB is an address; find it in the file through the loaded segments
+    let Some(strtab) = strtab.and_then(|addr| file_offset(segments, addr)) else {
+        return;
+    };
+    linkage.needed = needed
+        .into_iter()
+        .filter_map(|name| elf.str(strtab + usize::try_from(name).ok()?))
+        .collect();
+
+    let table = elf
+        .bytes
+        .get(strtab..strtab.saturating_add(strsz))
+        .unwrap_or_default();
+    for name in table.split(|&b| b == 0) {
+        let name = String::from_utf8_lossy(name);
+        if name.starts_with("GLIBC_") && !linkage.glibc_versions.contains(&name.to_string()) {
+            linkage.glibc_versions.push(name.into_owned());
+        }
+    }
+}
+
+fn file_offset(segments: &[Segment], addr: u64) -> Option<usize> {
+    segments
+        .iter()
+        .filter(|s| s.kind == PT_LOAD)
+        .find(|s| addr >= s.vaddr && addr - s.vaddr < s.filesz as u64)
+        .and_then(|s| {
+            usize::try_from(addr - s.vaddr)
+                .ok()
+                .map(|delta| s.offset + delta)
+        })
+}
+
+/// Whether a note segment holds the GNU ABI tag of glibc's `crt1.o`
+fn has_abi_tag(elf: &Reader<'_>, note: &Segment) -> bool {
+    let align = if note.align == 8 { 8 } else { 4 };
+    let pad = |n: usize| (n + align - 1) / align * align;
+    let end = note.offset + note.filesz;
+    let mut offset = note.offset;
+    while offset + 12 <= end {
+        let (Some(namesz), Some(descsz), Some(kind)) =
+            (elf.u32(offset), elf.u32(offset + 4), elf.u32(offset + 8))
+        else {
+            return false;
+        };
+        let name = offset + 12;
+        if kind == NT_GNU_ABI_TAG && elf.bytes.get(name..name + 4) == Some(b"GNU\0") {
+            return true;
+        }
+        offset = name + pad(namesz as usize) + pad(descsz as usize);
+    }
+    false
+}
+
+/// Check that the executables of a musl build are statically linked
+///
+/// Problems are warnings: the binaries work on systems that have the
+/// libraries, they just aren't the self-contained executables musl promises.
+pub(super) fn check_static(target: &Target, release: bool) {
+    let binaries = target_binaries(&target_dir_for(&target.triple), &target.triple, release);
+    let mut checked = 0;
+    let mut dynamic = 0;
+    for binary in &binaries {
+        let Some(linkage) = std::fs::read(binary).ok().and_then(|bytes| inspect(&bytes)) else {
+            continue;
+        };
+        checked += 1;
+        if linkage.is_static() {
+            continue;
+        }
+        dynamic += 1;
+        let name = binary.file_name().unwrap_or_default().to_string_lossy();
+        helpers::warning(format!("{name} is not statically linked"));
+        for problem in linkage.problems() {
+            helpers::info(format!("  {problem}"));
+        }
+    }
+
+    if dynamic > 0 {
+        helpers::hint(format!(
+            "A host or Zig linker can pull in the system's libc for {}",
+            target.triple
+        ));
+        helpers::tip(
+            "Link with a musl toolchain (e.g. --container), and don't set -C target-feature=-crt-static",
+        );
+    } else if checked > 0 {
+        helpers::success(format!(
+            "{checked} binar{} statically linked",
+            if checked == 1 { "y" } else { "ies" }
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal little-endian ELF64 executable with the given program headers
+    fn elf64(segments: &[(u32, &[u8], u64)]) -> Vec<u8> {
+        let phoff = 64;
+        let data_start = phoff + 56 * segments.len();
+        let mut bytes = vec![0u8; data_start];
+        bytes[..4].copy_from_slice(b"\x7fELF");
+        bytes[4] = 2;
+        bytes[5] = 1;
+        bytes[0x20..0x28].copy_from_slice(&(phoff as u64).to_le_bytes());
+        bytes[0x36..0x38].copy_from_slice(&56u16.to_le_bytes());
+        bytes[0x38..0x3A].copy_from_slice(&u16::try_from(segments.len()).unwrap().to_le_bytes());
+
+        for (idx, (kind, data, vaddr)) in segments.iter().enumerate() {
+            let offset = bytes.len() as u64;
+            let ph = phoff + idx * 56;
+            bytes[ph..ph + 4].copy_from_slice(&kind.to_le_bytes());
+            bytes[ph + 8..ph + 16].copy_from_slice(&offset.to_le_bytes());
+            bytes[ph + 16..ph + 24].copy_from_slice(&vaddr.to_le_bytes());
+            bytes[ph + 32..ph + 40].copy_from_slice(&(data.len() as u64).to_le_bytes());
+            bytes[ph + 48..ph + 56].copy_from_slice(&4u64.to_le_bytes());
+            bytes.extend_from_slice(data);
+        }
+        bytes
+    }
+
+    fn dynamic(entries: &[(u64, u64)]) -> Vec<u8> {
+        entries
+            .iter()
+            .chain(std::iter::once(&(DT_NULL, 0)))
+            .flat_map(|(tag, value)| [tag.to_le_bytes(), value.to_le_bytes()].concat())
+            .collect()
+    }
+
+    #[test]
+    fn test_inspect_static() {
+        let linkage = inspect(&elf64(&[(PT_LOAD, &[0; 16], 0x1000)])).unwrap();
+        assert!(linkage.is_static());
+        assert!(linkage.problems().is_empty());
+
+        // Static PIE: a dynamic section for relocations only
+        let pie = elf64(&[(PT_DYNAMIC, &dynamic(&[(7, 0)]), 0x2000)]);
+        assert!(inspect(&pie).unwrap().is_static());
+
+        assert_eq!(inspect(b"MZ\x90\0"), None);
+        assert_eq!(inspect(b"\x7fEL"), None);
+    }
+
+    #[test]
+    fn test_inspect_dynamic_glibc() {
+        let strtab = b"\0libc.so.6\0GLIBC_2.34\0GLIBC_2.2.5\0";
+        let binary = elf64(&[
+            (PT_INTERP, b"/lib64/ld-linux-x86-64.so.2\0", 0x300),
+            (PT_LOAD, strtab, 0x400),
+            (
+                PT_DYNAMIC,
+                &dynamic(&[(DT_NEEDED, 1), (DT_STRTAB, 0x400), (DT_STRSZ, 34)]),
+                0x500,
+            ),
+        ]);
+        let linkage = inspect(&binary).unwrap();
+        assert!(!linkage.is_static());
+        assert_eq!(
+            linkage.interpreter.as_deref(),
+            Some("/lib64/ld-linux-x86-64.so.2")
+        );
+        assert_eq!(linkage.needed, vec!["libc.so.6"]);
+        assert_eq!(linkage.glibc_versions, vec!["GLIBC_2.34", "GLIBC_2.2.5"]);
+        assert_eq!(linkage.problems().len(), 3);
+    }
+
+    #[test]
+    fn test_inspect_glibc_startup() {
+        // The .note.ABI-tag of glibc's crt1.o: GNU, type 1, Linux 3.2.0
+        let mut note = Vec::new();
+        for field in [4u32, 16, NT_GNU_ABI_TAG] {
+            note.extend_from_slice(&field.to_le_bytes());
+        }
+        note.extend_from_slice(b"GNU\0");
+        for field in [0u32, 3, 2, 0] {
+            note.extend_from_slice(&field.to_le_bytes());
+        }
+        let linkage = inspect(&elf64(&[(PT_NOTE, &note, 0x200)])).unwrap();
+        assert!(linkage.glibc_startup);
+        assert!(!linkage.is_static());
+
+        // A build ID note (type 3) is no sign of glibc
+        note[8..12].copy_from_slice(&3u32.to_le_bytes());
+        assert!(inspect(&elf64(&[(PT_NOTE, &note, 0x200)]))
+            .unwrap()
+            .is_static());
+    }
+}
@@ -0,0 +1,270 @@
+//! Locked cross environments in `xcargo.lock`
+//!
+//! The first successful build of a target records what it was built with: the
+//! Rust release (which fixes the target's standard library), the Zig version,
+//! and the digest of the container image. Later builds use the same versions,
+//! or fail if they aren't available, until `xcargo update-lock` refreshes
+//! them. Committing the file makes coworkers and CI build in identical
+//! environments.
+
+use crate::config::ConfigDiscovery;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Lock file, relative to the project root
+pub const LOCK_FILE: &str = "xcargo.lock";
+
+/// Path of the project's lock file: next to its xcargo.toml, or to the
+/// Cargo.toml holding its xcargo configuration
+///
+/// Builds run from a member crate's directory use the project's lock. Without
+/// a project configuration, the lock file is in the current directory.
+#[must_use]
+pub fn lock_path() -> PathBuf {
+    match std::env::current_dir() {
+        Ok(dir) => lock_path_from(&dir),
+        Err(_) => PathBuf::from(LOCK_FILE),
+    }
+}
+
+/// Path of the lock file of the project containing `dir`
+fn lock_path_from(dir: &Path) -> PathBuf {
+    let config = ConfigDiscovery::find_from(dir.to_path_buf())
+        .ok()
+        .flatten()
+        .or_else(|| ConfigDiscovery::find_manifest_from(dir).ok().flatten());
+    let root = config
+        .as_deref()
+        .and_then(Path::parent)
+        .map_or_else(|| dir.to_path_buf(), Path::to_path_buf);
+    root.join(LOCK_FILE)
+}
+
+/// Current lock file format
+const LOCK_VERSION: u32 = 1;
+
+const LOCK_HEADER: &str = "# This file is generated by xcargo and should be committed.\n\
+                           # Run `xcargo update-lock` to refresh the locked versions.\n\n";
+
+/// Serializes read-modify-write cycles of parallel target builds
+static LOCK_WRITE: Mutex<()> = Mutex::new(());
+
+/// Environment a target was built in
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LockedTarget {
+    /// How the target was built, e.g. "native", "zig", or "container"
+    pub strategy: String,
+
+    /// Rust release of the toolchain, e.g. "1.82.0"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rust: Option<String>,
+
+    /// Zig version used as the C compiler and linker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zig: Option<String>,
+
+    /// Container image as configured, e.g. "ghcr.io/cross-rs/aarch64-unknown-linux-gnu:latest"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+
+    /// Registry digest the image resolved to, e.g. "sha256:..."
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_digest: Option<String>,
+}
+
+impl LockedTarget {
+    /// Image reference pinned to its digest, e.g. "repository@sha256:..."
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::LockedTarget;
+    ///
+    /// let locked = LockedTarget {
+    ///     strategy: "container".to_string(),
+    ///     image: Some("ghcr.io/cross-rs/aarch64-unknown-linux-gnu:latest".to_string()),
+    ///     image_digest: Some("sha256:abc".to_string()),
+    ///     ..LockedTarget::default()
+    /// };
+    /// assert_eq!(
+    ///     locked.pinned_image().as_deref(),
+    ///     Some("ghcr.io/cross-rs/aarch64-unknown-linux-gnu@sha256:abc")
+    /// );
+    /// ```
+    #[must_use]
+    pub fn pinned_image(&self) -> Option<String> {
+        let image = self.image.as_deref()?;
+        let digest = self.image_digest.as_deref()?;
+        let repository = image.split_once('@').map_or(image, |(repo, _)| repo);
+        // A ':' after the last '/' is a tag, not a registry port
+        let name_start = repository.rfind('/').map_or(0, |idx| idx + 1);
+        let repository = match repository[name_start..].rfind(':') {
+            Some(idx) => &repository[..name_start + idx],
+            None => repository,
+        };
+        Some(format!("{repository}@{digest}"))
+    }
+}
+
+/// Contents of `xcargo.lock`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LockFile {
+    /// Format version
+    pub version: u32,
+
+    /// Locked environments keyed by target triple
+    #[serde(default)]
+    pub targets: BTreeMap<String, LockedTarget>,
+}
+
+impl Default for LockFile {
+    fn default() -> Self {
+        Self {
+            version: LOCK_VERSION,
+            targets: BTreeMap::new(),
+        }
+    }
+}
+
+impl LockFile {
+    /// Load the lock file, or `None` if it doesn't exist
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(path)?;
+        let lock: Self = toml::from_str(&contents)
+            .map_err(|e| Error::Config(format!("Failed to parse {}: {e}", path.display())))?;
+        if lock.version > LOCK_VERSION {
+            return Err(Error::Config(format!(
+                "{} was written by a newer xcargo (version {}); upgrade xcargo to use it",
+                path.display(),
+                lock.version
+            )));
+        }
+        Ok(Some(lock))
+    }
+
+    /// Write the lock file
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = toml::to_string_pretty(self)
+            .map_err(|e| Error::Config(format!("Failed to serialize {LOCK_FILE}: {e}")))?;
+        std::fs::write(path, format!("{LOCK_HEADER}{contents}"))?;
+        Ok(())
+    }
+
+    /// Locked environment of a target
+    #[must_use]
+    pub fn get(&self, target: &str) -> Option<&LockedTarget> {
+        self.targets.get(target)
+    }
+
+    /// Record a target's environment unless it is already locked
+    ///
+    /// Returns whether the file was changed.
+    ///
+    /// # Errors
+    /// Returns an error if the lock file cannot be read or written
+    pub fn record(path: &Path, target: &str, entry: LockedTarget) -> Result<bool> {
+        let _guard = LOCK_WRITE
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut lock = Self::load(path)?.unwrap_or_default();
+        if lock.targets.contains_key(target) {
+            return Ok(false);
+        }
+        lock.targets.insert(target.to_string(), entry);
+        lock.save(path)?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn native(rust: &str) -> LockedTarget {
+        LockedTarget {
+            strategy: "native".to_string(),
+            rust: Some(rust.to_string()),
+            ..LockedTarget::default()
+        }
+    }
+
+    #[test]
+    fn test_lock_path_is_next_to_project_config() {
+        let temp = TempDir::new().unwrap();
+        let member = temp.path().join("crates").join("cli");
+        std::fs::create_dir_all(&member).unwrap();
+        assert_eq!(lock_path_from(&member), member.join(LOCK_FILE));
+
+        std::fs::write(temp.path().join("xcargo.toml"), "").unwrap();
+        assert_eq!(lock_path_from(&member), temp.path().join(LOCK_FILE));
+    }
+
+    #[test]
+    fn test_record_keeps_locked_entries() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(LOCK_FILE);
+        assert_eq!(LockFile::load(&path).unwrap(), None);
+
+        let target = "x86_64-unknown-linux-musl";
+        assert!(LockFile::record(&path, target, native("1.82.0")).unwrap());
+        assert!(!LockFile::record(&path, target, native("1.83.0")).unwrap());
+
+        let lock = LockFile::load(&path).unwrap().unwrap();
+        assert_eq!(lock.version, LOCK_VERSION);
+        assert_eq!(lock.get(target), Some(&native("1.82.0")));
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("# This file is generated by xcargo"));
+    }
+
+    #[test]
+    fn test_load_rejects_unknown_content() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(LOCK_FILE);
+
+        std::fs::write(&path, "version = 99\n").unwrap();
+        assert!(LockFile::load(&path).is_err());
+
+        std::fs::write(
+            &path,
+            "version = 1\n[targets.wasm32-unknown-unknown]\nstrategy = \"native\"\nrustc = \"1.82.0\"\n",
+        )
+        .unwrap();
+        assert!(LockFile::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_pinned_image() {
+        let locked = |image: &str| LockedTarget {
+            image: Some(image.to_string()),
+            image_digest: Some("sha256:abc".to_string()),
+            ..LockedTarget::default()
+        };
+        assert_eq!(
+            locked("localhost:5000/cross:main")
+                .pinned_image()
+                .as_deref(),
+            Some("localhost:5000/cross@sha256:abc")
+        );
+        assert_eq!(
+            locked("localhost:5000/cross").pinned_image().as_deref(),
+            Some("localhost:5000/cross@sha256:abc")
+        );
+        assert_eq!(native("1.82.0").pinned_image(), None);
+    }
+}
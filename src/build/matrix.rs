@@ -0,0 +1,828 @@
+//! Build matrices: targets × feature sets × profiles
+//!
+//! `xcargo matrix` builds every combination of the configured targets,
+//! feature sets and profiles, and reports the outcome as a grid with a row
+//! per target. Values that select the same build, such as a target and its
+//! alias or `all` and `all,serde`, are merged so each combination runs once.
+//!
+//! `--toolchain-matrix stable,beta,nightly` on build, check and test runs the
+//! operation for every target with each toolchain instead, to catch breakage
+//! in upcoming Rust releases before it reaches stable.
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use crate::output::table::align_columns;
+use crate::target::Target;
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tokio::task;
+
+use super::args::CargoArgs;
+use super::diagnostics::MAX_REPORTED_ERRORS;
+use super::executor::Builder;
+use super::options::BuildOptions;
+use super::report::BuildReport;
+use super::scheduler::{available_jobs, SchedulePlan};
+use super::summary::TargetStatus;
+
+/// Features a matrix cell is built with
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct FeatureSet {
+    /// Every feature (`--all-features`)
+    pub all: bool,
+
+    /// Without the default features (`--no-default-features`)
+    pub no_default: bool,
+
+    /// Features enabled on top (`--features`)
+    pub features: BTreeSet<String>,
+}
+
+impl FeatureSet {
+    /// Cargo arguments selecting the features
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::FeatureSet;
+    ///
+    /// let set: FeatureSet = "no-default, tokio, serde".parse().unwrap();
+    /// assert_eq!(
+    ///     set.cargo_args(),
+    ///     ["--no-default-features", "--features", "serde,tokio"]
+    /// );
+    /// assert_eq!(set.to_string(), "no-default,serde,tokio");
+    /// ```
+    #[must_use]
+    pub fn cargo_args(&self) -> Vec<String> {
+        if self.all {
+            return vec!["--all-features".to_string()];
+        }
+        let mut args = Vec::new();
+        if self.no_default {
+            args.push("--no-default-features".to_string());
+        }
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.iter().cloned().collect::<Vec<_>>().join(","));
+        }
+        args
+    }
+}
+
+impl FromStr for FeatureSet {
+    type Err = String;
+
+    /// Parse a comma-separated feature set; `default`, `no-default` and
+    /// `all` stand for cargo's feature flags
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut set = Self::default();
+        for feature in s.split([',', ' ']).filter(|f| !f.is_empty()) {
+            match feature {
+                "default" => {}
+                "no-default" => set.no_default = true,
+                "all" => set.all = true,
+                feature
+                    if feature.chars().all(|c| {
+                        c.is_alphanumeric() || matches!(c, '-' | '_' | '/' | '+' | '.')
+                    }) =>
+                {
+                    set.features.insert(feature.to_string());
+                }
+                feature => return Err(format!("invalid feature name '{feature}' in '{s}'")),
+            }
+        }
+
+        // Every feature is enabled either way
+        if set.all {
+            set.no_default = false;
+            set.features.clear();
+        }
+        Ok(set)
+    }
+}
+
+impl fmt::Display for FeatureSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.all {
+            return write!(f, "all");
+        }
+        let mut parts: Vec<&str> = Vec::new();
+        if self.no_default {
+            parts.push("no-default");
+        }
+        parts.extend(self.features.iter().map(String::as_str));
+        if parts.is_empty() {
+            write!(f, "default")
+        } else {
+            write!(f, "{}", parts.join(","))
+        }
+    }
+}
+
+/// One build of a matrix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatrixCell {
+    /// Target triple
+    pub target: String,
+
+    /// Features to build with
+    pub features: FeatureSet,
+
+    /// Release profile instead of debug
+    pub release: bool,
+}
+
+impl MatrixCell {
+    /// Column of the grid the cell is shown in, e.g. "no-default/release"
+    #[must_use]
+    pub fn column(&self) -> String {
+        format!("{}/{}", self.features, profile_name(self.release))
+    }
+}
+
+impl fmt::Display for MatrixCell {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.target, self.column())
+    }
+}
+
+/// Targets, feature sets and profiles to build every combination of
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildMatrix {
+    /// Target triples
+    pub targets: Vec<String>,
+
+    /// Feature sets
+    pub feature_sets: Vec<FeatureSet>,
+
+    /// Profiles: `false` for debug, `true` for release
+    pub profiles: Vec<bool>,
+}
+
+impl BuildMatrix {
+    /// The matrix for `config`, with the non-empty arguments replacing the
+    /// `[matrix]` settings
+    ///
+    /// Targets default to `targets.default`, feature sets to the default
+    /// features, and profiles to debug.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no targets, or a target group, feature
+    /// set or profile is invalid.
+    pub fn from_config(
+        config: &Config,
+        targets: &[String],
+        features: &[String],
+        profiles: &[String],
+    ) -> Result<Self> {
+        let pick = |args: &[String], configured: &[String]| {
+            if args.is_empty() {
+                configured.to_vec()
+            } else {
+                args.to_vec()
+            }
+        };
+
+        let mut targets = pick(targets, &config.matrix.targets);
+        if targets.is_empty() {
+            targets.clone_from(&config.targets.default);
+        }
+        let targets = config.expand_targets(&targets)?;
+        if targets.is_empty() {
+            return Err(Error::Config(
+                "No targets for the matrix: set matrix.targets or targets.default in xcargo.toml, or pass --target"
+                    .to_string(),
+            ));
+        }
+
+        let mut features = pick(features, &config.matrix.features);
+        if features.is_empty() {
+            features.push("default".to_string());
+        }
+        let feature_sets = features
+            .iter()
+            .map(|set| set.parse().map_err(Error::Config))
+            .collect::<Result<Vec<FeatureSet>>>()?;
+
+        let mut profiles = pick(profiles, &config.matrix.profiles);
+        if profiles.is_empty() {
+            profiles.push("debug".to_string());
+        }
+        let profiles = profiles
+            .iter()
+            .map(|profile| parse_profile(profile))
+            .collect::<Result<Vec<bool>>>()?;
+
+        Ok(Self::new(targets, feature_sets, profiles))
+    }
+
+    /// A matrix of the given values, without duplicates; target aliases are
+    /// resolved to their triples
+    #[must_use]
+    pub fn new(targets: Vec<String>, feature_sets: Vec<FeatureSet>, profiles: Vec<bool>) -> Self {
+        let targets = targets
+            .into_iter()
+            .map(|target| Target::resolve_alias(&target).unwrap_or(target))
+            .collect();
+        Self {
+            targets: dedup(targets),
+            feature_sets: dedup(feature_sets),
+            profiles: dedup(profiles),
+        }
+    }
+
+    /// Every combination, target by target
+    #[must_use]
+    pub fn cells(&self) -> Vec<MatrixCell> {
+        let mut cells = Vec::new();
+        for target in &self.targets {
+            for features in &self.feature_sets {
+                for &release in &self.profiles {
+                    cells.push(MatrixCell {
+                        target: target.clone(),
+                        features: features.clone(),
+                        release,
+                    });
+                }
+            }
+        }
+        cells
+    }
+
+    /// Render the outcome of each cell as a grid: a row per target and a
+    /// column per feature set and profile
+    #[must_use]
+    pub fn render_grid(&self, results: &[(MatrixCell, TargetStatus)]) -> String {
+        let columns: Vec<(&FeatureSet, bool)> = self
+            .feature_sets
+            .iter()
+            .flat_map(|features| {
+                self.profiles
+                    .iter()
+                    .map(move |&release| (features, release))
+            })
+            .collect();
+
+        let mut rows = vec![std::iter::once("target".to_string())
+            .chain(
+                columns
+                    .iter()
+                    .map(|(features, release)| format!("{features}/{}", profile_name(*release))),
+            )
+            .collect::<Vec<_>>()];
+        for target in &self.targets {
+            let mut row = vec![target.clone()];
+            for (features, release) in &columns {
+                let status = results.iter().find_map(|(cell, status)| {
+                    (cell.target == *target
+                        && cell.features == **features
+                        && cell.release == *release)
+                        .then_some(*status)
+                });
+                row.push(status.map_or("-", |status| status.as_str()).to_string());
+            }
+            rows.push(row);
+        }
+        align_columns(&rows).join("\n")
+    }
+}
+
+/// Outcome of a target run with one toolchain of a toolchain matrix
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct ToolchainCell {
+    pub(super) toolchain: String,
+    pub(super) target: String,
+    pub(super) status: TargetStatus,
+}
+
+/// Outcomes of running an operation for targets with several toolchains
+pub(super) struct ToolchainRuns {
+    /// Toolchains in the order they ran
+    pub(super) toolchains: Vec<String>,
+    /// Column heading per toolchain, with its rustc release when it differs
+    pub(super) columns: Vec<String>,
+    pub(super) targets: Vec<String>,
+    pub(super) results: Vec<ToolchainCell>,
+    /// Label and error summary of each failed run
+    pub(super) failures: Vec<(String, Option<String>)>,
+}
+
+impl ToolchainRuns {
+    /// Grid of outcomes with a row per target and a column per toolchain
+    pub(super) fn grid(&self) -> String {
+        render_toolchain_grid(
+            &self.toolchains,
+            &self.columns,
+            &self.targets,
+            &self.results,
+        )
+    }
+
+    /// Number of runs that did not succeed
+    pub(super) fn failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|cell| cell.status != TargetStatus::Success)
+            .count()
+    }
+}
+
+/// Render toolchain matrix outcomes as a grid: a row per target and a
+/// column per toolchain, headed by `columns`
+fn render_toolchain_grid(
+    toolchains: &[String],
+    columns: &[String],
+    targets: &[String],
+    results: &[ToolchainCell],
+) -> String {
+    let mut rows = vec![std::iter::once("target".to_string())
+        .chain(columns.iter().cloned())
+        .collect::<Vec<_>>()];
+    for target in targets {
+        let mut row = vec![target.clone()];
+        for toolchain in toolchains {
+            let status = results
+                .iter()
+                .find(|cell| cell.target == *target && cell.toolchain == *toolchain)
+                .map(|cell| cell.status);
+            row.push(status.map_or("-", |status| status.as_str()).to_string());
+        }
+        rows.push(row);
+    }
+    align_columns(&rows).join("\n")
+}
+
+/// Targets that succeed with the first toolchain but fail with a later one,
+/// per later toolchain
+fn toolchain_regressions(
+    toolchains: &[String],
+    results: &[ToolchainCell],
+) -> Vec<(String, Vec<String>)> {
+    let Some((baseline, later)) = toolchains.split_first() else {
+        return Vec::new();
+    };
+    let passes = |toolchain: &str, target: &str| {
+        results.iter().any(|cell| {
+            cell.toolchain == toolchain
+                && cell.target == target
+                && cell.status == TargetStatus::Success
+        })
+    };
+    later
+        .iter()
+        .filter_map(|toolchain| {
+            let broken: Vec<String> = results
+                .iter()
+                .filter(|cell| {
+                    cell.toolchain == *toolchain
+                        && cell.status == TargetStatus::Failed
+                        && passes(baseline, &cell.target)
+                })
+                .map(|cell| cell.target.clone())
+                .collect();
+            (!broken.is_empty()).then(|| (toolchain.clone(), broken))
+        })
+        .collect()
+}
+
+/// Read a matrix profile: "debug" or "release"
+fn parse_profile(profile: &str) -> Result<bool> {
+    match profile {
+        "debug" => Ok(false),
+        "release" => Ok(true),
+        other => Err(Error::Config(format!(
+            "Unknown matrix profile: {other}. Must be one of: debug, release"
+        ))),
+    }
+}
+
+fn profile_name(release: bool) -> &'static str {
+    if release {
+        "release"
+    } else {
+        "debug"
+    }
+}
+
+/// Targets in order with aliases resolved, without repeats
+pub(super) fn dedup_targets(targets: &[String]) -> Vec<String> {
+    dedup(
+        targets
+            .iter()
+            .map(|target| Target::resolve_alias(target).unwrap_or_else(|_| target.clone()))
+            .collect(),
+    )
+}
+
+/// `values` in order, without repeats
+fn dedup<T: PartialEq>(values: Vec<T>) -> Vec<T> {
+    let mut unique = Vec::with_capacity(values.len());
+    for value in values {
+        if !unique.contains(&value) {
+            unique.push(value);
+        }
+    }
+    unique
+}
+
+impl Builder {
+    /// Build every cell of `matrix` and print the grid of outcomes
+    ///
+    /// Cells are scheduled like the targets of a parallel build: with
+    /// `build.parallel`, up to [`SchedulePlan::max_concurrent`] at a time,
+    /// each with its share of the jobs; otherwise one after another.
+    pub async fn run_matrix(&self, matrix: &BuildMatrix, options: &BuildOptions) -> Result<()> {
+        helpers::section(format!("xcargo matrix ({})", options.operation.as_str()));
+
+        let cargo_args = CargoArgs::parse(&options.cargo_args)?;
+        if cargo_args
+            .passthrough
+            .iter()
+            .take_while(|arg| *arg != "--")
+            .any(|arg| {
+                arg.starts_with("--features")
+                    || arg == "-F"
+                    || arg == "--all-features"
+                    || arg == "--no-default-features"
+            })
+        {
+            return Err(Error::Config(
+                "Feature flags cannot be passed through to cargo in a matrix; list them with --features instead"
+                    .to_string(),
+            ));
+        }
+
+        let cells = matrix.cells();
+        let labels: Vec<String> = cells.iter().map(ToString::to_string).collect();
+        let config = self.config().clone();
+        let plan = SchedulePlan::new(&labels, available_jobs(config.build.jobs), None);
+        let parallel = config.build.parallel && !crate::output::is_plain();
+        let workers = if parallel { plan.max_concurrent } else { 1 };
+        let inject_jobs = parallel && !cargo_args.sets_jobs();
+
+        helpers::info(format!(
+            "{} targets × {} feature sets × {} profiles: {} builds, up to {workers} at a time",
+            matrix.targets.len(),
+            matrix.feature_sets.len(),
+            matrix.profiles.len(),
+            cells.len()
+        ));
+
+        let queue: VecDeque<(usize, MatrixCell, BuildOptions)> = cells
+            .iter()
+            .enumerate()
+            .map(|(idx, cell)| {
+                let mut cell_options = options.clone();
+                cell_options.target = Some(cell.target.clone());
+                cell_options.release = cell.release;
+                cell_options.cargo_args = cell.features.cargo_args();
+                if inject_jobs {
+                    cell_options.cargo_args.push("--jobs".to_string());
+                    cell_options
+                        .cargo_args
+                        .push(plan.jobs_per_target.to_string());
+                }
+                cell_options.cargo_args.extend(cargo_args.to_args());
+                (idx, cell.clone(), cell_options)
+            })
+            .collect();
+        let queue = Arc::new(Mutex::new(queue));
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let failures = Arc::new(Mutex::new(Vec::new()));
+
+        let mut handles = Vec::new();
+        for _ in 0..workers {
+            let queue = Arc::clone(&queue);
+            let results = Arc::clone(&results);
+            let failures = Arc::clone(&failures);
+            let builder = Builder::with_config(config.clone())?;
+            let total = cells.len();
+
+            handles.push(task::spawn_blocking(move || loop {
+                let Some((idx, cell, cell_options)) = queue.lock().unwrap().pop_front() else {
+                    break;
+                };
+
+                helpers::line("");
+                helpers::info(format!("[{}/{total}] {cell}", idx + 1));
+                helpers::rule();
+
+                let mut report = BuildReport::default();
+                let outcome = builder.execute(&cell_options, &mut report);
+                let status = if outcome.is_ok() {
+                    TargetStatus::Success
+                } else {
+                    TargetStatus::Failed
+                };
+                if let Err(e) = outcome {
+                    helpers::error(format!("Failed to build {cell}: {e}"));
+                    failures.lock().unwrap().push((
+                        idx,
+                        cell.to_string(),
+                        report.diagnostics.error_summary(MAX_REPORTED_ERRORS),
+                    ));
+                }
+                results.lock().unwrap().push((cell, status));
+            }));
+        }
+
+        for handle in handles {
+            handle
+                .await
+                .map_err(|e| Error::Build(format!("Task join error: {e}")))?;
+        }
+
+        let results = results.lock().unwrap();
+        helpers::line("");
+        helpers::line(matrix.render_grid(&results));
+
+        let mut failures = failures.lock().unwrap().clone();
+        if failures.is_empty() {
+            helpers::success(format!("All {} matrix builds succeeded", cells.len()));
+            return Ok(());
+        }
+
+        failures.sort_by_key(|(idx, _, _)| *idx);
+        let failures: Vec<(String, Option<String>)> = failures
+            .into_iter()
+            .map(|(_, label, errors)| (label, errors))
+            .collect();
+        helpers::line("");
+        helpers::error("Failed builds:");
+        Self::report_failures(&failures);
+        Err(Error::Build(format!(
+            "{} of {} matrix builds failed",
+            failures.len(),
+            cells.len()
+        )))
+    }
+
+    /// Run the operation for every target with each toolchain, installing
+    /// missing toolchains, and print a grid with a column per toolchain
+    ///
+    /// The first toolchain is the baseline: targets that work with it but
+    /// fail with a later one (usually beta or nightly) are reported as
+    /// regressions. Runs go one at a time, since every toolchain rebuilds
+    /// the dependencies in the same target directory.
+    ///
+    /// # Errors
+    /// Returns an error if any run failed or a toolchain could not be installed
+    pub fn run_toolchain_matrix(
+        &self,
+        toolchains: &[String],
+        targets: &[String],
+        options: &BuildOptions,
+    ) -> Result<()> {
+        helpers::section(format!(
+            "xcargo {} (toolchain matrix)",
+            options.operation.as_str()
+        ));
+
+        let toolchains = dedup(toolchains.to_vec());
+        let targets = dedup_targets(targets);
+        let total = toolchains.len() * targets.len();
+        helpers::info(format!(
+            "{} toolchains × {} targets: {total} runs",
+            toolchains.len(),
+            targets.len()
+        ));
+
+        let runs = self.run_toolchains(&toolchains, &targets, options);
+        helpers::line("");
+        helpers::line(runs.grid());
+
+        for (toolchain, broken) in toolchain_regressions(&toolchains, &runs.results) {
+            helpers::warning(format!(
+                "Works with {} but fails with {toolchain}: {}",
+                toolchains[0],
+                broken.join(", ")
+            ));
+        }
+        if runs.failures.is_empty() {
+            helpers::success(format!("All {total} toolchain matrix runs succeeded"));
+            return Ok(());
+        }
+
+        helpers::line("");
+        helpers::error("Failed runs:");
+        Self::report_failures(&runs.failures);
+        Err(Error::Build(format!(
+            "{} of {total} toolchain matrix runs failed",
+            runs.failed()
+        )))
+    }
+
+    /// Run the operation for every target with each toolchain, installing
+    /// toolchains rustup doesn't have yet
+    ///
+    /// A toolchain that cannot be installed is recorded as one failure, and
+    /// its targets as not built.
+    pub(super) fn run_toolchains(
+        &self,
+        toolchains: &[String],
+        targets: &[String],
+        options: &BuildOptions,
+    ) -> ToolchainRuns {
+        let operation = options.operation.as_str();
+        let total = toolchains.len() * targets.len();
+        let manager = self.toolchain_manager();
+        let mut runs = ToolchainRuns {
+            toolchains: toolchains.to_vec(),
+            columns: Vec::new(),
+            targets: targets.to_vec(),
+            results: Vec::new(),
+            failures: Vec::new(),
+        };
+        let mut run = 0;
+        for toolchain in toolchains {
+            if let Err(e) = manager.ensure_toolchain(toolchain) {
+                helpers::error(format!("Cannot use toolchain {toolchain}: {e}"));
+                runs.failures.push((format!("toolchain {toolchain}"), None));
+                runs.columns.push(toolchain.clone());
+                runs.results
+                    .extend(targets.iter().map(|target| ToolchainCell {
+                        toolchain: toolchain.clone(),
+                        target: target.clone(),
+                        status: TargetStatus::NotBuilt,
+                    }));
+                run += targets.len();
+                continue;
+            }
+            runs.columns.push(match manager.rustc_release(toolchain) {
+                Some(release) if release != *toolchain => format!("{toolchain} ({release})"),
+                _ => toolchain.clone(),
+            });
+
+            for target in targets {
+                run += 1;
+                let label = format!("{target} with {toolchain}");
+                helpers::line("");
+                helpers::info(format!("[{run}/{total}] {label}"));
+                helpers::rule();
+
+                let mut run_options = options.clone();
+                run_options.target = Some(target.clone());
+                run_options.toolchain = Some(toolchain.clone());
+                let mut report = BuildReport::default();
+                let status = match self.execute(&run_options, &mut report) {
+                    Ok(_) => TargetStatus::Success,
+                    Err(e) => {
+                        helpers::error(format!("Failed to {operation} {label}: {e}"));
+                        runs.failures
+                            .push((label, report.diagnostics.error_summary(MAX_REPORTED_ERRORS)));
+                        TargetStatus::Failed
+                    }
+                };
+                runs.results.push(ToolchainCell {
+                    toolchain: toolchain.clone(),
+                    target: target.clone(),
+                    status,
+                });
+            }
+        }
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(s: &str) -> FeatureSet {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_feature_set_parse() {
+        assert_eq!(set(""), FeatureSet::default());
+        assert_eq!(set("default"), FeatureSet::default());
+        assert_eq!(set("all, serde"), set("no-default,all"));
+        assert_eq!(set("serde,tokio"), set("tokio serde serde"));
+        assert_eq!(set("serde/derive").to_string(), "serde/derive");
+        assert_eq!(set("all").cargo_args(), ["--all-features"]);
+        assert!(set("").cargo_args().is_empty());
+        assert!("serde;rm".parse::<FeatureSet>().is_err());
+    }
+
+    #[test]
+    fn test_matrix_dedups_equivalent_cells() {
+        let matrix = BuildMatrix::new(
+            vec![
+                "aarch64-unknown-linux-gnu".to_string(),
+                "x86_64-pc-windows-gnu".to_string(),
+                "aarch64-unknown-linux-gnu".to_string(),
+            ],
+            vec![set("default"), set(""), set("all"), set("all,serde")],
+            vec![false, true, false],
+        );
+        assert_eq!(matrix.targets.len(), 2);
+        assert_eq!(matrix.feature_sets, [set("default"), set("all")]);
+        assert_eq!(matrix.profiles, [false, true]);
+
+        let cells = matrix.cells();
+        assert_eq!(cells.len(), 8);
+        assert_eq!(
+            cells[3].to_string(),
+            "aarch64-unknown-linux-gnu all/release"
+        );
+    }
+
+    #[test]
+    fn test_from_config() {
+        let config = Config::from_str(
+            r#"
+            [targets]
+            default = ["x86_64-unknown-linux-gnu"]
+
+            [matrix]
+            features = ["default", "no-default"]
+            profiles = ["debug", "release"]
+            "#,
+        )
+        .unwrap();
+
+        let matrix = BuildMatrix::from_config(&config, &[], &[], &[]).unwrap();
+        assert_eq!(matrix.targets, ["x86_64-unknown-linux-gnu"]);
+        assert_eq!(matrix.cells().len(), 4);
+
+        let matrix = BuildMatrix::from_config(&config, &[], &["all".to_string()], &[]).unwrap();
+        assert_eq!(matrix.feature_sets, [set("all")]);
+
+        assert!(BuildMatrix::from_config(&config, &[], &[], &["bench".to_string()]).is_err());
+        assert!(BuildMatrix::from_config(&Config::default(), &[], &[], &[]).is_err());
+    }
+
+    #[test]
+    fn test_render_grid() {
+        let matrix = BuildMatrix::new(
+            vec!["a".to_string(), "b".to_string()],
+            vec![set("default"), set("no-default")],
+            vec![false],
+        );
+        let results: Vec<(MatrixCell, TargetStatus)> = matrix
+            .cells()
+            .into_iter()
+            .map(|cell| {
+                let status = if cell.target == "b" && cell.features.no_default {
+                    TargetStatus::Failed
+                } else {
+                    TargetStatus::Success
+                };
+                (cell, status)
+            })
+            .collect();
+
+        let grid = matrix.render_grid(&results);
+        let lines: Vec<&str> = grid.lines().collect();
+        assert_eq!(lines[0], "target  default/debug  no-default/debug");
+        assert_eq!(lines[1], "a       ok             ok");
+        assert_eq!(lines[2], "b       ok             failed");
+    }
+
+    fn toolchain_cell(toolchain: &str, target: &str, status: TargetStatus) -> ToolchainCell {
+        ToolchainCell {
+            toolchain: toolchain.to_string(),
+            target: target.to_string(),
+            status,
+        }
+    }
+
+    #[test]
+    fn test_toolchain_grid_and_regressions() {
+        let toolchains = vec!["stable".to_string(), "nightly".to_string()];
+        let targets = vec![
+            "x86_64-unknown-linux-gnu".to_string(),
+            "aarch64-unknown-linux-gnu".to_string(),
+        ];
+        let results = vec![
+            toolchain_cell("stable", &targets[0], TargetStatus::Success),
+            toolchain_cell("stable", &targets[1], TargetStatus::Failed),
+            toolchain_cell("nightly", &targets[0], TargetStatus::Failed),
+            toolchain_cell("nightly", &targets[1], TargetStatus::Failed),
+        ];
+        let columns = vec!["stable (1.80.0)".to_string(), "nightly".to_string()];
+
+        let grid = render_toolchain_grid(&toolchains, &columns, &targets, &results);
+        let lines: Vec<&str> = grid.lines().collect();
+        assert_eq!(
+            lines[0],
+            "target                     stable (1.80.0)  nightly"
+        );
+        assert_eq!(
+            lines[1],
+            "x86_64-unknown-linux-gnu   ok               failed"
+        );
+
+        // aarch64 is broken on stable too, so only x86_64 regressed
+        assert_eq!(
+            toolchain_regressions(&toolchains, &results),
+            vec![("nightly".to_string(), vec![targets[0].clone()])]
+        );
+        assert!(toolchain_regressions(&toolchains[..1], &results).is_empty());
+    }
+}
@@ -3,10 +3,106 @@
 //! This module handles the actual build process, including invoking cargo
 //! with the appropriate flags for cross-compilation.
 
+mod affected;
+mod args;
+mod artifacts;
+mod attest;
+mod audit;
+mod bench;
+mod binary;
+mod bisect;
+mod bloat;
+mod cancel;
+mod cargo_config;
+mod cargo_progress;
+mod clean;
+mod collect;
+mod debuginfo;
+mod deploy;
+mod diagnostics;
+mod doc;
+pub mod events;
 mod executor;
+mod fallback;
+mod hooks;
+mod ide;
+mod install;
+mod libtest;
+mod linkage;
+mod lock;
+mod matrix;
+mod msrv;
 mod options;
+#[cfg(feature = "container")]
+mod package;
 mod parallel;
+mod plan;
+mod postprocess;
+mod prune;
+mod report;
+mod runner;
+mod rustflags;
+mod scheduler;
+mod sign;
+mod stats;
+mod summary;
+mod target_output;
+mod tasks;
+mod wasm;
 
 // Re-export public types
+pub use affected::{changed_files, Affected, IncrementalPlan, WorkspaceGraph};
+pub use args::CargoArgs;
+pub use artifacts::ARTIFACTS_FILE;
+pub use attest::{BuildHost, Provenance};
+pub use audit::{DependencyAudit, Finding, Severity};
+pub use bench::{parse_bench_output, BenchResult, BenchTable};
+pub use bisect::{
+    bisect, bisect_check_args, check_revision, BisectVerdict, FirstBadCommit, BISECT_CHECK_COMMAND,
+};
+pub use bloat::{render_size_comparison, CrateSize, SectionSize, SizeReport, SymbolSize};
+pub use cargo_progress::UnitProgress;
+pub use clean::{remove_clean_items, target_clean_items, CleanItem};
+pub use collect::{
+    collected_manifests, run_collected, CollectedTest, TestManifest, TEST_MANIFEST,
+};
+pub use debuginfo::SYMBOLS_DIR;
+pub use deploy::DeployRequest;
+pub use diagnostics::{
+    format_counts, CargoMessage, Diagnostic, DiagnosticLevel, Diagnostics, MAX_REPORTED_ERRORS,
+};
+pub use doc::{doc_dir, merge_docs, merged_doc_dir, MergedDocs, MERGED_DOC_DIR};
+pub use events::BuildEvent;
 pub use executor::Builder;
-pub use options::{BuildOptions, CargoOperation};
+pub use ide::{merge_settings, rust_analyzer_settings, VSCODE_SETTINGS};
+pub use install::InstallRequest;
+pub use libtest::{parse_test_output, TestCase, TestOutcome, TestReport};
+pub use lock::{lock_path, LockFile, LockedTarget, LOCK_FILE};
+pub use matrix::{BuildMatrix, FeatureSet, MatrixCell};
+pub use options::{ArtifactSelection, BuildOptions, BuildStrategy, CargoOperation, NetworkFlags};
+#[cfg(feature = "container")]
+pub use package::{image_platform, ImagePackage, DISTROLESS_CC, DISTROLESS_STATIC};
+pub use plan::TargetPlan;
+pub use postprocess::PostStep;
+pub use prune::{
+    parse_size, remove_artifact_dirs, scan_artifact_dirs, ArtifactDir, RetentionPolicy,
+};
+pub use report::BuildReport;
+pub use runner::{
+    events_path, format_duration, parse_duration, read_events, run_with_policy, RunnerEvent,
+    RunnerEventKind, RunnerPolicy, RunnerReport, RUNNER_EVENTS_ENV, RUN_TEST_COMMAND,
+    TIMEOUT_EXIT_CODE,
+};
+pub use scheduler::{available_jobs, SchedulePlan};
+pub use sign::{CERTIFICATE_ENV, IDENTITY_ENV};
+pub use stats::{
+    format_trend, load_records, parse_since, render_stats, summarize, BuildRecord, TargetStats,
+    STATS_FILE,
+};
+pub use summary::{
+    artifact_size, format_size_delta, isolated_target_dir, render_summary, target_dir,
+    target_dir_for, use_target_dir_config, BuildHistory, HistoryEntry, TargetResult,
+    TargetStatus, HISTORY_FILE, ISOLATED_DIR,
+};
+pub use tasks::{render_tasks, task_matrix, Task, TaskFormat};
+pub use tokio_util::sync::CancellationToken;
@@ -0,0 +1,83 @@
+//! Checking the minimum supported Rust version for every target
+//!
+//! Code behind `cfg(target_os = ...)` is only compiled for its targets, so a
+//! newer API used there breaks the MSRV without a host build noticing.
+//! `xcargo msrv verify` checks each configured target with the toolchain of
+//! the `rust-version` in Cargo.toml.
+
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use crate::toolchain::RustVersion;
+
+use super::executor::Builder;
+use super::matrix::dedup_targets;
+use super::options::BuildOptions;
+
+impl Builder {
+    /// Run the operation in `options` (usually check) for every target with
+    /// the MSRV toolchain, installing it if needed
+    ///
+    /// # Errors
+    /// Returns an error if the toolchain cannot be installed or any target
+    /// fails with it
+    pub fn verify_msrv(
+        &self,
+        msrv: &RustVersion,
+        targets: &[String],
+        options: &BuildOptions,
+    ) -> Result<()> {
+        let toolchain = msrv.toolchain();
+        helpers::section(format!("xcargo msrv verify (Rust {toolchain})"));
+        helpers::info(format!(
+            "rust-version = \"{}\" in {}",
+            msrv.version,
+            msrv.manifest.display()
+        ));
+
+        self.toolchain_manager().ensure_toolchain(&toolchain)?;
+
+        let targets = dedup_targets(targets);
+        let runs = self.run_toolchains(std::slice::from_ref(&toolchain), &targets, options);
+        helpers::line("");
+        helpers::line(runs.grid());
+
+        if runs.failures.is_empty() {
+            helpers::success(match targets.len() {
+                1 => format!("{} works with the MSRV, Rust {toolchain}", targets[0]),
+                n => format!("All {n} targets work with the MSRV, Rust {toolchain}"),
+            });
+            return Ok(());
+        }
+
+        // Targets rustc reported errors for; others failed for reasons such
+        // as a target that could not be installed
+        let suffix = format!(" with {toolchain}");
+        let violating: Vec<&str> = runs
+            .failures
+            .iter()
+            .filter(|(_, errors)| errors.is_some())
+            .filter_map(|(label, _)| label.strip_suffix(&suffix))
+            .collect();
+        if !violating.is_empty() {
+            helpers::warning(format!(
+                "Need a newer Rust than rust-version {}: {}",
+                msrv.version,
+                violating.join(", ")
+            ));
+        }
+        helpers::line("");
+        helpers::error("Failed runs:");
+        Self::report_failures(&runs.failures);
+        if !violating.is_empty() {
+            helpers::hint(format!(
+                "Raise rust-version in {}, or avoid the newer APIs in these targets' code paths",
+                msrv.manifest.display()
+            ));
+        }
+        Err(Error::Build(format!(
+            "{} of {} targets fail with the MSRV, Rust {toolchain}",
+            runs.failed(),
+            targets.len()
+        )))
+    }
+}
@@ -1,5 +1,9 @@
 //! Build options and cargo operations
 
+use crate::config::ParallelOutput;
+use crate::target::Target;
+use std::path::{Path, PathBuf};
+
 /// Cargo operation type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum CargoOperation {
@@ -10,6 +14,12 @@ pub enum CargoOperation {
     Check,
     /// cargo test
     Test,
+    /// cargo bench
+    Bench,
+    /// cargo doc
+    Doc,
+    /// cargo install, of a crate rather than the current project
+    Install,
 }
 
 impl CargoOperation {
@@ -20,6 +30,9 @@ impl CargoOperation {
             CargoOperation::Build => "build",
             CargoOperation::Check => "check",
             CargoOperation::Test => "test",
+            CargoOperation::Bench => "bench",
+            CargoOperation::Doc => "doc",
+            CargoOperation::Install => "install",
         }
     }
 
@@ -30,12 +43,224 @@ impl CargoOperation {
             CargoOperation::Build => "Building",
             CargoOperation::Check => "Checking",
             CargoOperation::Test => "Testing",
+            CargoOperation::Bench => "Benchmarking",
+            CargoOperation::Doc => "Documenting",
+            CargoOperation::Install => "Installing",
+        }
+    }
+}
+
+/// Cross-compilation strategy used for a build
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildStrategy {
+    /// Host or system cross toolchain
+    Native,
+    /// Zig as C compiler and linker
+    Zig,
+    /// Rustc's bundled rust-lld, for targets that link without a system linker
+    RustLld,
+    /// Container image with a prepared toolchain
+    Container,
+}
+
+impl BuildStrategy {
+    /// Get the strategy name as used in configuration
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BuildStrategy::Native => "native",
+            BuildStrategy::Zig => "zig",
+            BuildStrategy::RustLld => "rust-lld",
+            BuildStrategy::Container => "container",
+        }
+    }
+
+    /// Parse a strategy name from configuration
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "native" => Some(BuildStrategy::Native),
+            "zig" => Some(BuildStrategy::Zig),
+            "rust-lld" => Some(BuildStrategy::RustLld),
+            "container" => Some(BuildStrategy::Container),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for BuildStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Cargo's lockfile and network flags (`--locked`, `--offline`, `--frozen`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NetworkFlags {
+    /// Require `Cargo.lock` to be up to date
+    pub locked: bool,
+
+    /// Don't access the network
+    pub offline: bool,
+}
+
+impl NetworkFlags {
+    /// Combine the command-line flags; `--frozen` is `--locked` plus `--offline`
+    #[must_use]
+    pub fn new(locked: bool, offline: bool, frozen: bool) -> Self {
+        Self {
+            locked: locked || frozen,
+            offline: offline || frozen,
+        }
+    }
+
+    /// Put the flags in front of `cargo_args`, leaving out ones already passed through
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::NetworkFlags;
+    ///
+    /// let flags = NetworkFlags::new(false, false, true);
+    /// let args = flags.with_cargo_args(vec!["--features".to_string(), "simd".to_string()]);
+    /// assert_eq!(args, ["--frozen", "--features", "simd"]);
+    /// ```
+    #[must_use]
+    pub fn with_cargo_args(&self, cargo_args: Vec<String>) -> Vec<String> {
+        let passed = |flag: &str| {
+            cargo_args
+                .iter()
+                .take_while(|arg| *arg != "--")
+                .any(|arg| arg == flag || arg == "--frozen")
+        };
+        let flags: &[&str] = match (self.locked, self.offline) {
+            (true, true) => &["--frozen"],
+            (true, false) => &["--locked"],
+            (false, true) => &["--offline"],
+            (false, false) => &[],
+        };
+
+        let mut args: Vec<String> = flags
+            .iter()
+            .filter(|flag| !passed(flag))
+            .map(|flag| (*flag).to_string())
+            .collect();
+        args.extend(cargo_args);
+        args
+    }
+}
+
+/// Packages and cargo targets selected with `-p`, `--bin`, `--example` and `--lib`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ArtifactSelection {
+    /// Packages to build (`-p`/`--package`)
+    pub packages: Vec<String>,
+
+    /// Binaries to build (`--bin`)
+    pub bins: Vec<String>,
+
+    /// Examples to build (`--example`)
+    pub examples: Vec<String>,
+
+    /// Build the package's library (`--lib`)
+    pub lib: bool,
+}
+
+impl ArtifactSelection {
+    /// Whether nothing is selected, so cargo builds its default targets
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty() && self.bins.is_empty() && self.examples.is_empty() && !self.lib
+    }
+
+    /// The selection as cargo arguments
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::ArtifactSelection;
+    ///
+    /// let selection = ArtifactSelection {
+    ///     packages: vec!["cli".to_string()],
+    ///     bins: vec!["app".to_string()],
+    ///     ..ArtifactSelection::default()
+    /// };
+    /// assert_eq!(selection.to_args(), ["--package", "cli", "--bin", "app"]);
+    /// ```
+    #[must_use]
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        for (flag, names) in [
+            ("--package", &self.packages),
+            ("--bin", &self.bins),
+            ("--example", &self.examples),
+        ] {
+            for name in names {
+                args.push(flag.to_string());
+                args.push(name.clone());
+            }
+        }
+        if self.lib {
+            args.push("--lib".to_string());
         }
+        args
+    }
+
+    /// Human-readable description of the selection, if anything is selected
+    #[must_use]
+    pub fn description(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        for (kind, names) in [
+            ("packages", &self.packages),
+            ("binaries", &self.bins),
+            ("examples", &self.examples),
+        ] {
+            if !names.is_empty() {
+                parts.push(format!("{kind} {}", names.join(", ")));
+            }
+        }
+        if self.lib {
+            parts.push("library".to_string());
+        }
+        (!parts.is_empty()).then(|| parts.join("; "))
+    }
+
+    /// Paths of the selected binaries and examples once built for `target`
+    ///
+    /// Libraries are left out: their file names depend on the crate type.
+    #[must_use]
+    pub fn artifact_paths(
+        &self,
+        target_dir: &Path,
+        target: &Target,
+        release: bool,
+    ) -> Vec<PathBuf> {
+        let profile = if release { "release" } else { "debug" };
+        let profile_dir = target_dir.join(&target.triple).join(profile);
+        let suffix = if target.os == "windows" {
+            ".exe"
+        } else if target.is_wasm() {
+            ".wasm"
+        } else {
+            ""
+        };
+
+        let bins = self
+            .bins
+            .iter()
+            .map(|bin| profile_dir.join(format!("{bin}{suffix}")));
+        let examples = self.examples.iter().map(|example| {
+            profile_dir
+                .join("examples")
+                .join(format!("{example}{suffix}"))
+        });
+        bins.chain(examples).collect()
     }
 }
 
 /// Build options and configuration
 #[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct BuildOptions {
     /// Target triple to build for
     pub target: Option<String>,
@@ -58,8 +283,36 @@ pub struct BuildOptions {
     /// Zig preference: None = auto, Some(true) = force, Some(false) = disable
     pub use_zig: Option<bool>,
 
-    /// Cargo operation (build, check, test)
+    /// Cargo operation (build, check, test, bench, install)
     pub operation: CargoOperation,
+
+    /// Git ref to compare against; multi-target builds then only build
+    /// the workspace members affected by changes since it
+    pub affected_since: Option<String>,
+
+    /// Install missing toolchain components without asking
+    pub assume_yes: bool,
+
+    /// Copy the test binaries of a `--no-run` test build into this directory
+    pub collect_tests: Option<PathBuf>,
+
+    /// Write a provenance record next to each built artifact
+    pub attest: bool,
+
+    /// Packages and cargo targets to build; everything cargo builds by default if empty
+    pub selection: ArtifactSelection,
+
+    /// Stop a multi-target build at the first failed target; None uses
+    /// `build.fail_fast`
+    pub fail_fast: Option<bool>,
+
+    /// How cargo's output is shown in a parallel build; None uses
+    /// `build.output`
+    pub output: Option<ParallelOutput>,
+
+    /// Build with the versions locked in `xcargo.lock`, and lock targets
+    /// built for the first time
+    pub lock: bool,
 }
 
 impl Default for BuildOptions {
@@ -73,6 +326,14 @@ impl Default for BuildOptions {
             use_container: false,
             use_zig: None,
             operation: CargoOperation::Build,
+            affected_since: None,
+            assume_yes: false,
+            collect_tests: None,
+            attest: false,
+            selection: ArtifactSelection::default(),
+            fail_fast: None,
+            output: None,
+            lock: true,
         }
     }
 }
@@ -86,6 +347,9 @@ mod tests {
         assert_eq!(CargoOperation::Build.as_str(), "build");
         assert_eq!(CargoOperation::Check.as_str(), "check");
         assert_eq!(CargoOperation::Test.as_str(), "test");
+        assert_eq!(CargoOperation::Bench.as_str(), "bench");
+        assert_eq!(CargoOperation::Doc.as_str(), "doc");
+        assert_eq!(CargoOperation::Install.as_str(), "install");
     }
 
     #[test]
@@ -93,6 +357,21 @@ mod tests {
         assert_eq!(CargoOperation::Build.description(), "Building");
         assert_eq!(CargoOperation::Check.description(), "Checking");
         assert_eq!(CargoOperation::Test.description(), "Testing");
+        assert_eq!(CargoOperation::Bench.description(), "Benchmarking");
+        assert_eq!(CargoOperation::Doc.description(), "Documenting");
+    }
+
+    #[test]
+    fn test_build_strategy_names() {
+        for strategy in [
+            BuildStrategy::Native,
+            BuildStrategy::Zig,
+            BuildStrategy::RustLld,
+            BuildStrategy::Container,
+        ] {
+            assert_eq!(BuildStrategy::from_name(strategy.as_str()), Some(strategy));
+        }
+        assert_eq!(BuildStrategy::from_name("qemu"), None);
     }
 
     #[test]
@@ -102,4 +381,78 @@ mod tests {
         assert!(!options.release);
         assert!(options.cargo_args.is_empty());
     }
+
+    #[test]
+    fn test_artifact_selection() {
+        assert!(ArtifactSelection::default().is_empty());
+        assert_eq!(ArtifactSelection::default().description(), None);
+
+        let selection = ArtifactSelection {
+            packages: vec!["cli".to_string()],
+            bins: vec!["app".to_string()],
+            examples: vec!["demo".to_string()],
+            lib: true,
+        };
+        assert_eq!(
+            selection.to_args(),
+            [
+                "--package",
+                "cli",
+                "--bin",
+                "app",
+                "--example",
+                "demo",
+                "--lib"
+            ]
+        );
+        assert_eq!(
+            selection.description().unwrap(),
+            "packages cli; binaries app; examples demo; library"
+        );
+
+        let windows = Target::from_triple("x86_64-pc-windows-gnu").unwrap();
+        assert_eq!(
+            selection.artifact_paths(Path::new("target"), &windows, true),
+            [
+                PathBuf::from("target/x86_64-pc-windows-gnu/release/app.exe"),
+                PathBuf::from("target/x86_64-pc-windows-gnu/release/examples/demo.exe"),
+            ]
+        );
+        let linux = Target::from_triple("aarch64-unknown-linux-gnu").unwrap();
+        assert_eq!(
+            selection.artifact_paths(Path::new("target"), &linux, false)[0],
+            PathBuf::from("target/aarch64-unknown-linux-gnu/debug/app")
+        );
+    }
+
+    #[test]
+    fn test_network_flags() {
+        let args = |list: &[&str]| list.iter().map(|s| (*s).to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            NetworkFlags::new(true, false, false).with_cargo_args(args(&["--", "--nocapture"])),
+            args(&["--locked", "--", "--nocapture"])
+        );
+        assert_eq!(
+            NetworkFlags::new(false, true, false).with_cargo_args(Vec::new()),
+            args(&["--offline"])
+        );
+        assert_eq!(
+            NetworkFlags::new(true, true, false),
+            NetworkFlags::new(false, false, true)
+        );
+
+        // Flags already passed through are not repeated
+        assert_eq!(
+            NetworkFlags::new(true, false, false).with_cargo_args(args(&["--locked"])),
+            args(&["--locked"])
+        );
+        assert_eq!(
+            NetworkFlags::new(false, true, false).with_cargo_args(args(&["--frozen"])),
+            args(&["--frozen"])
+        );
+        assert!(NetworkFlags::default()
+            .with_cargo_args(Vec::new())
+            .is_empty());
+    }
 }
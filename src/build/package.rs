@@ -0,0 +1,386 @@
+//! Container images of built binaries
+//!
+//! `xcargo package docker` builds a Linux target for release and packages
+//! its binaries into a minimal image, based on distroless or `scratch`, with
+//! the configured container runtime. The image is tagged for the target's
+//! platform and can be pushed to a registry.
+//!
+//! Repositories, tags and base images are configured under
+//! `[package.docker]`.
+
+use crate::config::DockerPackageConfig;
+use crate::container::{ContainerBuilder, ImageBuild, ImageSelector, RuntimeType};
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use crate::target::Target;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+use super::deploy::select_binaries;
+use super::executor::Builder;
+use super::options::BuildOptions;
+
+/// Base image for statically linked (musl) binaries
+pub const DISTROLESS_STATIC: &str = "gcr.io/distroless/static-debian12";
+
+/// Base image for binaries linked against glibc
+pub const DISTROLESS_CC: &str = "gcr.io/distroless/cc-debian12";
+
+/// Where the binaries are copied in the image
+const IMAGE_BIN_DIR: &str = "/usr/local/bin";
+
+/// Unprivileged user the distroless images define as "nonroot"
+const IMAGE_USER: &str = "65532:65532";
+
+/// Image to package a build into
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImagePackage {
+    /// Image repository, e.g. "ghcr.io/acme/gateway"
+    pub repository: String,
+
+    /// Tags of the image, e.g. "1.2.0" and "latest"
+    pub tags: Vec<String>,
+
+    /// Base image: "distroless", "scratch", or an image reference; distroless
+    /// when unset
+    pub base: Option<String>,
+
+    /// Binary the image runs
+    pub bin: Option<String>,
+
+    /// Build the target before packaging
+    pub build: bool,
+
+    /// Push the image after building it
+    pub push: bool,
+}
+
+impl ImagePackage {
+    /// Image configured under `[package.docker]`, named after the package in
+    /// `manifest` where the repository or tags are not set
+    ///
+    /// # Errors
+    /// Returns an error if no repository is configured and the manifest has
+    /// no package name
+    pub fn from_config(config: &DockerPackageConfig, manifest: &Path) -> Result<Self> {
+        let package = manifest_package(manifest);
+        let repository = match (&config.repository, &package) {
+            (Some(repository), _) => repository.clone(),
+            (None, Some((name, _))) => name.to_lowercase(),
+            (None, None) => {
+                return Err(Error::Config(format!(
+                    "No image repository; set package.docker.repository in xcargo.toml or run xcargo package in a package with {}",
+                    manifest.display()
+                )))
+            }
+        };
+        let tags = if config.tags.is_empty() {
+            let version = package.map(|(_, version)| version);
+            version.into_iter().chain(["latest".to_string()]).collect()
+        } else {
+            config.tags.clone()
+        };
+
+        Ok(Self {
+            repository,
+            tags,
+            base: config.base.clone(),
+            bin: config.bin.clone(),
+            build: true,
+            push: false,
+        })
+    }
+
+    /// Full references of the image, one per tag
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::ImagePackage;
+    ///
+    /// let package = ImagePackage {
+    ///     repository: "ghcr.io/acme/gateway".to_string(),
+    ///     tags: vec!["1.2.0".to_string(), "latest".to_string()],
+    ///     base: None,
+    ///     bin: None,
+    ///     build: true,
+    ///     push: false,
+    /// };
+    /// assert_eq!(
+    ///     package.references(),
+    ///     ["ghcr.io/acme/gateway:1.2.0", "ghcr.io/acme/gateway:latest"]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn references(&self) -> Vec<String> {
+        self.tags
+            .iter()
+            .map(|tag| format!("{}:{tag}", self.repository))
+            .collect()
+    }
+}
+
+impl Builder {
+    /// Build a target for release and package its binaries into an image
+    ///
+    /// `options.target` selects the target, which must be a Linux target.
+    /// Returns the references the image was tagged with.
+    ///
+    /// # Errors
+    /// Returns an error if no target is given or it has no image platform,
+    /// the build fails, there are no binaries to package, or building or
+    /// pushing the image fails
+    pub fn package_image(
+        &self,
+        package: &ImagePackage,
+        options: &BuildOptions,
+    ) -> Result<Vec<String>> {
+        let Some(target) = options.target.as_deref() else {
+            return Err(Error::Config(
+                "xcargo package docker needs a target, e.g. --target x86_64-unknown-linux-musl"
+                    .to_string(),
+            ));
+        };
+        let target = Target::from_triple(target)?;
+        let platform = image_platform(&target)?;
+        let base = base_image(&target, package.base.as_deref());
+        if base == "scratch" && !is_static(&target) {
+            helpers::warning(format!(
+                "{} binaries link against glibc, which the scratch image does not have; use a musl target or the distroless base",
+                target.triple
+            ));
+        }
+
+        if package.build {
+            self.build(&BuildOptions {
+                release: true,
+                ..options.clone()
+            })?;
+        }
+        let binaries = select_binaries(
+            &self.artifact_paths(&target.triple, "release"),
+            package.bin.as_deref(),
+        );
+        if binaries.is_empty() {
+            let what = package
+                .bin
+                .as_ref()
+                .map_or_else(|| "binaries".to_string(), |bin| format!("binary '{bin}'"));
+            return Err(Error::Build(format!(
+                "No {what} in the release build of {0}; build it with 'xcargo build --target {0} --release'",
+                target.triple
+            )));
+        }
+
+        let runtime_type =
+            RuntimeType::from_str(&self.config().container.runtime).unwrap_or(RuntimeType::Auto);
+        let runtime = ContainerBuilder::new(runtime_type)?
+            .with_image_selector(ImageSelector::from_config(&self.config().container));
+
+        helpers::section(format!("Packaging {}", package.repository));
+        let references = package.references();
+        let context = std::env::temp_dir().join(format!("xcargo-image-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&context);
+        let built =
+            write_context(&context, base, &binaries, package.bin.as_deref()).and_then(|()| {
+                runtime.build_image(&ImageBuild {
+                    context: context.clone(),
+                    tags: references.clone(),
+                    platform: Some(platform.to_string()),
+                })
+            });
+        let _ = std::fs::remove_dir_all(&context);
+        built?;
+        for reference in &references {
+            helpers::success(format!("Built {reference} ({platform}, from {base})"));
+        }
+
+        if package.push {
+            for reference in &references {
+                runtime.push_image(reference)?;
+                helpers::success(format!("Pushed {reference}"));
+            }
+        }
+        Ok(references)
+    }
+}
+
+/// OCI platform of a Linux target, e.g. "linux/arm64"
+///
+/// # Errors
+/// Returns an error for targets that do not run in Linux containers
+pub fn image_platform(target: &Target) -> Result<&'static str> {
+    let linux = target.os == "linux" && !target.triple.contains("android");
+    let platform = match target.arch.as_str() {
+        _ if !linux => None,
+        "x86_64" => Some("linux/amd64"),
+        "aarch64" => Some("linux/arm64"),
+        "armv7" | "thumbv7neon" => Some("linux/arm/v7"),
+        "arm" => Some("linux/arm/v6"),
+        "i686" | "i586" => Some("linux/386"),
+        "riscv64gc" => Some("linux/riscv64"),
+        "s390x" => Some("linux/s390x"),
+        "powerpc64le" => Some("linux/ppc64le"),
+        _ => None,
+    };
+    platform.ok_or_else(|| {
+        Error::Container(format!(
+            "{} has no container image platform; package a Linux target such as x86_64-unknown-linux-musl",
+            target.triple
+        ))
+    })
+}
+
+/// Image to base the package on, from the configured base
+fn base_image<'a>(target: &Target, base: Option<&'a str>) -> &'a str {
+    match base {
+        None | Some("distroless") if is_static(target) => DISTROLESS_STATIC,
+        None | Some("distroless") => DISTROLESS_CC,
+        Some(base) => base,
+    }
+}
+
+/// Whether the target's binaries are statically linked by default
+fn is_static(target: &Target) -> bool {
+    target
+        .env
+        .as_deref()
+        .is_some_and(|env| env.starts_with("musl"))
+}
+
+/// Copy the binaries into `context` next to a Dockerfile packaging them
+fn write_context(
+    context: &Path,
+    base: &str,
+    binaries: &[PathBuf],
+    bin: Option<&str>,
+) -> Result<()> {
+    std::fs::create_dir_all(context)?;
+    let mut names = Vec::new();
+    for binary in binaries {
+        let Some(name) = binary.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        std::fs::copy(binary, context.join(name))?;
+        names.push(name.to_string());
+    }
+    let package = manifest_package(Path::new("Cargo.toml")).map(|(name, _)| name);
+    let entrypoint = entrypoint(&names, bin.or(package.as_deref()));
+    std::fs::write(
+        context.join("Dockerfile"),
+        dockerfile(base, &names, entrypoint),
+    )?;
+    Ok(())
+}
+
+/// Binary the image runs: `preferred` if it was packaged, or the only one
+fn entrypoint<'a>(names: &'a [String], preferred: Option<&str>) -> Option<&'a str> {
+    match names {
+        [only] => Some(only),
+        _ => names
+            .iter()
+            .find(|name| Some(name.as_str()) == preferred)
+            .map(String::as_str),
+    }
+}
+
+/// Dockerfile copying `binaries` from the build context onto `base`
+fn dockerfile(base: &str, binaries: &[String], entrypoint: Option<&str>) -> String {
+    let mut dockerfile = format!("FROM {base}\n");
+    for name in binaries {
+        let _ = writeln!(dockerfile, "COPY {name} {IMAGE_BIN_DIR}/{name}");
+    }
+    let _ = writeln!(dockerfile, "USER {IMAGE_USER}");
+    if let Some(entrypoint) = entrypoint {
+        let _ = writeln!(dockerfile, "ENTRYPOINT [\"{IMAGE_BIN_DIR}/{entrypoint}\"]");
+    }
+    dockerfile
+}
+
+/// Name and version of the package in `manifest`
+fn manifest_package(manifest: &Path) -> Option<(String, String)> {
+    let manifest: toml::Table = std::fs::read_to_string(manifest).ok()?.parse().ok()?;
+    let package = manifest.get("package")?;
+    let name = package.get("name")?.as_str()?.to_string();
+    let version = package
+        .get("version")
+        .and_then(toml::Value::as_str)
+        .unwrap_or("0.0.0")
+        .to_string();
+    Some((name, version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(triple: &str) -> Target {
+        Target::from_triple(triple).unwrap()
+    }
+
+    #[test]
+    fn test_image_platform() {
+        let platform = |triple| image_platform(&target(triple)).unwrap();
+        assert_eq!(platform("x86_64-unknown-linux-musl"), "linux/amd64");
+        assert_eq!(platform("aarch64-unknown-linux-gnu"), "linux/arm64");
+        assert_eq!(platform("armv7-unknown-linux-gnueabihf"), "linux/arm/v7");
+        assert_eq!(platform("arm-unknown-linux-gnueabihf"), "linux/arm/v6");
+        assert_eq!(platform("riscv64gc-unknown-linux-gnu"), "linux/riscv64");
+        assert!(image_platform(&target("x86_64-pc-windows-gnu")).is_err());
+        assert!(image_platform(&target("aarch64-linux-android")).is_err());
+    }
+
+    #[test]
+    fn test_base_image() {
+        let musl = target("x86_64-unknown-linux-musl");
+        let gnu = target("x86_64-unknown-linux-gnu");
+        assert_eq!(base_image(&musl, None), DISTROLESS_STATIC);
+        assert_eq!(base_image(&gnu, Some("distroless")), DISTROLESS_CC);
+        assert_eq!(base_image(&gnu, Some("scratch")), "scratch");
+        assert_eq!(base_image(&musl, Some("alpine:3.20")), "alpine:3.20");
+    }
+
+    #[test]
+    fn test_dockerfile() {
+        let names = ["gateway".to_string(), "migrate".to_string()];
+        assert_eq!(entrypoint(&names, Some("gateway")), Some("gateway"));
+        assert_eq!(entrypoint(&names, None), None);
+        assert_eq!(entrypoint(&names[1..], Some("gateway")), Some("migrate"));
+        assert_eq!(
+            dockerfile("scratch", &names, Some("gateway")),
+            "FROM scratch\n\
+             COPY gateway /usr/local/bin/gateway\n\
+             COPY migrate /usr/local/bin/migrate\n\
+             USER 65532:65532\n\
+             ENTRYPOINT [\"/usr/local/bin/gateway\"]\n"
+        );
+    }
+
+    #[test]
+    fn test_image_package_from_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let manifest = dir.path().join("Cargo.toml");
+        std::fs::write(
+            &manifest,
+            "[package]\nname = \"Gateway\"\nversion = \"1.2.0\"\n",
+        )
+        .unwrap();
+
+        let package =
+            ImagePackage::from_config(&DockerPackageConfig::default(), &manifest).unwrap();
+        assert_eq!(package.references(), ["gateway:1.2.0", "gateway:latest"]);
+
+        let config = DockerPackageConfig {
+            repository: Some("ghcr.io/acme/gateway".to_string()),
+            tags: vec!["edge".to_string()],
+            ..DockerPackageConfig::default()
+        };
+        let package = ImagePackage::from_config(&config, &dir.path().join("missing.toml")).unwrap();
+        assert_eq!(package.references(), ["ghcr.io/acme/gateway:edge"]);
+        assert!(ImagePackage::from_config(
+            &DockerPackageConfig::default(),
+            &dir.path().join("missing.toml")
+        )
+        .is_err());
+    }
+}
@@ -2,14 +2,35 @@
 
 use crate::error::{Error, Result};
 use crate::output::helpers;
+use crate::target::Target;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use tokio::task;
 
+use super::args::CargoArgs;
+use super::diagnostics::MAX_REPORTED_ERRORS;
+use super::events::{self, BuildEvent};
 use super::executor::Builder;
 use super::options::BuildOptions;
+use super::report::BuildReport;
+use super::scheduler::{available_jobs, SchedulePlan};
+use super::summary::TargetResult;
+
+/// A target waiting to be built: its position in the target list and options
+type QueuedBuild = (usize, String, BuildOptions);
 
 impl Builder {
     /// Build multiple targets in parallel using tokio tasks
+    ///
+    /// At most [`SchedulePlan::max_concurrent`] targets build at the same
+    /// time, and each cargo invocation gets its share of `build.jobs` (or of
+    /// the CPUs) unless `--jobs` was passed through. With `build.host_first`,
+    /// the host target is built alone before the others.
+    ///
+    /// With fail-fast, no further targets start once one fails; the builds
+    /// already running finish, so their artifacts are kept.
     pub async fn build_all_parallel(
         &self,
         targets: &[String],
@@ -18,53 +39,210 @@ impl Builder {
         use crate::output::progress::MultiTargetProgress;
 
         helpers::section(format!("xcargo {} (parallel)", options.operation.as_str()));
+
+        // Reject invalid passthrough arguments once instead of failing every target
+        let cargo_args = CargoArgs::parse(&options.cargo_args)?;
+        if let Some(scope) = cargo_args.scope_description() {
+            helpers::info(format!("Scope: {scope}"));
+        }
+
+        let config = self.config().clone();
+        let host = if config.build.host_first {
+            Some(Target::detect_host()?.triple)
+        } else {
+            None
+        };
+        let plan = SchedulePlan::new(targets, available_jobs(config.build.jobs), host.as_deref());
+        let inject_jobs = !cargo_args.sets_jobs();
+
         helpers::info(format!(
-            "{} for {} targets in parallel",
+            "{} for {} targets, up to {} at a time",
             options.operation.description(),
-            targets.len()
+            targets.len(),
+            plan.max_concurrent
         ));
+        if inject_jobs {
+            helpers::info(format!(
+                "Using {} jobs per target ({} total)",
+                plan.jobs_per_target, plan.total_jobs
+            ));
+        }
+
+        let incremental = Self::incremental_plan(&cargo_args, options);
+        events::emit(&BuildEvent::BuildStarted {
+            operation: options.operation.as_str().to_string(),
+            targets: targets.to_vec(),
+        });
+
+        // Options for one target, or None if nothing in it is affected
+        let target_options = |idx: usize, target: &str, jobs: usize| {
+            let mut target_options = options.clone();
+            target_options.target = Some(target.to_string());
+
+            if let Some(members) = incremental.as_ref().and_then(|p| p.members_for(target)) {
+                if members.is_empty() {
+                    helpers::info(format!(
+                        "[{}] No affected workspace members for {target}, skipping",
+                        idx + 1
+                    ));
+                    events::emit(&BuildEvent::target_finished(&TargetResult::skipped(target)));
+                    return None;
+                }
+                target_options.cargo_args = cargo_args.with_packages(&members);
+            }
+
+            if inject_jobs {
+                target_options
+                    .cargo_args
+                    .splice(0..0, ["--jobs".to_string(), jobs.to_string()]);
+            }
+            Some(target_options)
+        };
 
         let multi_progress = MultiTargetProgress::new();
         let successes = Arc::new(Mutex::new(Vec::new()));
         let failures = Arc::new(Mutex::new(Vec::new()));
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let fail_fast = options.fail_fast.unwrap_or(config.build.fail_fast);
+        let stop = Arc::new(AtomicBool::new(false));
 
-        let mut handles = Vec::new();
+        // The host target gets every job and finishes before the rest start
+        if let Some(first) = &plan.first {
+            let idx = targets.iter().position(|t| t == first).unwrap_or(0);
+            if let Some(first_options) = target_options(idx, first, plan.total_jobs) {
+                helpers::info(format!("[{}] Building host target {first} first", idx + 1));
+                let builder = Builder::with_config(config.clone())?
+                    .with_multi_progress(multi_progress.clone());
+                let first = first.clone();
+                let started = Instant::now();
+                let (result, report) = task::spawn_blocking(move || {
+                    let mut report = BuildReport::default();
+                    let result = builder.execute(&first_options, &mut report);
+                    (result, report)
+                })
+                .await
+                .map_err(|e| Error::Build(format!("Task join error: {e}")))?;
+                let diagnostics = &report.diagnostics;
+                results.lock().unwrap().push((
+                    idx,
+                    TargetResult::measure(&first, result.is_ok(), started.elapsed(), options)
+                        .with_diagnostics(diagnostics)
+                        .with_strategy(&report),
+                ));
+                match result {
+                    Ok(_) => successes.lock().unwrap().push(first),
+                    Err(e) => {
+                        helpers::error(format!("Failed to build {first}: {e}"));
+                        if fail_fast {
+                            stop.store(true, Ordering::Relaxed);
+                        }
+                        failures
+                            .lock()
+                            .unwrap()
+                            .push((first, diagnostics.error_summary(MAX_REPORTED_ERRORS)));
+                    }
+                }
+            } else {
+                results
+                    .lock()
+                    .unwrap()
+                    .push((idx, TargetResult::skipped(first)));
+            }
+        }
 
-        for (idx, target) in targets.iter().enumerate() {
-            let target = target.clone();
-            let mut target_options = options.clone();
-            target_options.target = Some(target.clone());
+        let queue: VecDeque<QueuedBuild> = plan
+            .queue
+            .iter()
+            .filter_map(|target| {
+                let idx = targets.iter().position(|t| t == target).unwrap_or(0);
+                let queued = target_options(idx, target, plan.jobs_per_target)
+                    .map(|target_options| (idx, target.clone(), target_options));
+                if queued.is_none() {
+                    results
+                        .lock()
+                        .unwrap()
+                        .push((idx, TargetResult::skipped(target)));
+                }
+                queued
+            })
+            .collect();
+        let queue = Arc::new(Mutex::new(queue));
 
+        // Each worker builds queued targets one after another
+        let mut handles = Vec::new();
+        for _ in 0..plan.max_concurrent {
+            let queue = Arc::clone(&queue);
             let successes = Arc::clone(&successes);
             let failures = Arc::clone(&failures);
+            let results = Arc::clone(&results);
+            let stop = Arc::clone(&stop);
+            let config = config.clone();
+            let multi_progress = multi_progress.clone();
 
             let handle = task::spawn_blocking(move || {
                 use crate::output::helpers;
 
-                println!();
-                helpers::info(format!("[{}] Starting build for: {}", idx + 1, target));
-                println!("{}", "─".repeat(50));
-
-                // Create a new builder for this task
-                let builder = match Builder::new() {
-                    Ok(b) => b,
+                let builder = match Builder::with_config(config) {
+                    Ok(b) => b.with_multi_progress(multi_progress),
                     Err(e) => {
+                        let mut queue = queue.lock().unwrap();
                         let mut failures = failures.lock().unwrap();
-                        failures.push(target.clone());
-                        helpers::error(format!("Failed to create builder for {target}: {e}"));
+                        let mut results = results.lock().unwrap();
+                        for (idx, target, target_options) in queue.drain(..) {
+                            helpers::error(format!("Failed to create builder for {target}: {e}"));
+                            let result = TargetResult::measure(
+                                &target,
+                                false,
+                                std::time::Duration::ZERO,
+                                &target_options,
+                            );
+                            events::emit(&BuildEvent::target_finished(&result));
+                            results.push((idx, result));
+                            failures.push((target, None));
+                        }
                         return;
                     }
                 };
 
-                match builder.build(&target_options) {
-                    Ok(()) => {
-                        let mut successes = successes.lock().unwrap();
-                        successes.push(target.clone());
-                    }
-                    Err(e) => {
-                        let mut failures = failures.lock().unwrap();
-                        failures.push(target.clone());
-                        helpers::error(format!("Failed to build {target}: {e}"));
+                // Targets still queued when the build stops are not built
+                while !stop.load(Ordering::Relaxed) {
+                    let Some((idx, target, target_options)) = queue.lock().unwrap().pop_front()
+                    else {
+                        break;
+                    };
+
+                    helpers::line("");
+                    helpers::info(format!("[{}] Starting build for: {}", idx + 1, target));
+                    helpers::rule();
+
+                    let started = Instant::now();
+                    let mut report = BuildReport::default();
+                    let outcome = builder.execute(&target_options, &mut report);
+                    let diagnostics = &report.diagnostics;
+                    results.lock().unwrap().push((
+                        idx,
+                        TargetResult::measure(
+                            &target,
+                            outcome.is_ok(),
+                            started.elapsed(),
+                            &target_options,
+                        )
+                        .with_diagnostics(diagnostics)
+                        .with_strategy(&report),
+                    ));
+                    match outcome {
+                        Ok(_) => {
+                            let mut successes = successes.lock().unwrap();
+                            successes.push(target);
+                        }
+                        Err(e) => {
+                            helpers::error(format!("Failed to build {target}: {e}"));
+                            if fail_fast {
+                                stop.store(true, Ordering::Relaxed);
+                            }
+                            let mut failures = failures.lock().unwrap();
+                            failures.push((target, diagnostics.error_summary(MAX_REPORTED_ERRORS)));
+                        }
                     }
                 }
             });
@@ -79,19 +257,33 @@ impl Builder {
                 .map_err(|e| Error::Build(format!("Task join error: {e}")))?;
         }
 
+        for (idx, target, _) in queue.lock().unwrap().drain(..) {
+            let result = TargetResult::not_built(&target);
+            events::emit(&BuildEvent::target_finished(&result));
+            results.lock().unwrap().push((idx, result));
+        }
+
         let successes = successes.lock().unwrap();
         let failures = failures.lock().unwrap();
 
         // Show summary with elapsed time
         multi_progress.finish_summary(successes.len(), failures.len());
 
+        let mut results = results.lock().unwrap().clone();
+        results.sort_by_key(|(idx, _)| *idx);
+        let results: Vec<TargetResult> = results.into_iter().map(|(_, r)| r).collect();
+        helpers::line("");
+        Self::report_target_summary(&results, options);
+
         if !failures.is_empty() {
-            println!();
+            helpers::line("");
             helpers::error("Failed targets:");
-            for target in failures.iter() {
-                helpers::error(format!("  - {target}"));
-            }
-            return Err(Error::Build("Some targets failed to build".to_string()));
+            Self::report_failures(&failures);
+            Self::report_not_built(&results);
+            return Err(Error::TargetsFailed {
+                failed: failures.iter().map(|(target, _)| target.clone()).collect(),
+                succeeded: successes.len(),
+            });
         }
 
         Ok(())
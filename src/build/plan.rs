@@ -0,0 +1,624 @@
+//! Build plans: what `xcargo build` would do, without doing it
+//!
+//! `xcargo build --plan` resolves, for each target, the decisions a build
+//! makes before cargo runs: the toolchain, the strategy (native, rust-lld,
+//! Zig, or a container), the linker, the environment cargo gets, the
+//! container image, and the prerequisites that are missing. Nothing is
+//! installed or written, and nothing is printed, so the plan can be emitted
+//! as JSON.
+
+use crate::error::Result;
+use crate::output::log::quote;
+use crate::target::{normalize_triple, Target};
+use crate::toolchain::zig::ZigToolchain;
+use crate::toolchain::RUST_LLD;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fmt::Write;
+
+use super::args::CargoArgs;
+use super::executor::Builder;
+use super::lock::{lock_path, LockFile, LockedTarget, LOCK_FILE};
+use super::options::{BuildOptions, BuildStrategy};
+use super::rustflags;
+use super::summary::{target_dir, target_dir_for};
+use super::wasm;
+
+/// The decisions a build would make for one target
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TargetPlan {
+    /// Target triple, as normalized for the toolchain
+    pub target: String,
+
+    /// Toolchain cargo would run with
+    pub toolchain: String,
+
+    /// Where the toolchain came from: --toolchain, xcargo.toml, xcargo.lock, ...
+    pub toolchain_source: String,
+
+    /// Rust release of the toolchain, if it is installed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rust: Option<String>,
+
+    /// Build strategy: "native", "rust-lld", "zig", or "container"
+    pub strategy: String,
+
+    /// Why the strategy was chosen
+    pub reason: String,
+
+    /// Linker cargo would be told to use, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linker: Option<String>,
+
+    /// Version of the Zig used for a Zig build
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zig: Option<String>,
+
+    /// Container image for a container build
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+
+    /// Environment variables set for cargo
+    pub env: BTreeMap<String, String>,
+
+    /// Variables from the environment that the build uses: compilers and
+    /// flags that win over xcargo's, and pkg-config settings for the target
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub inherited: BTreeMap<String, String>,
+
+    /// Prerequisites that are not installed yet
+    pub missing: Vec<String>,
+
+    /// Problems that would stop the build
+    pub errors: Vec<String>,
+
+    /// Warnings the build would print
+    pub warnings: Vec<String>,
+}
+
+impl TargetPlan {
+    /// Whether the build could run as planned, installing rustup targets
+    /// and components on the way
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Every variable to set to reproduce the build outside xcargo: the
+    /// inherited ones, and those xcargo sets
+    #[must_use]
+    pub fn full_env(&self) -> BTreeMap<String, String> {
+        let mut env = self.inherited.clone();
+        env.extend(self.env.clone());
+        env
+    }
+
+    /// [`full_env`](Self::full_env) as `export` lines for a POSIX shell
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::TargetPlan;
+    ///
+    /// let mut plan = TargetPlan::default();
+    /// plan.env.insert("RUSTFLAGS".to_string(), "-C target-feature=+crt-static".to_string());
+    /// assert_eq!(
+    ///     plan.shell_exports(),
+    ///     "export RUSTFLAGS='-C target-feature=+crt-static'\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn shell_exports(&self) -> String {
+        let mut exports = String::new();
+        for (key, value) in self.full_env() {
+            let _ = writeln!(exports, "export {key}={}", quote(OsStr::new(&value)));
+        }
+        exports
+    }
+}
+
+impl Builder {
+    /// Plan a build of each of `targets`, without running cargo
+    ///
+    /// # Errors
+    /// Returns an error if the toolchain cannot be resolved, xcargo.lock is
+    /// invalid, or a target triple is not recognized
+    pub fn plan(&self, targets: &[String], options: &BuildOptions) -> Result<Vec<TargetPlan>> {
+        let lock = LockFile::load(&lock_path())?;
+        targets
+            .iter()
+            .map(|triple| {
+                let locked = lock
+                    .as_ref()
+                    .and_then(|lock| lock.targets.get(triple.as_str()));
+                self.plan_target(triple, locked, options)
+            })
+            .collect()
+    }
+
+    /// Plan one target, mirroring the decisions of a build
+    fn plan_target(
+        &self,
+        triple: &str,
+        locked: Option<&LockedTarget>,
+        options: &BuildOptions,
+    ) -> Result<TargetPlan> {
+        let config = self.config();
+        let manager = self.toolchain_manager();
+        let mut plan = TargetPlan::default();
+
+        let selection = manager.resolve_toolchain(
+            options.toolchain.as_deref(),
+            config.toolchain.channel.as_deref(),
+        )?;
+        plan.warnings.extend(selection.conflict.clone());
+        let (toolchain, _, lock_warning) = self.locked_toolchain(&selection, locked)?;
+        plan.toolchain_source = if toolchain == selection.name {
+            selection.source.to_string()
+        } else {
+            LOCK_FILE.to_string()
+        };
+        plan.warnings.extend(lock_warning);
+        plan.rust = manager.rustc_release(&toolchain);
+
+        let normalized = normalize_triple(triple, plan.rust.as_deref());
+        plan.warnings.extend(normalized.warning);
+        let target = Target::from_triple(&normalized.triple)?;
+        plan.target = target.versioned_triple();
+        if target.is_wasm() {
+            if let Err(e) = wasm::check_wasm_options(&target, options) {
+                plan.errors.push(e.to_string());
+            }
+        }
+        if let Some(glibc) = &target.glibc {
+            if options.use_container || options.use_zig == Some(false) {
+                plan.errors.push(format!(
+                    "{triple} targets glibc {glibc}, which needs a Zig build; drop --container and --no-zig"
+                ));
+            }
+        }
+
+        let host = Target::detect_host()?;
+        let target_config = config.get_target_config(&target.triple);
+        let rust_lld = if target.glibc.is_some() {
+            None
+        } else {
+            self.rust_lld_choice(&target, &toolchain, options)?
+        };
+        let use_container = rust_lld.is_none()
+            && target.glibc.is_none()
+            && (options.use_container || self.should_use_container_for_target(&target)?);
+
+        let mut strategy_flags = Vec::new();
+        let strategy = if use_container {
+            plan.reason = if options.use_container {
+                "--container".to_string()
+            } else {
+                format!("container.use_when = \"{}\"", config.container.use_when)
+            };
+            self.plan_container(&target, &mut plan);
+            BuildStrategy::Container
+        } else if let Some((support, _)) = rust_lld {
+            plan.reason =
+                "no system linker; the toolchain's rust-lld supports the target".to_string();
+            plan.linker = Some(RUST_LLD.to_string());
+            strategy_flags = support.rustflags();
+            BuildStrategy::RustLld
+        } else if self.plan_zig(&target, &host, options, &mut plan) {
+            BuildStrategy::Zig
+        } else {
+            plan.reason = if target.is_wasm() {
+                "WebAssembly builds with the Rust toolchain alone".to_string()
+            } else if target.os == host.os {
+                "target OS matches the host".to_string()
+            } else {
+                "no Zig or container build configured for this cross-compilation".to_string()
+            };
+            plan.linker = target_config
+                .and_then(|c| c.linker.clone())
+                .or_else(|| target.get_requirements().linker);
+            if let Some(linker) = &plan.linker {
+                if which::which(linker).is_err() {
+                    plan.missing.push(format!("linker {linker}"));
+                }
+            }
+            BuildStrategy::Native
+        };
+        plan.strategy = strategy.to_string();
+
+        if let Err(e) = self.check_release_policy(strategy, options) {
+            plan.errors.push(e.to_string());
+        }
+        plan.warnings
+            .extend(Self::locked_strategy_warning(locked, strategy));
+        if let (Some(locked_zig), Some(zig)) = (locked.and_then(|l| l.zig.as_deref()), &plan.zig) {
+            if locked_zig != zig {
+                plan.errors.push(format!(
+                    "{LOCK_FILE} locks Zig {locked_zig} for {triple}, but Zig {zig} is installed"
+                ));
+            }
+        }
+
+        if strategy != BuildStrategy::Container {
+            if let Some(linker) = plan
+                .linker
+                .clone()
+                .filter(|_| strategy != BuildStrategy::Zig)
+            {
+                plan.env.insert(linker_var(&target.triple), linker);
+            }
+            let triple_target_dir = target_dir_for(&target.triple);
+            if triple_target_dir != target_dir() {
+                plan.env.insert(
+                    "CARGO_TARGET_DIR".to_string(),
+                    triple_target_dir.display().to_string(),
+                );
+            }
+            self.plan_prerequisites(&target, &toolchain, options, &mut plan);
+        }
+
+        // The environment's own compilers and flags win in builds on the host
+        let cc_linker = plan
+            .linker
+            .clone()
+            .or_else(|| target.get_requirements().linker);
+        for (key, value) in
+            Builder::cc_environment(target_config, &target, strategy, cc_linker.as_deref())
+        {
+            if strategy != BuildStrategy::Container && std::env::var_os(&key).is_some() {
+                if let Ok(ambient) = std::env::var(&key) {
+                    plan.inherited.insert(key, ambient);
+                }
+                continue;
+            }
+            plan.env.insert(key, value);
+        }
+        if strategy != BuildStrategy::Container {
+            plan.inherited.extend(pkg_config_env(
+                &target.triple,
+                target.triple != host.triple,
+                |key| std::env::var(key).ok(),
+            ));
+        }
+
+        let runner = match target_config.and_then(|c| c.runner.clone()) {
+            Some(runner) => Some(runner),
+            None => Builder::default_runner(&target)?,
+        };
+        if let Some(runner) = runner {
+            plan.env.insert(
+                format!("CARGO_TARGET_{}_RUNNER", env_triple(&target.triple)),
+                runner,
+            );
+        }
+        if let Some(target_config) = target_config {
+            plan.env.extend(target_config.env.clone());
+        }
+        let rustflags = if strategy == BuildStrategy::Container {
+            Some(rustflags::configured_rustflags(config, &target.triple))
+                .filter(|flags| !flags.is_empty())
+        } else {
+            rustflags::merged_rustflags(config, &target.triple, &strategy_flags)
+        };
+        let ambient_rustflags = rustflags::ambient_rustflags();
+        if strategy == BuildStrategy::Container && !ambient_rustflags.is_empty() {
+            plan.warnings.push(
+                "RUSTFLAGS from the environment is not passed into the container".to_string(),
+            );
+        }
+        if let Some(rustflags) = rustflags {
+            let shadowed = (strategy != BuildStrategy::Container && ambient_rustflags.is_empty())
+                .then(|| std::env::current_dir().ok())
+                .flatten()
+                .and_then(|cwd| rustflags::shadowed_cargo_config(&cwd, &target.triple));
+            if let Some(cargo_config) = shadowed {
+                plan.warnings.push(format!(
+                    "Cargo ignores the rustflags in {} while xcargo sets its own",
+                    cargo_config.display()
+                ));
+            }
+            plan.env.insert(
+                "CARGO_ENCODED_RUSTFLAGS".to_string(),
+                rustflags::encode(&rustflags),
+            );
+        }
+
+        plan.toolchain = toolchain;
+        Ok(plan)
+    }
+
+    /// Decide whether Zig builds the target, as the build would; returns
+    /// whether it does
+    fn plan_zig(
+        &self,
+        target: &Target,
+        host: &Target,
+        options: &BuildOptions,
+        plan: &mut TargetPlan,
+    ) -> bool {
+        if options.use_zig == Some(false) {
+            return false;
+        }
+        let force_zig = options.use_zig == Some(true) || target.glibc.is_some();
+        let is_cross_os = target.os != host.os;
+        if !force_zig && !is_cross_os {
+            return false;
+        }
+
+        plan.reason = if let Some(glibc) = &target.glibc {
+            format!("glibc {glibc} can only be targeted with Zig")
+        } else if options.use_zig == Some(true) {
+            "--zig".to_string()
+        } else {
+            "cross-compiling to another OS, and Zig supports the target".to_string()
+        };
+
+        match self.zig() {
+            Some(zig) if zig.supports_target(target) => {
+                plan.zig = Some(zig.version().to_string());
+                let env = zig.planned_environment(target);
+                plan.linker = env
+                    .get(&linker_var(&target.triple))
+                    .map(|path| path.display().to_string());
+                plan.env.extend(
+                    env.into_iter()
+                        .map(|(key, path)| (key, path.display().to_string())),
+                );
+                true
+            }
+            Some(zig) if force_zig => {
+                plan.zig = Some(zig.version().to_string());
+                plan.errors
+                    .push(match ZigToolchain::required_version(&target.triple) {
+                        Some(required) => format!(
+                            "Zig {} is too old for {}; it needs Zig {required} or newer",
+                            zig.version(),
+                            target.triple
+                        ),
+                        None => format!("Zig does not support {}", target.triple),
+                    });
+                true
+            }
+            None if force_zig => {
+                plan.missing.push("zig".to_string());
+                true
+            }
+            None => {
+                if ZigToolchain::supports_target_name(&target.triple) {
+                    plan.warnings.push(
+                        "Zig is not installed but could simplify this cross-compilation"
+                            .to_string(),
+                    );
+                }
+                false
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// Record the image and runtime a container build would use
+    #[cfg(feature = "container")]
+    fn plan_container(&self, target: &Target, plan: &mut TargetPlan) {
+        use crate::container::{ContainerBuilder, ImageSelector, RuntimeType};
+
+        match ImageSelector::from_config(&self.config().container).select_for_target(&target.triple) {
+            Ok(image) => plan.image = Some(image.full_name()),
+            Err(e) => plan.errors.push(e.to_string()),
+        }
+        let runtime_type =
+            RuntimeType::from_str(&self.config().container.runtime).unwrap_or(RuntimeType::Auto);
+        let available = ContainerBuilder::new(runtime_type).is_ok_and(|b| b.is_available());
+        if !available {
+            plan.missing
+                .push("container runtime (docker or podman)".to_string());
+        }
+    }
+
+    /// Record the image and runtime a container build would use
+    #[cfg(not(feature = "container"))]
+    fn plan_container(&self, _target: &Target, plan: &mut TargetPlan) {
+        plan.errors.push(
+            "Container support not enabled; rebuild xcargo with --features container".to_string(),
+        );
+    }
+
+    /// Record the toolchain, rustup target, and components a build would
+    /// have to install
+    fn plan_prerequisites(
+        &self,
+        target: &Target,
+        toolchain: &str,
+        options: &BuildOptions,
+        plan: &mut TargetPlan,
+    ) {
+        let manager = self.toolchain_manager();
+        if let Ok(false) = manager.is_toolchain_installed(toolchain) {
+            plan.missing.push(format!("Rust toolchain {toolchain}"));
+            return;
+        }
+        if let Ok(false) = manager.is_target_installed(toolchain, &target.triple) {
+            plan.missing
+                .push(format!("rustup target {}", target.triple));
+        }
+
+        let mut components = target.get_requirements().components;
+        let configured = self
+            .config()
+            .get_target_config(&target.triple)
+            .and_then(|c| c.components.clone())
+            .unwrap_or_default();
+        for component in configured {
+            if !components.contains(&component) {
+                components.push(component);
+            }
+        }
+        let uses_build_std =
+            CargoArgs::parse(&options.cargo_args).is_ok_and(|args| args.uses_build_std());
+        if uses_build_std && !components.iter().any(|c| c == "rust-src") {
+            components.push("rust-src".to_string());
+        }
+        if let Ok(missing) = manager.missing_components(toolchain, &components) {
+            plan.missing
+                .extend(missing.into_iter().map(|c| format!("component {c}")));
+        }
+    }
+}
+
+/// A target triple in cargo's environment variable form
+fn env_triple(triple: &str) -> String {
+    triple.to_uppercase().replace('-', "_")
+}
+
+/// Variables configuring pkg-config, which `*-sys` build scripts use to
+/// find C libraries
+const PKG_CONFIG_VARS: [&str; 5] = [
+    "PKG_CONFIG",
+    "PKG_CONFIG_PATH",
+    "PKG_CONFIG_LIBDIR",
+    "PKG_CONFIG_SYSROOT_DIR",
+    "PKG_CONFIG_ALLOW_CROSS",
+];
+
+/// The pkg-config variables set in the environment that apply to `triple`
+///
+/// The pkg-config crate reads each variable suffixed with the triple, then
+/// with `TARGET_` (or `HOST_` when not cross-compiling) in front, then plain.
+fn pkg_config_env(
+    triple: &str,
+    cross: bool,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> BTreeMap<String, String> {
+    let kind = if cross { "TARGET" } else { "HOST" };
+    PKG_CONFIG_VARS
+        .iter()
+        .flat_map(|var| {
+            [
+                format!("{var}_{triple}"),
+                format!("{var}_{}", triple.replace('-', "_")),
+                format!("{kind}_{var}"),
+                (*var).to_string(),
+            ]
+        })
+        .filter_map(|key| lookup(&key).map(|value| (key, value)))
+        .collect()
+}
+
+/// Cargo's linker variable for a target, `CARGO_TARGET_<TRIPLE>_LINKER`
+fn linker_var(triple: &str) -> String {
+    format!("CARGO_TARGET_{}_LINKER", env_triple(triple))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    #[test]
+    fn test_linker_var() {
+        assert_eq!(
+            linker_var("x86_64-pc-windows-gnu"),
+            "CARGO_TARGET_X86_64_PC_WINDOWS_GNU_LINKER"
+        );
+    }
+
+    #[test]
+    fn test_pkg_config_env() {
+        let lookup = |key: &str| {
+            matches!(
+                key,
+                "PKG_CONFIG_SYSROOT_DIR_aarch64_unknown_linux_gnu"
+                    | "TARGET_PKG_CONFIG_PATH"
+                    | "HOST_PKG_CONFIG_PATH"
+                    | "PKG_CONFIG_ALLOW_CROSS"
+            )
+            .then(|| format!("{key}-value"))
+        };
+        let env = pkg_config_env("aarch64-unknown-linux-gnu", true, lookup);
+        assert_eq!(
+            env.keys().collect::<Vec<_>>(),
+            [
+                "PKG_CONFIG_ALLOW_CROSS",
+                "PKG_CONFIG_SYSROOT_DIR_aarch64_unknown_linux_gnu",
+                "TARGET_PKG_CONFIG_PATH"
+            ]
+        );
+        assert!(pkg_config_env("x86_64-unknown-linux-gnu", false, lookup)
+            .contains_key("HOST_PKG_CONFIG_PATH"));
+    }
+
+    #[test]
+    fn test_shell_exports_quote_values() {
+        let mut plan = TargetPlan::default();
+        plan.inherited
+            .insert("PKG_CONFIG_ALLOW_CROSS".to_string(), "1".to_string());
+        plan.env.insert("CC_x".to_string(), "it's cc".to_string());
+        assert_eq!(
+            plan.shell_exports(),
+            "export CC_x='it'\\''s cc'\nexport PKG_CONFIG_ALLOW_CROSS=1\n"
+        );
+    }
+
+    #[test]
+    fn test_plan_host_target_is_native() {
+        let Ok(builder) = Builder::with_config(Config::default()) else {
+            return;
+        };
+        let host = Target::detect_host().unwrap();
+        let options = BuildOptions {
+            use_zig: Some(false),
+            ..BuildOptions::default()
+        };
+        let Ok(plans) = builder.plan(std::slice::from_ref(&host.triple), &options) else {
+            return;
+        };
+
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].target, host.triple);
+        assert_eq!(plans[0].strategy, "native");
+        assert!(plans[0].image.is_none());
+        assert!(plans[0].is_ready());
+    }
+
+    #[test]
+    fn test_plan_encodes_rustflags_with_spaces() {
+        let mut config = Config::default();
+        config.build.rustflags = vec!["-L".to_string(), "/opt/my libs".to_string()];
+        let Ok(builder) = Builder::with_config(config) else {
+            return;
+        };
+        let host = Target::detect_host().unwrap();
+        let options = BuildOptions {
+            use_zig: Some(false),
+            ..BuildOptions::default()
+        };
+        let Ok(plans) = builder.plan(&[host.triple], &options) else {
+            return;
+        };
+
+        let encoded = &plans[0].env["CARGO_ENCODED_RUSTFLAGS"];
+        assert!(encoded.starts_with("-L\x1f/opt/my libs"));
+        assert!(!plans[0].env.contains_key("RUSTFLAGS"));
+    }
+
+    #[test]
+    fn test_plan_reports_release_policy() {
+        let mut config = Config::default();
+        config.policy.release_requires = Some("container".to_string());
+        let Ok(builder) = Builder::with_config(config) else {
+            return;
+        };
+        let host = Target::detect_host().unwrap();
+        let options = BuildOptions {
+            release: true,
+            use_zig: Some(false),
+            ..BuildOptions::default()
+        };
+        let Ok(plans) = builder.plan(&[host.triple], &options) else {
+            return;
+        };
+
+        assert!(!plans[0].is_ready());
+        assert!(plans[0].errors[0].contains("release_requires"));
+    }
+}
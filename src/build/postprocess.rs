@@ -0,0 +1,391 @@
+//! Post-build processing of binaries: split debug info, strip, objcopy,
+//! compress
+//!
+//! Embedded Linux and bare-metal deployments usually need stripped binaries,
+//! raw images produced by objcopy, or UPX-compressed executables; production
+//! builds ship without debug info but keep it for symbolication. The host's
+//! binutils don't understand foreign object formats, so the tools are looked
+//! up for the target: the cross binutils next to its GCC linker, LLVM's
+//! target-independent tools (on `PATH`, from rustup's `llvm-tools`, or the
+//! `rust-objcopy` in every toolchain), and finally `zig objcopy`.
+
+use crate::config::TargetCustomConfig;
+use crate::error::{Error, Result};
+use crate::output::terminal::symbol;
+use crate::output::{helpers, log};
+use crate::target::Target;
+use crate::toolchain::{format_size, gnu_prefix};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::debuginfo::split_debuginfo;
+use super::summary::target_dir_for;
+
+/// One step of the post-build pipeline, in the order they run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostStep {
+    /// Move debug information into an archived symbol file
+    SplitDebuginfo,
+    /// Remove symbols and debug information
+    Strip,
+    /// Run objcopy with these arguments
+    Objcopy(Vec<String>),
+    /// Compress the executable with UPX
+    Upx,
+}
+
+impl PostStep {
+    /// Steps configured for a target: split debug info, then strip, then
+    /// objcopy, then UPX
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::PostStep;
+    /// use xcargo::config::Config;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let config = Config::from_str(r#"
+    ///     [targets."armv7-unknown-linux-gnueabihf"]
+    ///     strip = true
+    ///     upx = true
+    /// "#)?;
+    /// let target = config.get_target_config("armv7-unknown-linux-gnueabihf").unwrap();
+    /// assert_eq!(PostStep::for_target(target), vec![PostStep::Strip, PostStep::Upx]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn for_target(config: &TargetCustomConfig) -> Vec<Self> {
+        let mut steps = Vec::new();
+        if config.split_debuginfo == Some(true) {
+            steps.push(Self::SplitDebuginfo);
+        }
+        if config.strip == Some(true) {
+            steps.push(Self::Strip);
+        }
+        if let Some(args) = config.objcopy.as_ref().filter(|a| !a.is_empty()) {
+            steps.push(Self::Objcopy(args.clone()));
+        }
+        if config.upx == Some(true) {
+            steps.push(Self::Upx);
+        }
+        steps
+    }
+}
+
+/// A program plus the arguments that select the tool, e.g. `zig objcopy`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Tool {
+    program: PathBuf,
+    args: Vec<String>,
+}
+
+impl Tool {
+    fn new(program: PathBuf) -> Self {
+        Self {
+            program,
+            args: Vec::new(),
+        }
+    }
+
+    pub(super) fn command(&self) -> Command {
+        let mut cmd = Command::new(&self.program);
+        cmd.args(&self.args);
+        cmd
+    }
+
+    pub(super) fn display(&self) -> String {
+        let name = self.program.file_name().map_or_else(
+            || self.program.display().to_string(),
+            |n| n.to_string_lossy().to_string(),
+        );
+        std::iter::once(name)
+            .chain(self.args.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Binutils that understand a target's object format
+#[derive(Debug, Clone, Default)]
+struct Binutils {
+    strip: Option<Tool>,
+    objcopy: Option<Tool>,
+}
+
+impl Binutils {
+    /// Look up the tools for `target`, preferring its own cross binutils
+    fn find(
+        target: &Target,
+        linker: Option<&str>,
+        host: &str,
+        sysroot: Option<&Path>,
+        zig: Option<&Path>,
+    ) -> Self {
+        let prefix = linker
+            .map(ToString::to_string)
+            .or_else(|| target.get_requirements().linker)
+            .and_then(|linker| gnu_prefix(&linker));
+        let llvm_tools = sysroot.map(|sysroot| sysroot.join("lib/rustlib").join(host).join("bin"));
+
+        let find = |name: &str| {
+            let mut candidates = Vec::new();
+            if let Some(prefix) = &prefix {
+                candidates.push(format!("{prefix}{name}"));
+            }
+            if target.triple == host {
+                candidates.push(name.to_string());
+            }
+            candidates.push(format!("llvm-{name}"));
+            candidates
+                .iter()
+                .find_map(|candidate| which::which(candidate).ok())
+                .or_else(|| {
+                    // llvm-tools-preview, or the rust-objcopy every toolchain ships
+                    let tools = llvm_tools.as_ref()?;
+                    [format!("llvm-{name}"), format!("rust-{name}")]
+                        .iter()
+                        .map(|tool| tools.join(format!("{tool}{}", std::env::consts::EXE_SUFFIX)))
+                        .find(|path| path.is_file())
+                })
+                .map(Tool::new)
+        };
+
+        let objcopy = find("objcopy").or_else(|| {
+            zig.map(|zig| Tool {
+                program: zig.to_path_buf(),
+                args: vec!["objcopy".to_string()],
+            })
+        });
+        Self {
+            strip: find("strip"),
+            objcopy,
+        }
+    }
+}
+
+/// File objcopy writes to: a sibling with the output format's extension,
+/// or the binary itself when the format doesn't change
+fn objcopy_output(binary: &Path, args: &[String]) -> PathBuf {
+    let format = args.iter().enumerate().find_map(|(idx, arg)| {
+        if arg == "-O" || arg == "--output-target" {
+            args.get(idx + 1).map(String::as_str)
+        } else {
+            arg.strip_prefix("--output-target=")
+                .or_else(|| arg.strip_prefix("-O").filter(|f| !f.is_empty()))
+        }
+    });
+    let extension = match format {
+        Some("binary") => "bin",
+        Some("ihex") => "hex",
+        Some("srec") => "srec",
+        _ => return binary.to_path_buf(),
+    };
+    binary.with_extension(extension)
+}
+
+/// Executables cargo placed in the profile directory for `target`
+pub(super) fn target_binaries(target_dir: &Path, target: &str, release: bool) -> Vec<PathBuf> {
+    let profile = if release { "release" } else { "debug" };
+    let Ok(entries) = std::fs::read_dir(target_dir.join(target).join(profile)) else {
+        return Vec::new();
+    };
+
+    let mut binaries: Vec<PathBuf> = entries
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            let hidden = path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            let executable = match path.extension() {
+                None => true,
+                Some(ext) => ext == "exe",
+            };
+            !hidden && executable
+        })
+        .collect();
+    binaries.sort();
+    binaries
+}
+
+pub(super) fn run_tool(mut cmd: Command, what: &str, binary: &Path) -> Result<()> {
+    log::command(&cmd);
+    let output = cmd
+        .output()
+        .map_err(|e| Error::Build(format!("Failed to run {what}: {e}")))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::Build(format!(
+            "{what} failed for {}: {}",
+            binary.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+pub(super) fn file_size(path: &Path) -> u64 {
+    std::fs::metadata(path).map_or(0, |m| m.len())
+}
+
+/// Run a target's configured post-build steps on the binaries it built
+///
+/// `sysroot` is searched for LLVM tools shipped with rustc, and `zig` provides
+/// `zig objcopy` as a last resort.
+///
+/// # Errors
+/// Returns an error if a needed tool is missing or fails
+pub(super) fn post_process(
+    target: &Target,
+    config: &TargetCustomConfig,
+    release: bool,
+    sysroot: Option<&Path>,
+    zig: Option<&Path>,
+) -> Result<()> {
+    let steps = PostStep::for_target(config);
+    if steps.is_empty() {
+        return Ok(());
+    }
+    let binaries = target_binaries(&target_dir_for(&target.triple), &target.triple, release);
+    if binaries.is_empty() {
+        return Ok(());
+    }
+
+    let host = Target::detect_host()?.triple;
+    let binutils = Binutils::find(target, config.linker.as_deref(), &host, sysroot, zig);
+
+    helpers::progress(format!(
+        "Post-processing {} binar{}",
+        binaries.len(),
+        if binaries.len() == 1 { "y" } else { "ies" }
+    ));
+    for binary in &binaries {
+        for step in &steps {
+            run_post_step(step, binary, &binutils, &target.triple)?;
+        }
+    }
+    Ok(())
+}
+
+fn run_post_step(step: &PostStep, binary: &Path, binutils: &Binutils, triple: &str) -> Result<()> {
+    let name = binary.file_name().unwrap_or_default().to_string_lossy();
+    let before = file_size(binary);
+    match step {
+        PostStep::SplitDebuginfo => {
+            split_debuginfo(binary, binutils.objcopy.as_ref(), triple)?;
+        }
+        PostStep::Strip => {
+            // objcopy --strip-all does the same where no strip is available
+            let cmd = if let Some(strip) = &binutils.strip {
+                let mut cmd = strip.command();
+                cmd.arg(binary);
+                cmd
+            } else if let Some(objcopy) = &binutils.objcopy {
+                let mut cmd = objcopy.command();
+                cmd.arg("--strip-all").arg(binary).arg(binary);
+                cmd
+            } else {
+                return Err(missing_binutils("strip", triple));
+            };
+            run_tool(cmd, "strip", binary)?;
+            helpers::success(format!(
+                "Stripped {name} ({} {} {})",
+                format_size(before),
+                symbol("→", "->"),
+                format_size(file_size(binary))
+            ));
+        }
+        PostStep::Objcopy(args) => {
+            let Some(objcopy) = &binutils.objcopy else {
+                return Err(missing_binutils("objcopy", triple));
+            };
+            let output = objcopy_output(binary, args);
+            let mut cmd = objcopy.command();
+            cmd.args(args).arg(binary).arg(&output);
+            run_tool(cmd, &objcopy.display(), binary)?;
+            helpers::success(format!(
+                "objcopy {} {} {} ({})",
+                name,
+                symbol("→", "->"),
+                output.file_name().unwrap_or_default().to_string_lossy(),
+                format_size(file_size(&output))
+            ));
+        }
+        PostStep::Upx => {
+            let upx = which::which("upx").map_err(|_| {
+                Error::Build("upx = true needs UPX on PATH (https://upx.github.io)".to_string())
+            })?;
+            // Cargo only replaces the binary when it rebuilds it
+            let packed = Command::new(&upx)
+                .args(["-t", "-q"])
+                .arg(binary)
+                .output()
+                .is_ok_and(|output| output.status.success());
+            if packed {
+                helpers::info(format!("{name} is already compressed"));
+                return Ok(());
+            }
+            let mut cmd = Command::new(&upx);
+            cmd.arg("-q").arg(binary);
+            run_tool(cmd, "upx", binary)?;
+            helpers::success(format!(
+                "Compressed {name} ({} {} {})",
+                format_size(before),
+                symbol("→", "->"),
+                format_size(file_size(binary))
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn missing_binutils(tool: &str, triple: &str) -> Error {
+    helpers::hint(format!("No {tool} for {triple} found"));
+    helpers::tip(
+        "Install the target's cross binutils, or: rustup component add llvm-tools-preview",
+    );
+    Error::Build(format!("No {tool} available for {triple}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_objcopy_output() {
+        let binary = Path::new("target/thumbv7em-none-eabihf/release/firmware");
+        let args = |args: &[&str]| args.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            objcopy_output(binary, &args(&["-O", "binary"])),
+            binary.with_extension("bin")
+        );
+        assert_eq!(
+            objcopy_output(binary, &args(&["--output-target=ihex"])),
+            binary.with_extension("hex")
+        );
+        assert_eq!(
+            objcopy_output(binary, &args(&["--only-keep-debug"])),
+            binary
+        );
+    }
+
+    #[test]
+    fn test_target_binaries() {
+        let temp = TempDir::new().unwrap();
+        let profile_dir = temp.path().join("x86_64-pc-windows-gnu/release");
+        std::fs::create_dir_all(profile_dir.join("deps")).unwrap();
+        for file in ["app.exe", "app.d", "libapp.rlib", "tool", ".cargo-lock"] {
+            std::fs::write(profile_dir.join(file), "").unwrap();
+        }
+
+        assert_eq!(
+            target_binaries(temp.path(), "x86_64-pc-windows-gnu", true),
+            [profile_dir.join("app.exe"), profile_dir.join("tool")]
+        );
+        assert!(target_binaries(temp.path(), "x86_64-pc-windows-gnu", false).is_empty());
+    }
+}
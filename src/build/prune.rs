@@ -0,0 +1,384 @@
+//! Retention and pruning of per-target build artifacts
+//!
+//! Building many targets fills `target/<triple>/<profile>/` directories that
+//! are rarely cleaned up. `xcargo prune-artifacts` removes the least recently
+//! built ones according to `[artifacts]` in `xcargo.toml`. How recently a
+//! directory was built comes from the build history in `.xcargo/history.json`,
+//! or from modification times for builds it doesn't record.
+
+use crate::config::ArtifactsConfig;
+use crate::error::{Error, Result};
+use crate::toolchain::dir_size;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::summary::{BuildHistory, ISOLATED_DIR};
+
+/// Parse a size such as "20GiB", "500MB", "1.5G", or "4096" (bytes)
+///
+/// Single-letter and `iB` units are binary (1024-based), `B` units decimal.
+///
+/// # Errors
+/// Returns an error for empty input, unknown units, or a zero size
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::build::parse_size;
+///
+/// assert_eq!(parse_size("2KiB").unwrap(), 2048);
+/// assert_eq!(parse_size("1.5G").unwrap(), 1_610_612_736);
+/// assert_eq!(parse_size("500MB").unwrap(), 500_000_000);
+/// assert!(parse_size("lots").is_err());
+/// ```
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+pub fn parse_size(value: &str) -> Result<u64> {
+    let invalid = || {
+        Error::Config(format!(
+            "Invalid size '{value}'. Use a number with a unit, e.g. \"500MiB\" or \"20GB\""
+        ))
+    };
+
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kib" => 1 << 10,
+        "m" | "mib" => 1 << 20,
+        "g" | "gib" => 1 << 30,
+        "t" | "tib" => 1 << 40,
+        "kb" => 1_000,
+        "mb" => 1_000_000,
+        "gb" => 1_000_000_000,
+        "tb" => 1_000_000_000_000,
+        _ => return Err(invalid()),
+    };
+
+    let bytes = (number * multiplier as f64).round();
+    if bytes < 1.0 || bytes >= u64::MAX as f64 {
+        return Err(invalid());
+    }
+    Ok(bytes as u64)
+}
+
+/// Limits on the artifact directories kept in the target directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetentionPolicy {
+    /// Keep this many of the most recently built directories
+    pub keep_last_builds: Option<usize>,
+
+    /// Keep the most recently built directories that fit in this many bytes
+    pub max_total_size: Option<u64>,
+}
+
+impl RetentionPolicy {
+    /// Policy from the `[artifacts]` section of `xcargo.toml`
+    ///
+    /// # Errors
+    /// Returns an error if `max_total_size` is not a valid size
+    pub fn from_config(config: &ArtifactsConfig) -> Result<Self> {
+        Ok(Self {
+            keep_last_builds: config.keep_last_builds,
+            max_total_size: config
+                .max_total_size
+                .as_deref()
+                .map(parse_size)
+                .transpose()?,
+        })
+    }
+
+    /// Whether the policy limits anything
+    #[must_use]
+    pub fn is_set(&self) -> bool {
+        self.keep_last_builds.is_some() || self.max_total_size.is_some()
+    }
+
+    /// Split `dirs` into the ones to keep and the ones to remove
+    ///
+    /// Directories are considered newest first. Once one exceeds a limit, it
+    /// and all older directories are removed.
+    #[must_use]
+    pub fn apply(&self, mut dirs: Vec<ArtifactDir>) -> (Vec<ArtifactDir>, Vec<ArtifactDir>) {
+        dirs.sort_by_key(|dir| std::cmp::Reverse(dir.last_built));
+
+        let mut total = 0u64;
+        let split = dirs
+            .iter()
+            .enumerate()
+            .position(|(idx, dir)| {
+                total += dir.size_bytes;
+                self.keep_last_builds.is_some_and(|keep| idx >= keep)
+                    || self.max_total_size.is_some_and(|max| total > max)
+            })
+            .unwrap_or(dirs.len());
+
+        let removed = dirs.split_off(split);
+        (dirs, removed)
+    }
+}
+
+/// Build output for one target and profile: `target/<triple>/<profile>/`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactDir {
+    /// Target triple
+    pub target: String,
+
+    /// Cargo profile directory name, e.g. "debug" or "release"
+    pub profile: String,
+
+    /// Path to the directory
+    pub path: PathBuf,
+
+    /// Total size of the directory, in bytes
+    pub size_bytes: u64,
+
+    /// When the directory was last built, in seconds since the Unix epoch
+    pub last_built: u64,
+}
+
+impl ArtifactDir {
+    /// Key of the directory in the build history
+    #[must_use]
+    pub fn history_key(&self) -> String {
+        format!("{}/{}", self.target, self.profile)
+    }
+}
+
+/// Find the per-target artifact directories in `target_dir`, including the
+/// target directories of triples built with `build.isolate_targets`
+///
+/// Host builds without `--target` (`target/debug`, `target/release`) are not
+/// per-target directories and are left alone.
+#[must_use]
+pub fn scan_artifact_dirs(target_dir: &Path, history: &BuildHistory) -> Vec<ArtifactDir> {
+    let mut dirs = scan_triple_dirs(target_dir, history);
+    if let Ok(isolated) = std::fs::read_dir(target_dir.join(ISOLATED_DIR)) {
+        for entry in isolated.filter_map(std::result::Result::ok) {
+            dirs.extend(scan_triple_dirs(&entry.path(), history));
+        }
+    }
+    dirs
+}
+
+fn scan_triple_dirs(target_dir: &Path, history: &BuildHistory) -> Vec<ArtifactDir> {
+    let Ok(entries) = std::fs::read_dir(target_dir) else {
+        return Vec::new();
+    };
+
+    let mut dirs = Vec::new();
+    for triple_dir in entries.filter_map(std::result::Result::ok) {
+        let target = triple_dir.file_name().to_string_lossy().to_string();
+        // Target triples always have at least two components
+        if target.starts_with('.') || !target.contains('-') || !triple_dir.path().is_dir() {
+            continue;
+        }
+        let Ok(profiles) = std::fs::read_dir(triple_dir.path()) else {
+            continue;
+        };
+
+        for profile_dir in profiles.filter_map(std::result::Result::ok) {
+            let path = profile_dir.path();
+            // Cargo keeps fingerprints in every profile directory it builds
+            if !path.join(".fingerprint").is_dir() {
+                continue;
+            }
+            let profile = profile_dir.file_name().to_string_lossy().to_string();
+            let last_built = history
+                .targets
+                .get(&format!("{target}/{profile}"))
+                .and_then(|entry| entry.built_at)
+                .or_else(|| modified_secs(&path))
+                .unwrap_or(0);
+
+            dirs.push(ArtifactDir {
+                target: target.clone(),
+                profile,
+                size_bytes: dir_size(&path),
+                path,
+                last_built,
+            });
+        }
+    }
+    dirs
+}
+
+/// Remove `dirs` and their build history entries
+///
+/// Target directories left without profile directories are removed as well.
+///
+/// # Errors
+/// Returns an error if a directory cannot be removed
+pub fn remove_artifact_dirs(dirs: &[ArtifactDir], history: &mut BuildHistory) -> Result<()> {
+    for dir in dirs {
+        std::fs::remove_dir_all(&dir.path)
+            .map_err(|e| Error::Build(format!("Failed to remove {}: {e}", dir.path.display())))?;
+        history.targets.remove(&dir.history_key());
+
+        // Cargo leaves a CACHEDIR.TAG next to the profile directories
+        if let Some(parent) = dir.path.parent() {
+            let only_tag = std::fs::read_dir(parent).is_ok_and(|entries| {
+                entries
+                    .filter_map(std::result::Result::ok)
+                    .all(|entry| entry.file_name() == "CACHEDIR.TAG")
+            });
+            if only_tag {
+                let _ = std::fs::remove_dir_all(parent);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Latest modification time of `path` or the files directly inside it
+fn modified_secs(path: &Path) -> Option<u64> {
+    let modified = |metadata: std::fs::Metadata| {
+        metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|elapsed| elapsed.as_secs())
+    };
+
+    let entries = std::fs::read_dir(path)
+        .into_iter()
+        .flatten()
+        .filter_map(std::result::Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter_map(modified);
+    std::fs::metadata(path)
+        .ok()
+        .and_then(modified)
+        .into_iter()
+        .chain(entries)
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::build::HistoryEntry;
+    use tempfile::TempDir;
+
+    fn dir(target: &str, size_bytes: u64, last_built: u64) -> ArtifactDir {
+        ArtifactDir {
+            target: target.to_string(),
+            profile: "release".to_string(),
+            path: PathBuf::from(target),
+            size_bytes,
+            last_built,
+        }
+    }
+
+    fn targets(dirs: &[ArtifactDir]) -> Vec<&str> {
+        dirs.iter().map(|d| d.target.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("4096").unwrap(), 4096);
+        assert_eq!(parse_size("20GiB").unwrap(), 20 << 30);
+        assert_eq!(parse_size(" 2 gb ").unwrap(), 2_000_000_000);
+        assert_eq!(parse_size("0.5k").unwrap(), 512);
+        assert!(parse_size("").is_err());
+        assert!(parse_size("0").is_err());
+        assert!(parse_size("10 parsecs").is_err());
+    }
+
+    #[test]
+    fn test_keep_last_builds() {
+        let policy = RetentionPolicy {
+            keep_last_builds: Some(2),
+            max_total_size: None,
+        };
+        let (kept, removed) = policy.apply(vec![
+            dir("old-target", 10, 100),
+            dir("newest-target", 10, 300),
+            dir("middle-target", 10, 200),
+        ]);
+        assert_eq!(targets(&kept), ["newest-target", "middle-target"]);
+        assert_eq!(targets(&removed), ["old-target"]);
+    }
+
+    #[test]
+    fn test_max_total_size() {
+        let policy = RetentionPolicy {
+            keep_last_builds: None,
+            max_total_size: Some(100),
+        };
+        let (kept, removed) = policy.apply(vec![
+            dir("a-new", 60, 300),
+            dir("b-middle", 50, 200),
+            dir("c-old", 10, 100),
+        ]);
+        // The oldest directory would fit, but everything older than the
+        // first directory over the limit goes
+        assert_eq!(targets(&kept), ["a-new"]);
+        assert_eq!(targets(&removed), ["b-middle", "c-old"]);
+
+        assert!(RetentionPolicy::default()
+            .apply(vec![dir("a-b", 1, 1)])
+            .1
+            .is_empty());
+    }
+
+    #[test]
+    fn test_scan_and_remove() {
+        let temp = TempDir::new().unwrap();
+        let target_dir = temp.path();
+        for (triple, profile) in [
+            ("aarch64-unknown-linux-gnu", "release"),
+            ("x86_64-pc-windows-gnu", "debug"),
+        ] {
+            let profile_dir = target_dir.join(triple).join(profile);
+            std::fs::create_dir_all(profile_dir.join(".fingerprint")).unwrap();
+            std::fs::write(profile_dir.join("app"), vec![0u8; 100]).unwrap();
+            std::fs::write(target_dir.join(triple).join("CACHEDIR.TAG"), "").unwrap();
+        }
+        // Isolated targets have a target directory of their own
+        let riscv = "riscv64gc-unknown-linux-gnu";
+        let isolated = target_dir.join(ISOLATED_DIR).join(riscv).join(riscv);
+        std::fs::create_dir_all(isolated.join("debug/.fingerprint")).unwrap();
+        // Host builds and other directories are not per-target artifacts
+        std::fs::create_dir_all(target_dir.join("debug/.fingerprint")).unwrap();
+        std::fs::create_dir_all(target_dir.join("wasm32-unknown-unknown/doc")).unwrap();
+
+        let mut history = BuildHistory::default();
+        history.targets.insert(
+            "aarch64-unknown-linux-gnu/release".to_string(),
+            HistoryEntry {
+                size_bytes: 100,
+                duration_ms: 1000,
+                built_at: Some(42),
+            },
+        );
+
+        let mut dirs = scan_artifact_dirs(target_dir, &history);
+        dirs.sort_by(|a, b| a.target.cmp(&b.target));
+        assert_eq!(
+            targets(&dirs),
+            [
+                "aarch64-unknown-linux-gnu",
+                "riscv64gc-unknown-linux-gnu",
+                "x86_64-pc-windows-gnu"
+            ]
+        );
+        assert_eq!(dirs[0].last_built, 42);
+        assert_eq!(dirs[0].size_bytes, 100);
+        assert!(dirs[1].last_built > 42);
+
+        remove_artifact_dirs(&dirs[..1], &mut history).unwrap();
+        assert!(!target_dir.join("aarch64-unknown-linux-gnu").exists());
+        assert!(target_dir.join("x86_64-pc-windows-gnu/debug").exists());
+        assert!(history.targets.is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+//! Programmatic result of a single-target build
+//!
+//! [`Builder::build_with_report`] returns what happened instead of leaving it
+//! in the terminal output, so tools embedding xcargo can find the artifacts,
+//! the strategy that was used, and rustc's warnings.
+
+use crate::error::Result;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use super::diagnostics::Diagnostics;
+use super::executor::Builder;
+use super::options::{BuildOptions, BuildStrategy, CargoOperation};
+
+/// What a successful cargo operation for one target produced
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BuildReport {
+    /// Target triple cargo built for
+    pub target: String,
+
+    /// How the target was cross-compiled
+    pub strategy: Option<BuildStrategy>,
+
+    /// Strategies that failed before `strategy`, with `build.fallback`
+    pub fallback_from: Vec<BuildStrategy>,
+
+    /// Wall time of the operation
+    pub duration: Duration,
+
+    /// Binaries and libraries the build produced, as cargo reported them
+    pub artifacts: Vec<PathBuf>,
+
+    /// Warnings (and, for failed builds, errors) rustc reported
+    pub diagnostics: Diagnostics,
+}
+
+impl BuildReport {
+    /// Number of warnings rustc reported
+    #[must_use]
+    pub fn warnings(&self) -> usize {
+        self.diagnostics.warnings.len()
+    }
+}
+
+impl Builder {
+    /// Run the cargo operation for a single target and report the outcome
+    ///
+    /// Progress is still printed as with [`Builder::build`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xcargo::build::{Builder, BuildOptions};
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let builder = Builder::new()?;
+    /// let options = BuildOptions {
+    ///     target: Some("x86_64-pc-windows-gnu".to_string()),
+    ///     release: true,
+    ///     ..Default::default()
+    /// };
+    /// let report = builder.build_with_report(&options)?;
+    /// for artifact in &report.artifacts {
+    ///     println!("{}", artifact.display());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Builder::build`].
+    pub fn build_with_report(&self, options: &BuildOptions) -> Result<BuildReport> {
+        let mut report = BuildReport::default();
+        let started = Instant::now();
+        self.execute(options, &mut report)?;
+        report.duration = started.elapsed();
+
+        if options.operation == CargoOperation::Build && report.artifacts.is_empty() {
+            let profile = if options.release { "release" } else { "debug" };
+            report.artifacts = self.artifact_paths(&report.target, profile);
+        }
+        Ok(report)
+    }
+}
@@ -0,0 +1,460 @@
+//! Timeouts and retries for test binaries run through a target runner
+//!
+//! Emulated test runs (e.g. under QEMU) occasionally hang or fail
+//! spuriously. When `[runners.<triple>]` sets `test_timeout` or `retries`,
+//! cargo runs each test binary through `xcargo __run-test`, which enforces the
+//! timeout, retries failed runs, and appends what happened to an events file.
+//! The build then reports those events separately from real test failures.
+
+use crate::config::RunnerConfig;
+use crate::error::{Error, Result};
+use crate::output::log;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// Hidden xcargo subcommand cargo invokes for each test binary
+pub const RUN_TEST_COMMAND: &str = "__run-test";
+
+/// Environment variable naming the file runner events are appended to
+pub const RUNNER_EVENTS_ENV: &str = "XCARGO_RUNNER_EVENTS";
+
+/// Exit code reported for a test binary that timed out, as with `timeout(1)`
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// How often a running test binary is checked against its timeout
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Parse a duration such as "90s", "10m", "1h30m", or "45" (seconds)
+///
+/// # Errors
+/// Returns an error for empty input, unknown units, or a zero duration
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use xcargo::build::parse_duration;
+///
+/// assert_eq!(parse_duration("10m").unwrap(), Duration::from_secs(600));
+/// assert_eq!(parse_duration("1h30m").unwrap(), Duration::from_secs(5400));
+/// assert!(parse_duration("10 minutes").is_err());
+/// ```
+pub fn parse_duration(value: &str) -> Result<Duration> {
+    let invalid = || {
+        Error::Config(format!(
+            "Invalid duration '{value}'. Use a number with a unit, e.g. \"90s\", \"10m\", or \"1h\""
+        ))
+    };
+
+    let mut total = 0u64;
+    let mut digits = String::new();
+    for c in value.trim().chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            _ => return Err(invalid()),
+        };
+        let amount: u64 = digits.parse().map_err(|_| invalid())?;
+        total += amount * unit;
+        digits.clear();
+    }
+    if !digits.is_empty() {
+        total += digits.parse::<u64>().map_err(|_| invalid())?;
+    }
+
+    if total == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// Format a duration in the largest whole unit, e.g. "10m" or "90s"
+#[must_use]
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs > 0 && secs % 3600 == 0 {
+        format!("{}h", secs / 3600)
+    } else if secs > 0 && secs % 60 == 0 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// Timeout and retry settings applied to each test binary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RunnerPolicy {
+    /// Time a single run of a test binary may take
+    pub timeout: Option<Duration>,
+
+    /// How many times a failed or timed-out run is retried
+    pub retries: u32,
+}
+
+impl RunnerPolicy {
+    /// Policy described by a `[runners.<triple>]` section
+    ///
+    /// # Errors
+    /// Returns an error if `test_timeout` is not a valid duration
+    pub fn from_config(config: &RunnerConfig) -> Result<Self> {
+        Ok(Self {
+            timeout: config
+                .test_timeout
+                .as_deref()
+                .map(parse_duration)
+                .transpose()?,
+            retries: config.retries,
+        })
+    }
+
+    /// Whether the policy changes how test binaries are run
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.timeout.is_some() || self.retries > 0
+    }
+
+    /// Runner command that wraps `runner` (if any) in `xcargo __run-test`
+    ///
+    /// Cargo splits runner commands on whitespace and appends the test
+    /// binary and its arguments.
+    #[must_use]
+    pub fn wrapper_command(&self, xcargo: &Path, runner: Option<&str>) -> String {
+        let runner_words: Vec<&str> = runner
+            .map(|r| r.split_whitespace().collect())
+            .unwrap_or_default();
+
+        let mut command = vec![
+            xcargo.display().to_string(),
+            RUN_TEST_COMMAND.to_string(),
+            "--runner-len".to_string(),
+            runner_words.len().to_string(),
+        ];
+        if let Some(timeout) = self.timeout {
+            command.push("--timeout".to_string());
+            command.push(format_duration(timeout));
+        }
+        if self.retries > 0 {
+            command.push("--retries".to_string());
+            command.push(self.retries.to_string());
+        }
+        command.push("--".to_string());
+        command.extend(runner_words.into_iter().map(str::to_string));
+        command.join(" ")
+    }
+}
+
+/// What happened to one run of a test binary
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunnerEventKind {
+    /// The run exceeded the timeout and was killed
+    Timeout,
+    /// The run failed and was retried
+    Retry,
+    /// A retried binary passed
+    Recovered,
+}
+
+/// A timeout or retry recorded while running a test binary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunnerEvent {
+    /// Test binary file name
+    pub binary: String,
+
+    /// What happened
+    pub kind: RunnerEventKind,
+
+    /// Attempt the event belongs to, starting at 1
+    pub attempt: u32,
+
+    /// Attempts allowed (retries + 1)
+    pub max_attempts: u32,
+
+    /// Exit code of a failed run
+    pub exit_code: Option<i32>,
+}
+
+/// Run a test binary under `policy` and return the exit code to report
+///
+/// `command` is the runner (its first `runner_len` words) followed by the
+/// test binary and its arguments. Timeouts and retries are appended to
+/// `events` if given.
+///
+/// # Errors
+/// Returns an error if the command is empty or cannot be started
+pub fn run_with_policy(
+    command: &[String],
+    runner_len: usize,
+    policy: &RunnerPolicy,
+    events: Option<&Path>,
+) -> Result<i32> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(Error::Build(
+            "No test binary given to the runner".to_string(),
+        ));
+    };
+    let binary = command
+        .get(runner_len)
+        .map(|path| {
+            Path::new(path)
+                .file_name()
+                .map_or_else(|| path.clone(), |name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+
+    let max_attempts = policy.retries + 1;
+    let record = |kind: RunnerEventKind, attempt: u32, exit_code: Option<i32>| {
+        if let Some(path) = events {
+            let event = RunnerEvent {
+                binary: binary.clone(),
+                kind,
+                attempt,
+                max_attempts,
+                exit_code,
+            };
+            append_event(path, &event);
+        }
+    };
+
+    let mut exit_code = 0;
+    for attempt in 1..=max_attempts {
+        let retrying = attempt < max_attempts;
+
+        match run_once(program, args, policy.timeout)? {
+            RunOutcome::Exited(0) => {
+                if attempt > 1 {
+                    record(RunnerEventKind::Recovered, attempt, None);
+                }
+                return Ok(0);
+            }
+            RunOutcome::TimedOut => {
+                eprintln!(
+                    "xcargo: {binary} timed out after {} (attempt {attempt}/{max_attempts})",
+                    policy.timeout.map(format_duration).unwrap_or_default()
+                );
+                record(RunnerEventKind::Timeout, attempt, None);
+                exit_code = TIMEOUT_EXIT_CODE;
+            }
+            RunOutcome::Exited(code) => {
+                if retrying {
+                    eprintln!(
+                        "xcargo: {binary} failed with exit code {code}, retrying (attempt {attempt}/{max_attempts})"
+                    );
+                    record(RunnerEventKind::Retry, attempt, Some(code));
+                }
+                exit_code = code;
+            }
+        }
+    }
+    Ok(exit_code)
+}
+
+/// How a single run of a test binary ended
+enum RunOutcome {
+    Exited(i32),
+    TimedOut,
+}
+
+/// Run the command once, killing it after `timeout`
+fn run_once(program: &str, args: &[String], timeout: Option<Duration>) -> Result<RunOutcome> {
+    let mut cmd = Command::new(program);
+    cmd.args(args);
+    log::command(&cmd);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| Error::Build(format!("Failed to run {program}: {e}")))?;
+
+    let Some(timeout) = timeout else {
+        let status = child.wait()?;
+        return Ok(RunOutcome::Exited(status.code().unwrap_or(1)));
+    };
+
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(RunOutcome::Exited(status.code().unwrap_or(1)));
+        }
+        if started.elapsed() >= timeout {
+            // The process may exit between the check and the kill
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(RunOutcome::TimedOut);
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn append_event(path: &Path, event: &RunnerEvent) {
+    let Ok(line) = serde_json::to_string(event) else {
+        return;
+    };
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(path) {
+        let _ = writeln!(file, "{line}");
+    }
+}
+
+/// Events file for a test run of `target`
+#[must_use]
+pub fn events_path(target: &str) -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "xcargo-runner-{}-{target}.jsonl",
+        std::process::id()
+    ))
+}
+
+/// Read the events recorded in `path`, skipping malformed lines
+#[must_use]
+pub fn read_events(path: &Path) -> Vec<RunnerEvent> {
+    std::fs::read_to_string(path)
+        .map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Summary of runner events, kept apart from test results
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RunnerReport {
+    /// Runs killed for exceeding the timeout
+    pub timeouts: usize,
+
+    /// Failed runs that were retried
+    pub retries: usize,
+
+    /// Binaries that passed after being retried
+    pub recovered: Vec<String>,
+}
+
+impl RunnerReport {
+    /// Summarize recorded events
+    #[must_use]
+    pub fn from_events(events: &[RunnerEvent]) -> Self {
+        let count = |kind| events.iter().filter(|e| e.kind == kind).count();
+        Self {
+            timeouts: count(RunnerEventKind::Timeout),
+            retries: count(RunnerEventKind::Retry),
+            recovered: events
+                .iter()
+                .filter(|e| e.kind == RunnerEventKind::Recovered)
+                .map(|e| e.binary.clone())
+                .collect(),
+        }
+    }
+
+    /// Whether nothing was recorded
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.timeouts == 0 && self.retries == 0 && self.recovered.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn policy(timeout: Option<Duration>, retries: u32) -> RunnerPolicy {
+        RunnerPolicy { timeout, retries }
+    }
+
+    fn sh(script: &str) -> Vec<String> {
+        vec!["sh".to_string(), "-c".to_string(), script.to_string()]
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("45").unwrap(), Duration::from_secs(45));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("10d").is_err());
+        assert!(parse_duration("m").is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(600)), "10m");
+        assert_eq!(format_duration(Duration::from_secs(7200)), "2h");
+        assert_eq!(format_duration(Duration::from_secs(90)), "90s");
+    }
+
+    #[test]
+    fn test_wrapper_command() {
+        let command = policy(Some(Duration::from_secs(600)), 1).wrapper_command(
+            Path::new("/usr/bin/xcargo"),
+            Some("qemu-aarch64 -L /usr/aarch64-linux-gnu"),
+        );
+        assert_eq!(
+            command,
+            "/usr/bin/xcargo __run-test --runner-len 3 --timeout 10m --retries 1 -- qemu-aarch64 -L /usr/aarch64-linux-gnu"
+        );
+
+        let command = policy(None, 2).wrapper_command(Path::new("xcargo"), None);
+        assert_eq!(command, "xcargo __run-test --runner-len 0 --retries 2 --");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_timeout_is_recorded() {
+        let temp = TempDir::new().unwrap();
+        let events = temp.path().join("events.jsonl");
+
+        let code = run_with_policy(
+            &sh("sleep 5"),
+            0,
+            &policy(Some(Duration::from_millis(100)), 1),
+            Some(&events),
+        )
+        .unwrap();
+        assert_eq!(code, TIMEOUT_EXIT_CODE);
+
+        let recorded = read_events(&events);
+        assert_eq!(recorded.len(), 2);
+        assert!(recorded.iter().all(|e| e.kind == RunnerEventKind::Timeout));
+        assert_eq!(RunnerReport::from_events(&recorded).timeouts, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_retry_recovers_flaky_binary() {
+        let temp = TempDir::new().unwrap();
+        let events = temp.path().join("events.jsonl");
+        let marker = temp.path().join("ran-once");
+        let script = format!(
+            "if [ -e {0} ]; then exit 0; else touch {0}; exit 101; fi",
+            marker.display()
+        );
+
+        let code = run_with_policy(&sh(&script), 0, &policy(None, 1), Some(&events)).unwrap();
+        assert_eq!(code, 0);
+
+        let report = RunnerReport::from_events(&read_events(&events));
+        assert_eq!(report.retries, 1);
+        assert_eq!(report.recovered.len(), 1);
+        assert_eq!(report.timeouts, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_real_failure_is_not_an_event() {
+        let temp = TempDir::new().unwrap();
+        let events = temp.path().join("events.jsonl");
+
+        let code = run_with_policy(&sh("exit 101"), 0, &policy(None, 0), Some(&events)).unwrap();
+        assert_eq!(code, 101);
+        assert!(RunnerReport::from_events(&read_events(&events)).is_empty());
+    }
+}
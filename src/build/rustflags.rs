@@ -0,0 +1,324 @@
+//! Rustflags a build runs with
+//!
+//! Cargo reads rustc flags from one place only: `CARGO_ENCODED_RUSTFLAGS`,
+//! else `RUSTFLAGS`, else `.cargo/config.toml`. xcargo builds a single list
+//! and sets it as `CARGO_ENCODED_RUSTFLAGS`, which keeps flags containing
+//! spaces intact, in this order:
+//!
+//! 1. `[build] rustflags` from xcargo.toml
+//! 2. `rustflags` of the target's section
+//! 3. flags xcargo needs for the build strategy (e.g. rust-lld's linker flavor)
+//! 4. the environment's `CARGO_ENCODED_RUSTFLAGS` or `RUSTFLAGS`
+//!
+//! Later flags win where rustc only keeps one value, so a `RUSTFLAGS` set for
+//! a single invocation overrides the configuration. Rustflags in
+//! `.cargo/config.toml` are ignored by cargo once xcargo sets its flags, so
+//! xcargo warns when its flags would hide them.
+//!
+//! Rustdoc doesn't get rustflags, so `cargo doc` runs with their `--cfg` flags
+//! added to `RUSTDOCFLAGS`.
+
+use crate::config::Config;
+use std::path::{Path, PathBuf};
+
+/// Separator of the flags in `CARGO_ENCODED_RUSTFLAGS`
+const ENCODED_SEPARATOR: &str = "\x1f";
+
+/// `flags` in the form of `CARGO_ENCODED_RUSTFLAGS` and
+/// `CARGO_ENCODED_RUSTDOCFLAGS`
+#[must_use]
+pub(super) fn encode(flags: &[String]) -> String {
+    flags.join(ENCODED_SEPARATOR)
+}
+
+/// Flags of a `CARGO_ENCODED_RUSTFLAGS` value
+#[must_use]
+pub(super) fn decode(encoded: &str) -> Vec<String> {
+    encoded
+        .split(ENCODED_SEPARATOR)
+        .filter(|flag| !flag.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Flags from xcargo.toml for a target: `[build] rustflags`, then the
+/// target's own `rustflags`
+#[must_use]
+pub(super) fn configured_rustflags(config: &Config, triple: &str) -> Vec<String> {
+    let mut flags = config.build.rustflags.clone();
+    if let Some(target_flags) = config
+        .get_target_config(triple)
+        .and_then(|c| c.rustflags.as_ref())
+    {
+        flags.extend(target_flags.iter().cloned());
+    }
+    flags
+}
+
+/// The environment's rustflags, as cargo would read them
+#[must_use]
+pub(super) fn ambient_rustflags() -> Vec<String> {
+    parse_ambient(
+        std::env::var("CARGO_ENCODED_RUSTFLAGS").ok().as_deref(),
+        std::env::var("RUSTFLAGS").ok().as_deref(),
+    )
+}
+
+/// Rustflags for a target, or `None` if xcargo adds no flags and the
+/// environment's are left for cargo to read
+#[must_use]
+pub(super) fn merged_rustflags(
+    config: &Config,
+    triple: &str,
+    strategy_flags: &[String],
+) -> Option<Vec<String>> {
+    merge(
+        configured_rustflags(config, triple),
+        strategy_flags,
+        ambient_rustflags(),
+    )
+}
+
+/// Flags for rustdoc: the `--cfg` and `--check-cfg` flags of `rustflags`,
+/// then the environment's `CARGO_ENCODED_RUSTDOCFLAGS` or `RUSTDOCFLAGS`
+///
+/// Cargo passes rustflags to rustc only, so without their cfgs the items
+/// behind them would be missing from the docs.
+#[must_use]
+pub(super) fn rustdoc_flags(rustflags: &[String]) -> Vec<String> {
+    let mut flags = cfg_flags(rustflags);
+    flags.extend(parse_ambient(
+        std::env::var("CARGO_ENCODED_RUSTDOCFLAGS").ok().as_deref(),
+        std::env::var("RUSTDOCFLAGS").ok().as_deref(),
+    ));
+    flags
+}
+
+/// The first `.cargo/config.toml` from `dir` upwards that sets rustflags for
+/// `triple`, which cargo ignores while xcargo sets its flags
+#[must_use]
+pub(super) fn shadowed_cargo_config(dir: &Path, triple: &str) -> Option<PathBuf> {
+    dir.ancestors()
+        .flat_map(|dir| {
+            let cargo_dir = dir.join(".cargo");
+            [cargo_dir.join("config.toml"), cargo_dir.join("config")]
+        })
+        .find(|path| {
+            std::fs::read_to_string(path)
+                .ok()
+                .and_then(|contents| contents.parse::<toml::Table>().ok())
+                .is_some_and(|table| sets_rustflags(&table, triple))
+        })
+}
+
+/// Whether a cargo configuration sets `build.rustflags` or
+/// `target.<triple>.rustflags`
+fn sets_rustflags(table: &toml::Table, triple: &str) -> bool {
+    let has_rustflags = |section: Option<&toml::Value>| {
+        section
+            .and_then(toml::Value::as_table)
+            .is_some_and(|section| section.contains_key("rustflags"))
+    };
+    has_rustflags(table.get("build"))
+        || has_rustflags(
+            table
+                .get("target")
+                .and_then(toml::Value::as_table)
+                .and_then(|targets| targets.get(triple)),
+        )
+}
+
+/// Append the strategy's flags and then the environment's to the configured ones
+///
+/// An environment that already starts with these flags, as after
+/// `eval "$(xcargo env)"` or in rust-analyzer set up by `xcargo ide setup`,
+/// is used as it is rather than repeating them.
+fn merge(
+    mut configured: Vec<String>,
+    strategy_flags: &[String],
+    ambient: Vec<String>,
+) -> Option<Vec<String>> {
+    configured.extend(strategy_flags.iter().cloned());
+    if configured.is_empty() {
+        return None;
+    }
+    if ambient.starts_with(&configured) {
+        return Some(ambient);
+    }
+    configured.extend(ambient);
+    Some(configured)
+}
+
+/// `--cfg` and `--check-cfg` flags with their values, in either the
+/// `--cfg foo` or the `--cfg=foo` form
+fn cfg_flags(rustflags: &[String]) -> Vec<String> {
+    let mut flags = Vec::new();
+    let mut iter = rustflags.iter();
+    while let Some(flag) = iter.next() {
+        match flag.as_str() {
+            "--cfg" | "--check-cfg" => {
+                if let Some(value) = iter.next() {
+                    flags.extend([flag.clone(), value.clone()]);
+                }
+            }
+            _ if flag.starts_with("--cfg=") || flag.starts_with("--check-cfg=") => {
+                flags.push(flag.clone());
+            }
+            _ => {}
+        }
+    }
+    flags
+}
+
+/// Flags from `CARGO_ENCODED_RUSTFLAGS` if set, else from `RUSTFLAGS`
+fn parse_ambient(encoded: Option<&str>, plain: Option<&str>) -> Vec<String> {
+    match encoded {
+        Some(encoded) => decode(encoded),
+        None => plain
+            .unwrap_or_default()
+            .split_whitespace()
+            .map(String::from)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(flags: &[&str]) -> Vec<String> {
+        flags.iter().map(|flag| (*flag).to_string()).collect()
+    }
+
+    #[test]
+    fn test_configured_rustflags_order() {
+        let config = Config::from_str(
+            r#"
+            [build]
+            rustflags = ["-C", "opt-level=s"]
+
+            [targets."thumbv7em-none-eabihf"]
+            rustflags = ["-C", "link-arg=-Tlink.x"]
+        "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            configured_rustflags(&config, "thumbv7em-none-eabihf"),
+            strings(&["-C", "opt-level=s", "-C", "link-arg=-Tlink.x"])
+        );
+        assert_eq!(
+            configured_rustflags(&config, "x86_64-unknown-linux-gnu"),
+            strings(&["-C", "opt-level=s"])
+        );
+    }
+
+    #[test]
+    fn test_merge_keeps_environment_last() {
+        let merged = merge(
+            strings(&["-C", "opt-level=s"]),
+            &strings(&["-C", "linker-flavor=ld.lld"]),
+            strings(&["-C", "opt-level=3"]),
+        )
+        .unwrap();
+        assert_eq!(
+            merged,
+            strings(&[
+                "-C",
+                "opt-level=s",
+                "-C",
+                "linker-flavor=ld.lld",
+                "-C",
+                "opt-level=3"
+            ])
+        );
+
+        // Nothing configured: cargo reads the environment itself
+        assert_eq!(merge(Vec::new(), &[], strings(&["-g"])), None);
+
+        // The environment already holds xcargo's flags
+        assert_eq!(
+            merge(
+                strings(&["-C", "opt-level=s"]),
+                &[],
+                strings(&["-C", "opt-level=s", "-g"])
+            ),
+            Some(strings(&["-C", "opt-level=s", "-g"]))
+        );
+    }
+
+    #[test]
+    fn test_cfg_flags() {
+        let rustflags = strings(&[
+            "--cfg",
+            "tokio_unstable",
+            "-C",
+            "target-cpu=native",
+            "--cfg=feature=\"simd\"",
+            "--check-cfg",
+            "cfg(tokio_unstable)",
+        ]);
+        assert_eq!(
+            cfg_flags(&rustflags),
+            strings(&[
+                "--cfg",
+                "tokio_unstable",
+                "--cfg=feature=\"simd\"",
+                "--check-cfg",
+                "cfg(tokio_unstable)"
+            ])
+        );
+    }
+
+    #[test]
+    fn test_shadowed_cargo_config() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let nested = temp_dir.path().join("crates/app");
+        std::fs::create_dir_all(&nested).unwrap();
+        assert_eq!(
+            shadowed_cargo_config(&nested, "thumbv7em-none-eabihf"),
+            None
+        );
+
+        let cargo_dir = temp_dir.path().join(".cargo");
+        std::fs::create_dir(&cargo_dir).unwrap();
+        std::fs::write(
+            cargo_dir.join("config.toml"),
+            "[target.thumbv7em-none-eabihf]\nrustflags = [\"-C\", \"link-arg=-Tlink.x\"]\n",
+        )
+        .unwrap();
+        assert_eq!(
+            shadowed_cargo_config(&nested, "thumbv7em-none-eabihf"),
+            Some(cargo_dir.join("config.toml"))
+        );
+        assert_eq!(
+            shadowed_cargo_config(&nested, "x86_64-unknown-linux-gnu"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_ambient() {
+        assert_eq!(
+            parse_ambient(None, Some(" -C  debuginfo=2 ")),
+            strings(&["-C", "debuginfo=2"])
+        );
+        // Cargo prefers the encoded form, which allows spaces in a flag
+        assert_eq!(
+            parse_ambient(Some("-C\x1flink-arg=-L/my dir"), Some("-g")),
+            strings(&["-C", "link-arg=-L/my dir"])
+        );
+        assert!(parse_ambient(None, None).is_empty());
+    }
+
+    #[test]
+    fn test_encode_keeps_spaces() {
+        let flags = strings(&["-L", "/opt/my libs", "-Clink-arg=-Wl,-rpath,/my dir"]);
+        let encoded = encode(&flags);
+        assert_eq!(
+            encoded,
+            "-L\x1f/opt/my libs\x1f-Clink-arg=-Wl,-rpath,/my dir"
+        );
+        assert_eq!(decode(&encoded), flags);
+    }
+}
@@ -0,0 +1,143 @@
+//! Scheduling of concurrent multi-target builds
+//!
+//! Each cargo invocation already uses every CPU by default, so building six
+//! targets at once would run six times as many compiler processes as there
+//! are cores. The scheduler limits how many targets build at the same time and
+//! divides the job budget (`build.jobs`, or the number of CPUs) among them.
+
+/// Fewest cargo jobs given to each concurrently building target
+const MIN_JOBS_PER_TARGET: usize = 2;
+
+/// How a set of targets is built concurrently
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchedulePlan {
+    /// Target built alone, with the whole job budget, before the others start
+    pub first: Option<String>,
+
+    /// Targets built concurrently, in order
+    pub queue: Vec<String>,
+
+    /// Most targets building at the same time
+    pub max_concurrent: usize,
+
+    /// `--jobs` passed to each concurrent cargo invocation
+    pub jobs_per_target: usize,
+
+    /// Total job budget
+    pub total_jobs: usize,
+}
+
+impl SchedulePlan {
+    /// Plan a build of `targets` with a budget of `total_jobs` cargo jobs
+    ///
+    /// If `host_first` names one of the targets, it is built alone first so
+    /// build scripts and proc-macros compiled for the host are in place
+    /// before the cross builds start.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::build::SchedulePlan;
+    ///
+    /// let targets: Vec<String> = ["a", "b", "c", "d", "e", "f"]
+    ///     .iter()
+    ///     .map(|t| t.to_string())
+    ///     .collect();
+    /// let plan = SchedulePlan::new(&targets, 8, None);
+    /// assert_eq!(plan.max_concurrent, 4);
+    /// assert_eq!(plan.jobs_per_target, 2);
+    /// ```
+    #[must_use]
+    pub fn new(targets: &[String], total_jobs: usize, host_first: Option<&str>) -> Self {
+        let total_jobs = total_jobs.max(1);
+
+        let first = host_first
+            .filter(|host| targets.iter().any(|t| t == host))
+            .map(str::to_string);
+        let queue: Vec<String> = targets
+            .iter()
+            .filter(|t| first.as_ref() != Some(*t))
+            .cloned()
+            .collect();
+
+        let max_concurrent = (total_jobs / MIN_JOBS_PER_TARGET).clamp(1, queue.len().max(1));
+        let jobs_per_target = (total_jobs / max_concurrent).max(1);
+
+        Self {
+            first,
+            queue,
+            max_concurrent,
+            jobs_per_target,
+            total_jobs,
+        }
+    }
+}
+
+/// Job budget for a build: `build.jobs` if configured, otherwise the number of CPUs
+#[must_use]
+pub fn available_jobs(configured: Option<usize>) -> usize {
+    configured.unwrap_or_else(|| {
+        std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn targets(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn test_jobs_divided_among_targets() {
+        let plan = SchedulePlan::new(&targets(&["a", "b", "c", "d", "e", "f"]), 12, None);
+        assert_eq!(plan.max_concurrent, 6);
+        assert_eq!(plan.jobs_per_target, 2);
+
+        let plan = SchedulePlan::new(&targets(&["a", "b"]), 12, None);
+        assert_eq!(plan.max_concurrent, 2);
+        assert_eq!(plan.jobs_per_target, 6);
+    }
+
+    #[test]
+    fn test_small_budget_builds_one_at_a_time() {
+        let plan = SchedulePlan::new(&targets(&["a", "b", "c"]), 1, None);
+        assert_eq!(plan.max_concurrent, 1);
+        assert_eq!(plan.jobs_per_target, 1);
+
+        let plan = SchedulePlan::new(&targets(&["a", "b", "c"]), 0, None);
+        assert_eq!(plan.total_jobs, 1);
+    }
+
+    #[test]
+    fn test_single_job_budget() {
+        // Below the two jobs a target usually gets, the one job is not split
+        let plan = SchedulePlan::new(&targets(&["a"]), 1, None);
+        assert_eq!(plan.max_concurrent, 1);
+        assert_eq!(plan.jobs_per_target, 1);
+
+        let plan = SchedulePlan::new(&targets(&["host", "a", "b"]), 1, Some("host"));
+        assert_eq!(plan.max_concurrent, 1);
+        assert_eq!(plan.jobs_per_target, 1);
+        assert_eq!(plan.total_jobs, 1);
+    }
+
+    #[test]
+    fn test_host_first() {
+        let plan = SchedulePlan::new(&targets(&["a", "host", "b"]), 4, Some("host"));
+        assert_eq!(plan.first.as_deref(), Some("host"));
+        assert_eq!(plan.queue, targets(&["a", "b"]));
+
+        // A host that is not being built is ignored
+        let plan = SchedulePlan::new(&targets(&["a", "b"]), 4, Some("host"));
+        assert!(plan.first.is_none());
+        assert_eq!(plan.queue.len(), 2);
+    }
+
+    #[test]
+    fn test_available_jobs() {
+        assert_eq!(available_jobs(Some(3)), 3);
+        assert!(available_jobs(None) >= 1);
+    }
+}
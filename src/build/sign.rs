@@ -0,0 +1,408 @@
+//! Code signing of macOS and Windows release builds
+//!
+//! With a `[signing.macos]` or `[signing.windows]` table, every release build
+//! of a matching target signs its executables and libraries after the
+//! post-build steps, so provenance records describe the signed files:
+//!
+//! - macOS binaries are signed with `codesign` (hardened runtime, secure
+//!   timestamp) and optionally notarized with `xcrun notarytool`
+//! - Windows executables and DLLs are signed with `signtool`, or with
+//!   `osslsigncode` from Linux and macOS hosts
+//!
+//! Identities, certificates and passwords come from environment variables,
+//! so they can be CI secrets; when a variable is unset the build is left
+//! unsigned with a warning.
+
+use crate::config::{MacosSigningConfig, SigningConfig, WindowsSigningConfig};
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use crate::target::Target;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::attest;
+use super::postprocess::run_tool;
+use super::summary::target_dir_for;
+
+/// Variable holding the codesign identity when `identity_env` is unset
+pub const IDENTITY_ENV: &str = "APPLE_SIGNING_IDENTITY";
+
+/// Variables holding the notarization credentials by default
+const APPLE_ID_ENV: &str = "APPLE_ID";
+const TEAM_ID_ENV: &str = "APPLE_TEAM_ID";
+const APPLE_PASSWORD_ENV: &str = "APPLE_APP_PASSWORD";
+
+/// Variable holding the .pfx certificate path when `certificate_env` is unset
+pub const CERTIFICATE_ENV: &str = "WINDOWS_CERTIFICATE";
+
+/// Variable holding the certificate password when `password_env` is unset
+const CERTIFICATE_PASSWORD_ENV: &str = "WINDOWS_CERTIFICATE_PASSWORD";
+
+/// Platform whose signing applies to a target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Platform {
+    Macos,
+    Windows,
+}
+
+impl Platform {
+    fn for_target(target: &Target) -> Option<Self> {
+        if target.triple.ends_with("-apple-darwin") {
+            Some(Self::Macos)
+        } else if target.os == "windows" {
+            Some(Self::Windows)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a built file is signed: executables and dynamic libraries
+    fn signs(self, path: &Path) -> bool {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        match self {
+            Self::Macos => matches!(extension, None | Some("dylib")),
+            Self::Windows => matches!(extension, Some("exe" | "dll")),
+        }
+    }
+}
+
+/// Sign a target's release build, if signing is configured for it
+///
+/// # Errors
+/// Returns an error if a signing tool is missing or fails
+pub(super) fn sign_artifacts(target: &Target, config: &SigningConfig, release: bool) -> Result<()> {
+    let Some(platform) = Platform::for_target(target) else {
+        return Ok(());
+    };
+    let selected = config.targets.is_empty()
+        || config
+            .targets
+            .iter()
+            .any(|t| *t == target.triple || *t == target.versioned_triple());
+    if !release || !selected {
+        return Ok(());
+    }
+    let files: Vec<PathBuf> =
+        attest::artifacts(&target_dir_for(&target.triple), &target.triple, release)
+            .into_iter()
+            .filter(|path| platform.signs(path))
+            .collect();
+    match platform {
+        _ if files.is_empty() => Ok(()),
+        Platform::Macos => config
+            .macos
+            .as_ref()
+            .map_or(Ok(()), |macos| sign_macos(macos, &files)),
+        Platform::Windows => config
+            .windows
+            .as_ref()
+            .map_or(Ok(()), |windows| sign_windows(windows, &files)),
+    }
+}
+
+fn sign_macos(config: &MacosSigningConfig, files: &[PathBuf]) -> Result<()> {
+    let identity_env = config.identity_env.as_deref().unwrap_or(IDENTITY_ENV);
+    let Some(identity) = env(identity_env) else {
+        helpers::warning(format!(
+            "Not signing the macOS binaries: {identity_env} is not set"
+        ));
+        return Ok(());
+    };
+    let codesign = which::which("codesign").map_err(|_| {
+        helpers::hint("Sign macOS binaries on a macOS host, e.g. a macOS CI runner");
+        Error::Build("[signing.macos] needs codesign, which only runs on macOS".to_string())
+    })?;
+
+    for file in files {
+        let cmd = codesign_command(&codesign, &identity, config.entitlements.as_deref(), file);
+        run_tool(cmd, "codesign", file)?;
+        helpers::success(format!("Signed {}", file_name(file)));
+    }
+
+    if config.notarize == Some(true) {
+        let Some(credentials) = notary_credentials(config) else {
+            helpers::warning(format!(
+                "Not notarizing: set notary_profile, or {}, {} and {}",
+                config.apple_id_env.as_deref().unwrap_or(APPLE_ID_ENV),
+                config.team_id_env.as_deref().unwrap_or(TEAM_ID_ENV),
+                config.password_env.as_deref().unwrap_or(APPLE_PASSWORD_ENV)
+            ));
+            return Ok(());
+        };
+        for file in files {
+            notarize(file, &credentials)?;
+        }
+    }
+    Ok(())
+}
+
+fn codesign_command(
+    codesign: &Path,
+    identity: &str,
+    entitlements: Option<&str>,
+    file: &Path,
+) -> Command {
+    let mut cmd = Command::new(codesign);
+    cmd.args([
+        "--force",
+        "--timestamp",
+        "--options",
+        "runtime",
+        "--sign",
+        identity,
+    ]);
+    if let Some(entitlements) = entitlements {
+        cmd.arg("--entitlements").arg(entitlements);
+    }
+    cmd.arg(file);
+    cmd
+}
+
+/// notarytool arguments selecting the account to notarize with
+fn notary_credentials(config: &MacosSigningConfig) -> Option<Vec<String>> {
+    if let Some(profile) = &config.notary_profile {
+        return Some(vec!["--keychain-profile".to_string(), profile.clone()]);
+    }
+    let apple_id = env(config.apple_id_env.as_deref().unwrap_or(APPLE_ID_ENV))?;
+    let team_id = env(config.team_id_env.as_deref().unwrap_or(TEAM_ID_ENV))?;
+    let password = env(config.password_env.as_deref().unwrap_or(APPLE_PASSWORD_ENV))?;
+    Some(vec![
+        "--apple-id".to_string(),
+        apple_id,
+        "--team-id".to_string(),
+        team_id,
+        "--password".to_string(),
+        password,
+    ])
+}
+
+/// Submit a signed binary, zipped as notarytool requires, and wait for the
+/// verdict; a bare binary cannot be stapled, so Gatekeeper looks the ticket
+/// up online
+fn notarize(file: &Path, credentials: &[String]) -> Result<()> {
+    let archive = PathBuf::from(format!("{}.zip", file.display()));
+    let mut zip = Command::new("ditto");
+    zip.args(["-c", "-k", "--keepParent"])
+        .arg(file)
+        .arg(&archive);
+    run_tool(zip, "ditto", file)?;
+
+    helpers::progress(format!(
+        "Notarizing {} (this can take a few minutes)",
+        file_name(file)
+    ));
+    let mut submit = Command::new("xcrun");
+    submit
+        .args(["notarytool", "submit"])
+        .arg(&archive)
+        .arg("--wait")
+        .args(credentials);
+    let submitted = run_tool(submit, "notarytool", file);
+    let _ = std::fs::remove_file(&archive);
+    submitted?;
+    helpers::success(format!("Notarized {}", file_name(file)));
+    Ok(())
+}
+
+/// Tool signing Windows binaries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WindowsSigner {
+    Signtool,
+    Osslsigncode,
+}
+
+impl WindowsSigner {
+    fn from_config(tool: Option<&str>, host_os: &str) -> Result<Self> {
+        match tool {
+            Some("signtool") => Ok(Self::Signtool),
+            Some("osslsigncode") => Ok(Self::Osslsigncode),
+            Some(other) => Err(Error::Config(format!(
+                "signing.windows.tool must be \"signtool\" or \"osslsigncode\", not \"{other}\""
+            ))),
+            None if host_os == "windows" => Ok(Self::Signtool),
+            None => Ok(Self::Osslsigncode),
+        }
+    }
+
+    fn program(self) -> &'static str {
+        match self {
+            Self::Signtool => "signtool",
+            Self::Osslsigncode => "osslsigncode",
+        }
+    }
+
+    /// Command signing `file`; osslsigncode writes the signed copy to `output`
+    fn command(
+        self,
+        certificate: &str,
+        password: Option<&str>,
+        timestamp_url: Option<&str>,
+        file: &Path,
+        output: &Path,
+    ) -> Command {
+        let mut cmd = Command::new(self.program());
+        match self {
+            Self::Signtool => {
+                cmd.args(["sign", "/fd", "SHA256", "/f", certificate]);
+                if let Some(password) = password {
+                    cmd.args(["/p", password]);
+                }
+                if let Some(url) = timestamp_url {
+                    cmd.args(["/tr", url, "/td", "SHA256"]);
+                }
+                cmd.arg(file);
+            }
+            Self::Osslsigncode => {
+                cmd.args(["sign", "-h", "sha256", "-pkcs12", certificate]);
+                if let Some(password) = password {
+                    cmd.args(["-pass", password]);
+                }
+                if let Some(url) = timestamp_url {
+                    cmd.args(["-ts", url]);
+                }
+                cmd.arg("-in").arg(file).arg("-out").arg(output);
+            }
+        }
+        cmd
+    }
+}
+
+fn sign_windows(config: &WindowsSigningConfig, files: &[PathBuf]) -> Result<()> {
+    let certificate_env = config.certificate_env.as_deref().unwrap_or(CERTIFICATE_ENV);
+    let Some(certificate) = env(certificate_env) else {
+        helpers::warning(format!(
+            "Not signing the Windows binaries: {certificate_env} is not set"
+        ));
+        return Ok(());
+    };
+    let password = env(config
+        .password_env
+        .as_deref()
+        .unwrap_or(CERTIFICATE_PASSWORD_ENV));
+    let signer = WindowsSigner::from_config(config.tool.as_deref(), std::env::consts::OS)?;
+    if which::which(signer.program()).is_err() {
+        helpers::hint(match signer {
+            WindowsSigner::Signtool => "signtool comes with the Windows SDK",
+            WindowsSigner::Osslsigncode => {
+                "Install osslsigncode, e.g. sudo apt-get install osslsigncode or brew install osslsigncode"
+            }
+        });
+        return Err(Error::Build(format!(
+            "[signing.windows] needs {}, which is not on PATH",
+            signer.program()
+        )));
+    }
+
+    for file in files {
+        let output = PathBuf::from(format!("{}.signed", file.display()));
+        let cmd = signer.command(
+            &certificate,
+            password.as_deref(),
+            config.timestamp_url.as_deref(),
+            file,
+            &output,
+        );
+        run_tool(cmd, signer.program(), file)?;
+        if signer == WindowsSigner::Osslsigncode {
+            std::fs::rename(&output, file)?;
+        }
+        helpers::success(format!("Signed {}", file_name(file)));
+    }
+    Ok(())
+}
+
+/// A non-empty environment variable
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::log::command_line;
+
+    #[test]
+    fn test_platform() {
+        let platform = |triple| Platform::for_target(&Target::from_triple(triple).unwrap());
+        assert_eq!(platform("aarch64-apple-darwin"), Some(Platform::Macos));
+        assert_eq!(platform("x86_64-pc-windows-gnu"), Some(Platform::Windows));
+        assert_eq!(platform("aarch64-apple-ios"), None);
+        assert_eq!(platform("x86_64-unknown-linux-gnu"), None);
+
+        assert!(Platform::Macos.signs(Path::new("release/app")));
+        assert!(Platform::Macos.signs(Path::new("release/libapp.dylib")));
+        assert!(!Platform::Macos.signs(Path::new("release/libapp.a")));
+        assert!(Platform::Windows.signs(Path::new("release/app.dll")));
+        assert!(!Platform::Windows.signs(Path::new("release/app.pdb")));
+    }
+
+    #[test]
+    fn test_signing_commands() {
+        let cmd = codesign_command(
+            Path::new("codesign"),
+            "Developer ID Application: Acme (TEAM)",
+            Some("app.entitlements"),
+            Path::new("app"),
+        );
+        assert_eq!(
+            command_line(&cmd),
+            "codesign --force --timestamp --options runtime --sign 'Developer ID Application: Acme (TEAM)' --entitlements app.entitlements app"
+        );
+
+        let app = Path::new("app.exe");
+        let signed = Path::new("app.exe.signed");
+        let cmd = WindowsSigner::Signtool.command("cert.pfx", Some("pw"), None, app, signed);
+        assert_eq!(
+            command_line(&cmd),
+            "signtool sign /fd SHA256 /f cert.pfx /p pw app.exe"
+        );
+        let cmd = WindowsSigner::Osslsigncode.command(
+            "cert.pfx",
+            None,
+            Some("http://timestamp.digicert.com"),
+            app,
+            signed,
+        );
+        assert_eq!(
+            command_line(&cmd),
+            "osslsigncode sign -h sha256 -pkcs12 cert.pfx -ts http://timestamp.digicert.com -in app.exe -out app.exe.signed"
+        );
+    }
+
+    #[test]
+    fn test_windows_signer() {
+        assert_eq!(
+            WindowsSigner::from_config(None, "windows").unwrap(),
+            WindowsSigner::Signtool
+        );
+        assert_eq!(
+            WindowsSigner::from_config(None, "linux").unwrap(),
+            WindowsSigner::Osslsigncode
+        );
+        assert!(WindowsSigner::from_config(Some("jsign"), "linux").is_err());
+    }
+
+    #[test]
+    fn test_notary_credentials() {
+        let config = MacosSigningConfig {
+            notary_profile: Some("release".to_string()),
+            ..MacosSigningConfig::default()
+        };
+        assert_eq!(
+            notary_credentials(&config).unwrap(),
+            ["--keychain-profile", "release"]
+        );
+        let config = MacosSigningConfig {
+            apple_id_env: Some("XCARGO_TEST_UNSET_APPLE_ID".to_string()),
+            ..MacosSigningConfig::default()
+        };
+        assert_eq!(notary_credentials(&config), None);
+    }
+}
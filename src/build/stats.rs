@@ -0,0 +1,340 @@
+//! Statistics of past builds
+//!
+//! Every target xcargo builds, checks, tests or benchmarks is appended to
+//! `.xcargo/builds.jsonl`, one JSON record per line, with its duration,
+//! strategy, outcome and artifact size. `xcargo stats` summarizes the records
+//! per target: how long builds take, how the time is trending, and how often
+//! they fail.
+
+use crate::error::{Error, Result};
+use crate::output::progress::format_duration;
+use crate::output::table::align_columns;
+use crate::toolchain::format_size;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::summary::{TargetResult, TargetStatus};
+
+/// Build records, relative to the project root
+pub const STATS_FILE: &str = ".xcargo/builds.jsonl";
+
+/// Builds the recent average time is taken over
+const RECENT_BUILDS: usize = 5;
+
+/// One recorded build of a target
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildRecord {
+    /// When the build finished, in seconds since the Unix epoch
+    pub finished_at: u64,
+
+    /// Target triple
+    pub target: String,
+
+    /// Cargo operation, e.g. "build" or "test"
+    pub operation: String,
+
+    /// "debug" or "release"
+    pub profile: String,
+
+    /// Wall time, in milliseconds
+    pub duration_ms: u64,
+
+    /// Strategy the target was built with, if one was chosen
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy: Option<String>,
+
+    /// Whether the operation succeeded
+    pub success: bool,
+
+    /// Combined size of the artifacts, for successful builds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size_bytes: Option<u64>,
+}
+
+impl BuildRecord {
+    /// Record of a finished target
+    #[must_use]
+    pub fn new(result: &TargetResult, operation: &str, release: bool) -> Self {
+        Self {
+            finished_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            target: result.target.clone(),
+            operation: operation.to_string(),
+            profile: if release { "release" } else { "debug" }.to_string(),
+            duration_ms: u64::try_from(result.duration.as_millis()).unwrap_or(u64::MAX),
+            strategy: result.strategy.map(|strategy| strategy.to_string()),
+            success: result.status == TargetStatus::Success,
+            size_bytes: result.artifact_bytes,
+        }
+    }
+
+    /// Append the record to `path`, creating the file and its directory if
+    /// needed
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written
+    pub fn append(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let line = serde_json::to_string(self)
+            .map_err(|e| Error::Build(format!("Failed to serialize build record: {e}")))?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        writeln!(file, "{line}")?;
+        Ok(())
+    }
+}
+
+/// Records in `path`, oldest first; lines that cannot be read are skipped
+#[must_use]
+pub fn load_records(path: &Path) -> Vec<BuildRecord> {
+    std::fs::read_to_string(path)
+        .map(|contents| {
+            contents
+                .lines()
+                .filter_map(|line| serde_json::from_str(line).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Parse how far back `xcargo stats --since` looks: a number of days such as
+/// "7d", or a duration such as "12h"
+///
+/// # Errors
+/// Returns an error if the value is neither
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use xcargo::build::parse_since;
+///
+/// assert_eq!(parse_since("7d").unwrap(), Duration::from_secs(7 * 86_400));
+/// assert_eq!(parse_since("12h").unwrap(), Duration::from_secs(12 * 3600));
+/// ```
+pub fn parse_since(value: &str) -> Result<Duration> {
+    match value.strip_suffix('d').map(str::parse::<u64>) {
+        Some(Ok(days)) if days > 0 => Ok(Duration::from_secs(days * 86_400)),
+        _ => super::runner::parse_duration(value),
+    }
+}
+
+/// Summary of a target's recorded builds for one operation and profile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TargetStats {
+    /// Target triple
+    pub target: String,
+
+    /// Cargo operation
+    pub operation: String,
+
+    /// "debug" or "release"
+    pub profile: String,
+
+    /// Number of builds
+    pub builds: usize,
+
+    /// Number of failed builds
+    pub failures: usize,
+
+    /// Average time of the successful builds
+    #[serde(rename = "average_ms", serialize_with = "serialize_millis")]
+    pub average: Option<Duration>,
+
+    /// Average time of the last few successful builds
+    #[serde(rename = "recent_ms", serialize_with = "serialize_millis")]
+    pub recent: Option<Duration>,
+
+    /// Artifact size of the latest successful build that measured one
+    pub size_bytes: Option<u64>,
+
+    /// Strategy of the latest build
+    pub strategy: Option<String>,
+}
+
+impl TargetStats {
+    /// Share of the builds that failed, in percent
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn failure_rate(&self) -> f64 {
+        if self.builds == 0 {
+            return 0.0;
+        }
+        self.failures as f64 / self.builds as f64 * 100.0
+    }
+}
+
+/// Summarize `records` per target, operation and profile, sorted by target
+#[must_use]
+pub fn summarize(records: &[BuildRecord]) -> Vec<TargetStats> {
+    let mut groups: BTreeMap<(&str, &str, &str), Vec<&BuildRecord>> = BTreeMap::new();
+    for record in records {
+        groups
+            .entry((&record.target, &record.operation, &record.profile))
+            .or_default()
+            .push(record);
+    }
+
+    groups
+        .into_iter()
+        .map(|((target, operation, profile), records)| {
+            let successes: Vec<Duration> = records
+                .iter()
+                .filter(|record| record.success)
+                .map(|record| Duration::from_millis(record.duration_ms))
+                .collect();
+            let recent = &successes[successes.len().saturating_sub(RECENT_BUILDS)..];
+
+            TargetStats {
+                target: target.to_string(),
+                operation: operation.to_string(),
+                profile: profile.to_string(),
+                builds: records.len(),
+                failures: records.iter().filter(|record| !record.success).count(),
+                average: average(&successes),
+                recent: average(recent),
+                size_bytes: records.iter().rev().find_map(|record| record.size_bytes),
+                strategy: records.last().and_then(|record| record.strategy.clone()),
+            }
+        })
+        .collect()
+}
+
+// serde hands `serialize_with` functions a reference to the field
+#[allow(clippy::ref_option)]
+fn serialize_millis<S: serde::Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    duration
+        .map(|d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+        .serialize(serializer)
+}
+
+fn average(durations: &[Duration]) -> Option<Duration> {
+    let count = u32::try_from(durations.len()).ok().filter(|&n| n > 0)?;
+    Some(durations.iter().sum::<Duration>() / count)
+}
+
+/// Change of the recent average relative to the overall one, e.g. "-12%"
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn format_trend(stats: &TargetStats) -> String {
+    match (stats.recent, stats.average) {
+        (Some(recent), Some(average)) if !average.is_zero() => {
+            let change = (recent.as_secs_f64() / average.as_secs_f64() - 1.0) * 100.0;
+            if change.abs() < 1.0 {
+                "steady".to_string()
+            } else {
+                format!("{change:+.0}%")
+            }
+        }
+        _ => "-".to_string(),
+    }
+}
+
+/// Render `stats` as a table
+#[must_use]
+pub fn render_stats(stats: &[TargetStats]) -> String {
+    let mut rows = vec![vec![
+        "target".to_string(),
+        "operation".to_string(),
+        "profile".to_string(),
+        "builds".to_string(),
+        "failed".to_string(),
+        "avg time".to_string(),
+        "recent".to_string(),
+        "trend".to_string(),
+        "size".to_string(),
+        "strategy".to_string(),
+    ]];
+
+    for target in stats {
+        let duration = |d: Option<Duration>| d.map_or_else(|| "-".to_string(), format_duration);
+        rows.push(vec![
+            target.target.clone(),
+            target.operation.clone(),
+            target.profile.clone(),
+            target.builds.to_string(),
+            format!("{:.0}%", target.failure_rate()),
+            duration(target.average),
+            duration(target.recent),
+            format_trend(target),
+            target
+                .size_bytes
+                .map_or_else(|| "-".to_string(), format_size),
+            target.strategy.clone().unwrap_or_else(|| "-".to_string()),
+        ]);
+    }
+
+    align_columns(&rows).join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(target: &str, seconds: u64, success: bool) -> BuildRecord {
+        BuildRecord {
+            finished_at: 1_700_000_000,
+            target: target.to_string(),
+            operation: "build".to_string(),
+            profile: "debug".to_string(),
+            duration_ms: seconds * 1000,
+            strategy: Some("zig".to_string()),
+            success,
+            size_bytes: success.then_some(2048),
+        }
+    }
+
+    #[test]
+    fn test_append_and_load() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(STATS_FILE);
+        assert!(load_records(&path).is_empty());
+
+        record("a", 1, true).append(&path).unwrap();
+        record("b", 2, false).append(&path).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+
+        let records = load_records(&path);
+        assert_eq!(records, [record("a", 1, true), record("b", 2, false)]);
+    }
+
+    #[test]
+    fn test_summarize() {
+        let mut records: Vec<BuildRecord> = (1..=10).map(|s| record("a", s, true)).collect();
+        records.push(record("a", 60, false));
+        records.push(record("b", 4, true));
+
+        let stats = summarize(&records);
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].builds, 11);
+        assert_eq!(stats[0].failures, 1);
+        assert_eq!(stats[0].average, Some(Duration::from_millis(5500)));
+        assert_eq!(stats[0].recent, Some(Duration::from_secs(8)));
+        assert_eq!(stats[0].size_bytes, Some(2048));
+        assert_eq!(format_trend(&stats[0]), "+45%");
+        assert_eq!(format_trend(&stats[1]), "steady");
+
+        let table = render_stats(&stats);
+        let lines: Vec<&str> = table.lines().collect();
+        assert!(lines[0].starts_with("target"));
+        assert!(lines[1].contains("9%"));
+        assert!(lines[2].starts_with('b'));
+    }
+}
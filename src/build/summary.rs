@@ -0,0 +1,478 @@
+//! Per-target summary of multi-target builds
+//!
+//! After building several targets, xcargo prints a table with each target's
+//! status, wall time, artifact size, and compiler warnings and errors. Sizes
+//! of successful builds are kept in `.xcargo/history.json` so the next build
+//! can show how they changed.
+
+use crate::config::BuildConfig;
+use crate::error::{Error, Result};
+use crate::output::progress::format_duration;
+use crate::output::table::align_columns;
+use crate::target::Target;
+use crate::toolchain::format_size;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use super::attest::is_attestation;
+use super::diagnostics::{format_counts, Diagnostics};
+use super::options::{BuildOptions, BuildStrategy, CargoOperation};
+use super::report::BuildReport;
+
+/// Build history file, relative to the project root
+pub const HISTORY_FILE: &str = ".xcargo/history.json";
+
+/// How a target's build ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetStatus {
+    /// The cargo operation succeeded
+    Success,
+    /// The cargo operation failed
+    Failed,
+    /// Nothing needed building (no affected workspace members)
+    Skipped,
+    /// Not built because an earlier target failed with `build.fail_fast`
+    NotBuilt,
+}
+
+impl TargetStatus {
+    /// Status label for display
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Success => "ok",
+            Self::Failed => "failed",
+            Self::Skipped => "skipped",
+            Self::NotBuilt => "not built",
+        }
+    }
+}
+
+/// Result of building one target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetResult {
+    /// Target triple
+    pub target: String,
+
+    /// How the build ended
+    pub status: TargetStatus,
+
+    /// Wall time of the build
+    pub duration: Duration,
+
+    /// Combined size of the build artifacts, for successful builds
+    pub artifact_bytes: Option<u64>,
+
+    /// Compile errors rustc reported
+    pub errors: usize,
+
+    /// Warnings rustc reported
+    pub warnings: usize,
+
+    /// How the target was cross-compiled, if a strategy was chosen
+    pub strategy: Option<BuildStrategy>,
+
+    /// Whether `strategy` was a fallback after other strategies failed
+    pub fallback: bool,
+}
+
+impl TargetResult {
+    /// Record the outcome of building `target`, measuring its artifacts if
+    /// it was built successfully
+    #[must_use]
+    pub fn measure(
+        target: &str,
+        succeeded: bool,
+        duration: Duration,
+        options: &BuildOptions,
+    ) -> Self {
+        // cargo writes x86_64-unknown-linux-gnu.2.17 builds under the plain triple
+        let triple = Target::from_triple(target).map_or_else(|_| target.to_string(), |t| t.triple);
+        let artifact_bytes = (succeeded && options.operation == CargoOperation::Build)
+            .then(|| artifact_size(&target_dir_for(&triple), &triple, options.release))
+            .flatten();
+
+        Self {
+            target: target.to_string(),
+            status: if succeeded {
+                TargetStatus::Success
+            } else {
+                TargetStatus::Failed
+            },
+            duration,
+            artifact_bytes,
+            errors: 0,
+            warnings: 0,
+            strategy: None,
+            fallback: false,
+        }
+    }
+
+    /// Add the strategy the target was built with
+    #[must_use]
+    pub fn with_strategy(self, report: &BuildReport) -> Self {
+        Self {
+            strategy: report.strategy,
+            fallback: !report.fallback_from.is_empty(),
+            ..self
+        }
+    }
+
+    /// Add the counts of the warnings and errors reported for the target
+    #[must_use]
+    pub fn with_diagnostics(self, diagnostics: &Diagnostics) -> Self {
+        Self {
+            errors: diagnostics.errors.len(),
+            warnings: diagnostics.warnings.len(),
+            ..self
+        }
+    }
+
+    /// A target that was not built
+    #[must_use]
+    pub fn skipped(target: &str) -> Self {
+        Self {
+            target: target.to_string(),
+            status: TargetStatus::Skipped,
+            duration: Duration::ZERO,
+            artifact_bytes: None,
+            errors: 0,
+            warnings: 0,
+            strategy: None,
+            fallback: false,
+        }
+    }
+
+    /// A target left unbuilt after another target failed
+    #[must_use]
+    pub fn not_built(target: &str) -> Self {
+        Self {
+            status: TargetStatus::NotBuilt,
+            ..Self::skipped(target)
+        }
+    }
+}
+
+/// Artifact size and build time from a previous build
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Combined size of the build artifacts, in bytes
+    pub size_bytes: u64,
+
+    /// Wall time of the build, in milliseconds
+    pub duration_ms: u64,
+
+    /// When the build finished, in seconds since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub built_at: Option<u64>,
+}
+
+/// Artifact sizes of previous builds, keyed by target and profile
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BuildHistory {
+    /// Entries keyed by "<triple>/<profile>", e.g. "x86_64-pc-windows-gnu/release"
+    #[serde(default)]
+    pub targets: BTreeMap<String, HistoryEntry>,
+}
+
+impl BuildHistory {
+    /// Load the history, or an empty one if the file is missing or unreadable
+    #[must_use]
+    pub fn load(path: &Path) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the history, creating its directory if needed
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be written
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::Build(format!("Failed to serialize build history: {e}")))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// Key for a target built with a profile
+    #[must_use]
+    pub fn key(target: &str, release: bool) -> String {
+        format!("{target}/{}", if release { "release" } else { "debug" })
+    }
+
+    /// Previous entry for a target and profile
+    #[must_use]
+    pub fn get(&self, target: &str, release: bool) -> Option<&HistoryEntry> {
+        self.targets.get(&Self::key(target, release))
+    }
+
+    /// Record the measured successful builds
+    pub fn record(&mut self, results: &[TargetResult], release: bool) {
+        for result in results {
+            if let (TargetStatus::Success, Some(size_bytes)) =
+                (result.status, result.artifact_bytes)
+            {
+                self.targets.insert(
+                    Self::key(&result.target, release),
+                    HistoryEntry {
+                        size_bytes,
+                        duration_ms: u64::try_from(result.duration.as_millis()).unwrap_or(u64::MAX),
+                        built_at: SystemTime::now()
+                            .duration_since(UNIX_EPOCH)
+                            .ok()
+                            .map(|elapsed| elapsed.as_secs()),
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Cargo's target directory: `$CARGO_TARGET_DIR`, or `target`
+#[must_use]
+pub fn target_dir() -> PathBuf {
+    std::env::var_os("CARGO_TARGET_DIR").map_or_else(|| PathBuf::from("target"), PathBuf::from)
+}
+
+/// Directory under the target directory that holds one target directory per
+/// triple when `build.isolate_targets` is set
+pub const ISOLATED_DIR: &str = "isolated";
+
+/// Whether each triple builds in its own target directory
+static ISOLATE_TARGETS: AtomicBool = AtomicBool::new(false);
+
+/// Apply `build.target_dir` and `build.isolate_targets` from xcargo.toml
+///
+/// `build.target_dir` becomes `CARGO_TARGET_DIR`, for cargo and for
+/// [`target_dir`], unless the variable is already set.
+pub fn use_target_dir_config(build: &BuildConfig) {
+    if let Some(dir) = &build.target_dir {
+        if std::env::var_os("CARGO_TARGET_DIR").is_none() {
+            std::env::set_var("CARGO_TARGET_DIR", dir);
+        }
+    }
+    ISOLATE_TARGETS.store(build.isolate_targets, Ordering::Relaxed);
+}
+
+/// Target directory cargo builds `triple` in
+///
+/// This is [`target_dir`], or `<target_dir>/isolated/<triple>` when
+/// `build.isolate_targets` is set, so parallel builds of different triples
+/// don't wait on each other's lock of the target directory.
+#[must_use]
+pub fn target_dir_for(triple: &str) -> PathBuf {
+    if ISOLATE_TARGETS.load(Ordering::Relaxed) {
+        isolated_target_dir(&target_dir(), triple)
+    } else {
+        target_dir()
+    }
+}
+
+/// The isolated target directory of `triple` under `target_dir`
+#[must_use]
+pub fn isolated_target_dir(target_dir: &Path, triple: &str) -> PathBuf {
+    target_dir.join(ISOLATED_DIR).join(triple)
+}
+
+/// The nearest Cargo.lock, from the current directory upward
+pub(super) fn find_cargo_lock() -> Option<PathBuf> {
+    crate::cache::find_cargo_lock(&std::env::current_dir().ok()?)
+}
+
+/// Combined size of the artifacts cargo placed in the profile directory
+/// for `target` (binaries and libraries, not dependency info files)
+#[must_use]
+pub fn artifact_size(target_dir: &Path, target: &str, release: bool) -> Option<u64> {
+    let profile = if release { "release" } else { "debug" };
+    let entries = std::fs::read_dir(target_dir.join(target).join(profile)).ok()?;
+
+    let size = entries
+        .filter_map(std::result::Result::ok)
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            !name.starts_with('.') && !name.ends_with(".d") && !is_attestation(&name)
+        })
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(std::fs::Metadata::is_file)
+        .map(|metadata| metadata.len())
+        .sum();
+    Some(size)
+}
+
+/// Size change relative to a previous build, e.g. "+12.0 KiB (+3.1%)"
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn format_size_delta(current: u64, previous: u64) -> String {
+    if current == previous {
+        return "unchanged".to_string();
+    }
+    let sign = if current > previous { '+' } else { '-' };
+    let change = current.abs_diff(previous);
+    if previous == 0 {
+        return format!("{sign}{}", format_size(change));
+    }
+    let percent = change as f64 / previous as f64 * 100.0;
+    format!("{sign}{} ({sign}{percent:.1}%)", format_size(change))
+}
+
+/// Render the summary table for `results`, comparing sizes with `history`
+#[must_use]
+pub fn render_summary(results: &[TargetResult], history: &BuildHistory, release: bool) -> String {
+    let mut rows = vec![vec![
+        "target".to_string(),
+        "status".to_string(),
+        "time".to_string(),
+        "size".to_string(),
+        "change".to_string(),
+        "strategy".to_string(),
+        "diagnostics".to_string(),
+    ]];
+
+    for result in results {
+        let time = if matches!(
+            result.status,
+            TargetStatus::Skipped | TargetStatus::NotBuilt
+        ) {
+            "-".to_string()
+        } else {
+            format_duration(result.duration)
+        };
+        let size = result
+            .artifact_bytes
+            .map_or_else(|| "-".to_string(), format_size);
+        let change = match (result.artifact_bytes, history.get(&result.target, release)) {
+            (Some(current), Some(previous)) => format_size_delta(current, previous.size_bytes),
+            (Some(_), None) => "new".to_string(),
+            _ => "-".to_string(),
+        };
+        let strategy = match (result.strategy, result.fallback) {
+            (Some(strategy), true) => format!("{strategy} (fallback)"),
+            (Some(strategy), false) => strategy.to_string(),
+            (None, _) => "-".to_string(),
+        };
+        rows.push(vec![
+            result.target.clone(),
+            result.status.as_str().to_string(),
+            time,
+            size,
+            change,
+            strategy,
+            format_counts(result.errors, result.warnings),
+        ]);
+    }
+
+    align_columns(&rows).join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn success(target: &str, bytes: u64) -> TargetResult {
+        TargetResult {
+            target: target.to_string(),
+            status: TargetStatus::Success,
+            duration: Duration::from_millis(1500),
+            artifact_bytes: Some(bytes),
+            errors: 0,
+            warnings: 0,
+            strategy: Some(BuildStrategy::Zig),
+            fallback: false,
+        }
+    }
+
+    #[test]
+    fn test_artifact_size_skips_dep_info() {
+        let temp = TempDir::new().unwrap();
+        let profile = temp.path().join("aarch64-unknown-linux-gnu/release");
+        std::fs::create_dir_all(profile.join("deps")).unwrap();
+        std::fs::write(profile.join("app"), [0u8; 1000]).unwrap();
+        std::fs::write(profile.join("app.d"), [0u8; 50]).unwrap();
+        std::fs::write(profile.join(".cargo-lock"), [0u8; 1]).unwrap();
+        std::fs::write(profile.join("deps/libdep.rlib"), [0u8; 500]).unwrap();
+
+        assert_eq!(
+            artifact_size(temp.path(), "aarch64-unknown-linux-gnu", true),
+            Some(1000)
+        );
+        assert_eq!(
+            artifact_size(temp.path(), "aarch64-unknown-linux-gnu", false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_size_delta() {
+        assert_eq!(format_size_delta(2048, 2048), "unchanged");
+        assert_eq!(format_size_delta(2048 + 1024, 2048), "+1.0 KiB (+50.0%)");
+        assert_eq!(format_size_delta(1024, 2048), "-1.0 KiB (-50.0%)");
+    }
+
+    #[test]
+    fn test_history_roundtrip_and_record() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join(HISTORY_FILE);
+
+        assert_eq!(BuildHistory::load(&path), BuildHistory::default());
+
+        let mut history = BuildHistory::default();
+        history.record(
+            &[
+                success("x86_64-pc-windows-gnu", 4096),
+                TargetResult::skipped("wasm32-unknown-unknown"),
+            ],
+            true,
+        );
+        history.save(&path).unwrap();
+
+        let loaded = BuildHistory::load(&path);
+        assert_eq!(loaded.targets.len(), 1);
+        assert_eq!(
+            loaded
+                .get("x86_64-pc-windows-gnu", true)
+                .unwrap()
+                .size_bytes,
+            4096
+        );
+        assert!(loaded.get("x86_64-pc-windows-gnu", false).is_none());
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let mut history = BuildHistory::default();
+        history.record(&[success("a", 1024)], false);
+
+        let failed = TargetResult {
+            target: "b".to_string(),
+            status: TargetStatus::Failed,
+            duration: Duration::from_secs(2),
+            artifact_bytes: None,
+            errors: 2,
+            warnings: 1,
+            strategy: Some(BuildStrategy::Container),
+            fallback: true,
+        };
+        let table = render_summary(&[success("a", 2048), failed], &history, false);
+        let lines: Vec<&str> = table.lines().collect();
+
+        assert!(lines[0].starts_with("target"));
+        assert!(lines[1].contains("1.50s"));
+        assert!(lines[1].contains("2.0 KiB"));
+        assert!(lines[1].contains("+1.0 KiB (+100.0%)"));
+        assert!(lines[1].contains("zig"));
+        assert!(lines[2].contains("failed"));
+        assert!(lines[2].contains("container (fallback)"));
+        assert!(lines[2].ends_with("2 errors, 1 warning"));
+    }
+}
@@ -0,0 +1,155 @@
+//! Cargo's output for one target of a parallel build
+//!
+//! When several targets build at once, the lines their cargo processes print
+//! would mix with nothing telling which target a warning or error belongs
+//! to. With interleaved output, every line is printed as it arrives behind a
+//! colored `[target]` tag; with grouped output, a target's lines are held
+//! until its build finishes and printed together under its name.
+
+use crate::config::ParallelOutput;
+use crate::output::colors;
+use crate::output::progress::BuildProgress;
+use crate::output::theme;
+use std::io::Write;
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Colors the tags of targets are drawn with, picked by the target's name
+const TAG_COLORS: [&str; 5] = [
+    colors::CYAN,
+    colors::MAGENTA,
+    colors::BLUE,
+    colors::YELLOW,
+    colors::GREEN,
+];
+
+/// Stream cargo printed a line to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Where cargo's output for one target goes, shared with the thread reading
+/// its stderr
+#[derive(Debug, Clone)]
+pub(super) struct TargetOutput {
+    /// The target's `[target]` tag, colored if colors are enabled
+    tag: String,
+
+    /// How the output is shown
+    mode: ParallelOutput,
+
+    /// Lines held back until the build finishes, in grouped mode
+    held: Arc<Mutex<Vec<(Stream, String)>>>,
+}
+
+impl TargetOutput {
+    pub(super) fn new(target: &str, mode: ParallelOutput) -> Self {
+        Self {
+            tag: theme::paint(tag_color(target), format!("[{target}]")),
+            mode,
+            held: Arc::default(),
+        }
+    }
+
+    /// Show `text` that cargo printed to stdout
+    pub(super) fn stdout(&self, text: &str, progress: Option<&BuildProgress>) {
+        self.emit(Stream::Stdout, text, progress);
+    }
+
+    /// Show `text` that cargo printed to stderr
+    pub(super) fn stderr(&self, text: &str, progress: Option<&BuildProgress>) {
+        self.emit(Stream::Stderr, text, progress);
+    }
+
+    fn emit(&self, stream: Stream, text: &str, progress: Option<&BuildProgress>) {
+        let lines = text.lines().map(ToString::to_string);
+        match self.mode {
+            ParallelOutput::Interleaved => {
+                let lines: Vec<_> = lines.map(|line| (stream, line)).collect();
+                let print = || print_lines(&lines, Some(&self.tag));
+                match progress {
+                    Some(progress) => progress.suspend(print),
+                    None => print(),
+                }
+            }
+            ParallelOutput::Grouped => self
+                .held
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .extend(lines.map(|line| (stream, line))),
+        }
+    }
+
+    /// Print the lines held back for the target under its tag, once cargo
+    /// has exited
+    pub(super) fn flush(&self, progress: &BuildProgress) {
+        let mut held =
+            std::mem::take(&mut *self.held.lock().unwrap_or_else(PoisonError::into_inner));
+        if held.is_empty() {
+            return;
+        }
+        held.insert(0, (Stream::Stderr, theme::paint(colors::BOLD, &self.tag)));
+        progress.suspend(|| print_lines(&held, None));
+    }
+}
+
+/// Print `lines` to the streams cargo printed them to, behind `tag`, without
+/// another target's lines coming in between
+fn print_lines(lines: &[(Stream, String)], tag: Option<&str>) {
+    let mut stdout = std::io::stdout().lock();
+    let mut stderr = std::io::stderr().lock();
+    for (stream, line) in lines {
+        let out: &mut dyn Write = match stream {
+            Stream::Stdout => &mut stdout,
+            Stream::Stderr => &mut stderr,
+        };
+        let _ = match tag {
+            Some(tag) if line.is_empty() => writeln!(out, "{tag}"),
+            Some(tag) => writeln!(out, "{tag} {line}"),
+            None => writeln!(out, "{line}"),
+        };
+    }
+    let _ = stdout.flush();
+}
+
+/// Color of `target`'s tag; the same target always gets the same color
+fn tag_color(target: &str) -> &'static str {
+    let hash = target.bytes().fold(0usize, |hash, byte| {
+        hash.wrapping_mul(31).wrapping_add(byte.into())
+    });
+    TAG_COLORS[hash % TAG_COLORS.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grouped_output_is_held_until_flushed() {
+        let output = TargetOutput::new("aarch64-unknown-linux-gnu", ParallelOutput::Grouped);
+        output.stderr("warning: unused variable\n  --> src/lib.rs:1:5\n", None);
+        output.stdout("test tests::it_works ... ok", None);
+        assert_eq!(
+            *output.held.lock().unwrap(),
+            [
+                (Stream::Stderr, "warning: unused variable".to_string()),
+                (Stream::Stderr, "  --> src/lib.rs:1:5".to_string()),
+                (Stream::Stdout, "test tests::it_works ... ok".to_string()),
+            ]
+        );
+
+        output.flush(&BuildProgress::new(
+            "aarch64-unknown-linux-gnu",
+            "Compiling",
+        ));
+        assert!(output.held.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_tag_color_is_stable() {
+        let target = "x86_64-pc-windows-gnu";
+        assert_eq!(tag_color(target), tag_color(target));
+        assert!(TAG_COLORS.contains(&tag_color("wasm32-unknown-unknown")));
+    }
+}
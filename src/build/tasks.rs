@@ -0,0 +1,247 @@
+//! Task runner definitions generated from xcargo.toml
+//!
+//! Teams that drive their builds through `just` or `cargo-make` get the same
+//! entry points everywhere by generating the task file from the configured
+//! targets instead of maintaining it by hand.
+
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Task runner to generate definitions for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskFormat {
+    /// A `justfile` for <https://github.com/casey/just>
+    Just,
+    /// A `Makefile.toml` for `cargo-make`
+    CargoMake,
+}
+
+impl TaskFormat {
+    /// File the task runner reads by default
+    #[must_use]
+    pub fn default_file(self) -> &'static str {
+        match self {
+            Self::Just => "justfile",
+            Self::CargoMake => "Makefile.toml",
+        }
+    }
+
+    /// Name used on the command line
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Just => "just",
+            Self::CargoMake => "cargo-make",
+        }
+    }
+}
+
+impl FromStr for TaskFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "just" => Ok(Self::Just),
+            "cargo-make" => Ok(Self::CargoMake),
+            other => Err(format!(
+                "Unknown task format '{other}' (expected just or cargo-make)"
+            )),
+        }
+    }
+}
+
+/// One task wrapping an xcargo invocation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Task {
+    /// Task name, e.g. "build-all"
+    pub name: String,
+
+    /// One-line description
+    pub description: String,
+
+    /// Arguments to xcargo; empty for tasks that only run their dependencies
+    pub args: Vec<String>,
+
+    /// Tasks that run first
+    pub dependencies: Vec<String>,
+}
+
+impl Task {
+    fn new(name: impl Into<String>, description: impl Into<String>, args: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            args: args.iter().map(ToString::to_string).collect(),
+            dependencies: Vec::new(),
+        }
+    }
+}
+
+/// Tasks for a target matrix: the matrix-wide entry points, then one build
+/// task per target
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::build::task_matrix;
+///
+/// let tasks = task_matrix(&["aarch64-unknown-linux-gnu".to_string()]);
+/// let names: Vec<_> = tasks.iter().map(|t| t.name.as_str()).collect();
+/// assert_eq!(
+///     names,
+///     ["build-all", "release", "check-all", "test-all", "verify", "build-aarch64-unknown-linux-gnu"]
+/// );
+/// ```
+#[must_use]
+pub fn task_matrix(targets: &[String]) -> Vec<Task> {
+    let mut tasks = vec![
+        Task::new(
+            "build-all",
+            "Build every configured target",
+            &["build", "--all"],
+        ),
+        Task::new(
+            "release",
+            "Release build of every configured target",
+            &["build", "--all", "--release"],
+        ),
+        Task::new(
+            "check-all",
+            "Check every configured target",
+            &["check", "--all"],
+        ),
+        Task::new(
+            "test-all",
+            "Test every configured target",
+            &["test", "--all"],
+        ),
+        Task {
+            dependencies: vec!["check-all".to_string(), "test-all".to_string()],
+            ..Task::new("verify", "Check and test every configured target", &[])
+        },
+    ];
+    tasks.extend(targets.iter().map(|target| {
+        Task::new(
+            format!("build-{}", task_name(target)),
+            format!("Build for {target}"),
+            &["build", "--target", target],
+        )
+    }));
+    tasks
+}
+
+/// Render tasks in a task runner's format
+#[must_use]
+pub fn render_tasks(format: TaskFormat, tasks: &[Task]) -> String {
+    let mut out = format!(
+        "# Generated by `xcargo export tasks --format {}` from xcargo.toml.\n\
+         # Regenerate it after changing the configured targets.\n",
+        format.as_str()
+    );
+    for task in tasks {
+        out.push('\n');
+        match format {
+            TaskFormat::Just => render_just(&mut out, task),
+            TaskFormat::CargoMake => render_cargo_make(&mut out, task),
+        }
+    }
+    out
+}
+
+fn render_just(out: &mut String, task: &Task) {
+    let _ = writeln!(out, "# {}", task.description);
+    let mut header = format!("{}:", task.name);
+    for dependency in &task.dependencies {
+        header.push(' ');
+        header.push_str(dependency);
+    }
+    let _ = writeln!(out, "{header}");
+    if !task.args.is_empty() {
+        let _ = writeln!(out, "    xcargo {}", task.args.join(" "));
+    }
+}
+
+fn render_cargo_make(out: &mut String, task: &Task) {
+    let _ = writeln!(out, "[tasks.{}]", task.name);
+    let _ = writeln!(out, "description = {}", toml_string(&task.description));
+    if !task.args.is_empty() {
+        let _ = writeln!(out, "command = \"xcargo\"");
+        let _ = writeln!(out, "args = {}", toml_array(&task.args));
+    }
+    if !task.dependencies.is_empty() {
+        let _ = writeln!(out, "dependencies = {}", toml_array(&task.dependencies));
+    }
+}
+
+/// Task name for a target, keeping only characters both runners accept
+fn task_name(target: &str) -> String {
+    target
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn toml_string(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
+
+fn toml_array(items: &[String]) -> String {
+    let items: Vec<String> = items.iter().map(|item| toml_string(item)).collect();
+    format!("[{}]", items.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix() -> Vec<Task> {
+        task_matrix(&[
+            "aarch64-unknown-linux-gnu".to_string(),
+            "x86_64-pc-windows-gnu".to_string(),
+        ])
+    }
+
+    #[test]
+    fn test_render_just() {
+        let justfile = render_tasks(TaskFormat::Just, &matrix());
+        assert!(justfile.starts_with("# Generated by `xcargo export tasks --format just`"));
+        assert!(justfile.contains("build-all:\n    xcargo build --all\n"));
+        assert!(justfile.contains("verify: check-all test-all\n"));
+        assert!(justfile.contains(
+            "# Build for x86_64-pc-windows-gnu\n\
+             build-x86_64-pc-windows-gnu:\n    xcargo build --target x86_64-pc-windows-gnu\n"
+        ));
+    }
+
+    #[test]
+    fn test_render_cargo_make() {
+        let makefile = render_tasks(TaskFormat::CargoMake, &matrix());
+        let parsed: toml::Value = toml::from_str(&makefile).unwrap();
+        let tasks = parsed["tasks"].as_table().unwrap();
+        assert_eq!(tasks.len(), 7);
+        assert_eq!(tasks["release"]["command"].as_str(), Some("xcargo"));
+        assert_eq!(
+            tasks["build-aarch64-unknown-linux-gnu"]["args"],
+            toml::Value::try_from(["build", "--target", "aarch64-unknown-linux-gnu"]).unwrap()
+        );
+        assert!(tasks["verify"].get("command").is_none());
+        assert_eq!(
+            tasks["verify"]["dependencies"],
+            toml::Value::try_from(["check-all", "test-all"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_task_format_and_names() {
+        assert_eq!("just".parse(), Ok(TaskFormat::Just));
+        assert_eq!("Cargo-Make".parse(), Ok(TaskFormat::CargoMake));
+        assert!("make-it-so".parse::<TaskFormat>().is_err());
+        assert_eq!(task_name("targets/my.custom"), "targets-my-custom");
+    }
+}
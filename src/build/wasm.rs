@@ -0,0 +1,318 @@
+//! WebAssembly builds
+//!
+//! WebAssembly targets build on every host with rustup's standard library and
+//! rust-lld, so containers and Zig never apply. After a build,
+//! `wasm32-unknown-unknown` modules go through wasm-bindgen and wasm-opt the
+//! way wasm-pack would, and tests run under wasmtime (WASI) or
+//! wasm-bindgen-test-runner when no runner is configured.
+
+use crate::config::WasmConfig;
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use crate::output::terminal::symbol;
+use crate::target::Target;
+use crate::toolchain::format_size;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::options::BuildOptions;
+use super::postprocess::{file_size, run_tool};
+use super::summary::{find_cargo_lock, target_dir_for};
+
+/// Reject options that can't apply to a WebAssembly target
+///
+/// # Errors
+/// Returns an error naming the option for `--container` and `--zig`
+pub(super) fn check_wasm_options(target: &Target, options: &BuildOptions) -> Result<()> {
+    let option = if options.use_container {
+        "--container"
+    } else if options.use_zig == Some(true) {
+        "--zig"
+    } else {
+        return Ok(());
+    };
+    Err(Error::Config(format!(
+        "{option} does not apply to {}: WebAssembly builds natively with rustup's standard library and rust-lld",
+        target.triple
+    )))
+}
+
+/// Runner for test binaries when none is configured: wasmtime for WASI,
+/// wasm-bindgen-test-runner for `wasm32-unknown-unknown`, if installed
+pub(super) fn default_runner(target: &Target) -> Option<String> {
+    let runner = if target.os == "wasi" {
+        "wasmtime"
+    } else {
+        "wasm-bindgen-test-runner"
+    };
+    which::which(runner).ok().map(|_| runner.to_string())
+}
+
+/// Error for WebAssembly tests with no runner to execute them
+pub(super) fn missing_runner(target: &Target) -> Error {
+    if target.os == "wasi" {
+        helpers::tip("Install wasmtime: curl https://wasmtime.dev/install.sh -sSf | bash");
+    } else {
+        helpers::tip("Install the test runner: cargo install wasm-bindgen-cli");
+    }
+    helpers::tip(format!(
+        "Or configure one in xcargo.toml: [targets.\"{}\"] runner = \"...\"",
+        target.triple
+    ));
+    Error::Build(format!(
+        "No WebAssembly runtime to run {} tests",
+        target.triple
+    ))
+}
+
+/// Run wasm-bindgen and wasm-opt on the modules of a WebAssembly build
+///
+/// # Errors
+/// Returns an error if an explicitly enabled step's tool is missing or fails;
+/// automatic steps only warn
+pub(super) fn wasm_post_build(
+    target: &Target,
+    config: Option<&WasmConfig>,
+    release: bool,
+) -> Result<()> {
+    let config = config.cloned().unwrap_or_default();
+    let modules = wasm_modules(&target_dir_for(&target.triple), &target.triple, release);
+    if modules.is_empty() {
+        return Ok(());
+    }
+
+    let mut optimize = modules.clone();
+    if let Some(bindgen) = find_bindgen(target, &config)? {
+        let flavor = config.bindgen_target.as_deref().unwrap_or("bundler");
+        let out_dir = PathBuf::from(config.out_dir.as_deref().unwrap_or("pkg"));
+        optimize.clear();
+        for module in &modules {
+            let mut cmd = Command::new(&bindgen);
+            cmd.args(["--target", flavor])
+                .arg("--out-dir")
+                .arg(&out_dir)
+                .arg(module);
+            if run_step(cmd, "wasm-bindgen", module, config.bindgen == Some(true))? {
+                helpers::success(format!(
+                    "Generated {flavor} bindings for {} in {}",
+                    file_name(module),
+                    out_dir.display()
+                ));
+                optimize.push(out_dir.join(format!("{}_bg.wasm", file_stem(module))));
+            }
+        }
+    }
+
+    let Some(wasm_opt) = find_wasm_opt(&config, release)? else {
+        return Ok(());
+    };
+    let default_args = vec!["-O".to_string()];
+    let args = config.opt_args.as_ref().unwrap_or(&default_args);
+    for module in &optimize {
+        let before = file_size(module);
+        let mut cmd = Command::new(&wasm_opt);
+        cmd.args(args).arg(module).arg("-o").arg(module);
+        if run_step(cmd, "wasm-opt", module, config.opt == Some(true))? {
+            helpers::success(format!(
+                "Optimized {} ({} {} {})",
+                file_name(module),
+                format_size(before),
+                symbol("→", "->"),
+                format_size(file_size(module))
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// wasm-bindgen to run, if the step applies
+fn find_bindgen(target: &Target, config: &WasmConfig) -> Result<Option<PathBuf>> {
+    let explicit = match config.bindgen {
+        Some(false) => return Ok(None),
+        Some(true) => true,
+        // Bindings are for the browser, and only crates using wasm-bindgen have any
+        None if target.os != "unknown" => return Ok(None),
+        None => false,
+    };
+    let locked = find_cargo_lock()
+        .and_then(|lock| std::fs::read_to_string(lock).ok())
+        .and_then(|contents| locked_bindgen_version(&contents));
+    if !explicit && locked.is_none() {
+        return Ok(None);
+    }
+
+    let Ok(bindgen) = which::which("wasm-bindgen") else {
+        let install = match &locked {
+            Some(version) => format!("cargo install wasm-bindgen-cli --version {version}"),
+            None => "cargo install wasm-bindgen-cli".to_string(),
+        };
+        if explicit {
+            helpers::tip(format!("Install it with: {install}"));
+            return Err(Error::Build(
+                "bindgen = true needs wasm-bindgen on PATH".to_string(),
+            ));
+        }
+        helpers::tip(format!(
+            "This crate uses wasm-bindgen; generate JS bindings after each build by installing the CLI: {install}"
+        ));
+        return Ok(None);
+    };
+
+    // The CLI must match the crate's wasm-bindgen exactly
+    if let (Some(locked), Some(cli)) = (&locked, bindgen_cli_version(&bindgen)) {
+        if *locked != cli {
+            helpers::warning(format!(
+                "wasm-bindgen CLI {cli} does not match the crate's wasm-bindgen {locked}"
+            ));
+            helpers::tip(format!(
+                "Install the matching CLI: cargo install wasm-bindgen-cli --version {locked}"
+            ));
+        }
+    }
+    Ok(Some(bindgen))
+}
+
+/// wasm-opt to run, if the step applies
+fn find_wasm_opt(config: &WasmConfig, release: bool) -> Result<Option<PathBuf>> {
+    match (config.opt, which::which("wasm-opt")) {
+        (Some(false), _) => Ok(None),
+        (Some(true), Ok(path)) => Ok(Some(path)),
+        (Some(true), Err(_)) => {
+            helpers::tip("Install binaryen (https://github.com/WebAssembly/binaryen) or: cargo install wasm-opt");
+            Err(Error::Build(
+                "opt = true needs wasm-opt on PATH".to_string(),
+            ))
+        }
+        // Optimizing takes a while, so debug builds skip it
+        (None, Ok(path)) if release => Ok(Some(path)),
+        (None, _) => Ok(None),
+    }
+}
+
+/// Run a step's command; an automatic step that fails only warns
+fn run_step(cmd: Command, what: &str, module: &Path, explicit: bool) -> Result<bool> {
+    match run_tool(cmd, what, module) {
+        Ok(()) => Ok(true),
+        Err(e) if !explicit => {
+            helpers::warning(format!("{e}; skipping {what}"));
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// The `.wasm` modules cargo placed in the profile directory for `target`
+fn wasm_modules(target_dir: &Path, target: &str, release: bool) -> Vec<PathBuf> {
+    let profile = if release { "release" } else { "debug" };
+    let Ok(entries) = std::fs::read_dir(target_dir.join(target).join(profile)) else {
+        return Vec::new();
+    };
+    let mut modules: Vec<PathBuf> = entries
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "wasm"))
+        .collect();
+    modules.sort();
+    modules
+}
+
+/// Version of the `wasm-bindgen` crate in a Cargo.lock
+fn locked_bindgen_version(lock: &str) -> Option<String> {
+    let lock: toml::Table = lock.parse().ok()?;
+    lock.get("package")?
+        .as_array()?
+        .iter()
+        .find(|package| package.get("name").and_then(toml::Value::as_str) == Some("wasm-bindgen"))?
+        .get("version")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// Version reported by `wasm-bindgen --version`, e.g. "0.2.92"
+fn bindgen_cli_version(bindgen: &Path) -> Option<String> {
+    let output = Command::new(bindgen).arg("--version").output().ok()?;
+    parse_bindgen_version(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_bindgen_version(output: &str) -> Option<String> {
+    output.split_whitespace().nth(1).map(str::to_string)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_wasm_options() {
+        let target = Target::from_triple("wasm32-unknown-unknown").unwrap();
+        assert!(check_wasm_options(&target, &BuildOptions::default()).is_ok());
+
+        let container = BuildOptions {
+            use_container: true,
+            ..BuildOptions::default()
+        };
+        let err = check_wasm_options(&target, &container).unwrap_err();
+        assert!(err.to_string().contains("--container does not apply"));
+
+        let zig = BuildOptions {
+            use_zig: Some(true),
+            ..BuildOptions::default()
+        };
+        assert!(check_wasm_options(&target, &zig).is_err());
+    }
+
+    #[test]
+    fn test_wasm_modules() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join("wasm32-unknown-unknown/release");
+        std::fs::create_dir_all(dir.join("deps")).unwrap();
+        std::fs::write(dir.join("app.wasm"), "module").unwrap();
+        std::fs::write(dir.join("app.d"), "deps").unwrap();
+        std::fs::write(dir.join("deps/dep.wasm"), "module").unwrap();
+
+        assert_eq!(
+            wasm_modules(temp.path(), "wasm32-unknown-unknown", true),
+            [dir.join("app.wasm")]
+        );
+        assert!(wasm_modules(temp.path(), "wasm32-unknown-unknown", false).is_empty());
+    }
+
+    #[test]
+    fn test_bindgen_versions() {
+        let lock = r#"
+            version = 3
+
+            [[package]]
+            name = "app"
+            version = "0.1.0"
+
+            [[package]]
+            name = "wasm-bindgen"
+            version = "0.2.92"
+        "#;
+        assert_eq!(locked_bindgen_version(lock).as_deref(), Some("0.2.92"));
+        assert_eq!(
+            locked_bindgen_version("[[package]]\nname = \"app\"\n"),
+            None
+        );
+        assert_eq!(
+            parse_bindgen_version("wasm-bindgen 0.2.92\n").as_deref(),
+            Some("0.2.92")
+        );
+    }
+}
@@ -0,0 +1,319 @@
+//! Prebuilt dependencies, archived per target
+//!
+//! Compiling dependencies takes most of the time of a fresh cross build.
+//! `xcargo cache export` packs what cargo built under
+//! `target/<triple>/<profile>/` (`deps`, `build` and `.fingerprint`) into a
+//! tarball, with a manifest of the key it is valid for: the contents of
+//! `Cargo.lock` and the rustc version. `xcargo cache import` unpacks it into
+//! a fresh checkout or CI runner when the key still matches, so cargo reuses
+//! the dependencies and only compiles the workspace itself.
+
+use crate::build::ISOLATED_DIR;
+use crate::error::{Error, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Name of the manifest inside a tarball
+pub const MANIFEST_FILE: &str = "xcargo-deps-cache.json";
+
+/// Directories of a profile that hold dependency builds
+const CACHED_DIRS: [&str; 3] = ["deps", "build", ".fingerprint"];
+
+/// What a dependency cache is valid for
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepsCacheKey {
+    /// SHA-256 of `Cargo.lock`
+    pub lock_sha256: String,
+
+    /// The toolchain's `rustc --version`, e.g. "rustc 1.78.0 (9b00956e5 2024-04-29)"
+    pub rustc: String,
+}
+
+impl DepsCacheKey {
+    /// Key for the dependencies locked in `lock_file`, built with `rustc`
+    ///
+    /// # Errors
+    /// Returns an error if `lock_file` cannot be read
+    pub fn new(lock_file: &Path, rustc: &str) -> Result<Self> {
+        Ok(Self {
+            lock_sha256: super::sha256_file(lock_file)?,
+            rustc: rustc.to_string(),
+        })
+    }
+
+    /// Short digest of the key, for naming tarballs and CI cache entries
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::cache::DepsCacheKey;
+    ///
+    /// let key = DepsCacheKey {
+    ///     lock_sha256: "ab".repeat(32),
+    ///     rustc: "rustc 1.78.0 (9b00956e5 2024-04-29)".to_string(),
+    /// };
+    /// assert_eq!(key.digest().len(), 16);
+    /// ```
+    #[must_use]
+    pub fn digest(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.lock_sha256.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(self.rustc.as_bytes());
+        format!("{:x}", hasher.finalize())[..16].to_string()
+    }
+
+    /// Why a cache made for `self` cannot be used for `current`, if it can't
+    #[must_use]
+    pub fn mismatch(&self, current: &Self) -> Option<String> {
+        if self.lock_sha256 != current.lock_sha256 {
+            Some("Cargo.lock has changed since the cache was exported".to_string())
+        } else if self.rustc != current.rustc {
+            Some(format!(
+                "the cache was built with {}, but this project builds with {}",
+                self.rustc, current.rustc
+            ))
+        } else {
+            None
+        }
+    }
+}
+
+/// Contents of a dependency cache tarball
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DepsCacheManifest {
+    /// What the cache is valid for
+    pub key: DepsCacheKey,
+
+    /// "debug" or "release"
+    pub profile: String,
+
+    /// Target triples the cache holds
+    pub targets: Vec<String>,
+
+    /// When the cache was exported, in seconds since the Unix epoch
+    pub created_at: u64,
+}
+
+/// Archive the dependency builds of `targets` in `target_dir` into `tarball`
+///
+/// # Errors
+/// Returns an error if a target has not been built with the profile, or tar
+/// fails
+pub fn export_deps(
+    target_dir: &Path,
+    targets: &[String],
+    release: bool,
+    key: DepsCacheKey,
+    tarball: &Path,
+) -> Result<DepsCacheManifest> {
+    let profile = if release { "release" } else { "debug" };
+
+    let mut paths = Vec::new();
+    for target in targets {
+        // Targets built with build.isolate_targets have a target directory of their own
+        let profile_dir = [
+            Path::new(target).join(profile),
+            Path::new(ISOLATED_DIR).join(target).join(target).join(profile),
+        ]
+        .into_iter()
+        .find(|dir| target_dir.join(dir).join("deps").is_dir())
+        .ok_or_else(|| {
+            Error::Build(format!(
+                "No {profile} build of {target} to export; build it first, e.g. xcargo build --target {target}{}",
+                if release { " --release" } else { "" }
+            ))
+        })?;
+        paths.extend(
+            CACHED_DIRS
+                .iter()
+                .map(|dir| profile_dir.join(dir))
+                .filter(|path| target_dir.join(path).is_dir()),
+        );
+    }
+
+    let manifest = DepsCacheManifest {
+        key,
+        profile: profile.to_string(),
+        targets: targets.to_vec(),
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs()),
+    };
+    let staging = Staging::new()?;
+    let json = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| Error::Build(format!("Failed to serialize cache manifest: {e}")))?;
+    fs::write(staging.path().join(MANIFEST_FILE), json)?;
+
+    if let Some(parent) = tarball.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    // The staging directory is absolute, so it comes after a relative
+    // target directory, which tar would otherwise resolve against it
+    let mut cmd = Command::new("tar");
+    cmd.arg("-czf")
+        .arg(tarball)
+        .arg("-C")
+        .arg(target_dir)
+        .args(&paths)
+        .arg("-C")
+        .arg(staging.path())
+        .arg(MANIFEST_FILE);
+    run_tar(&mut cmd, tarball)?;
+    Ok(manifest)
+}
+
+/// The manifest of a dependency cache tarball
+///
+/// # Errors
+/// Returns an error if the tarball cannot be read or has no manifest
+pub fn read_manifest(tarball: &Path) -> Result<DepsCacheManifest> {
+    let staging = Staging::new()?;
+    let mut cmd = Command::new("tar");
+    cmd.arg("-xzf")
+        .arg(tarball)
+        .arg("-C")
+        .arg(staging.path())
+        .arg(MANIFEST_FILE);
+    run_tar(&mut cmd, tarball)?;
+
+    let contents = fs::read_to_string(staging.path().join(MANIFEST_FILE))?;
+    serde_json::from_str(&contents).map_err(|e| {
+        Error::Build(format!(
+            "{} is not an xcargo dependency cache: {e}",
+            tarball.display()
+        ))
+    })
+}
+
+/// Unpack the dependency builds in `tarball` into `target_dir`
+///
+/// # Errors
+/// Returns an error if tar fails
+pub fn import_deps(tarball: &Path, target_dir: &Path) -> Result<()> {
+    fs::create_dir_all(target_dir)?;
+    let mut cmd = Command::new("tar");
+    cmd.arg("-xzf")
+        .arg(tarball)
+        .arg("-C")
+        .arg(target_dir)
+        .arg(format!("--exclude={MANIFEST_FILE}"));
+    run_tar(&mut cmd, tarball)
+}
+
+/// The nearest Cargo.lock, from `dir` upward
+#[must_use]
+pub fn find_cargo_lock(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|lock| lock.is_file())
+}
+
+fn run_tar(cmd: &mut Command, tarball: &Path) -> Result<()> {
+    let output = cmd
+        .output()
+        .map_err(|e| Error::Build(format!("Failed to run tar: {e}")))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(Error::Build(format!(
+            "tar failed for {}: {}",
+            tarball.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+/// Temporary directory for the manifest, removed when dropped
+struct Staging(PathBuf);
+
+impl Staging {
+    fn new() -> Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.subsec_nanos());
+        let path =
+            std::env::temp_dir().join(format!("xcargo-deps-cache-{}-{nanos}", std::process::id()));
+        fs::create_dir_all(&path)?;
+        Ok(Self(path))
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn key(lock: &str, rustc: &str) -> DepsCacheKey {
+        DepsCacheKey {
+            lock_sha256: lock.to_string(),
+            rustc: rustc.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_key_mismatch() {
+        let exported = key("aaa", "rustc 1.78.0");
+        assert!(exported.mismatch(&key("aaa", "rustc 1.78.0")).is_none());
+        assert!(exported
+            .mismatch(&key("bbb", "rustc 1.78.0"))
+            .unwrap()
+            .contains("Cargo.lock"));
+        assert!(exported
+            .mismatch(&key("aaa", "rustc 1.79.0"))
+            .unwrap()
+            .contains("rustc 1.79.0"));
+        assert_ne!(exported.digest(), key("aaa", "rustc 1.79.0").digest());
+    }
+
+    #[test]
+    fn test_export_and_import() {
+        let temp = TempDir::new().unwrap();
+        let built = temp.path().join("built");
+        let deps = built.join("aarch64-unknown-linux-gnu/release/deps");
+        fs::create_dir_all(&deps).unwrap();
+        fs::create_dir_all(built.join("aarch64-unknown-linux-gnu/release/.fingerprint/serde"))
+            .unwrap();
+        fs::write(deps.join("libserde.rlib"), b"rlib").unwrap();
+        fs::write(
+            built.join("aarch64-unknown-linux-gnu/release/app"),
+            b"not cached",
+        )
+        .unwrap();
+
+        let tarball = temp.path().join("out/deps.tar.gz");
+        let targets = vec!["aarch64-unknown-linux-gnu".to_string()];
+        export_deps(&built, &targets, true, key("aaa", "rustc"), &tarball).unwrap();
+        assert!(export_deps(&built, &targets, false, key("aaa", "rustc"), &tarball).is_err());
+
+        let manifest = read_manifest(&tarball).unwrap();
+        assert_eq!(manifest.profile, "release");
+        assert_eq!(manifest.targets, targets);
+        assert_eq!(manifest.key, key("aaa", "rustc"));
+
+        let fresh = temp.path().join("fresh");
+        import_deps(&tarball, &fresh).unwrap();
+        let release = fresh.join("aarch64-unknown-linux-gnu/release");
+        assert_eq!(
+            fs::read(release.join("deps/libserde.rlib")).unwrap(),
+            b"rlib"
+        );
+        assert!(release.join(".fingerprint/serde").is_dir());
+        assert!(!release.join("app").exists());
+        assert!(!fresh.join(MANIFEST_FILE).exists());
+    }
+}
@@ -1,6 +1,7 @@
 //! File hashing utilities for cache invalidation
 
 use crate::error::{Error, Result};
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::Path;
 use std::time::SystemTime;
@@ -68,6 +69,17 @@ pub fn hash_files(paths: &[&Path]) -> Option<u64> {
     Some(hash_combine(&hashes))
 }
 
+/// Hex-encoded SHA-256 of a file's content
+///
+/// Unlike [`hash_file`], this identifies the content itself, for checksums
+/// that are published or compared against published ones.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
 /// Check if a file has changed by comparing hashes
 pub fn has_file_changed(path: &Path, previous_hash: u64) -> Result<bool> {
     let current_hash = hash_file(path)
@@ -119,6 +131,18 @@ mod tests {
         assert_eq!(hash1, hash2);
     }
 
+    #[test]
+    fn test_sha256_file() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        write!(temp_file, "abc").unwrap();
+        temp_file.flush().unwrap();
+
+        assert_eq!(
+            sha256_file(temp_file.path()).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
     #[test]
     fn test_hash_file_changes() {
         let mut temp_file = NamedTempFile::new().unwrap();
@@ -3,9 +3,14 @@
 //! This module provides caching functionality to speed up repeated builds
 //! by detecting when source files haven't changed.
 
+mod deps;
 mod hash;
 
-pub use hash::{hash_file, hash_files, has_file_changed};
+pub use deps::{
+    export_deps, find_cargo_lock, import_deps, read_manifest, DepsCacheKey, DepsCacheManifest,
+    MANIFEST_FILE,
+};
+pub use hash::{hash_file, hash_files, has_file_changed, sha256_file};
 
 use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
@@ -73,7 +78,7 @@ impl BuildCache {
     ///
     /// # Errors
     /// Returns error if home directory cannot be determined
-    fn default_cache_dir() -> Result<PathBuf> {
+    pub fn default_cache_dir() -> Result<PathBuf> {
         let home = dirs::home_dir().ok_or_else(|| {
             Error::Config("Could not determine home directory".to_string())
         })?;
@@ -0,0 +1,233 @@
+//! `container.use_when` conditions
+//!
+//! A condition is `always`, `never`, or a comparison of target and host
+//! properties, such as `target.os != host.os` or `target.arch == "aarch64"`.
+
+use crate::target::Target;
+use std::fmt;
+use std::str::FromStr;
+
+/// Property of a target or the host compared in a condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Property {
+    /// Operating system, e.g. "linux"
+    Os,
+    /// Architecture, e.g. "aarch64"
+    Arch,
+    /// Environment/ABI, e.g. "musl"; empty if the triple has none
+    Env,
+}
+
+/// One side of a comparison
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Operand {
+    /// Property of the target being built, e.g. `target.os`
+    Target(Property),
+    /// Property of the host, e.g. `host.arch`
+    Host(Property),
+    /// Quoted text, e.g. `"windows"`
+    Literal(String),
+}
+
+/// When a target is built in a container
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UseWhen {
+    /// Every target
+    Always,
+    /// No target
+    Never,
+    /// Targets for which both sides compare equal, or unequal
+    Compare {
+        /// Left-hand side
+        left: Operand,
+        /// `==` if true, `!=` if false
+        equal: bool,
+        /// Right-hand side
+        right: Operand,
+    },
+}
+
+impl Property {
+    fn value(self, target: &Target) -> &str {
+        match self {
+            Self::Os => &target.os,
+            Self::Arch => &target.arch,
+            Self::Env => target.env.as_deref().unwrap_or(""),
+        }
+    }
+}
+
+impl Operand {
+    fn value<'a>(&'a self, target: &'a Target, host: &'a Target) -> &'a str {
+        match self {
+            Self::Target(property) => property.value(target),
+            Self::Host(property) => property.value(host),
+            Self::Literal(text) => text,
+        }
+    }
+}
+
+impl UseWhen {
+    /// Whether `target` is built in a container on `host`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::config::UseWhen;
+    /// use xcargo::target::Target;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let condition: UseWhen = "target.os != host.os".parse().unwrap();
+    /// let host = Target::from_triple("x86_64-unknown-linux-gnu")?;
+    /// let windows = Target::from_triple("x86_64-pc-windows-gnu")?;
+    /// assert!(condition.matches(&windows, &host));
+    /// assert!(!condition.matches(&host, &host));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn matches(&self, target: &Target, host: &Target) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::Compare { left, equal, right } => {
+                (left.value(target, host) == right.value(target, host)) == *equal
+            }
+        }
+    }
+}
+
+impl FromStr for Operand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(text) = s
+            .strip_prefix('"')
+            .and_then(|rest| rest.strip_suffix('"'))
+            .or_else(|| {
+                s.strip_prefix('\'')
+                    .and_then(|rest| rest.strip_suffix('\''))
+            })
+        {
+            return Ok(Self::Literal(text.to_string()));
+        }
+
+        let (side, property) = s.split_once('.').unwrap_or((s, ""));
+        let property = match property {
+            "os" => Property::Os,
+            "arch" => Property::Arch,
+            "env" => Property::Env,
+            _ => {
+                return Err(format!(
+                    "unknown operand '{s}' (expected target.os, target.arch, target.env, the same for host, or quoted text)"
+                ))
+            }
+        };
+        match side {
+            "target" => Ok(Self::Target(property)),
+            "host" => Ok(Self::Host(property)),
+            _ => Err(format!(
+                "unknown operand '{s}' (expected target.<property>, host.<property>, or quoted text)"
+            )),
+        }
+    }
+}
+
+impl FromStr for UseWhen {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "always" => return Ok(Self::Always),
+            "never" => return Ok(Self::Never),
+            _ => {}
+        }
+        let (left, equal, right) = if let Some((left, right)) = s.split_once("!=") {
+            (left, false, right)
+        } else if let Some((left, right)) = s.split_once("==") {
+            (left, true, right)
+        } else {
+            return Err(format!(
+                "cannot read '{}': expected always, never, or a comparison such as target.os != host.os",
+                s.trim()
+            ));
+        };
+        Ok(Self::Compare {
+            left: left.parse()?,
+            equal,
+            right: right.parse()?,
+        })
+    }
+}
+
+impl fmt::Display for Operand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (side, property) = match self {
+            Self::Target(property) => ("target", property),
+            Self::Host(property) => ("host", property),
+            Self::Literal(text) => return write!(f, "\"{text}\""),
+        };
+        let property = match property {
+            Property::Os => "os",
+            Property::Arch => "arch",
+            Property::Env => "env",
+        };
+        write!(f, "{side}.{property}")
+    }
+}
+
+impl fmt::Display for UseWhen {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Always => f.write_str("always"),
+            Self::Never => f.write_str("never"),
+            Self::Compare { left, equal, right } => {
+                let op = if *equal { "==" } else { "!=" };
+                write!(f, "{left} {op} {right}")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_use_when() {
+        assert_eq!("always".parse::<UseWhen>(), Ok(UseWhen::Always));
+        assert_eq!(" never ".parse::<UseWhen>(), Ok(UseWhen::Never));
+        assert_eq!(
+            "target.os != host.os".parse::<UseWhen>(),
+            Ok(UseWhen::Compare {
+                left: Operand::Target(Property::Os),
+                equal: false,
+                right: Operand::Host(Property::Os),
+            })
+        );
+
+        let condition: UseWhen = "target.env == 'musl'".parse().unwrap();
+        assert_eq!(condition.to_string(), "target.env == \"musl\"");
+
+        assert!("sometimes".parse::<UseWhen>().is_err());
+        assert!("target.vendor == host.vendor".parse::<UseWhen>().is_err());
+        assert!("guest.os != host.os".parse::<UseWhen>().is_err());
+    }
+
+    #[test]
+    fn test_use_when_matches() {
+        let host = Target::from_triple("x86_64-unknown-linux-gnu").unwrap();
+        let musl = Target::from_triple("aarch64-unknown-linux-musl").unwrap();
+
+        let condition: UseWhen = "target.arch != host.arch".parse().unwrap();
+        assert!(condition.matches(&musl, &host));
+
+        let condition: UseWhen = "target.env == \"musl\"".parse().unwrap();
+        assert!(condition.matches(&musl, &host));
+        assert!(!condition.matches(&host, &host));
+
+        assert!(UseWhen::Always.matches(&host, &host));
+        assert!(!UseWhen::Never.matches(&musl, &host));
+    }
+}
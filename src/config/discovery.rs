@@ -1,10 +1,13 @@
 //! Configuration file discovery
 //!
-//! This module handles finding xcargo.toml files in the filesystem
+//! This module handles finding xcargo.toml files in the filesystem, the
+//! `[package.metadata.xcargo]` tables of Cargo.toml files, and the user
+//! configuration shared by all projects
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use toml::Table;
 
 /// Configuration file discovery utility
 pub struct ConfigDiscovery;
@@ -52,6 +55,39 @@ impl ConfigDiscovery {
         Ok(None)
     }
 
+    /// Find a Cargo.toml with xcargo configuration, starting from the
+    /// current directory
+    ///
+    /// Small projects can keep their configuration in
+    /// `[package.metadata.xcargo]` (or `[workspace.metadata.xcargo]`) instead
+    /// of a separate xcargo.toml.
+    pub fn find_manifest() -> Result<Option<PathBuf>> {
+        Self::find_manifest_from(&env::current_dir()?)
+    }
+
+    /// Find a Cargo.toml with xcargo configuration, starting from a specific
+    /// directory
+    ///
+    /// Manifests that cannot be read or parsed are skipped; cargo reports
+    /// those itself.
+    pub fn find_manifest_from(start: &Path) -> Result<Option<PathBuf>> {
+        let mut current = Some(start);
+
+        while let Some(dir) = current {
+            let manifest_path = dir.join("Cargo.toml");
+            let has_config = std::fs::read_to_string(&manifest_path)
+                .ok()
+                .and_then(|contents| contents.parse::<Table>().ok())
+                .is_some_and(|manifest| manifest_metadata(&manifest).is_some());
+            if has_config {
+                return Ok(Some(manifest_path));
+            }
+            current = dir.parent();
+        }
+
+        Ok(None)
+    }
+
     /// Check if xcargo.toml exists in the current directory
     pub fn exists_in_current() -> Result<bool> {
         let current = env::current_dir()?;
@@ -62,6 +98,70 @@ impl ConfigDiscovery {
     pub fn default_path() -> Result<PathBuf> {
         Ok(env::current_dir()?.join("xcargo.toml"))
     }
+
+    /// Path of the user configuration file, whether or not it exists
+    ///
+    /// `$XDG_CONFIG_HOME/xcargo/config.toml`, defaulting to
+    /// `~/.config/xcargo/config.toml`. On Windows it is in the roaming
+    /// application data directory instead.
+    #[must_use]
+    pub fn user_config_path() -> Option<PathBuf> {
+        let xdg = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute());
+        xdg.or_else(default_config_dir)
+            .map(|dir| dir.join("xcargo").join("config.toml"))
+    }
+}
+
+/// The xcargo table of a parsed Cargo.toml: `[package.metadata.xcargo]`,
+/// or else `[workspace.metadata.xcargo]`
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::config::manifest_metadata;
+///
+/// let manifest: toml::Table = r#"
+///     [package]
+///     name = "app"
+///
+///     [package.metadata.xcargo.targets]
+///     default = ["aarch64-unknown-linux-gnu"]
+/// "#.parse().unwrap();
+/// let config = manifest_metadata(&manifest).unwrap();
+/// assert!(config.contains_key("targets"));
+/// ```
+#[must_use]
+pub fn manifest_metadata(manifest: &Table) -> Option<&Table> {
+    ["package", "workspace"].iter().find_map(|section| {
+        manifest
+            .get(*section)?
+            .get("metadata")?
+            .get("xcargo")?
+            .as_table()
+    })
+}
+
+/// Read and parse the Cargo.toml at `path`
+pub(crate) fn read_manifest(path: &Path) -> Result<Table> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| Error::Config(format!("Failed to read {}: {e}", path.display())))?;
+    contents.parse::<Table>().map_err(|e| Error::ConfigParse {
+        path: path.display().to_string(),
+        line: None,
+        message: e.to_string(),
+    })
+}
+
+#[cfg(windows)]
+fn default_config_dir() -> Option<PathBuf> {
+    dirs::config_dir()
+}
+
+#[cfg(not(windows))]
+fn default_config_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".config"))
 }
 
 #[cfg(test)]
@@ -114,6 +214,41 @@ mod tests {
         assert!(found.is_none());
     }
 
+    #[test]
+    fn test_find_manifest_config() {
+        let temp = TempDir::new().unwrap();
+        let member = temp.path().join("crates/app");
+        fs::create_dir_all(&member).unwrap();
+        fs::write(
+            member.join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+
+        // A member without xcargo metadata doesn't count
+        assert!(ConfigDiscovery::find_manifest_from(&member)
+            .unwrap()
+            .is_none());
+
+        let root_manifest = temp.path().join("Cargo.toml");
+        fs::write(
+            &root_manifest,
+            "[workspace]\nmembers = [\"crates/app\"]\n\n[workspace.metadata.xcargo.build]\njobs = 2\n",
+        )
+        .unwrap();
+        assert_eq!(
+            ConfigDiscovery::find_manifest_from(&member).unwrap(),
+            Some(root_manifest)
+        );
+    }
+
+    #[test]
+    fn test_user_config_path() {
+        if let Some(path) = ConfigDiscovery::user_config_path() {
+            assert!(path.ends_with("xcargo/config.toml"));
+        }
+    }
+
     #[test]
     fn test_default_path() {
         let path = ConfigDiscovery::default_path().unwrap();
@@ -0,0 +1,254 @@
+//! Configuration from `XCARGO_*` environment variables
+//!
+//! Every key of the `[build]`, `[container]`, `[policy]`, `[toolchain]`,
+//! `[zig]`, `[output]`, `[artifacts]`, and `[attest]` sections, and
+//! `targets.default`, can be set with a variable named after it:
+//! `build.parallel` is `XCARGO_BUILD_PARALLEL`.
+//! Tables keyed by name (per-target settings, profiles, runners, target
+//! groups) can only be set in files.
+
+use crate::error::{Error, Result};
+use crate::output::i18n::{Locale, LANG_ENV};
+use crate::output::theme::{Theme, THEME_ENV};
+use toml::Value;
+
+/// How the value of a variable is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnvKind {
+    /// `true`/`false` or `1`/`0`
+    Bool,
+    /// Number greater than 0
+    Count,
+    /// Any text
+    Text,
+    /// Theme name
+    Theme,
+    /// Language, e.g. `pt-BR`
+    Language,
+    /// Comma-separated list, e.g. target triples
+    List,
+    /// Whitespace-separated list, e.g. cargo flags
+    Args,
+}
+
+/// Keys that can be set from the environment
+const ENV_KEYS: [(&str, EnvKind); 35] = [
+    ("targets.default", EnvKind::List),
+    ("build.parallel", EnvKind::Bool),
+    ("build.jobs", EnvKind::Count),
+    ("build.host_first", EnvKind::Bool),
+    ("build.cache", EnvKind::Bool),
+    ("build.force_container", EnvKind::Bool),
+    ("build.cargo_flags", EnvKind::Args),
+    ("build.rustflags", EnvKind::Args),
+    ("build.fallback", EnvKind::Bool),
+    ("build.target_dir", EnvKind::Text),
+    ("build.isolate_targets", EnvKind::Bool),
+    ("build.fail_fast", EnvKind::Bool),
+    ("build.timeout", EnvKind::Text),
+    ("build.output", EnvKind::Text),
+    ("container.runtime", EnvKind::Text),
+    ("container.use_when", EnvKind::Text),
+    ("container.registry", EnvKind::Text),
+    ("container.pull_policy", EnvKind::Text),
+    ("container.map_user", EnvKind::Bool),
+    ("container.credentials", EnvKind::Text),
+    ("container.volumes", EnvKind::List),
+    ("container.cargo_cache", EnvKind::Text),
+    ("container.forward_registry_tokens", EnvKind::Bool),
+    ("container.forward_netrc", EnvKind::Bool),
+    ("policy.release_requires", EnvKind::Text),
+    ("toolchain.channel", EnvKind::Text),
+    ("zig.version", EnvKind::Text),
+    ("output.theme", EnvKind::Theme),
+    ("output.plain", EnvKind::Bool),
+    ("output.language", EnvKind::Language),
+    ("artifacts.keep_last_builds", EnvKind::Count),
+    ("artifacts.max_total_size", EnvKind::Text),
+    ("attest.enabled", EnvKind::Bool),
+    ("attest.sign", EnvKind::Text),
+    ("attest.key", EnvKind::Text),
+];
+
+/// Short variable names from before every key had one
+const ENV_ALIASES: [(&str, &str); 5] = [
+    ("XCARGO_RUNTIME", "container.runtime"),
+    ("XCARGO_JOBS", "build.jobs"),
+    ("XCARGO_CACHE", "build.cache"),
+    (THEME_ENV, "output.theme"),
+    (LANG_ENV, "output.language"),
+];
+
+/// An environment variable that sets a configuration key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvOverride {
+    /// Variable name, e.g. `XCARGO_BUILD_JOBS`
+    pub var: String,
+
+    /// Configuration key it sets, e.g. `build.jobs`
+    pub key: &'static str,
+
+    /// Whether this is a short alias; the full name wins if both are set
+    pub alias: bool,
+
+    kind: EnvKind,
+}
+
+impl EnvOverride {
+    /// Read the variable's value for the configuration
+    ///
+    /// Returns `None` for empty values, which count as unset.
+    ///
+    /// # Errors
+    /// Returns an error if the value does not fit the key
+    pub fn parse(&self, raw: &str) -> Result<Option<Value>> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            return Ok(None);
+        }
+        let invalid = |expected: &str| {
+            Error::Config(format!(
+                "Invalid {} value '{raw}': expected {expected}",
+                self.var
+            ))
+        };
+
+        let value = match self.kind {
+            EnvKind::Bool => match trimmed.to_ascii_lowercase().as_str() {
+                "1" | "true" => Value::Boolean(true),
+                "0" | "false" => Value::Boolean(false),
+                _ => return Err(invalid("true/false or 1/0")),
+            },
+            EnvKind::Count => match trimmed.parse::<i64>() {
+                Ok(count) if count > 0 => Value::Integer(count),
+                _ => return Err(invalid("a number greater than 0")),
+            },
+            EnvKind::Text => Value::String(trimmed.to_string()),
+            EnvKind::Theme => match trimmed.parse::<Theme>() {
+                Ok(theme) => Value::String(theme.as_str().to_string()),
+                // The theme module warns about an unknown XCARGO_THEME itself
+                Err(_) if self.var == THEME_ENV => return Ok(None),
+                Err(e) => return Err(invalid(&e)),
+            },
+            EnvKind::Language => match trimmed.parse::<Locale>() {
+                Ok(locale) => Value::String(locale.as_str().to_string()),
+                // The i18n module warns about an unknown XCARGO_LANG itself
+                Err(_) if self.var == LANG_ENV => return Ok(None),
+                Err(e) => return Err(invalid(&e)),
+            },
+            EnvKind::List => list(trimmed.split(',')),
+            EnvKind::Args => list(trimmed.split_whitespace()),
+        };
+        Ok(Some(value))
+    }
+}
+
+fn list<'a>(items: impl Iterator<Item = &'a str>) -> Value {
+    Value::Array(
+        items
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(|item| Value::String(item.to_string()))
+            .collect(),
+    )
+}
+
+/// Variable name for a configuration key
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::config::env_var_name;
+///
+/// assert_eq!(env_var_name("build.parallel"), "XCARGO_BUILD_PARALLEL");
+/// ```
+#[must_use]
+pub fn env_var_name(key: &str) -> String {
+    format!("XCARGO_{}", key.to_ascii_uppercase().replace('.', "_"))
+}
+
+/// Every recognized variable, in the order they are applied
+///
+/// Aliases come first so the full names take precedence over them.
+#[must_use]
+pub fn env_overrides() -> Vec<EnvOverride> {
+    let kind_of = |key: &str| {
+        ENV_KEYS
+            .iter()
+            .find(|(known, _)| *known == key)
+            .map_or(EnvKind::Text, |(_, kind)| *kind)
+    };
+    let aliases = ENV_ALIASES.iter().map(|(var, key)| EnvOverride {
+        var: (*var).to_string(),
+        key,
+        alias: true,
+        kind: kind_of(key),
+    });
+    let full = ENV_KEYS.iter().map(|(key, kind)| EnvOverride {
+        var: env_var_name(key),
+        key,
+        alias: false,
+        kind: *kind,
+    });
+    aliases.chain(full).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(var: &str) -> EnvOverride {
+        env_overrides()
+            .into_iter()
+            .find(|env| env.var == var)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let overrides = env_overrides();
+        assert_eq!(overrides.len(), ENV_ALIASES.len() + ENV_KEYS.len());
+        assert!(overrides[0].alias);
+        assert_eq!(find("XCARGO_CONTAINER_REGISTRY").key, "container.registry");
+        assert_eq!(find("XCARGO_JOBS").key, "build.jobs");
+    }
+
+    #[test]
+    fn test_parse_values() {
+        let parallel = find("XCARGO_BUILD_PARALLEL");
+        assert_eq!(
+            parallel.parse("false").unwrap(),
+            Some(Value::Boolean(false))
+        );
+        assert!(parallel.parse("maybe").is_err());
+        assert_eq!(parallel.parse("  ").unwrap(), None);
+
+        let targets = find("XCARGO_TARGETS_DEFAULT")
+            .parse("x86_64-pc-windows-gnu, aarch64-unknown-linux-gnu,")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            targets.as_array().unwrap().len(),
+            2,
+            "trailing commas are ignored"
+        );
+
+        let flags = find("XCARGO_BUILD_CARGO_FLAGS")
+            .parse("--locked  --features=a,b")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            flags.as_array().unwrap()[1].as_str(),
+            Some("--features=a,b")
+        );
+
+        assert!(find("XCARGO_BUILD_JOBS").parse("0").is_err());
+        assert!(find("XCARGO_OUTPUT_THEME").parse("neon").is_err());
+        assert_eq!(find("XCARGO_THEME").parse("neon").unwrap(), None);
+        assert_eq!(
+            find("XCARGO_LANG").parse("pt_BR.UTF-8").unwrap(),
+            Some(Value::String("pt-BR".to_string()))
+        );
+        assert!(find("XCARGO_OUTPUT_LANGUAGE").parse("klingon").is_err());
+    }
+}
@@ -0,0 +1,485 @@
+//! Layered configuration loading
+//!
+//! The effective configuration is built from several layers, each overriding
+//! the ones before it:
+//!
+//! 1. Built-in defaults
+//! 2. User configuration (`~/.config/xcargo/config.toml`)
+//! 3. `[package.metadata.xcargo]` or `[workspace.metadata.xcargo]` in Cargo.toml
+//! 4. Workspace configuration (`xcargo.toml`)
+//! 5. `XCARGO_*` environment variables
+//! 6. `--config KEY=VALUE` on the command line
+//!
+//! Layers are merged as TOML tables, so a layer only overrides the keys it
+//! actually sets. The layer each key came from is recorded for
+//! `xcargo config --show-origin`.
+
+use crate::error::{Error, Result};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use toml::{Table, Value};
+
+use super::env::env_overrides;
+use super::validate::parse_error;
+use super::version::check_required_version;
+use super::{manifest_metadata, read_manifest, Config, ConfigDiscovery};
+
+/// Where a configuration value came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// Built-in default
+    Default,
+    /// User configuration file
+    User(PathBuf),
+    /// xcargo table in a `Cargo.toml`
+    Manifest(PathBuf),
+    /// Workspace `xcargo.toml`
+    Workspace(PathBuf),
+    /// Environment variable
+    Env(String),
+    /// `--config` on the command line
+    Cli,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Default => f.write_str("default"),
+            Self::User(path) => write!(f, "user config {}", path.display()),
+            Self::Manifest(path) => write!(f, "xcargo metadata in {}", path.display()),
+            Self::Workspace(path) => write!(f, "workspace config {}", path.display()),
+            Self::Env(var) => write!(f, "environment variable {var}"),
+            Self::Cli => f.write_str("--config"),
+        }
+    }
+}
+
+/// `--config` overrides, set once from the command line
+static CLI_OVERRIDES: OnceLock<Vec<(String, Value)>> = OnceLock::new();
+
+/// Apply `--config KEY=VALUE` overrides to every configuration loaded later
+///
+/// # Errors
+/// Returns an error if an override is not `KEY=VALUE`
+pub fn set_cli_overrides(overrides: &[String]) -> Result<()> {
+    let parsed = overrides
+        .iter()
+        .map(|entry| parse_override(entry))
+        .collect::<Result<Vec<_>>>()?;
+    // Only the command line sets overrides, once at startup
+    let _ = CLI_OVERRIDES.set(parsed);
+    Ok(())
+}
+
+/// Parse `KEY=VALUE`, reading the value as TOML or else as a plain string
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::config::parse_override;
+///
+/// let (key, value) = parse_override("build.jobs=4").unwrap();
+/// assert_eq!(key, "build.jobs");
+/// assert_eq!(value.as_integer(), Some(4));
+///
+/// let (_, value) = parse_override("container.runtime=podman").unwrap();
+/// assert_eq!(value.as_str(), Some("podman"));
+/// ```
+///
+/// # Errors
+/// Returns an error if there is no `=` or the key is empty
+pub fn parse_override(entry: &str) -> Result<(String, Value)> {
+    let (key, value) = entry
+        .split_once('=')
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| {
+            Error::Config(format!(
+                "Invalid --config '{entry}'. Use KEY=VALUE, e.g. --config build.jobs=4"
+            ))
+        })?;
+
+    let value = format!("value = {value}")
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(value.to_string()));
+    Ok((key.to_string(), value))
+}
+
+/// Configuration merged from all layers, with the origin of each value
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    /// The effective configuration
+    pub config: Config,
+
+    /// Configuration files that were read, lowest precedence first
+    pub files: Vec<ConfigSource>,
+
+    /// Layer that set each key, by dotted key path
+    origins: BTreeMap<String, ConfigSource>,
+}
+
+impl LayeredConfig {
+    /// Load the configuration for the current directory from all layers
+    ///
+    /// # Errors
+    /// Returns an error if a configuration file or environment variable is
+    /// invalid
+    pub fn discover() -> Result<Self> {
+        let mut loader = ConfigLoader::new()?;
+        if let Some(path) = ConfigDiscovery::user_config_path().filter(|p| p.is_file()) {
+            loader.add_file(ConfigSource::User(path))?;
+        }
+        if let Some(path) = ConfigDiscovery::find_manifest()? {
+            loader.add_file(ConfigSource::Manifest(path))?;
+        }
+        if let Some(path) = ConfigDiscovery::find()? {
+            loader.add_file(ConfigSource::Workspace(path))?;
+        }
+        loader.add_env(|var| std::env::var(var).ok())?;
+        if let Some(overrides) = CLI_OVERRIDES.get() {
+            for (key, value) in overrides {
+                loader.set(key, value.clone(), &ConfigSource::Cli);
+            }
+        }
+        loader.finish()
+    }
+
+    /// Layer that set `key`, e.g. "build.jobs"
+    #[must_use]
+    pub fn origin(&self, key: &str) -> &ConfigSource {
+        self.origins.get(key).unwrap_or(&ConfigSource::Default)
+    }
+
+    /// Every effective value as `(key, value, origin)`, sorted by key
+    ///
+    /// # Errors
+    /// Returns an error if the configuration cannot be serialized
+    pub fn entries(&self) -> Result<Vec<(String, Value, &ConfigSource)>> {
+        let table = Table::try_from(&self.config)
+            .map_err(|e| Error::Config(format!("Failed to serialize config: {e}")))?;
+        let mut entries = Vec::new();
+        flatten(&table, "", &mut entries);
+        Ok(entries
+            .into_iter()
+            .map(|(key, value)| {
+                let origin = self.origin(&key);
+                (key, value, origin)
+            })
+            .collect())
+    }
+}
+
+/// Merges configuration layers in order of precedence
+struct ConfigLoader {
+    merged: Table,
+    files: Vec<ConfigSource>,
+    origins: BTreeMap<String, ConfigSource>,
+}
+
+impl ConfigLoader {
+    /// Start from the built-in defaults
+    fn new() -> Result<Self> {
+        let merged = Table::try_from(Config::default())
+            .map_err(|e| Error::Config(format!("Failed to serialize config: {e}")))?;
+        Ok(Self {
+            merged,
+            files: Vec::new(),
+            origins: BTreeMap::new(),
+        })
+    }
+
+    /// Merge the file of a user, manifest, or workspace source
+    fn add_file(&mut self, source: ConfigSource) -> Result<()> {
+        let table = match &source {
+            ConfigSource::User(path) | ConfigSource::Workspace(path) => read_table(path)?,
+            ConfigSource::Manifest(path) => read_manifest_table(path)?,
+            _ => return Ok(()),
+        };
+        merge(&mut self.merged, table, "", &source, &mut self.origins);
+        self.files.push(source);
+        Ok(())
+    }
+
+    /// Merge the `XCARGO_*` variables that `var` returns a value for
+    fn add_env(&mut self, var: impl Fn(&str) -> Option<String>) -> Result<()> {
+        for env in env_overrides() {
+            let Some(raw) = var(&env.var) else {
+                continue;
+            };
+            if let Some(value) = env.parse(&raw)? {
+                self.set(env.key, value, &ConfigSource::Env(env.var));
+            }
+        }
+        Ok(())
+    }
+
+    /// Set a single dotted key
+    fn set(&mut self, key: &str, value: Value, source: &ConfigSource) {
+        let mut layer = value;
+        for part in key.rsplit('.') {
+            let mut table = Table::new();
+            table.insert(part.to_string(), layer);
+            layer = Value::Table(table);
+        }
+        if let Value::Table(table) = layer {
+            merge(&mut self.merged, table, "", source, &mut self.origins);
+        }
+    }
+
+    /// Build the configuration from the merged layers
+    fn finish(self) -> Result<LayeredConfig> {
+        let config = Value::Table(self.merged)
+            .try_into::<Config>()
+            .map_err(|e| Error::Config(format!("Invalid configuration: {e}")))?;
+        Ok(LayeredConfig {
+            config,
+            files: self.files,
+            origins: self.origins,
+        })
+    }
+}
+
+/// Read a configuration file, checking it on its own so errors name the file
+fn read_table(path: &Path) -> Result<Table> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        Error::Config(format!(
+            "Failed to read config file {}: {e}",
+            path.display()
+        ))
+    })?;
+    let table: Table = toml::from_str(&contents).map_err(|e| parse_error(path, &contents, &e))?;
+    check_required_version(&table, path)?;
+    toml::from_str::<Config>(&contents).map_err(|e| parse_error(path, &contents, &e))?;
+    Ok(table)
+}
+
+/// Read the xcargo table of a Cargo.toml, checking it like a configuration file
+fn read_manifest_table(path: &Path) -> Result<Table> {
+    let manifest = read_manifest(path)?;
+    let table = manifest_metadata(&manifest).cloned().unwrap_or_default();
+    check_required_version(&table, path)?;
+    Value::Table(table.clone())
+        .try_into::<Config>()
+        .map_err(|e| {
+            Error::Config(format!(
+                "Invalid xcargo metadata in {}: {e}",
+                path.display()
+            ))
+        })?;
+    Ok(table)
+}
+
+/// Merge `layer` into `base`: tables merge key by key, other values replace
+fn merge(
+    base: &mut Table,
+    layer: Table,
+    prefix: &str,
+    source: &ConfigSource,
+    origins: &mut BTreeMap<String, ConfigSource>,
+) {
+    for (key, value) in layer {
+        let path = join_key(prefix, &key);
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(existing)), Value::Table(table)) => {
+                merge(existing, table, &path, source, origins);
+            }
+            (_, Value::Table(table)) => {
+                let mut fresh = Table::new();
+                merge(&mut fresh, table, &path, source, origins);
+                base.insert(key, Value::Table(fresh));
+            }
+            (_, value) => {
+                origins.insert(path, source.clone());
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Collect the non-table values of `table` under their dotted key paths
+fn flatten(table: &Table, prefix: &str, entries: &mut Vec<(String, Value)>) {
+    for (key, value) in table {
+        let path = join_key(prefix, key);
+        match value {
+            Value::Table(inner) => flatten(inner, &path, entries),
+            value => entries.push((path, value.clone())),
+        }
+    }
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::theme::Theme;
+    use tempfile::TempDir;
+
+    fn write(dir: &TempDir, name: &str, contents: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_layer_precedence() {
+        let temp = TempDir::new().unwrap();
+        let user = write(
+            &temp,
+            "config.toml",
+            "[container]\nregistry = \"ghcr.io/acme\"\nruntime = \"podman\"\n\n[build]\njobs = 2\n",
+        );
+        let workspace = write(
+            &temp,
+            "xcargo.toml",
+            "[build]\njobs = 4\nparallel = false\n",
+        );
+
+        let mut loader = ConfigLoader::new().unwrap();
+        loader.add_file(ConfigSource::User(user.clone())).unwrap();
+        loader
+            .add_file(ConfigSource::Workspace(workspace.clone()))
+            .unwrap();
+        loader
+            .add_env(|var| (var == "XCARGO_RUNTIME").then(|| "docker".to_string()))
+            .unwrap();
+        loader.set("build.jobs", Value::Integer(8), &ConfigSource::Cli);
+        let layered = loader.finish().unwrap();
+
+        let config = &layered.config;
+        assert_eq!(config.container.registry.as_deref(), Some("ghcr.io/acme"));
+        assert_eq!(config.container.runtime, "docker");
+        assert_eq!(config.build.jobs, Some(8));
+        assert!(!config.build.parallel);
+        // Keys no layer sets keep their defaults
+        assert!(config.build.cache);
+
+        assert_eq!(
+            layered.origin("container.registry"),
+            &ConfigSource::User(user)
+        );
+        assert_eq!(
+            layered.origin("build.parallel"),
+            &ConfigSource::Workspace(workspace)
+        );
+        assert_eq!(
+            layered.origin("container.runtime"),
+            &ConfigSource::Env("XCARGO_RUNTIME".to_string())
+        );
+        assert_eq!(layered.origin("build.jobs"), &ConfigSource::Cli);
+        assert_eq!(layered.origin("build.cache"), &ConfigSource::Default);
+        assert_eq!(layered.files.len(), 2);
+
+        let entries = layered.entries().unwrap();
+        let (_, value, origin) = entries
+            .iter()
+            .find(|(key, _, _)| key == "build.jobs")
+            .unwrap();
+        assert_eq!(value.as_integer(), Some(8));
+        assert_eq!(*origin, &ConfigSource::Cli);
+    }
+
+    #[test]
+    fn test_manifest_metadata_layer() {
+        let temp = TempDir::new().unwrap();
+        let manifest = write(
+            &temp,
+            "Cargo.toml",
+            "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n\
+             [package.metadata.xcargo.targets]\ndefault = [\"aarch64-unknown-linux-gnu\"]\n\n\
+             [package.metadata.xcargo.build]\njobs = 2\n",
+        );
+        let workspace = write(&temp, "xcargo.toml", "[build]\njobs = 4\n");
+
+        let mut loader = ConfigLoader::new().unwrap();
+        loader
+            .add_file(ConfigSource::Manifest(manifest.clone()))
+            .unwrap();
+        loader
+            .add_file(ConfigSource::Workspace(workspace.clone()))
+            .unwrap();
+        let layered = loader.finish().unwrap();
+
+        // xcargo.toml takes precedence over Cargo.toml metadata
+        assert_eq!(
+            layered.config.targets.default,
+            ["aarch64-unknown-linux-gnu"]
+        );
+        assert_eq!(layered.config.build.jobs, Some(4));
+        assert_eq!(
+            layered.origin("targets.default"),
+            &ConfigSource::Manifest(manifest.clone())
+        );
+        assert_eq!(
+            layered.origin("build.jobs"),
+            &ConfigSource::Workspace(workspace)
+        );
+
+        write(
+            &temp,
+            "Cargo.toml",
+            "[package.metadata.xcargo.build]\njobs = \"many\"\n",
+        );
+        let err = ConfigLoader::new()
+            .unwrap()
+            .add_file(ConfigSource::Manifest(manifest))
+            .unwrap_err();
+        assert!(err.to_string().contains("xcargo metadata in"));
+    }
+
+    #[test]
+    fn test_env_values() {
+        let mut loader = ConfigLoader::new().unwrap();
+        loader
+            .add_env(|var| match var {
+                "XCARGO_CACHE" => Some("0".to_string()),
+                "XCARGO_THEME" => Some("colorblind".to_string()),
+                "XCARGO_TARGETS_DEFAULT" => Some("wasm32-unknown-unknown".to_string()),
+                _ => None,
+            })
+            .unwrap();
+        let config = loader.finish().unwrap().config;
+        assert!(!config.build.cache);
+        assert_eq!(config.output.theme, Some(Theme::ColorblindSafe));
+        assert_eq!(config.targets.default, ["wasm32-unknown-unknown"]);
+
+        let mut loader = ConfigLoader::new().unwrap();
+        let err = loader
+            .add_env(|var| (var == "XCARGO_JOBS").then(|| "many".to_string()))
+            .unwrap_err();
+        assert!(err.to_string().contains("XCARGO_JOBS"));
+    }
+
+    #[test]
+    fn test_invalid_file_names_path() {
+        let temp = TempDir::new().unwrap();
+        let user = write(&temp, "config.toml", "[bulid]\njobs = 2\n");
+
+        let mut loader = ConfigLoader::new().unwrap();
+        let err = loader.add_file(ConfigSource::User(user)).unwrap_err();
+        assert!(err.to_string().contains("config.toml"));
+    }
+
+    #[test]
+    fn test_parse_override() {
+        let (key, value) = parse_override("targets.default=[\"wasm32-unknown-unknown\"]").unwrap();
+        assert_eq!(key, "targets.default");
+        assert!(value.is_array());
+
+        let (_, value) = parse_override("build.cache = false").unwrap();
+        assert_eq!(value.as_bool(), Some(false));
+
+        assert!(parse_override("build.jobs").is_err());
+        assert!(parse_override("=4").is_err());
+    }
+}
@@ -3,19 +3,45 @@
 //! This module handles parsing and managing xcargo.toml configuration files.
 
 use crate::error::{Error, Result};
+use crate::output::i18n::Locale;
+use crate::output::theme::Theme;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
+mod condition;
 mod discovery;
-
-pub use discovery::ConfigDiscovery;
+mod env;
+mod layers;
+mod suggest;
+mod templates;
+mod validate;
+mod version;
+mod volume;
+mod write;
+
+pub use condition::{Operand, Property, UseWhen};
+pub use discovery::{manifest_metadata, ConfigDiscovery};
+pub use env::{env_overrides, env_var_name, EnvOverride};
+pub use layers::{parse_override, set_cli_overrides, ConfigSource, LayeredConfig};
+pub use suggest::{ProjectFacts, SuggestedTarget, Suggestion};
+pub use templates::ProjectTemplate;
+pub use validate::{check_config, check_config_file, check_config_settings, ConfigProblem};
+pub use version::{current_version, version_matches};
+pub use volume::VolumeMount;
+pub use write::{backup_path, write_config_file};
+
+pub(crate) use discovery::read_manifest;
 
 /// Main configuration structure for xcargo.toml
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(deny_unknown_fields)]
 #[derive(Default)]
 pub struct Config {
+    /// Versions of xcargo this project can be built with, e.g. ">=0.5"
+    pub required_version: Option<String>,
+
     /// Target platform configuration
     #[serde(default)]
     pub targets: TargetsConfig,
@@ -31,6 +57,62 @@ pub struct Config {
     /// Custom profiles for different build scenarios
     #[serde(default)]
     pub profiles: HashMap<String, ProfileConfig>,
+
+    /// Named sets of targets, used as `@name` in place of a triple
+    #[serde(default, rename = "target-groups")]
+    pub target_groups: HashMap<String, Vec<String>>,
+
+    /// Organizational build policies
+    #[serde(default)]
+    pub policy: PolicyConfig,
+
+    /// Rust toolchain pinning
+    #[serde(default)]
+    pub toolchain: ToolchainConfig,
+
+    /// Zig pinning
+    #[serde(default)]
+    pub zig: ZigConfig,
+
+    /// Proxy and CA bundle for downloads and image pulls
+    #[serde(default)]
+    pub network: NetworkConfig,
+
+    /// Test runner timeouts and retries per target triple
+    #[serde(default)]
+    pub runners: HashMap<String, RunnerConfig>,
+
+    /// Terminal output settings
+    #[serde(default)]
+    pub output: OutputConfig,
+
+    /// Retention of per-target build artifacts
+    #[serde(default)]
+    pub artifacts: ArtifactsConfig,
+
+    /// Provenance records for built artifacts
+    #[serde(default)]
+    pub attest: AttestConfig,
+
+    /// Commands run before and after each target's build
+    #[serde(default)]
+    pub hooks: HooksConfig,
+
+    /// Combinations built by `xcargo matrix`
+    #[serde(default)]
+    pub matrix: MatrixConfig,
+
+    /// Remote hosts `xcargo deploy` copies builds to
+    #[serde(default)]
+    pub deploy: DeployConfig,
+
+    /// Packages made from built binaries by `xcargo package`
+    #[serde(default)]
+    pub package: PackageConfig,
+
+    /// Code signing of macOS and Windows release builds
+    #[serde(default)]
+    pub signing: SigningConfig,
 }
 
 /// Target configuration section
@@ -45,6 +127,9 @@ pub struct TargetsConfig {
     pub custom: HashMap<String, TargetCustomConfig>,
 }
 
+/// Prefix naming a target group where a target triple is expected
+pub const TARGET_GROUP_PREFIX: char = '@';
+
 /// Custom configuration for a specific target
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct TargetCustomConfig {
@@ -60,18 +145,106 @@ pub struct TargetCustomConfig {
 
     /// Additional rustflags
     pub rustflags: Option<Vec<String>>,
+
+    /// Flags for C code that build scripts compile with the `cc` crate
+    pub cflags: Option<Vec<String>>,
+
+    /// Flags for C++ code that build scripts compile with the `cc` crate
+    pub cxxflags: Option<Vec<String>>,
+
+    /// Command used to run test and bench binaries (e.g. "qemu-aarch64 -L /usr/aarch64-linux-gnu")
+    pub runner: Option<String>,
+
+    /// Cargo features to enable for this target
+    pub features: Option<Vec<String>>,
+
+    /// Disable the package's default features for this target
+    #[serde(rename = "no-default-features")]
+    pub no_default_features: Option<bool>,
+
+    /// Rustup components needed for this target (e.g. "llvm-tools-preview")
+    pub components: Option<Vec<String>>,
+
+    /// Strip symbols from binaries after a build
+    pub strip: Option<bool>,
+
+    /// objcopy arguments applied to binaries after a build (e.g. `["-O", "binary"]`)
+    pub objcopy: Option<Vec<String>>,
+
+    /// Compress binaries with UPX after a build
+    pub upx: Option<bool>,
+
+    /// Move debug info out of binaries after a build and archive it under
+    /// `target/symbols` by build ID
+    pub split_debuginfo: Option<bool>,
+
+    /// Commands run around this target's builds, after the `[hooks]` ones
+    pub hooks: Option<HooksConfig>,
+
+    /// wasm-bindgen and wasm-opt steps for WebAssembly targets
+    pub wasm: Option<WasmConfig>,
+
+    /// Strategies a failed build falls back through with `build.fallback`,
+    /// e.g. `["zig", "container"]`
+    pub strategies: Option<Vec<String>>,
+
+    /// Time this target's builds may take, instead of `build.timeout`
+    pub timeout: Option<String>,
+}
+
+impl TargetCustomConfig {
+    /// Cargo arguments selecting this target's features
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::config::Config;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let config = Config::from_str(r#"
+    ///     [targets."wasm32-unknown-unknown"]
+    ///     features = ["web"]
+    ///     no-default-features = true
+    /// "#)?;
+    /// let target = config.get_target_config("wasm32-unknown-unknown").unwrap();
+    /// assert_eq!(target.feature_args(), vec!["--no-default-features", "--features", "web"]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn feature_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        if self.no_default_features == Some(true) {
+            args.push("--no-default-features".to_string());
+        }
+        if let Some(features) = self.features.as_ref().filter(|f| !f.is_empty()) {
+            args.push("--features".to_string());
+            args.push(features.join(","));
+        }
+
+        args
+    }
 }
 
 /// Build configuration section
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct BuildConfig {
     /// Enable parallel builds for multiple targets
     #[serde(default = "default_true")]
     pub parallel: bool,
 
     /// Number of parallel jobs (None = auto-detect)
+    ///
+    /// Parallel multi-target builds divide these jobs among the targets
+    /// building at the same time.
     pub jobs: Option<usize>,
 
+    /// In parallel builds, build the host target alone before the others
+    #[serde(default)]
+    pub host_first: bool,
+
     /// Enable build caching
     #[serde(default = "default_true")]
     pub cache: bool,
@@ -83,6 +256,70 @@ pub struct BuildConfig {
     /// Additional cargo flags
     #[serde(default)]
     pub cargo_flags: Vec<String>,
+
+    /// Rustc flags for every target, before the target's own `rustflags`
+    #[serde(default)]
+    pub rustflags: Vec<String>,
+
+    /// Retry a failed build with the next strategy of the target's chain
+    #[serde(default)]
+    pub fallback: bool,
+
+    /// Cargo's target directory, unless `CARGO_TARGET_DIR` is set
+    pub target_dir: Option<String>,
+
+    /// Build each target triple in its own directory under the target
+    /// directory, so parallel builds don't wait on each other's locks
+    #[serde(default)]
+    pub isolate_targets: bool,
+
+    /// Stop a multi-target build at the first failed target instead of
+    /// building the rest
+    #[serde(default)]
+    pub fail_fast: bool,
+
+    /// Kill a target's cargo or container after this long, e.g. "45m"
+    pub timeout: Option<String>,
+
+    /// How cargo's output is shown while targets build in parallel
+    #[serde(default)]
+    pub output: ParallelOutput,
+}
+
+/// How the output of targets building in parallel is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParallelOutput {
+    /// Each line as cargo prints it, behind a `[target]` tag
+    #[default]
+    Interleaved,
+    /// Each target's output in one block once its build finishes
+    Grouped,
+}
+
+impl ParallelOutput {
+    /// Name used in `xcargo.toml` and on the command line
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Interleaved => "interleaved",
+            Self::Grouped => "grouped",
+        }
+    }
+}
+
+impl FromStr for ParallelOutput {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "interleaved" => Ok(Self::Interleaved),
+            "grouped" => Ok(Self::Grouped),
+            _ => Err(format!(
+                "Unknown output mode '{s}' (expected interleaved or grouped)"
+            )),
+        }
+    }
 }
 
 /// Container runtime configuration
@@ -103,6 +340,436 @@ pub struct ContainerConfig {
     /// Image pull policy: always, never, if-not-present
     #[serde(default = "default_pull_policy")]
     pub pull_policy: String,
+
+    /// Run containers as the host user so build outputs are not owned by root
+    #[serde(default = "default_true")]
+    pub map_user: bool,
+
+    /// Git credentials forwarded into containers: none, ssh-agent
+    #[serde(default = "default_credentials")]
+    pub credentials: String,
+
+    /// Images to build targets with, keyed by target triple; they take
+    /// precedence over the built-in images
+    #[serde(default)]
+    pub images: HashMap<String, String>,
+
+    /// Extra volumes mounted into build containers, e.g. `./vendor:/vendor:ro`
+    #[serde(default)]
+    pub volumes: Vec<String>,
+
+    /// Where containers keep cargo's registry and git caches: volume, host, none
+    #[serde(default = "default_cargo_cache")]
+    pub cargo_cache: String,
+
+    /// Forward `CARGO_REGISTRY_TOKEN` and `CARGO_REGISTRIES_*` into containers
+    #[serde(default)]
+    pub forward_registry_tokens: bool,
+
+    /// Mount the host's netrc file into containers for HTTPS git and registries
+    #[serde(default)]
+    pub forward_netrc: bool,
+}
+
+/// Build policy configuration
+///
+/// Policies are enforced before a build starts; violations fail the build
+/// instead of being reported as warnings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct PolicyConfig {
+    /// Strategy that release builds must use: native, zig, rust-lld, or container
+    pub release_requires: Option<String>,
+}
+
+/// Rust toolchain configuration
+///
+/// Takes precedence over `rust-toolchain.toml` in the project.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct ToolchainConfig {
+    /// Toolchain channel to build with, e.g. "1.78.0" or "nightly-2024-05-01"
+    pub channel: Option<String>,
+}
+
+/// Zig configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ZigConfig {
+    /// Zig release to build with, e.g. "0.13.0"; installed into
+    /// `~/.xcargo/zig/<version>` when it isn't on PATH
+    pub version: Option<String>,
+}
+
+/// Proxy and CA bundle for everything xcargo downloads
+///
+/// Environment variables that are already set take precedence.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct NetworkConfig {
+    /// Proxy URL for HTTP and HTTPS, e.g. `http://proxy.corp.example:3128`
+    pub proxy: Option<String>,
+
+    /// Hosts reached without the proxy, e.g. "localhost,.corp.example"
+    pub no_proxy: Option<String>,
+
+    /// PEM file with the CA certificates to trust, including the one of a
+    /// proxy that intercepts TLS
+    pub ca_bundle: Option<PathBuf>,
+}
+
+/// Terminal output settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct OutputConfig {
+    /// Color theme; `XCARGO_THEME` takes precedence
+    pub theme: Option<Theme>,
+
+    /// Plain output without colors, icons, or spinners, as with `--plain`
+    pub plain: Option<bool>,
+
+    /// Language of messages; `XCARGO_LANG` takes precedence, and the system
+    /// locale is used if neither is set
+    pub language: Option<Locale>,
+}
+
+/// Retention of per-target build artifacts, applied by `xcargo prune-artifacts`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ArtifactsConfig {
+    /// Number of most recently built `target/<triple>/<profile>` directories to keep
+    pub keep_last_builds: Option<usize>,
+
+    /// Total size the kept directories may use, e.g. "20GiB"
+    pub max_total_size: Option<String>,
+}
+
+/// Provenance records written next to built artifacts, as with `--attest`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct AttestConfig {
+    /// Attest every build, not only those run with `--attest`
+    pub enabled: Option<bool>,
+
+    /// Sign provenance files with "minisign" or "cosign"
+    pub sign: Option<String>,
+
+    /// Signing key: a minisign secret key file, or a cosign key reference
+    /// (cosign signs keyless when unset)
+    pub key: Option<String>,
+}
+
+/// Code signing run after release builds of macOS and Windows targets
+///
+/// Secrets are never written to xcargo.toml: the identity, certificate and
+/// passwords are read from the environment variables named here.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct SigningConfig {
+    /// Targets to sign; every macOS and Windows target when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+
+    /// Signing of `*-apple-darwin` binaries with codesign
+    pub macos: Option<MacosSigningConfig>,
+
+    /// Signing of Windows executables and DLLs with signtool or osslsigncode
+    pub windows: Option<WindowsSigningConfig>,
+}
+
+/// codesign and notarytool settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct MacosSigningConfig {
+    /// Variable holding the signing identity, e.g. "Developer ID Application:
+    /// Acme (TEAMID)" (default `APPLE_SIGNING_IDENTITY`)
+    pub identity_env: Option<String>,
+
+    /// Entitlements plist signed into the binaries
+    pub entitlements: Option<String>,
+
+    /// Submit signed binaries to Apple's notary service
+    pub notarize: Option<bool>,
+
+    /// notarytool keychain profile, from `xcrun notarytool store-credentials`;
+    /// used instead of an Apple ID when set
+    pub notary_profile: Option<String>,
+
+    /// Variable holding the Apple ID to notarize with (default `APPLE_ID`)
+    pub apple_id_env: Option<String>,
+
+    /// Variable holding the team ID (default `APPLE_TEAM_ID`)
+    pub team_id_env: Option<String>,
+
+    /// Variable holding an app-specific password for the Apple ID
+    /// (default `APPLE_APP_PASSWORD`)
+    pub password_env: Option<String>,
+}
+
+/// Authenticode settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct WindowsSigningConfig {
+    /// Variable holding the path of a PKCS#12 (.pfx) certificate
+    /// (default `WINDOWS_CERTIFICATE`)
+    pub certificate_env: Option<String>,
+
+    /// Variable holding the certificate's password
+    /// (default `WINDOWS_CERTIFICATE_PASSWORD`)
+    pub password_env: Option<String>,
+
+    /// RFC 3161 timestamp server
+    pub timestamp_url: Option<String>,
+
+    /// "signtool" or "osslsigncode"; signtool on Windows, osslsigncode
+    /// elsewhere when unset
+    pub tool: Option<String>,
+}
+
+/// WebAssembly post-build steps
+///
+/// Unset switches are automatic: wasm-bindgen runs when the crate depends on
+/// `wasm-bindgen` and the CLI is installed, wasm-opt runs on release builds
+/// when it is installed.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct WasmConfig {
+    /// Generate JS bindings with wasm-bindgen
+    pub bindgen: Option<bool>,
+
+    /// wasm-bindgen output flavor, e.g. "web" or "nodejs" (default "bundler")
+    pub bindgen_target: Option<String>,
+
+    /// Directory for the generated package (default "pkg")
+    pub out_dir: Option<String>,
+
+    /// Optimize the module with wasm-opt
+    pub opt: Option<bool>,
+
+    /// Arguments for wasm-opt (default `["-O"]`)
+    pub opt_args: Option<Vec<String>>,
+}
+
+/// Commands run around builds, e.g. code generators or upload scripts
+///
+/// Each command runs through the shell with `XCARGO_TARGET`, `XCARGO_PROFILE`,
+/// `XCARGO_OPERATION`, and `XCARGO_HOOK` set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct HooksConfig {
+    /// Commands run before a target is built; a failing command aborts the build
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_build: Vec<String>,
+
+    /// Commands run after a target built successfully
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_build: Vec<String>,
+}
+
+/// Build matrix run by `xcargo matrix`: every combination of its targets,
+/// feature sets and profiles
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct MatrixConfig {
+    /// Targets or `@groups`; `targets.default` when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+
+    /// Feature sets, e.g. "default", "no-default,serde", or "all"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+
+    /// Profiles: "debug", "release"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub profiles: Vec<String>,
+}
+
+/// Settings for `xcargo deploy`
+///
+/// Tables named after a target, e.g. `[deploy."aarch64-unknown-linux-gnu"]`,
+/// hold settings for deploying that target, which take precedence over the
+/// ones directly under `[deploy]`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct DeployConfig {
+    /// SSH destination, e.g. "pi@raspberrypi.local"
+    pub host: Option<String>,
+
+    /// Target whose build is deployed; the only target with its own table,
+    /// or the first default target, when unset
+    pub target: Option<String>,
+
+    /// Directory on the host the binaries are copied into
+    pub path: Option<String>,
+
+    /// systemd service stopped before the copy and started after it
+    pub service: Option<String>,
+
+    /// Command run on the host after the copy, e.g. "sudo systemctl restart myapp"
+    pub restart: Option<String>,
+
+    /// How the binaries are copied
+    pub method: Option<DeployMethod>,
+
+    /// Settings for deploying one target
+    #[serde(default, flatten)]
+    pub targets: HashMap<String, DeployTargetConfig>,
+}
+
+/// Settings for deploying one target; unset ones come from `[deploy]`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct DeployTargetConfig {
+    /// SSH destination, e.g. "pi@raspberrypi.local"
+    pub host: Option<String>,
+
+    /// Directory on the host the binaries are copied into
+    pub path: Option<String>,
+
+    /// systemd service stopped before the copy and started after it
+    pub service: Option<String>,
+
+    /// Command run on the host after the copy
+    pub restart: Option<String>,
+
+    /// How the binaries are copied
+    pub method: Option<DeployMethod>,
+}
+
+/// How `xcargo deploy` copies binaries to the host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeployMethod {
+    /// `scp`, which comes with every OpenSSH client
+    #[default]
+    Scp,
+    /// `rsync` over SSH, which sends only what changed; it must also be
+    /// installed on the host
+    Rsync,
+}
+
+impl DeployMethod {
+    /// Name used in `xcargo.toml` and on the command line
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Scp => "scp",
+            Self::Rsync => "rsync",
+        }
+    }
+}
+
+impl FromStr for DeployMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "scp" => Ok(Self::Scp),
+            "rsync" => Ok(Self::Rsync),
+            _ => Err(format!(
+                "Unknown deploy method '{s}' (expected scp or rsync)"
+            )),
+        }
+    }
+}
+
+impl DeployConfig {
+    /// Settings for deploying `target`: those of its own table, and the
+    /// ones under `[deploy]` for the rest
+    ///
+    /// The table of a target written with a glibc version, or under the
+    /// other name of a renamed target, also applies.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::config::Config;
+    ///
+    /// let config = Config::from_str(r#"
+    /// [deploy]
+    /// host = "pi@raspberrypi.local"
+    ///
+    /// [deploy."armv7-unknown-linux-gnueabihf"]
+    /// host = "pi@pi2.local"
+    /// restart = "sudo systemctl restart sensor"
+    /// "#).unwrap();
+    ///
+    /// let armv7 = config.deploy.for_target("armv7-unknown-linux-gnueabihf");
+    /// assert_eq!(armv7.host.as_deref(), Some("pi@pi2.local"));
+    /// let aarch64 = config.deploy.for_target("aarch64-unknown-linux-gnu");
+    /// assert_eq!(aarch64.host.as_deref(), Some("pi@raspberrypi.local"));
+    /// assert_eq!(aarch64.restart, None);
+    /// ```
+    #[must_use]
+    pub fn for_target(&self, target: &str) -> DeployTargetConfig {
+        let base = crate::target::Target::from_triple(target).map(|t| t.triple);
+        let own = self
+            .targets
+            .get(target)
+            .or_else(|| base.as_ref().ok().and_then(|base| self.targets.get(base)))
+            .or_else(|| {
+                crate::target::renamed_counterpart(target).and_then(|other| self.targets.get(other))
+            })
+            .cloned()
+            .unwrap_or_default();
+        DeployTargetConfig {
+            host: own.host.or_else(|| self.host.clone()),
+            path: own.path.or_else(|| self.path.clone()),
+            service: own.service.or_else(|| self.service.clone()),
+            restart: own.restart.or_else(|| self.restart.clone()),
+            method: own.method.or(self.method),
+        }
+    }
+
+    /// Target deployed when none is given: `target`, or the only target
+    /// with its own table
+    #[must_use]
+    pub fn default_target(&self) -> Option<&str> {
+        match (&self.target, self.targets.len()) {
+            (Some(target), _) => Some(target),
+            (None, 1) => self.targets.keys().next().map(String::as_str),
+            _ => None,
+        }
+    }
+}
+
+/// Packages made from built binaries
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PackageConfig {
+    /// Container images built by `xcargo package docker`
+    #[serde(default)]
+    pub docker: DockerPackageConfig,
+}
+
+/// Container image holding a target's binaries
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct DockerPackageConfig {
+    /// Image repository, e.g. "ghcr.io/acme/gateway"; the package name when unset
+    pub repository: Option<String>,
+
+    /// Tags of the image; the package version and "latest" when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// Base image: "distroless", "scratch", or an image reference
+    pub base: Option<String>,
+
+    /// Binary the image runs; the only binary, or the one named after the
+    /// package, when unset
+    pub bin: Option<String>,
+}
+
+/// Timeouts and retries for running a target's test binaries
+///
+/// Useful for emulated runs (e.g. under QEMU) that occasionally hang or flake.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(deny_unknown_fields)]
+pub struct RunnerConfig {
+    /// Time each test binary may run, e.g. "10m" or "90s"
+    pub test_timeout: Option<String>,
+
+    /// How many times a failed or timed-out test binary is run again
+    #[serde(default)]
+    pub retries: u32,
 }
 
 /// Profile configuration for different build scenarios
@@ -113,7 +780,73 @@ pub struct ProfileConfig {
 
     /// Build configuration overrides
     #[serde(flatten)]
-    pub build: Option<BuildConfig>,
+    pub build: BuildOverrides,
+}
+
+/// Build settings a profile can override; unset fields keep the `[build]` value
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct BuildOverrides {
+    /// Enable parallel builds for multiple targets
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel: Option<bool>,
+
+    /// Number of parallel jobs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<usize>,
+
+    /// Build the host target before the others
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_first: Option<bool>,
+
+    /// Enable build caching
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<bool>,
+
+    /// Force container builds even when native is possible
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub force_container: Option<bool>,
+
+    /// Additional cargo flags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cargo_flags: Option<Vec<String>>,
+
+    /// Rustc flags for every target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rustflags: Option<Vec<String>>,
+
+    /// Stop at the first failed target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fail_fast: Option<bool>,
+}
+
+impl BuildConfig {
+    /// Apply profile overrides on top of this configuration
+    fn apply(&mut self, overrides: &BuildOverrides) {
+        if let Some(parallel) = overrides.parallel {
+            self.parallel = parallel;
+        }
+        if overrides.jobs.is_some() {
+            self.jobs = overrides.jobs;
+        }
+        if let Some(host_first) = overrides.host_first {
+            self.host_first = host_first;
+        }
+        if let Some(cache) = overrides.cache {
+            self.cache = cache;
+        }
+        if let Some(force_container) = overrides.force_container {
+            self.force_container = force_container;
+        }
+        if let Some(cargo_flags) = &overrides.cargo_flags {
+            self.cargo_flags.clone_from(cargo_flags);
+        }
+        if let Some(rustflags) = &overrides.rustflags {
+            self.rustflags.clone_from(rustflags);
+        }
+        if let Some(fail_fast) = overrides.fail_fast {
+            self.fail_fast = fail_fast;
+        }
+    }
 }
 
 impl Default for BuildConfig {
@@ -121,9 +854,17 @@ impl Default for BuildConfig {
         Self {
             parallel: true,
             jobs: None, // Auto-detect
+            host_first: false,
             cache: true,
             force_container: false,
             cargo_flags: Vec::new(),
+            rustflags: Vec::new(),
+            fallback: false,
+            target_dir: None,
+            isolate_targets: false,
+            fail_fast: false,
+            timeout: None,
+            output: ParallelOutput::default(),
         }
     }
 }
@@ -135,6 +876,13 @@ impl Default for ContainerConfig {
             use_when: default_use_when(),
             registry: None,
             pull_policy: default_pull_policy(),
+            map_user: true,
+            credentials: default_credentials(),
+            images: HashMap::new(),
+            volumes: Vec::new(),
+            cargo_cache: default_cargo_cache(),
+            forward_registry_tokens: false,
+            forward_netrc: false,
         }
     }
 }
@@ -156,129 +904,530 @@ fn default_pull_policy() -> String {
     "if-not-present".to_string()
 }
 
+fn default_credentials() -> String {
+    "none".to_string()
+}
+
+fn default_cargo_cache() -> String {
+    "volume".to_string()
+}
+
 impl Config {
     /// Load configuration from a TOML file
     ///
     /// # Examples
     ///
-    /// ```no_run
+    /// ```no_run
+    /// use xcargo::config::Config;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let config = Config::from_file("xcargo.toml")?;
+    /// println!("Default targets: {:?}", config.targets.default);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| Error::Config(format!("Failed to read config file: {e}")))?;
+
+        toml::from_str(&contents).map_err(|e| validate::parse_error(path.as_ref(), &contents, &e))
+    }
+
+    /// Parse configuration from a TOML string
+    pub fn from_str(toml: &str) -> Result<Self> {
+        toml::from_str(toml).map_err(|e| Error::Config(format!("Failed to parse TOML: {e}")))
+    }
+
+    /// Load the effective configuration for the current directory
+    ///
+    /// Combines the built-in defaults, the user configuration,
+    /// `[package.metadata.xcargo]` in Cargo.toml, the project's xcargo.toml,
+    /// `XCARGO_*` environment variables, and `--config` overrides; see
+    /// [`LayeredConfig`]. Target groups in `targets.default` are expanded.
+    pub fn load() -> Result<Self> {
+        let mut config = LayeredConfig::discover()?.config;
+        config.targets.default = config.expand_targets(&config.targets.default)?;
+        Ok(config)
+    }
+
+    /// Discover and load configuration from the current directory
+    ///
+    /// Searches for xcargo.toml in current directory and parent directories.
+    /// Only the project file is read; use [`Config::load`] for the effective
+    /// configuration.
+    pub fn discover() -> Result<Option<(Self, PathBuf)>> {
+        if let Some(path) = ConfigDiscovery::find()? {
+            let config = Self::from_file(&path)?;
+            Ok(Some((config, path)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Get the default configuration
+    #[must_use]
+    pub fn default_config() -> Self {
+        Self::default()
+    }
+
+    /// Merge this configuration with another, with other taking precedence
+    pub fn merge(&mut self, other: &Config) {
+        if other.required_version.is_some() {
+            self.required_version = other.required_version.clone();
+        }
+
+        // Merge targets
+        if !other.targets.default.is_empty() {
+            self.targets.default = other.targets.default.clone();
+        }
+        for (key, value) in &other.targets.custom {
+            self.targets.custom.insert(key.clone(), value.clone());
+        }
+
+        // Merge build config (other overrides self)
+        self.build.parallel = other.build.parallel;
+        if other.build.jobs.is_some() {
+            self.build.jobs = other.build.jobs;
+        }
+        self.build.host_first = other.build.host_first;
+        self.build.cache = other.build.cache;
+        self.build.force_container = other.build.force_container;
+        self.build.fallback = other.build.fallback;
+        if other.build.target_dir.is_some() {
+            self.build.target_dir = other.build.target_dir.clone();
+        }
+        self.build.isolate_targets = other.build.isolate_targets;
+        self.build.fail_fast = other.build.fail_fast;
+        if other.build.timeout.is_some() {
+            self.build.timeout = other.build.timeout.clone();
+        }
+        self.build.output = other.build.output;
+        if !other.build.cargo_flags.is_empty() {
+            self.build.cargo_flags = other.build.cargo_flags.clone();
+        }
+        if !other.build.rustflags.is_empty() {
+            self.build.rustflags = other.build.rustflags.clone();
+        }
+
+        // Merge container config
+        self.container.runtime = other.container.runtime.clone();
+        self.container.use_when = other.container.use_when.clone();
+        if other.container.registry.is_some() {
+            self.container.registry = other.container.registry.clone();
+        }
+        self.container.pull_policy = other.container.pull_policy.clone();
+        self.container.map_user = other.container.map_user;
+        self.container.credentials = other.container.credentials.clone();
+        for (key, value) in &other.container.images {
+            self.container.images.insert(key.clone(), value.clone());
+        }
+        if !other.container.volumes.is_empty() {
+            self.container.volumes = other.container.volumes.clone();
+        }
+        self.container.cargo_cache = other.container.cargo_cache.clone();
+        self.container.forward_registry_tokens = other.container.forward_registry_tokens;
+        self.container.forward_netrc = other.container.forward_netrc;
+
+        // Merge profiles
+        for (key, value) in &other.profiles {
+            self.profiles.insert(key.clone(), value.clone());
+        }
+
+        // Merge target groups
+        for (key, value) in &other.target_groups {
+            self.target_groups.insert(key.clone(), value.clone());
+        }
+
+        // Merge policy
+        if other.policy.release_requires.is_some() {
+            self.policy.release_requires = other.policy.release_requires.clone();
+        }
+
+        // Merge toolchain pin
+        if other.toolchain.channel.is_some() {
+            self.toolchain.channel = other.toolchain.channel.clone();
+        }
+
+        // Merge Zig pin
+        if other.zig.version.is_some() {
+            self.zig.version = other.zig.version.clone();
+        }
+
+        // Merge network settings
+        if other.network.proxy.is_some() {
+            self.network.proxy = other.network.proxy.clone();
+        }
+        if other.network.no_proxy.is_some() {
+            self.network.no_proxy = other.network.no_proxy.clone();
+        }
+        if other.network.ca_bundle.is_some() {
+            self.network.ca_bundle = other.network.ca_bundle.clone();
+        }
+
+        // Merge runner settings
+        for (key, value) in &other.runners {
+            self.runners.insert(key.clone(), value.clone());
+        }
+
+        // Merge output settings
+        if other.output.theme.is_some() {
+            self.output.theme = other.output.theme;
+        }
+        if other.output.plain.is_some() {
+            self.output.plain = other.output.plain;
+        }
+        if other.output.language.is_some() {
+            self.output.language = other.output.language;
+        }
+
+        // Merge artifact retention
+        if other.artifacts.keep_last_builds.is_some() {
+            self.artifacts.keep_last_builds = other.artifacts.keep_last_builds;
+        }
+        if other.artifacts.max_total_size.is_some() {
+            self.artifacts.max_total_size = other.artifacts.max_total_size.clone();
+        }
+
+        // Merge attestation settings
+        if other.attest.enabled.is_some() {
+            self.attest.enabled = other.attest.enabled;
+        }
+        if other.attest.sign.is_some() {
+            self.attest.sign = other.attest.sign.clone();
+        }
+        if other.attest.key.is_some() {
+            self.attest.key = other.attest.key.clone();
+        }
+
+        // Merge hooks
+        if !other.hooks.pre_build.is_empty() {
+            self.hooks.pre_build = other.hooks.pre_build.clone();
+        }
+        if !other.hooks.post_build.is_empty() {
+            self.hooks.post_build = other.hooks.post_build.clone();
+        }
+
+        // Merge the build matrix
+        if !other.matrix.targets.is_empty() {
+            self.matrix.targets = other.matrix.targets.clone();
+        }
+        if !other.matrix.features.is_empty() {
+            self.matrix.features = other.matrix.features.clone();
+        }
+        if !other.matrix.profiles.is_empty() {
+            self.matrix.profiles = other.matrix.profiles.clone();
+        }
+
+        // Merge deploy settings; a target's table replaces the one it overrides
+        if other.deploy.host.is_some() {
+            self.deploy.host = other.deploy.host.clone();
+        }
+        if other.deploy.target.is_some() {
+            self.deploy.target = other.deploy.target.clone();
+        }
+        if other.deploy.path.is_some() {
+            self.deploy.path = other.deploy.path.clone();
+        }
+        if other.deploy.service.is_some() {
+            self.deploy.service = other.deploy.service.clone();
+        }
+        if other.deploy.restart.is_some() {
+            self.deploy.restart = other.deploy.restart.clone();
+        }
+        if other.deploy.method.is_some() {
+            self.deploy.method = other.deploy.method;
+        }
+        for (key, value) in &other.deploy.targets {
+            self.deploy.targets.insert(key.clone(), value.clone());
+        }
+
+        // Merge container image packaging
+        let docker = &other.package.docker;
+        if docker.repository.is_some() {
+            self.package.docker.repository = docker.repository.clone();
+        }
+        if !docker.tags.is_empty() {
+            self.package.docker.tags = docker.tags.clone();
+        }
+        if docker.base.is_some() {
+            self.package.docker.base = docker.base.clone();
+        }
+        if docker.bin.is_some() {
+            self.package.docker.bin = docker.bin.clone();
+        }
+
+        // Merge signing settings; a platform's table replaces the one it overrides
+        if !other.signing.targets.is_empty() {
+            self.signing.targets = other.signing.targets.clone();
+        }
+        if other.signing.macos.is_some() {
+            self.signing.macos = other.signing.macos.clone();
+        }
+        if other.signing.windows.is_some() {
+            self.signing.windows = other.signing.windows.clone();
+        }
+    }
+
+    /// Get configuration for a specific target
+    ///
+    /// Configuration written under the other name of a renamed target
+    /// (e.g. `wasm32-wasi` for `wasm32-wasip1`) also applies.
+    #[must_use]
+    pub fn get_target_config(&self, target: &str) -> Option<&TargetCustomConfig> {
+        self.targets.custom.get(target).or_else(|| {
+            crate::target::renamed_counterpart(target)
+                .and_then(|other| self.targets.custom.get(other))
+        })
+    }
+
+    /// Get the test runner settings for a specific target
+    ///
+    /// Like [`get_target_config`](Self::get_target_config), settings under the
+    /// other name of a renamed target also apply.
+    #[must_use]
+    pub fn get_runner_config(&self, target: &str) -> Option<&RunnerConfig> {
+        self.runners.get(target).or_else(|| {
+            crate::target::renamed_counterpart(target).and_then(|other| self.runners.get(other))
+        })
+    }
+
+    /// Time a build of `target` may take before it is killed: the target's
+    /// `timeout`, or `build.timeout`
+    ///
+    /// # Errors
+    /// Returns an error if the timeout is not a valid duration
+    pub fn build_timeout(&self, target: &str) -> Result<Option<std::time::Duration>> {
+        self.get_target_config(target)
+            .and_then(|config| config.timeout.as_deref())
+            .or(self.build.timeout.as_deref())
+            .map(crate::build::parse_duration)
+            .transpose()
+    }
+
+    /// Get a profile by name
+    #[must_use]
+    pub fn get_profile(&self, name: &str) -> Option<&ProfileConfig> {
+        self.profiles.get(name)
+    }
+
+    /// Apply a named profile
+    ///
+    /// The profile's targets replace the default targets, and its build
+    /// settings override the `[build]` section.
+    ///
+    /// # Errors
+    /// Returns `Error::ProfileNotFound` with similarly named profiles if the
+    /// profile does not exist
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::config::Config;
+    ///
+    /// let mut config = Config::from_str(r#"
+    ///     [profiles.ci]
+    ///     targets = ["x86_64-unknown-linux-gnu"]
+    ///     parallel = false
+    /// "#).unwrap();
+    ///
+    /// config.apply_profile("ci").unwrap();
+    /// assert_eq!(config.targets.default, vec!["x86_64-unknown-linux-gnu"]);
+    /// assert!(!config.build.parallel);
+    /// assert!(config.apply_profile("cj").is_err());
+    /// ```
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let profile = self
+            .get_profile(name)
+            .cloned()
+            .ok_or_else(|| Error::ProfileNotFound {
+                profile: name.to_string(),
+                suggestions: self.similar_profiles(name),
+            })?;
+
+        if !profile.targets.is_empty() {
+            self.targets.default = self.expand_targets(&profile.targets)?;
+        }
+        self.build.apply(&profile.build);
+
+        Ok(())
+    }
+
+    /// Replace `@group` entries with the targets of the group
+    ///
+    /// Groups can name other groups. Targets are kept in order, and a target
+    /// listed twice is built once.
+    ///
+    /// # Errors
+    /// Returns an error if a group is not defined or includes itself
+    ///
+    /// # Examples
+    ///
+    /// ```
     /// use xcargo::config::Config;
     ///
-    /// # fn example() -> xcargo::Result<()> {
-    /// let config = Config::from_file("xcargo.toml")?;
-    /// println!("Default targets: {:?}", config.targets.default);
-    /// # Ok(())
-    /// # }
+    /// let config = Config::from_str(r#"
+    ///     [target-groups]
+    ///     linux = ["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gnu"]
+    ///     desktop = ["@linux", "x86_64-pc-windows-gnu"]
+    /// "#).unwrap();
+    ///
+    /// let targets = config.expand_targets(&["@desktop".to_string()]).unwrap();
+    /// assert_eq!(targets.len(), 3);
+    /// assert!(config.expand_targets(&["@mobile".to_string()]).is_err());
     /// ```
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let contents = std::fs::read_to_string(path.as_ref())
-            .map_err(|e| Error::Config(format!("Failed to read config file: {e}")))?;
-
-        Self::from_str(&contents)
+    pub fn expand_targets(&self, targets: &[String]) -> Result<Vec<String>> {
+        let mut expanded = Vec::new();
+        self.expand_into(targets, &mut Vec::new(), &mut expanded)?;
+        Ok(expanded)
     }
 
-    /// Parse configuration from a TOML string
-    pub fn from_str(toml: &str) -> Result<Self> {
-        toml::from_str(toml).map_err(|e| Error::Config(format!("Failed to parse TOML: {e}")))
-    }
+    /// Expand `targets` into `expanded`; `within` holds the groups being
+    /// expanded, to catch a group that includes itself
+    fn expand_into<'a>(
+        &'a self,
+        targets: &'a [String],
+        within: &mut Vec<&'a str>,
+        expanded: &mut Vec<String>,
+    ) -> Result<()> {
+        for target in targets {
+            let Some(name) = target.strip_prefix(TARGET_GROUP_PREFIX) else {
+                if !expanded.contains(target) {
+                    expanded.push(target.clone());
+                }
+                continue;
+            };
+
+            let Some((name, members)) = self.target_groups.get_key_value(name) else {
+                let mut known: Vec<String> = self
+                    .target_groups
+                    .keys()
+                    .map(|group| format!("{TARGET_GROUP_PREFIX}{group}"))
+                    .collect();
+                known.sort();
+                return Err(Error::Config(if known.is_empty() {
+                    format!("Unknown target group {target}: xcargo.toml has no [target-groups]")
+                } else {
+                    format!(
+                        "Unknown target group {target}. Defined groups: {}",
+                        known.join(", ")
+                    )
+                }));
+            };
+            if within.contains(&name.as_str()) {
+                return Err(Error::Config(format!(
+                    "Target group {target} includes itself"
+                )));
+            }
 
-    /// Discover and load configuration from the current directory
-    ///
-    /// Searches for xcargo.toml in current directory and parent directories
-    pub fn discover() -> Result<Option<(Self, PathBuf)>> {
-        if let Some(path) = ConfigDiscovery::find()? {
-            let config = Self::from_file(&path)?;
-            Ok(Some((config, path)))
-        } else {
-            Ok(None)
+            within.push(name);
+            self.expand_into(members, within, expanded)?;
+            within.pop();
         }
+        Ok(())
     }
 
-    /// Get the default configuration
-    #[must_use]
-    pub fn default_config() -> Self {
-        Self::default()
-    }
-
-    /// Merge this configuration with another, with other taking precedence
-    pub fn merge(&mut self, other: &Config) {
-        // Merge targets
-        if !other.targets.default.is_empty() {
-            self.targets.default = other.targets.default.clone();
+    /// Every target triple the configuration names, with the key naming it
+    ///
+    /// Group references (`@name`) are left out; the groups' members are
+    /// listed under `target-groups`.
+    fn target_references(&self) -> Vec<(Vec<String>, &str)> {
+        let mut lists: Vec<(Vec<&str>, &[String])> =
+            vec![(vec!["targets", "default"], &self.targets.default)];
+        for (name, profile) in &self.profiles {
+            lists.push((vec!["profiles", name, "targets"], &profile.targets));
         }
-        for (key, value) in &other.targets.custom {
-            self.targets.custom.insert(key.clone(), value.clone());
+        for (name, members) in &self.target_groups {
+            lists.push((vec!["target-groups", name], members));
         }
-
-        // Merge build config (other overrides self)
-        self.build.parallel = other.build.parallel;
-        if other.build.jobs.is_some() {
-            self.build.jobs = other.build.jobs;
+        lists.push((vec!["matrix", "targets"], &self.matrix.targets));
+        lists.push((vec!["signing", "targets"], &self.signing.targets));
+
+        let key = |path: &[&str]| path.iter().map(ToString::to_string).collect::<Vec<_>>();
+        let mut references = Vec::new();
+        for (path, targets) in lists {
+            for (idx, triple) in targets.iter().enumerate() {
+                if triple.starts_with(TARGET_GROUP_PREFIX) {
+                    continue;
+                }
+                let mut path = key(&path);
+                path.push(idx.to_string());
+                references.push((path, triple.as_str()));
+            }
         }
-        self.build.cache = other.build.cache;
-        self.build.force_container = other.build.force_container;
-        if !other.build.cargo_flags.is_empty() {
-            self.build.cargo_flags = other.build.cargo_flags.clone();
+        for triple in self.targets.custom.keys() {
+            references.push((key(&["targets", triple]), triple.as_str()));
         }
-
-        // Merge container config
-        self.container.runtime = other.container.runtime.clone();
-        self.container.use_when = other.container.use_when.clone();
-        if other.container.registry.is_some() {
-            self.container.registry = other.container.registry.clone();
+        if let Some(triple) = &self.deploy.target {
+            references.push((key(&["deploy", "target"]), triple.as_str()));
         }
-        self.container.pull_policy = other.container.pull_policy.clone();
-
-        // Merge profiles
-        for (key, value) in &other.profiles {
-            self.profiles.insert(key.clone(), value.clone());
+        for triple in self.deploy.targets.keys() {
+            references.push((key(&["deploy", triple]), triple.as_str()));
+        }
+        for triple in self.container.images.keys() {
+            references.push((key(&["container", "images", triple]), triple.as_str()));
         }
+        references
     }
 
-    /// Get configuration for a specific target
+    /// Targets the configuration names anywhere: default targets, profiles,
+    /// target groups, the matrix, per-target tables, deploys and signing,
+    /// sorted and without duplicates
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::config::Config;
+    ///
+    /// let config = Config::from_str(r#"
+    ///     [targets]
+    ///     default = ["x86_64-unknown-linux-gnu", "@arm"]
+    ///
+    ///     [target-groups]
+    ///     arm = ["aarch64-unknown-linux-gnu", "x86_64-unknown-linux-gnu"]
+    /// "#).unwrap();
+    ///
+    /// assert_eq!(
+    ///     config.referenced_targets(),
+    ///     ["aarch64-unknown-linux-gnu", "x86_64-unknown-linux-gnu"]
+    /// );
+    /// ```
     #[must_use]
-    pub fn get_target_config(&self, target: &str) -> Option<&TargetCustomConfig> {
-        self.targets.custom.get(target)
+    pub fn referenced_targets(&self) -> Vec<String> {
+        let mut targets: Vec<String> = self
+            .target_references()
+            .into_iter()
+            .map(|(_, triple)| triple.to_string())
+            .collect();
+        targets.sort();
+        targets.dedup();
+        targets
     }
 
-    /// Get a profile by name
-    #[must_use]
-    pub fn get_profile(&self, name: &str) -> Option<&ProfileConfig> {
-        self.profiles.get(name)
+    /// Profile names close to `name`, for "did you mean" suggestions
+    fn similar_profiles(&self, name: &str) -> Vec<String> {
+        let mut similar: Vec<String> = self
+            .profiles
+            .keys()
+            .filter(|candidate| {
+                candidate.contains(name)
+                    || name.contains(candidate.as_str())
+                    || edit_distance(candidate, name) <= 2
+            })
+            .cloned()
+            .collect();
+        similar.sort();
+        similar
     }
 
     /// Validate the configuration
+    ///
+    /// # Errors
+    /// Returns the first of [`Config::problems`]
     pub fn validate(&self) -> Result<()> {
-        // Validate runtime
-        let valid_runtimes = ["auto", "youki", "docker", "podman"];
-        if !valid_runtimes.contains(&self.container.runtime.as_str()) {
-            return Err(Error::Config(format!(
-                "Invalid container runtime: {}. Must be one of: {}",
-                self.container.runtime,
-                valid_runtimes.join(", ")
-            )));
-        }
-
-        // Validate pull policy
-        let valid_policies = ["always", "never", "if-not-present"];
-        if !valid_policies.contains(&self.container.pull_policy.as_str()) {
-            return Err(Error::Config(format!(
-                "Invalid pull policy: {}. Must be one of: {}",
-                self.container.pull_policy,
-                valid_policies.join(", ")
-            )));
-        }
-
-        // Validate jobs count
-        if let Some(jobs) = self.build.jobs {
-            if jobs == 0 {
-                return Err(Error::Config(
-                    "build.jobs must be greater than 0".to_string(),
-                ));
-            }
+        match self.problems().into_iter().next() {
+            Some(problem) => Err(Error::Config(problem.message)),
+            None => Ok(()),
         }
-
-        Ok(())
     }
 
     /// Convert configuration to TOML string
@@ -288,12 +1437,34 @@ impl Config {
     }
 
     /// Save configuration to a file
+    ///
+    /// The file is replaced atomically and its previous version kept as
+    /// `<file>.bak`; see [`write_config_file`]
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let toml = self.to_toml()?;
-        std::fs::write(path.as_ref(), toml)
-            .map_err(|e| Error::Config(format!("Failed to write config file: {e}")))?;
-        Ok(())
+        write_config_file(path.as_ref(), &self.to_toml()?)
+    }
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
     }
+
+    row[b.len()]
 }
 
 #[cfg(test)]
@@ -330,9 +1501,12 @@ mod tests {
             [build]
             parallel = true
             jobs = 4
+            host_first = true
             cache = true
             force_container = false
             cargo_flags = ["--verbose"]
+            fail_fast = true
+            output = "grouped"
 
             [container]
             runtime = "docker"
@@ -340,6 +1514,9 @@ mod tests {
             registry = "ghcr.io/xcargo"
             pull_policy = "if-not-present"
 
+            [container.images]
+            "aarch64-unknown-linux-gnu" = "ghcr.io/myorg/cross-aarch64:latest"
+
             [profiles.release-all]
             targets = ["x86_64-unknown-linux-gnu", "x86_64-pc-windows-gnu"]
         "#;
@@ -347,10 +1524,138 @@ mod tests {
         let config = Config::from_str(toml).unwrap();
         assert_eq!(config.targets.default.len(), 2);
         assert_eq!(config.build.jobs, Some(4));
+        assert!(config.build.host_first);
+        assert!(config.build.fail_fast);
+        assert_eq!(config.build.output, ParallelOutput::Grouped);
         assert_eq!(config.container.runtime, "docker");
+        assert_eq!(
+            config.container.images["aarch64-unknown-linux-gnu"],
+            "ghcr.io/myorg/cross-aarch64:latest"
+        );
         assert!(config.profiles.contains_key("release-all"));
     }
 
+    #[test]
+    fn test_renamed_target_config() {
+        let toml = r#"
+            [targets."wasm32-wasi"]
+            runner = "wasmtime"
+        "#;
+
+        let config = Config::from_str(toml).unwrap();
+        let target_config = config.get_target_config("wasm32-wasip1").unwrap();
+        assert_eq!(target_config.runner, Some("wasmtime".to_string()));
+    }
+
+    #[test]
+    fn test_runner_config() {
+        let toml = r#"
+            [runners."aarch64-unknown-linux-gnu"]
+            test_timeout = "10m"
+            retries = 1
+        "#;
+
+        let config = Config::from_str(toml).unwrap();
+        let runner = config
+            .get_runner_config("aarch64-unknown-linux-gnu")
+            .unwrap();
+        assert_eq!(runner.test_timeout.as_deref(), Some("10m"));
+        assert_eq!(runner.retries, 1);
+        assert!(config.validate().is_ok());
+
+        let invalid = Config::from_str(
+            r#"
+            [runners."aarch64-unknown-linux-gnu"]
+            test_timeout = "ten minutes"
+        "#,
+        )
+        .unwrap();
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_build_timeout() {
+        let config = Config::from_str(
+            r#"
+            [build]
+            timeout = "45m"
+
+            [targets."aarch64-unknown-linux-gnu"]
+            timeout = "2h"
+        "#,
+        )
+        .unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.build_timeout("aarch64-unknown-linux-gnu").unwrap(),
+            Some(std::time::Duration::from_secs(7200))
+        );
+        assert_eq!(
+            config.build_timeout("x86_64-pc-windows-gnu").unwrap(),
+            Some(std::time::Duration::from_secs(2700))
+        );
+        assert_eq!(
+            Config::default().build_timeout("wasm32-wasip1").unwrap(),
+            None
+        );
+
+        let invalid = Config::from_str(
+            r#"
+            [targets."aarch64-unknown-linux-gnu"]
+            timeout = "forever"
+        "#,
+        )
+        .unwrap();
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_output_theme() {
+        let config = Config::from_str(
+            r#"
+            [output]
+            theme = "colorblind-safe"
+            plain = true
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.output.theme, Some(Theme::ColorblindSafe));
+        assert_eq!(config.output.plain, Some(true));
+        assert_eq!(Config::default().output.theme, None);
+
+        assert!(Config::from_str("[output]\ntheme = \"neon\"").is_err());
+    }
+
+    #[test]
+    fn test_output_language() {
+        let config = Config::from_str("[output]\nlanguage = \"pt-BR\"").unwrap();
+        assert_eq!(config.output.language, Some(Locale::PtBr));
+        assert!(Config::from_str("[output]\nlanguage = \"klingon\"").is_err());
+    }
+
+    #[test]
+    fn test_artifacts_retention() {
+        let mut config = Config::from_str(
+            r#"
+            [artifacts]
+            keep_last_builds = 6
+            max_total_size = "20GiB"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(config.artifacts.keep_last_builds, Some(6));
+        assert_eq!(config.artifacts.max_total_size.as_deref(), Some("20GiB"));
+        assert!(config.validate().is_ok());
+
+        config.artifacts.max_total_size = Some("huge".to_string());
+        assert!(config.validate().is_err());
+        config.artifacts.max_total_size = None;
+        config.artifacts.keep_last_builds = Some(0);
+        assert!(config.validate().is_err());
+
+        assert!(Config::from_str("[artifacts]\nkeep_last = 3").is_err());
+    }
+
     #[test]
     fn test_custom_target_config() {
         let toml = r#"
@@ -378,6 +1683,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_target_feature_matrix() {
+        let toml = r#"
+            [targets."wasm32-unknown-unknown"]
+            features = ["rt", "macros"]
+            no-default-features = true
+
+            [targets."x86_64-unknown-linux-gnu"]
+            features = ["full"]
+        "#;
+
+        let config = Config::from_str(toml).unwrap();
+        let wasm = config.get_target_config("wasm32-unknown-unknown").unwrap();
+        assert_eq!(
+            wasm.feature_args(),
+            vec!["--no-default-features", "--features", "rt,macros"]
+        );
+
+        let server = config
+            .get_target_config("x86_64-unknown-linux-gnu")
+            .unwrap();
+        assert_eq!(server.feature_args(), vec!["--features", "full"]);
+    }
+
     #[test]
     fn test_config_validation() {
         let mut config = Config::default();
@@ -392,12 +1721,66 @@ mod tests {
         config.container.pull_policy = "invalid".to_string();
         assert!(config.validate().is_err());
 
-        // Fix pull policy, test invalid jobs
+        // Fix pull policy, test invalid credentials policy
         config.container.pull_policy = "always".to_string();
+        config.container.credentials = "everything".to_string();
+        assert!(config.validate().is_err());
+
+        // Fix credentials policy, test invalid jobs
+        config.container.credentials = "ssh-agent".to_string();
+        config.container.cargo_cache = "shared".to_string();
+        assert!(config.validate().is_err());
+
+        config.container.cargo_cache = "host".to_string();
+        config.container.volumes = vec!["./vendor".to_string()];
+        assert!(config.validate().is_err());
+
+        config.container.volumes = vec!["./vendor:/vendor:ro".to_string()];
         config.build.jobs = Some(0);
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_policy_config() {
+        let toml = r#"
+            [policy]
+            release_requires = "container"
+        "#;
+
+        let mut config = Config::from_str(toml).unwrap();
+        assert_eq!(
+            config.policy.release_requires,
+            Some("container".to_string())
+        );
+        assert!(config.validate().is_ok());
+
+        config.policy.release_requires = Some("docker".to_string());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_fallback_strategies_config() {
+        let toml = r#"
+            [build]
+            fallback = true
+
+            [targets."aarch64-unknown-linux-gnu"]
+            strategies = ["zig", "container"]
+        "#;
+
+        let mut config = Config::from_str(toml).unwrap();
+        assert!(config.build.fallback);
+        assert!(config.validate().is_ok());
+
+        config
+            .targets
+            .custom
+            .get_mut("aarch64-unknown-linux-gnu")
+            .unwrap()
+            .strategies = Some(vec!["qemu".to_string()]);
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_config_merge() {
         let mut base = Config::default();
@@ -425,4 +1808,251 @@ mod tests {
         assert!(toml.contains("[build]"));
         assert!(toml.contains("[container]"));
     }
+
+    #[test]
+    fn test_apply_profile() {
+        let toml = r#"
+            [targets]
+            default = ["x86_64-unknown-linux-gnu"]
+
+            [build]
+            parallel = false
+            cargo_flags = ["--locked"]
+
+            [profiles.release-all]
+            targets = ["x86_64-pc-windows-gnu", "aarch64-apple-darwin"]
+            jobs = 2
+            rustflags = ["-C", "strip=symbols"]
+        "#;
+
+        let mut config = Config::from_str(toml).unwrap();
+        config.apply_profile("release-all").unwrap();
+        assert_eq!(
+            config.targets.default,
+            vec!["x86_64-pc-windows-gnu", "aarch64-apple-darwin"]
+        );
+        assert_eq!(config.build.jobs, Some(2));
+        assert!(!config.build.parallel);
+        assert_eq!(config.build.cargo_flags, vec!["--locked"]);
+        assert_eq!(config.build.rustflags, vec!["-C", "strip=symbols"]);
+
+        match config.apply_profile("release") {
+            Err(Error::ProfileNotFound { suggestions, .. }) => {
+                assert_eq!(suggestions, vec!["release-all"]);
+            }
+            other => panic!("expected ProfileNotFound, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_target_groups() {
+        let toml = r#"
+            [targets]
+            default = ["@smoke"]
+
+            [target-groups]
+            smoke = ["x86_64-unknown-linux-gnu"]
+            desktop = ["@smoke", "x86_64-pc-windows-gnu", "x86_64-apple-darwin"]
+
+            [profiles.release]
+            targets = ["@desktop", "aarch64-apple-darwin"]
+        "#;
+
+        let mut config = Config::from_str(toml).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.expand_targets(&config.targets.default).unwrap(),
+            vec!["x86_64-unknown-linux-gnu"]
+        );
+
+        config.apply_profile("release").unwrap();
+        assert_eq!(
+            config.targets.default,
+            vec![
+                "x86_64-unknown-linux-gnu",
+                "x86_64-pc-windows-gnu",
+                "x86_64-apple-darwin",
+                "aarch64-apple-darwin"
+            ]
+        );
+
+        let err = config.expand_targets(&["@mobile".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("Defined groups: @desktop, @smoke"));
+
+        config
+            .target_groups
+            .insert("smoke".to_string(), vec!["@desktop".to_string()]);
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("includes itself"));
+    }
+
+    #[test]
+    fn test_toolchain_config() {
+        let config = Config::from_str("[toolchain]\nchannel = \"1.78.0\"\n").unwrap();
+        assert_eq!(config.toolchain.channel.as_deref(), Some("1.78.0"));
+
+        let mut base = Config::default();
+        base.merge(&config);
+        assert_eq!(base.toolchain.channel.as_deref(), Some("1.78.0"));
+    }
+
+    #[test]
+    fn test_zig_config() {
+        let config = Config::from_str("[zig]\nversion = \"0.13.0\"\n").unwrap();
+        assert_eq!(config.zig.version.as_deref(), Some("0.13.0"));
+        assert!(Config::from_str("[zig]\npath = \"/opt/zig\"\n").is_err());
+
+        let mut base = Config::default();
+        base.merge(&config);
+        assert_eq!(base.zig.version.as_deref(), Some("0.13.0"));
+    }
+
+    #[test]
+    fn test_network_config() {
+        let config = Config::from_str(
+            "[network]\nproxy = \"http://proxy:3128\"\nca_bundle = \"certs/corp.pem\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.network.proxy.as_deref(), Some("http://proxy:3128"));
+        assert!(config.network.no_proxy.is_none());
+        assert!(Config::from_str("[network]\nhttps_proxy = \"http://proxy\"\n").is_err());
+
+        let mut base = Config::default();
+        base.merge(&config);
+        assert_eq!(
+            base.network.ca_bundle.as_deref(),
+            Some(Path::new("certs/corp.pem"))
+        );
+    }
+
+    #[test]
+    fn test_deploy_config() {
+        let config =
+            Config::from_str("[deploy]\nhost = \"pi@raspberrypi.local\"\nservice = \"sensor\"\n")
+                .unwrap();
+        assert_eq!(config.deploy.host.as_deref(), Some("pi@raspberrypi.local"));
+        assert!(config.deploy.target.is_none());
+        assert!(Config::from_str("[deploy]\nuser = \"pi\"\n").is_err());
+
+        let mut base = Config::default();
+        base.deploy.path = Some("/opt/sensor".to_string());
+        base.merge(&config);
+        assert_eq!(base.deploy.service.as_deref(), Some("sensor"));
+        assert_eq!(base.deploy.path.as_deref(), Some("/opt/sensor"));
+
+        let config = Config::from_str(
+            r#"
+[deploy]
+host = "pi@raspberrypi.local"
+method = "rsync"
+
+[deploy."aarch64-unknown-linux-gnu"]
+path = "/opt/sensor"
+restart = "sudo systemctl restart sensor"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.deploy.default_target(),
+            Some("aarch64-unknown-linux-gnu")
+        );
+        let settings = config.deploy.for_target("aarch64-unknown-linux-gnu.2.31");
+        assert_eq!(settings.host.as_deref(), Some("pi@raspberrypi.local"));
+        assert_eq!(settings.path.as_deref(), Some("/opt/sensor"));
+        assert_eq!(settings.method, Some(DeployMethod::Rsync));
+        assert!(config
+            .deploy
+            .for_target("x86_64-unknown-linux-gnu")
+            .restart
+            .is_none());
+        assert!(
+            Config::from_str("[deploy.\"aarch64-unknown-linux-gnu\"]\ntarget = \"x\"\n").is_err()
+        );
+        assert!(Config::from_str("[deploy]\nmethod = \"ftp\"\n").is_err());
+    }
+
+    #[test]
+    fn test_package_config() {
+        let config = Config::from_str(
+            "[package.docker]\nrepository = \"ghcr.io/acme/gateway\"\ntags = [\"edge\"]\n",
+        )
+        .unwrap();
+        assert_eq!(config.package.docker.tags, ["edge"]);
+        assert!(config.package.docker.base.is_none());
+        assert!(Config::from_str("[package.docker]\nimage = \"gateway\"\n").is_err());
+
+        let mut base = Config::default();
+        base.package.docker.base = Some("scratch".to_string());
+        base.merge(&config);
+        assert_eq!(
+            base.package.docker.repository.as_deref(),
+            Some("ghcr.io/acme/gateway")
+        );
+        assert_eq!(base.package.docker.base.as_deref(), Some("scratch"));
+    }
+
+    #[test]
+    fn test_signing_config() {
+        let config = Config::from_str(
+            "[signing.macos]\nidentity_env = \"MAC_ID\"\nnotarize = true\n\n[signing.windows]\ntool = \"osslsigncode\"\n",
+        )
+        .unwrap();
+        let macos = config.signing.macos.as_ref().unwrap();
+        assert_eq!(macos.identity_env.as_deref(), Some("MAC_ID"));
+        assert_eq!(macos.notarize, Some(true));
+        assert!(Config::from_str("[signing.macos]\nidentity = \"Acme\"\n").is_err());
+
+        let mut base =
+            Config::from_str("[signing]\ntargets = [\"x86_64-pc-windows-gnu\"]\n").unwrap();
+        base.merge(&config);
+        assert_eq!(base.signing.targets, ["x86_64-pc-windows-gnu"]);
+        assert_eq!(
+            base.signing
+                .windows
+                .and_then(|windows| windows.tool)
+                .as_deref(),
+            Some("osslsigncode")
+        );
+    }
+
+    #[test]
+    fn test_referenced_targets() {
+        let config = Config::from_str(
+            r#"
+            [targets]
+            default = ["x86_64-unknown-linux-gnu"]
+
+            [targets."thumbv7em-none-eabihf"]
+            objcopy = ["-O", "binary"]
+
+            [profiles.release]
+            targets = ["x86_64-pc-windows-gnu"]
+
+            [deploy]
+            target = "aarch64-unknown-linux-gnu"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.referenced_targets(),
+            [
+                "aarch64-unknown-linux-gnu",
+                "thumbv7em-none-eabihf",
+                "x86_64-pc-windows-gnu",
+                "x86_64-unknown-linux-gnu"
+            ]
+        );
+        assert!(Config::default().referenced_targets().is_empty());
+    }
+
+    #[test]
+    fn test_attest_config() {
+        let config = Config::from_str("[attest]\nenabled = true\nsign = \"minisign\"\n").unwrap();
+        assert_eq!(config.attest.enabled, Some(true));
+
+        let mut base = Config::from_str("[attest]\nkey = \"release.key\"\n").unwrap();
+        base.merge(&config);
+        assert_eq!(base.attest.sign.as_deref(), Some("minisign"));
+        assert_eq!(base.attest.key.as_deref(), Some("release.key"));
+    }
 }
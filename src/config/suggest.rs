@@ -0,0 +1,548 @@
+//! Recommending targets from what a project is and depends on
+//!
+//! `xcargo suggest` reads Cargo.toml, and the manifests of a workspace's
+//! members, for the kinds of crates that decide where a program runs:
+//! binaries or only a library, wasm-bindgen and WASI bindings, async servers,
+//! Windows and macOS system APIs, and embedded HALs, whose chip decides the
+//! exact target. The result is a target list with the reason for each, an
+//! xcargo.toml snippet, and the `xcargo init` template that fits.
+
+use crate::error::{Error, Result};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use toml::Table;
+
+use super::read_manifest;
+use super::templates::ProjectTemplate;
+
+/// Crates for the browser
+const WEB_CRATES: &[&str] = &[
+    "wasm-bindgen",
+    "web-sys",
+    "js-sys",
+    "gloo",
+    "yew",
+    "leptos",
+    "dioxus-web",
+];
+
+/// Crates for WASI runtimes
+const WASI_CRATES: &[&str] = &["wasi", "wit-bindgen", "wasip2"];
+
+/// Async runtimes and web frameworks of network services
+const SERVER_CRATES: &[&str] = &[
+    "tokio",
+    "axum",
+    "actix-web",
+    "hyper",
+    "warp",
+    "rocket",
+    "tonic",
+    "poem",
+];
+
+/// GPIO and peripheral access on a Raspberry Pi running Linux
+const RASPBERRY_PI_CRATES: &[&str] = &["rppal", "linux-embedded-hal", "gpio-cdev", "sysfs_gpio"];
+
+/// Windows system APIs
+const WINDOWS_CRATES: &[&str] = &["windows", "windows-sys", "winapi", "winreg"];
+
+/// macOS system APIs
+const MACOS_CRATES: &[&str] = &["cocoa", "objc2", "core-foundation", "security-framework"];
+
+/// HALs and board crates, with the target of their chip's core
+const EMBEDDED_CHIPS: &[(&[&str], &str, &str)] = &[
+    (
+        &["rp2040-hal", "rp-pico", "embassy-rp"],
+        "thumbv6m-none-eabi",
+        "the RP2040 is a Cortex-M0+",
+    ),
+    (
+        &["stm32f0xx-hal", "stm32g0xx-hal"],
+        "thumbv6m-none-eabi",
+        "STM32F0 and G0 chips are Cortex-M0/M0+",
+    ),
+    (
+        &["stm32f1xx-hal"],
+        "thumbv7m-none-eabi",
+        "STM32F1 chips are Cortex-M3",
+    ),
+    (
+        &[
+            "stm32f3xx-hal",
+            "stm32f4xx-hal",
+            "stm32f7xx-hal",
+            "stm32h7xx-hal",
+            "stm32l4xx-hal",
+        ],
+        "thumbv7em-none-eabihf",
+        "STM32F3, F4, F7, H7 and L4 chips are Cortex-M4F/M7F",
+    ),
+    (
+        &[
+            "nrf52840-hal",
+            "nrf52833-hal",
+            "nrf52832-hal",
+            "embassy-nrf",
+        ],
+        "thumbv7em-none-eabihf",
+        "nRF52 chips are Cortex-M4F",
+    ),
+    (
+        &["esp-hal"],
+        "riscv32imc-unknown-none-elf",
+        "the ESP32-C3 is a RISC-V core; Xtensa ESP32s need the esp toolchain",
+    ),
+];
+
+/// Crates of bare-metal firmware that don't name a chip
+const EMBEDDED_CRATES: &[&str] = &[
+    "cortex-m",
+    "cortex-m-rt",
+    "embedded-hal",
+    "embassy-executor",
+    "embassy-stm32",
+    "rtic",
+    "panic-halt",
+];
+
+/// What a project builds and depends on, read from its manifests
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectFacts {
+    /// Some package builds a binary
+    pub has_bin: bool,
+
+    /// Some package builds a library
+    pub has_lib: bool,
+
+    /// Some library is built as a `cdylib`, e.g. for WebAssembly
+    pub cdylib: bool,
+
+    /// Names of the crates the packages depend on, for any target
+    pub dependencies: BTreeSet<String>,
+}
+
+impl ProjectFacts {
+    /// Read the package or workspace at `manifest`
+    ///
+    /// # Errors
+    /// Returns an error if a manifest cannot be read or parsed, or cargo
+    /// cannot list a workspace's members
+    pub fn from_manifest(manifest: &Path) -> Result<Self> {
+        let mut facts = Self::default();
+        let table = read_manifest(manifest)?;
+        if !table.contains_key("workspace") {
+            facts.add_package(&table, manifest.parent().unwrap_or(Path::new(".")));
+            return Ok(facts);
+        }
+        for member in workspace_members(manifest)? {
+            let dir = member.parent().unwrap_or(Path::new("."));
+            facts.add_package(&read_manifest(&member)?, dir);
+        }
+        Ok(facts)
+    }
+
+    /// Add the package described by `manifest` in `dir`, if it is one
+    fn add_package(&mut self, manifest: &Table, dir: &Path) {
+        if !manifest.contains_key("package") {
+            return;
+        }
+        self.has_bin |= manifest.contains_key("bin")
+            || dir.join("src/main.rs").is_file()
+            || dir.join("src/bin").is_dir();
+        let lib = manifest.get("lib").and_then(toml::Value::as_table);
+        self.has_lib |= lib.is_some() || dir.join("src/lib.rs").is_file();
+        self.cdylib |= lib
+            .and_then(|lib| lib.get("crate-type"))
+            .and_then(toml::Value::as_array)
+            .is_some_and(|types| types.iter().any(|ty| ty.as_str() == Some("cdylib")));
+
+        let mut tables = vec![manifest.get("dependencies")];
+        if let Some(targets) = manifest.get("target").and_then(toml::Value::as_table) {
+            tables.extend(targets.values().map(|target| target.get("dependencies")));
+        }
+        for deps in tables
+            .into_iter()
+            .flatten()
+            .filter_map(toml::Value::as_table)
+        {
+            for (name, spec) in deps {
+                // `alias = { package = "real-name" }`
+                let package = spec.get("package").and_then(toml::Value::as_str);
+                self.dependencies
+                    .insert(package.unwrap_or(name).to_string());
+            }
+        }
+    }
+}
+
+/// A recommended target and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SuggestedTarget {
+    /// Target triple
+    pub triple: String,
+
+    /// What in the project suggests it
+    pub reason: String,
+}
+
+/// Targets recommended for a project
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suggestion {
+    /// Recommended targets, most important first
+    pub targets: Vec<SuggestedTarget>,
+
+    /// The `xcargo init` template for this kind of project, if one fits
+    pub template: Option<ProjectTemplate>,
+}
+
+impl Suggestion {
+    /// Recommend targets for a project
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::config::{ProjectFacts, ProjectTemplate, Suggestion};
+    ///
+    /// let mut facts = ProjectFacts { has_bin: true, ..ProjectFacts::default() };
+    /// facts.dependencies.insert("rp2040-hal".to_string());
+    ///
+    /// let suggestion = Suggestion::for_project(&facts);
+    /// assert_eq!(suggestion.triples(), ["thumbv6m-none-eabi"]);
+    /// assert_eq!(suggestion.template, Some(ProjectTemplate::Embedded));
+    /// ```
+    #[must_use]
+    pub fn for_project(facts: &ProjectFacts) -> Self {
+        let mut suggestion = Self {
+            targets: Vec::new(),
+            template: None,
+        };
+
+        // Firmware runs on one chip and nothing else
+        let chip = EMBEDDED_CHIPS.iter().find_map(|(crates, triple, why)| {
+            first_dependency(facts, crates).map(|name| (name, triple, why))
+        });
+        if let Some((name, triple, why)) = chip {
+            suggestion.add(triple, format!("depends on {name}; {why}"));
+            suggestion.template = Some(ProjectTemplate::Embedded);
+            return suggestion;
+        }
+        if let Some(name) = first_dependency(facts, EMBEDDED_CRATES) {
+            suggestion.add(
+                "thumbv7em-none-eabihf",
+                format!("depends on {name}; Cortex-M4F/M7F is the most common core, change it to match your chip"),
+            );
+            suggestion.template = Some(ProjectTemplate::Embedded);
+            return suggestion;
+        }
+
+        if let Some(name) = first_dependency(facts, RASPBERRY_PI_CRATES) {
+            let why = format!("depends on {name}, which drives Raspberry Pi hardware");
+            suggestion.add(
+                "aarch64-unknown-linux-gnu",
+                format!("{why}; 64-bit Raspberry Pi OS"),
+            );
+            suggestion.add(
+                "armv7-unknown-linux-gnueabihf",
+                format!("{why}; 32-bit Raspberry Pi OS"),
+            );
+            suggestion.template = Some(ProjectTemplate::RaspberryPi);
+        }
+
+        if let Some(name) = first_dependency(facts, WEB_CRATES) {
+            suggestion.add(
+                "wasm32-unknown-unknown",
+                format!("depends on {name}, which runs in the browser"),
+            );
+        }
+        if let Some(name) = first_dependency(facts, WASI_CRATES) {
+            suggestion.add(
+                "wasm32-wasip1",
+                format!("depends on {name}, which runs under WASI runtimes"),
+            );
+        }
+        // Binaries of a wasm project run in the browser or a WASI runtime too
+        let wasm_only = suggestion
+            .targets
+            .iter()
+            .all(|t| t.triple.starts_with("wasm"));
+        if !suggestion.targets.is_empty() && wasm_only {
+            suggestion.template = Some(ProjectTemplate::Wasm);
+            return suggestion;
+        }
+
+        if facts.has_bin && suggestion.template.is_none() {
+            if let Some(name) = first_dependency(facts, SERVER_CRATES) {
+                let why = format!("a binary using {name}, usually a service");
+                suggestion.add(
+                    "x86_64-unknown-linux-musl",
+                    format!("{why}; static, so it runs in scratch or distroless images"),
+                );
+                suggestion.add(
+                    "aarch64-unknown-linux-musl",
+                    format!("{why}; for ARM64 servers such as AWS Graviton"),
+                );
+                suggestion.template = Some(ProjectTemplate::Server);
+            } else {
+                suggestion.add(
+                    "x86_64-unknown-linux-musl",
+                    "a binary; static Linux builds run on any distribution",
+                );
+                suggestion.add("x86_64-pc-windows-gnu", "a binary, released for Windows");
+                suggestion.add(
+                    "aarch64-apple-darwin",
+                    "a binary, released for Apple silicon Macs",
+                );
+                suggestion.add("x86_64-apple-darwin", "a binary, released for Intel Macs");
+                suggestion.template = Some(ProjectTemplate::Cli);
+            }
+        }
+
+        if let Some(name) = first_dependency(facts, WINDOWS_CRATES) {
+            suggestion.add(
+                "x86_64-pc-windows-gnu",
+                format!("depends on {name}; cross-compiles from Linux and macOS with MinGW"),
+            );
+        }
+        if let Some(name) = first_dependency(facts, MACOS_CRATES) {
+            suggestion.add("aarch64-apple-darwin", format!("depends on {name}"));
+            suggestion.add("x86_64-apple-darwin", format!("depends on {name}"));
+        }
+
+        if suggestion.targets.is_empty() && facts.has_lib {
+            // Check that a library builds where its users build
+            let why = "a library; check it builds on each major platform";
+            suggestion.add("x86_64-unknown-linux-gnu", why);
+            suggestion.add("x86_64-pc-windows-gnu", why);
+            suggestion.add("aarch64-apple-darwin", why);
+        }
+        suggestion
+    }
+
+    /// Add `triple`, unless it is already suggested
+    fn add(&mut self, triple: &str, reason: impl Into<String>) {
+        if !self.targets.iter().any(|target| target.triple == triple) {
+            self.targets.push(SuggestedTarget {
+                triple: triple.to_string(),
+                reason: reason.into(),
+            });
+        }
+    }
+
+    /// The suggested triples
+    #[must_use]
+    pub fn triples(&self) -> Vec<&str> {
+        self.targets
+            .iter()
+            .map(|target| target.triple.as_str())
+            .collect()
+    }
+
+    /// xcargo.toml settings for the suggested targets
+    #[must_use]
+    pub fn snippet(&self) -> String {
+        let mut snippet = String::from("[targets]\ndefault = [");
+        let quoted: Vec<String> = self
+            .targets
+            .iter()
+            .map(|target| format!("\"{}\"", target.triple))
+            .collect();
+        snippet.push_str(&quoted.join(", "));
+        snippet.push_str("]\n");
+
+        for target in &self.targets {
+            let triple = target.triple.as_str();
+            let settings = if triple.starts_with("thumb") || triple.ends_with("-none-elf") {
+                "rustflags = [\"-C\", \"link-arg=-Tlink.x\"]\nobjcopy = [\"-O\", \"binary\"]\n"
+            } else if triple.contains("-linux-musl") || triple.contains("-windows-gnu") {
+                "strip = true\n"
+            } else if triple.starts_with("wasm32-wasi") {
+                "runner = \"wasmtime\"\n"
+            } else {
+                continue;
+            };
+            let _ = write!(snippet, "\n[targets.\"{triple}\"]\n{settings}");
+        }
+        if self.triples().contains(&"wasm32-unknown-unknown") {
+            snippet.push_str(
+                "\n[targets.\"wasm32-unknown-unknown\".wasm]\nbindgen_target = \"web\"\n",
+            );
+        }
+        snippet
+    }
+}
+
+/// The first of `crates` the project depends on
+fn first_dependency<'a>(facts: &ProjectFacts, crates: &[&'a str]) -> Option<&'a str> {
+    crates
+        .iter()
+        .copied()
+        .find(|name| facts.dependencies.contains(*name))
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<MetadataPackage>,
+    workspace_members: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct MetadataPackage {
+    id: String,
+    manifest_path: PathBuf,
+}
+
+/// Manifests of the members of the workspace at `manifest`, with its
+/// `members` globs and `exclude` list resolved by cargo
+fn workspace_members(manifest: &Path) -> Result<Vec<PathBuf>> {
+    let output = Command::new("cargo")
+        .args([
+            "metadata",
+            "--no-deps",
+            "--format-version",
+            "1",
+            "--manifest-path",
+        ])
+        .arg(manifest)
+        .output()
+        .map_err(|e| Error::Build(format!("Failed to run cargo metadata: {e}")))?;
+
+    if !output.status.success() {
+        return Err(Error::Build(format!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let metadata: Metadata = serde_json::from_slice(&output.stdout)
+        .map_err(|e| Error::Build(format!("Invalid cargo metadata output: {e}")))?;
+    let mut members: Vec<PathBuf> = metadata
+        .packages
+        .into_iter()
+        .filter(|p| metadata.workspace_members.contains(&p.id))
+        .map(|p| p.manifest_path)
+        .collect();
+    members.sort();
+    Ok(members)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use tempfile::TempDir;
+
+    fn facts(has_bin: bool, dependencies: &[&str]) -> ProjectFacts {
+        ProjectFacts {
+            has_bin,
+            has_lib: !has_bin,
+            cdylib: false,
+            dependencies: dependencies.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[test]
+    fn test_suggest_by_dependencies() {
+        let cli = Suggestion::for_project(&facts(true, &["clap"]));
+        assert_eq!(cli.template, Some(ProjectTemplate::Cli));
+        assert_eq!(cli.targets.len(), 4);
+
+        let server = Suggestion::for_project(&facts(true, &["axum", "tokio"]));
+        assert_eq!(
+            server.triples(),
+            ["x86_64-unknown-linux-musl", "aarch64-unknown-linux-musl"]
+        );
+        assert!(server.targets[0].reason.contains("tokio"));
+
+        let web = Suggestion::for_project(&ProjectFacts {
+            cdylib: true,
+            ..facts(false, &["wasm-bindgen", "web-sys"])
+        });
+        assert_eq!(web.triples(), ["wasm32-unknown-unknown"]);
+        assert_eq!(web.template, Some(ProjectTemplate::Wasm));
+
+        let pi = Suggestion::for_project(&facts(true, &["rppal", "tokio"]));
+        assert_eq!(pi.template, Some(ProjectTemplate::RaspberryPi));
+        assert_eq!(pi.targets.len(), 2);
+
+        // A Windows API crate adds Windows to a library's checks
+        let lib = Suggestion::for_project(&facts(false, &["windows-sys"]));
+        assert_eq!(lib.triples(), ["x86_64-pc-windows-gnu"]);
+        assert_eq!(lib.template, None);
+
+        let plain = Suggestion::for_project(&facts(false, &["serde"]));
+        assert_eq!(plain.targets.len(), 3);
+    }
+
+    #[test]
+    fn test_snippets_are_valid_configs() {
+        let projects = [
+            facts(true, &["clap"]),
+            facts(true, &["cortex-m-rt"]),
+            facts(true, &["esp-hal"]),
+            facts(false, &["wasm-bindgen", "wit-bindgen"]),
+            facts(true, &["rppal"]),
+        ];
+        for facts in projects {
+            let suggestion = Suggestion::for_project(&facts);
+            let snippet = suggestion.snippet();
+            let config = Config::from_str(&snippet).unwrap_or_else(|e| panic!("{snippet}: {e}"));
+            assert_eq!(config.targets.default, suggestion.triples());
+        }
+    }
+
+    #[test]
+    fn test_facts_from_workspace() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\", \"tools/gen-*\"]\nexclude = [\"crates/legacy\"]\n",
+        )
+        .unwrap();
+        // An excluded crate is not a member
+        let legacy = dir.path().join("crates/legacy");
+        std::fs::create_dir_all(legacy.join("src")).unwrap();
+        std::fs::write(legacy.join("src/lib.rs"), "").unwrap();
+        std::fs::write(
+            legacy.join("Cargo.toml"),
+            "[package]\nname = \"legacy\"\n\n[dependencies]\nwinapi = \"0.3\"\n",
+        )
+        .unwrap();
+        let codegen = dir.path().join("tools/gen-bindings");
+        std::fs::create_dir_all(codegen.join("src")).unwrap();
+        std::fs::write(codegen.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(
+            codegen.join("Cargo.toml"),
+            "[package]\nname = \"gen-bindings\"\n\n[dependencies]\nclap = \"4\"\n",
+        )
+        .unwrap();
+        let app = dir.path().join("crates/app");
+        std::fs::create_dir_all(app.join("src")).unwrap();
+        std::fs::write(app.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(
+            app.join("Cargo.toml"),
+            r#"
+            [package]
+            name = "app"
+
+            [dependencies]
+            rt = { package = "tokio", version = "1" }
+
+            [target.'cfg(windows)'.dependencies]
+            windows-sys = "0.59"
+            "#,
+        )
+        .unwrap();
+
+        let facts = ProjectFacts::from_manifest(&dir.path().join("Cargo.toml")).unwrap();
+        assert!(facts.has_bin);
+        assert!(!facts.has_lib);
+        assert_eq!(
+            facts.dependencies.into_iter().collect::<Vec<_>>(),
+            ["clap", "tokio", "windows-sys"]
+        );
+    }
+}
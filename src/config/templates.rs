@@ -0,0 +1,267 @@
+//! Starting configurations for common kinds of cross-compiled projects
+//!
+//! `xcargo init --template <name>` writes one of these instead of the flat
+//! target list of the setup wizard, with the rustflags and post-build steps
+//! the targets usually need already filled in.
+
+use std::str::FromStr;
+
+/// Kind of project to generate an xcargo.toml for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectTemplate {
+    /// Bare-metal Cortex-M firmware
+    Embedded,
+    /// WebAssembly for the browser and WASI runtimes
+    Wasm,
+    /// Command-line tool released for the major desktop platforms
+    Cli,
+    /// Service deployed to Linux servers and containers
+    Server,
+    /// Program for Raspberry Pi OS, deployed to the board over SSH
+    RaspberryPi,
+}
+
+impl ProjectTemplate {
+    /// Every template, in the order they are listed to users
+    pub const ALL: [Self; 5] = [
+        Self::Embedded,
+        Self::Wasm,
+        Self::Cli,
+        Self::Server,
+        Self::RaspberryPi,
+    ];
+
+    /// Name used on the command line
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Embedded => "embedded",
+            Self::Wasm => "wasm",
+            Self::Cli => "cli",
+            Self::Server => "server",
+            Self::RaspberryPi => "raspberry-pi",
+        }
+    }
+
+    /// Contents of the generated xcargo.toml
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::config::{Config, ProjectTemplate};
+    ///
+    /// let config = Config::from_str(ProjectTemplate::Embedded.render()).unwrap();
+    /// assert_eq!(config.targets.default, ["thumbv7em-none-eabihf"]);
+    /// ```
+    #[must_use]
+    pub fn render(self) -> &'static str {
+        match self {
+            Self::Embedded => EMBEDDED,
+            Self::Wasm => WASM,
+            Self::Cli => CLI,
+            Self::Server => SERVER,
+            Self::RaspberryPi => RASPBERRY_PI,
+        }
+    }
+
+    /// Follow-up steps shown once the configuration is written
+    #[must_use]
+    pub fn next_steps(self) -> &'static [&'static str] {
+        match self {
+            Self::Embedded => &[
+                "Add a memory.x for your chip; the rustflags link with cortex-m-rt's link.x",
+                "Firmware images are written next to the ELF as <name>.bin",
+                "Rebuild core from source with 'xcargo build --profile build-std --toolchain nightly'",
+            ],
+            Self::Wasm => &[
+                "Install the bindings generator with 'cargo install wasm-bindgen-cli'; JS glue is then written to pkg/ after each build",
+                "Install wasm-opt to shrink release builds",
+                "WASI builds run their tests under wasmtime",
+            ],
+            Self::Cli => &[
+                "Build release binaries for every platform with 'xcargo build --all --release'",
+                "Linux binaries are static (musl) and stripped, ready to upload",
+            ],
+            Self::Server => &[
+                "Static musl binaries run in scratch or distroless container images",
+                "ARM64 tests run under qemu-aarch64; install qemu-user to run them",
+            ],
+            Self::RaspberryPi => &[
+                "Install Zig to link against the glibc of Raspberry Pi OS 11; 'xcargo doctor' checks for it",
+                "Set deploy.host to your Pi, then build and copy with 'xcargo deploy'",
+                "Set deploy.service to restart the systemd service running the program after each deploy",
+                "Give the 32-bit Pi its own [deploy.\"armv7-unknown-linux-gnueabihf\"] host, and deploy with 'xcargo deploy --target rpi32'",
+            ],
+        }
+    }
+}
+
+impl FromStr for ProjectTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|template| template.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown template '{s}' (expected embedded, wasm, cli, server, or raspberry-pi)"
+                )
+            })
+    }
+}
+
+const EMBEDDED: &str = r#"# Generated by `xcargo init --template embedded`
+
+[targets]
+# Cortex-M4F/M7F; add thumbv6m-none-eabi (M0) or thumbv7m-none-eabi (M3) as needed
+default = ["thumbv7em-none-eabihf"]
+
+[targets."thumbv7em-none-eabihf"]
+rustflags = ["-C", "link-arg=-Tlink.x"]
+components = ["llvm-tools-preview"]
+# Raw firmware image for flashing, written next to the ELF as <name>.bin
+objcopy = ["-O", "binary"]
+
+[targets."thumbv7m-none-eabi"]
+rustflags = ["-C", "link-arg=-Tlink.x"]
+components = ["llvm-tools-preview"]
+objcopy = ["-O", "binary"]
+
+[build]
+parallel = false
+
+# Rebuild core and alloc from source with your profile settings (needs nightly):
+# xcargo build --profile build-std --toolchain nightly
+[profiles.build-std]
+targets = ["thumbv7em-none-eabihf"]
+cargo_flags = ["-Z", "build-std=core,alloc"]
+"#;
+
+const WASM: &str = r#"# Generated by `xcargo init --template wasm`
+
+[targets]
+default = ["wasm32-unknown-unknown", "wasm32-wasip1"]
+
+# JS bindings for the browser, generated into pkg/ when wasm-bindgen is installed
+[targets."wasm32-unknown-unknown".wasm]
+bindgen_target = "web"
+
+[targets."wasm32-wasip1"]
+runner = "wasmtime"
+
+[build]
+parallel = true
+"#;
+
+const CLI: &str = r#"# Generated by `xcargo init --template cli`
+
+[targets]
+default = [
+    "x86_64-unknown-linux-musl",
+    "aarch64-unknown-linux-musl",
+    "x86_64-pc-windows-gnu",
+    "x86_64-apple-darwin",
+    "aarch64-apple-darwin",
+]
+
+# Static Linux binaries that run on any distribution
+[targets."x86_64-unknown-linux-musl"]
+strip = true
+
+[targets."aarch64-unknown-linux-musl"]
+strip = true
+
+[targets."x86_64-pc-windows-gnu"]
+strip = true
+
+[build]
+parallel = true
+host_first = true
+"#;
+
+const SERVER: &str = r#"# Generated by `xcargo init --template server`
+
+[targets]
+# Static binaries for scratch or distroless container images
+default = ["x86_64-unknown-linux-musl", "aarch64-unknown-linux-musl"]
+
+[targets."x86_64-unknown-linux-musl"]
+strip = true
+
+[targets."aarch64-unknown-linux-musl"]
+strip = true
+runner = "qemu-aarch64"
+
+# Emulated test runs are slow and occasionally hang
+[runners."aarch64-unknown-linux-musl"]
+test_timeout = "15m"
+retries = 1
+
+[build]
+parallel = true
+"#;
+
+const RASPBERRY_PI: &str = r#"# Generated by `xcargo init --template raspberry-pi`
+
+[targets]
+# 64-bit Raspberry Pi OS (Pi 3, 4, 5 and Zero 2 W) and 32-bit Raspberry Pi OS
+# (Pi 2 and later; the Pi 1 and Zero need arm-unknown-linux-gnueabihf). The .2.31
+# suffix links against the glibc of Raspberry Pi OS 11 (Bullseye) with Zig, so
+# the binaries also run on later releases.
+default = ["aarch64-unknown-linux-gnu.2.31", "armv7-unknown-linux-gnueabihf.2.31"]
+
+# Linkers for builds without the glibc suffix, from the gcc-aarch64-linux-gnu
+# and gcc-arm-linux-gnueabihf packages
+[targets."aarch64-unknown-linux-gnu"]
+linker = "aarch64-linux-gnu-gcc"
+strip = true
+
+[targets."armv7-unknown-linux-gnueabihf"]
+linker = "arm-linux-gnueabihf-gcc"
+strip = true
+
+[build]
+parallel = true
+
+# Copied to the Pi by `xcargo deploy`
+[deploy]
+host = "pi@raspberrypi.local"
+target = "aarch64-unknown-linux-gnu.2.31"
+# Restarted around each copy
+# service = "my-app"
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::target::Target;
+
+    #[test]
+    fn test_templates_are_valid_configs() {
+        for template in ProjectTemplate::ALL {
+            let config = Config::from_str(template.render())
+                .unwrap_or_else(|e| panic!("{}: {e}", template.as_str()));
+            assert!(!config.targets.default.is_empty());
+            for triple in config
+                .targets
+                .default
+                .iter()
+                .chain(config.targets.custom.keys())
+            {
+                assert!(Target::from_triple(triple).is_ok(), "{triple}");
+            }
+            assert!(!template.next_steps().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_template_names() {
+        for template in ProjectTemplate::ALL {
+            assert_eq!(template.as_str().parse(), Ok(template));
+        }
+        assert_eq!("WASM".parse(), Ok(ProjectTemplate::Wasm));
+        assert!("desktop".parse::<ProjectTemplate>().is_err());
+    }
+}
@@ -0,0 +1,593 @@
+//! Checking configuration files for every problem at once
+//!
+//! [`Config::validate`] stops at the first invalid setting. `xcargo config
+//! validate` reports all of a file's problems instead, with the line each is
+//! on, and also checks the settings against the machine: targets rustup
+//! knows and linkers on PATH.
+
+use crate::error::{Error, Result};
+use crate::target::{renamed_counterpart, Target};
+use std::fmt::Write;
+use std::path::Path;
+use toml_edit::{ImDocument, Item};
+
+use super::{edit_distance, Config, UseWhen, VolumeMount};
+
+/// A problem with one setting of a configuration file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigProblem {
+    /// Path to the setting, e.g. `["targets", "default", "1"]` for the
+    /// second default target
+    pub path: Vec<String>,
+
+    /// Line of the setting, when it is in the file
+    pub line: Option<usize>,
+
+    /// What is wrong
+    pub message: String,
+
+    /// How to fix it, if known
+    pub suggestion: Option<String>,
+}
+
+impl ConfigProblem {
+    fn new(path: &[&str], message: impl Into<String>) -> Self {
+        Self {
+            path: path.iter().map(|part| (*part).to_string()).collect(),
+            line: None,
+            message: message.into(),
+            suggestion: None,
+        }
+    }
+
+    fn with_suggestion(self, suggestion: impl Into<String>) -> Self {
+        Self {
+            suggestion: Some(suggestion.into()),
+            ..self
+        }
+    }
+
+    /// The setting as written in TOML, e.g. `targets.default[1]`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::config::Config;
+    ///
+    /// let mut config = Config::default();
+    /// config.targets.default = vec!["x86_64-pc-windows-gnu".to_string(), "@mobile".to_string()];
+    /// let problems = config.problems();
+    /// assert_eq!(problems[0].key(), "targets.default");
+    /// ```
+    #[must_use]
+    pub fn key(&self) -> String {
+        let mut key = String::new();
+        for part in &self.path {
+            if part.parse::<usize>().is_ok() {
+                let _ = write!(key, "[{part}]");
+                continue;
+            }
+            if !key.is_empty() {
+                key.push('.');
+            }
+            let bare = part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+            if bare {
+                key.push_str(part);
+            } else {
+                let _ = write!(key, "\"{part}\"");
+            }
+        }
+        key
+    }
+}
+
+impl Config {
+    /// Every invalid setting; [`Config::validate`] fails with the first
+    #[must_use]
+    pub fn problems(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+
+        // Validate runtime
+        let valid_runtimes = ["auto", "youki", "docker", "podman"];
+        if !valid_runtimes.contains(&self.container.runtime.as_str()) {
+            problems.push(ConfigProblem::new(
+                &["container", "runtime"],
+                format!(
+                    "Invalid container runtime: {}. Must be one of: {}",
+                    self.container.runtime,
+                    valid_runtimes.join(", ")
+                ),
+            ));
+        }
+
+        // Validate pull policy
+        let valid_policies = ["always", "never", "if-not-present"];
+        if !valid_policies.contains(&self.container.pull_policy.as_str()) {
+            problems.push(ConfigProblem::new(
+                &["container", "pull_policy"],
+                format!(
+                    "Invalid pull policy: {}. Must be one of: {}",
+                    self.container.pull_policy,
+                    valid_policies.join(", ")
+                ),
+            ));
+        }
+
+        // Validate credentials policy
+        let valid_credentials = ["none", "ssh-agent"];
+        if !valid_credentials.contains(&self.container.credentials.as_str()) {
+            problems.push(ConfigProblem::new(
+                &["container", "credentials"],
+                format!(
+                    "Invalid container credentials policy: {}. Must be one of: {}",
+                    self.container.credentials,
+                    valid_credentials.join(", ")
+                ),
+            ));
+        }
+
+        // Validate the cargo cache strategy and extra volumes
+        let valid_caches = ["volume", "host", "none"];
+        if !valid_caches.contains(&self.container.cargo_cache.as_str()) {
+            problems.push(ConfigProblem::new(
+                &["container", "cargo_cache"],
+                format!(
+                    "Invalid container.cargo_cache: {}. Must be one of: {}",
+                    self.container.cargo_cache,
+                    valid_caches.join(", ")
+                ),
+            ));
+        }
+        for (idx, volume) in self.container.volumes.iter().enumerate() {
+            if let Err(e) = volume.parse::<VolumeMount>() {
+                problems.push(ConfigProblem::new(
+                    &["container", "volumes", &idx.to_string()],
+                    format!("Invalid container.volumes entry: {e}"),
+                ));
+            }
+        }
+
+        // Validate the container condition
+        if let Err(e) = self.container.use_when.parse::<UseWhen>() {
+            problems.push(ConfigProblem::new(
+                &["container", "use_when"],
+                format!("Invalid container.use_when: {e}"),
+            ));
+        }
+
+        // Validate per-target images
+        for (target, image) in &self.container.images {
+            if image.trim().is_empty() || image.contains(char::is_whitespace) {
+                problems.push(ConfigProblem::new(
+                    &["container", "images", target],
+                    format!("Invalid container.images.\"{target}\": '{image}' is not an image reference"),
+                ));
+            }
+        }
+
+        // Validate release policy
+        let valid_strategies = ["native", "zig", "rust-lld", "container"];
+        if let Some(required) = &self.policy.release_requires {
+            if !valid_strategies.contains(&required.as_str()) {
+                problems.push(ConfigProblem::new(
+                    &["policy", "release_requires"],
+                    format!(
+                        "Invalid policy.release_requires: {}. Must be one of: {}",
+                        required,
+                        valid_strategies.join(", ")
+                    ),
+                ));
+            }
+        }
+
+        // Validate fallback strategy chains
+        for (target, config) in &self.targets.custom {
+            for (idx, strategy) in config.strategies.iter().flatten().enumerate() {
+                if !valid_strategies.contains(&strategy.as_str()) {
+                    problems.push(ConfigProblem::new(
+                        &["targets", target, "strategies", &idx.to_string()],
+                        format!(
+                            "Invalid targets.\"{target}\".strategies entry: {strategy}. Must be one of: {}",
+                            valid_strategies.join(", ")
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // Validate build timeouts
+        if let Some(timeout) = &self.build.timeout {
+            if crate::build::parse_duration(timeout).is_err() {
+                problems.push(ConfigProblem::new(
+                    &["build", "timeout"],
+                    format!(
+                        "Invalid build.timeout: {timeout}. Use a duration such as \"45m\" or \"2h\""
+                    ),
+                ));
+            }
+        }
+        for (target, config) in &self.targets.custom {
+            if let Some(timeout) = &config.timeout {
+                if crate::build::parse_duration(timeout).is_err() {
+                    problems.push(ConfigProblem::new(
+                        &["targets", target, "timeout"],
+                        format!(
+                            "Invalid targets.\"{target}\".timeout: {timeout}. Use a duration such as \"45m\" or \"2h\""
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // Validate test runner timeouts
+        for (target, runner) in &self.runners {
+            if let Some(timeout) = &runner.test_timeout {
+                if crate::build::parse_duration(timeout).is_err() {
+                    problems.push(ConfigProblem::new(
+                        &["runners", target, "test_timeout"],
+                        format!(
+                            "Invalid runners.\"{target}\".test_timeout: {timeout}. Use a duration such as \"90s\" or \"10m\""
+                        ),
+                    ));
+                }
+            }
+        }
+
+        // Validate artifact retention
+        if self.artifacts.keep_last_builds == Some(0) {
+            problems.push(ConfigProblem::new(
+                &["artifacts", "keep_last_builds"],
+                "artifacts.keep_last_builds must be greater than 0",
+            ));
+        }
+        if let Some(size) = &self.artifacts.max_total_size {
+            if crate::build::parse_size(size).is_err() {
+                problems.push(ConfigProblem::new(
+                    &["artifacts", "max_total_size"],
+                    format!(
+                        "Invalid artifacts.max_total_size: {size}. Use a size such as \"500MiB\" or \"20GiB\""
+                    ),
+                ));
+            }
+        }
+
+        // Validate the build matrix
+        for (idx, set) in self.matrix.features.iter().enumerate() {
+            if let Err(e) = set.parse::<crate::build::FeatureSet>() {
+                problems.push(ConfigProblem::new(
+                    &["matrix", "features", &idx.to_string()],
+                    format!("Invalid matrix feature set: {e}"),
+                ));
+            }
+        }
+        for (idx, profile) in self.matrix.profiles.iter().enumerate() {
+            if !["debug", "release"].contains(&profile.as_str()) {
+                problems.push(ConfigProblem::new(
+                    &["matrix", "profiles", &idx.to_string()],
+                    format!("Invalid matrix profile: {profile}. Must be one of: debug, release"),
+                ));
+            }
+        }
+
+        // Validate target groups and their use
+        let mut expand = |path: &[&str], targets: &[String]| {
+            if let Err(e) = self.expand_targets(targets) {
+                problems.push(ConfigProblem::new(path, config_message(e)));
+            }
+        };
+        for (name, members) in &self.target_groups {
+            expand(&["target-groups", name], members);
+        }
+        expand(&["targets", "default"], &self.targets.default);
+        expand(&["matrix", "targets"], &self.matrix.targets);
+        for (name, profile) in &self.profiles {
+            expand(&["profiles", name, "targets"], &profile.targets);
+        }
+
+        // Validate the Windows signing tool
+        let windows_tool = self.signing.windows.as_ref().and_then(|w| w.tool.as_ref());
+        if let Some(tool) = windows_tool {
+            if !["signtool", "osslsigncode"].contains(&tool.as_str()) {
+                problems.push(ConfigProblem::new(
+                    &["signing", "windows", "tool"],
+                    format!(
+                        "Invalid signing.windows.tool: {tool}. Must be one of: signtool, osslsigncode"
+                    ),
+                ));
+            }
+        }
+
+        // Validate jobs count
+        if self.build.jobs == Some(0) {
+            problems.push(ConfigProblem::new(
+                &["build", "jobs"],
+                "build.jobs must be greater than 0",
+            ));
+        }
+
+        problems
+    }
+
+    /// Settings that do not fit this machine: targets missing from
+    /// `known_targets` (rustup's list, when available) and linkers that are
+    /// not on PATH
+    #[must_use]
+    pub fn environment_problems(&self, known_targets: Option<&[String]>) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+
+        // Target triples, wherever they are used
+        for (path, triple) in self.target_references() {
+            let path: Vec<&str> = path.iter().map(String::as_str).collect();
+            problems.extend(target_problem(&path, triple, known_targets));
+        }
+
+        // Custom linkers
+        for (triple, target) in &self.targets.custom {
+            if let Some(linker) = &target.linker {
+                if which::which(linker).is_err() {
+                    problems.push(
+                        ConfigProblem::new(
+                            &["targets", triple, "linker"],
+                            format!("Linker '{linker}' for {triple} is not on PATH"),
+                        )
+                        .with_suggestion(
+                            "Install it, or set the linker's full path; `xcargo doctor --target <triple>` lists what the target needs",
+                        ),
+                    );
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+/// Why `triple` at `path` cannot be built, if it cannot
+fn target_problem(
+    path: &[&str],
+    triple: &str,
+    known_targets: Option<&[String]>,
+) -> Option<ConfigProblem> {
+    let target = match Target::from_triple(triple) {
+        Ok(target) => target,
+        Err(e) => return Some(ConfigProblem::new(path, target_message(e))),
+    };
+    let known = known_targets?;
+    let listed = |triple: &str| known.iter().any(|known| known == triple);
+    if listed(&target.triple) || renamed_counterpart(&target.triple).is_some_and(listed) {
+        return None;
+    }
+
+    let problem = ConfigProblem::new(
+        path,
+        format!("Unknown target '{triple}': rustup does not list it"),
+    );
+    let closest = known
+        .iter()
+        .map(|known| (edit_distance(known, &target.triple), known))
+        .filter(|(distance, _)| *distance <= 3)
+        .min();
+    Some(match closest {
+        Some((_, known)) => problem.with_suggestion(format!("Did you mean '{known}'?")),
+        None => problem.with_suggestion("Run 'xcargo target list' to see available targets"),
+    })
+}
+
+fn config_message(error: Error) -> String {
+    match error {
+        Error::Config(message) => message,
+        other => other.to_string(),
+    }
+}
+
+fn target_message(error: Error) -> String {
+    match error {
+        Error::TargetNotFound(message) => message,
+        other => other.to_string(),
+    }
+}
+
+/// Line of the byte at `offset` in `contents`, counting from 1
+pub(super) fn line_at(contents: &str, offset: usize) -> usize {
+    contents[..offset.min(contents.len())].matches('\n').count() + 1
+}
+
+/// [`Error::ConfigParse`] for a TOML error in the file at `path`
+pub(super) fn parse_error(path: &Path, contents: &str, error: &toml::de::Error) -> Error {
+    Error::ConfigParse {
+        path: path.display().to_string(),
+        line: error.span().map(|span| line_at(contents, span.start)),
+        message: error.message().to_string(),
+    }
+}
+
+/// Line of the setting at `path` in `doc`, or of the nearest enclosing
+/// table when the setting itself is not written in the file
+fn locate(doc: &ImDocument<&str>, path: &[String]) -> Option<usize> {
+    let mut item: &Item = doc.as_item();
+    let mut span = None;
+    for part in path {
+        let next = match part.parse::<usize>() {
+            Ok(idx) => item.get(idx),
+            Err(_) => item.get(part.as_str()),
+        };
+        let Some(next) = next else {
+            break;
+        };
+        item = next;
+        span = item.span().or(span);
+    }
+    span.map(|span| line_at(doc.raw(), span.start))
+}
+
+/// Every problem with the configuration in `contents`
+///
+/// A file that is not valid TOML, or does not fit xcargo's configuration,
+/// reports only that. Otherwise each invalid setting is reported, followed by
+/// [`Config::environment_problems`], sorted by line.
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::config::check_config;
+///
+/// let problems = check_config("[build]\njobs = 0\n\n[container]\nruntime = \"lxc\"\n", None);
+/// assert_eq!(problems.len(), 2);
+/// assert_eq!(problems[0].line, Some(2));
+/// assert_eq!(problems[1].key(), "container.runtime");
+/// ```
+#[must_use]
+pub fn check_config(contents: &str, known_targets: Option<&[String]>) -> Vec<ConfigProblem> {
+    check(contents, |config| {
+        config.environment_problems(known_targets)
+    })
+}
+
+/// Problems that make the configuration in `contents` invalid, without
+/// checking it against this machine; see [`check_config`]
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::config::check_config_settings;
+///
+/// assert!(check_config_settings("[targets]\ndefault = [\"not-a-target\"]\n").is_empty());
+/// assert_eq!(check_config_settings("[build]\njobs = 0\n").len(), 1);
+/// ```
+#[must_use]
+pub fn check_config_settings(contents: &str) -> Vec<ConfigProblem> {
+    check(contents, |_| Vec::new())
+}
+
+fn check(
+    contents: &str,
+    environment: impl FnOnce(&Config) -> Vec<ConfigProblem>,
+) -> Vec<ConfigProblem> {
+    let doc = match ImDocument::parse(contents) {
+        Ok(doc) => doc,
+        Err(e) => {
+            return vec![ConfigProblem {
+                path: Vec::new(),
+                line: e.span().map(|span| line_at(contents, span.start)),
+                message: e.message().to_string(),
+                suggestion: None,
+            }]
+        }
+    };
+    let config: Config = match toml::from_str(contents) {
+        Ok(config) => config,
+        Err(e) => {
+            return vec![ConfigProblem {
+                path: Vec::new(),
+                line: e.span().map(|span| line_at(contents, span.start)),
+                message: e.message().to_string(),
+                suggestion: None,
+            }]
+        }
+    };
+
+    let mut problems = config.problems();
+    problems.extend(environment(&config));
+    for problem in &mut problems {
+        problem.line = locate(&doc, &problem.path);
+    }
+    problems.sort_by(|a, b| {
+        (a.line.is_none(), a.line, a.key()).cmp(&(b.line.is_none(), b.line, b.key()))
+    });
+    problems
+}
+
+/// Every problem with the configuration file at `path`; see [`check_config`]
+///
+/// # Errors
+/// Returns an error if the file cannot be read
+pub fn check_config_file(
+    path: &Path,
+    known_targets: Option<&[String]>,
+) -> Result<Vec<ConfigProblem>> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        Error::Config(format!(
+            "Failed to read config file {}: {e}",
+            path.display()
+        ))
+    })?;
+    Ok(check_config(&contents, known_targets))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known() -> Vec<String> {
+        [
+            "x86_64-unknown-linux-gnu",
+            "aarch64-unknown-linux-gnu",
+            "wasm32-wasip1",
+        ]
+        .map(String::from)
+        .to_vec()
+    }
+
+    #[test]
+    fn test_check_config_reports_every_problem() {
+        let contents = r#"[targets]
+default = ["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gun", "@mobile"]
+
+[targets."wasm32-wasi"]
+linker = "definitely-not-a-linker-xcargo"
+
+[container]
+use_when = "target.os <> host.os"
+
+[profiles.release]
+targets = ["x86_64"]
+"#;
+        let known = known();
+        let problems = check_config(contents, Some(&known));
+        let summary: Vec<(Option<usize>, String)> =
+            problems.iter().map(|p| (p.line, p.key())).collect();
+
+        assert_eq!(
+            summary,
+            [
+                (Some(2), "targets.default".to_string()),
+                (Some(2), "targets.default[1]".to_string()),
+                (Some(5), "targets.wasm32-wasi.linker".to_string()),
+                (Some(8), "container.use_when".to_string()),
+                (Some(11), "profiles.release.targets[0]".to_string()),
+            ]
+        );
+        assert_eq!(
+            problems[1].suggestion.as_deref(),
+            Some("Did you mean 'aarch64-unknown-linux-gnu'?")
+        );
+        assert!(problems[4].message.contains("Invalid target triple"));
+    }
+
+    #[test]
+    fn test_check_config_parse_errors() {
+        let problems = check_config("[build]\njobs = \n", None);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(2));
+
+        let problems = check_config("[build]\nparallel = true\njobs = \"four\"\n", None);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(3));
+
+        assert!(check_config("[build]\njobs = 4\n", Some(&known())).is_empty());
+    }
+
+    #[test]
+    fn test_problem_key() {
+        let problem = ConfigProblem::new(
+            &["runners", "x86_64-unknown-linux-gnu.2.17", "test_timeout"],
+            "",
+        );
+        assert_eq!(
+            problem.key(),
+            "runners.\"x86_64-unknown-linux-gnu.2.17\".test_timeout"
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Minimum xcargo version required by a project
+//!
+//! `required_version = ">=0.5"` in xcargo.toml makes older xcargo binaries
+//! stop with an upgrade message before they try to make sense of
+//! configuration keys they don't know about. Requirements use Cargo's syntax:
+//! comma-separated comparators such as `>=0.5`, `<1.0`, `^0.5`, `~0.5.2`, or
+//! a bare `0.5` (the same as `^0.5`).
+
+use crate::error::{Error, Result};
+use std::path::Path;
+use toml::Table;
+
+/// Version of the running xcargo
+#[must_use]
+pub fn current_version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// Whether `version` satisfies the requirement `req`
+///
+/// # Errors
+/// Returns an error if the requirement or the version cannot be parsed
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::config::version_matches;
+///
+/// assert!(version_matches(">=0.5", "0.5.2").unwrap());
+/// assert!(!version_matches(">=0.5", "0.4.9").unwrap());
+/// assert!(version_matches(">=0.3, <1.0", "0.9.0").unwrap());
+/// assert!(!version_matches("0.5", "0.6.0").unwrap());
+/// ```
+pub fn version_matches(req: &str, version: &str) -> Result<bool> {
+    let (major, minor, patch) = parse_partial(version)
+        .filter(|(_, minor, patch)| minor.is_some() && patch.is_some())
+        .ok_or_else(|| Error::Config(format!("Invalid version '{version}'")))?;
+    let version = (major, minor.unwrap_or(0), patch.unwrap_or(0));
+
+    let comparators: Vec<&str> = req.split(',').map(str::trim).collect();
+    if comparators.iter().any(|c| c.is_empty()) {
+        return Err(invalid_requirement(req));
+    }
+    for comparator in comparators {
+        if !comparator_matches(comparator, version).ok_or_else(|| invalid_requirement(req))? {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+/// Check a configuration layer's `required_version` against this xcargo
+///
+/// Runs on the raw TOML so that a project relying on newer configuration
+/// keys fails with an upgrade message rather than a parse error.
+///
+/// # Errors
+/// Returns `Error::VersionRequired` if this xcargo is too old or too new,
+/// or an error if the requirement is invalid
+pub(super) fn check_required_version(table: &Table, path: &Path) -> Result<()> {
+    let Some(value) = table.get("required_version") else {
+        return Ok(());
+    };
+    let required = value.as_str().ok_or_else(|| {
+        Error::Config(format!(
+            "required_version in {} must be a string, e.g. \">=0.5\"",
+            path.display()
+        ))
+    })?;
+    let matches = version_matches(required, current_version()).map_err(|_| {
+        Error::Config(format!(
+            "Invalid required_version '{required}' in {}. Use a requirement such as \">=0.5\"",
+            path.display()
+        ))
+    })?;
+    if matches {
+        Ok(())
+    } else {
+        Err(Error::VersionRequired {
+            required: required.to_string(),
+            current: current_version().to_string(),
+            path: path.display().to_string(),
+        })
+    }
+}
+
+fn invalid_requirement(req: &str) -> Error {
+    Error::Config(format!("Invalid version requirement '{req}'"))
+}
+
+type Partial = (u64, Option<u64>, Option<u64>);
+
+/// Parse "1", "1.2", or "1.2.3"; a pre-release suffix is ignored
+fn parse_partial(version: &str) -> Option<Partial> {
+    let version = version
+        .split_once('-')
+        .map_or(version, |(release, _)| release);
+    let mut parts = version.trim().split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map(str::parse).transpose().ok()?;
+    let patch = parts.next().map(str::parse).transpose().ok()?;
+    if parts.next().is_some() || (minor.is_none() && patch.is_some()) {
+        return None;
+    }
+    Some((major, minor, patch))
+}
+
+/// Lowest version above everything `partial` matches, e.g. 1.3.0 for "1.2"
+fn bump(partial: Partial) -> (u64, u64, u64) {
+    match partial {
+        (major, None, _) => (major + 1, 0, 0),
+        (major, Some(minor), None) => (major, minor + 1, 0),
+        (major, Some(minor), Some(patch)) => (major, minor, patch + 1),
+    }
+}
+
+/// Evaluate one comparator with Cargo's rules, or `None` if it's invalid
+fn comparator_matches(comparator: &str, version: (u64, u64, u64)) -> Option<bool> {
+    if comparator == "*" {
+        return Some(true);
+    }
+    let (op, rest) = [">=", "<=", ">", "<", "=", "^", "~"]
+        .iter()
+        .find_map(|op| comparator.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("^", comparator));
+    let partial = parse_partial(rest)?;
+    let (major, minor, patch) = partial;
+    let lower = (major, minor.unwrap_or(0), patch.unwrap_or(0));
+
+    Some(match op {
+        ">=" => version >= lower,
+        ">" => version >= bump(partial) || (patch.is_some() && version > lower),
+        "<" => version < lower,
+        "<=" => version < bump(partial),
+        "=" => version >= lower && version < bump(partial),
+        "~" => version >= lower && version < bump((major, minor, None)),
+        // Caret: compatible updates, where 0.x releases are breaking
+        _ => {
+            let upper = match partial {
+                (0, Some(0), Some(_)) => bump(partial),
+                (0, Some(minor), _) => (0, minor + 1, 0),
+                _ => (major + 1, 0, 0),
+            };
+            version >= lower && version < upper
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comparators() {
+        let matches = |req: &str, version: &str| version_matches(req, version).unwrap();
+
+        assert!(matches(">0.5", "0.6.0"));
+        assert!(!matches(">0.5", "0.5.9"));
+        assert!(matches(">0.5.1", "0.5.2"));
+        assert!(matches("<=0.5", "0.5.9"));
+        assert!(!matches("<0.5", "0.5.0"));
+        assert!(matches("=0.5", "0.5.3"));
+        assert!(!matches("=0.5.1", "0.5.3"));
+        assert!(matches("~0.5.1", "0.5.7"));
+        assert!(!matches("~0.5.1", "0.6.0"));
+        assert!(matches("^1.2", "1.9.0"));
+        assert!(!matches("^1.2", "2.0.0"));
+        assert!(!matches("^0.0.3", "0.0.4"));
+        assert!(matches("*", "0.1.0"));
+    }
+
+    #[test]
+    fn test_invalid_requirements() {
+        for req in ["", ">=", ">=0.x", "0.5,", ">=1.2.3.4"] {
+            assert!(version_matches(req, "0.5.0").is_err(), "{req}");
+        }
+        assert!(version_matches(">=0.5", "0.5").is_err());
+    }
+
+    #[test]
+    fn test_check_required_version() {
+        let path = Path::new("xcargo.toml");
+        let table =
+            |req: &str| -> Table { format!("required_version = \"{req}\"").parse().unwrap() };
+
+        assert!(check_required_version(&Table::new(), path).is_ok());
+        assert!(check_required_version(&table(">=0.1"), path).is_ok());
+        assert!(matches!(
+            check_required_version(&table(">=99.0"), path),
+            Err(Error::VersionRequired { .. })
+        ));
+        assert!(matches!(
+            check_required_version(&table("newest"), path),
+            Err(Error::Config(_))
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+//! `container.volumes` entries
+//!
+//! An entry mounts a host path or a named volume into build containers, in
+//! the runtime's own `source:destination[:options]` form, such as
+//! `./vendor:/vendor:ro`.
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// A volume mounted into build containers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumeMount {
+    /// Host path, relative to the project directory, or a named volume
+    pub source: String,
+
+    /// Absolute path inside the container
+    pub destination: String,
+
+    /// Mount options such as "ro", if any
+    pub options: Option<String>,
+}
+
+impl VolumeMount {
+    /// Whether `source` is a path on the host rather than a named volume
+    #[must_use]
+    pub fn is_host_path(&self) -> bool {
+        self.source.starts_with(['.', '/', '~'])
+            || self.source.contains(['/', '\\'])
+            || Path::new(&self.source).is_absolute()
+    }
+
+    /// Host side and container side of the mount, with a relative source
+    /// resolved against `project_dir`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::path::Path;
+    /// use xcargo::config::VolumeMount;
+    ///
+    /// let mount: VolumeMount = "./vendor:/vendor:ro".parse().unwrap();
+    /// let (host, container) = mount.resolve(Path::new("/work/app"));
+    /// assert_eq!(host, "/work/app/./vendor");
+    /// assert_eq!(container, "/vendor:ro");
+    /// ```
+    #[must_use]
+    pub fn resolve(&self, project_dir: &Path) -> (String, String) {
+        let source = if !self.is_host_path() || Path::new(&self.source).is_absolute() {
+            self.source.clone()
+        } else if let Some(rest) = self.source.strip_prefix("~/") {
+            dirs::home_dir().map_or_else(
+                || self.source.clone(),
+                |home| home.join(rest).to_string_lossy().to_string(),
+            )
+        } else {
+            project_dir.join(&self.source).to_string_lossy().to_string()
+        };
+        let destination = match &self.options {
+            Some(options) => format!("{}:{options}", self.destination),
+            None => self.destination.clone(),
+        };
+        (source, destination)
+    }
+}
+
+impl FromStr for VolumeMount {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!("cannot read volume '{s}': expected source:/destination[:options], e.g. ./vendor:/vendor:ro")
+        };
+        // The source may contain ':' itself (C:\vendor), so read from the end
+        let (rest, last) = s.rsplit_once(':').ok_or_else(invalid)?;
+        let (source, destination, options) = if last.starts_with('/') {
+            (rest, last, None)
+        } else {
+            let (source, destination) = rest.rsplit_once(':').ok_or_else(invalid)?;
+            (source, destination, Some(last))
+        };
+        if source.is_empty() || !destination.starts_with('/') || options == Some("") {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            options: options.map(str::to_string),
+        })
+    }
+}
+
+impl fmt::Display for VolumeMount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.source, self.destination)?;
+        if let Some(options) = &self.options {
+            write!(f, ":{options}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_volume() {
+        let mount: VolumeMount = "./vendor:/vendor:ro".parse().unwrap();
+        assert_eq!(mount.source, "./vendor");
+        assert_eq!(mount.destination, "/vendor");
+        assert_eq!(mount.options.as_deref(), Some("ro"));
+        assert!(mount.is_host_path());
+        assert_eq!(mount.to_string(), "./vendor:/vendor:ro");
+
+        let mount: VolumeMount = r"C:\deps:/deps".parse().unwrap();
+        assert_eq!(mount.source, r"C:\deps");
+        assert_eq!(mount.options, None);
+
+        let mount: VolumeMount = "sccache:/sccache".parse().unwrap();
+        assert!(!mount.is_host_path());
+        assert_eq!(
+            mount.resolve(Path::new("/work")),
+            ("sccache".to_string(), "/sccache".to_string())
+        );
+
+        assert!("./vendor".parse::<VolumeMount>().is_err());
+        assert!("./vendor:vendor".parse::<VolumeMount>().is_err());
+        assert!(":/vendor".parse::<VolumeMount>().is_err());
+        assert!("./vendor:/vendor:".parse::<VolumeMount>().is_err());
+    }
+}
@@ -0,0 +1,141 @@
+//! Writing configuration files without losing them
+//!
+//! A configuration file is never written in place: the new contents go to a
+//! temporary file next to it, which is flushed to disk and then renamed over
+//! the file, so a crash or a full disk leaves either the old or the new
+//! version, never a truncated one. The previous version is kept as
+//! `<file>.bak`.
+
+use crate::error::{Error, Result};
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Where the previous version of a configuration file is kept
+///
+/// # Examples
+///
+/// ```
+/// use std::path::Path;
+/// use xcargo::config::backup_path;
+///
+/// assert_eq!(backup_path(Path::new("app/xcargo.toml")), Path::new("app/xcargo.toml.bak"));
+/// ```
+#[must_use]
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+/// Replace the configuration file at `path` with `contents` atomically,
+/// keeping the previous version as [`backup_path`]
+///
+/// # Errors
+/// Returns an error if the file or its backup cannot be written; the file
+/// is then left as it was
+pub fn write_config_file(path: &Path, contents: &str) -> Result<()> {
+    // Replace the file a symlink points to, not the symlink
+    let path = match fs::canonicalize(path) {
+        Ok(resolved) if path.is_symlink() => resolved,
+        _ => path.to_path_buf(),
+    };
+    let temp = temp_path(&path);
+    let written = write_synced(&temp, contents, &path)
+        .and_then(|()| backup(&path))
+        .and_then(|()| fs::rename(&temp, &path));
+    written.map_err(|e| {
+        let _ = fs::remove_file(&temp);
+        Error::Config(format!(
+            "Failed to write config file {}: {e}",
+            path.display()
+        ))
+    })
+}
+
+/// Hidden file next to `path` the new contents are written to first
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(format!(".{}.tmp", std::process::id()));
+    path.with_file_name(name)
+}
+
+/// Write `contents` to `temp` and flush them to disk, with the permissions
+/// of the file it replaces
+fn write_synced(temp: &Path, contents: &str, replaces: &Path) -> std::io::Result<()> {
+    let mut file = File::create(temp)?;
+    file.write_all(contents.as_bytes())?;
+    file.sync_all()?;
+    if let Ok(metadata) = fs::metadata(replaces) {
+        fs::set_permissions(temp, metadata.permissions())?;
+    }
+    Ok(())
+}
+
+/// Keep the current version of `path` as its backup
+///
+/// A hard link keeps the old contents once the file is replaced, without a
+/// window in which the backup is half-written; filesystems without links
+/// get a copy.
+fn backup(path: &Path) -> std::io::Result<()> {
+    if !path.is_file() {
+        return Ok(());
+    }
+    let backup = backup_path(path);
+    match fs::remove_file(&backup) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    if fs::hard_link(path, &backup).is_err() {
+        fs::copy(path, &backup)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_config_file_keeps_backup() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("xcargo.toml");
+
+        write_config_file(&path, "[build]\njobs = 2\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[build]\njobs = 2\n");
+        assert!(!backup_path(&path).exists());
+
+        write_config_file(&path, "[build]\njobs = 4\n").unwrap();
+        write_config_file(&path, "[build]\njobs = 8\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[build]\njobs = 8\n");
+        assert_eq!(
+            fs::read_to_string(backup_path(&path)).unwrap(),
+            "[build]\njobs = 4\n"
+        );
+
+        // No temporary files are left behind
+        let mut names: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["xcargo.toml", "xcargo.toml.bak"]);
+    }
+
+    #[test]
+    fn test_write_config_file_failure_leaves_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("xcargo.toml");
+        fs::write(&path, "[build]\njobs = 2\n").unwrap();
+        // A directory where the backup goes can't be replaced
+        fs::create_dir(backup_path(&path)).unwrap();
+        fs::write(backup_path(&path).join("keep"), "").unwrap();
+
+        assert!(write_config_file(&path, "[build]\njobs = 4\n").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[build]\njobs = 2\n");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+}
@@ -0,0 +1,95 @@
+//! Registry credentials for private container images
+//!
+//! Pulls go through the runtime CLI, so credentials stored by `docker login`
+//! or `podman login` are always used. In CI, where nothing is stored, the
+//! `XCARGO_REGISTRY_USERNAME` and `XCARGO_REGISTRY_PASSWORD` variables log in
+//! to the configured registries before their images are pulled.
+
+use crate::error::{Error, Result};
+use std::fmt;
+
+/// Variable holding the registry user name
+pub const USERNAME_ENV: &str = "XCARGO_REGISTRY_USERNAME";
+
+/// Variable holding the registry password or access token
+pub const PASSWORD_ENV: &str = "XCARGO_REGISTRY_PASSWORD";
+
+/// User name and password for a container registry
+#[derive(Clone, PartialEq, Eq)]
+pub struct RegistryCredentials {
+    /// User name, e.g. a GitHub user or "AWS" for ECR
+    pub username: String,
+
+    /// Password or access token
+    pub password: String,
+}
+
+impl RegistryCredentials {
+    /// Credentials from `XCARGO_REGISTRY_USERNAME` and
+    /// `XCARGO_REGISTRY_PASSWORD`, if both are set
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        Self::from_vars(
+            std::env::var(USERNAME_ENV).ok(),
+            std::env::var(PASSWORD_ENV).ok(),
+        )
+    }
+
+    fn from_vars(username: Option<String>, password: Option<String>) -> Option<Self> {
+        let username = username.filter(|v| !v.is_empty())?;
+        let password = password.filter(|v| !v.is_empty())?;
+        Some(Self { username, password })
+    }
+
+    /// Credentials from `username` and `password`, falling back to
+    /// `XCARGO_REGISTRY_USERNAME` and `XCARGO_REGISTRY_PASSWORD`
+    pub fn resolve(username: Option<String>, password: Option<String>) -> Result<Self> {
+        let username = username
+            .or_else(|| std::env::var(USERNAME_ENV).ok())
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| {
+                Error::Container(format!(
+                    "No registry user name: pass --username or set {USERNAME_ENV}"
+                ))
+            })?;
+        let password = password
+            .or_else(|| std::env::var(PASSWORD_ENV).ok())
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| {
+                Error::Container(format!(
+                    "No registry password: set {PASSWORD_ENV} or pass --password-stdin"
+                ))
+            })?;
+        Ok(Self { username, password })
+    }
+}
+
+// Keep the password out of logs and error messages
+impl fmt::Debug for RegistryCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RegistryCredentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credentials_from_vars() {
+        let credentials =
+            RegistryCredentials::from_vars(Some("octocat".to_string()), Some("ghp_x".to_string()))
+                .unwrap();
+        assert_eq!(credentials.username, "octocat");
+        assert!(!format!("{credentials:?}").contains("ghp_x"));
+
+        assert!(RegistryCredentials::from_vars(Some("octocat".to_string()), None).is_none());
+        assert!(
+            RegistryCredentials::from_vars(Some("octocat".to_string()), Some(String::new()))
+                .is_none()
+        );
+    }
+}
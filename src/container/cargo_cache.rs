@@ -0,0 +1,156 @@
+//! Cargo's registry and git caches in build containers
+//!
+//! By default the caches live in named volumes, one pair per toolchain, so
+//! containers never write root-owned files into the host's cargo home.
+
+use crate::error::{Error, Result};
+
+/// Caches shared between container builds
+const CACHES: [&str; 2] = ["registry", "git"];
+
+/// Where containers keep cargo's registry and git caches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CargoCache {
+    /// Named volumes per toolchain, e.g. `xcargo-cargo-registry-stable`
+    #[default]
+    Volume,
+    /// The host's cargo home
+    Host,
+    /// No cache; every build downloads its dependencies
+    None,
+}
+
+impl CargoCache {
+    /// Parse from a `container.cargo_cache` value
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "volume" => Ok(Self::Volume),
+            "host" => Ok(Self::Host),
+            "none" => Ok(Self::None),
+            _ => Err(Error::Config(format!("Unknown cargo cache: {s}"))),
+        }
+    }
+}
+
+/// Name of the volume holding `cache` for `toolchain`
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::container::cache_volume_name;
+///
+/// assert_eq!(cache_volume_name("registry", "1.78.0"), "xcargo-cargo-registry-1.78.0");
+/// assert_eq!(cache_volume_name("git", "nightly/x"), "xcargo-cargo-git-nightly-x");
+/// ```
+#[must_use]
+pub fn cache_volume_name(cache: &str, toolchain: &str) -> String {
+    let toolchain: String = toolchain
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("xcargo-cargo-{cache}-{toolchain}")
+}
+
+/// Mounts for the caches of `toolchain` builds
+///
+/// Podman chowns a volume to the mapped user with `:U`; with other runtimes
+/// the volumes are prepared by [`needs_chown`] instead.
+#[must_use]
+pub fn cargo_cache_mounts(
+    cache: CargoCache,
+    toolchain: &str,
+    runtime: &str,
+    user_args: &[String],
+) -> Vec<(String, String)> {
+    match cache {
+        CargoCache::Volume => CACHES
+            .iter()
+            .map(|cache| {
+                let mut destination = format!("/root/.cargo/{cache}");
+                if runtime == "podman" && !user_args.is_empty() {
+                    destination.push_str(":U");
+                }
+                (cache_volume_name(cache, toolchain), destination)
+            })
+            .collect(),
+        CargoCache::Host => crate::toolchain::cargo_home()
+            .map(|cargo_home| {
+                CACHES
+                    .iter()
+                    .map(|cache| {
+                        (
+                            cargo_home.join(cache).to_string_lossy().to_string(),
+                            format!("/root/.cargo/{cache}"),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default(),
+        CargoCache::None => Vec::new(),
+    }
+}
+
+/// The `uid:gid` that cache volumes must be chowned to before the build, if
+/// the runtime cannot do it itself
+#[must_use]
+pub fn needs_chown(cache: CargoCache, runtime: &str, user_args: &[String]) -> Option<String> {
+    if cache != CargoCache::Volume || runtime == "podman" {
+        return None;
+    }
+    user_args
+        .iter()
+        .position(|arg| arg == "--user")
+        .and_then(|idx| user_args.get(idx + 1))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cargo_cache_from_str() {
+        assert_eq!(CargoCache::from_str("volume").unwrap(), CargoCache::Volume);
+        assert_eq!(CargoCache::from_str("host").unwrap(), CargoCache::Host);
+        assert_eq!(CargoCache::from_str("none").unwrap(), CargoCache::None);
+        assert!(CargoCache::from_str("shared").is_err());
+    }
+
+    #[test]
+    fn test_volume_mounts() {
+        let user = vec!["--user".to_string(), "1000:1000".to_string()];
+
+        let mounts = cargo_cache_mounts(CargoCache::Volume, "stable", "docker", &user);
+        assert_eq!(
+            mounts,
+            [
+                (
+                    "xcargo-cargo-registry-stable".to_string(),
+                    "/root/.cargo/registry".to_string()
+                ),
+                (
+                    "xcargo-cargo-git-stable".to_string(),
+                    "/root/.cargo/git".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            needs_chown(CargoCache::Volume, "docker", &user).as_deref(),
+            Some("1000:1000")
+        );
+
+        let keep_id = vec!["--userns=keep-id".to_string()];
+        let mounts = cargo_cache_mounts(CargoCache::Volume, "stable", "podman", &keep_id);
+        assert!(mounts.iter().all(|(_, dest)| dest.ends_with(":U")));
+        assert_eq!(needs_chown(CargoCache::Volume, "podman", &keep_id), None);
+
+        assert!(cargo_cache_mounts(CargoCache::None, "stable", "docker", &user).is_empty());
+        assert_eq!(needs_chown(CargoCache::Host, "docker", &user), None);
+    }
+}
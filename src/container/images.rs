@@ -1,6 +1,7 @@
 //! Container image selection for cross-compilation targets
 
 use crate::error::{Error, Result};
+use std::collections::HashMap;
 
 /// Container image information
 #[derive(Debug, Clone)]
@@ -21,12 +22,65 @@ impl CrossImage {
     pub fn full_name(&self) -> String {
         format!("{}:{}", self.repository, self.tag)
     }
+
+    /// Registry host the image is pulled from, e.g. "ghcr.io"
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::container::CrossImage;
+    ///
+    /// let image = CrossImage::from_reference("ghcr.io/myorg/cross:1.0", "x86_64-pc-windows-gnu");
+    /// assert_eq!(image.registry(), "ghcr.io");
+    /// let image = CrossImage::from_reference("rust:latest", "x86_64-unknown-linux-gnu");
+    /// assert_eq!(image.registry(), "docker.io");
+    /// ```
+    #[must_use]
+    pub fn registry(&self) -> &str {
+        match self.repository.split_once('/') {
+            Some((host, _)) if host.contains(['.', ':']) || host == "localhost" => host,
+            _ => "docker.io",
+        }
+    }
+
+    /// Image for `target` from a reference such as `ghcr.io/org/image:1.0`;
+    /// the tag defaults to "latest"
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::container::CrossImage;
+    ///
+    /// let image = CrossImage::from_reference("localhost:5000/cross", "x86_64-pc-windows-gnu");
+    /// assert_eq!(image.repository, "localhost:5000/cross");
+    /// assert_eq!(image.full_name(), "localhost:5000/cross:latest");
+    /// ```
+    #[must_use]
+    pub fn from_reference(reference: &str, target: &str) -> Self {
+        // A ':' after the last '/' is a tag, not a registry port
+        let name_start = reference.rfind('/').map_or(0, |idx| idx + 1);
+        let (repository, tag) = match reference[name_start..].rfind(':') {
+            Some(idx) => (
+                &reference[..name_start + idx],
+                &reference[name_start + idx + 1..],
+            ),
+            None => (reference, "latest"),
+        };
+        Self {
+            repository: repository.to_string(),
+            tag: tag.to_string(),
+            target: target.to_string(),
+        }
+    }
 }
 
 /// Image selector for choosing appropriate images
 pub struct ImageSelector {
     /// Image registry (default: ghcr.io/cross-rs)
     registry: String,
+
+    /// Images configured per target, consulted before the built-in table
+    images: HashMap<String, String>,
 }
 
 impl ImageSelector {
@@ -35,17 +89,63 @@ impl ImageSelector {
     pub fn new() -> Self {
         Self {
             registry: "ghcr.io/cross-rs".to_string(),
+            images: HashMap::new(),
         }
     }
 
     /// Create with custom registry
     #[must_use]
     pub fn with_registry(registry: String) -> Self {
-        Self { registry }
+        Self {
+            registry,
+            images: HashMap::new(),
+        }
+    }
+
+    /// Create from the `[container]` section: its registry and per-target
+    /// `images`
+    #[must_use]
+    pub fn from_config(config: &crate::config::ContainerConfig) -> Self {
+        let mut selector = config
+            .registry
+            .clone()
+            .map_or_else(Self::new, Self::with_registry);
+        selector.images = config.images.clone();
+        selector
+    }
+
+    /// Registry hosts set in the configuration, through `registry` or the
+    /// per-target `images`
+    #[must_use]
+    pub fn configured_registries(&self) -> Vec<String> {
+        let registry = (self.registry != Self::new().registry)
+            .then(|| CrossImage::from_reference(&format!("{}/image", self.registry), ""));
+        let mut hosts: Vec<String> = registry
+            .into_iter()
+            .chain(
+                self.images
+                    .iter()
+                    .map(|(target, reference)| CrossImage::from_reference(reference, target)),
+            )
+            .map(|image| image.registry().to_string())
+            .collect();
+        hosts.sort();
+        hosts.dedup();
+        hosts
+    }
+
+    /// Whether the image for `target` comes from the configuration
+    #[must_use]
+    pub fn is_configured(&self, target: &str) -> bool {
+        self.images.contains_key(target)
     }
 
     /// Select appropriate image for a target
     pub fn select_for_target(&self, target: &str) -> Result<CrossImage> {
+        if let Some(reference) = self.images.get(target) {
+            return Ok(CrossImage::from_reference(reference, target));
+        }
+
         let (image_name, tag) = match target {
             // Linux targets
             "x86_64-unknown-linux-gnu" => ("x86_64-unknown-linux-gnu", "latest"),
@@ -54,10 +154,22 @@ impl ImageSelector {
             "aarch64-unknown-linux-musl" => ("aarch64-unknown-linux-musl", "latest"),
             "armv7-unknown-linux-gnueabihf" => ("armv7-unknown-linux-gnueabihf", "latest"),
             "arm-unknown-linux-gnueabihf" => ("arm-unknown-linux-gnueabihf", "latest"),
+            "riscv64gc-unknown-linux-gnu" => ("riscv64gc-unknown-linux-gnu", "latest"),
+            "s390x-unknown-linux-gnu" => ("s390x-unknown-linux-gnu", "latest"),
 
             // Windows targets
             "x86_64-pc-windows-gnu" => ("x86_64-pc-windows-gnu", "latest"),
 
+            // BSD targets, with the system's libc and a cross toolchain
+            "x86_64-unknown-freebsd" => ("x86_64-unknown-freebsd", "latest"),
+            "i686-unknown-freebsd" => ("i686-unknown-freebsd", "latest"),
+            "x86_64-unknown-netbsd" => ("x86_64-unknown-netbsd", "latest"),
+            "x86_64-unknown-openbsd" | "aarch64-unknown-openbsd" => {
+                return Err(Error::Container(format!(
+                    "No container image available for OpenBSD target: {target}\nOpenBSD has no stable system ABI to cross-compile against; build on OpenBSD"
+                )));
+            }
+
             // macOS targets - cross-rs doesn't have macOS images, would need osxcross
             "x86_64-apple-darwin" | "aarch64-apple-darwin" => {
                 return Err(Error::Container(format!(
@@ -81,7 +193,7 @@ impl ImageSelector {
             // Unknown target
             _ => {
                 return Err(Error::Container(format!(
-                    "No container image mapping for target: {target}\nYou can specify a custom image in xcargo.toml"
+                    "No container image mapping for target: {target}\nSet one in xcargo.toml under [container.images]"
                 )));
             }
         };
@@ -104,8 +216,14 @@ impl ImageSelector {
             "aarch64-unknown-linux-musl",
             "armv7-unknown-linux-gnueabihf",
             "arm-unknown-linux-gnueabihf",
+            "riscv64gc-unknown-linux-gnu",
+            "s390x-unknown-linux-gnu",
             // Windows
             "x86_64-pc-windows-gnu",
+            // BSD
+            "x86_64-unknown-freebsd",
+            "i686-unknown-freebsd",
+            "x86_64-unknown-netbsd",
             // Android
             "aarch64-linux-android",
             "armv7-linux-androideabi",
@@ -142,6 +260,25 @@ mod tests {
         assert_eq!(image.target, "x86_64-pc-windows-gnu");
     }
 
+    #[test]
+    fn test_select_bsd_and_server_targets() {
+        let selector = ImageSelector::new();
+        let image = selector
+            .select_for_target("x86_64-unknown-freebsd")
+            .unwrap();
+        assert_eq!(
+            image.full_name(),
+            "ghcr.io/cross-rs/x86_64-unknown-freebsd:latest"
+        );
+        assert!(selector.select_for_target("x86_64-unknown-netbsd").is_ok());
+        assert!(selector
+            .select_for_target("s390x-unknown-linux-gnu")
+            .is_ok());
+        assert!(selector
+            .select_for_target("x86_64-unknown-openbsd")
+            .is_err());
+    }
+
     #[test]
     fn test_macos_target_returns_error() {
         let selector = ImageSelector::new();
@@ -162,6 +299,72 @@ mod tests {
         assert!(selector.select_for_target("unknown-target").is_err());
     }
 
+    #[test]
+    fn test_configured_image_takes_precedence() {
+        let config = crate::config::ContainerConfig {
+            registry: Some("registry.example.com/cross".to_string()),
+            images: HashMap::from([(
+                "aarch64-unknown-linux-gnu".to_string(),
+                "ghcr.io/myorg/cross-aarch64:1.2".to_string(),
+            )]),
+            ..crate::config::ContainerConfig::default()
+        };
+        let selector = ImageSelector::from_config(&config);
+
+        let image = selector
+            .select_for_target("aarch64-unknown-linux-gnu")
+            .unwrap();
+        assert_eq!(image.repository, "ghcr.io/myorg/cross-aarch64");
+        assert_eq!(image.tag, "1.2");
+        assert!(selector.is_configured("aarch64-unknown-linux-gnu"));
+
+        let image = selector.select_for_target("x86_64-pc-windows-gnu").unwrap();
+        assert_eq!(
+            image.full_name(),
+            "registry.example.com/cross/x86_64-pc-windows-gnu:latest"
+        );
+        assert!(!selector.is_configured("x86_64-pc-windows-gnu"));
+    }
+
+    #[test]
+    fn test_configured_registries() {
+        assert!(ImageSelector::new().configured_registries().is_empty());
+
+        let config = crate::config::ContainerConfig {
+            registry: Some("123456789.dkr.ecr.us-east-1.amazonaws.com/cross".to_string()),
+            images: HashMap::from([
+                (
+                    "aarch64-unknown-linux-gnu".to_string(),
+                    "ghcr.io/myorg/cross-aarch64".to_string(),
+                ),
+                (
+                    "x86_64-unknown-linux-gnu".to_string(),
+                    "ghcr.io/myorg/cross-x86_64".to_string(),
+                ),
+            ]),
+            ..crate::config::ContainerConfig::default()
+        };
+        assert_eq!(
+            ImageSelector::from_config(&config).configured_registries(),
+            ["123456789.dkr.ecr.us-east-1.amazonaws.com", "ghcr.io"]
+        );
+    }
+
+    #[test]
+    fn test_configured_image_for_unmapped_target() {
+        let config = crate::config::ContainerConfig {
+            images: HashMap::from([(
+                "x86_64-apple-darwin".to_string(),
+                "ghcr.io/myorg/osxcross".to_string(),
+            )]),
+            ..crate::config::ContainerConfig::default()
+        };
+        let image = ImageSelector::from_config(&config)
+            .select_for_target("x86_64-apple-darwin")
+            .unwrap();
+        assert_eq!(image.full_name(), "ghcr.io/myorg/osxcross:latest");
+    }
+
     #[test]
     fn test_supported_targets_not_empty() {
         let selector = ImageSelector::new();
@@ -4,12 +4,25 @@
 //! cross-compilation toolchains are not available or practical.
 
 use crate::error::{Error, Result};
+use std::sync::{Mutex, PoisonError};
 
+mod auth;
+mod cargo_cache;
 mod images;
+mod patches;
 mod runtime;
 
+pub use auth::{RegistryCredentials, PASSWORD_ENV, USERNAME_ENV};
+pub use cargo_cache::{cache_volume_name, cargo_cache_mounts, needs_chown, CargoCache};
 pub use images::{CrossImage, ImageSelector};
-pub use runtime::{ContainerRuntime, RuntimeType};
+pub use patches::{
+    cargo_credentials, find_external_sources, plan_mounts, CredentialsPolicy, ExternalSource,
+    SourceLocation, SourceMounts,
+};
+pub use runtime::{
+    host_volume_path, path_owner, user_mapping_args, CapturedRun, ContainerRuntime, ImageBuild, PullPolicy, RunSpec,
+    RuntimeInfo, RuntimeType,
+};
 
 /// Container build configuration
 #[derive(Debug, Clone)]
@@ -26,8 +39,30 @@ pub struct ContainerConfig {
     /// Environment variables to pass to container
     pub env: Vec<(String, String)>,
 
+    /// Environment variables holding secrets, kept out of logs and the
+    /// runtime's arguments
+    pub secrets: Vec<(String, String)>,
+
     /// Working directory inside container
     pub workdir: String,
+
+    /// Run as the host user so build outputs are not owned by root
+    pub map_user: bool,
+
+    /// Cargo subcommand to run, e.g. "build" or "install"
+    pub cargo_command: String,
+
+    /// When the image is pulled before the build
+    pub pull_policy: PullPolicy,
+
+    /// Where cargo's registry and git caches are kept
+    pub cargo_cache: CargoCache,
+
+    /// Toolchain the cache volumes belong to, e.g. "stable"
+    pub toolchain: String,
+
+    /// Time the build may take before its container is removed
+    pub timeout: Option<std::time::Duration>,
 }
 
 impl Default for ContainerConfig {
@@ -37,7 +72,14 @@ impl Default for ContainerConfig {
             image: String::new(),
             volumes: Vec::new(),
             env: Vec::new(),
+            secrets: Vec::new(),
             workdir: "/project".to_string(),
+            map_user: true,
+            cargo_command: "build".to_string(),
+            pull_policy: PullPolicy::IfNotPresent,
+            cargo_cache: CargoCache::Volume,
+            toolchain: "stable".to_string(),
+            timeout: None,
         }
     }
 }
@@ -46,6 +88,8 @@ impl Default for ContainerConfig {
 pub struct ContainerBuilder {
     runtime: Box<dyn ContainerRuntime>,
     image_selector: ImageSelector,
+    credentials: Option<RegistryCredentials>,
+    logged_in: Mutex<Vec<String>>,
 }
 
 impl ContainerBuilder {
@@ -57,9 +101,18 @@ impl ContainerBuilder {
         Ok(Self {
             runtime,
             image_selector,
+            credentials: RegistryCredentials::from_env(),
+            logged_in: Mutex::new(Vec::new()),
         })
     }
 
+    /// Select images with `image_selector` instead of the built-in table
+    #[must_use]
+    pub fn with_image_selector(mut self, image_selector: ImageSelector) -> Self {
+        self.image_selector = image_selector;
+        self
+    }
+
     /// Check if the container runtime is available
     #[must_use]
     pub fn is_available(&self) -> bool {
@@ -72,6 +125,171 @@ impl ContainerBuilder {
         self.runtime.name()
     }
 
+    /// Detect the runtime's active context, endpoint and rootless mode
+    #[must_use]
+    pub fn runtime_info(&self) -> RuntimeInfo {
+        self.runtime.info()
+    }
+
+    /// Runtime flags that map the owner of `host_dir` into the container
+    fn user_args(&self, host_dir: &std::path::Path) -> Vec<String> {
+        user_mapping_args(
+            self.runtime_name(),
+            &self.runtime.info(),
+            path_owner(host_dir),
+        )
+    }
+
+    /// Verify that a container can write to a bind-mounted host directory
+    ///
+    /// A probe file is created from inside `image` in a scratch directory
+    /// under `host_dir`; the check fails if the write is rejected or the file
+    /// ends up owned by a different user than the directory.
+    pub fn check_volume_permissions(&self, image: &str, host_dir: &std::path::Path) -> Result<()> {
+        let scratch = host_dir.join(".xcargo-volume-check");
+        std::fs::create_dir_all(&scratch).map_err(|e| {
+            Error::Container(format!("Failed to create {}: {e}", scratch.display()))
+        })?;
+
+        let spec = RunSpec {
+            image: image.to_string(),
+            command: vec!["touch".to_string(), "/check/probe".to_string()],
+            volumes: vec![(scratch.to_string_lossy().to_string(), "/check".to_string())],
+            env: Vec::new(),
+            workdir: "/check".to_string(),
+            extra_args: self.user_args(host_dir),
+            secrets: Vec::new(),
+            timeout: None,
+        };
+
+        let result = self
+            .runtime
+            .run(&spec)
+            .map_err(|_| {
+                Error::Container(format!(
+                    "Container could not write to a volume mounted from {}",
+                    host_dir.display()
+                ))
+            })
+            .and_then(|()| {
+                let expected = path_owner(host_dir);
+                let actual = path_owner(&scratch.join("probe"));
+                match (expected, actual) {
+                    (Some(_), Some((uid, gid))) if actual != expected => Err(Error::Container(
+                        format!("Files written in the container are owned by {uid}:{gid} instead of the host user"),
+                    )),
+                    _ => Ok(()),
+                }
+            });
+
+        let _ = std::fs::remove_dir_all(&scratch);
+        result
+    }
+
+    /// Whether `image` (as `repository:tag` or `repository@digest`) has
+    /// already been pulled
+    #[must_use]
+    pub fn has_image(&self, image: &str) -> bool {
+        if image.contains('@') {
+            return self.runtime.image_digest(image).is_some();
+        }
+        self.runtime
+            .list_images()
+            .is_ok_and(|images| images.iter().any(|i| i == image))
+    }
+
+    /// Registry digest of a pulled image, e.g. "sha256:..."
+    #[must_use]
+    pub fn image_digest(&self, image: &str) -> Option<String> {
+        self.runtime.image_digest(image)
+    }
+
+    /// Pull an image, e.g. to resolve the current digest of its tag
+    pub fn pull_image(&self, image: &str) -> Result<()> {
+        crate::toolchain::require_network(&format!("pull {image}"))?;
+        self.runtime.pull_image(image)
+    }
+
+    /// Make `image` available according to `policy`, pulling it if needed
+    ///
+    /// Returns whether the image was pulled. Offline, only images that are
+    /// already present can be used.
+    pub fn ensure_image(&self, image: &str, policy: PullPolicy) -> Result<bool> {
+        let present = self.has_image(image);
+        if crate::toolchain::is_offline() {
+            return if present {
+                Ok(false)
+            } else {
+                Err(Error::Container(format!(
+                    "Image {image} has not been pulled and cannot be pulled in offline mode"
+                )))
+            };
+        }
+        match policy {
+            PullPolicy::Always => {}
+            PullPolicy::IfNotPresent if present => return Ok(false),
+            PullPolicy::IfNotPresent => {}
+            PullPolicy::Never if present => return Ok(false),
+            PullPolicy::Never => {
+                return Err(Error::Container(format!(
+                    "Image {image} is not present and container.pull_policy is \"never\""
+                )))
+            }
+        }
+        self.login_for(image)?;
+        self.runtime.pull_image(image)?;
+        Ok(true)
+    }
+
+    /// Log in to `registry`, e.g. "ghcr.io", so its private images can be pulled
+    pub fn login(&self, registry: &str, credentials: &RegistryCredentials) -> Result<()> {
+        crate::toolchain::require_network(&format!("log in to {registry}"))?;
+        self.runtime.login(registry, credentials)
+    }
+
+    /// Log in to the registry of `image` with the credentials from the
+    /// environment, once, if it is one of the configured registries
+    fn login_for(&self, image: &str) -> Result<()> {
+        let Some(credentials) = &self.credentials else {
+            return Ok(());
+        };
+        let registry = CrossImage::from_reference(image, "").registry().to_string();
+        if !self.image_selector.configured_registries().contains(&registry) {
+            return Ok(());
+        }
+
+        let mut logged_in = self.logged_in.lock().unwrap_or_else(PoisonError::into_inner);
+        if !logged_in.contains(&registry) {
+            self.login(&registry, credentials)?;
+            logged_in.push(registry);
+        }
+        Ok(())
+    }
+
+    /// Write a pulled image to a tar archive at `path`
+    pub fn save_image(&self, image: &str, path: &std::path::Path) -> Result<()> {
+        self.runtime.save_image(image, path)
+    }
+
+    /// Build an image, e.g. one packaging built binaries
+    pub fn build_image(&self, build: &ImageBuild) -> Result<()> {
+        if !self.is_available() {
+            return Err(Error::Container(format!(
+                "Container runtime '{}' is not available",
+                self.runtime_name()
+            )));
+        }
+        self.runtime.build_image(build)
+    }
+
+    /// Push a built image, logging in to its registry first if it is one of
+    /// the configured registries and credentials are set
+    pub fn push_image(&self, image: &str) -> Result<()> {
+        crate::toolchain::require_network(&format!("push {image}"))?;
+        self.login_for(image)?;
+        self.runtime.push_image(image)
+    }
+
     /// Select appropriate image for target
     pub fn select_image(&self, target: &str) -> Result<CrossImage> {
         self.image_selector.select_for_target(target)
@@ -84,6 +302,55 @@ impl ContainerBuilder {
         cargo_args: &[String],
         config: &ContainerConfig,
     ) -> Result<()> {
+        let spec = self.run_spec(target, cargo_args, config)?;
+        self.runtime.run(&spec).map_err(|e| timed_out(e, target))
+    }
+
+    /// Execute a cargo command in a container, capturing its stdout
+    ///
+    /// Unlike [`build`](Self::build), a command that fails is not an error,
+    /// so its output can still be inspected (e.g. for failing tests).
+    pub fn run_captured(
+        &self,
+        target: &str,
+        cargo_args: &[String],
+        config: &ContainerConfig,
+    ) -> Result<CapturedRun> {
+        let spec = self.run_spec(target, cargo_args, config)?;
+        self.runtime
+            .run_captured(&spec)
+            .map_err(|e| timed_out(e, target))
+    }
+
+    /// Hand named volumes to `owner` ("uid:gid"), which runs the build, as
+    /// the runtime creates them owned by root
+    fn chown_volumes(&self, image: &str, mounts: &[(String, String)], owner: &str) -> Result<()> {
+        let mut command = vec!["chown".to_string(), owner.to_string()];
+        command.extend(mounts.iter().map(|(_, destination)| destination.clone()));
+        let spec = RunSpec {
+            image: image.to_string(),
+            command,
+            volumes: mounts.to_vec(),
+            env: Vec::new(),
+            workdir: "/".to_string(),
+            extra_args: vec!["--user".to_string(), "0".to_string()],
+            secrets: Vec::new(),
+            timeout: None,
+        };
+        self.runtime.run(&spec).map_err(|_| {
+            Error::Container(format!(
+                "Could not give the cargo cache volumes to {owner}; set container.cargo_cache = \"host\" or \"none\""
+            ))
+        })
+    }
+
+    /// Pull the image and describe the `run` that executes cargo in it
+    fn run_spec(
+        &self,
+        target: &str,
+        cargo_args: &[String],
+        config: &ContainerConfig,
+    ) -> Result<RunSpec> {
         // Verify runtime is available
         if !self.is_available() {
             return Err(Error::Container(format!(
@@ -99,8 +366,8 @@ impl ContainerBuilder {
             config.image.clone()
         };
 
-        // Pull image if needed
-        self.runtime.pull_image(&image)?;
+        // Pull image if needed; offline builds can only use images already present
+        self.ensure_image(&image, config.pull_policy)?;
 
         // Build the container command
         let mut volumes = config.volumes.clone();
@@ -111,21 +378,71 @@ impl ContainerBuilder {
         let current_dir_str = current_dir.to_string_lossy().to_string();
         volumes.push((current_dir_str.clone(), config.workdir.clone()));
 
-        // Add cargo cache volume for faster builds
-        if let Ok(home) = std::env::var("HOME") {
-            let cargo_cache = format!("{home}/.cargo");
-            volumes.push((cargo_cache, "/root/.cargo".to_string()));
+        let extra_args = if config.map_user {
+            self.user_args(&current_dir)
+        } else {
+            Vec::new()
+        };
+
+        // Share cargo's download caches between builds
+        let cache_mounts = cargo_cache_mounts(
+            config.cargo_cache,
+            &config.toolchain,
+            self.runtime_name(),
+            &extra_args,
+        );
+        if let Some(owner) = needs_chown(config.cargo_cache, self.runtime_name(), &extra_args) {
+            self.chown_volumes(&image, &cache_mounts, &owner)?;
         }
+        volumes.extend(cache_mounts);
 
         // Build cargo command
-        let mut cmd = vec!["cargo".to_string(), "build".to_string()];
+        let mut cmd = vec!["cargo".to_string(), config.cargo_command.clone()];
         cmd.push("--target".to_string());
         cmd.push(target.to_string());
         cmd.extend_from_slice(cargo_args);
 
-        // Run in container
-        self.runtime
-            .run(&image, &cmd, &volumes, &config.env, &config.workdir)
+        let mut env = config.env.clone();
+        if crate::toolchain::is_offline() {
+            env.push((crate::toolchain::OFFLINE_ENV.to_string(), "true".to_string()));
+        }
+
+        // The host's proxy and CA bundle, unless container.env sets its own
+        let mut network_env = crate::toolchain::proxy_env();
+        if let Some(bundle) = crate::toolchain::ca_bundle() {
+            volumes.push((
+                bundle.to_string_lossy().to_string(),
+                format!("{}:ro", crate::toolchain::CONTAINER_CA_BUNDLE),
+            ));
+            network_env.extend(crate::toolchain::container_ca_env());
+        }
+        for (key, value) in network_env {
+            if !env.iter().any(|(set, _)| *set == key) {
+                env.push((key, value));
+            }
+        }
+
+        Ok(RunSpec {
+            image,
+            command: cmd,
+            volumes,
+            env,
+            workdir: config.workdir.clone(),
+            extra_args,
+            secrets: config.secrets.clone(),
+            timeout: config.timeout,
+        })
+    }
+}
+
+/// Report a container run that timed out as a timeout of `target`'s build
+fn timed_out(error: Error, target: &str) -> Error {
+    match error {
+        Error::BuildTimeout { timeout, .. } => Error::BuildTimeout {
+            target: target.to_string(),
+            timeout,
+        },
+        error => error,
     }
 }
 
@@ -138,6 +455,8 @@ mod tests {
         let config = ContainerConfig::default();
         assert_eq!(config.runtime, RuntimeType::Auto);
         assert_eq!(config.workdir, "/project");
+        assert!(config.map_user);
+        assert_eq!(config.cargo_command, "build");
     }
 
     #[test]
@@ -0,0 +1,561 @@
+//! Cargo `[patch]`, `[replace]`, and git dependency handling for container builds
+//!
+//! Only the project directory is mounted into a build container, so path
+//! patches pointing outside of it and git dependencies on private
+//! repositories fail inside the container. This module finds those sources
+//! from `cargo metadata` and the workspace manifest, works out the extra
+//! mounts they need, and forwards git credentials when the configured
+//! credentials policy allows it.
+
+use crate::error::{Error, Result};
+use serde::Deserialize;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+/// Where an external source points to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceLocation {
+    /// Local path, as declared and resolved against its manifest directory
+    Path {
+        /// Path as written in the manifest
+        declared: PathBuf,
+        /// Absolute host path
+        resolved: PathBuf,
+    },
+    /// Git repository URL
+    Git(String),
+}
+
+/// A dependency source that lives outside the project directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalSource {
+    /// Manifest entry that declares it, e.g. `[patch.crates-io] serde`
+    pub entry: String,
+
+    /// Where the source points to
+    pub location: SourceLocation,
+}
+
+impl ExternalSource {
+    /// Whether fetching this source needs SSH credentials
+    #[must_use]
+    pub fn needs_ssh(&self) -> bool {
+        match &self.location {
+            SourceLocation::Git(url) => {
+                url.starts_with("ssh://")
+                    || url.starts_with("git+ssh://")
+                    || url.starts_with("git@")
+            }
+            SourceLocation::Path { .. } => false,
+        }
+    }
+}
+
+/// Which git credentials may be forwarded into build containers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CredentialsPolicy {
+    /// Never forward credentials
+    #[default]
+    None,
+    /// Forward the host SSH agent socket
+    SshAgent,
+}
+
+impl FromStr for CredentialsPolicy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "none" => Ok(Self::None),
+            "ssh-agent" => Ok(Self::SshAgent),
+            _ => Err(Error::Config(format!(
+                "Invalid container credentials policy: {s}. Must be one of: none, ssh-agent"
+            ))),
+        }
+    }
+}
+
+/// Extra mounts and environment a container build needs for external sources
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMounts {
+    /// Host path to container path volume mounts
+    pub volumes: Vec<(String, String)>,
+
+    /// Environment variables for git and HTTPS authentication
+    pub env: Vec<(String, String)>,
+
+    /// Environment variables holding registry tokens
+    pub secrets: Vec<(String, String)>,
+
+    /// Entries that will likely fail to fetch inside the container, with the reason
+    pub unresolved: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    packages: Vec<MetadataPackage>,
+    workspace_members: Vec<String>,
+    workspace_root: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct MetadataPackage {
+    id: String,
+    name: String,
+    manifest_path: PathBuf,
+    #[serde(default)]
+    dependencies: Vec<MetadataDependency>,
+}
+
+#[derive(Deserialize)]
+struct MetadataDependency {
+    name: String,
+    source: Option<String>,
+    path: Option<PathBuf>,
+}
+
+/// Find patches, replacements, and dependencies that live outside `host_root`
+///
+/// # Errors
+/// Returns an error if `cargo metadata` fails or the workspace manifest
+/// cannot be read
+pub fn find_external_sources(host_root: &Path) -> Result<Vec<ExternalSource>> {
+    let output = Command::new("cargo")
+        .args(["metadata", "--no-deps", "--format-version", "1"])
+        .current_dir(host_root)
+        .output()
+        .map_err(|e| Error::Build(format!("Failed to run cargo metadata: {e}")))?;
+
+    if !output.status.success() {
+        return Err(Error::Build(format!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let metadata: Metadata = serde_json::from_slice(&output.stdout)
+        .map_err(|e| Error::Build(format!("Invalid cargo metadata output: {e}")))?;
+
+    let manifest_path = metadata.workspace_root.join("Cargo.toml");
+    let manifest = std::fs::read_to_string(&manifest_path)?;
+
+    let mut sources = sources_from_manifest(&manifest, &metadata.workspace_root)?;
+    sources.extend(sources_from_metadata(&metadata));
+    sources.retain(|source| match &source.location {
+        SourceLocation::Path { resolved, .. } => !resolved.starts_with(host_root),
+        SourceLocation::Git(_) => true,
+    });
+
+    Ok(sources)
+}
+
+/// `[patch.<registry>]` and `[replace]` entries of a workspace manifest
+fn sources_from_manifest(manifest: &str, manifest_dir: &Path) -> Result<Vec<ExternalSource>> {
+    let manifest: toml::Value = toml::from_str(manifest)
+        .map_err(|e| Error::Config(format!("Failed to parse Cargo.toml: {e}")))?;
+
+    let mut sections = Vec::new();
+    if let Some(patch) = manifest.get("patch").and_then(toml::Value::as_table) {
+        for (registry, entries) in patch {
+            sections.push((format!("patch.{registry}"), entries));
+        }
+    }
+    if let Some(replace) = manifest.get("replace") {
+        sections.push(("replace".to_string(), replace));
+    }
+
+    let mut sources = Vec::new();
+    for (section, entries) in sections {
+        let Some(entries) = entries.as_table() else {
+            continue;
+        };
+        for (name, spec) in entries {
+            let entry = format!("[{section}] {name}");
+            if let Some(path) = spec.get("path").and_then(toml::Value::as_str) {
+                sources.push(ExternalSource {
+                    entry,
+                    location: path_location(Path::new(path), manifest_dir),
+                });
+            } else if let Some(git) = spec.get("git").and_then(toml::Value::as_str) {
+                sources.push(ExternalSource {
+                    entry,
+                    location: SourceLocation::Git(git.to_string()),
+                });
+            }
+        }
+    }
+
+    Ok(sources)
+}
+
+/// Git and path dependencies declared by workspace members
+fn sources_from_metadata(metadata: &Metadata) -> Vec<ExternalSource> {
+    let mut sources = Vec::new();
+
+    for package in metadata
+        .packages
+        .iter()
+        .filter(|p| metadata.workspace_members.contains(&p.id))
+    {
+        let manifest_dir = package.manifest_path.parent().unwrap_or(Path::new("."));
+
+        for dep in &package.dependencies {
+            let entry = format!("dependency `{}` of `{}`", dep.name, package.name);
+            if let Some(path) = &dep.path {
+                let declared = relative_path(path, manifest_dir);
+                sources.push(ExternalSource {
+                    entry,
+                    location: SourceLocation::Path {
+                        declared,
+                        resolved: path.clone(),
+                    },
+                });
+            } else if let Some(url) = dep.source.as_deref().and_then(|s| s.strip_prefix("git+")) {
+                sources.push(ExternalSource {
+                    entry,
+                    location: SourceLocation::Git(url.to_string()),
+                });
+            }
+        }
+    }
+
+    sources
+}
+
+fn path_location(declared: &Path, manifest_dir: &Path) -> SourceLocation {
+    SourceLocation::Path {
+        declared: declared.to_path_buf(),
+        resolved: normalize(&manifest_dir.join(declared)),
+    }
+}
+
+/// Work out the mounts and environment needed for `sources`
+///
+/// Relative paths are mounted where they resolve to from `container_root`,
+/// absolute paths at the same location as on the host.
+///
+/// # Errors
+/// Returns `Error::Config` naming the entry if a path source does not exist
+pub fn plan_mounts(
+    sources: &[ExternalSource],
+    host_root: &Path,
+    container_root: &str,
+    credentials: CredentialsPolicy,
+) -> Result<SourceMounts> {
+    let mut mounts = SourceMounts::default();
+
+    for source in sources {
+        match &source.location {
+            SourceLocation::Path { declared, resolved } => {
+                if !resolved.exists() {
+                    return Err(Error::Config(format!(
+                        "{} points to {}, which does not exist",
+                        source.entry,
+                        declared.display()
+                    )));
+                }
+
+                let container_path = if declared.is_absolute() {
+                    resolved.clone()
+                } else {
+                    normalize(&Path::new(container_root).join(relative_path(resolved, host_root)))
+                };
+
+                if container_path == Path::new("/") {
+                    mounts.unresolved.push(format!(
+                        "{} ({}) cannot be mounted inside the container",
+                        source.entry,
+                        declared.display()
+                    ));
+                    continue;
+                }
+
+                let volume = (
+                    resolved.to_string_lossy().to_string(),
+                    container_path.to_string_lossy().to_string(),
+                );
+                if !mounts.volumes.contains(&volume) {
+                    mounts.volumes.push(volume);
+                }
+            }
+            SourceLocation::Git(url) if source.needs_ssh() => match credentials {
+                CredentialsPolicy::SshAgent => {}
+                CredentialsPolicy::None => mounts.unresolved.push(format!(
+                    "{} ({url}) needs SSH credentials, which are not forwarded",
+                    source.entry
+                )),
+            },
+            SourceLocation::Git(_) => {}
+        }
+    }
+
+    // The agent is forwarded whenever it is allowed, as registries and
+    // dependencies of dependencies may be fetched over SSH too
+    if credentials == CredentialsPolicy::SshAgent {
+        if let Some(socket) = std::env::var_os("SSH_AUTH_SOCK") {
+            mounts.volumes.push((
+                socket.to_string_lossy().to_string(),
+                "/ssh-agent".to_string(),
+            ));
+            mounts
+                .env
+                .push(("SSH_AUTH_SOCK".to_string(), "/ssh-agent".to_string()));
+            mounts.env.push((
+                "GIT_SSH_COMMAND".to_string(),
+                "ssh -o StrictHostKeyChecking=accept-new".to_string(),
+            ));
+            mounts.env.push((
+                "CARGO_NET_GIT_FETCH_WITH_CLI".to_string(),
+                "true".to_string(),
+            ));
+        } else {
+            mounts
+                .unresolved
+                .extend(sources.iter().filter(|s| s.needs_ssh()).map(|s| {
+                    format!(
+                        "{} needs SSH credentials, but SSH_AUTH_SOCK is not set",
+                        s.entry
+                    )
+                }));
+        }
+    }
+
+    Ok(mounts)
+}
+
+/// Home directory of builds that run as the host user, where the netrc file
+/// is mounted; such a user cannot read `/root`
+const MAPPED_HOME: &str = "/xcargo-home";
+
+/// Cargo registry settings and tokens from the environment, and the netrc
+/// file git and curl log in to HTTPS hosts with, for builds that opted in
+///
+/// `map_user` tells whether the build runs as the host user instead of root.
+#[must_use]
+pub fn cargo_credentials(registry_tokens: bool, netrc: bool, map_user: bool) -> SourceMounts {
+    let mut mounts = SourceMounts::default();
+    if registry_tokens {
+        mounts.secrets = registry_env(std::env::vars());
+    }
+    if netrc {
+        let path = std::env::var_os("NETRC")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".netrc")));
+        match path.filter(|path| path.is_file()) {
+            Some(path) => mount_netrc(&mut mounts, &path, map_user),
+            None => mounts
+                .unresolved
+                .push("no netrc file was found to forward".to_string()),
+        }
+    }
+    mounts
+}
+
+/// Mount the netrc file at `path` into the home of the user the build runs
+/// as, so git and curl find it there
+fn mount_netrc(mounts: &mut SourceMounts, path: &Path, map_user: bool) {
+    let home = if map_user { MAPPED_HOME } else { "/root" };
+    let netrc = format!("{home}/.netrc");
+    mounts
+        .volumes
+        .push((path.to_string_lossy().to_string(), format!("{netrc}:ro")));
+    if map_user {
+        mounts.env.push(("HOME".to_string(), home.to_string()));
+        mounts.env.push(("NETRC".to_string(), netrc));
+    }
+}
+
+/// The variables among `vars` that configure cargo registries:
+/// `CARGO_REGISTRY_TOKEN` and every `CARGO_REGISTRIES_<NAME>_*`
+fn registry_env(vars: impl Iterator<Item = (String, String)>) -> Vec<(String, String)> {
+    let mut env: Vec<(String, String)> = vars
+        .filter(|(key, _)| key == "CARGO_REGISTRY_TOKEN" || key.starts_with("CARGO_REGISTRIES_"))
+        .collect();
+    env.sort();
+    env
+}
+
+/// Lexically normalize a path, resolving `.` and `..` without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Express `path` relative to `base`, using `..` where needed
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<Component> = path.components().collect();
+    let base: Vec<Component> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    for component in &path[common..] {
+        relative.push(component);
+    }
+    relative
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sources_from_manifest() {
+        let manifest = r#"
+            [package]
+            name = "app"
+
+            [patch.crates-io]
+            serde = { path = "../serde" }
+            tokio = { git = "ssh://git@example.com/tokio.git" }
+
+            [replace]
+            "log:0.4.0" = { git = "https://github.com/rust-lang/log" }
+        "#;
+
+        let sources = sources_from_manifest(manifest, Path::new("/work/app")).unwrap();
+        assert_eq!(sources.len(), 3);
+        assert_eq!(sources[0].entry, "[patch.crates-io] serde");
+        assert_eq!(
+            sources[0].location,
+            SourceLocation::Path {
+                declared: PathBuf::from("../serde"),
+                resolved: PathBuf::from("/work/serde"),
+            }
+        );
+        assert!(sources[1].needs_ssh());
+        assert_eq!(sources[2].entry, "[replace] log:0.4.0");
+        assert!(!sources[2].needs_ssh());
+    }
+
+    #[test]
+    fn test_plan_mounts() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path().join("app");
+        let sibling = temp.path().join("serde");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::create_dir_all(&sibling).unwrap();
+
+        let sources = vec![
+            ExternalSource {
+                entry: "[patch.crates-io] serde".to_string(),
+                location: SourceLocation::Path {
+                    declared: PathBuf::from("../serde"),
+                    resolved: sibling.clone(),
+                },
+            },
+            ExternalSource {
+                entry: "[patch.crates-io] tokio".to_string(),
+                location: SourceLocation::Git("git@example.com:tokio.git".to_string()),
+            },
+        ];
+
+        let mounts = plan_mounts(&sources, &root, "/project", CredentialsPolicy::None).unwrap();
+        assert_eq!(
+            mounts.volumes,
+            vec![(sibling.to_string_lossy().to_string(), "/serde".to_string())]
+        );
+        assert_eq!(mounts.unresolved.len(), 1);
+        assert!(mounts.unresolved[0].starts_with("[patch.crates-io] tokio"));
+    }
+
+    #[test]
+    fn test_netrc_is_mounted_in_home_of_build_user() {
+        let netrc = Path::new("/home/me/.netrc");
+
+        let mut mounts = SourceMounts::default();
+        mount_netrc(&mut mounts, netrc, false);
+        assert_eq!(
+            mounts.volumes,
+            [("/home/me/.netrc".to_string(), "/root/.netrc:ro".to_string())]
+        );
+        assert!(mounts.env.is_empty());
+
+        // A mapped user cannot read /root, so the file goes into a home of its own
+        let mut mounts = SourceMounts::default();
+        mount_netrc(&mut mounts, netrc, true);
+        assert_eq!(
+            mounts.volumes,
+            [(
+                "/home/me/.netrc".to_string(),
+                "/xcargo-home/.netrc:ro".to_string()
+            )]
+        );
+        assert_eq!(
+            mounts.env,
+            [
+                ("HOME".to_string(), "/xcargo-home".to_string()),
+                ("NETRC".to_string(), "/xcargo-home/.netrc".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_registry_env() {
+        let vars = [
+            ("PATH", "/usr/bin"),
+            ("CARGO_REGISTRIES_ACME_TOKEN", "secret"),
+            ("CARGO_REGISTRIES_ACME_INDEX", "sparse+https://acme.dev/"),
+            ("CARGO_REGISTRY_TOKEN", "crates-io"),
+            ("CARGO_HOME", "/home/me/.cargo"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()));
+
+        let keys: Vec<String> = registry_env(vars.into_iter())
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                "CARGO_REGISTRIES_ACME_INDEX",
+                "CARGO_REGISTRIES_ACME_TOKEN",
+                "CARGO_REGISTRY_TOKEN"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_plan_mounts_missing_path() {
+        let sources = vec![ExternalSource {
+            entry: "[patch.crates-io] serde".to_string(),
+            location: SourceLocation::Path {
+                declared: PathBuf::from("../missing"),
+                resolved: PathBuf::from("/nonexistent/xcargo/missing"),
+            },
+        }];
+
+        let err = plan_mounts(
+            &sources,
+            Path::new("/nonexistent/xcargo/app"),
+            "/project",
+            CredentialsPolicy::None,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("[patch.crates-io] serde"));
+    }
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            relative_path(Path::new("/a/b/c"), Path::new("/a/d")),
+            PathBuf::from("../b/c")
+        );
+        assert_eq!(
+            normalize(Path::new("/project/../../serde")),
+            PathBuf::from("/serde")
+        );
+    }
+}
@@ -1,7 +1,19 @@
 //! Container runtime abstraction layer
 
+use super::auth::RegistryCredentials;
+use super::images::CrossImage;
 use crate::error::{Error, Result};
-use std::process::Command;
+use crate::output::log;
+use std::io::{BufRead, BufReader, IsTerminal, Write};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// How often a container run is checked against its timeout
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Containers started by this process, for unique container names
+static CONTAINERS: AtomicUsize = AtomicUsize::new(0);
 
 /// Container runtime type
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -26,6 +38,30 @@ impl RuntimeType {
     }
 }
 
+/// When images are pulled before they are used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PullPolicy {
+    /// Pull every time, picking up a moved tag
+    Always,
+    /// Only use images that are already present
+    Never,
+    /// Pull images that are not present yet
+    #[default]
+    IfNotPresent,
+}
+
+impl PullPolicy {
+    /// Parse from a `container.pull_policy` value
+    pub fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            "if-not-present" => Ok(Self::IfNotPresent),
+            _ => Err(Error::Config(format!("Unknown pull policy: {s}"))),
+        }
+    }
+}
+
 /// Container runtime trait
 pub trait ContainerRuntime: Send + Sync {
     /// Check if this runtime is available
@@ -38,17 +74,493 @@ pub trait ContainerRuntime: Send + Sync {
     fn pull_image(&self, image: &str) -> Result<()>;
 
     /// Run a command in a container
-    fn run(
-        &self,
-        image: &str,
-        command: &[String],
-        volumes: &[(String, String)],
-        env: &[(String, String)],
-        workdir: &str,
-    ) -> Result<()>;
+    fn run(&self, spec: &RunSpec) -> Result<()>;
+
+    /// Run a command in a container, echoing its stdout while capturing it
+    fn run_captured(&self, spec: &RunSpec) -> Result<CapturedRun>;
 
     /// List available images
     fn list_images(&self) -> Result<Vec<String>>;
+
+    /// Write a pulled image to a tar archive that `load` can read
+    fn save_image(&self, image: &str, path: &std::path::Path) -> Result<()>;
+
+    /// Build an image from the `Dockerfile` in a context directory
+    fn build_image(&self, build: &ImageBuild) -> Result<()>;
+
+    /// Push a built image to its registry
+    fn push_image(&self, image: &str) -> Result<()>;
+
+    /// Log in to `registry`, storing the credentials for later pulls
+    fn login(&self, registry: &str, credentials: &RegistryCredentials) -> Result<()>;
+
+    /// Registry digest of a pulled image, e.g. "sha256:...", if known
+    fn image_digest(&self, image: &str) -> Option<String>;
+
+    /// Detect the active context, endpoint and rootless mode
+    fn info(&self) -> RuntimeInfo;
+}
+
+/// A single `run` invocation
+#[derive(Debug, Clone, Default)]
+pub struct RunSpec {
+    /// Image to run
+    pub image: String,
+
+    /// Command and arguments executed inside the container
+    pub command: Vec<String>,
+
+    /// Volume mounts as (host, container) pairs
+    pub volumes: Vec<(String, String)>,
+
+    /// Environment variables passed to the container
+    pub env: Vec<(String, String)>,
+
+    /// Working directory inside the container
+    pub workdir: String,
+
+    /// Extra runtime flags placed before the image (e.g. `--user`, `--userns`)
+    pub extra_args: Vec<String>,
+
+    /// Environment variables holding secrets; they are handed to the runtime
+    /// through its own environment, so they never appear in its arguments
+    pub secrets: Vec<(String, String)>,
+
+    /// Time the container may run before it is removed
+    pub timeout: Option<Duration>,
+}
+
+/// A single image `build` invocation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageBuild {
+    /// Directory holding the `Dockerfile` and the files it copies
+    pub context: std::path::PathBuf,
+
+    /// References the image is tagged with, e.g. "ghcr.io/acme/app:1.0"
+    pub tags: Vec<String>,
+
+    /// Platform of the image, e.g. "linux/arm64"
+    pub platform: Option<String>,
+}
+
+/// Outcome of a container run whose stdout was captured
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CapturedRun {
+    /// Whether the command exited successfully
+    pub success: bool,
+
+    /// Everything the command wrote to stdout
+    pub stdout: String,
+}
+
+/// Where and how a runtime is executing containers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RuntimeInfo {
+    /// Active Docker context or Podman connection, if any
+    pub context: Option<String>,
+
+    /// Daemon endpoint or socket the runtime talks to
+    pub endpoint: Option<String>,
+
+    /// Whether the runtime runs without root privileges
+    pub rootless: bool,
+}
+
+impl RuntimeInfo {
+    /// Whether the endpoint is on another machine, so host paths cannot be mounted
+    #[must_use]
+    pub fn is_remote(&self) -> bool {
+        self.endpoint
+            .as_deref()
+            .is_some_and(|e| e.starts_with("tcp://") || e.starts_with("ssh://"))
+    }
+}
+
+/// Flags that make files written to bind mounts belong to the host user
+///
+/// `owner` is the (uid, gid) owning the mounted project directory. Rootless
+/// Podman maps that user with `--userns=keep-id`; rootless Docker already maps
+/// container root to the invoking user, so it needs nothing. Rootful runtimes
+/// run as the owner explicitly via `--user`.
+#[must_use]
+pub fn user_mapping_args(
+    runtime: &str,
+    info: &RuntimeInfo,
+    owner: Option<(u32, u32)>,
+) -> Vec<String> {
+    if info.is_remote() {
+        return Vec::new();
+    }
+
+    match (runtime, info.rootless, owner) {
+        ("podman", true, _) => vec!["--userns=keep-id".to_string()],
+        (_, true, _) | (_, false, None | Some((0, _))) => Vec::new(),
+        (_, false, Some((uid, gid))) => vec!["--user".to_string(), format!("{uid}:{gid}")],
+    }
+}
+
+/// Owner (uid, gid) of a host path, where the platform has one
+#[must_use]
+pub fn path_owner(path: &std::path::Path) -> Option<(u32, u32)> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        std::fs::metadata(path).ok().map(|m| (m.uid(), m.gid()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = path;
+        None
+    }
+}
+
+/// Host side of a volume mount in the form Docker and Podman take on Windows
+///
+/// Backslashes become forward slashes and the `\\?\` prefix of canonical
+/// paths is dropped, so `\\?\C:\Users\me\app` becomes `C:/Users/me/app`.
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::container::host_volume_path;
+///
+/// assert_eq!(host_volume_path(r"C:\Users\me\app"), "C:/Users/me/app");
+/// assert_eq!(host_volume_path("/home/me/app"), "/home/me/app");
+/// ```
+#[must_use]
+pub fn host_volume_path(path: &str) -> String {
+    let path = match path.strip_prefix(r"\\?\UNC\") {
+        Some(share) => format!(r"\\{share}"),
+        None => path.strip_prefix(r"\\?\").unwrap_or(path).to_string(),
+    };
+    path.replace('\\', "/")
+}
+
+/// `<binary> run` command for `spec`, with a terminal attached if `tty`
+fn run_command(binary: &str, spec: &RunSpec, tty: bool, name: Option<&str>) -> Command {
+    let mut cmd = Command::new(binary);
+    cmd.arg("run").arg("--rm");
+    if let Some(name) = name {
+        cmd.arg("--name").arg(name);
+    }
+    if tty {
+        cmd.arg("-it");
+    }
+    cmd.arg("-w").arg(&spec.workdir);
+    cmd.args(&spec.extra_args);
+
+    // Add volumes
+    for (host, container) in &spec.volumes {
+        let host = if cfg!(windows) {
+            host_volume_path(host)
+        } else {
+            host.clone()
+        };
+        cmd.arg("-v").arg(format!("{host}:{container}"));
+    }
+
+    // Add environment variables; `-e NAME` takes the value from our environment
+    for (key, value) in &spec.env {
+        cmd.arg("-e").arg(format!("{key}={value}"));
+    }
+    for (key, value) in &spec.secrets {
+        cmd.arg("-e").arg(key).env(key, value);
+    }
+
+    // Add image and command
+    cmd.arg(&spec.image);
+    cmd.args(&spec.command);
+    cmd
+}
+
+/// Name for a container that may have to be removed, when `spec` has a timeout
+fn container_name(spec: &RunSpec) -> Option<String> {
+    spec.timeout.map(|_| {
+        format!(
+            "xcargo-{}-{}",
+            std::process::id(),
+            CONTAINERS.fetch_add(1, Ordering::Relaxed)
+        )
+    })
+}
+
+/// Wait for a container run to exit, removing the container named `name`
+/// once it has run longer than `spec.timeout`
+///
+/// Killing the runtime's client alone would leave the container running.
+fn wait_run(
+    binary: &str,
+    child: &mut Child,
+    spec: &RunSpec,
+    name: Option<&str>,
+) -> Result<ExitStatus> {
+    let (Some(timeout), Some(name)) = (spec.timeout, name) else {
+        return Ok(child.wait()?);
+    };
+
+    let started = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status);
+        }
+        if started.elapsed() >= timeout {
+            let mut remove = Command::new(binary);
+            remove.arg("rm").arg("-f").arg(name);
+            log::command(&remove);
+            let _ = remove.output();
+            // The client may exit on its own once the container is gone
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(Error::BuildTimeout {
+                target: spec.image.clone(),
+                timeout,
+            });
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Run a container with the given runtime binary
+fn run_with(binary: &str, spec: &RunSpec) -> Result<()> {
+    let name = container_name(spec);
+    // -t fails outright when there is no terminal (CI, piped output)
+    let mut cmd = run_command(
+        binary,
+        spec,
+        std::io::stdin().is_terminal(),
+        name.as_deref(),
+    );
+    log::command(&cmd);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| Error::Container(format!("Failed to execute {binary} run: {e}")))?;
+    let status = wait_run(binary, &mut child, spec, name.as_deref())?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Container("Container build failed".to_string()))
+    }
+}
+
+/// Run a container with the given runtime binary, capturing its stdout
+fn capture_with(binary: &str, spec: &RunSpec) -> Result<CapturedRun> {
+    let name = container_name(spec);
+    // A terminal would merge stderr into the captured stdout
+    let mut cmd = run_command(binary, spec, false, name.as_deref());
+    log::command(&cmd);
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::Container(format!("Failed to execute {binary} run: {e}")))?;
+
+    // Read on another thread so the run can time out while it is silent
+    let reader = child.stdout.take().map(|pipe| {
+        std::thread::spawn(move || {
+            let mut stdout = String::new();
+            for line in BufReader::new(pipe).lines().map_while(std::io::Result::ok) {
+                println!("{line}");
+                stdout.push_str(&line);
+                stdout.push('\n');
+            }
+            stdout
+        })
+    });
+    let status = wait_run(binary, &mut child, spec, name.as_deref())?;
+    let stdout = reader
+        .and_then(|reader| reader.join().ok())
+        .unwrap_or_default();
+
+    Ok(CapturedRun {
+        success: status.success(),
+        stdout,
+    })
+}
+
+/// Pull an image with the given runtime binary
+///
+/// The runtime's errors are shown as they come and kept, to explain a pull
+/// that failed because a proxy intercepts TLS.
+fn pull_with(binary: &str, image: &str) -> Result<()> {
+    let mut cmd = Command::new(binary);
+    cmd.arg("pull").arg(image).stderr(Stdio::piped());
+    log::command(&cmd);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| Error::Container(format!("Failed to execute {binary} pull: {e}")))?;
+
+    let mut stderr = String::new();
+    if let Some(pipe) = child.stderr.take() {
+        for line in BufReader::new(pipe).lines().map_while(std::io::Result::ok) {
+            eprintln!("{line}");
+            stderr.push_str(&line);
+            stderr.push('\n');
+        }
+    }
+    let status = child.wait()?;
+
+    if status.success() {
+        Ok(())
+    } else if crate::toolchain::is_untrusted_certificate(&stderr) {
+        Err(Error::Container(untrusted_registry_message(binary, image)))
+    } else {
+        Err(Error::Container(format!("Failed to pull image: {image}")))
+    }
+}
+
+/// Explain a pull that failed on the registry's certificate
+///
+/// Podman pulls in its own process and so trusts `network.ca_bundle`; the
+/// Docker daemon needs the proxy's CA in its own trust store.
+fn untrusted_registry_message(binary: &str, image: &str) -> String {
+    let registry = CrossImage::from_reference(image, "").registry().to_string();
+    let fix = if binary == "docker" {
+        format!(
+            "Add the proxy's CA certificate to /etc/docker/certs.d/{registry}/ca.crt \
+             or the system trust store and restart the Docker daemon; \
+             network.ca_bundle does not reach the daemon"
+        )
+    } else {
+        format!(
+            "Set network.ca_bundle in xcargo.toml, or add the proxy's CA certificate \
+             to /etc/containers/certs.d/{registry}/ca.crt"
+        )
+    };
+    format!(
+        "Failed to pull image {image}: the certificate of {registry} is not trusted, \
+         usually because a proxy intercepts TLS. {fix}"
+    )
+}
+
+/// Save an image to a tar archive with the given runtime binary
+fn save_with(binary: &str, image: &str, path: &std::path::Path) -> Result<()> {
+    let mut cmd = Command::new(binary);
+    cmd.arg("save").arg("-o").arg(path).arg(image);
+    log::command(&cmd);
+    let status = cmd
+        .status()
+        .map_err(|e| Error::Container(format!("Failed to execute {binary} save: {e}")))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Container(format!("Failed to save image: {image}")))
+    }
+}
+
+/// Command building an image with the given runtime binary
+fn image_build_command(binary: &str, build: &ImageBuild) -> Command {
+    let mut cmd = Command::new(binary);
+    cmd.arg("build");
+    if let Some(platform) = &build.platform {
+        cmd.arg("--platform").arg(platform);
+    }
+    for tag in &build.tags {
+        cmd.arg("--tag").arg(tag);
+    }
+    cmd.arg(&build.context);
+    cmd
+}
+
+/// Build an image with the given runtime binary
+fn build_image_with(binary: &str, build: &ImageBuild) -> Result<()> {
+    let mut cmd = image_build_command(binary, build);
+    log::command(&cmd);
+    let status = cmd
+        .status()
+        .map_err(|e| Error::Container(format!("Failed to execute {binary} build: {e}")))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Container(format!(
+            "Failed to build image {}",
+            build.tags.first().map_or("", String::as_str)
+        )))
+    }
+}
+
+/// Push an image with the given runtime binary
+fn push_with(binary: &str, image: &str) -> Result<()> {
+    let mut cmd = Command::new(binary);
+    cmd.arg("push").arg(image);
+    log::command(&cmd);
+    let status = cmd
+        .status()
+        .map_err(|e| Error::Container(format!("Failed to execute {binary} push: {e}")))?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Container(format!("Failed to push image: {image}")))
+    }
+}
+
+/// Log in to a registry with the given runtime binary, passing the password
+/// on stdin so it never shows up in the process list
+fn login_with(binary: &str, registry: &str, credentials: &RegistryCredentials) -> Result<()> {
+    let mut cmd = Command::new(binary);
+    cmd.arg("login")
+        .arg("--username")
+        .arg(&credentials.username)
+        .arg("--password-stdin")
+        .arg(registry)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null());
+    log::command(&cmd);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| Error::Container(format!("Failed to execute {binary} login: {e}")))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(credentials.password.as_bytes())?;
+    }
+    let status = child.wait()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Container(format!(
+            "Failed to log in to {registry} as {}",
+            credentials.username
+        )))
+    }
+}
+
+/// Run a runtime query and return its trimmed stdout, if it succeeded and is non-empty
+fn query(binary: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(binary).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if value.is_empty() || value == "<no value>" {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Registry digest of a pulled image, from its first repository digest
+fn inspect_digest(binary: &str, image: &str) -> Option<String> {
+    let format = "{{index .RepoDigests 0}}";
+    repo_digest(&query(
+        binary,
+        &["image", "inspect", "--format", format, image],
+    )?)
+}
+
+/// Digest part of a `repository@sha256:...` reference
+fn repo_digest(reference: &str) -> Option<String> {
+    reference
+        .split_once('@')
+        .map(|(_, digest)| digest.to_string())
+}
+
+/// Read a non-empty environment variable
+fn env_value(key: &str) -> Option<String> {
+    std::env::var(key).ok().filter(|v| !v.is_empty())
 }
 
 /// Docker runtime implementation
@@ -74,57 +586,35 @@ impl ContainerRuntime for DockerRuntime {
     }
 
     fn pull_image(&self, image: &str) -> Result<()> {
-        let status = Command::new("docker")
-            .arg("pull")
-            .arg(image)
-            .status()
-            .map_err(|e| Error::Container(format!("Failed to execute docker pull: {e}")))?;
-
-        if status.success() {
-            Ok(())
-        } else {
-            Err(Error::Container(format!("Failed to pull image: {image}")))
-        }
+        pull_with("docker", image)
     }
 
-    fn run(
-        &self,
-        image: &str,
-        command: &[String],
-        volumes: &[(String, String)],
-        env: &[(String, String)],
-        workdir: &str,
-    ) -> Result<()> {
-        let mut cmd = Command::new("docker");
-        cmd.arg("run").arg("--rm").arg("-it").arg("-w").arg(workdir);
-
-        // Add volumes
-        for (host, container) in volumes {
-            cmd.arg("-v").arg(format!("{host}:{container}"));
-        }
+    fn run(&self, spec: &RunSpec) -> Result<()> {
+        run_with("docker", spec)
+    }
 
-        // Add environment variables
-        for (key, value) in env {
-            cmd.arg("-e").arg(format!("{key}={value}"));
-        }
+    fn run_captured(&self, spec: &RunSpec) -> Result<CapturedRun> {
+        capture_with("docker", spec)
+    }
 
-        // Add image
-        cmd.arg(image);
+    fn image_digest(&self, image: &str) -> Option<String> {
+        inspect_digest("docker", image)
+    }
 
-        // Add command
-        for arg in command {
-            cmd.arg(arg);
-        }
+    fn save_image(&self, image: &str, path: &std::path::Path) -> Result<()> {
+        save_with("docker", image, path)
+    }
 
-        let status = cmd
-            .status()
-            .map_err(|e| Error::Container(format!("Failed to execute docker run: {e}")))?;
+    fn build_image(&self, build: &ImageBuild) -> Result<()> {
+        build_image_with("docker", build)
+    }
 
-        if status.success() {
-            Ok(())
-        } else {
-            Err(Error::Container("Container build failed".to_string()))
-        }
+    fn push_image(&self, image: &str) -> Result<()> {
+        push_with("docker", image)
+    }
+
+    fn login(&self, registry: &str, credentials: &RegistryCredentials) -> Result<()> {
+        login_with("docker", registry, credentials)
     }
 
     fn list_images(&self) -> Result<Vec<String>> {
@@ -145,6 +635,32 @@ impl ContainerRuntime for DockerRuntime {
             Err(Error::Container("Failed to list images".to_string()))
         }
     }
+
+    fn info(&self) -> RuntimeInfo {
+        // DOCKER_HOST overrides the context; DOCKER_CONTEXT overrides the current one
+        let context = env_value("DOCKER_CONTEXT").or_else(|| query("docker", &["context", "show"]));
+        let endpoint = env_value("DOCKER_HOST").or_else(|| {
+            let name = context.clone().unwrap_or_else(|| "default".to_string());
+            query(
+                "docker",
+                &[
+                    "context",
+                    "inspect",
+                    &name,
+                    "--format",
+                    "{{.Endpoints.docker.Host}}",
+                ],
+            )
+        });
+        let rootless = query("docker", &["info", "--format", "{{json .SecurityOptions}}"])
+            .is_some_and(|opts| opts.contains("rootless"));
+
+        RuntimeInfo {
+            context,
+            endpoint,
+            rootless,
+        }
+    }
 }
 
 /// Podman runtime implementation
@@ -170,57 +686,35 @@ impl ContainerRuntime for PodmanRuntime {
     }
 
     fn pull_image(&self, image: &str) -> Result<()> {
-        let status = Command::new("podman")
-            .arg("pull")
-            .arg(image)
-            .status()
-            .map_err(|e| Error::Container(format!("Failed to execute podman pull: {e}")))?;
-
-        if status.success() {
-            Ok(())
-        } else {
-            Err(Error::Container(format!("Failed to pull image: {image}")))
-        }
+        pull_with("podman", image)
     }
 
-    fn run(
-        &self,
-        image: &str,
-        command: &[String],
-        volumes: &[(String, String)],
-        env: &[(String, String)],
-        workdir: &str,
-    ) -> Result<()> {
-        let mut cmd = Command::new("podman");
-        cmd.arg("run").arg("--rm").arg("-it").arg("-w").arg(workdir);
-
-        // Add volumes
-        for (host, container) in volumes {
-            cmd.arg("-v").arg(format!("{host}:{container}"));
-        }
+    fn run(&self, spec: &RunSpec) -> Result<()> {
+        run_with("podman", spec)
+    }
 
-        // Add environment variables
-        for (key, value) in env {
-            cmd.arg("-e").arg(format!("{key}={value}"));
-        }
+    fn run_captured(&self, spec: &RunSpec) -> Result<CapturedRun> {
+        capture_with("podman", spec)
+    }
 
-        // Add image
-        cmd.arg(image);
+    fn image_digest(&self, image: &str) -> Option<String> {
+        inspect_digest("podman", image)
+    }
 
-        // Add command
-        for arg in command {
-            cmd.arg(arg);
-        }
+    fn save_image(&self, image: &str, path: &std::path::Path) -> Result<()> {
+        save_with("podman", image, path)
+    }
 
-        let status = cmd
-            .status()
-            .map_err(|e| Error::Container(format!("Failed to execute podman run: {e}")))?;
+    fn build_image(&self, build: &ImageBuild) -> Result<()> {
+        build_image_with("podman", build)
+    }
 
-        if status.success() {
-            Ok(())
-        } else {
-            Err(Error::Container("Container build failed".to_string()))
-        }
+    fn push_image(&self, image: &str) -> Result<()> {
+        push_with("podman", image)
+    }
+
+    fn login(&self, registry: &str, credentials: &RegistryCredentials) -> Result<()> {
+        login_with("podman", registry, credentials)
     }
 
     fn list_images(&self) -> Result<Vec<String>> {
@@ -241,6 +735,27 @@ impl ContainerRuntime for PodmanRuntime {
             Err(Error::Container("Failed to list images".to_string()))
         }
     }
+
+    fn info(&self) -> RuntimeInfo {
+        let context = env_value("CONTAINER_CONNECTION");
+        let endpoint = env_value("CONTAINER_HOST").or_else(|| {
+            query(
+                "podman",
+                &["info", "--format", "{{.Host.RemoteSocket.Path}}"],
+            )
+        });
+        let rootless = query(
+            "podman",
+            &["info", "--format", "{{.Host.Security.Rootless}}"],
+        )
+        .is_some_and(|v| v == "true");
+
+        RuntimeInfo {
+            context,
+            endpoint,
+            rootless,
+        }
+    }
 }
 
 /// Create a container runtime based on the type
@@ -317,6 +832,43 @@ mod tests {
         assert!(RuntimeType::from_str("invalid").is_err());
     }
 
+    #[test]
+    fn test_pull_policy_from_str() {
+        assert_eq!(PullPolicy::from_str("always").unwrap(), PullPolicy::Always);
+        assert_eq!(PullPolicy::from_str("never").unwrap(), PullPolicy::Never);
+        assert_eq!(
+            PullPolicy::from_str("if-not-present").unwrap(),
+            PullPolicy::IfNotPresent
+        );
+        assert!(PullPolicy::from_str("sometimes").is_err());
+    }
+
+    #[test]
+    fn test_repo_digest() {
+        assert_eq!(
+            repo_digest("ghcr.io/cross-rs/aarch64-unknown-linux-gnu@sha256:abc123").as_deref(),
+            Some("sha256:abc123")
+        );
+        assert_eq!(
+            repo_digest("ghcr.io/cross-rs/aarch64-unknown-linux-gnu:latest"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_image_build_command() {
+        let build = ImageBuild {
+            context: std::path::PathBuf::from("/tmp/context"),
+            tags: vec!["app:1.0".to_string(), "app:latest".to_string()],
+            platform: Some("linux/arm64".to_string()),
+        };
+        let cmd = image_build_command("podman", &build);
+        assert_eq!(
+            log::command_line(&cmd),
+            "podman build --platform linux/arm64 --tag app:1.0 --tag app:latest /tmp/context"
+        );
+    }
+
     #[test]
     fn test_docker_runtime_name() {
         let runtime = DockerRuntime::new();
@@ -328,4 +880,62 @@ mod tests {
         let runtime = PodmanRuntime::new();
         assert_eq!(runtime.name(), "podman");
     }
+
+    #[test]
+    fn test_user_mapping_args() {
+        let rootful = RuntimeInfo::default();
+        let rootless = RuntimeInfo {
+            rootless: true,
+            ..RuntimeInfo::default()
+        };
+        let owner = Some((1000, 1000));
+
+        assert_eq!(
+            user_mapping_args("podman", &rootless, owner),
+            vec!["--userns=keep-id"]
+        );
+        assert!(user_mapping_args("docker", &rootless, owner).is_empty());
+        assert_eq!(
+            user_mapping_args("docker", &rootful, owner),
+            vec!["--user", "1000:1000"]
+        );
+        assert!(user_mapping_args("docker", &rootful, Some((0, 0))).is_empty());
+        assert!(user_mapping_args("docker", &rootful, None).is_empty());
+    }
+
+    #[test]
+    fn test_remote_endpoint_skips_user_mapping() {
+        let info = RuntimeInfo {
+            endpoint: Some("ssh://builder@ci-host".to_string()),
+            ..RuntimeInfo::default()
+        };
+        assert!(info.is_remote());
+        assert!(user_mapping_args("docker", &info, Some((1000, 1000))).is_empty());
+
+        let local = RuntimeInfo {
+            endpoint: Some("unix:///var/run/docker.sock".to_string()),
+            ..RuntimeInfo::default()
+        };
+        assert!(!local.is_remote());
+    }
+
+    #[test]
+    fn test_host_volume_path() {
+        assert_eq!(host_volume_path(r"\\?\D:\src\app"), "D:/src/app");
+        assert_eq!(
+            host_volume_path(r"\\?\UNC\server\share\app"),
+            "//server/share/app"
+        );
+        assert_eq!(host_volume_path("C:/Users/me/app"), "C:/Users/me/app");
+    }
+
+    #[test]
+    fn test_untrusted_registry_message() {
+        let docker =
+            untrusted_registry_message("docker", "ghcr.io/cross-rs/x86_64-pc-windows-gnu:main");
+        assert!(docker.contains("/etc/docker/certs.d/ghcr.io/ca.crt"));
+        let podman = untrusted_registry_message("podman", "rust:1.78");
+        assert!(podman.contains("/etc/containers/certs.d/docker.io/ca.crt"));
+        assert!(podman.contains("network.ca_bundle"));
+    }
 }
@@ -1,12 +1,16 @@
 //! Individual diagnostic checks
 
 use crate::config::ConfigDiscovery;
-use crate::toolchain::ToolchainManager;
+use crate::target::RequirementDatabase;
+use crate::toolchain::{StandaloneToolchain, ToolchainManager};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use which::which;
 
 /// Status of a diagnostic check
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum CheckStatus {
     /// Check passed successfully
     Pass,
@@ -19,7 +23,7 @@ pub enum CheckStatus {
 }
 
 /// Result of a diagnostic check
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct CheckResult {
     /// Name of the check
     pub name: String,
@@ -107,11 +111,22 @@ pub fn check_rustup() -> CheckResult {
                 CheckResult::pass("rustup", format!("Found at {:?}", path))
             }
         }
-        Err(_) => CheckResult::critical(
-            "rustup",
-            "rustup not found in PATH",
-            "Install rustup from https://rustup.rs/",
-        ),
+        Err(_) => match StandaloneToolchain::detect() {
+            Ok(standalone) => CheckResult::warning(
+                "rustup",
+                format!(
+                    "rustup not found; using standalone rustc {} at {}",
+                    standalone.release,
+                    standalone.sysroot.display()
+                ),
+                "Targets and components must be installed manually; install rustup from https://rustup.rs/ to manage them",
+            ),
+            Err(_) => CheckResult::critical(
+                "rustup",
+                "rustup not found in PATH",
+                "Install rustup from https://rustup.rs/",
+            ),
+        },
     }
 }
 
@@ -240,6 +255,27 @@ pub fn check_zig() -> CheckResult {
     }
 }
 
+/// Check that the Zig release xcargo.toml pins is installed
+pub fn check_zig_pin(version: &str) -> CheckResult {
+    let name = format!("zig {version}");
+    if let Some(path) = crate::toolchain::managed_zig(version) {
+        return CheckResult::pass(name, format!("Installed at {}", path.display()));
+    }
+    let on_path = Command::new("zig")
+        .arg("version")
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == version);
+    if on_path {
+        CheckResult::pass(name, "The zig on PATH is the pinned version")
+    } else {
+        CheckResult::warning(
+            name,
+            format!("xcargo.toml pins Zig {version} but it is not installed"),
+            "Run 'xcargo toolchain zig install' (builds install it on first use)",
+        )
+    }
+}
+
 /// Check if Docker is available
 pub fn check_docker() -> CheckResult {
     match which("docker") {
@@ -291,6 +327,64 @@ pub fn check_podman() -> CheckResult {
     }
 }
 
+/// Report the container runtime's context, endpoint and rootless mode
+#[cfg(feature = "container")]
+pub fn check_container_runtime(builder: &crate::container::ContainerBuilder) -> CheckResult {
+    let info = builder.runtime_info();
+    let mut details = vec![format!(
+        "{} ({})",
+        builder.runtime_name(),
+        if info.rootless { "rootless" } else { "rootful" }
+    )];
+    if let Some(context) = &info.context {
+        details.push(format!("context: {context}"));
+    }
+    if let Some(endpoint) = &info.endpoint {
+        details.push(format!("endpoint: {endpoint}"));
+    }
+    let message = details.join(", ");
+
+    if info.is_remote() {
+        CheckResult::warning(
+            "container runtime",
+            message,
+            "The runtime is on a remote host, so the project directory cannot be bind-mounted; switch to a local context",
+        )
+    } else {
+        CheckResult::pass("container runtime", message)
+    }
+}
+
+/// Check that containers can write to the mounted project directory as the host user
+#[cfg(feature = "container")]
+pub fn check_container_volume_permissions(
+    builder: &crate::container::ContainerBuilder,
+    image: &str,
+) -> CheckResult {
+    let dir = match std::env::current_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            return CheckResult::fail(
+                "volume permissions",
+                format!("Cannot determine current directory: {e}"),
+                "Run from inside your project directory",
+            )
+        }
+    };
+
+    match builder.check_volume_permissions(image, &dir) {
+        Ok(()) => CheckResult::pass(
+            "volume permissions",
+            format!("{image} can write to {} as the host user", dir.display()),
+        ),
+        Err(e) => CheckResult::fail(
+            "volume permissions",
+            e.to_string(),
+            "Use rootless Podman, or keep map_user = true in the [container] section of xcargo.toml",
+        ),
+    }
+}
+
 /// Check for common linkers
 pub fn check_common_linkers() -> CheckResult {
     let linkers = vec![
@@ -336,6 +430,104 @@ pub fn check_common_linkers() -> CheckResult {
     }
 }
 
+/// Directories libclang is usually installed in
+const LIBCLANG_DIRS: [&str; 10] = [
+    "/usr/lib",
+    "/usr/lib64",
+    "/usr/local/lib",
+    "/usr/lib/x86_64-linux-gnu",
+    "/usr/lib/aarch64-linux-gnu",
+    "/opt/homebrew/opt/llvm/lib",
+    "/usr/local/opt/llvm/lib",
+    "/Library/Developer/CommandLineTools/usr/lib",
+    "/Applications/Xcode.app/Contents/Developer/Toolchains/XcodeDefault.xctoolchain/usr/lib",
+    "C:\\Program Files\\LLVM\\bin",
+];
+
+/// Check for libclang, which bindgen loads to parse C headers
+///
+/// A missing libclang only fails the check when the project depends on
+/// bindgen.
+pub fn check_libclang() -> CheckResult {
+    let suggestion = "Install libclang (apt install libclang-dev, dnf install clang-devel, \
+                      brew install llvm) or set LIBCLANG_PATH to its directory";
+    let uses_bindgen = std::env::current_dir()
+        .ok()
+        .and_then(|dir| crate::cache::find_cargo_lock(&dir))
+        .and_then(|lock| std::fs::read_to_string(lock).ok())
+        .is_some_and(|lock| lock.contains("name = \"bindgen\""));
+
+    match find_libclang() {
+        Some(path) => CheckResult::pass("libclang", format!("Found at {}", path.display())),
+        None if uses_bindgen => CheckResult::fail(
+            "libclang",
+            "libclang not found, but the project depends on bindgen",
+            suggestion,
+        ),
+        None => CheckResult::warning(
+            "libclang",
+            "libclang not found (only needed by crates using bindgen)",
+            suggestion,
+        ),
+    }
+}
+
+/// libclang from `LIBCLANG_PATH`, `llvm-config --libdir`, or a usual
+/// install location
+fn find_libclang() -> Option<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::env::var_os("LIBCLANG_PATH")
+        .map(PathBuf::from)
+        .into_iter()
+        .collect();
+    if let Ok(output) = Command::new("llvm-config").arg("--libdir").output() {
+        if output.status.success() {
+            dirs.push(PathBuf::from(
+                String::from_utf8_lossy(&output.stdout).trim(),
+            ));
+        }
+    }
+    dirs.extend(LIBCLANG_DIRS.iter().map(PathBuf::from));
+    // Versioned LLVM packages, e.g. /usr/lib/llvm-16/lib
+    if let Ok(entries) = std::fs::read_dir("/usr/lib") {
+        dirs.extend(
+            entries
+                .filter_map(std::result::Result::ok)
+                .filter(|entry| entry.file_name().to_string_lossy().starts_with("llvm-"))
+                .map(|entry| entry.path().join("lib")),
+        );
+    }
+    dirs.iter().find_map(|dir| libclang_in(dir))
+}
+
+/// The libclang in `dir`, or `dir` itself if it is the library
+fn libclang_in(dir: &Path) -> Option<PathBuf> {
+    if dir.is_file() {
+        return Some(dir.to_path_buf());
+    }
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| {
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            // libclang.so.1 and libclang-16.so, but not libclang-cpp.so
+            let Some(rest) = name.strip_prefix("libclang") else {
+                return false;
+            };
+            let versioned = rest
+                .strip_prefix('-')
+                .is_some_and(|version| version.starts_with(|c: char| c.is_ascii_digit()));
+            let library = name.contains(".so")
+                || path.extension().is_some_and(|ext| {
+                    ext.eq_ignore_ascii_case("dylib") || ext.eq_ignore_ascii_case("dll")
+                });
+            (rest.starts_with('.') || versioned) && library
+        })
+}
+
 /// Check for xcargo configuration file
 pub fn check_config_file() -> CheckResult {
     match ConfigDiscovery::find() {
@@ -343,11 +535,20 @@ pub fn check_config_file() -> CheckResult {
             "xcargo.toml",
             format!("Found configuration at: {}", path.display()),
         ),
-        Ok(None) => CheckResult::warning(
-            "xcargo.toml",
-            "No xcargo.toml found in current directory or parents",
-            "Run 'xcargo init' to create a configuration file",
-        ),
+        Ok(None) => match ConfigDiscovery::find_manifest() {
+            Ok(Some(path)) => CheckResult::pass(
+                "xcargo.toml",
+                format!(
+                    "Found configuration in the xcargo metadata of: {}",
+                    path.display()
+                ),
+            ),
+            _ => CheckResult::warning(
+                "xcargo.toml",
+                "No xcargo.toml found in current directory or parents",
+                "Run 'xcargo init' to create a configuration file",
+            ),
+        },
         Err(e) => CheckResult::fail(
             "xcargo.toml",
             format!("Error checking configuration: {}", e),
@@ -356,6 +557,35 @@ pub fn check_config_file() -> CheckResult {
     }
 }
 
+/// Check the target requirement database, including the user's
+/// `targets.d` files
+pub fn check_target_database() -> CheckResult {
+    let database = RequirementDatabase::global();
+    let sources = match database.sources() {
+        [] => "built-in entries only".to_string(),
+        sources => format!(
+            "{} user file(s): {}",
+            sources.len(),
+            sources
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    };
+    let message = format!("{} entries, {sources}", database.len());
+
+    if database.errors().is_empty() {
+        CheckResult::pass("target database", message)
+    } else {
+        CheckResult::warning(
+            "target database",
+            format!("{message}; skipped {}", database.errors().join("; ")),
+            "Fix the [[target]] entries of the skipped files",
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -400,6 +630,25 @@ mod tests {
         assert!(!result.name.is_empty());
     }
 
+    #[test]
+    fn test_check_zig_pin() {
+        let result = check_zig_pin("0.0.1");
+        assert_eq!(result.name, "zig 0.0.1");
+        assert_ne!(result.status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_libclang_in() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("libclang-cpp.so.16"), "").unwrap();
+        assert_eq!(libclang_in(temp.path()), None);
+
+        let library = temp.path().join("libclang-16.so.1");
+        std::fs::write(&library, "").unwrap();
+        assert_eq!(libclang_in(temp.path()), Some(library.clone()));
+        assert_eq!(libclang_in(&library), Some(library));
+    }
+
     #[test]
     fn test_check_zig() {
         let result = check_zig();
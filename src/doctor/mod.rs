@@ -5,17 +5,21 @@
 
 mod checks;
 mod report;
+mod target;
 
 pub use checks::{Check, CheckResult, CheckStatus};
-pub use report::DoctorReport;
+pub use report::{DoctorReport, ReportFormat, ReportSummary};
+pub use target::{target_c_library, target_linker};
 
 use crate::error::Result;
 use crate::output::helpers;
 
-/// Run all diagnostic checks and display the report
-pub fn run() -> Result<()> {
-    helpers::section("xcargo doctor - System Diagnostics");
-    println!("Checking your cross-compilation environment...\n");
+/// Run all diagnostic checks and print the report in `format`
+pub fn run(format: ReportFormat) -> Result<()> {
+    if format == ReportFormat::Text {
+        helpers::section("xcargo doctor - System Diagnostics");
+        println!("Checking your cross-compilation environment...\n");
+    }
 
     let mut report = DoctorReport::new();
 
@@ -25,13 +29,20 @@ pub fn run() -> Result<()> {
     report.add_check(checks::check_default_toolchain());
     report.add_check(checks::check_installed_targets());
     report.add_check(checks::check_zig());
+    if let Some(version) = crate::config::Config::load()
+        .ok()
+        .and_then(|config| config.zig.version)
+    {
+        report.add_check(checks::check_zig_pin(&version));
+    }
     report.add_check(checks::check_docker());
     report.add_check(checks::check_podman());
     report.add_check(checks::check_common_linkers());
+    report.add_check(checks::check_libclang());
     report.add_check(checks::check_config_file());
+    report.add_check(checks::check_target_database());
 
-    // Display the report
-    report.display();
+    report.print(format, "xcargo doctor")?;
 
     // Return success/failure based on critical checks
     if report.has_critical_failures() {
@@ -43,6 +54,119 @@ pub fn run() -> Result<()> {
     }
 }
 
+/// Run checks for building one target and print the report in `format`
+///
+/// Fails if any check fails, since the target cannot be built until the
+/// reported problems are fixed.
+pub fn run_target(triple: &str, format: ReportFormat) -> Result<()> {
+    use crate::config::Config;
+    use crate::target::{normalize_triple, Target};
+    use crate::toolchain::zig::ZigToolchain;
+    use crate::toolchain::{managed_zig, ToolchainManager};
+
+    let config = Config::load()?;
+    let manager = ToolchainManager::new()?;
+    let toolchain = manager
+        .resolve_toolchain(None, config.toolchain.channel.as_deref())?
+        .name;
+
+    let triple = Target::resolve_alias(triple)?;
+    let normalized = normalize_triple(&triple, manager.rustc_release(&toolchain).as_deref());
+    if let Some(warning) = &normalized.warning {
+        // Keep stdout parseable for machine-readable formats
+        if format == ReportFormat::Text {
+            helpers::warning(warning);
+        } else {
+            eprintln!("warning: {warning}");
+        }
+    }
+    let target = Target::from_triple(&normalized.triple)?;
+    let host = Target::detect_host()?;
+    // The pinned Zig if it's installed; doctor never downloads it
+    let zig = match config.zig.version.as_deref().and_then(managed_zig) {
+        Some(path) => ZigToolchain::from_path(path),
+        None => ZigToolchain::detect(),
+    }
+    .ok()
+    .flatten();
+    let requirements = target.get_requirements();
+
+    if format == ReportFormat::Text {
+        helpers::section(format!("xcargo doctor - {}", target.triple));
+        println!("Checking whether {} can be built here...\n", target.triple);
+    }
+
+    let mut report = DoctorReport::new();
+    report.add_check(target::check_target_installed(
+        &manager, &toolchain, &target,
+    ));
+    if let Some(check) = target::check_target_components(&manager, &toolchain, &requirements) {
+        report.add_check(check);
+    }
+    report.add_check(target::check_target_linker(
+        &target,
+        &host,
+        &config,
+        zig.as_ref(),
+    ));
+    if let Some(check) = target::check_target_tools(&target, &requirements) {
+        report.add_check(check);
+    }
+    let linker = target_linker(&target, &host, &config);
+    if let Some(check) = target::check_target_system_libs(&target, linker.as_deref()) {
+        report.add_check(check);
+    }
+    // Sysroots, Zig and containers only matter when cross-compiling
+    if target.triple != host.triple {
+        if let Some(check) = target::check_target_sysroot(&target, linker.as_deref(), zig.as_ref())
+        {
+            report.add_check(check);
+        }
+        report.add_check(target::check_target_zig(&target, zig.as_ref()));
+        #[cfg(feature = "container")]
+        report.add_check(target::check_target_container_image(&target, &config));
+    }
+
+    report.print(format, &format!("xcargo doctor {}", target.triple))?;
+
+    let summary = report.summary();
+    if summary.failed > 0 || summary.critical > 0 {
+        Err(crate::error::Error::Toolchain(format!(
+            "{} is not ready to build. See diagnostics above.",
+            target.triple
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Run container-specific diagnostics with the configured runtime
+///
+/// Volume permissions are verified by writing a file from inside `image`.
+#[cfg(feature = "container")]
+pub fn run_container(image: &str) -> Result<()> {
+    use crate::container::{ContainerBuilder, RuntimeType};
+
+    helpers::section("xcargo container doctor");
+
+    let runtime = crate::config::Config::load()
+        .map_or_else(|_| "auto".to_string(), |config| config.container.runtime);
+    let builder = ContainerBuilder::new(RuntimeType::from_str(&runtime)?)?;
+
+    let mut report = DoctorReport::new();
+    report.add_check(checks::check_container_runtime(&builder));
+    report.add_check(checks::check_container_volume_permissions(&builder, image));
+    report.display();
+
+    if report.summary().failed > 0 {
+        Err(crate::error::Error::Container(
+            "Container checks failed. See diagnostics above.".to_string(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,6 +174,6 @@ mod tests {
     #[test]
     fn test_doctor_run() {
         // Doctor should not panic, but may return error
-        let _ = run();
+        let _ = run(ReportFormat::Text);
     }
 }
@@ -1,7 +1,39 @@
 //! Doctor report formatting and display
 
 use super::{CheckResult, CheckStatus};
-use colored::Colorize;
+use crate::error::{Error, Result};
+use crate::output::theme::{self, paint};
+use crate::output::{colors, is_plain, MessageType};
+use serde::Serialize;
+use std::fmt::Write;
+use std::str::FromStr;
+
+/// Output format of a doctor report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    /// Colored, human-readable output
+    #[default]
+    Text,
+    /// JSON object with the checks and a summary
+    Json,
+    /// `JUnit` XML, one test case per check
+    Junit,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "junit" => Ok(Self::Junit),
+            other => Err(format!(
+                "Unknown report format '{other}' (expected text, json or junit)"
+            )),
+        }
+    }
+}
 
 /// Doctor diagnostic report
 #[derive(Debug, Default)]
@@ -45,6 +77,99 @@ impl DoctorReport {
         summary
     }
 
+    /// Print the report to stdout in `format`; `suite` names the `JUnit` test suite
+    pub fn print(&self, format: ReportFormat, suite: &str) -> Result<()> {
+        match format {
+            ReportFormat::Text => self.display(),
+            ReportFormat::Json => println!("{}", self.to_json()?),
+            ReportFormat::Junit => print!("{}", self.to_junit(suite)),
+        }
+        Ok(())
+    }
+
+    /// Serialize the report as pretty-printed JSON
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::doctor::{CheckResult, DoctorReport};
+    ///
+    /// let mut report = DoctorReport::new();
+    /// report.add_check(CheckResult::pass("rustup", "rustup 1.27.1"));
+    /// let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+    /// assert_eq!(json["checks"][0]["status"], "pass");
+    /// assert_eq!(json["summary"]["passed"], 1);
+    /// ```
+    pub fn to_json(&self) -> Result<String> {
+        #[derive(Serialize)]
+        struct JsonReport<'a> {
+            checks: &'a [CheckResult],
+            summary: ReportSummary,
+        }
+
+        serde_json::to_string_pretty(&JsonReport {
+            checks: &self.checks,
+            summary: self.summary(),
+        })
+        .map_err(|e| Error::Config(format!("Failed to serialize doctor report: {e}")))
+    }
+
+    /// Render the report as a `JUnit` XML test suite named `suite`
+    ///
+    /// Failed and critical checks become failures; warnings pass, with the
+    /// warning in the test case's output.
+    #[must_use]
+    pub fn to_junit(&self, suite: &str) -> String {
+        let summary = self.summary();
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = writeln!(
+            xml,
+            "<testsuites name=\"{suite}\" tests=\"{total}\" failures=\"{failures}\">\n  \
+             <testsuite name=\"{suite}\" tests=\"{total}\" failures=\"{failures}\" errors=\"0\" skipped=\"0\">",
+            suite = xml_escape(suite),
+            total = summary.total,
+            failures = summary.failed + summary.critical,
+        );
+
+        for check in &self.checks {
+            let _ = write!(
+                xml,
+                "    <testcase name=\"{}\" classname=\"xcargo.doctor\"",
+                xml_escape(&check.name)
+            );
+            let details = match &check.suggestion {
+                Some(suggestion) => format!("{}\n{suggestion}", check.message),
+                None => check.message.clone(),
+            };
+            match check.status {
+                CheckStatus::Pass => xml.push_str(" />\n"),
+                CheckStatus::Warning => {
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <system-out>{}</system-out>\n    </testcase>",
+                        xml_escape(&details)
+                    );
+                }
+                CheckStatus::Fail | CheckStatus::Critical => {
+                    let kind = if check.status == CheckStatus::Critical {
+                        "critical"
+                    } else {
+                        "fail"
+                    };
+                    let _ = writeln!(
+                        xml,
+                        ">\n      <failure type=\"{kind}\" message=\"{}\">{}</failure>\n    </testcase>",
+                        xml_escape(&check.message),
+                        xml_escape(&details)
+                    );
+                }
+            }
+        }
+
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+
     /// Display the report to stdout
     pub fn display(&self) {
         // Display each check
@@ -59,24 +184,38 @@ impl DoctorReport {
     }
 
     fn display_check(&self, check: &CheckResult) {
-        let (icon, status_text, color_fn): (&str, &str, fn(&str) -> colored::ColoredString) =
-            match check.status {
-                CheckStatus::Pass => ("✓", "PASS", |s| s.green()),
-                CheckStatus::Warning => ("⚠", "WARN", |s| s.yellow()),
-                CheckStatus::Fail => ("✗", "FAIL", |s| s.red()),
-                CheckStatus::Critical => ("✗", "CRIT", |s| s.bright_red().bold()),
-            };
+        let palette = theme::palette();
+        let (icon, status_text, color) = match check.status {
+            CheckStatus::Pass => (MessageType::Success.symbol(), "PASS", palette.success),
+            CheckStatus::Warning => (MessageType::Warning.symbol(), "WARN", palette.warning),
+            CheckStatus::Fail => (MessageType::Error.symbol(), "FAIL", palette.error),
+            CheckStatus::Critical => (MessageType::Error.symbol(), "CRIT", palette.error),
+        };
+
+        if is_plain() {
+            println!("{status_text}: {}", check.name);
+            println!("  {}", check.message);
+            if let Some(suggestion) = &check.suggestion {
+                println!("  Suggestion: {suggestion}");
+            }
+            println!();
+            return;
+        }
 
         println!(
             "{} {} {}",
             icon,
-            color_fn(&format!("[{:^4}]", status_text)),
-            check.name.bold()
+            paint(color, format!("[{:^4}]", status_text)),
+            paint(colors::BOLD, &check.name)
         );
-        println!("  {}", check.message.dimmed());
+        println!("  {}", paint(palette.dim, &check.message));
 
         if let Some(suggestion) = &check.suggestion {
-            println!("  {} {}", "→".cyan(), suggestion.cyan());
+            println!(
+                "  {} {}",
+                paint(palette.hint, MessageType::Hint.symbol()),
+                paint(palette.hint, suggestion)
+            );
         }
 
         println!();
@@ -84,42 +223,53 @@ impl DoctorReport {
 
     fn display_summary(&self) {
         let summary = self.summary();
+        if is_plain() {
+            Self::display_plain_summary(&summary);
+            return;
+        }
+        let palette = theme::palette();
+        let critical = |text: &str| paint(colors::BOLD, paint(palette.error, text));
+        let (passed, warning, failed) = (
+            MessageType::Success.symbol(),
+            MessageType::Warning.symbol(),
+            MessageType::Error.symbol(),
+        );
 
-        println!("{}", "=".repeat(60).dimmed());
-        println!("{}", "Summary".bold());
-        println!("{}", "=".repeat(60).dimmed());
+        println!("{}", paint(palette.dim, "=".repeat(60)));
+        println!("{}", paint(colors::BOLD, "Summary"));
+        println!("{}", paint(palette.dim, "=".repeat(60)));
 
         println!(
             "  Total checks:      {}",
-            summary.total.to_string().bold()
+            paint(colors::BOLD, summary.total)
         );
         println!(
             "  {} Passed:          {}",
-            "✓".green(),
-            summary.passed.to_string().green()
+            paint(palette.success, passed),
+            paint(palette.success, summary.passed)
         );
 
         if summary.warnings > 0 {
             println!(
                 "  {} Warnings:        {}",
-                "⚠".yellow(),
-                summary.warnings.to_string().yellow()
+                paint(palette.warning, warning),
+                paint(palette.warning, summary.warnings)
             );
         }
 
         if summary.failed > 0 {
             println!(
                 "  {} Failed:          {}",
-                "✗".red(),
-                summary.failed.to_string().red()
+                paint(palette.error, failed),
+                paint(palette.error, summary.failed)
             );
         }
 
         if summary.critical > 0 {
             println!(
                 "  {} Critical:        {}",
-                "✗".bright_red().bold(),
-                summary.critical.to_string().bright_red().bold()
+                critical(failed),
+                critical(&summary.critical.to_string())
             );
         }
 
@@ -129,36 +279,67 @@ impl DoctorReport {
         if summary.critical > 0 {
             println!(
                 "{}",
-                "❌ Critical issues found. xcargo may not function correctly."
-                    .bright_red()
-                    .bold()
+                critical(&format!(
+                    "{failed} Critical issues found. xcargo may not function correctly."
+                ))
+            );
+            println!(
+                "{}",
+                paint(palette.error, "   Please address the issues above.")
             );
-            println!("{}", "   Please address the issues above.".red());
         } else if summary.failed > 0 {
             println!(
                 "{}",
-                "⚠️  Some checks failed. Some features may not work.".yellow()
+                paint(
+                    palette.warning,
+                    format!("{warning} Some checks failed. Some features may not work.")
+                )
             );
-            println!("{}", "   Review the issues above.".yellow());
+            println!("{}", paint(palette.warning, "   Review the issues above."));
         } else if summary.warnings > 0 {
             println!(
                 "{}",
-                "✓ System is functional. Some optional features unavailable."
-                    .yellow()
+                paint(
+                    palette.warning,
+                    format!("{passed} System is functional. Some optional features unavailable.")
+                )
             );
         } else {
             println!(
                 "{}",
-                "✓ All checks passed! Your system is ready for cross-compilation."
-                    .green()
-                    .bold()
+                paint(
+                    colors::BOLD,
+                    paint(
+                        palette.success,
+                        format!(
+                            "{passed} All checks passed! Your system is ready for cross-compilation."
+                        )
+                    )
+                )
             );
         }
     }
+
+    /// Summary as sentences, for plain output
+    fn display_plain_summary(summary: &ReportSummary) {
+        println!(
+            "Summary: {} checks, {} passed, {} with warnings, {} failed, {} critical.",
+            summary.total, summary.passed, summary.warnings, summary.failed, summary.critical
+        );
+        if summary.critical > 0 {
+            println!("Critical issues found. xcargo may not function correctly. Please address the issues above.");
+        } else if summary.failed > 0 {
+            println!("Some checks failed. Some features may not work. Review the issues above.");
+        } else if summary.warnings > 0 {
+            println!("System is functional. Some optional features are unavailable.");
+        } else {
+            println!("All checks passed. Your system is ready for cross-compilation.");
+        }
+    }
 }
 
 /// Summary statistics for the report
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct ReportSummary {
     /// Total number of checks
     pub total: usize,
@@ -172,6 +353,22 @@ pub struct ReportSummary {
     pub critical: usize,
 }
 
+/// Escape text for XML attributes and content
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -229,4 +426,45 @@ mod tests {
         // Should not panic
         report.display();
     }
+
+    #[test]
+    fn test_report_to_json() {
+        let mut report = DoctorReport::new();
+        report.add_check(CheckResult::pass("cargo", "cargo 1.80.0"));
+        report.add_check(CheckResult::fail("zig", "not found", "Install Zig"));
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json().unwrap()).unwrap();
+        assert_eq!(json["checks"][1]["name"], "zig");
+        assert_eq!(json["checks"][1]["status"], "fail");
+        assert_eq!(json["checks"][1]["suggestion"], "Install Zig");
+        assert!(json["checks"][0]["suggestion"].is_null());
+        assert_eq!(json["summary"]["total"], 2);
+        assert_eq!(json["summary"]["failed"], 1);
+    }
+
+    #[test]
+    fn test_report_to_junit() {
+        let mut report = DoctorReport::new();
+        report.add_check(CheckResult::pass("cargo", "ok"));
+        report.add_check(CheckResult::warning("docker", "not running", "Start it"));
+        report.add_check(CheckResult::critical(
+            "rustup",
+            "<missing>",
+            "Install \"rustup\"",
+        ));
+
+        let xml = report.to_junit("xcargo doctor");
+        assert!(xml.contains(r#"<testsuite name="xcargo doctor" tests="3" failures="1""#));
+        assert!(xml.contains(r#"<testcase name="cargo" classname="xcargo.doctor" />"#));
+        assert!(xml.contains("<system-out>not running\nStart it</system-out>"));
+        assert!(xml.contains(r#"<failure type="critical" message="&lt;missing&gt;">"#));
+        assert!(xml.contains("Install &quot;rustup&quot;</failure>"));
+    }
+
+    #[test]
+    fn test_report_format_from_str() {
+        assert_eq!("JSON".parse::<ReportFormat>(), Ok(ReportFormat::Json));
+        assert_eq!("junit".parse::<ReportFormat>(), Ok(ReportFormat::Junit));
+        assert!("yaml".parse::<ReportFormat>().is_err());
+    }
 }
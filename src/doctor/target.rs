@@ -0,0 +1,474 @@
+//! Target-scoped diagnostic checks
+//!
+//! `xcargo doctor --target <triple>` checks whether one target can be built
+//! from this machine: the Rust target, its linker and tools, Zig support, a
+//! container image, and the target's C library.
+
+use super::checks::CheckResult;
+use crate::config::Config;
+use crate::target::{RequirementDatabase, Target, TargetRequirements};
+use crate::toolchain::zig::ZigToolchain;
+use crate::toolchain::ToolchainManager;
+use std::path::Path;
+use std::process::Command;
+use which::which;
+
+/// Check that the Rust standard library for `target` is installed
+pub fn check_target_installed(
+    manager: &ToolchainManager,
+    toolchain: &str,
+    target: &Target,
+) -> CheckResult {
+    match manager.is_target_installed(toolchain, &target.triple) {
+        Ok(true) => CheckResult::pass(
+            "rust target",
+            format!("{} is installed for {toolchain}", target.triple),
+        ),
+        Ok(false) => CheckResult::fail(
+            "rust target",
+            format!("{} is not installed for {toolchain}", target.triple),
+            format!("Run: xcargo target add {}", target.triple),
+        ),
+        Err(e) => CheckResult::fail(
+            "rust target",
+            format!("Could not check installed targets: {e}"),
+            "Ensure rustup is properly installed",
+        ),
+    }
+}
+
+/// Check the rustup components the target needs (e.g. `rust-src` for bare metal)
+///
+/// Returns `None` if the target needs no components.
+pub fn check_target_components(
+    manager: &ToolchainManager,
+    toolchain: &str,
+    requirements: &TargetRequirements,
+) -> Option<CheckResult> {
+    if requirements.components.is_empty() {
+        return None;
+    }
+
+    Some(
+        match manager.missing_components(toolchain, &requirements.components) {
+            Ok(missing) if missing.is_empty() => CheckResult::pass(
+                "components",
+                format!("Installed: {}", requirements.components.join(", ")),
+            ),
+            Ok(missing) => CheckResult::fail(
+                "components",
+                format!("Missing: {}", missing.join(", ")),
+                format!(
+                    "Run: rustup component add --toolchain {toolchain} {}",
+                    missing.join(" ")
+                ),
+            ),
+            Err(e) => CheckResult::warning(
+                "components",
+                format!("Could not check components: {e}"),
+                "Ensure rustup is properly installed",
+            ),
+        },
+    )
+}
+
+/// Linker xcargo would use for `target`: the one configured in xcargo.toml,
+/// the target's cross linker, or `None` for the host's default linker
+#[must_use]
+pub fn target_linker(target: &Target, host: &Target, config: &Config) -> Option<String> {
+    config
+        .get_target_config(&target.triple)
+        .and_then(|c| c.linker.clone())
+        .or_else(|| {
+            if target.triple == host.triple {
+                None
+            } else {
+                target.get_requirements().linker
+            }
+        })
+}
+
+/// Check that the target's linker is available
+///
+/// A missing cross linker is only a warning when Zig can link instead.
+pub fn check_target_linker(
+    target: &Target,
+    host: &Target,
+    config: &Config,
+    zig: Option<&ZigToolchain>,
+) -> CheckResult {
+    let configured = config
+        .get_target_config(&target.triple)
+        .and_then(|c| c.linker.as_deref());
+
+    let Some(linker) = target_linker(target, host, config) else {
+        return CheckResult::pass(
+            "linker",
+            if target.triple == host.triple {
+                "Uses the host's default linker"
+            } else {
+                "No separate linker required"
+            },
+        );
+    };
+
+    if let Ok(path) = which(&linker) {
+        return CheckResult::pass("linker", format!("{linker} found at {}", path.display()));
+    }
+
+    if configured.is_some() {
+        return CheckResult::fail(
+            "linker",
+            format!("{linker} (configured in xcargo.toml) not found in PATH"),
+            "Install it or update the linker in xcargo.toml",
+        );
+    }
+
+    if zig.is_some_and(|zig| zig.supports_target(target)) {
+        return CheckResult::warning(
+            "linker",
+            format!("{linker} not found; Zig will be used to link instead"),
+            install_hint(target, &linker),
+        );
+    }
+
+    CheckResult::fail(
+        "linker",
+        format!("{linker} not found in PATH"),
+        install_hint(target, &linker),
+    )
+}
+
+/// Check tools other than the linker that the target needs (e.g. `xcrun`)
+///
+/// Returns `None` if the target needs no other tools.
+#[must_use]
+pub fn check_target_tools(
+    target: &Target,
+    requirements: &TargetRequirements,
+) -> Option<CheckResult> {
+    let tools: Vec<&String> = requirements
+        .tools
+        .iter()
+        .filter(|tool| requirements.linker.as_ref() != Some(*tool))
+        .collect();
+    if tools.is_empty() {
+        return None;
+    }
+
+    let missing: Vec<&str> = tools
+        .iter()
+        .filter(|tool| which(tool.as_str()).is_err())
+        .map(|tool| tool.as_str())
+        .collect();
+
+    Some(if missing.is_empty() {
+        CheckResult::pass(
+            "tools",
+            format!(
+                "Found: {}",
+                tools
+                    .iter()
+                    .map(|t| t.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        )
+    } else {
+        let mut problems = vec![format!("Missing: {}", missing.join(", "))];
+        problems.extend(
+            requirements
+                .env_vars
+                .iter()
+                .filter(|(name, _)| std::env::var_os(name).is_none())
+                .map(|(name, _)| format!("{name} is not set")),
+        );
+        CheckResult::fail(
+            "tools",
+            problems.join("; "),
+            install_hint(target, missing[0]),
+        )
+    })
+}
+
+/// Check whether Zig can cross-compile to the target
+#[must_use]
+pub fn check_target_zig(target: &Target, zig: Option<&ZigToolchain>) -> CheckResult {
+    let supported = ZigToolchain::supports_target_name(&target.triple);
+    match (supported, zig) {
+        (true, Some(zig)) => CheckResult::pass(
+            "zig",
+            format!(
+                "Zig {} can cross-compile to {}",
+                zig.version(),
+                target.triple
+            ),
+        ),
+        (true, None) => CheckResult::warning(
+            "zig",
+            format!(
+                "Zig supports {} but is not installed (optional)",
+                target.triple
+            ),
+            "Install Zig for zero-config cross-compilation: https://ziglang.org/download/",
+        ),
+        (false, _) => CheckResult::pass(
+            "zig",
+            format!(
+                "Not used for {} (native or container builds apply)",
+                target.triple
+            ),
+        ),
+    }
+}
+
+/// Check that a container image for the target exists and is available locally
+#[cfg(feature = "container")]
+#[must_use]
+pub fn check_target_container_image(target: &Target, config: &Config) -> CheckResult {
+    use crate::container::{ContainerBuilder, ImageSelector, RuntimeType};
+
+    let image = match ImageSelector::from_config(&config.container).select_for_target(&target.triple) {
+        Ok(image) => image.full_name(),
+        Err(e) => {
+            return CheckResult::warning(
+                "container image",
+                e.to_string().lines().next().unwrap_or_default().to_string(),
+                "Set an image for this target under [container.images] in xcargo.toml",
+            )
+        }
+    };
+
+    let builder = RuntimeType::from_str(&config.container.runtime)
+        .and_then(ContainerBuilder::new)
+        .ok()
+        .filter(ContainerBuilder::is_available);
+    let Some(builder) = builder else {
+        return CheckResult::warning(
+            "container image",
+            format!("{image} is available, but no container runtime was found"),
+            "Install Docker or Podman for container builds",
+        );
+    };
+
+    if builder.has_image(&image) {
+        CheckResult::pass("container image", format!("{image} is present locally"))
+    } else {
+        CheckResult::warning(
+            "container image",
+            format!("{image} has not been pulled yet"),
+            format!(
+                "It is pulled on the first container build, or run: {} pull {image}",
+                builder.runtime_name()
+            ),
+        )
+    }
+}
+
+/// C library the linker must find for `target`, if it links against one
+///
+/// musl targets are skipped since Rust ships a self-contained musl.
+#[must_use]
+pub fn target_c_library(target: &Target) -> Option<&'static str> {
+    match (target.os.as_str(), target.env.as_deref()) {
+        ("windows", Some("gnu")) => Some("libkernel32.a"),
+        ("linux", Some(env)) if env.starts_with("gnu") => Some("libc.so"),
+        _ => None,
+    }
+}
+
+/// Check that the linker finds the target's C library (e.g. a cross glibc)
+///
+/// Returns `None` if the target has no such library or the linker is missing,
+/// which the linker check already reports.
+#[must_use]
+pub fn check_target_system_libs(target: &Target, linker: Option<&str>) -> Option<CheckResult> {
+    let library = target_c_library(target)?;
+    let linker = linker.unwrap_or("cc");
+    which(linker).ok()?;
+
+    Some(match linker_library(linker, library) {
+        Some(found) => CheckResult::pass("system libraries", format!("{library} found at {found}")),
+        None => CheckResult::fail(
+            "system libraries",
+            format!("{linker} cannot find {library} for {}", target.triple),
+            install_hint(target, linker),
+        ),
+    })
+}
+
+/// Check that a BSD target's libc is available to link against
+///
+/// Zig provides FreeBSD's; otherwise the linker must use a sysroot of the
+/// target OS. Returns `None` for other targets, or if the linker is missing,
+/// which the linker check already reports. Only meaningful when
+/// cross-compiling.
+#[must_use]
+pub fn check_target_sysroot(
+    target: &Target,
+    linker: Option<&str>,
+    zig: Option<&ZigToolchain>,
+) -> Option<CheckResult> {
+    if !target.is_bsd() {
+        return None;
+    }
+    let system = bsd_name(&target.os);
+
+    if let Some(zig) = zig.filter(|zig| zig.supports_target(target)) {
+        return Some(CheckResult::pass(
+            "sysroot",
+            format!("Zig {} provides {system}'s libc", zig.version()),
+        ));
+    }
+
+    let Some(linker) = linker else {
+        return Some(CheckResult::fail(
+            "sysroot",
+            format!(
+                "No {system} sysroot; the host's linker cannot link for {}",
+                target.triple
+            ),
+            sysroot_hint(target, system),
+        ));
+    };
+    which(linker).ok()?;
+
+    Some(match linker_library(linker, "libc.so") {
+        Some(found) => CheckResult::pass("sysroot", format!("{linker} links against {found}")),
+        None => CheckResult::fail(
+            "sysroot",
+            format!("{linker} cannot find {system}'s libc.so"),
+            sysroot_hint(target, system),
+        ),
+    })
+}
+
+/// Path of `library` as the linker resolves it, if the linker finds it
+fn linker_library(linker: &str, library: &str) -> Option<String> {
+    let output = Command::new(linker)
+        .arg(format!("-print-file-name={library}"))
+        .output()
+        .ok()?;
+    let found = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // gcc and clang print the bare name back when the library is not found
+    (Path::new(&found).is_absolute() && Path::new(&found).exists()).then_some(found)
+}
+
+/// Display name of a BSD operating system
+fn bsd_name(os: &str) -> &str {
+    match os {
+        "freebsd" => "FreeBSD",
+        "netbsd" => "NetBSD",
+        "openbsd" => "OpenBSD",
+        "dragonfly" => "DragonFly BSD",
+        other => other,
+    }
+}
+
+/// How to get a sysroot for a BSD target, from the target database
+fn sysroot_hint(target: &Target, system: &str) -> String {
+    RequirementDatabase::global()
+        .lookup(&target.triple)
+        .install_instructions(&target.triple, std::env::consts::OS)
+        .and_then(|lines| lines.into_iter().find(|line| !line.starts_with('#')))
+        .map_or_else(
+            || {
+                format!(
+                    "Build on {system}, or set a linker using a {system} sysroot in xcargo.toml"
+                )
+            },
+            |command| format!("Run: {command}"),
+        )
+}
+
+/// Installation instruction for a missing tool
+fn install_hint(target: &Target, tool: &str) -> String {
+    target
+        .get_install_instructions()
+        .into_iter()
+        .find(|line| !line.starts_with('#'))
+        .map_or_else(
+            || format!("Install {tool}"),
+            |command| format!("Install {tool}, e.g.: {command}"),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_c_library() {
+        let target = |triple| Target::from_triple(triple).unwrap();
+        assert_eq!(
+            target_c_library(&target("aarch64-unknown-linux-gnu")),
+            Some("libc.so")
+        );
+        assert_eq!(
+            target_c_library(&target("x86_64-pc-windows-gnu")),
+            Some("libkernel32.a")
+        );
+        assert_eq!(target_c_library(&target("x86_64-unknown-linux-musl")), None);
+        assert_eq!(target_c_library(&target("wasm32-unknown-unknown")), None);
+    }
+
+    #[test]
+    fn test_check_target_sysroot() {
+        let target = |triple| Target::from_triple(triple).unwrap();
+        assert!(check_target_sysroot(&target("x86_64-unknown-linux-gnu"), None, None).is_none());
+
+        let check = check_target_sysroot(&target("x86_64-unknown-freebsd"), None, None).unwrap();
+        assert_eq!(check.status, super::super::CheckStatus::Fail);
+        assert!(check.message.contains("No FreeBSD sysroot"));
+        assert_eq!(
+            check.suggestion.as_deref(),
+            Some("Run: xcargo build --target x86_64-unknown-freebsd --zig")
+        );
+
+        let check = check_target_sysroot(&target("x86_64-unknown-openbsd"), None, None).unwrap();
+        assert!(check.suggestion.unwrap().starts_with("Build on OpenBSD"));
+    }
+
+    #[test]
+    fn test_target_linker() {
+        let host = Target::from_triple("x86_64-unknown-linux-gnu").unwrap();
+        let target = Target::from_triple("aarch64-unknown-linux-gnu").unwrap();
+
+        let config = Config::default();
+        assert_eq!(target_linker(&host, &host, &config), None);
+        assert_eq!(
+            target_linker(&target, &host, &config).as_deref(),
+            Some("aarch64-linux-gnu-gcc")
+        );
+
+        let config = Config::from_str(
+            r#"
+            [targets."aarch64-unknown-linux-gnu"]
+            linker = "my-linker"
+        "#,
+        )
+        .unwrap();
+        assert_eq!(
+            target_linker(&target, &host, &config).as_deref(),
+            Some("my-linker")
+        );
+    }
+
+    #[test]
+    fn test_configured_linker_missing() {
+        let host = Target::from_triple("x86_64-unknown-linux-gnu").unwrap();
+        let target = Target::from_triple("aarch64-unknown-linux-gnu").unwrap();
+        let config = Config::from_str(
+            r#"
+            [targets."aarch64-unknown-linux-gnu"]
+            linker = "xcargo-no-such-linker"
+        "#,
+        )
+        .unwrap();
+
+        let result = check_target_linker(&target, &host, &config, None);
+        assert_eq!(result.status, super::super::CheckStatus::Fail);
+        assert!(result.message.contains("xcargo.toml"));
+    }
+}
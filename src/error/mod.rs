@@ -2,6 +2,8 @@
 
 mod suggestions;
 
+pub use suggestions::missing_package_suggestions;
+
 use thiserror::Error;
 
 /// Exit codes for CI systems
@@ -24,6 +26,12 @@ pub enum ExitCode {
     ContainerError = 6,
     /// IO error (file not found, permission denied)
     IoError = 7,
+    /// Build policy violation
+    PolicyError = 8,
+    /// Some targets of a multi-target build failed, but others built
+    PartialBuild = 9,
+    /// A build ran past its timeout and was killed, as with `timeout(1)`
+    Timeout = 124,
     /// User cancelled operation
     UserCancelled = 130,
 }
@@ -37,11 +45,21 @@ impl From<&Error> for ExitCode {
             Error::Toolchain(_)
             | Error::ToolchainMissing { .. }
             | Error::LinkerMissing { .. } => ExitCode::ToolchainError,
-            Error::Build(_) | Error::BuildFailed { .. } => ExitCode::BuildError,
-            Error::Config(_) | Error::ConfigParse { .. } => ExitCode::ConfigError,
+            Error::TargetsFailed { succeeded, .. } if *succeeded > 0 => ExitCode::PartialBuild,
+            Error::Build(_) | Error::BuildFailed { .. } | Error::TargetsFailed { .. } => {
+                ExitCode::BuildError
+            }
+            Error::Config(_)
+            | Error::ConfigParse { .. }
+            | Error::ProfileNotFound { .. }
+            | Error::PluginIncompatible { .. }
+            | Error::VersionRequired { .. } => ExitCode::ConfigError,
             Error::Container(_) | Error::ContainerNotAvailable { .. } => {
                 ExitCode::ContainerError
             }
+            Error::PolicyViolation { .. } => ExitCode::PolicyError,
+            Error::Cancelled { .. } => ExitCode::UserCancelled,
+            Error::BuildTimeout { .. } => ExitCode::Timeout,
         }
     }
 }
@@ -109,12 +127,33 @@ pub enum Error {
         suggestion: Option<String>,
     },
 
+    /// Some targets of a multi-target build failed
+    #[error("{} target(s) failed: {}", .failed.len(), .failed.join(", "))]
+    TargetsFailed {
+        /// Targets that failed, in build order
+        failed: Vec<String>,
+        /// Number of targets that built successfully
+        succeeded: usize,
+    },
+
     /// Configuration error (simple)
     #[error("Configuration error: {0}")]
     Config(String),
 
+    /// Named profile not defined in xcargo.toml
+    #[error("Profile '{profile}' not found")]
+    ProfileNotFound {
+        /// The requested profile
+        profile: String,
+        /// Similarly named profiles
+        suggestions: Vec<String>,
+    },
+
     /// Config parse error with location
-    #[error("Failed to parse configuration")]
+    #[error(
+        "Failed to parse {path}{}: {message}",
+        .line.map(|line| format!(":{line}")).unwrap_or_default()
+    )]
     ConfigParse {
         /// Config file path
         path: String,
@@ -136,6 +175,58 @@ pub enum Error {
         /// Install hint
         install_hint: String,
     },
+
+    /// Build policy violation
+    #[error("Policy '{policy}' requires the {required} strategy, but {actual} was selected")]
+    PolicyViolation {
+        /// Name of the violated policy
+        policy: String,
+        /// Strategy required by the policy
+        required: String,
+        /// Strategy xcargo would have used
+        actual: String,
+    },
+
+    /// Plugin failed the version/capability handshake
+    #[error("Plugin '{plugin}' is incompatible: {reason}")]
+    PluginIncompatible {
+        /// Plugin name
+        plugin: String,
+        /// Why the plugin was refused
+        reason: String,
+        /// How to resolve the incompatibility
+        upgrade: String,
+    },
+
+    /// The project requires a different version of xcargo
+    #[error("{path} requires xcargo {required}, but this is xcargo {current}")]
+    VersionRequired {
+        /// Version requirement from the configuration
+        required: String,
+        /// Version of the running xcargo
+        current: String,
+        /// Configuration file that set the requirement
+        path: String,
+    },
+
+    /// A build was cancelled through its cancellation token
+    #[error("Build for target '{target}' was cancelled")]
+    Cancelled {
+        /// Target whose build was cancelled
+        target: String,
+    },
+
+    /// A build ran longer than `build.timeout` and was killed
+    #[error(
+        "Build for target '{target}' timed out after {}",
+        crate::build::format_duration(*.timeout)
+    )]
+    BuildTimeout {
+        /// Target whose build timed out
+        target: String,
+        /// The timeout that was exceeded
+        timeout: std::time::Duration,
+    },
 }
 
 impl Error {
@@ -171,6 +262,48 @@ mod tests {
         assert_eq!(err.exit_code(), ExitCode::BuildError as i32);
     }
 
+    #[test]
+    fn test_exit_code_targets_failed() {
+        let partial = Error::TargetsFailed {
+            failed: vec!["aarch64-apple-darwin".to_string()],
+            succeeded: 2,
+        };
+        assert_eq!(partial.exit_code(), ExitCode::PartialBuild as i32);
+        assert_eq!(
+            partial.to_string(),
+            "1 target(s) failed: aarch64-apple-darwin"
+        );
+
+        let all = Error::TargetsFailed {
+            failed: vec!["aarch64-apple-darwin".to_string()],
+            succeeded: 0,
+        };
+        assert_eq!(all.exit_code(), ExitCode::BuildError as i32);
+    }
+
+    #[test]
+    fn test_exit_code_build_timeout() {
+        let err = Error::BuildTimeout {
+            target: "aarch64-unknown-linux-gnu".to_string(),
+            timeout: std::time::Duration::from_secs(2700),
+        };
+        assert_eq!(err.exit_code(), ExitCode::Timeout as i32);
+        assert_eq!(
+            err.to_string(),
+            "Build for target 'aarch64-unknown-linux-gnu' timed out after 45m"
+        );
+    }
+
+    #[test]
+    fn test_exit_code_policy_error() {
+        let err = Error::PolicyViolation {
+            policy: "release_requires".to_string(),
+            required: "container".to_string(),
+            actual: "native".to_string(),
+        };
+        assert_eq!(err.exit_code(), ExitCode::PolicyError as i32);
+    }
+
     #[test]
     fn test_exit_code_config_error() {
         let err = Error::Config("bad config".to_string());
@@ -1,6 +1,236 @@
 //! Error suggestion and hint generation
 
 use super::Error;
+use crate::output::i18n::{tr, trf};
+
+/// A system package a failed build was missing, recognized in its output
+struct BuildErrorRule {
+    /// Libraries the linker could not find, as in `-lssl`
+    libs: &'static [&'static str],
+    /// Other text in cargo's or a build script's output that identifies the
+    /// failure
+    patterns: &'static [&'static str],
+    /// What is missing, e.g. "OpenSSL"
+    missing: &'static str,
+    /// Debian/Ubuntu package
+    apt: &'static str,
+    /// Fedora/RHEL package
+    dnf: &'static str,
+    /// Homebrew formula
+    brew: &'static str,
+    /// A way around the system package, if there is one
+    alternative: Option<&'static str>,
+}
+
+/// How linkers report a library they cannot find: GNU ld, LLVM's lld, and
+/// Apple's ld
+const LINKER_PATTERNS: &[&str] = &[
+    "cannot find -l",
+    "unable to find library -l",
+    "library not found for -l",
+];
+
+/// Failures caused by missing system packages
+const BUILD_ERROR_RULES: &[BuildErrorRule] = &[
+    BuildErrorRule {
+        libs: &["ssl", "crypto"],
+        patterns: &[
+            "Could not find directory of OpenSSL installation",
+            "Could not find openssl via pkg-config",
+        ],
+        missing: "OpenSSL",
+        apt: "libssl-dev",
+        dnf: "openssl-devel",
+        brew: "openssl@3",
+        alternative: Some(
+            "Or build OpenSSL from source with the openssl crate's \"vendored\" feature",
+        ),
+    },
+    BuildErrorRule {
+        libs: &["z"],
+        patterns: &["The system library `zlib` required"],
+        missing: "zlib",
+        apt: "zlib1g-dev",
+        dnf: "zlib-devel",
+        brew: "zlib",
+        alternative: None,
+    },
+    BuildErrorRule {
+        libs: &["zstd"],
+        patterns: &["Relink with --no-zstd", "The system library `libzstd` required"],
+        missing: "zstd",
+        apt: "libzstd-dev",
+        dnf: "libzstd-devel",
+        brew: "zstd",
+        alternative: None,
+    },
+    BuildErrorRule {
+        libs: &["sqlite3"],
+        patterns: &["The system library `sqlite3` required"],
+        missing: "SQLite",
+        apt: "libsqlite3-dev",
+        dnf: "sqlite-devel",
+        brew: "sqlite",
+        alternative: Some("Or bundle SQLite with rusqlite's \"bundled\" feature"),
+    },
+    BuildErrorRule {
+        libs: &["asound"],
+        patterns: &["The system library `alsa` required"],
+        missing: "ALSA",
+        apt: "libasound2-dev",
+        dnf: "alsa-lib-devel",
+        brew: "",
+        alternative: None,
+    },
+    BuildErrorRule {
+        libs: &["udev"],
+        patterns: &["The system library `libudev` required"],
+        missing: "libudev",
+        apt: "libudev-dev",
+        dnf: "systemd-devel",
+        brew: "",
+        alternative: None,
+    },
+    BuildErrorRule {
+        libs: &["dbus-1"],
+        patterns: &["The system library `dbus-1` required"],
+        missing: "D-Bus",
+        apt: "libdbus-1-dev",
+        dnf: "dbus-devel",
+        brew: "dbus",
+        alternative: None,
+    },
+    BuildErrorRule {
+        libs: &[],
+        patterns: &[
+            "pkg-config command could not be found",
+            "Could not run `\"pkg-config\"",
+            "Could not run `PKG_CONFIG_ALLOW_SYSTEM_CFLAGS=1 \"pkg-config\"",
+        ],
+        missing: "pkg-config",
+        apt: "pkg-config",
+        dnf: "pkgconf-pkg-config",
+        brew: "pkgconf",
+        alternative: None,
+    },
+];
+
+/// Install suggestions for the system packages a failed build of `target`
+/// on `host` was missing, recognized in the build's output
+///
+/// Each suggestion names what is missing and how to install it on the host;
+/// cross builds on Debian-based hosts install the target's architecture of
+/// the package. Returns nothing if the output matches no known failure.
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::error::missing_package_suggestions;
+///
+/// let output = "/usr/bin/ld: cannot find -lssl: No such file or directory";
+/// let suggestions = missing_package_suggestions(
+///     output,
+///     "x86_64-unknown-linux-gnu",
+///     "x86_64-unknown-linux-gnu",
+/// );
+/// assert!(suggestions[0].contains("sudo apt install libssl-dev"));
+/// ```
+#[must_use]
+pub fn missing_package_suggestions(output: &str, target: &str, host: &str) -> Vec<String> {
+    let mut suggestions = Vec::new();
+    for rule in BUILD_ERROR_RULES {
+        let found = rule.libs.iter().any(|lib| missing_lib(output, lib))
+            || rule.patterns.iter().any(|pattern| output.contains(pattern));
+        if !found {
+            continue;
+        }
+        suggestions.push(install_suggestion(rule, target, host));
+        if let Some(alternative) = rule.alternative {
+            suggestions.push(tr(alternative).to_string());
+        }
+    }
+    suggestions
+}
+
+/// Whether a linker reported in `output` that it cannot find `lib`
+///
+/// Only whole library names match, so `-lz` does not match `-lzstd`.
+fn missing_lib(output: &str, lib: &str) -> bool {
+    LINKER_PATTERNS.iter().any(|pattern| {
+        let pattern = format!("{pattern}{lib}");
+        output.match_indices(&pattern).any(|(start, _)| {
+            output[start + pattern.len()..]
+                .chars()
+                .next()
+                .map_or(true, |next| {
+                    !(next.is_ascii_alphanumeric() || "_-+.".contains(next))
+                })
+        })
+    })
+}
+
+/// How to install `rule`'s package for `target` on `host`
+fn install_suggestion(rule: &BuildErrorRule, target: &str, host: &str) -> String {
+    let missing = rule.missing;
+    let native = target == host;
+    if host.contains("linux") {
+        if native {
+            return trf(
+                "{missing} is missing: sudo apt install {apt}  # or: sudo dnf install {dnf}",
+                &[
+                    ("missing", &missing),
+                    ("apt", &rule.apt),
+                    ("dnf", &rule.dnf),
+                ],
+            );
+        }
+        // Multiarch packages only exist for glibc targets
+        let glibc = target.ends_with("-linux-gnu") || target.contains("-linux-gnueabi");
+        if let Some(arch) = debian_arch(target).filter(|_| glibc) {
+            return trf(
+                "{missing} for {target} is missing: sudo dpkg --add-architecture {arch} && sudo apt update && sudo apt install {apt}:{arch}",
+                &[
+                    ("missing", &missing),
+                    ("target", &target),
+                    ("arch", &arch),
+                    ("apt", &rule.apt),
+                ],
+            );
+        }
+    } else if host.contains("apple") && native && !rule.brew.is_empty() {
+        return trf(
+            "{missing} is missing: brew install {brew}",
+            &[("missing", &missing), ("brew", &rule.brew)],
+        );
+    }
+    if native {
+        return trf(
+            "{missing} is missing; install its development files",
+            &[("missing", &missing)],
+        );
+    }
+    trf(
+        "{missing} for {target} is missing; build in a container with --container, where the target's packages can be installed",
+        &[("missing", &missing), ("target", &target)],
+    )
+}
+
+/// Debian's name for the architecture of a Rust target, for multiarch packages
+fn debian_arch(target: &str) -> Option<&'static str> {
+    let arch = target.split('-').next()?;
+    Some(match arch {
+        "x86_64" => "amd64",
+        "aarch64" => "arm64",
+        "i686" | "i586" => "i386",
+        "armv7" => "armhf",
+        "arm" if target.ends_with("hf") => "armhf",
+        "arm" => "armel",
+        "riscv64gc" => "riscv64",
+        "powerpc64le" => "ppc64el",
+        "s390x" => "s390x",
+        _ => return None,
+    })
+}
 
 impl Error {
     /// Get a suggestion for fixing this error
@@ -9,16 +239,47 @@ impl Error {
         match self {
             Error::InvalidTarget { suggestions, .. } => {
                 if suggestions.is_empty() {
-                    Some("Run 'xcargo target list' to see available targets".to_string())
+                    Some(tr("Run 'xcargo target list' to see available targets").to_string())
                 } else {
-                    Some(format!("Did you mean: {}?", suggestions.join(", ")))
+                    Some(trf(
+                        "Did you mean: {suggestions}?",
+                        &[("suggestions", &suggestions.join(", "))],
+                    ))
                 }
             }
             Error::ToolchainMissing { install_hint, .. } => Some(install_hint.clone()),
             Error::LinkerMissing { install_hint, .. } => Some(install_hint.clone()),
             Error::BuildFailed { suggestion, .. } => suggestion.clone(),
             Error::ContainerNotAvailable { install_hint, .. } => Some(install_hint.clone()),
-            Error::ConfigParse { path, .. } => Some(format!("Check {path} for syntax errors")),
+            Error::ConfigParse { path, .. } => {
+                Some(trf("Check {path} for syntax errors", &[("path", path)]))
+            }
+            Error::ProfileNotFound { suggestions, .. } => {
+                if suggestions.is_empty() {
+                    Some(tr("Run 'xcargo profile list' to see available profiles").to_string())
+                } else {
+                    Some(trf(
+                        "Did you mean: {suggestions}?",
+                        &[("suggestions", &suggestions.join(", "))],
+                    ))
+                }
+            }
+            Error::PolicyViolation { required, .. } => {
+                let flag = match required.as_str() {
+                    "container" => "--container",
+                    "zig" => "--zig",
+                    _ => "--no-zig",
+                };
+                Some(trf("Re-run with {flag}", &[("flag", &flag)]))
+            }
+            Error::PluginIncompatible { upgrade, .. } => Some(upgrade.clone()),
+            Error::VersionRequired { .. } => {
+                Some(tr("Upgrade with: cargo install xcargo --locked").to_string())
+            }
+            Error::BuildTimeout { target, .. } => Some(trf(
+                "Raise build.timeout, or set targets.\"{target}\".timeout for this target",
+                &[("target", target)],
+            )),
             _ => None,
         }
     }
@@ -28,18 +289,38 @@ impl Error {
     pub fn hint(&self) -> Option<String> {
         match self {
             Error::TargetNotFound(_) | Error::InvalidTarget { .. } => {
-                Some("Use 'xcargo target list' to see available targets".to_string())
+                Some(tr("Use 'xcargo target list' to see available targets").to_string())
             }
-            Error::LinkerMissing { target, .. } => Some(format!(
-                "Cross-compiling to {target} requires a compatible linker"
+            Error::LinkerMissing { target, .. } => Some(trf(
+                "Cross-compiling to {target} requires a compatible linker",
+                &[("target", target)],
             )),
             Error::BuildFailed {
                 exit_code: Some(code),
                 ..
-            } => Some(format!("Cargo exited with code {code}")),
-            Error::ContainerNotAvailable { runtime, .. } => {
-                Some(format!("Tried to use {runtime} but it's not running"))
-            }
+            } => Some(trf("Cargo exited with code {code}", &[("code", code)])),
+            Error::TargetsFailed { succeeded, .. } if *succeeded > 0 => Some(trf(
+                "The artifacts of the {succeeded} target(s) that built are in place",
+                &[("succeeded", succeeded)],
+            )),
+            Error::ContainerNotAvailable { runtime, .. } => Some(trf(
+                "Tried to use {runtime} but it's not running",
+                &[("runtime", runtime)],
+            )),
+            Error::PolicyViolation { .. } => Some(
+                tr("Build policies are set in the [policy] section of xcargo.toml").to_string(),
+            ),
+            Error::ProfileNotFound { .. } => Some(
+                tr("Profiles are defined in [profiles.<name>] sections of xcargo.toml").to_string(),
+            ),
+            Error::PluginIncompatible { .. } => Some(trf(
+                "This xcargo implements plugin API v{version}",
+                &[("version", &crate::plugin::PLUGIN_API_VERSION)],
+            )),
+            Error::VersionRequired { path, .. } => Some(trf(
+                "{path} sets required_version because the project relies on newer xcargo features",
+                &[("path", path)],
+            )),
             _ => None,
         }
     }
@@ -48,22 +329,27 @@ impl Error {
     #[must_use]
     pub fn linker_not_found(linker: &str, target: &str, host_os: &str) -> Self {
         let install_hint = match (host_os, target) {
-            ("macos", t) if t.contains("windows") => {
-                "brew install mingw-w64".to_string()
-            }
-            ("macos", t) if t.contains("linux") => {
-                "Consider using Zig: brew install zig && xcargo build --zig".to_string()
-            }
-            ("linux", t) if t.contains("windows") => {
-                "sudo apt install mingw-w64  # or your distro's package manager".to_string()
-            }
-            ("linux", t) if t.contains("darwin") || t.contains("apple") => {
-                "macOS cross-compilation requires osxcross: https://github.com/tpoechtrager/osxcross".to_string()
-            }
-            ("windows", t) if t.contains("linux") => {
-                "Consider using Zig: scoop install zig && xcargo build --zig".to_string()
-            }
-            _ => format!("Install a linker that supports {target}"),
+            ("macos", t) if t.contains("windows") => "brew install mingw-w64".to_string(),
+            ("macos", t) if t.contains("linux") => trf(
+                "Consider using Zig: {command}",
+                &[("command", &"brew install zig && xcargo build --zig")],
+            ),
+            ("linux", t) if t.contains("windows") => trf(
+                "{command}  # or your distro's package manager",
+                &[("command", &"sudo apt install mingw-w64")],
+            ),
+            ("linux", t) if t.contains("darwin") || t.contains("apple") => trf(
+                "macOS cross-compilation requires osxcross: {url}",
+                &[("url", &"https://github.com/tpoechtrager/osxcross")],
+            ),
+            ("windows", t) if t.contains("linux") => trf(
+                "Consider using Zig: {command}",
+                &[("command", &"scoop install zig && xcargo build --zig")],
+            ),
+            _ => trf(
+                "Install a linker that supports {target}",
+                &[("target", &target)],
+            ),
         };
 
         Error::LinkerMissing {
@@ -76,23 +362,39 @@ impl Error {
     /// Create a container not available error with platform-specific hints
     #[must_use]
     pub fn container_not_found(runtime: &str, host_os: &str) -> Self {
+        let docker_desktop = || {
+            trf(
+                "Install Docker Desktop: {url}",
+                &[("url", &"https://www.docker.com/products/docker-desktop")],
+            )
+        };
+        let podman = |command: &str| trf("Or Podman: {command}", &[("command", &command)]);
         let install_hint = match host_os {
-            "macos" => {
-                "Install Docker Desktop: https://www.docker.com/products/docker-desktop\n\
-                 Or Podman: brew install podman && podman machine init && podman machine start"
-                    .to_string()
-            }
-            "linux" => {
-                "Install Docker: sudo apt install docker.io && sudo systemctl start docker\n\
-                 Or Podman: sudo apt install podman"
-                    .to_string()
-            }
-            "windows" => {
-                "Install Docker Desktop: https://www.docker.com/products/docker-desktop\n\
-                 Or Podman: winget install RedHat.Podman"
-                    .to_string()
-            }
-            _ => format!("Install {runtime} or a compatible container runtime"),
+            "macos" => format!(
+                "{}\n{}",
+                docker_desktop(),
+                podman("brew install podman && podman machine init && podman machine start")
+            ),
+            "linux" => format!(
+                "{}\n{}",
+                trf(
+                    "Install Docker: {command}",
+                    &[(
+                        "command",
+                        &"sudo apt install docker.io && sudo systemctl start docker"
+                    )],
+                ),
+                podman("sudo apt install podman")
+            ),
+            "windows" => format!(
+                "{}\n{}",
+                docker_desktop(),
+                podman("winget install RedHat.Podman")
+            ),
+            _ => trf(
+                "Install {runtime} or a compatible container runtime",
+                &[("runtime", &runtime)],
+            ),
         };
 
         Error::ContainerNotAvailable {
@@ -139,6 +441,33 @@ mod tests {
         assert!(suggestion.unwrap().contains("Did you mean"));
     }
 
+    #[test]
+    fn test_missing_package_suggestions() {
+        let linux = "x86_64-unknown-linux-gnu";
+        let output = "  = note: rust-lld: error: unable to find library -lzstd";
+        let suggestions = missing_package_suggestions(output, linux, linux);
+        assert_eq!(suggestions.len(), 1);
+        assert!(suggestions[0].starts_with("zstd is missing"));
+
+        let output = "Could not find directory of OpenSSL installation";
+        let suggestions = missing_package_suggestions(output, "aarch64-unknown-linux-gnu", linux);
+        assert!(suggestions[0].contains("sudo apt install libssl-dev:arm64"));
+        assert!(suggestions[1].contains("vendored"));
+
+        let output = "The pkg-config command could not be found.";
+        let mac = "aarch64-apple-darwin";
+        let suggestions = missing_package_suggestions(output, mac, mac);
+        assert_eq!(suggestions, ["pkg-config is missing: brew install pkgconf"]);
+
+        // musl targets have no multiarch packages
+        let musl = "x86_64-unknown-linux-musl";
+        let suggestions = missing_package_suggestions("cannot find -lz", musl, linux);
+        assert!(suggestions[0].contains("--container"));
+
+        let output = "error[E0308]: mismatched types";
+        assert!(missing_package_suggestions(output, linux, linux).is_empty());
+    }
+
     #[test]
     fn test_hint_target_not_found() {
         let err = Error::TargetNotFound("invalid-target".to_string());
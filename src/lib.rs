@@ -48,15 +48,25 @@
 //! }
 //!
 //! // Configure and execute a build
-//! let options = BuildOptions::new()
-//!     .target("x86_64-pc-windows-gnu")
-//!     .release(true)
-//!     .use_zig(true);
-//!
-//! let builder = Builder::new(options)?;
-//! builder.execute()?;
+//! let options = BuildOptions {
+//!     target: Some("x86_64-pc-windows-gnu".to_string()),
+//!     release: true,
+//!     use_zig: Some(true),
+//!     ..Default::default()
+//! };
+//!
+//! let builder = Builder::new()?;
+//! let report = builder.build_with_report(&options)?;
+//! println!("Built with {:?} in {:?}", report.strategy, report.duration);
+//! for artifact in &report.artifacts {
+//!     println!("  {}", artifact.display());
+//! }
 //! ```
 //!
+//! Services built on tokio can use [`Builder::build_async`](build::Builder::build_async)
+//! instead, which runs cargo on the blocking pool and kills it when a
+//! [`CancellationToken`](build::CancellationToken) is cancelled.
+//!
 //! ## Configuration
 //!
 //! Create an `xcargo.toml` file to configure cross-compilation targets:
@@ -219,6 +229,9 @@ pub mod plugin;
 /// System diagnostics
 pub mod doctor;
 
+/// First-run detection and guided setup
+pub mod bootstrap;
+
 /// Prelude for convenient imports
 pub mod prelude {
     //! Convenient re-exports
@@ -228,7 +241,7 @@ pub mod prelude {
     //! ```
     #![allow(clippy::mixed_attributes_style)]
 
-    pub use crate::build::{BuildOptions, Builder, CargoOperation};
+    pub use crate::build::{BuildOptions, BuildReport, Builder, CargoOperation};
     pub use crate::config::Config;
     pub use crate::error::{Error, ExitCode, Result};
     pub use crate::target::{Target, TargetRequirements, TargetTier};
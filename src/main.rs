@@ -1,14 +1,40 @@
 //! xcargo CLI entry point
 
-use clap::{Parser, Subcommand};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use inquire::{Confirm, InquireError, MultiSelect, Select};
-use std::path::Path;
-use xcargo::build::{BuildOptions, Builder, CargoOperation};
-use xcargo::config::Config;
+use std::path::{Path, PathBuf};
+use xcargo::bootstrap::{self, EnvironmentStatus};
+use xcargo::build::{
+    bisect, bisect_check_args, check_revision, events, format_counts, load_records, lock_path,
+    merge_docs, merge_settings, merged_doc_dir, parse_duration, parse_since, parse_size,
+    remove_artifact_dirs, remove_clean_items, render_size_comparison, render_stats, render_tasks,
+    run_collected, run_with_policy, rust_analyzer_settings, scan_artifact_dirs, summarize,
+    target_clean_items, target_dir, task_matrix, use_target_dir_config, ArtifactSelection,
+    BuildHistory, BuildMatrix, BuildOptions, BuildRecord, BuildReport, Builder, CargoOperation,
+    CleanItem, DependencyAudit, DeployRequest, InstallRequest, LockFile, LockedTarget,
+    NetworkFlags, RetentionPolicy, RunnerPolicy, Severity, SizeReport, TargetPlan, TaskFormat,
+    HISTORY_FILE, LOCK_FILE, RUNNER_EVENTS_ENV, STATS_FILE, VSCODE_SETTINGS,
+};
+use xcargo::cache::{export_deps, find_cargo_lock, import_deps, read_manifest, DepsCacheKey};
+use xcargo::config::{
+    backup_path, check_config, check_config_file, check_config_settings, env_overrides,
+    set_cli_overrides, write_config_file, Config, ConfigDiscovery, ConfigProblem, ConfigSource,
+    DeployMethod, LayeredConfig, ParallelOutput, ProjectFacts, ProjectTemplate, Suggestion,
+};
+use xcargo::doctor::ReportFormat;
 use xcargo::error::Error;
-use xcargo::output::{helpers, tips};
-use xcargo::target::Target;
-use xcargo::toolchain::ToolchainManager;
+use xcargo::output::log::{self, Verbosity};
+use xcargo::output::progress::format_duration;
+use xcargo::output::table::align_columns;
+use xcargo::output::terminal::{self, ColorChoice};
+use xcargo::output::{self, helpers, i18n, theme, tips};
+use xcargo::plugin::{discover_external, ExternalContext, ExternalPlugin, EXTERNAL_PREFIX};
+use xcargo::target::{normalize_triple, Target};
+use xcargo::toolchain::zig::ZigToolchain;
+use xcargo::toolchain::{
+    format_size, install_zig, installed_zig_versions, managed_zig, RustVersion, TargetSync,
+    ToolchainManager,
+};
 
 /// Result type for main using xcargo's error type
 type Result<T> = std::result::Result<T, Error>;
@@ -30,6 +56,7 @@ fn exit_with_error(error: &Error) -> ! {
         helpers::tip(suggestion);
     }
 
+    log::write("Exit", &format!("exit code {}", error.exit_code()));
     std::process::exit(error.exit_code())
 }
 
@@ -42,16 +69,60 @@ struct Cli {
     #[command(subcommand)]
     command: Commands,
 
-    /// Enable verbose output
-    #[arg(short, long, global = true)]
-    verbose: bool,
+    /// More output: -v shows the commands xcargo runs, -vv their environment too
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Only print warnings and errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
+    /// Append every message and command, with timestamps, to this file
+    #[arg(long, global = true, value_name = "PATH")]
+    log_file: Option<PathBuf>,
+
+    /// Skip the first-run guided setup (also XCARGO_NO_FIRST_RUN=1)
+    #[arg(long, global = true)]
+    no_first_run: bool,
+
+    /// Install missing toolchain components without asking
+    #[arg(short = 'y', long, global = true)]
+    yes: bool,
+
+    /// Require Cargo.lock to be up to date (passed to cargo)
+    #[arg(long, global = true)]
+    locked: bool,
+
+    /// Don't access the network: no toolchain installs or image pulls (passed to cargo)
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Same as --locked and --offline together
+    #[arg(long, global = true)]
+    frozen: bool,
+
+    /// Stream JSON build progress events to this Unix socket or named pipe
+    #[arg(long, global = true, value_name = "PATH")]
+    progress_socket: Option<PathBuf>,
+
+    /// Plain output for screen readers and logs: no colors, icons, or spinners
+    #[arg(long, global = true)]
+    plain: bool,
+
+    /// When to use colors: auto (not with NO_COLOR or when piped), always, never
+    #[arg(long, global = true, default_value = "auto", value_name = "WHEN")]
+    color: ColorChoice,
+
+    /// Override a configuration value, e.g. --config build.jobs=4 (repeatable)
+    #[arg(long = "config", global = true, value_name = "KEY=VALUE")]
+    config_overrides: Vec<String>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Build for target platform(s)
     Build {
-        /// Target triple (e.g., x86_64-pc-windows-gnu)
+        /// Target triple (e.g., x86_64-pc-windows-gnu) or @group from [target-groups]
         #[arg(short, long)]
         target: Option<String>,
 
@@ -59,6 +130,20 @@ enum Commands {
         #[arg(long, conflicts_with = "target")]
         all: bool,
 
+        /// With --all, only build workspace members changed since this git ref
+        #[arg(
+            long,
+            value_name = "REF",
+            requires = "all",
+            conflicts_with = "full",
+            default_value = "origin/HEAD"
+        )]
+        since: String,
+
+        /// With --all, build every workspace member even if unchanged
+        #[arg(long, requires = "all")]
+        full: bool,
+
         /// Build in release mode
         #[arg(short, long)]
         release: bool,
@@ -75,10 +160,53 @@ enum Commands {
         #[arg(long, conflicts_with = "zig")]
         no_zig: bool,
 
+        /// Use a named profile from xcargo.toml (its targets and build settings)
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
         /// Toolchain to use (e.g., stable, nightly)
         #[arg(long)]
         toolchain: Option<String>,
 
+        /// Run once with each toolchain and compare, e.g. stable,beta,nightly
+        #[arg(
+            long,
+            value_delimiter = ',',
+            value_name = "TOOLCHAINS",
+            conflicts_with_all = ["toolchain", "plan", "show_env"]
+        )]
+        toolchain_matrix: Vec<String>,
+
+        /// Write a checksum and provenance record next to each built artifact
+        #[arg(long)]
+        attest: bool,
+
+        /// Print what the build would do for each target, without running cargo
+        #[arg(
+            long,
+            value_enum,
+            value_name = "FORMAT",
+            num_args = 0..=1,
+            default_missing_value = "table"
+        )]
+        plan: Option<OutputFormat>,
+
+        /// Print the environment xcargo would set for cargo, as KEY=value
+        /// lines, without running cargo
+        #[arg(long, conflicts_with = "plan")]
+        show_env: bool,
+
+        #[command(flatten)]
+        failure: FailureArgs,
+
+        /// How parallel builds show cargo's output: interleaved, tagged with
+        /// the target, or grouped per target (default: build.output)
+        #[arg(long, value_name = "MODE")]
+        output: Option<ParallelOutput>,
+
+        #[command(flatten)]
+        selection: SelectionArgs,
+
         /// Additional cargo arguments
         #[arg(last = true)]
         cargo_args: Vec<String>,
@@ -95,18 +223,36 @@ enum Commands {
         /// Interactive setup wizard
         #[arg(short, long)]
         interactive: bool,
+
+        /// Start from a template: embedded, wasm, cli, server, or raspberry-pi
+        #[arg(long, conflicts_with = "interactive")]
+        template: Option<ProjectTemplate>,
     },
 
+    /// Recommend targets and xcargo.toml settings from Cargo.toml
+    Suggest,
+
     /// Display configuration
     Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+
         /// Show default config
         #[arg(long)]
         default: bool,
+
+        /// Show each effective value with the layer it came from
+        #[arg(long, conflicts_with = "default")]
+        show_origin: bool,
+
+        /// List the XCARGO_* environment variables and their current values
+        #[arg(long, conflicts_with_all = ["default", "show_origin"])]
+        env: bool,
     },
 
     /// Check target(s) for errors without building
     Check {
-        /// Target triple (e.g., x86_64-pc-windows-gnu)
+        /// Target triple (e.g., x86_64-pc-windows-gnu) or @group from [target-groups]
         #[arg(short, long)]
         target: Option<String>,
 
@@ -122,10 +268,39 @@ enum Commands {
         #[arg(long, conflicts_with = "zig")]
         no_zig: bool,
 
+        /// Use a named profile from xcargo.toml (its targets and build settings)
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
         /// Toolchain to use (e.g., stable, nightly)
         #[arg(long)]
         toolchain: Option<String>,
 
+        /// Run once with each toolchain and compare, e.g. stable,beta,nightly
+        #[arg(
+            long,
+            value_delimiter = ',',
+            value_name = "TOOLCHAINS",
+            conflicts_with = "toolchain"
+        )]
+        toolchain_matrix: Vec<String>,
+
+        /// Cargo's message format, passed through (e.g. json for
+        /// rust-analyzer); with a JSON format xcargo's own output goes to stderr
+        #[arg(long, value_name = "FMT", conflicts_with = "toolchain_matrix")]
+        message_format: Option<String>,
+
+        #[command(flatten)]
+        failure: FailureArgs,
+
+        /// How parallel builds show cargo's output: interleaved, tagged with
+        /// the target, or grouped per target (default: build.output)
+        #[arg(long, value_name = "MODE")]
+        output: Option<ParallelOutput>,
+
+        #[command(flatten)]
+        selection: SelectionArgs,
+
         /// Additional cargo arguments
         #[arg(last = true)]
         cargo_args: Vec<String>,
@@ -133,7 +308,7 @@ enum Commands {
 
     /// Run tests for target(s)
     Test {
-        /// Target triple (e.g., x86_64-pc-windows-gnu)
+        /// Target triple (e.g., x86_64-pc-windows-gnu) or @group from [target-groups]
         #[arg(short, long)]
         target: Option<String>,
 
@@ -145,6 +320,26 @@ enum Commands {
         #[arg(short, long)]
         release: bool,
 
+        /// Compile the tests but don't run them
+        #[arg(long)]
+        no_run: bool,
+
+        /// With --no-run, copy the test binaries and a manifest into DIR/<target>/
+        #[arg(long, value_name = "DIR", requires = "no_run")]
+        collect: Option<PathBuf>,
+
+        /// Run test binaries collected with --collect instead of building
+        #[arg(
+            long,
+            value_name = "DIR",
+            conflicts_with_all = ["all", "release", "no_run", "zig", "no_zig", "toolchain", "toolchain_matrix", "container"]
+        )]
+        from_collected: Option<PathBuf>,
+
+        /// Run the tests inside the target's container image (requires --features container)
+        #[arg(long, conflicts_with = "collect")]
+        container: bool,
+
         /// Force using Zig for cross-compilation
         #[arg(long, conflicts_with = "no_zig")]
         zig: bool,
@@ -153,638 +348,4187 @@ enum Commands {
         #[arg(long, conflicts_with = "zig")]
         no_zig: bool,
 
+        /// Use a named profile from xcargo.toml (its targets and build settings)
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
         /// Toolchain to use (e.g., stable, nightly)
         #[arg(long)]
         toolchain: Option<String>,
 
-        /// Additional cargo arguments
+        /// Run once with each toolchain and compare, e.g. stable,beta,nightly
+        #[arg(
+            long,
+            value_delimiter = ',',
+            value_name = "TOOLCHAINS",
+            conflicts_with = "toolchain"
+        )]
+        toolchain_matrix: Vec<String>,
+
+        #[command(flatten)]
+        failure: FailureArgs,
+
+        /// How parallel builds show cargo's output: interleaved, tagged with
+        /// the target, or grouped per target (default: build.output)
+        #[arg(long, value_name = "MODE")]
+        output: Option<ParallelOutput>,
+
+        #[command(flatten)]
+        selection: SelectionArgs,
+
+        /// Additional cargo arguments (with --from-collected, test binary arguments)
         #[arg(last = true)]
         cargo_args: Vec<String>,
     },
 
-    /// Check system setup and diagnose issues
-    Doctor,
-
-    /// Show version information
-    Version,
-}
+    /// Run benchmarks for target(s) and compare results
+    Bench {
+        /// Target triple (e.g., aarch64-unknown-linux-gnu) or @group from [target-groups]
+        #[arg(short, long)]
+        target: Option<String>,
 
-#[derive(Subcommand)]
-enum TargetAction {
-    /// Add a target
-    Add {
-        /// Target name or triple
-        target: String,
+        /// Benchmark all configured targets
+        #[arg(long, conflicts_with = "target")]
+        all: bool,
 
-        /// Toolchain to add target to
-        #[arg(long, default_value = "stable")]
-        toolchain: String,
-    },
+        /// Force using Zig for cross-compilation
+        #[arg(long, conflicts_with = "no_zig")]
+        zig: bool,
 
-    /// List targets
-    List {
-        /// Show only installed targets
-        #[arg(long)]
-        installed: bool,
+        /// Disable Zig cross-compilation
+        #[arg(long, conflicts_with = "zig")]
+        no_zig: bool,
 
-        /// Toolchain to list targets for
+        /// Toolchain to use (e.g., stable, nightly)
         #[arg(long)]
         toolchain: Option<String>,
-    },
-
-    /// Show target information
-    Info {
-        /// Target triple
-        target: String,
-    },
-}
 
-/// Run basic non-interactive setup
-fn run_basic_setup() -> Result<()> {
-    helpers::section("Initialize xcargo");
+        #[command(flatten)]
+        failure: FailureArgs,
 
-    if Path::new("xcargo.toml").exists() {
-        helpers::warning("xcargo.toml already exists");
-        let overwrite = Confirm::new("Overwrite existing configuration?")
-            .with_default(false)
-            .prompt()
-            .map_err(prompt_err)?;
+        /// How parallel builds show cargo's output: interleaved, tagged with
+        /// the target, or grouped per target (default: build.output)
+        #[arg(long, value_name = "MODE")]
+        output: Option<ParallelOutput>,
 
-        if !overwrite {
-            helpers::info("Setup cancelled");
-            return Ok(());
-        }
-    }
+        #[command(flatten)]
+        selection: SelectionArgs,
 
-    let host = Target::detect_host()?;
-    let mut config = Config::default();
-    config.targets.default = vec![host.triple.clone()];
+        /// Additional cargo arguments
+        #[arg(last = true)]
+        cargo_args: Vec<String>,
+    },
 
-    config.save("xcargo.toml")?;
+    /// Build documentation for target(s), with their target-specific APIs
+    Doc {
+        /// Target triple (e.g., x86_64-pc-windows-msvc) or @group from [target-groups]
+        #[arg(short, long)]
+        target: Option<String>,
 
-    helpers::success("Created xcargo.toml with default configuration");
-    helpers::tip(format!("Default target: {}", host.triple));
-    helpers::hint("Use 'xcargo init --interactive' for guided setup");
+        /// Document all configured targets
+        #[arg(long, conflicts_with = "target")]
+        all: bool,
 
-    Ok(())
-}
+        /// Build docs in release mode
+        #[arg(short, long)]
+        release: bool,
 
-/// Run interactive TUI setup wizard
-fn run_interactive_setup() -> Result<()> {
-    use xcargo::output::colors;
+        /// Force using Zig for cross-compilation
+        #[arg(long, conflicts_with = "no_zig")]
+        zig: bool,
 
-    println!(
-        "\n{}{}✨ xcargo Interactive Setup{}",
-        colors::BOLD,
-        colors::CYAN,
-        colors::RESET
-    );
-    println!(
-        "{}Let's configure cross-compilation for your project!{}\n",
-        colors::DIM,
-        colors::RESET
-    );
+        /// Disable Zig cross-compilation
+        #[arg(long, conflicts_with = "zig")]
+        no_zig: bool,
 
-    // Check for existing config
-    if Path::new("xcargo.toml").exists() {
-        helpers::warning("xcargo.toml already exists");
-        let overwrite = Confirm::new("Overwrite existing configuration?")
-            .with_default(false)
-            .prompt()
-            .map_err(prompt_err)?;
+        /// Use a named profile from xcargo.toml (its targets and build settings)
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
 
-        if !overwrite {
-            helpers::info("Setup cancelled");
-            return Ok(());
-        }
-    }
+        /// Toolchain to use (e.g., stable, nightly)
+        #[arg(long)]
+        toolchain: Option<String>,
 
-    // Detect host
-    let host = Target::detect_host()?;
-    helpers::success(format!("Detected host platform: {}", host.triple));
-    println!();
+        /// Merge the docs of all targets into one directory with a target
+        /// switcher (default: target/doc-merged)
+        #[arg(long, value_name = "DIR", num_args = 0..=1)]
+        merge: Option<Option<PathBuf>>,
 
-    // Select target platforms
-    let target_options = [
-        ("Linux x86_64", "x86_64-unknown-linux-gnu"),
-        ("Linux x86_64 (musl)", "x86_64-unknown-linux-musl"),
-        ("Linux ARM64", "aarch64-unknown-linux-gnu"),
-        ("Windows x86_64 (GNU)", "x86_64-pc-windows-gnu"),
-        ("Windows x86_64 (MSVC)", "x86_64-pc-windows-msvc"),
-        ("macOS x86_64", "x86_64-apple-darwin"),
-        ("macOS ARM64 (M1/M2)", "aarch64-apple-darwin"),
-        ("WebAssembly", "wasm32-unknown-unknown"),
-    ];
+        #[command(flatten)]
+        failure: FailureArgs,
 
-    let selected_names = MultiSelect::new(
-        "Which targets do you want to build for?",
-        target_options.iter().map(|(name, _)| *name).collect(),
-    )
-    .with_help_message("Use ↑↓ to navigate, Space to select, Enter to confirm")
-    .prompt()
-    .map_err(prompt_err)?;
+        /// How parallel builds show cargo's output: interleaved, tagged with
+        /// the target, or grouped per target (default: build.output)
+        #[arg(long, value_name = "MODE")]
+        output: Option<ParallelOutput>,
 
-    let selected_targets: Vec<String> = selected_names
-        .iter()
-        .filter_map(|&selected_name| {
-            target_options
-                .iter()
-                .find(|(name, _)| name == &selected_name)
-                .map(|(_, triple)| triple.to_string())
-        })
-        .collect();
+        #[command(flatten)]
+        selection: SelectionArgs,
 
-    if selected_targets.is_empty() {
-        helpers::warning("No targets selected, using host target");
-    }
+        /// Additional cargo arguments
+        #[arg(last = true)]
+        cargo_args: Vec<String>,
+    },
 
-    println!();
+    /// Build every combination of targets, feature sets and profiles ([matrix] in xcargo.toml)
+    Matrix {
+        /// Target triple or @group to build (repeatable; default: matrix.targets)
+        #[arg(short, long)]
+        target: Vec<String>,
 
-    // Parallel builds
-    let parallel = Confirm::new("Enable parallel builds?")
-        .with_default(true)
-        .with_help_message("Build multiple targets concurrently for faster builds")
-        .prompt()
-        .map_err(prompt_err)?;
+        /// Feature set, e.g. "default", "no-default,serde" or "all" (repeatable)
+        #[arg(short = 'F', long = "features", value_name = "SET")]
+        features: Vec<String>,
 
-    // Build caching
-    let cache = Confirm::new("Enable build caching?")
-        .with_default(true)
-        .with_help_message("Cache build artifacts to speed up subsequent builds")
-        .prompt()
-        .map_err(prompt_err)?;
+        /// Profiles to build, e.g. debug,release (default: matrix.profiles)
+        #[arg(long, value_delimiter = ',', value_name = "PROFILES")]
+        profiles: Vec<String>,
 
-    // Container strategy
-    let container_options = vec![
-        "Auto (use containers only when necessary)",
-        "Always use containers",
-        "Never use containers",
-    ];
+        /// Run cargo check instead of cargo build
+        #[arg(long, conflicts_with = "test")]
+        check: bool,
 
-    let container_choice = Select::new("Container build strategy:", container_options)
-        .with_help_message("Containers ensure reproducible builds")
-        .prompt()
-        .map_err(prompt_err)?;
+        /// Run cargo test instead of cargo build
+        #[arg(long)]
+        test: bool,
 
-    let use_when = match container_choice {
-        "Auto (use containers only when necessary)" => "target.os != host.os",
-        "Always use containers" => "always",
-        "Never use containers" => "never",
-        _ => "target.os != host.os",
-    };
+        /// List the builds of the matrix without running them
+        #[arg(long)]
+        dry_run: bool,
 
-    println!();
-    helpers::progress("Creating configuration...");
+        /// Force using Zig for cross-compilation
+        #[arg(long, conflicts_with = "no_zig")]
+        zig: bool,
 
-    // Build configuration
-    let mut config = Config::default();
-    let host_triple = host.triple.clone();
-    config.targets.default = if selected_targets.is_empty() {
-        vec![host_triple.clone()]
-    } else {
-        selected_targets.clone()
-    };
-    config.build.parallel = parallel;
-    config.build.cache = cache;
-    config.container.use_when = use_when.to_string();
+        /// Disable Zig cross-compilation
+        #[arg(long, conflicts_with = "zig")]
+        no_zig: bool,
 
-    // Save configuration
-    config.save("xcargo.toml")?;
+        /// Toolchain to use (e.g., stable, nightly)
+        #[arg(long)]
+        toolchain: Option<String>,
 
-    println!();
-    helpers::success("✨ Configuration created successfully!");
-    println!();
+        /// Additional cargo arguments
+        #[arg(last = true)]
+        cargo_args: Vec<String>,
+    },
 
-    // Summary
-    helpers::section("Configuration Summary");
-    println!("Targets: {}", selected_targets.join(", "));
-    println!(
-        "Parallel builds: {}",
-        if parallel { "enabled" } else { "disabled" }
-    );
-    println!(
-        "Build cache: {}",
-        if cache { "enabled" } else { "disabled" }
-    );
-    println!("Container strategy: {}", use_when);
-    println!();
+    /// Find the commit that broke a target with git bisect
+    BisectTarget {
+        /// Target triple (e.g., aarch64-unknown-linux-gnu)
+        target: String,
 
-    // Next steps
-    helpers::section("Next Steps");
-    helpers::tip("Run 'xcargo build' to build for your host platform");
-    helpers::tip("Run 'xcargo build --all' to build for all configured targets");
-    helpers::tip("Run 'xcargo target add <triple>' to add more targets");
-    println!();
+        /// Last known commit where the target built
+        #[arg(long)]
+        good: String,
 
-    // Offer to install targets
-    let install_now = Confirm::new("Install selected targets now?")
-        .with_default(true)
-        .prompt()
-        .map_err(prompt_err)?;
+        /// Commit where the target is broken
+        #[arg(long, default_value = "HEAD")]
+        bad: String,
 
-    if install_now && !selected_targets.is_empty() {
+        /// Force using Zig for cross-compilation
+        #[arg(long, conflicts_with = "no_zig")]
+        zig: bool,
+
+        /// Disable Zig cross-compilation
+        #[arg(long, conflicts_with = "zig")]
+        no_zig: bool,
+
+        /// Toolchain to use (e.g., stable, nightly)
+        #[arg(long)]
+        toolchain: Option<String>,
+
+        /// Additional cargo arguments
+        #[arg(last = true)]
+        cargo_args: Vec<String>,
+    },
+
+    /// Remove the least recently built per-target artifacts ([artifacts] in xcargo.toml)
+    PruneArtifacts {
+        /// Keep this many of the most recently built target/profile directories
+        #[arg(long, value_name = "N")]
+        keep_last: Option<usize>,
+
+        /// Keep at most this much in target directories (e.g., 20GiB)
+        #[arg(long, value_name = "SIZE")]
+        max_size: Option<String>,
+
+        /// Show what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Cross-compile a crate from crates.io and copy its binaries to a directory
+    Install {
+        /// Crate to install
+        #[arg(value_name = "CRATE")]
+        krate: String,
+
+        /// Target triple or alias (e.g., aarch64-unknown-linux-musl)
+        #[arg(short, long)]
+        target: String,
+
+        /// Version requirement (e.g., 1.2 or =1.2.3)
+        #[arg(long)]
+        version: Option<String>,
+
+        /// Directory to copy the binaries into
+        #[arg(long, value_name = "DIR", default_value = ".")]
+        out_dir: PathBuf,
+
+        /// Use container for build (requires --features container)
+        #[arg(long)]
+        container: bool,
+
+        /// Force using Zig for cross-compilation
+        #[arg(long, conflicts_with = "no_zig")]
+        zig: bool,
+
+        /// Disable Zig cross-compilation (use native toolchain or container)
+        #[arg(long, conflicts_with = "zig")]
+        no_zig: bool,
+
+        /// Toolchain to use (e.g., stable, nightly)
+        #[arg(long)]
+        toolchain: Option<String>,
+
+        /// Additional cargo install arguments
+        #[arg(last = true)]
+        cargo_args: Vec<String>,
+    },
+
+    /// Build a target for release and copy its binaries to a remote host over SSH
+    Deploy {
+        /// SSH destination, e.g. pi@192.168.1.10 (default: from [deploy])
+        #[arg(long)]
+        host: Option<String>,
+
+        /// Target triple or alias (default: deploy.target, the only target with its own
+        /// [deploy."<triple>"] table, or the first default target)
+        #[arg(short, long)]
+        target: Option<String>,
+
+        /// Directory on the host to copy the binaries into (default: from [deploy], or the login directory)
+        #[arg(long, value_name = "DIR")]
+        path: Option<String>,
+
+        /// How to copy the binaries: scp or rsync (default: from [deploy], or scp)
+        #[arg(long)]
+        method: Option<DeployMethod>,
+
+        /// systemd service to stop before copying and start afterwards (default: from [deploy])
+        #[arg(long)]
+        service: Option<String>,
+
+        /// Command to run on the host after copying, e.g. "sudo systemctl restart myapp" (default: from [deploy])
+        #[arg(long, value_name = "COMMAND")]
+        restart: Option<String>,
+
+        /// Binary to copy (default: all of the build's binaries)
+        #[arg(long)]
+        bin: Option<String>,
+
+        /// Deploy a debug build instead of a release build
+        #[arg(long)]
+        debug: bool,
+
+        /// Copy the latest build without building first
+        #[arg(long)]
+        no_build: bool,
+
+        /// Force using Zig for cross-compilation
+        #[arg(long, conflicts_with = "no_zig")]
+        zig: bool,
+
+        /// Disable Zig cross-compilation (use native toolchain or container)
+        #[arg(long, conflicts_with = "zig")]
+        no_zig: bool,
+
+        /// Print the copy and ssh commands for the latest build without building or running them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Package a target's release build, e.g. into a container image
+    #[cfg(feature = "container")]
+    Package {
+        #[command(subcommand)]
+        action: PackageAction,
+    },
+
+    /// Remove build output for selected targets, and xcargo's caches
+    Clean {
+        /// Target to clean, e.g. aarch64-unknown-linux-gnu or an alias (repeatable)
+        #[arg(short, long)]
+        target: Vec<String>,
+
+        /// Clean every target configured in xcargo.toml
+        #[arg(long)]
+        all: bool,
+
+        /// Remove the generated Zig compiler wrappers
+        #[arg(long)]
+        zig: bool,
+
+        /// Remove xcargo's build cache
+        #[arg(long)]
+        caches: bool,
+
+        /// Show what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show build time, failure rate, and size trends per target from past builds
+    Stats {
+        /// Only show this target (e.g., aarch64-unknown-linux-gnu or an alias)
+        #[arg(short, long)]
+        target: Option<String>,
+
+        /// Only count builds from this long ago until now (e.g., 7d or 12h)
+        #[arg(long, value_name = "DURATION")]
+        since: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "table", value_name = "FORMAT")]
+        format: OutputFormat,
+
+        /// Delete the recorded builds
+        #[arg(long, conflicts_with_all = ["target", "since"])]
+        clear: bool,
+    },
+
+    /// Show what takes up space in built binaries, and compare sizes across targets
+    Bloat {
+        /// Target to analyze, e.g. aarch64-unknown-linux-musl or an alias
+        /// (repeatable; default: the configured default targets, or the host)
+        #[arg(short, long)]
+        target: Vec<String>,
+
+        /// Analyze release builds
+        #[arg(short, long)]
+        release: bool,
+
+        /// Only analyze this binary or library (file name, with or without extension)
+        #[arg(long, value_name = "NAME")]
+        bin: Option<String>,
+
+        /// Number of sections, crates and symbols to list
+        #[arg(short = 'n', long, default_value_t = 10, value_name = "N")]
+        top: usize,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "table", value_name = "FORMAT")]
+        format: OutputFormat,
+    },
+
+    /// Inspect the dependency graph as it resolves for targets
+    Deps {
+        #[command(subcommand)]
+        action: DepsAction,
+    },
+
+    /// Verify the minimum supported Rust version (rust-version in Cargo.toml)
+    Msrv {
+        #[command(subcommand)]
+        action: MsrvAction,
+    },
+
+    /// Share prebuilt dependencies between checkouts as tarballs keyed on
+    /// Cargo.lock and the toolchain
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// Refresh the toolchain, Zig, and image versions locked in xcargo.lock
+    UpdateLock {
+        /// Targets to refresh (default: every locked target)
+        targets: Vec<String>,
+    },
+
+    /// Generate files for other tools from xcargo.toml
+    Export {
+        #[command(subcommand)]
+        action: ExportAction,
+    },
+
+    /// Print the environment xcargo sets when building a target, to build
+    /// by hand or configure other tools
+    Env {
+        /// Target triple or alias (default: the first default target, or the host)
+        #[arg(short, long)]
+        target: Option<String>,
+
+        /// Use a named profile from xcargo.toml (its build settings)
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// Toolchain to use (e.g., stable, nightly)
+        #[arg(long)]
+        toolchain: Option<String>,
+
+        /// Use the environment of a container build
+        #[arg(long)]
+        container: bool,
+
+        /// Use the environment of a Zig build
+        #[arg(long, conflicts_with = "no_zig")]
+        zig: bool,
+
+        /// Use the environment of a build without Zig
+        #[arg(long, conflicts_with = "zig")]
+        no_zig: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "shell", value_name = "FORMAT")]
+        format: EnvFormat,
+    },
+
+    /// Configure editors for working on a cross target
+    Ide {
+        #[command(subcommand)]
+        action: IdeAction,
+    },
+
+    /// Inspect installed Rust toolchains and manage Zig releases
+    Toolchain {
+        #[command(subcommand)]
+        action: ToolchainAction,
+    },
+
+    /// Check system setup and diagnose issues
+    Doctor {
+        /// Check whether this target can be built (e.g., aarch64-unknown-linux-gnu)
+        #[arg(short, long)]
+        target: Option<String>,
+
+        /// Report format: text, json or junit
+        #[arg(long, default_value = "text", value_name = "FORMAT")]
+        format: ReportFormat,
+    },
+
+    /// Manage the container runtime used for container builds
+    #[cfg(feature = "container")]
+    Container {
+        #[command(subcommand)]
+        action: ContainerAction,
+    },
+
+    /// Manage build profiles
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+
+    /// Show version information
+    Version,
+
+    /// List external plugins (xcargo-<name> executables on PATH)
+    Plugins,
+
+    /// Run a test binary with a timeout and retries (invoked by cargo as a target runner)
+    #[command(name = "__run-test", hide = true)]
+    RunTest {
+        /// Number of leading words in the command that make up the runner
+        #[arg(long, default_value_t = 0)]
+        runner_len: usize,
+
+        /// Time each run may take (e.g. "10m")
+        #[arg(long)]
+        timeout: Option<String>,
+
+        /// How many times a failed run is retried
+        #[arg(long, default_value_t = 0)]
+        retries: u32,
+
+        /// Runner and test binary, followed by the binary's arguments
+        #[arg(last = true, required = true)]
+        command: Vec<String>,
+    },
+
+    /// Check one commit for `git bisect run` (invoked by bisect-target)
+    #[command(name = "__bisect-check", hide = true)]
+    BisectCheck {
+        /// Target triple
+        #[arg(long)]
+        target: String,
+
+        /// Force using Zig for cross-compilation
+        #[arg(long, conflicts_with = "no_zig")]
+        zig: bool,
+
+        /// Disable Zig cross-compilation
+        #[arg(long, conflicts_with = "zig")]
+        no_zig: bool,
+
+        /// Toolchain to use
+        #[arg(long)]
+        toolchain: Option<String>,
+
+        /// Additional cargo arguments
+        #[arg(last = true)]
+        cargo_args: Vec<String>,
+    },
+
+    /// A subcommand provided by an xcargo-<name> plugin on PATH
+    #[command(external_subcommand)]
+    External(Vec<String>),
+}
+
+#[derive(Subcommand)]
+enum TargetAction {
+    /// Add one or more targets
+    Add {
+        /// Target names or triples
+        #[arg(required = true)]
+        targets: Vec<String>,
+
+        /// Toolchain to add targets to (default: the project toolchain)
+        #[arg(long)]
+        toolchain: Option<String>,
+    },
+
+    /// Remove one or more targets
+    Remove {
+        /// Target names or triples
+        #[arg(required = true)]
+        targets: Vec<String>,
+
+        /// Toolchain to remove targets from (default: the project toolchain)
+        #[arg(long)]
+        toolchain: Option<String>,
+    },
+
+    /// Install the targets xcargo.toml refers to that the toolchain is missing
+    Sync {
+        /// Also remove installed targets xcargo.toml doesn't refer to
+        #[arg(long)]
+        prune: bool,
+
+        /// Show what would change without installing or removing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Toolchain to sync (default: the project toolchain)
+        #[arg(long)]
+        toolchain: Option<String>,
+    },
+
+    /// List targets
+    List {
+        /// Show only installed targets
+        #[arg(long)]
+        installed: bool,
+
+        /// Toolchain to list targets for
+        #[arg(long)]
+        toolchain: Option<String>,
+    },
+
+    /// Show target information
+    Info {
+        /// Target triple
+        target: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ToolchainAction {
+    /// List installed toolchains and their targets
+    List {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+
+    /// Show components, rustc version and installed targets of a toolchain
+    Info {
+        /// Toolchain name (e.g., stable, nightly, 1.78.0)
+        name: String,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Table)]
+        format: OutputFormat,
+    },
+
+    /// Install and list Zig releases in ~/.xcargo/zig
+    Zig {
+        #[command(subcommand)]
+        action: ZigAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum ZigAction {
+    /// Download a Zig release, verify its checksum, and install it
+    Install {
+        /// Zig version, e.g. 0.13.0 (default: zig.version from xcargo.toml)
+        version: Option<String>,
+    },
+
+    /// List installed Zig releases
+    List,
+}
+
+/// Packages and cargo targets to build, instead of cargo's defaults
+#[derive(Args)]
+struct SelectionArgs {
+    /// Package to build (repeatable)
+    #[arg(short, long = "package", value_name = "SPEC")]
+    package: Vec<String>,
+
+    /// Binary to build (repeatable)
+    #[arg(long = "bin", value_name = "NAME")]
+    bin: Vec<String>,
+
+    /// Example to build (repeatable)
+    #[arg(long = "example", value_name = "NAME")]
+    example: Vec<String>,
+
+    /// Build the package's library
+    #[arg(long)]
+    lib: bool,
+}
+
+/// What a multi-target build does when a target fails
+#[derive(Args)]
+struct FailureArgs {
+    /// Stop at the first failed target (default: build.fail_fast)
+    #[arg(long, conflicts_with = "keep_going")]
+    fail_fast: bool,
+
+    /// Build the remaining targets after one fails
+    #[arg(long, conflicts_with = "fail_fast")]
+    keep_going: bool,
+}
+
+impl FailureArgs {
+    /// The --fail-fast/--keep-going choice, or None to use build.fail_fast
+    fn fail_fast(&self) -> Option<bool> {
+        if self.fail_fast {
+            Some(true)
+        } else if self.keep_going {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+impl From<SelectionArgs> for ArtifactSelection {
+    fn from(args: SelectionArgs) -> Self {
+        Self {
+            packages: args.package,
+            bins: args.bin,
+            examples: args.example,
+            lib: args.lib,
+        }
+    }
+}
+
+/// Output format for listing commands
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable table
+    Table,
+    /// JSON
+    Json,
+}
+
+#[derive(Subcommand)]
+enum IdeAction {
+    /// Point rust-analyzer at a target in .vscode/settings.json: its cfgs,
+    /// check-on-save with xcargo check, and the build environment
+    Setup {
+        /// Target triple or alias (default: the first default target, or the host)
+        #[arg(short, long)]
+        target: Option<String>,
+
+        /// Use a named profile from xcargo.toml (its build settings)
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// Print the settings as JSON instead of writing them, e.g. for
+        /// editors other than VS Code
+        #[arg(long)]
+        stdout: bool,
+    },
+}
+
+/// Output format of `xcargo env`
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum EnvFormat {
+    /// `export` lines for sh, bash and zsh, to use with eval
+    Shell,
+    /// JSON object with the target, strategy, and variables
+    Json,
+}
+
+#[derive(Subcommand)]
+enum ExportAction {
+    /// Task runner definitions for the configured targets
+    Tasks {
+        /// Task runner: just or cargo-make
+        #[arg(long, value_name = "FORMAT")]
+        format: TaskFormat,
+
+        /// File to write (default: justfile or Makefile.toml)
+        #[arg(short, long, value_name = "FILE", conflicts_with = "stdout")]
+        output: Option<PathBuf>,
+
+        /// Print the definitions instead of writing a file
+        #[arg(long)]
+        stdout: bool,
+
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum DepsAction {
+    /// Report dependencies with known cross-compilation problems for a target
+    Audit {
+        /// Target triple, alias or @group to audit (repeatable; default: the
+        /// configured default targets, or the host)
+        #[arg(short, long)]
+        target: Vec<String>,
+
+        /// Use a named profile from xcargo.toml (its targets and build settings)
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value = "table", value_name = "FORMAT")]
+        format: OutputFormat,
+
+        /// Additional cargo metadata arguments, e.g. --features or --all-features
+        #[arg(last = true)]
+        cargo_args: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum MsrvAction {
+    /// Check every configured target with the rust-version toolchain
+    Verify {
+        /// Target triple, alias or @group to check (repeatable; default: the
+        /// configured default targets, or the host)
+        #[arg(short, long)]
+        target: Vec<String>,
+
+        /// Use a named profile from xcargo.toml (its targets and build settings)
+        #[arg(long, value_name = "NAME")]
+        profile: Option<String>,
+
+        /// Force using Zig for cross-compilation
+        #[arg(long, conflicts_with = "no_zig")]
+        zig: bool,
+
+        /// Disable Zig cross-compilation
+        #[arg(long, conflicts_with = "zig")]
+        no_zig: bool,
+
+        #[command(flatten)]
+        selection: SelectionArgs,
+
+        /// Additional cargo arguments
+        #[arg(last = true)]
+        cargo_args: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Archive the dependencies built for targets into a tarball
+    Export {
+        /// Tarball to write, e.g. deps-aarch64.tar.gz
+        tarball: PathBuf,
+
+        /// Target to archive, e.g. aarch64-unknown-linux-gnu or an alias
+        /// (repeatable; default: the targets configured in xcargo.toml)
+        #[arg(short, long)]
+        target: Vec<String>,
+
+        /// Archive debug builds instead of release builds
+        #[arg(long)]
+        debug: bool,
+
+        /// Toolchain the dependencies were built with (e.g., stable, nightly)
+        #[arg(long)]
+        toolchain: Option<String>,
+    },
+    /// Restore the dependencies in a tarball into the target directory
+    Import {
+        /// Tarball written by `xcargo cache export`
+        tarball: PathBuf,
+
+        /// Import even if Cargo.lock or the toolchain has changed
+        #[arg(long)]
+        force: bool,
+
+        /// Toolchain the project builds with (e.g., stable, nightly)
+        #[arg(long)]
+        toolchain: Option<String>,
+    },
+    /// Print the cache key of the project, for naming CI cache entries
+    Key {
+        /// Toolchain the project builds with (e.g., stable, nightly)
+        #[arg(long)]
+        toolchain: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Check the configuration files and report every problem with its line
+    Validate,
+
+    /// Open xcargo.toml in $EDITOR and save it only if it is still valid
+    Edit {
+        /// Edit the user configuration instead of the project's xcargo.toml
+        #[arg(long)]
+        user: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProfileAction {
+    /// List profiles defined in xcargo.toml
+    List,
+}
+
+#[cfg(feature = "container")]
+#[derive(Subcommand)]
+enum PackageAction {
+    /// Build a minimal image (distroless or scratch) holding the target's binaries
+    Docker {
+        /// Linux target triple or alias (default: the first default target)
+        #[arg(short, long)]
+        target: Option<String>,
+
+        /// Image repository, e.g. ghcr.io/acme/app (default: from [package.docker], or the package name)
+        #[arg(long)]
+        repository: Option<String>,
+
+        /// Tag of the image (repeatable; default: from [package.docker], or the package version and latest)
+        #[arg(long = "tag", value_name = "TAG")]
+        tags: Vec<String>,
+
+        /// Base image: distroless, scratch, or an image reference (default: from [package.docker], or distroless)
+        #[arg(long)]
+        base: Option<String>,
+
+        /// Binary the image runs (default: from [package.docker], the only binary, or the one named after the package)
+        #[arg(long)]
+        bin: Option<String>,
+
+        /// Push the image after building it
+        #[arg(long)]
+        push: bool,
+
+        /// Package the latest release build without building first
+        #[arg(long)]
+        no_build: bool,
+
+        /// Force using Zig for cross-compilation
+        #[arg(long, conflicts_with = "no_zig")]
+        zig: bool,
+
+        /// Disable Zig cross-compilation (use native toolchain or container)
+        #[arg(long, conflicts_with = "zig")]
+        no_zig: bool,
+    },
+}
+
+#[cfg(feature = "container")]
+#[derive(Subcommand)]
+enum ContainerAction {
+    /// Check the runtime context and volume write permissions
+    Doctor {
+        /// Image used to probe volume permissions
+        #[arg(long, default_value = "busybox:latest")]
+        image: String,
+    },
+    /// List the image each configured target builds with
+    Images,
+    /// Pull images ahead of time so builds can run without network access
+    Pull {
+        /// Target triple or @group to pull the image for
+        #[arg(short, long, required_unless_present = "all")]
+        target: Option<String>,
+
+        /// Pull the images of all configured targets
+        #[arg(long, conflicts_with = "target")]
+        all: bool,
+
+        /// Also save the images as tar archives in this directory, for
+        /// `docker load` on an air-gapped machine
+        #[arg(long, value_name = "DIR")]
+        archive: Option<PathBuf>,
+    },
+    /// Log in to a private registry so its images can be pulled
+    Login {
+        /// Registry host, e.g. ghcr.io (default: the configured registries)
+        registry: Option<String>,
+
+        /// User name (default: XCARGO_REGISTRY_USERNAME)
+        #[arg(short, long)]
+        username: Option<String>,
+
+        /// Read the password or token from stdin (default: XCARGO_REGISTRY_PASSWORD)
+        #[arg(long)]
+        password_stdin: bool,
+    },
+}
+
+/// Determine Zig preference: None = auto, Some(true) = force, Some(false) = disable
+fn zig_preference(zig: bool, no_zig: bool) -> Option<bool> {
+    if zig {
+        Some(true)
+    } else if no_zig {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+/// Print every effective configuration value with the layer that set it
+fn print_config_origins(layered: &LayeredConfig) -> Result<()> {
+    let entries: Vec<(String, String)> = layered
+        .entries()?
+        .into_iter()
+        .map(|(key, value, origin)| (format!("{} = {}", key, value), origin.to_string()))
+        .collect();
+    let width = entries
+        .iter()
+        .map(|(line, _)| line.len())
+        .max()
+        .unwrap_or(0);
+
+    for (line, origin) in entries {
+        println!("{:<width$}  # {}", line, origin, width = width);
+    }
+    Ok(())
+}
+
+/// Print the recognized XCARGO_* variables with their current values
+fn print_config_env() {
+    let rows: Vec<Vec<String>> = env_overrides()
+        .into_iter()
+        .map(|env| {
+            let key = if env.alias {
+                format!("{} (alias)", env.key)
+            } else {
+                env.key.to_string()
+            };
+            let value = std::env::var(&env.var).unwrap_or_else(|_| "-".to_string());
+            vec![env.var, key, value]
+        })
+        .collect();
+    for line in align_columns(&rows) {
+        println!("{line}");
+    }
+
+    println!();
+    helpers::tip(
+        "Variables override xcargo.toml and the user config; --config overrides variables",
+    );
+}
+
+/// Report every problem in the user and workspace configuration files
+fn validate_config_files() -> Result<()> {
+    helpers::section("Configuration Check");
+
+    let mut files = Vec::new();
+    if let Some(path) = ConfigDiscovery::user_config_path().filter(|p| p.is_file()) {
+        files.push(path);
+    }
+    if let Some(path) = ConfigDiscovery::find()? {
+        files.push(path);
+    }
+    if files.is_empty() {
+        helpers::info("No xcargo.toml found, nothing to check");
+        helpers::tip(tips::CONFIG_FILE);
+        return Ok(());
+    }
+
+    let known_targets: Option<Vec<String>> = match Target::list_available() {
+        Ok(targets) => Some(targets.into_iter().map(|t| t.triple).collect()),
+        Err(e) => {
+            helpers::warning(format!("Target names were not checked: {}", e));
+            None
+        }
+    };
+
+    let mut total = 0;
+    for path in &files {
+        let problems = check_config_file(path, known_targets.as_deref())?;
+        if problems.is_empty() {
+            helpers::success(format!("{}: no problems found", path.display()));
+            continue;
+        }
+        total += problems.len();
+        print_config_problems(path, problems, helpers::error);
+    }
+
+    if total > 0 {
+        return Err(Error::Config(format!(
+            "{} problem(s) found in the configuration",
+            total
+        )));
+    }
+    Ok(())
+}
+
+/// Print each problem of the configuration file at `path` with its line
+fn print_config_problems(path: &Path, problems: Vec<ConfigProblem>, report: fn(String)) {
+    for problem in problems {
+        let location = match problem.line {
+            Some(line) => format!("{}:{}", path.display(), line),
+            None => path.display().to_string(),
+        };
+        if problem.path.is_empty() {
+            report(format!("{}: {}", location, problem.message));
+        } else {
+            report(format!(
+                "{}: {}: {}",
+                location,
+                problem.key(),
+                problem.message
+            ));
+        }
+        if let Some(suggestion) = problem.suggestion {
+            helpers::tip(suggestion);
+        }
+    }
+}
+
+/// Edit a configuration file in $VISUAL or $EDITOR
+///
+/// The file is edited as a copy, which replaces it (keeping a `.bak`) only
+/// once it parses and its settings are valid; an invalid edit can be
+/// reopened or abandoned. Settings that don't fit this machine, like a
+/// linker that is not installed, are only warned about.
+fn edit_config_file(user: bool) -> Result<()> {
+    let path = if user {
+        ConfigDiscovery::user_config_path().ok_or_else(|| {
+            Error::Config("Cannot determine the user configuration directory".to_string())
+        })?
+    } else {
+        ConfigDiscovery::find()?.unwrap_or_else(|| PathBuf::from("xcargo.toml"))
+    };
+    let original = if path.is_file() {
+        std::fs::read_to_string(&path).map_err(|e| {
+            Error::Config(format!(
+                "Failed to read config file {}: {}",
+                path.display(),
+                e
+            ))
+        })?
+    } else {
+        String::new()
+    };
+
+    // Keep the file name so editors pick TOML highlighting
+    let draft_dir = std::env::temp_dir().join(format!("xcargo-edit-{}", std::process::id()));
+    std::fs::create_dir_all(&draft_dir)?;
+    let draft = draft_dir.join(path.file_name().unwrap_or_default());
+    std::fs::write(&draft, &original)?;
+
+    let edited = match edit_until_valid(&path, &draft) {
+        Ok(edited) => edited,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&draft_dir);
+            return Err(e);
+        }
+    };
+    let Some(edited) = edited else {
+        helpers::info(format!(
+            "{} was not changed; the invalid edit is in {}",
+            path.display(),
+            draft.display()
+        ));
+        return Ok(());
+    };
+    let _ = std::fs::remove_dir_all(&draft_dir);
+    if edited == original {
+        helpers::info(format!("No changes to {}", path.display()));
+        return Ok(());
+    }
+
+    let warnings = check_config(&edited, None);
+    print_config_problems(&path, warnings, helpers::warning);
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    write_config_file(&path, &edited)?;
+    helpers::success(format!("Saved {}", path.display()));
+    if !original.is_empty() {
+        helpers::info(format!(
+            "Previous version kept in {}",
+            backup_path(&path).display()
+        ));
+    }
+    Ok(())
+}
+
+/// Open `draft` in the editor until it holds a valid configuration for
+/// `path`, or the user gives up on it
+fn edit_until_valid(path: &Path, draft: &Path) -> Result<Option<String>> {
+    use std::io::IsTerminal;
+
+    loop {
+        run_editor(draft)?;
+        let edited = std::fs::read_to_string(draft)?;
+        let problems = check_config_settings(&edited);
+        if problems.is_empty() {
+            return Ok(Some(edited));
+        }
+        helpers::error(format!("{} is not valid:", path.display()));
+        print_config_problems(path, problems, helpers::error);
+        let again = std::io::stdin().is_terminal()
+            && Confirm::new("Edit it again?")
+                .with_default(true)
+                .prompt()
+                .map_err(prompt_err)?;
+        if !again {
+            return Ok(None);
+        }
+    }
+}
+
+/// Open `file` in the user's editor and wait for it to close
+fn run_editor(file: &Path) -> Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(std::env::var_os)
+        .map(|editor| editor.to_string_lossy().trim().to_string())
+        .find(|editor| !editor.is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    // Editors are often configured with arguments, e.g. "code --wait"
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let mut cmd = std::process::Command::new(program);
+    cmd.args(words).arg(file);
+    log::command(&cmd);
+    let status = cmd.status().map_err(|e| {
+        Error::Config(format!(
+            "Failed to start editor '{}': {}. Set $EDITOR to your editor",
+            editor, e
+        ))
+    })?;
+    if !status.success() {
+        return Err(Error::Config(format!(
+            "Editor '{}' exited with {}; the configuration was not changed",
+            editor, status
+        )));
+    }
+    Ok(())
+}
+
+/// Current versions for a locked target, built the way it was locked
+fn refresh_locked_target(
+    locked: &LockedTarget,
+    manager: &ToolchainManager,
+    config: &Config,
+) -> Result<LockedTarget> {
+    let mut entry = locked.clone();
+    if locked.image.is_some() {
+        entry.image_digest = latest_image_digest(locked, config)?;
+        return Ok(entry);
+    }
+
+    let toolchain = project_toolchain(manager, None)?;
+    entry.rust = manager.rustc_release(&toolchain);
+    if locked.zig.is_some() {
+        entry.zig = ZigToolchain::for_config(config)?.map(|zig| zig.version().to_string());
+    }
+    Ok(entry)
+}
+
+/// Pull a locked image's tag again and read the digest it now points to
+#[cfg(feature = "container")]
+fn latest_image_digest(locked: &LockedTarget, config: &Config) -> Result<Option<String>> {
+    use xcargo::container::{ContainerBuilder, RuntimeType};
+
+    let image = locked.image.as_deref().unwrap_or_default();
+    let runtime = RuntimeType::from_str(&config.container.runtime).unwrap_or(RuntimeType::Auto);
+    let builder = ContainerBuilder::new(runtime)?;
+    helpers::progress(format!("Pulling {}...", image));
+    builder.pull_image(image)?;
+    Ok(builder.image_digest(image))
+}
+
+/// List the image each configured target builds with and whether it was
+/// pulled already
+#[cfg(feature = "container")]
+fn list_container_images() -> Result<()> {
+    use xcargo::container::{ContainerBuilder, ImageSelector, RuntimeType};
+
+    helpers::section("Container Images");
+
+    let config = load_config(None)?;
+    let targets = configured_targets(&config);
+    if targets.is_empty() {
+        helpers::info("No targets configured");
+        helpers::tip(tips::CONFIG_FILE);
+        return Ok(());
+    }
+
+    let selector = ImageSelector::from_config(&config.container);
+    let builder = RuntimeType::from_str(&config.container.runtime)
+        .and_then(ContainerBuilder::new)
+        .ok()
+        .filter(ContainerBuilder::is_available);
+    if builder.is_none() {
+        helpers::warning("No container runtime found; local images were not checked");
+    }
+
+    for target in &targets {
+        println!("  {} {}", bullet(), target);
+        let image = match selector.select_for_target(target) {
+            Ok(image) => image.full_name(),
+            Err(e) => {
+                println!(
+                    "      image: none ({})",
+                    e.to_string().lines().next().unwrap_or_default()
+                );
+                continue;
+            }
+        };
+        let source = if selector.is_configured(target) {
+            "xcargo.toml"
+        } else {
+            "built-in"
+        };
+        println!("      image: {} ({})", image, source);
+        if let Some(builder) = &builder {
+            let local = if builder.has_image(&image) {
+                "present"
+            } else {
+                "not pulled"
+            };
+            println!("      local: {}", local);
+        }
+    }
+    println!();
+    helpers::tip("Set a target's image with [container.images] in xcargo.toml");
+    Ok(())
+}
+
+/// Pull the images of `target`, or of all configured targets, and save
+/// them to `archive` if given
+#[cfg(feature = "container")]
+fn pull_container_images(target: Option<&str>, all: bool, archive: Option<&Path>) -> Result<()> {
+    use xcargo::container::{
+        ContainerBuilder, ImageSelector, PullPolicy, RuntimeType, PASSWORD_ENV, USERNAME_ENV,
+    };
+
+    helpers::section("Pull Container Images");
+
+    let config = load_config(None)?;
+    let targets = match target {
+        Some(target) => config.expand_targets(&[target.to_string()])?,
+        None => configured_targets(&config),
+    };
+    if targets.is_empty() {
+        helpers::info("No targets configured");
+        helpers::tip(tips::CONFIG_FILE);
+        return Ok(());
+    }
+
+    let selector = ImageSelector::from_config(&config.container);
+    let mut images = Vec::new();
+    for target in &targets {
+        match selector.select_for_target(target) {
+            Ok(image) => images.push(image),
+            // With --all, targets without an image are built without a container
+            Err(e) if all => helpers::info(format!(
+                "Skipping {}: {}",
+                target,
+                e.to_string().lines().next().unwrap_or_default()
+            )),
+            Err(e) => return Err(e),
+        }
+    }
+
+    let runtime = RuntimeType::from_str(&config.container.runtime).unwrap_or(RuntimeType::Auto);
+    let builder = ContainerBuilder::new(runtime)?;
+    let policy = PullPolicy::from_str(&config.container.pull_policy).unwrap_or_default();
+    if let Some(dir) = archive {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    let mut failed = 0;
+    for image in &images {
+        let name = image.full_name();
+        helpers::progress(format!("Pulling {}...", name));
+        match builder.ensure_image(&name, policy) {
+            Ok(true) => helpers::success(format!("Pulled {}", name)),
+            Ok(false) => helpers::success(format!("{} is already present", name)),
+            Err(e) => {
+                helpers::error(format!("{}: {}", image.target, e));
+                helpers::hint(format!(
+                    "If {} needs authentication, run `xcargo container login {}` or set {} and {}",
+                    image.registry(),
+                    image.registry(),
+                    USERNAME_ENV,
+                    PASSWORD_ENV
+                ));
+                failed += 1;
+                continue;
+            }
+        }
+
+        if let Some(dir) = archive {
+            let file = dir.join(format!("{}.tar", name.replace(['/', ':', '@'], "_")));
+            match builder.save_image(&name, &file) {
+                Ok(()) => helpers::success(format!("Saved {}", file.display())),
+                Err(e) => {
+                    helpers::error(format!("{}: {}", image.target, e));
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    println!();
+    if failed > 0 {
+        return Err(Error::Container(format!(
+            "{} of {} image(s) could not be pulled or saved",
+            failed,
+            images.len()
+        )));
+    }
+    if let Some(dir) = archive {
+        helpers::tip(format!(
+            "On the offline machine, load each archive in {} with `{} load -i <file>`",
+            dir.display(),
+            builder.runtime_name()
+        ));
+    }
+    helpers::tip("Build without network access with: xcargo build --offline");
+    Ok(())
+}
+
+/// Log in to `registry`, or every configured registry, with the runtime
+#[cfg(feature = "container")]
+fn container_login(
+    registry: Option<String>,
+    username: Option<String>,
+    password_stdin: bool,
+) -> Result<()> {
+    use std::io::Read;
+    use xcargo::container::{ContainerBuilder, ImageSelector, RegistryCredentials, RuntimeType};
+
+    let config = load_config(None)?;
+    let registries = match registry {
+        Some(registry) => vec![registry],
+        None => ImageSelector::from_config(&config.container).configured_registries(),
+    };
+    if registries.is_empty() {
+        return Err(Error::Config(
+            "No registry given and none configured in container.registry or container.images"
+                .to_string(),
+        ));
+    }
+
+    let password = if password_stdin {
+        let mut password = String::new();
+        std::io::stdin().read_to_string(&mut password)?;
+        Some(password.trim_end_matches(['\r', '\n']).to_string())
+    } else {
+        None
+    };
+    let credentials = RegistryCredentials::resolve(username, password)?;
+
+    let runtime = RuntimeType::from_str(&config.container.runtime).unwrap_or(RuntimeType::Auto);
+    let builder = ContainerBuilder::new(runtime)?;
+    for registry in &registries {
+        builder.login(registry, &credentials)?;
+        helpers::success(format!(
+            "Logged in to {} as {}",
+            registry, credentials.username
+        ));
+    }
+    Ok(())
+}
+
+/// Targets named anywhere in the configuration: the default targets, those
+/// with their own settings, and those with a configured image
+#[cfg(feature = "container")]
+fn configured_targets(config: &Config) -> Vec<String> {
+    let mut targets: Vec<String> = config
+        .targets
+        .default
+        .iter()
+        .chain(config.targets.custom.keys())
+        .chain(config.container.images.keys())
+        .cloned()
+        .collect();
+    targets.sort();
+    targets.dedup();
+    targets
+}
+
+/// Image digests can only be refreshed with container support
+#[cfg(not(feature = "container"))]
+fn latest_image_digest(locked: &LockedTarget, _config: &Config) -> Result<Option<String>> {
+    helpers::warning(format!(
+        "Container support not enabled; keeping the locked digest of {}",
+        locked.image.as_deref().unwrap_or_default()
+    ));
+    Ok(locked.image_digest.clone())
+}
+
+/// Describe how a locked target changed, or `None` if it didn't
+fn lock_changes(old: &LockedTarget, new: &LockedTarget) -> Option<String> {
+    let fields = [
+        ("rust", &old.rust, &new.rust),
+        ("zig", &old.zig, &new.zig),
+        ("image", &old.image_digest, &new.image_digest),
+    ];
+    let changes: Vec<String> = fields
+        .iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(name, old, new)| {
+            format!(
+                "{} {} -> {}",
+                name,
+                old.as_deref().unwrap_or("-"),
+                new.as_deref().unwrap_or("-")
+            )
+        })
+        .collect();
+    (!changes.is_empty()).then(|| changes.join(", "))
+}
+
+/// Load the project configuration, applying a named profile if given
+fn load_config(profile: Option<&str>) -> Result<Config> {
+    let mut config = Config::load()?;
+
+    if let Some(name) = profile {
+        config.apply_profile(name)?;
+        helpers::info(format!("Using profile '{}'", name));
+    }
+
+    Ok(config)
+}
+
+/// Resolve the project toolchain (--toolchain, xcargo.toml, rust-toolchain.toml,
+/// rustup default), warning when xcargo.toml and the toolchain file disagree
+fn project_toolchain(manager: &ToolchainManager, requested: Option<&str>) -> Result<String> {
+    let config = load_config(None)?;
+    let selection = manager.resolve_toolchain(requested, config.toolchain.channel.as_deref())?;
+
+    if let Some(conflict) = &selection.conflict {
+        helpers::warning(conflict);
+    }
+
+    Ok(selection.name)
+}
+
+/// Install the targets the configuration refers to, and with `prune`
+/// remove the ones it doesn't
+fn sync_targets(toolchain: Option<&str>, prune: bool, dry_run: bool) -> Result<()> {
+    let manager = ToolchainManager::new()?;
+    let toolchain = project_toolchain(&manager, toolchain)?;
+    let config = load_config(None)?;
+
+    let mut referenced = Vec::new();
+    for target in config.referenced_targets() {
+        match resolve_target_arg(&manager, &toolchain, &target) {
+            Ok(triple) => referenced.push(triple),
+            Err(e) => helpers::warning(format!("Skipping {}: {}", target, e)),
+        }
+    }
+    if referenced.is_empty() {
+        helpers::info("xcargo.toml doesn't refer to any targets");
+        helpers::tip("List the targets you build in targets.default");
+    }
+
+    let installed = manager.list_targets(&toolchain)?;
+    // Without rustup every target is installed by hand, so none is marked
+    // unavailable
+    let available: Option<Vec<String>> = if manager.uses_rustup() {
+        manager.list_available_targets(&toolchain).ok()
+    } else {
+        None
+    };
+    let host = Target::detect_host()?.triple;
+    let sync = TargetSync::plan(&referenced, &installed, available.as_deref(), &host);
+
+    helpers::info(format!("Targets of toolchain {}:", toolchain));
+    let palette = theme::palette();
+    for line in sync.diff(prune).lines() {
+        let color = match line.chars().next() {
+            Some('+') => palette.success,
+            Some('-') => palette.error,
+            Some('?') => palette.warning,
+            _ => palette.dim,
+        };
+        println!("  {}", theme::paint(color, line));
+    }
+    println!();
+    if !sync.unavailable.is_empty() {
+        helpers::tip(
+            "Targets rustup doesn't distribute are built from source with -Z build-std; see `xcargo target info <triple>`",
+        );
+    }
+    if sync.is_current(prune) {
+        helpers::success("Installed targets match xcargo.toml");
+        return Ok(());
+    }
+    if dry_run {
+        helpers::tip("Run without --dry-run to apply these changes");
+        return Ok(());
+    }
+
+    let mut failed = Vec::new();
+    for triple in &sync.install {
+        if let Err(e) = manager.install_target(&toolchain, triple) {
+            helpers::error(e.to_string());
+            failed.push(triple.clone());
+        }
+    }
+    if prune {
+        for triple in &sync.unreferenced {
+            match manager.remove_target(&toolchain, triple) {
+                Ok(()) => helpers::success(format!("Removed target {}", triple)),
+                Err(e) => {
+                    helpers::error(e.to_string());
+                    failed.push(triple.clone());
+                }
+            }
+        }
+    }
+    if !failed.is_empty() {
+        return Err(Error::Toolchain(format!(
+            "Failed to sync {}",
+            failed.join(", ")
+        )));
+    }
+    helpers::success("Installed targets match xcargo.toml");
+    if !prune && !sync.unreferenced.is_empty() {
+        helpers::tip("Use --prune to remove the targets xcargo.toml doesn't refer to");
+    }
+    Ok(())
+}
+
+/// Targets recommended for the package in the current directory, or `None`
+/// without a Cargo.toml
+fn project_suggestion() -> Result<Option<Suggestion>> {
+    let manifest = Path::new("Cargo.toml");
+    if !manifest.is_file() {
+        return Ok(None);
+    }
+    let facts = ProjectFacts::from_manifest(manifest)?;
+    Ok(Some(Suggestion::for_project(&facts)))
+}
+
+/// Triple a target argument names for `toolchain`: aliases resolved and old
+/// names replaced, with a warning
+fn resolve_target_arg(manager: &ToolchainManager, toolchain: &str, target: &str) -> Result<String> {
+    let normalized = normalize_triple(
+        &Target::resolve_alias(target)?,
+        manager.rustc_release(toolchain).as_deref(),
+    );
+    if let Some(warning) = &normalized.warning {
+        helpers::warning(warning);
+    }
+    Ok(normalized.triple)
+}
+
+/// Remove a target named on the command line from `toolchain`, warning if
+/// the project still builds it by default
+fn remove_target(
+    manager: &ToolchainManager,
+    toolchain: &str,
+    target: &str,
+    default_targets: &[String],
+) -> Result<()> {
+    let triple = resolve_target_arg(manager, toolchain, target)?;
+    if !manager.is_target_installed(toolchain, &triple)? {
+        helpers::info(format!("Target {} is not installed", triple));
+        return Ok(());
+    }
+    manager.remove_target(toolchain, &triple)?;
+    helpers::success(format!("Removed target {}", triple));
+    if default_targets.contains(&triple) {
+        helpers::warning(format!(
+            "{} is still in targets.default in xcargo.toml",
+            triple
+        ));
+    }
+    Ok(())
+}
+
+/// Key of the dependencies the project would build: its Cargo.lock and the
+/// version of its toolchain's rustc
+fn deps_cache_key(requested: Option<&str>) -> Result<DepsCacheKey> {
+    let lock = find_cargo_lock(&std::env::current_dir()?).ok_or_else(|| {
+        Error::Config("No Cargo.lock found; run `cargo generate-lockfile` first".to_string())
+    })?;
+    let manager = ToolchainManager::new()?;
+    let toolchain = project_toolchain(&manager, requested)?;
+    let rustc = manager.rustc_version(&toolchain).ok_or_else(|| {
+        Error::Toolchain(format!("Could not run rustc of toolchain '{toolchain}'"))
+    })?;
+    DepsCacheKey::new(&lock, &rustc)
+}
+
+/// Run an operation for one target, or for every configured target with --all
+///
+/// With a profile and no explicit --target, every target in the profile is built.
+fn run_operation(options: &BuildOptions, all: bool, profile: Option<&str>) -> Result<()> {
+    let config = load_config(profile)?;
+    let builder = Builder::with_config(config.clone())?;
+
+    // --target @group builds each target of the group
+    if let Some(group) = options.target.as_ref().filter(|t| t.starts_with('@')) {
+        let targets = config.expand_targets(std::slice::from_ref(group))?;
+        let options = BuildOptions {
+            target: None,
+            ..options.clone()
+        };
+        return build_targets(&builder, &config, &targets, &options);
+    }
+
+    if !all && (profile.is_none() || options.target.is_some()) {
+        let report = builder.build_with_report(options)?;
+        print_build_report(&report, options.operation);
+        return Ok(());
+    }
+
+    if config.targets.default.is_empty() {
+        helpers::error("No default targets configured");
+        helpers::hint(
+            "Add targets to xcargo.toml: [targets] default = [\"x86_64-unknown-linux-gnu\"]",
+        );
+        helpers::tip(tips::CONFIG_FILE);
+        std::process::exit(1);
+    }
+
+    build_targets(&builder, &config, &config.targets.default, options)
+}
+
+/// Targets an operation applies to: the given target or group, every default
+/// target with --all or a profile, or else the first default or the host
+fn selected_targets(config: &Config, target: Option<String>, all: bool) -> Result<Vec<String>> {
+    match target {
+        Some(target) => config.expand_targets(&[target]),
+        None if all && !config.targets.default.is_empty() => Ok(config.targets.default.clone()),
+        None => match config.targets.default.first() {
+            Some(default) => Ok(vec![default.clone()]),
+            None => Ok(vec![Target::detect_host()?.triple]),
+        },
+    }
+}
+
+/// Run an operation once per toolchain for the selected targets and compare
+fn run_toolchain_matrix(
+    options: &BuildOptions,
+    toolchains: &[String],
+    all: bool,
+    profile: Option<&str>,
+) -> Result<()> {
+    let config = load_config(profile)?;
+    let targets = selected_targets(&config, options.target.clone(), all || profile.is_some())?;
+    Builder::with_config(config)?.run_toolchain_matrix(toolchains, &targets, options)
+}
+
+/// Run the cargo operation for several targets, in parallel if configured
+fn build_targets(
+    builder: &Builder,
+    config: &Config,
+    targets: &[String],
+    options: &BuildOptions,
+) -> Result<()> {
+    // Benchmarks run one target at a time so their timings don't interfere,
+    // and plain output keeps each target's lines together
+    if config.build.parallel && options.operation != CargoOperation::Bench && !output::is_plain() {
+        let rt = tokio::runtime::Runtime::new()?;
+        rt.block_on(builder.build_all_parallel(targets, options))
+    } else {
+        builder.build_all(targets, options)
+    }
+}
+
+/// Print rows as a left-aligned table; the first row is the header
+fn print_table(rows: &[Vec<String>]) {
+    for line in align_columns(rows) {
+        println!("  {line}");
+    }
+}
+
+/// Print the outcome of an operation for a single target
+fn print_build_report(report: &BuildReport, operation: CargoOperation) {
+    let mut details: Vec<String> = report
+        .strategy
+        .iter()
+        .map(|strategy| strategy.as_str().to_string())
+        .collect();
+    if report.warnings() > 0 {
+        details.push(format_counts(0, report.warnings()));
+    }
+    let details = if details.is_empty() {
+        String::new()
+    } else {
+        format!(" ({})", details.join(", "))
+    };
+
+    helpers::line("");
+    helpers::success(format!(
+        "Finished {} for {} in {}{details}",
+        operation.as_str(),
+        report.target,
+        format_duration(report.duration)
+    ));
+    for artifact in &report.artifacts {
+        helpers::line(format!("  {}", artifact.display()));
+    }
+}
+
+/// Print the findings of `xcargo deps audit` for one target
+fn print_dependency_audit(audit: &DependencyAudit) {
+    helpers::line("");
+    if audit.findings.is_empty() {
+        helpers::success(format!(
+            "{}: no known cross-compilation problems in {} crates",
+            audit.target, audit.crates
+        ));
+        return;
+    }
+
+    let problems = audit.problems();
+    let native = audit.findings.len() - problems;
+    let mut counts = vec![format!("{} crates", audit.crates)];
+    if problems > 0 {
+        counts.push(format!("{problems} with known problems"));
+    }
+    if native > 0 {
+        counts.push(format!("{native} building native code"));
+    }
+    helpers::info(format!("{}: {}", audit.target, counts.join(", ")));
+    for finding in &audit.findings {
+        let message = format!(
+            "{} {}: {}",
+            finding.crate_name, finding.version, finding.problem
+        );
+        match finding.severity {
+            Severity::Problem => helpers::warning(message),
+            Severity::Native => helpers::info(message),
+        }
+        if finding.path.len() > 2 {
+            helpers::line(format!("    via {}", finding.path.join(" > ")));
+        }
+        helpers::hint(&finding.advice);
+    }
+}
+
+/// Print the plans of `xcargo build --plan`
+fn print_build_plan(plans: &[TargetPlan], format: OutputFormat) -> Result<()> {
+    if format == OutputFormat::Json {
+        return print_json(&plans);
+    }
+
+    for plan in plans {
+        helpers::section(format!("Plan for {}", plan.target));
+        let mut rows = vec![
+            vec![
+                "toolchain".to_string(),
+                format!(
+                    "{} ({}{})",
+                    plan.toolchain,
+                    plan.toolchain_source,
+                    plan.rust
+                        .as_deref()
+                        .map(|rust| format!(", Rust {rust}"))
+                        .unwrap_or_default()
+                ),
+            ],
+            vec![
+                "strategy".to_string(),
+                format!("{} ({})", plan.strategy, plan.reason),
+            ],
+        ];
+        if let Some(linker) = &plan.linker {
+            rows.push(vec!["linker".to_string(), linker.clone()]);
+        }
+        if let Some(zig) = &plan.zig {
+            rows.push(vec!["zig".to_string(), zig.clone()]);
+        }
+        if let Some(image) = &plan.image {
+            rows.push(vec!["image".to_string(), image.clone()]);
+        }
+        for (key, value) in &plan.env {
+            rows.push(vec!["env".to_string(), format!("{}={}", key, value)]);
+        }
+        print_table(&rows);
+
+        for missing in &plan.missing {
+            helpers::hint(format!("Missing: {}", missing));
+        }
+        for warning in &plan.warnings {
+            helpers::warning(warning);
+        }
+        for error in &plan.errors {
+            helpers::error(error);
+        }
+        println!();
+    }
+
+    if plans.iter().all(TargetPlan::is_ready) {
+        helpers::tip("Run without --plan to build");
+        Ok(())
+    } else {
+        Err(Error::Build(
+            "The build plan has problems that would stop the build".to_string(),
+        ))
+    }
+}
+
+/// Print the environment of `xcargo build --show-env`, headed by the target
+/// when there are several
+///
+/// Problems are printed as comments, so the output stays valid for a shell.
+fn print_build_env(plans: &[TargetPlan]) {
+    for (idx, plan) in plans.iter().enumerate() {
+        if plans.len() > 1 {
+            if idx > 0 {
+                println!();
+            }
+            println!("# {}", plan.target);
+        }
+        for (key, value) in &plan.env {
+            println!("{key}={value}");
+        }
+        for missing in &plan.missing {
+            println!("# Missing: {missing}");
+        }
+        for error in &plan.errors {
+            println!("# Error: {error}");
+        }
+    }
+}
+
+/// Plan the build of one selected target, failing if the selection is a
+/// group of several
+fn plan_single_target(
+    target: Option<String>,
+    profile: Option<&str>,
+    options: &BuildOptions,
+) -> Result<TargetPlan> {
+    let config = load_config(profile)?;
+    let targets = selected_targets(&config, target, false)?;
+    let [triple] = targets.as_slice() else {
+        return Err(Error::Config(format!(
+            "Select a single target; the selection has {}: {}",
+            targets.len(),
+            targets.join(", ")
+        )));
+    };
+    let builder = Builder::with_config(config)?;
+    let mut plans = builder.plan(std::slice::from_ref(triple), options)?;
+    Ok(plans.remove(0))
+}
+
+/// Write rust-analyzer settings for a target to .vscode/settings.json, or
+/// print them
+fn setup_ide(target: Option<String>, profile: Option<&str>, stdout: bool) -> Result<()> {
+    let plan = plan_single_target(target, profile, &BuildOptions::default())?;
+    let target = Target::from_triple(&plan.target)?;
+    let settings = rust_analyzer_settings(&target, &plan, profile);
+    if stdout {
+        return print_json(&settings);
+    }
+
+    helpers::section("xcargo ide setup");
+    let path = Path::new(VSCODE_SETTINGS);
+    let existing = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let contents = match merge_settings(&existing, &settings) {
+        Ok(contents) => contents,
+        Err(e) => {
+            helpers::hint(format!(
+                "Add the settings to {} by hand; print them with: xcargo ide setup --stdout",
+                path.display()
+            ));
+            return Err(e);
+        }
+    };
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    write_config_file(path, &contents)?;
+
+    helpers::success(format!(
+        "rust-analyzer now checks {} ({} build) in {}",
+        target.triple,
+        plan.strategy,
+        path.display()
+    ));
+    if plan.strategy == "container" {
+        helpers::warning(
+            "rust-analyzer runs on the host, so its build scripts don't get the container's compilers",
+        );
+    }
+    for missing in &plan.missing {
+        helpers::warning(format!("Missing: {}", missing));
+    }
+    if plan
+        .missing
+        .iter()
+        .any(|missing| missing.starts_with("rustup target"))
+    {
+        helpers::tip(format!(
+            "rust-analyzer needs the target's standard library: xcargo target add {}",
+            target.triple
+        ));
+    } else if !plan.missing.is_empty() {
+        helpers::tip("Check-on-save fails until these are installed; see xcargo doctor");
+    }
+    helpers::tip("Reload the VS Code window to apply the settings");
+    Ok(())
+}
+
+/// Print the environment of `xcargo env`
+///
+/// In shell form, problems are printed as comments so the output can be
+/// passed to eval.
+fn print_target_env(plan: &TargetPlan, format: EnvFormat) -> Result<()> {
+    match format {
+        EnvFormat::Shell => {
+            println!("# {} ({} build)", plan.target, plan.strategy);
+            print!("{}", plan.shell_exports());
+            for missing in &plan.missing {
+                println!("# Missing: {missing}");
+            }
+            for error in &plan.errors {
+                println!("# Error: {error}");
+            }
+            Ok(())
+        }
+        EnvFormat::Json => print_json(&serde_json::json!({
+            "target": plan.target,
+            "toolchain": plan.toolchain,
+            "strategy": plan.strategy,
+            "env": plan.full_env(),
+            "missing": plan.missing,
+            "errors": plan.errors,
+        })),
+    }
+}
+
+/// Print a value as pretty JSON
+fn print_json<T: serde::Serialize>(value: &T) -> Result<()> {
+    let json = serde_json::to_string_pretty(value)
+        .map_err(|e| Error::Toolchain(format!("Failed to serialize output: {}", e)))?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Targets offered by the setup wizards
+const COMMON_TARGETS: [(&str, &str); 8] = [
+    ("Linux x86_64", "x86_64-unknown-linux-gnu"),
+    ("Linux x86_64 (musl)", "x86_64-unknown-linux-musl"),
+    ("Linux ARM64", "aarch64-unknown-linux-gnu"),
+    ("Windows x86_64 (GNU)", "x86_64-pc-windows-gnu"),
+    ("Windows x86_64 (MSVC)", "x86_64-pc-windows-msvc"),
+    ("macOS x86_64", "x86_64-apple-darwin"),
+    ("macOS ARM64 (M1/M2)", "aarch64-apple-darwin"),
+    ("WebAssembly", "wasm32-unknown-unknown"),
+];
+
+/// Whether to offer the first-run bootstrap before running `command`
+fn should_offer_first_run(cli: &Cli) -> bool {
+    use std::io::IsTerminal;
+
+    !cli.no_first_run
+        && std::env::var_os(bootstrap::NO_FIRST_RUN_ENV).is_none()
+        && std::io::stdin().is_terminal()
+        && !matches!(
+            cli.command,
+            Commands::Version
+                | Commands::Doctor { .. }
+                | Commands::Init { .. }
+                | Commands::RunTest { .. }
+                | Commands::BisectCheck { .. }
+        )
+        && bootstrap::is_first_run()
+}
+
+/// Offer a guided setup the first time xcargo runs on an environment that
+/// cannot cross-compile yet
+fn run_first_run_bootstrap() -> Result<()> {
+    // Only ever offer once, whatever the answer
+    if let Err(e) = bootstrap::mark_first_run_complete() {
+        helpers::warning(format!("Could not save first-run state: {}", e));
+    }
+
+    let status = EnvironmentStatus::detect();
+    if !status.is_incomplete() {
+        return Ok(());
+    }
+
+    helpers::section("Welcome to xcargo");
+    helpers::info("No cross-compilation route is set up on this machine yet:");
+    for missing in status.missing() {
+        helpers::warning(missing);
+    }
+    println!();
+
+    let run_setup = Confirm::new("Run guided setup now?")
+        .with_default(true)
+        .with_help_message("Runs diagnostics, installs targets, and creates xcargo.toml")
+        .prompt()
+        .map_err(prompt_err)?;
+
+    if !run_setup {
+        helpers::tip("Run 'xcargo doctor' or 'xcargo init --interactive' at any time");
+        return Ok(());
+    }
+
+    // Diagnostics are informational here; failures are shown in the report
+    let _ = xcargo::doctor::run(ReportFormat::Text);
+
+    if Path::new("Cargo.toml").exists() && !Path::new("xcargo.toml").exists() {
+        run_interactive_setup()?;
+    } else {
+        install_targets_prompt()?;
+    }
+
+    println!();
+    helpers::info("Continuing with your command...");
+    Ok(())
+}
+
+/// Let the user pick common targets and install them with rustup
+fn install_targets_prompt() -> Result<()> {
+    let host = Target::detect_host()?;
+    let choices: Vec<&str> = COMMON_TARGETS
+        .iter()
+        .filter(|(_, triple)| *triple != host.triple)
+        .map(|(name, _)| *name)
+        .collect();
+
+    let selected = MultiSelect::new("Which targets do you want to install?", choices)
+        .with_help_message("Use ↑↓ to navigate, Space to select, Enter to confirm")
+        .prompt()
+        .map_err(prompt_err)?;
+
+    let manager = ToolchainManager::new()?;
+    let toolchain = project_toolchain(&manager, None)?;
+    for (name, triple) in COMMON_TARGETS {
+        if !selected.contains(&name) {
+            continue;
+        }
+        match manager.ensure_target(&toolchain, triple) {
+            Ok(()) => helpers::success(format!("Installed {}", triple)),
+            Err(e) => helpers::warning(format!("Failed to install {}: {}", triple, e)),
+        }
+    }
+
+    Ok(())
+}
+
+/// Ask which targets to build when `xcargo build` has nothing to go on
+///
+/// Only offered on a terminal with no default targets configured; returns
+/// `None` otherwise, so scripts and CI keep building for the host.
+fn pick_build_targets() -> Result<Option<Vec<String>>> {
+    use std::io::IsTerminal;
+
+    if !std::io::stdin().is_terminal()
+        || !std::io::stdout().is_terminal()
+        || !load_config(None)?.targets.default.is_empty()
+    {
+        return Ok(None);
+    }
+
+    let host = Target::detect_host()?;
+    // If the installed targets can't be listed, only the popular targets are offered
+    let installed: Vec<String> = Target::detect_installed()
+        .map(|targets| targets.into_iter().map(|t| t.triple).collect())
+        .unwrap_or_default();
+
+    let mut choices = vec![format!("{} (host)", host.triple)];
+    choices.extend(
+        installed
+            .iter()
+            .filter(|triple| **triple != host.triple)
+            .map(|triple| format!("{} (installed)", triple)),
+    );
+    choices.extend(
+        COMMON_TARGETS
+            .iter()
+            .filter(|(_, triple)| *triple != host.triple && !installed.iter().any(|t| t == triple))
+            .map(|(name, triple)| format!("{} ({}, not installed)", triple, name)),
+    );
+
+    let selected = MultiSelect::new(
+        "No target configured. Which targets do you want to build?",
+        choices,
+    )
+    .with_default(&[0])
+    .with_help_message("Use ↑↓ to navigate, Space to select, Enter to confirm")
+    .prompt()
+    .map_err(prompt_err)?;
+
+    let mut targets: Vec<String> = selected
+        .iter()
+        .filter_map(|choice| choice.split_whitespace().next())
+        .map(str::to_string)
+        .collect();
+    if targets.is_empty() {
+        helpers::warning("No targets selected, using host target");
+        targets.push(host.triple);
+    }
+    helpers::tip("Set [targets] default in xcargo.toml to skip this question");
+    Ok(Some(targets))
+}
+
+/// Run basic non-interactive setup
+fn run_basic_setup() -> Result<()> {
+    helpers::section("Initialize xcargo");
+
+    if Path::new("xcargo.toml").exists() {
+        helpers::warning("xcargo.toml already exists");
+        let overwrite = Confirm::new("Overwrite existing configuration?")
+            .with_default(false)
+            .prompt()
+            .map_err(prompt_err)?;
+
+        if !overwrite {
+            helpers::info("Setup cancelled");
+            return Ok(());
+        }
+    }
+
+    let host = Target::detect_host()?;
+    let mut config = Config::default();
+    config.targets.default = vec![host.triple.clone()];
+
+    config.save("xcargo.toml")?;
+
+    helpers::success("Created xcargo.toml with default configuration");
+    helpers::tip(format!("Default target: {}", host.triple));
+    helpers::hint("Use 'xcargo init --interactive' for guided setup");
+
+    Ok(())
+}
+
+/// Write xcargo.toml from a project template
+fn run_template_setup(template: ProjectTemplate) -> Result<()> {
+    helpers::section("Initialize xcargo");
+
+    if Path::new("xcargo.toml").exists() {
+        helpers::warning("xcargo.toml already exists");
+        let overwrite = Confirm::new("Overwrite existing configuration?")
+            .with_default(false)
+            .prompt()
+            .map_err(prompt_err)?;
+
+        if !overwrite {
+            helpers::info("Setup cancelled");
+            return Ok(());
+        }
+    }
+
+    write_config_file(Path::new("xcargo.toml"), template.render())?;
+
+    helpers::success(format!(
+        "Created xcargo.toml from the {} template",
+        template.as_str()
+    ));
+    for step in template.next_steps() {
+        helpers::tip(*step);
+    }
+
+    Ok(())
+}
+
+/// Run interactive TUI setup wizard
+fn run_interactive_setup() -> Result<()> {
+    use xcargo::output::colors;
+
+    let palette = theme::palette();
+    let title = format!("{}xcargo Interactive Setup", terminal::symbol("✨ ", ""));
+    println!(
+        "\n{}",
+        theme::paint(colors::BOLD, theme::paint(palette.heading, title))
+    );
+    println!(
+        "{}\n",
+        theme::paint(
+            palette.dim,
+            "Let's configure cross-compilation for your project!"
+        )
+    );
+
+    // Check for existing config
+    if Path::new("xcargo.toml").exists() {
+        helpers::warning("xcargo.toml already exists");
+        let overwrite = Confirm::new("Overwrite existing configuration?")
+            .with_default(false)
+            .prompt()
+            .map_err(prompt_err)?;
+
+        if !overwrite {
+            helpers::info("Setup cancelled");
+            return Ok(());
+        }
+    }
+
+    // Detect host
+    let host = Target::detect_host()?;
+    helpers::success(format!("Detected host platform: {}", host.triple));
+    println!();
+
+    // Select target platforms, starting from what Cargo.toml suggests
+    let mut target_options: Vec<(String, String)> = COMMON_TARGETS
+        .iter()
+        .map(|(name, triple)| (name.to_string(), triple.to_string()))
+        .collect();
+    let suggested = match project_suggestion() {
+        Ok(suggestion) => suggestion
+            .map(|suggestion| suggestion.targets)
+            .unwrap_or_default(),
+        Err(e) => {
+            helpers::warning(format!(
+                "Could not read Cargo.toml to suggest targets: {}",
+                e
+            ));
+            Vec::new()
+        }
+    };
+    for target in &suggested {
+        if !target_options
+            .iter()
+            .any(|(_, triple)| *triple == target.triple)
+        {
+            target_options.push((target.triple.clone(), target.triple.clone()));
+        }
+    }
+    let preselected: Vec<usize> = target_options
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, triple))| suggested.iter().any(|target| target.triple == *triple))
+        .map(|(idx, _)| idx)
+        .collect();
+    if !suggested.is_empty() {
+        helpers::info(
+            "Targets suggested by Cargo.toml are preselected; see 'xcargo suggest' for why",
+        );
+    }
+
+    let selected_names = MultiSelect::new(
+        "Which targets do you want to build for?",
+        target_options
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect(),
+    )
+    .with_default(&preselected)
+    .with_help_message("Use ↑↓ to navigate, Space to select, Enter to confirm")
+    .prompt()
+    .map_err(prompt_err)?;
+
+    let selected_targets: Vec<String> = selected_names
+        .iter()
+        .filter_map(|&selected_name| {
+            target_options
+                .iter()
+                .find(|(name, _)| name == selected_name)
+                .map(|(_, triple)| triple.clone())
+        })
+        .collect();
+
+    if selected_targets.is_empty() {
+        helpers::warning("No targets selected, using host target");
+    }
+
+    println!();
+
+    // Parallel builds
+    let parallel = Confirm::new("Enable parallel builds?")
+        .with_default(true)
+        .with_help_message("Build multiple targets concurrently for faster builds")
+        .prompt()
+        .map_err(prompt_err)?;
+
+    // Build caching
+    let cache = Confirm::new("Enable build caching?")
+        .with_default(true)
+        .with_help_message("Cache build artifacts to speed up subsequent builds")
+        .prompt()
+        .map_err(prompt_err)?;
+
+    // Container strategy
+    let container_options = vec![
+        "Auto (use containers only when necessary)",
+        "Always use containers",
+        "Never use containers",
+    ];
+
+    let container_choice = Select::new("Container build strategy:", container_options)
+        .with_help_message("Containers ensure reproducible builds")
+        .prompt()
+        .map_err(prompt_err)?;
+
+    let use_when = match container_choice {
+        "Auto (use containers only when necessary)" => "target.os != host.os",
+        "Always use containers" => "always",
+        "Never use containers" => "never",
+        _ => "target.os != host.os",
+    };
+
+    println!();
+    helpers::progress("Creating configuration...");
+
+    // Build configuration
+    let mut config = Config::default();
+    let host_triple = host.triple.clone();
+    config.targets.default = if selected_targets.is_empty() {
+        vec![host_triple.clone()]
+    } else {
+        selected_targets.clone()
+    };
+    config.build.parallel = parallel;
+    config.build.cache = cache;
+    config.container.use_when = use_when.to_string();
+
+    // Save configuration
+    config.save("xcargo.toml")?;
+
+    println!();
+    helpers::success("Configuration created successfully!");
+    println!();
+
+    // Summary
+    helpers::section("Configuration Summary");
+    println!("Targets: {}", selected_targets.join(", "));
+    println!(
+        "Parallel builds: {}",
+        if parallel { "enabled" } else { "disabled" }
+    );
+    println!(
+        "Build cache: {}",
+        if cache { "enabled" } else { "disabled" }
+    );
+    println!("Container strategy: {}", use_when);
+    println!();
+
+    // Next steps
+    helpers::section("Next Steps");
+    helpers::tip("Run 'xcargo build' to build for your host platform");
+    helpers::tip("Run 'xcargo build --all' to build for all configured targets");
+    helpers::tip("Run 'xcargo target add <triple>' to add more targets");
+    println!();
+
+    // Offer to install targets
+    let install_now = Confirm::new("Install selected targets now?")
+        .with_default(true)
+        .prompt()
+        .map_err(prompt_err)?;
+
+    if install_now && !selected_targets.is_empty() {
         println!();
         helpers::progress("Installing targets...");
         let manager = ToolchainManager::new()?;
+        let toolchain = project_toolchain(&manager, None)?;
+
+        for target in &selected_targets {
+            if target != &host_triple {
+                match manager.ensure_target(&toolchain, target) {
+                    Ok(()) => helpers::success(format!("Installed {}", target)),
+                    Err(e) => helpers::warning(format!("Failed to install {}: {}", target, e)),
+                }
+            }
+        }
+
+        println!();
+        helpers::success(format!(
+            "Setup complete! You're ready to cross-compile{}",
+            terminal::symbol(" 🚀", "")
+        ));
+    } else {
+        helpers::success("Setup complete! Install targets later with 'xcargo target add <triple>'");
+    }
+
+    Ok(())
+}
+
+fn main() {
+    // Set up Ctrl+C handler for graceful shutdown
+    setup_signal_handler();
+
+    if let Err(e) = run() {
+        exit_with_error(&e);
+    }
+    log::write("Exit", "exit code 0");
+}
+
+/// Set up signal handler for graceful shutdown on Ctrl+C
+fn setup_signal_handler() {
+    ctrlc::set_handler(move || {
+        eprintln!("\n");
+        helpers::warning(i18n::tr("Received interrupt signal (Ctrl+C)"));
+        helpers::info(i18n::tr("Cleaning up and shutting down gracefully..."));
+
+        // Exit with code 130 (128 + SIGINT)
+        std::process::exit(130);
+    })
+    .expect("Error setting Ctrl-C handler");
+}
+
+/// Marker for list items: a bullet, or `-` without Unicode
+fn bullet() -> &'static str {
+    terminal::symbol("•", "-")
+}
+
+fn run() -> Result<()> {
+    let cli = Cli::parse();
+    log::set_verbosity(Verbosity::from_flags(cli.quiet, cli.verbose));
+    if let Some(path) = &cli.log_file {
+        log::open(path)
+            .map_err(|e| Error::Config(format!("Cannot write log file {}: {e}", path.display())))?;
+    }
+    set_cli_overrides(&cli.config_overrides)?;
+
+    // A broken xcargo.toml is reported by the command that loads it, but an
+    // xcargo too old for the project stops here, before anything misreads it
+    let loaded = Config::load();
+    if matches!(loaded, Err(Error::VersionRequired { .. })) {
+        return loaded.map(|_| ());
+    }
+    if let Ok(config) = &loaded {
+        use_target_dir_config(&config.build);
+        xcargo::toolchain::use_network_config(&config.network);
+    }
+    let output_config = loaded.ok().map(|config| config.output);
+    if cli.plain || output_config.as_ref().and_then(|o| o.plain) == Some(true) {
+        output::set_plain(true);
+        // Keep cargo's own progress bar and colors out of the output too
+        for (var, value) in [
+            ("CARGO_TERM_COLOR", "never"),
+            ("CARGO_TERM_PROGRESS_WHEN", "never"),
+        ] {
+            if std::env::var_os(var).is_none() {
+                std::env::set_var(var, value);
+            }
+        }
+    }
+    terminal::init(cli.color);
+    if !terminal::colors_enabled() || cli.color == ColorChoice::Always {
+        // Match cargo's colors to ours, e.g. when NO_COLOR is set
+        let value = if terminal::colors_enabled() {
+            "always"
+        } else {
+            "never"
+        };
+        if std::env::var_os("CARGO_TERM_COLOR").is_none() {
+            std::env::set_var("CARGO_TERM_COLOR", value);
+        }
+    }
+    if let Err(e) = theme::init(output_config.as_ref().and_then(|o| o.theme)) {
+        helpers::warning(e);
+    }
+    if let Err(e) = i18n::init(output_config.and_then(|o| o.language)) {
+        helpers::warning(e);
+    }
+
+    if let Some(path) = &cli.progress_socket {
+        if let Err(e) = events::connect(path) {
+            helpers::warning(format!("{}; building without progress events", e));
+        }
+    }
+
+    let network = NetworkFlags::new(cli.locked, cli.offline, cli.frozen);
+    if network.offline {
+        xcargo::toolchain::set_offline(true);
+    }
+
+    if should_offer_first_run(&cli) {
+        run_first_run_bootstrap()?;
+    }
+
+    match cli.command {
+        Commands::Build {
+            target,
+            all,
+            since,
+            full,
+            release,
+            container,
+            zig,
+            no_zig,
+            profile,
+            toolchain,
+            toolchain_matrix,
+            attest,
+            plan,
+            show_env,
+            failure,
+            output,
+            selection,
+            cargo_args,
+        } => {
+            let options = BuildOptions {
+                target: target.clone(),
+                release,
+                cargo_args: network.with_cargo_args(cargo_args),
+                toolchain,
+                verbose: cli.verbose > 0,
+                use_container: container,
+                use_zig: zig_preference(zig, no_zig),
+                operation: CargoOperation::Build,
+                affected_since: (all && !full).then_some(since),
+                assume_yes: cli.yes,
+                collect_tests: None,
+                attest,
+                selection: selection.into(),
+                fail_fast: failure.fail_fast(),
+                output,
+                lock: true,
+            };
+
+            if !toolchain_matrix.is_empty() {
+                return run_toolchain_matrix(&options, &toolchain_matrix, all, profile.as_deref());
+            }
+            if plan.is_some() || show_env {
+                let config = load_config(profile.as_deref())?;
+                let targets = selected_targets(&config, target, all || profile.is_some())?;
+                let plans = Builder::with_config(config)?.plan(&targets, &options)?;
+                return match plan {
+                    Some(format) => print_build_plan(&plans, format),
+                    None => {
+                        print_build_env(&plans);
+                        Ok(())
+                    }
+                };
+            }
+
+            if target.is_none() && !all && profile.is_none() && !cli.yes {
+                if let Some(targets) = pick_build_targets()? {
+                    let config = load_config(None)?;
+                    let builder = Builder::with_config(config.clone())?;
+                    return build_targets(&builder, &config, &targets, &options);
+                }
+            }
+
+            run_operation(&options, all, profile.as_deref())?;
+        }
+
+        Commands::Check {
+            target,
+            all,
+            zig,
+            no_zig,
+            profile,
+            toolchain,
+            toolchain_matrix,
+            message_format,
+            failure,
+            output,
+            selection,
+            mut cargo_args,
+        } => {
+            if let Some(format) = message_format {
+                if format.starts_with("json") {
+                    xcargo::output::set_messages_to_stderr(true);
+                }
+                cargo_args.insert(0, format!("--message-format={format}"));
+            }
+            let options = BuildOptions {
+                target: target.clone(),
+                release: false,
+                cargo_args: network.with_cargo_args(cargo_args),
+                toolchain,
+                verbose: cli.verbose > 0,
+                use_container: false,
+                use_zig: zig_preference(zig, no_zig),
+                operation: CargoOperation::Check,
+                affected_since: None,
+                assume_yes: cli.yes,
+                collect_tests: None,
+                attest: false,
+                selection: selection.into(),
+                fail_fast: failure.fail_fast(),
+                output,
+                lock: true,
+            };
+
+            if !toolchain_matrix.is_empty() {
+                return run_toolchain_matrix(&options, &toolchain_matrix, all, profile.as_deref());
+            }
+            run_operation(&options, all, profile.as_deref())?;
+        }
+
+        Commands::Test {
+            target,
+            all,
+            release,
+            no_run,
+            collect,
+            from_collected,
+            container,
+            zig,
+            no_zig,
+            profile,
+            toolchain,
+            toolchain_matrix,
+            failure,
+            output,
+            selection,
+            mut cargo_args,
+        } => {
+            if let Some(dir) = from_collected {
+                helpers::section("xcargo test --from-collected");
+                let target = target.map(|t| Target::resolve_alias(&t)).transpose()?;
+                return run_collected(
+                    &load_config(profile.as_deref())?,
+                    &dir,
+                    target.as_deref(),
+                    &cargo_args,
+                );
+            }
+
+            if no_run && !cargo_args.iter().any(|arg| arg == "--no-run") {
+                cargo_args.insert(0, "--no-run".to_string());
+            }
+            let options = BuildOptions {
+                target: target.clone(),
+                release,
+                cargo_args: network.with_cargo_args(cargo_args),
+                toolchain,
+                verbose: cli.verbose > 0,
+                use_container: container,
+                use_zig: zig_preference(zig, no_zig),
+                operation: CargoOperation::Test,
+                affected_since: None,
+                assume_yes: cli.yes,
+                collect_tests: collect,
+                attest: false,
+                selection: selection.into(),
+                fail_fast: failure.fail_fast(),
+                output,
+                lock: true,
+            };
+
+            if !toolchain_matrix.is_empty() {
+                return run_toolchain_matrix(&options, &toolchain_matrix, all, profile.as_deref());
+            }
+            run_operation(&options, all, profile.as_deref())?;
+        }
+
+        Commands::Bench {
+            target,
+            all,
+            zig,
+            no_zig,
+            toolchain,
+            failure,
+            output,
+            selection,
+            cargo_args,
+        } => {
+            let options = BuildOptions {
+                target,
+                release: false,
+                cargo_args: network.with_cargo_args(cargo_args),
+                toolchain,
+                verbose: cli.verbose > 0,
+                use_container: false,
+                use_zig: zig_preference(zig, no_zig),
+                operation: CargoOperation::Bench,
+                affected_since: None,
+                assume_yes: cli.yes,
+                collect_tests: None,
+                attest: false,
+                selection: selection.into(),
+                fail_fast: failure.fail_fast(),
+                output,
+                lock: true,
+            };
+
+            run_operation(&options, all, None)?;
+        }
+
+        Commands::Doc {
+            target,
+            all,
+            release,
+            zig,
+            no_zig,
+            profile,
+            toolchain,
+            merge,
+            failure,
+            output,
+            selection,
+            cargo_args,
+        } => {
+            let options = BuildOptions {
+                target: target.clone(),
+                release,
+                cargo_args: network.with_cargo_args(cargo_args),
+                toolchain,
+                verbose: cli.verbose > 0,
+                use_container: false,
+                use_zig: zig_preference(zig, no_zig),
+                operation: CargoOperation::Doc,
+                affected_since: None,
+                assume_yes: cli.yes,
+                collect_tests: None,
+                attest: false,
+                selection: selection.into(),
+                fail_fast: failure.fail_fast(),
+                output,
+                lock: true,
+            };
+
+            run_operation(&options, all, profile.as_deref())?;
+
+            if let Some(dir) = merge {
+                let config = load_config(profile.as_deref())?;
+                let targets = selected_targets(&config, target, all || profile.is_some())?;
+                let dir = dir.unwrap_or_else(merged_doc_dir);
+                let merged = merge_docs(&targets, &dir)?;
+                for triple in &merged.missing {
+                    helpers::warning(format!("No docs for {triple} to merge"));
+                }
+                helpers::success(format!(
+                    "Merged the docs of {} target{} into {}",
+                    merged.crates.len(),
+                    if merged.crates.len() == 1 { "" } else { "s" },
+                    dir.display()
+                ));
+                helpers::tip(format!("Open {}", merged.index.display()));
+            }
+        }
+
+        Commands::Matrix {
+            target,
+            features,
+            profiles,
+            check,
+            test,
+            dry_run,
+            zig,
+            no_zig,
+            toolchain,
+            cargo_args,
+        } => {
+            let config = load_config(None)?;
+            let matrix = BuildMatrix::from_config(&config, &target, &features, &profiles)?;
+
+            if dry_run {
+                helpers::section("xcargo matrix (dry run)");
+                for cell in matrix.cells() {
+                    helpers::line(format!("  {cell}"));
+                }
+                helpers::info(format!("{} builds", matrix.cells().len()));
+                return Ok(());
+            }
+
+            let operation = if check {
+                CargoOperation::Check
+            } else if test {
+                CargoOperation::Test
+            } else {
+                CargoOperation::Build
+            };
+            let options = BuildOptions {
+                target: None,
+                release: false,
+                cargo_args: network.with_cargo_args(cargo_args),
+                toolchain,
+                verbose: cli.verbose > 0,
+                use_container: false,
+                use_zig: zig_preference(zig, no_zig),
+                operation,
+                affected_since: None,
+                assume_yes: cli.yes,
+                collect_tests: None,
+                attest: false,
+                selection: ArtifactSelection::default(),
+                fail_fast: None,
+                output: None,
+                lock: true,
+            };
+
+            let builder = Builder::with_config(config)?;
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(builder.run_matrix(&matrix, &options))?;
+        }
+
+        Commands::Target { action } => match action {
+            TargetAction::Add { targets, toolchain } => {
+                helpers::section("Add Target");
+
+                let manager = ToolchainManager::new()?;
+                let toolchain = project_toolchain(&manager, toolchain.as_deref())?;
+                let mut added = Vec::new();
+                let mut present = 0;
+                let mut failed = Vec::new();
+                for target in &targets {
+                    let triple = match resolve_target_arg(&manager, &toolchain, target) {
+                        Ok(triple) => triple,
+                        Err(e) => {
+                            helpers::error(format!("{}: {}", target, e));
+                            failed.push(target.clone());
+                            continue;
+                        }
+                    };
+                    if manager.is_target_installed(&toolchain, &triple)? {
+                        helpers::info(format!("Target {} is already installed", triple));
+                        present += 1;
+                        continue;
+                    }
+                    match manager.install_target(&toolchain, &triple) {
+                        Ok(()) => added.push(triple),
+                        Err(e) => {
+                            helpers::error(e.to_string());
+                            failed.push(triple);
+                        }
+                    }
+                }
+
+                if targets.len() > 1 {
+                    println!();
+                    helpers::info(format!(
+                        "{} added, {} already installed, {} failed (toolchain {})",
+                        added.len(),
+                        present,
+                        failed.len(),
+                        toolchain
+                    ));
+                }
+                if !failed.is_empty() {
+                    return Err(Error::Toolchain(format!(
+                        "Failed to add {}",
+                        failed.join(", ")
+                    )));
+                }
+                if let [triple] = added.as_slice() {
+                    helpers::tip(format!(
+                        "Use 'xcargo build --target {}' to build for this target",
+                        triple
+                    ));
+                }
+            }
+
+            TargetAction::Remove { targets, toolchain } => {
+                helpers::section("Remove Target");
+
+                let manager = ToolchainManager::new()?;
+                let toolchain = project_toolchain(&manager, toolchain.as_deref())?;
+                let configured = load_config(None)?.targets.default;
+                let mut failed = Vec::new();
+                for target in &targets {
+                    let result = remove_target(&manager, &toolchain, target, &configured);
+                    if let Err(e) = result {
+                        helpers::error(format!("{}: {}", target, e));
+                        failed.push(target.clone());
+                    }
+                }
+                if !failed.is_empty() {
+                    return Err(Error::Toolchain(format!(
+                        "Failed to remove {}",
+                        failed.join(", ")
+                    )));
+                }
+            }
+
+            TargetAction::Sync {
+                prune,
+                dry_run,
+                toolchain,
+            } => {
+                helpers::section("Sync Targets");
+                sync_targets(toolchain.as_deref(), prune, dry_run)?;
+            }
+
+            TargetAction::List {
+                installed,
+                toolchain,
+            } => {
+                helpers::section("Available Targets");
+
+                if installed {
+                    let manager = ToolchainManager::new()?;
+                    let tc = project_toolchain(&manager, toolchain.as_deref())?;
+
+                    helpers::info(format!("Installed targets for toolchain '{}':", tc));
+                    println!();
+
+                    match manager.list_targets(&tc) {
+                        Ok(targets) => {
+                            if targets.is_empty() {
+                                println!("  No targets installed");
+                            } else {
+                                for target in targets {
+                                    println!("  {} {}", bullet(), target);
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            helpers::error(format!("Failed to list targets: {}", e));
+                            std::process::exit(1);
+                        }
+                    }
+                } else {
+                    println!("Common cross-compilation targets:\n");
+
+                    println!("Linux:");
+                    println!("  {} x86_64-unknown-linux-gnu   (Linux x86_64)", bullet());
+                    println!(
+                        "  {} x86_64-unknown-linux-musl  (Linux x86_64, statically linked)",
+                        bullet()
+                    );
+                    println!("  {} aarch64-unknown-linux-gnu  (Linux ARM64)", bullet());
+                    println!();
+
+                    println!("Windows:");
+                    println!(
+                        "  {} x86_64-pc-windows-gnu      (Windows x86_64, MinGW)",
+                        bullet()
+                    );
+                    println!(
+                        "  {} x86_64-pc-windows-msvc     (Windows x86_64, MSVC)",
+                        bullet()
+                    );
+                    println!();
+
+                    println!("macOS:");
+                    println!("  {} x86_64-apple-darwin        (macOS x86_64)", bullet());
+                    println!(
+                        "  {} aarch64-apple-darwin       (macOS ARM64, M1/M2)",
+                        bullet()
+                    );
+                    println!();
+
+                    helpers::hint("Use 'xcargo target list --installed' to see installed targets");
+                    helpers::tip("Use 'xcargo target add <triple>' to install a new target");
+                }
+            }
+
+            TargetAction::Info { target } => {
+                helpers::section("Target Information");
+
+                let target_triple = Target::resolve_alias(&target)?;
+                match Target::from_triple(&target_triple) {
+                    Ok(target) => {
+                        println!("Triple:       {}", target.triple);
+                        println!("Architecture: {}", target.arch);
+                        println!("OS:           {}", target.os);
+                        println!(
+                            "Environment:  {}",
+                            target.env.as_deref().unwrap_or("default")
+                        );
+                        println!("Tier:         {:?}", target.tier);
+                        println!();
+
+                        let requirements = target.get_requirements();
+                        if requirements.linker.is_some()
+                            || !requirements.tools.is_empty()
+                            || !requirements.system_libs.is_empty()
+                        {
+                            helpers::info("Requirements:");
+                            if let Some(linker) = requirements.linker {
+                                println!("  Linker: {}", linker);
+                            }
+                            if !requirements.tools.is_empty() {
+                                println!("  Tools: {}", requirements.tools.join(", "));
+                            }
+                            if !requirements.system_libs.is_empty() {
+                                println!("  System libs: {}", requirements.system_libs.join(", "));
+                            }
+                            println!();
+                        }
+
+                        let host = Target::detect_host()?;
+                        if target.can_cross_compile_from(&host) {
+                            helpers::success("Can cross-compile from this host");
+                        } else {
+                            helpers::warning("May require container for cross-compilation");
+                        }
+
+                        println!();
+                        helpers::tip(format!(
+                            "Add this target: xcargo target add {}",
+                            target.triple
+                        ));
+                        helpers::tip(format!(
+                            "Build for this target: xcargo build --target {}",
+                            target.triple
+                        ));
+                    }
+                    Err(e) => {
+                        helpers::error(format!("Invalid target: {}", e));
+                        std::process::exit(1);
+                    }
+                }
+            }
+        },
 
-        for target in &selected_targets {
-            if target != &host_triple {
-                match manager.ensure_target("stable", target) {
-                    Ok(()) => helpers::success(format!("Installed {}", target)),
-                    Err(e) => helpers::warning(format!("Failed to install {}: {}", target, e)),
+        Commands::Suggest => {
+            helpers::section("Suggested Targets");
+            let Some(suggestion) = project_suggestion()? else {
+                return Err(Error::Config(
+                    "No Cargo.toml in the current directory".to_string(),
+                ));
+            };
+            if suggestion.targets.is_empty() {
+                helpers::info("Cargo.toml has no binaries or libraries to suggest targets for");
+                return Ok(());
+            }
+            let width = suggestion
+                .targets
+                .iter()
+                .map(|target| target.triple.len())
+                .max()
+                .unwrap_or(0);
+            for target in &suggestion.targets {
+                println!(
+                    "  {} {:<width$}  {}",
+                    bullet(),
+                    target.triple,
+                    theme::paint(theme::palette().dim, &target.reason),
+                    width = width
+                );
+            }
+            println!();
+            helpers::info("Add to xcargo.toml:");
+            println!();
+            print!("{}", suggestion.snippet());
+            println!();
+            if let Some(template) = suggestion.template {
+                helpers::tip(format!(
+                    "Or start from the template: xcargo init --template {}",
+                    template.as_str()
+                ));
+            }
+            helpers::tip("xcargo init --interactive preselects these targets");
+        }
+
+        Commands::Init {
+            interactive,
+            template,
+        } => {
+            if let Some(template) = template {
+                run_template_setup(template)?;
+            } else if interactive {
+                run_interactive_setup()?;
+            } else {
+                run_basic_setup()?;
+            }
+        }
+
+        Commands::Config {
+            action,
+            default,
+            show_origin,
+            env,
+        } => {
+            match action {
+                Some(ConfigAction::Validate) => return validate_config_files(),
+                Some(ConfigAction::Edit { user }) => return edit_config_file(user),
+                None => {}
+            }
+            helpers::section("Configuration");
+
+            if env {
+                print_config_env();
+            } else if default {
+                let config = Config::default();
+                match config.to_toml() {
+                    Ok(toml) => {
+                        println!("{}", toml);
+                        println!();
+                        helpers::tip("Save this to xcargo.toml to customize your build");
+                    }
+                    Err(e) => {
+                        helpers::error(format!("Failed to generate config: {}", e));
+                        std::process::exit(1);
+                    }
+                }
+            } else {
+                let layered = match LayeredConfig::discover() {
+                    Ok(layered) => layered,
+                    Err(e) => {
+                        helpers::error(format!("Failed to load config: {}", e));
+                        std::process::exit(1);
+                    }
+                };
+                if layered.files.is_empty() {
+                    helpers::info("No xcargo.toml found, using defaults");
+                } else if !layered.files.iter().any(|file| {
+                    matches!(file, ConfigSource::Workspace(_) | ConfigSource::Manifest(_))
+                }) {
+                    helpers::info("No xcargo.toml found, using the user configuration");
+                }
+                for file in &layered.files {
+                    helpers::info(format!("Configuration from: {}", file));
+                }
+                println!();
+
+                if show_origin {
+                    print_config_origins(&layered)?;
+                } else {
+                    match layered.config.to_toml() {
+                        Ok(toml) => println!("{}", toml),
+                        Err(e) => {
+                            helpers::error(format!("Failed to serialize config: {}", e));
+                            std::process::exit(1);
+                        }
+                    }
+                }
+
+                if layered.files.is_empty() {
+                    println!();
+                    helpers::tip(tips::CONFIG_FILE);
                 }
             }
         }
 
-        println!();
-        helpers::success("Setup complete! You're ready to cross-compile 🚀");
-    } else {
-        helpers::success("Setup complete! Install targets later with 'xcargo target add <triple>'");
-    }
+        Commands::Toolchain { action } => match action {
+            ToolchainAction::List { format } => {
+                let manager = ToolchainManager::new()?;
+                let mut toolchains = manager.list_toolchains()?;
+                for toolchain in &mut toolchains {
+                    toolchain.targets = manager.list_targets(&toolchain.name)?;
+                }
 
-    Ok(())
-}
+                if format == OutputFormat::Json {
+                    print_json(&toolchains)?;
+                } else {
+                    helpers::section("Installed Toolchains");
 
-fn main() {
-    // Set up Ctrl+C handler for graceful shutdown
-    setup_signal_handler();
+                    let mut rows = vec![vec![
+                        "TOOLCHAIN".to_string(),
+                        "DEFAULT".to_string(),
+                        "TARGETS".to_string(),
+                    ]];
+                    for toolchain in &toolchains {
+                        rows.push(vec![
+                            toolchain.name.clone(),
+                            if toolchain.is_default { "yes" } else { "" }.to_string(),
+                            toolchain.targets.join(", "),
+                        ]);
+                    }
+                    print_table(&rows);
+                    println!();
+                    helpers::tip("Run 'xcargo toolchain info <name>' for details");
+                }
+            }
 
-    if let Err(e) = run() {
-        exit_with_error(&e);
-    }
-}
+            ToolchainAction::Info { name, format } => {
+                let manager = ToolchainManager::new()?;
+                let info = manager.toolchain_info(&name)?;
 
-/// Set up signal handler for graceful shutdown on Ctrl+C
-fn setup_signal_handler() {
-    ctrlc::set_handler(move || {
-        eprintln!("\n");
-        helpers::warning("Received interrupt signal (Ctrl+C)");
-        helpers::info("Cleaning up and shutting down gracefully...");
+                if format == OutputFormat::Json {
+                    print_json(&info)?;
+                } else {
+                    helpers::section(format!("Toolchain {}", info.name));
 
-        // Exit with code 130 (128 + SIGINT)
-        std::process::exit(130);
-    })
-    .expect("Error setting Ctrl-C handler");
-}
+                    println!(
+                        "  rustc:   {}",
+                        info.rustc_version.as_deref().unwrap_or("unknown")
+                    );
+                    if let Some(sysroot) = &info.sysroot {
+                        println!("  sysroot: {}", sysroot.display());
+                    }
+                    println!("  default: {}", if info.is_default { "yes" } else { "no" });
+                    println!();
 
-fn run() -> Result<()> {
-    let cli = Cli::parse();
+                    println!("Components:");
+                    for component in &info.components {
+                        println!("  {} {}", bullet(), component);
+                    }
+                    println!();
 
-    match cli.command {
-        Commands::Build {
+                    println!("Targets:");
+                    let mut rows = vec![vec!["TARGET".to_string(), "SIZE".to_string()]];
+                    for target in &info.targets {
+                        rows.push(vec![target.triple.clone(), format_size(target.size_bytes)]);
+                    }
+                    print_table(&rows);
+                }
+            }
+
+            ToolchainAction::Zig {
+                action: ZigAction::Install { version },
+            } => {
+                let version = match version {
+                    Some(version) => version,
+                    None => Config::load()?.zig.version.ok_or_else(|| {
+                        Error::Config(
+                            "No Zig version given and xcargo.toml does not pin zig.version"
+                                .to_string(),
+                        )
+                    })?,
+                };
+                if let Some(path) = managed_zig(&version) {
+                    helpers::success(format!(
+                        "Zig {} is already installed at {}",
+                        version,
+                        path.display()
+                    ));
+                } else {
+                    let path = install_zig(&version)?;
+                    helpers::success(format!("Installed Zig {} at {}", version, path.display()));
+                }
+            }
+
+            ToolchainAction::Zig {
+                action: ZigAction::List,
+            } => {
+                let versions = installed_zig_versions();
+                if versions.is_empty() {
+                    helpers::info("No Zig releases installed in ~/.xcargo/zig");
+                    helpers::tip("Install one with: xcargo toolchain zig install <version>");
+                } else {
+                    let pinned = Config::load().ok().and_then(|config| config.zig.version);
+                    helpers::section("Installed Zig Releases");
+                    for version in &versions {
+                        if pinned.as_ref() == Some(version) {
+                            println!("  {} {} (pinned in xcargo.toml)", bullet(), version);
+                        } else {
+                            println!("  {} {}", bullet(), version);
+                        }
+                    }
+                }
+            }
+        },
+
+        Commands::Doctor { target, format } => {
+            let result = match target {
+                Some(target) => xcargo::doctor::run_target(&target, format),
+                None => xcargo::doctor::run(format),
+            };
+            // Machine-readable reports carry the failures; only the exit code is added
+            match result {
+                Err(e) if format != ReportFormat::Text => std::process::exit(e.exit_code()),
+                result => result?,
+            }
+        }
+
+        #[cfg(feature = "container")]
+        Commands::Container { action } => match action {
+            ContainerAction::Doctor { image } => {
+                xcargo::doctor::run_container(&image)?;
+            }
+            ContainerAction::Images => {
+                list_container_images()?;
+            }
+            ContainerAction::Pull {
+                target,
+                all,
+                archive,
+            } => {
+                pull_container_images(target.as_deref(), all, archive.as_deref())?;
+            }
+            ContainerAction::Login {
+                registry,
+                username,
+                password_stdin,
+            } => {
+                container_login(registry, username, password_stdin)?;
+            }
+        },
+
+        Commands::Profile { action } => match action {
+            ProfileAction::List => {
+                helpers::section("Build Profiles");
+
+                let config = load_config(None)?;
+                if config.profiles.is_empty() {
+                    println!("  No profiles defined");
+                    println!();
+                    helpers::tip(tips::BUILD_PROFILES);
+                } else {
+                    let mut names: Vec<&String> = config.profiles.keys().collect();
+                    names.sort();
+
+                    for name in names {
+                        let profile = &config.profiles[name];
+                        println!("  {} {}", bullet(), name);
+                        if profile.targets.is_empty() {
+                            println!("      targets: (default targets)");
+                        } else {
+                            println!("      targets: {}", profile.targets.join(", "));
+                        }
+                    }
+                    println!();
+                    helpers::tip("Use a profile with: xcargo build --profile <name>");
+                }
+            }
+        },
+
+        Commands::Version => {
+            println!("xcargo {}", env!("CARGO_PKG_VERSION"));
+            println!(
+                "Cross-compilation, zero friction{}",
+                terminal::symbol(" 🎯", "")
+            );
+            println!();
+            println!("https://github.com/ibrahimcesar/xcargo");
+        }
+
+        Commands::Plugins => {
+            helpers::section("xcargo plugins");
+
+            let plugins = discover_external();
+            if plugins.is_empty() {
+                helpers::info("No plugins found on PATH");
+                helpers::tip(format!(
+                    "Install an executable named {}<name> on PATH to add 'xcargo <name>'",
+                    EXTERNAL_PREFIX
+                ));
+                return Ok(());
+            }
+            let mut rows = vec![vec!["command".to_string(), "path".to_string()]];
+            rows.extend(plugins.iter().map(|plugin| {
+                vec![
+                    format!("xcargo {}", plugin.name),
+                    plugin.path.display().to_string(),
+                ]
+            }));
+            print_table(&rows);
+        }
+
+        Commands::External(args) => {
+            let name = &args[0];
+            let Some(plugin) = ExternalPlugin::find(name) else {
+                // Let clap report the unknown subcommand, with its suggestions
+                Cli::command()
+                    .allow_external_subcommands(false)
+                    .external_subcommand_value_parser(clap::builder::Resettable::Reset)
+                    .get_matches();
+                unreachable!("clap exits on unknown subcommands");
+            };
+            let context = ExternalContext::new(Config::load()?, cli.verbose > 0);
+            let code = plugin.run(&args[1..], &context)?;
+            std::process::exit(code);
+        }
+
+        Commands::Stats {
+            target,
+            since,
+            format,
+            clear,
+        } => {
+            let path = Path::new(STATS_FILE);
+            if clear {
+                if path.exists() {
+                    std::fs::remove_file(path)?;
+                }
+                helpers::success(format!("Cleared {STATS_FILE}"));
+                return Ok(());
+            }
+
+            let target = target.map(|t| Target::resolve_alias(&t)).transpose()?;
+            let since = since
+                .map(|since| parse_since(&since))
+                .transpose()?
+                .map(|since| {
+                    std::time::SystemTime::now()
+                        .checked_sub(since)
+                        .and_then(|start| start.duration_since(std::time::UNIX_EPOCH).ok())
+                        .map_or(0, |start| start.as_secs())
+                });
+            let records: Vec<BuildRecord> = load_records(path)
+                .into_iter()
+                .filter(|record| target.as_ref().map_or(true, |t| &record.target == t))
+                .filter(|record| since.map_or(true, |since| record.finished_at >= since))
+                .collect();
+            let stats = summarize(&records);
+
+            if format == OutputFormat::Json {
+                return print_json(&stats);
+            }
+            helpers::section("xcargo stats");
+            if stats.is_empty() {
+                helpers::info("No builds recorded yet");
+                helpers::tip(format!(
+                    "Builds are recorded in {STATS_FILE} as xcargo runs them"
+                ));
+                return Ok(());
+            }
+            helpers::line(render_stats(&stats));
+            helpers::line("");
+            helpers::info(format!(
+                "{} builds recorded in {STATS_FILE}; \"recent\" averages the last 5 successful builds",
+                records.len()
+            ));
+        }
+
+        Commands::Bloat {
             target,
-            all,
             release,
-            container,
-            zig,
-            no_zig,
-            toolchain,
-            cargo_args,
+            bin,
+            top,
+            format,
         } => {
-            let builder = Builder::new()?;
+            let config = load_config(None)?;
+            let mut targets = target
+                .iter()
+                .map(|t| Target::resolve_alias(t))
+                .collect::<Result<Vec<_>>>()?;
+            if targets.is_empty() {
+                targets = config.targets.default.clone();
+            }
+            if targets.is_empty() {
+                targets.push(Target::detect_host()?.triple);
+            }
+            let mut seen = std::collections::HashSet::new();
+            targets.retain(|t| seen.insert(t.clone()));
+
+            let profile = if release { "release" } else { "debug" };
+            let builder = Builder::with_config(config)?;
+            let mut reports = Vec::new();
+            let mut missing = Vec::new();
+            for target in &targets {
+                let mut found = false;
+                for path in builder.artifact_paths(target, profile) {
+                    let selected = bin.as_ref().map_or(true, |bin| {
+                        path.file_name().is_some_and(|name| name == bin.as_str())
+                            || path.file_stem().is_some_and(|stem| stem == bin.as_str())
+                    });
+                    if !selected {
+                        continue;
+                    }
+                    if let Some(report) = SizeReport::analyze(target, &path, top)? {
+                        reports.push(report);
+                        found = true;
+                    }
+                }
+                if !found {
+                    missing.push(target.clone());
+                }
+            }
+
+            if format == OutputFormat::Json {
+                return print_json(&reports);
+            }
+            helpers::section("xcargo bloat");
+            for target in &missing {
+                helpers::info(format!("Nothing built for {target} ({profile})"));
+            }
+            for (idx, report) in reports.iter().enumerate() {
+                if idx > 0 {
+                    helpers::line("");
+                }
+                helpers::line(report.render(top));
+            }
+            if reports.len() > 1 {
+                helpers::section("Size comparison");
+                helpers::line(render_size_comparison(&reports));
+            }
+            if !missing.is_empty() {
+                helpers::tip(format!(
+                    "Build them first: xcargo build --target {}{}",
+                    missing[0],
+                    if release { " --release" } else { "" }
+                ));
+                if reports.is_empty() {
+                    return Err(Error::Build("No built binaries to analyze".to_string()));
+                }
+            }
+        }
 
-            // Determine Zig preference: None = auto, Some(true) = force, Some(false) = disable
-            let use_zig = if zig {
-                Some(true)
-            } else if no_zig {
-                Some(false)
+        Commands::Deps {
+            action:
+                DepsAction::Audit {
+                    target,
+                    profile,
+                    format,
+                    cargo_args,
+                },
+        } => {
+            let config = load_config(profile.as_deref())?;
+            let targets = if target.is_empty() {
+                selected_targets(&config, None, true)?
             } else {
-                None
+                config.expand_targets(&target)?
             };
+            let cargo_args = network.with_cargo_args(cargo_args);
+            let audits = targets
+                .iter()
+                .map(|target| DependencyAudit::run(target, &cargo_args))
+                .collect::<Result<Vec<_>>>()?;
+            if format == OutputFormat::Json {
+                print_json(&audits)?;
+            } else {
+                helpers::section("xcargo deps audit");
+                for audit in &audits {
+                    print_dependency_audit(audit);
+                }
+            }
+        }
 
+        Commands::Msrv {
+            action:
+                MsrvAction::Verify {
+                    target,
+                    profile,
+                    zig,
+                    no_zig,
+                    selection,
+                    cargo_args,
+                },
+        } => {
+            let msrv = RustVersion::find(&std::env::current_dir()?)?.ok_or_else(|| {
+                Error::Config(
+                    "No rust-version in Cargo.toml; set package.rust-version to the oldest \
+                     Rust release the crate supports"
+                        .to_string(),
+                )
+            })?;
+            let config = load_config(profile.as_deref())?;
+            let targets = if target.is_empty() {
+                selected_targets(&config, None, true)?
+            } else {
+                config.expand_targets(&target)?
+            };
             let options = BuildOptions {
-                target: target.clone(),
-                release,
-                cargo_args,
-                toolchain,
-                verbose: cli.verbose,
-                use_container: container,
-                use_zig,
-                operation: CargoOperation::Build,
+                target: None,
+                release: false,
+                cargo_args: network.with_cargo_args(cargo_args),
+                toolchain: None,
+                verbose: cli.verbose > 0,
+                use_container: false,
+                use_zig: zig_preference(zig, no_zig),
+                operation: CargoOperation::Check,
+                affected_since: None,
+                assume_yes: cli.yes,
+                collect_tests: None,
+                attest: false,
+                selection: selection.into(),
+                fail_fast: None,
+                output: None,
+                lock: true,
             };
+            Builder::with_config(config)?.verify_msrv(&msrv, &targets, &options)?;
+        }
 
-            if all {
-                // Build for all configured targets
-                let config = Config::discover()?.map(|(c, _)| c).unwrap_or_default();
+        Commands::Cache { action } => match action {
+            CacheAction::Export {
+                tarball,
+                target,
+                debug,
+                toolchain,
+            } => {
+                helpers::section("xcargo cache export");
 
-                if config.targets.default.is_empty() {
-                    helpers::error("No default targets configured");
-                    helpers::hint("Add targets to xcargo.toml: [targets] default = [\"x86_64-unknown-linux-gnu\"]");
-                    helpers::tip(tips::CONFIG_FILE);
+                let mut targets = target
+                    .iter()
+                    .map(|t| Target::resolve_alias(t))
+                    .collect::<Result<Vec<_>>>()?;
+                if targets.is_empty() {
+                    targets = Config::load()?.targets.default;
+                }
+                targets.sort();
+                targets.dedup();
+                if targets.is_empty() {
+                    helpers::error("No targets to export");
+                    helpers::hint("Pass --target or configure default targets in xcargo.toml:");
+                    println!(
+                        "  xcargo cache export deps.tar.gz --target aarch64-unknown-linux-gnu"
+                    );
                     std::process::exit(1);
                 }
 
-                // Use parallel builds if enabled in config
-                if config.build.parallel {
-                    let rt = tokio::runtime::Runtime::new()?;
-                    rt.block_on(builder.build_all_parallel(&config.targets.default, &options))?;
-                } else {
-                    builder.build_all(&config.targets.default, &options)?;
-                }
+                let key = deps_cache_key(toolchain.as_deref())?;
+                let manifest = export_deps(&target_dir(), &targets, !debug, key, &tarball)?;
+                let size = std::fs::metadata(&tarball).map_or(0, |m| m.len());
+                helpers::success(format!(
+                    "Exported {} dependencies of {} to {} ({})",
+                    manifest.profile,
+                    manifest.targets.join(", "),
+                    tarball.display(),
+                    format_size(size)
+                ));
+                helpers::info(format!(
+                    "Key {}: {}",
+                    manifest.key.digest(),
+                    manifest.key.rustc
+                ));
+            }
+            CacheAction::Import {
+                tarball,
+                force,
+                toolchain,
+            } => {
+                helpers::section("xcargo cache import");
+
+                let manifest = read_manifest(&tarball)?;
+                let key = deps_cache_key(toolchain.as_deref())?;
+                if let Some(reason) = manifest.key.mismatch(&key) {
+                    if !force {
+                        return Err(Error::Build(format!(
+                            "{} cannot be reused: {reason}. Pass --force to import it anyway",
+                            tarball.display()
+                        )));
+                    }
+                    helpers::warning(format!("Importing anyway: {reason}"));
+                }
+
+                import_deps(&tarball, &target_dir())?;
+                helpers::success(format!(
+                    "Imported {} dependencies of {} from {}",
+                    manifest.profile,
+                    manifest.targets.join(", "),
+                    tarball.display()
+                ));
+                helpers::tip("Cargo now only rebuilds the workspace's own crates");
+            }
+            CacheAction::Key { toolchain } => {
+                println!("{}", deps_cache_key(toolchain.as_deref())?.digest());
+            }
+        },
+
+        Commands::PruneArtifacts {
+            keep_last,
+            max_size,
+            dry_run,
+        } => {
+            helpers::section("xcargo prune-artifacts");
+
+            let config = Config::load()?;
+            let mut policy = RetentionPolicy::from_config(&config.artifacts)?;
+            if keep_last == Some(0) {
+                return Err(Error::Config(
+                    "--keep-last must be greater than 0".to_string(),
+                ));
+            }
+            if keep_last.is_some() {
+                policy.keep_last_builds = keep_last;
+            }
+            if let Some(size) = &max_size {
+                policy.max_total_size = Some(parse_size(size)?);
+            }
+            if !policy.is_set() {
+                helpers::error("No retention limits set");
+                helpers::hint("Pass --keep-last or --max-size, or add them to xcargo.toml:");
+                println!("  [artifacts]");
+                println!("  keep_last_builds = 6");
+                println!("  max_total_size = \"20GiB\"");
+                std::process::exit(1);
+            }
+
+            let history_path = Path::new(HISTORY_FILE);
+            let mut history = BuildHistory::load(history_path);
+            let dirs = scan_artifact_dirs(&target_dir(), &history);
+            let (kept, removed) = policy.apply(dirs);
+
+            if removed.is_empty() {
+                helpers::success(format!(
+                    "Nothing to prune: {} target director{} within the limits",
+                    kept.len(),
+                    if kept.len() == 1 { "y is" } else { "ies are" }
+                ));
+                return Ok(());
+            }
+
+            let mut rows = vec![vec![
+                "target".to_string(),
+                "profile".to_string(),
+                "size".to_string(),
+            ]];
+            rows.extend(removed.iter().map(|dir| {
+                vec![
+                    dir.target.clone(),
+                    dir.profile.clone(),
+                    format_size(dir.size_bytes),
+                ]
+            }));
+            print_table(&rows);
+            println!();
+
+            let reclaimed = format_size(removed.iter().map(|dir| dir.size_bytes).sum());
+            let kept_size = format_size(kept.iter().map(|dir| dir.size_bytes).sum());
+            if dry_run {
+                helpers::info(format!(
+                    "Would remove {} director{} and reclaim {}; {} kept ({})",
+                    removed.len(),
+                    if removed.len() == 1 { "y" } else { "ies" },
+                    reclaimed,
+                    kept.len(),
+                    kept_size
+                ));
             } else {
-                builder.build(&options)?;
+                remove_artifact_dirs(&removed, &mut history)?;
+                if let Err(e) = history.save(history_path) {
+                    helpers::warning(format!("Could not update {}: {}", HISTORY_FILE, e));
+                }
+                helpers::success(format!(
+                    "Removed {} director{} and reclaimed {}; {} kept ({})",
+                    removed.len(),
+                    if removed.len() == 1 { "y" } else { "ies" },
+                    reclaimed,
+                    kept.len(),
+                    kept_size
+                ));
             }
         }
 
-        Commands::Check {
+        Commands::Install {
+            krate,
             target,
-            all,
+            version,
+            out_dir,
+            container,
             zig,
             no_zig,
             toolchain,
             cargo_args,
         } => {
-            let builder = Builder::new()?;
-
-            let use_zig = if zig {
-                Some(true)
-            } else if no_zig {
-                Some(false)
-            } else {
-                None
-            };
-
             let options = BuildOptions {
-                target: target.clone(),
-                release: false,
-                cargo_args,
+                target: Some(target),
+                release: true,
+                cargo_args: network.with_cargo_args(cargo_args),
                 toolchain,
-                verbose: cli.verbose,
-                use_container: false,
-                use_zig,
-                operation: CargoOperation::Check,
+                verbose: cli.verbose > 0,
+                use_container: container,
+                use_zig: zig_preference(zig, no_zig),
+                operation: CargoOperation::Install,
+                affected_since: None,
+                assume_yes: cli.yes,
+                collect_tests: None,
+                attest: false,
+                selection: ArtifactSelection::default(),
+                fail_fast: None,
+                output: None,
+                lock: true,
+            };
+            let request = InstallRequest {
+                krate,
+                version,
+                out_dir,
             };
 
-            if all {
-                let config = Config::discover()?.map(|(c, _)| c).unwrap_or_default();
-
-                if config.targets.default.is_empty() {
-                    helpers::error("No default targets configured");
-                    helpers::hint("Add targets to xcargo.toml: [targets] default = [\"x86_64-unknown-linux-gnu\"]");
-                    std::process::exit(1);
-                }
-
-                if config.build.parallel {
-                    let rt = tokio::runtime::Runtime::new()?;
-                    rt.block_on(builder.build_all_parallel(&config.targets.default, &options))?;
-                } else {
-                    builder.build_all(&config.targets.default, &options)?;
-                }
-            } else {
-                builder.build(&options)?;
-            }
+            Builder::with_config(Config::load()?)?.install(&request, &options)?;
         }
 
-        Commands::Test {
+        Commands::Deploy {
+            host,
             target,
-            all,
-            release,
+            path,
+            method,
+            service,
+            restart,
+            bin,
+            debug,
+            no_build,
             zig,
             no_zig,
-            toolchain,
-            cargo_args,
+            dry_run,
         } => {
-            let builder = Builder::new()?;
-
-            let use_zig = if zig {
-                Some(true)
-            } else if no_zig {
-                Some(false)
-            } else {
-                None
+            let config = Config::load()?;
+            let target = match target.or_else(|| config.deploy.default_target().map(String::from)) {
+                Some(target) => Some(Target::resolve_alias(&target)?),
+                None => config
+                    .expand_targets(&config.targets.default)?
+                    .into_iter()
+                    .next(),
+            };
+            let settings = config
+                .deploy
+                .for_target(target.as_deref().unwrap_or_default());
+            let Some(host) = host.or(settings.host) else {
+                helpers::error("No host to deploy to");
+                helpers::hint(
+                    "Pass --host, e.g. --host pi@192.168.1.10, or set host under [deploy] in xcargo.toml",
+                );
+                std::process::exit(1);
+            };
+            let request = DeployRequest {
+                host,
+                path: path.or(settings.path),
+                method: method.or(settings.method).unwrap_or_default(),
+                service: service.or(settings.service),
+                restart: restart.or(settings.restart),
+                bin,
+                build: !no_build,
+                dry_run,
+            };
+            let options = BuildOptions {
+                target,
+                release: !debug,
+                cargo_args: network.with_cargo_args(Vec::new()),
+                toolchain: None,
+                verbose: cli.verbose > 0,
+                use_container: false,
+                use_zig: zig_preference(zig, no_zig),
+                operation: CargoOperation::Build,
+                affected_since: None,
+                assume_yes: cli.yes,
+                collect_tests: None,
+                attest: false,
+                selection: ArtifactSelection::default(),
+                fail_fast: None,
+                output: None,
+                lock: true,
             };
 
+            Builder::with_config(config)?.deploy(&request, &options)?;
+        }
+
+        #[cfg(feature = "container")]
+        Commands::Package {
+            action:
+                PackageAction::Docker {
+                    target,
+                    repository,
+                    tags,
+                    base,
+                    bin,
+                    push,
+                    no_build,
+                    zig,
+                    no_zig,
+                },
+        } => {
+            use xcargo::build::ImagePackage;
+
+            let config = Config::load()?;
+            let target = match target {
+                Some(target) => Some(Target::resolve_alias(&target)?),
+                None => config
+                    .expand_targets(&config.targets.default)?
+                    .into_iter()
+                    .next(),
+            };
+            let mut package =
+                ImagePackage::from_config(&config.package.docker, Path::new("Cargo.toml"))?;
+            if let Some(repository) = repository {
+                package.repository = repository;
+            }
+            if !tags.is_empty() {
+                package.tags = tags;
+            }
+            package.base = base.or(package.base);
+            package.bin = bin.or(package.bin);
+            package.build = !no_build;
+            package.push = push;
             let options = BuildOptions {
-                target: target.clone(),
-                release,
-                cargo_args,
-                toolchain,
-                verbose: cli.verbose,
+                target,
+                release: true,
+                cargo_args: network.with_cargo_args(Vec::new()),
+                toolchain: None,
+                verbose: cli.verbose > 0,
                 use_container: false,
-                use_zig,
-                operation: CargoOperation::Test,
+                use_zig: zig_preference(zig, no_zig),
+                operation: CargoOperation::Build,
+                affected_since: None,
+                assume_yes: cli.yes,
+                collect_tests: None,
+                attest: false,
+                selection: ArtifactSelection::default(),
+                fail_fast: None,
+                output: None,
+                lock: true,
             };
 
-            if all {
-                let config = Config::discover()?.map(|(c, _)| c).unwrap_or_default();
+            Builder::with_config(config)?.package_image(&package, &options)?;
+        }
+
+        Commands::Clean {
+            target,
+            all,
+            zig,
+            caches,
+            dry_run,
+        } => {
+            helpers::section("xcargo clean");
 
+            let mut targets = target
+                .iter()
+                .map(|t| Target::resolve_alias(t))
+                .collect::<Result<Vec<_>>>()?;
+            if all {
+                let config = Config::load()?;
                 if config.targets.default.is_empty() {
-                    helpers::error("No default targets configured");
-                    helpers::hint("Add targets to xcargo.toml: [targets] default = [\"x86_64-unknown-linux-gnu\"]");
-                    std::process::exit(1);
+                    helpers::warning("No default targets configured in xcargo.toml");
                 }
+                targets.extend(config.targets.default);
+            }
+            targets.sort();
+            targets.dedup();
+            if targets.is_empty() && !zig && !caches {
+                helpers::error("Nothing selected to clean");
+                helpers::hint("Pass --target, --all, --zig, or --caches, for example:");
+                println!("  xcargo clean --target aarch64-unknown-linux-gnu");
+                println!("  xcargo clean --all --caches");
+                std::process::exit(1);
+            }
 
-                if config.build.parallel {
-                    let rt = tokio::runtime::Runtime::new()?;
-                    rt.block_on(builder.build_all_parallel(&config.targets.default, &options))?;
-                } else {
-                    builder.build_all(&config.targets.default, &options)?;
+            let target_dir = target_dir();
+            let mut items = target_clean_items(&target_dir, &targets);
+            for target in targets
+                .iter()
+                .filter(|t| !items.iter().any(|item| item.target.as_ref() == Some(*t)))
+            {
+                helpers::info(format!("Nothing built for {}", target));
+            }
+            let extra = [
+                (zig, "Zig wrappers", ZigToolchain::wrapper_cache_dir()),
+                (
+                    caches,
+                    "build cache",
+                    xcargo::cache::BuildCache::default_cache_dir().ok(),
+                ),
+            ];
+            for (label, dir) in extra
+                .into_iter()
+                .filter(|(selected, _, _)| *selected)
+                .map(|(_, label, dir)| (label, dir))
+            {
+                match dir.and_then(|dir| CleanItem::for_dir(label, dir)) {
+                    Some(item) => items.push(item),
+                    None => helpers::info(format!("No {} to remove", label)),
                 }
-            } else {
-                builder.build(&options)?;
             }
-        }
 
-        Commands::Target { action } => match action {
-            TargetAction::Add { target, toolchain } => {
-                helpers::section("Add Target");
+            if items.is_empty() {
+                helpers::success("Nothing to clean");
+                return Ok(());
+            }
 
-                let manager = ToolchainManager::new()?;
-                let target_triple = Target::resolve_alias(&target)?;
+            let mut rows = vec![vec![
+                "removes".to_string(),
+                "path".to_string(),
+                "size".to_string(),
+            ]];
+            rows.extend(items.iter().map(|item| {
+                vec![
+                    item.label.clone(),
+                    item.path.display().to_string(),
+                    format_size(item.size_bytes),
+                ]
+            }));
+            print_table(&rows);
+            println!();
 
-                helpers::progress(format!(
-                    "Adding target {} to toolchain {}...",
-                    target_triple, toolchain
-                ));
+            let reclaimed = format_size(items.iter().map(|item| item.size_bytes).sum());
+            let count = format!(
+                "{} director{}",
+                items.len(),
+                if items.len() == 1 { "y" } else { "ies" }
+            );
+            if dry_run {
+                helpers::info(format!("Would remove {} and reclaim {}", count, reclaimed));
+            } else {
+                let history_path = Path::new(HISTORY_FILE);
+                let mut history = BuildHistory::load(history_path);
+                remove_clean_items(&items, &mut history)?;
+                if let Err(e) = history.save(history_path) {
+                    helpers::warning(format!("Could not update {}: {}", HISTORY_FILE, e));
+                }
+                helpers::success(format!("Removed {} and reclaimed {}", count, reclaimed));
+            }
+        }
 
-                manager.install_target(&toolchain, &target_triple)?;
+        Commands::UpdateLock { targets } => {
+            helpers::section("xcargo update-lock");
 
-                helpers::success(format!("Target {} added successfully", target_triple));
-                helpers::tip(format!(
-                    "Use 'xcargo build --target {}' to build for this target",
-                    target_triple
+            let lock_path = lock_path();
+            let Some(mut lock) = LockFile::load(&lock_path)? else {
+                helpers::info(format!(
+                    "No {} yet; the first build of each target writes it",
+                    LOCK_FILE
                 ));
+                return Ok(());
+            };
+            for target in targets.iter().filter(|t| !lock.targets.contains_key(*t)) {
+                helpers::warning(format!("{} is not locked in {}", target, LOCK_FILE));
             }
 
-            TargetAction::List {
-                installed,
-                toolchain,
-            } => {
-                helpers::section("Available Targets");
-
-                if installed {
-                    let manager = ToolchainManager::new()?;
-                    let tc = toolchain.unwrap_or_else(|| "stable".to_string());
-
-                    helpers::info(format!("Installed targets for toolchain '{}':", tc));
-                    println!();
-
-                    match manager.list_targets(&tc) {
-                        Ok(targets) => {
-                            if targets.is_empty() {
-                                println!("  No targets installed");
-                            } else {
-                                for target in targets {
-                                    println!("  • {}", target);
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            helpers::error(format!("Failed to list targets: {}", e));
-                            std::process::exit(1);
-                        }
+            let config = Config::load()?;
+            let manager = ToolchainManager::new()?;
+            let mut changed = 0;
+            for (target, locked) in lock.targets.iter_mut() {
+                if !targets.is_empty() && !targets.contains(target) {
+                    continue;
+                }
+                let refreshed = refresh_locked_target(locked, &manager, &config)?;
+                match lock_changes(locked, &refreshed) {
+                    Some(changes) => {
+                        helpers::info(format!("{}: {}", target, changes));
+                        *locked = refreshed;
+                        changed += 1;
                     }
-                } else {
-                    println!("Common cross-compilation targets:\n");
-
-                    println!("Linux:");
-                    println!("  • x86_64-unknown-linux-gnu   (Linux x86_64)");
-                    println!("  • x86_64-unknown-linux-musl  (Linux x86_64, statically linked)");
-                    println!("  • aarch64-unknown-linux-gnu  (Linux ARM64)");
-                    println!();
-
-                    println!("Windows:");
-                    println!("  • x86_64-pc-windows-gnu      (Windows x86_64, MinGW)");
-                    println!("  • x86_64-pc-windows-msvc     (Windows x86_64, MSVC)");
-                    println!();
-
-                    println!("macOS:");
-                    println!("  • x86_64-apple-darwin        (macOS x86_64)");
-                    println!("  • aarch64-apple-darwin       (macOS ARM64, M1/M2)");
-                    println!();
-
-                    helpers::hint("Use 'xcargo target list --installed' to see installed targets");
-                    helpers::tip("Use 'xcargo target add <triple>' to install a new target");
+                    None => helpers::success(format!("{}: up to date", target)),
                 }
             }
 
-            TargetAction::Info { target } => {
-                helpers::section("Target Information");
+            if changed > 0 {
+                lock.save(&lock_path)?;
+                helpers::success(format!(
+                    "Updated {} target{} in {}",
+                    changed,
+                    if changed == 1 { "" } else { "s" },
+                    LOCK_FILE
+                ));
+            }
+        }
 
-                let target_triple = Target::resolve_alias(&target)?;
-                match Target::from_triple(&target_triple) {
-                    Ok(target) => {
-                        println!("Triple:       {}", target.triple);
-                        println!("Architecture: {}", target.arch);
-                        println!("OS:           {}", target.os);
-                        println!(
-                            "Environment:  {}",
-                            target.env.as_deref().unwrap_or("default")
-                        );
-                        println!("Tier:         {:?}", target.tier);
-                        println!();
+        Commands::Env {
+            target,
+            profile,
+            toolchain,
+            container,
+            zig,
+            no_zig,
+            format,
+        } => {
+            let options = BuildOptions {
+                toolchain,
+                use_container: container,
+                use_zig: zig_preference(zig, no_zig),
+                ..BuildOptions::default()
+            };
+            let plan = plan_single_target(target, profile.as_deref(), &options)?;
+            print_target_env(&plan, format)?;
+        }
 
-                        let requirements = target.get_requirements();
-                        if requirements.linker.is_some()
-                            || !requirements.tools.is_empty()
-                            || !requirements.system_libs.is_empty()
-                        {
-                            helpers::info("Requirements:");
-                            if let Some(linker) = requirements.linker {
-                                println!("  Linker: {}", linker);
-                            }
-                            if !requirements.tools.is_empty() {
-                                println!("  Tools: {}", requirements.tools.join(", "));
-                            }
-                            if !requirements.system_libs.is_empty() {
-                                println!("  System libs: {}", requirements.system_libs.join(", "));
-                            }
-                            println!();
-                        }
+        Commands::Ide { action } => match action {
+            IdeAction::Setup {
+                target,
+                profile,
+                stdout,
+            } => setup_ide(target, profile.as_deref(), stdout)?,
+        },
 
-                        let host = Target::detect_host()?;
-                        if target.can_cross_compile_from(&host) {
-                            helpers::success("Can cross-compile from this host");
-                        } else {
-                            helpers::warning("May require container for cross-compilation");
-                        }
+        Commands::Export { action } => match action {
+            ExportAction::Tasks {
+                format,
+                output,
+                stdout,
+                force,
+            } => {
+                let config = Config::load()?;
+                let contents = render_tasks(format, &task_matrix(&config.targets.default));
+                if stdout {
+                    print!("{}", contents);
+                    return Ok(());
+                }
 
-                        println!();
-                        helpers::tip(format!(
-                            "Add this target: xcargo target add {}",
-                            target.triple
-                        ));
-                        helpers::tip(format!(
-                            "Build for this target: xcargo build --target {}",
-                            target.triple
-                        ));
-                    }
-                    Err(e) => {
-                        helpers::error(format!("Invalid target: {}", e));
-                        std::process::exit(1);
-                    }
+                helpers::section("xcargo export tasks");
+                let path = output.unwrap_or_else(|| PathBuf::from(format.default_file()));
+                if path.exists() && !force {
+                    helpers::error(format!("{} already exists", path.display()));
+                    helpers::hint("Use --force to overwrite it, or --stdout to print the tasks");
+                    std::process::exit(1);
+                }
+                if config.targets.default.is_empty() {
+                    helpers::warning(
+                        "No default targets configured; only the --all tasks were generated",
+                    );
                 }
+                std::fs::write(&path, contents)?;
+                helpers::success(format!(
+                    "Wrote {} tasks for {} target{} to {}",
+                    format.as_str(),
+                    config.targets.default.len(),
+                    if config.targets.default.len() == 1 {
+                        ""
+                    } else {
+                        "s"
+                    },
+                    path.display()
+                ));
             }
         },
 
-        Commands::Init { interactive } => {
-            if interactive {
-                run_interactive_setup()?;
-            } else {
-                run_basic_setup()?;
-            }
-        }
+        Commands::BisectTarget {
+            target,
+            good,
+            bad,
+            zig,
+            no_zig,
+            toolchain,
+            cargo_args,
+        } => {
+            let target = Target::resolve_alias(&target)?;
+            let options = BuildOptions {
+                cargo_args: network.with_cargo_args(cargo_args),
+                toolchain,
+                verbose: cli.verbose > 0,
+                use_zig: zig_preference(zig, no_zig),
+                operation: CargoOperation::Check,
+                assume_yes: cli.yes,
+                ..Default::default()
+            };
 
-        Commands::Config { default } => {
-            helpers::section("Configuration");
+            helpers::section(format!("xcargo bisect-target {}", target));
+            helpers::info(format!(
+                "Searching {}..{} for the first commit that breaks {}",
+                good, bad, target
+            ));
+            helpers::info("Commits that don't build for the host are skipped");
+            println!();
 
-            if default {
-                let config = Config::default();
-                match config.to_toml() {
-                    Ok(toml) => {
-                        println!("{}", toml);
-                        println!();
-                        helpers::tip("Save this to xcargo.toml to customize your build");
-                    }
-                    Err(e) => {
-                        helpers::error(format!("Failed to generate config: {}", e));
-                        std::process::exit(1);
-                    }
+            let xcargo = std::env::current_exe()?.to_string_lossy().to_string();
+            let mut check_command = vec![xcargo];
+            check_command.extend(bisect_check_args(&target, &options));
+
+            println!();
+            match bisect(Path::new("."), &good, &bad, &check_command)? {
+                Some(commit) => {
+                    helpers::success(format!("First commit that breaks {}:", target));
+                    println!("  {} {}", commit.hash, commit.summary);
                 }
-            } else {
-                match Config::discover() {
-                    Ok(Some((config, path))) => {
-                        helpers::info(format!("Configuration from: {}", path.display()));
-                        println!();
-                        match config.to_toml() {
-                            Ok(toml) => println!("{}", toml),
-                            Err(e) => {
-                                helpers::error(format!("Failed to serialize config: {}", e));
-                                std::process::exit(1);
-                            }
-                        }
-                    }
-                    Ok(None) => {
-                        helpers::info("No xcargo.toml found, using defaults");
-                        println!();
-                        let config = Config::default();
-                        match config.to_toml() {
-                            Ok(toml) => println!("{}", toml),
-                            Err(e) => {
-                                helpers::error(format!("Failed to generate config: {}", e));
-                                std::process::exit(1);
-                            }
-                        }
-                        println!();
-                        helpers::tip(tips::CONFIG_FILE);
-                    }
-                    Err(e) => {
-                        helpers::error(format!("Failed to load config: {}", e));
-                        std::process::exit(1);
-                    }
+                None => {
+                    helpers::warning("Could not narrow the breakage down to one commit");
+                    helpers::hint(
+                        "The candidates could not be checked because they don't build for the host",
+                    );
                 }
             }
         }
 
-        Commands::Doctor => {
-            xcargo::doctor::run()?;
+        Commands::BisectCheck {
+            target,
+            zig,
+            no_zig,
+            toolchain,
+            cargo_args,
+        } => {
+            let options = BuildOptions {
+                cargo_args: network.with_cargo_args(cargo_args),
+                toolchain,
+                verbose: cli.verbose > 0,
+                use_zig: zig_preference(zig, no_zig),
+                assume_yes: cli.yes,
+                ..Default::default()
+            };
+            let verdict = check_revision(&target, &options);
+            std::process::exit(verdict.exit_code());
         }
 
-        Commands::Version => {
-            println!("xcargo {}", env!("CARGO_PKG_VERSION"));
-            println!("Cross-compilation, zero friction 🎯");
-            println!();
-            println!("https://github.com/ibrahimcesar/xcargo");
+        Commands::RunTest {
+            runner_len,
+            timeout,
+            retries,
+            command,
+        } => {
+            let policy = RunnerPolicy {
+                timeout: timeout.as_deref().map(parse_duration).transpose()?,
+                retries,
+            };
+            let events = std::env::var_os(RUNNER_EVENTS_ENV).map(std::path::PathBuf::from);
+            let code = run_with_policy(&command, runner_len, &policy, events.as_deref())?;
+            std::process::exit(code);
         }
     }
 
@@ -0,0 +1,238 @@
+//! Translated messages
+//!
+//! Messages are looked up by their English text, so call sites stay
+//! readable and a message without a translation is shown in English. The
+//! language is chosen with `XCARGO_LANG`, `[output] language` in
+//! `xcargo.toml`, or the system locale (`LC_ALL`, `LC_MESSAGES`, `LANG`),
+//! in that order of precedence.
+//!
+//! Placeholders are written `{name}` and filled in with [`trf`]; a
+//! translation must use the same placeholders as its English text.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+mod pt_br;
+
+/// Environment variable selecting the output language
+pub const LANG_ENV: &str = "XCARGO_LANG";
+
+/// Language of xcargo's messages
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Locale {
+    /// English, the language messages are written in
+    #[default]
+    #[serde(rename = "en")]
+    En,
+    /// Brazilian Portuguese
+    #[serde(rename = "pt-BR", alias = "pt_BR", alias = "pt")]
+    PtBr,
+}
+
+impl Locale {
+    /// All locales, in display order
+    pub const ALL: [Locale; 2] = [Self::En, Self::PtBr];
+
+    /// Name used in `xcargo.toml` and `XCARGO_LANG`
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::En => "en",
+            Self::PtBr => "pt-BR",
+        }
+    }
+
+    /// Translations from English, empty for English itself
+    fn catalog(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Self::En => &[],
+            Self::PtBr => pt_br::CATALOG,
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    /// Parse a language tag such as `pt-BR`, or a POSIX locale such as
+    /// `pt_BR.UTF-8`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tag = s.trim().split(['.', '@']).next().unwrap_or_default();
+        let language = tag.split(['-', '_']).next().unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "en" | "c" | "posix" => Ok(Self::En),
+            // Portugal's Portuguese is closer to pt-BR than to English
+            "pt" => Ok(Self::PtBr),
+            _ => Err(format!(
+                "Unknown language '{}' (expected one of: {})",
+                s.trim(),
+                Locale::ALL.map(|l| l.as_str()).join(", ")
+            )),
+        }
+    }
+}
+
+/// Locale in use, stored as its index in [`Locale::ALL`]; `UNSET` until
+/// [`init`] or [`set_locale`] runs
+static ACTIVE: AtomicU8 = AtomicU8::new(UNSET);
+const UNSET: u8 = u8::MAX;
+
+/// Variables holding the system locale, in POSIX order of precedence
+const SYSTEM_LOCALE_VARS: [&str; 3] = ["LC_ALL", "LC_MESSAGES", "LANG"];
+
+/// Select the language from `XCARGO_LANG`, falling back to `configured`
+/// and then to the system locale
+///
+/// Returns an error message if `XCARGO_LANG` names an unknown language; the
+/// next choice is used in that case. A system locale without a translation
+/// means English.
+pub fn init(configured: Option<Locale>) -> std::result::Result<Locale, String> {
+    let (requested, result) = match std::env::var(LANG_ENV) {
+        Ok(name) if !name.trim().is_empty() => match name.parse::<Locale>() {
+            Ok(locale) => (Some(locale), Ok(())),
+            Err(e) => (None, Err(format!("{LANG_ENV}: {e}"))),
+        },
+        _ => (None, Ok(())),
+    };
+    let locale = requested.or(configured).unwrap_or_else(system_locale);
+    set_locale(locale);
+    result.map(|()| locale)
+}
+
+/// The locale of the environment, English if it has no translation
+fn system_locale() -> Locale {
+    SYSTEM_LOCALE_VARS
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Use `locale` for all further messages
+pub fn set_locale(locale: Locale) {
+    let index = Locale::ALL.iter().position(|l| *l == locale).unwrap_or(0);
+    ACTIVE.store(u8::try_from(index).unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Locale in use
+///
+/// Before [`init`] runs, this is the language named by `XCARGO_LANG`, or
+/// English.
+#[must_use]
+pub fn current() -> Locale {
+    match ACTIVE.load(Ordering::Relaxed) {
+        UNSET => std::env::var(LANG_ENV)
+            .ok()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or_default(),
+        index => Locale::ALL
+            .get(usize::from(index))
+            .copied()
+            .unwrap_or_default(),
+    }
+}
+
+/// `message` in the current language, or as it is without a translation
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::output::i18n::tr;
+///
+/// // Untranslated messages are shown in English
+/// assert_eq!(tr("Building for 3 targets"), "Building for 3 targets");
+/// ```
+#[must_use]
+pub fn tr(message: &str) -> &str {
+    lookup(current(), message)
+}
+
+/// `message` in the current language with its `{name}` placeholders
+/// replaced by `args`
+#[must_use]
+pub fn trf(message: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    fill(tr(message), args)
+}
+
+fn lookup(locale: Locale, message: &str) -> &str {
+    locale
+        .catalog()
+        .iter()
+        .find(|(english, _)| *english == message)
+        .map_or(message, |(_, translated)| translated)
+}
+
+fn fill(template: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    /// Names of the `{name}` placeholders in a message
+    fn placeholders(message: &str) -> BTreeSet<&str> {
+        message
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}'))
+            .map(|(name, _)| name)
+            .filter(|name| name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!("pt_BR.UTF-8".parse::<Locale>(), Ok(Locale::PtBr));
+        assert_eq!("pt-PT".parse::<Locale>(), Ok(Locale::PtBr));
+        assert_eq!("en_US.UTF-8".parse::<Locale>(), Ok(Locale::En));
+        assert_eq!("C".parse::<Locale>(), Ok(Locale::En));
+        assert!("de_DE".parse::<Locale>().unwrap_err().contains("pt-BR"));
+
+        for locale in Locale::ALL {
+            assert_eq!(locale.as_str().parse::<Locale>(), Ok(locale));
+        }
+    }
+
+    #[test]
+    fn test_catalogs_match_english() {
+        for locale in Locale::ALL {
+            let mut seen = BTreeSet::new();
+            for (english, translated) in locale.catalog() {
+                assert!(seen.insert(english), "{locale}: duplicate '{english}'");
+                assert_eq!(
+                    placeholders(english),
+                    placeholders(translated),
+                    "{locale}: placeholders of '{english}'"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let message = "Did you mean: {suggestions}?";
+        assert_eq!(lookup(Locale::En, message), message);
+        let translated = lookup(Locale::PtBr, message);
+        assert_eq!(
+            fill(translated, &[("suggestions", &"x86_64-pc-windows-gnu")]),
+            "Você quis dizer: x86_64-pc-windows-gnu?"
+        );
+        assert_eq!(
+            lookup(Locale::PtBr, "not in the catalog"),
+            "not in the catalog"
+        );
+    }
+}
@@ -0,0 +1,152 @@
+//! Brazilian Portuguese messages
+
+/// Translations keyed by the English message
+pub(super) const CATALOG: &[(&str, &str)] = &[
+    // Message labels in plain output
+    ("Success", "Sucesso"),
+    ("Error", "Erro"),
+    ("Warning", "Aviso"),
+    ("Info", "Info"),
+    ("Tip", "Dica"),
+    ("Hint", "Nota"),
+    ("Progress", "Progresso"),
+    // Progress of each target
+    ("Building", "Compilando"),
+    ("Compiling", "Compilando"),
+    ("Checking", "Verificando"),
+    ("Testing", "Testando"),
+    ("Benchmarking", "Medindo"),
+    ("Documenting", "Documentando"),
+    ("Installing", "Instalando"),
+    ("Started {operation} {target}.", "Iniciado: {operation} {target}."),
+    (
+        "Finished {operation} {target} in {duration}.",
+        "Concluído: {operation} {target} em {duration}.",
+    ),
+    (
+        "Failed {operation} {target} after {duration}: {error}.",
+        "Falhou: {operation} {target} após {duration}: {error}.",
+    ),
+    (
+        "All {count} targets completed in {duration}",
+        "Todos os {count} targets concluídos em {duration}",
+    ),
+    (
+        "{succeeded} succeeded, {failed} failed in {duration}",
+        "{succeeded} com sucesso, {failed} com falha em {duration}",
+    ),
+    ("{label} completed in {duration}", "{label} concluído em {duration}"),
+    (
+        "Received interrupt signal (Ctrl+C)",
+        "Sinal de interrupção recebido (Ctrl+C)",
+    ),
+    (
+        "Cleaning up and shutting down gracefully...",
+        "Limpando e encerrando com segurança...",
+    ),
+    // Error suggestions and hints
+    (
+        "Run 'xcargo target list' to see available targets",
+        "Execute 'xcargo target list' para ver os targets disponíveis",
+    ),
+    (
+        "Use 'xcargo target list' to see available targets",
+        "Use 'xcargo target list' para ver os targets disponíveis",
+    ),
+    ("Did you mean: {suggestions}?", "Você quis dizer: {suggestions}?"),
+    (
+        "Check {path} for syntax errors",
+        "Verifique se há erros de sintaxe em {path}",
+    ),
+    (
+        "Run 'xcargo profile list' to see available profiles",
+        "Execute 'xcargo profile list' para ver os perfis disponíveis",
+    ),
+    ("Re-run with {flag}", "Execute novamente com {flag}"),
+    (
+        "Upgrade with: cargo install xcargo --locked",
+        "Atualize com: cargo install xcargo --locked",
+    ),
+    (
+        "Raise build.timeout, or set targets.\"{target}\".timeout for this target",
+        "Aumente build.timeout ou defina targets.\"{target}\".timeout para este target",
+    ),
+    (
+        "Cross-compiling to {target} requires a compatible linker",
+        "A compilação cruzada para {target} requer um linker compatível",
+    ),
+    ("Cargo exited with code {code}", "O cargo terminou com o código {code}"),
+    (
+        "The artifacts of the {succeeded} target(s) that built are in place",
+        "Os artefatos do(s) {succeeded} target(s) compilado(s) estão disponíveis",
+    ),
+    (
+        "Tried to use {runtime} but it's not running",
+        "Tentamos usar {runtime}, mas ele não está em execução",
+    ),
+    (
+        "Build policies are set in the [policy] section of xcargo.toml",
+        "As políticas de build ficam na seção [policy] do xcargo.toml",
+    ),
+    (
+        "Profiles are defined in [profiles.<name>] sections of xcargo.toml",
+        "Os perfis são definidos nas seções [profiles.<nome>] do xcargo.toml",
+    ),
+    (
+        "This xcargo implements plugin API v{version}",
+        "Este xcargo implementa a API de plugins v{version}",
+    ),
+    (
+        "{path} sets required_version because the project relies on newer xcargo features",
+        "{path} define required_version porque o projeto depende de recursos mais novos do xcargo",
+    ),
+    ("Consider using Zig: {command}", "Considere usar o Zig: {command}"),
+    (
+        "{command}  # or your distro's package manager",
+        "{command}  # ou o gerenciador de pacotes da sua distribuição",
+    ),
+    (
+        "macOS cross-compilation requires osxcross: {url}",
+        "A compilação cruzada para macOS requer o osxcross: {url}",
+    ),
+    (
+        "Install a linker that supports {target}",
+        "Instale um linker com suporte a {target}",
+    ),
+    ("Install Docker Desktop: {url}", "Instale o Docker Desktop: {url}"),
+    ("Install Docker: {command}", "Instale o Docker: {command}"),
+    ("Or Podman: {command}", "Ou o Podman: {command}"),
+    (
+        "Install {runtime} or a compatible container runtime",
+        "Instale o {runtime} ou um runtime de contêiner compatível",
+    ),
+    // Missing system packages
+    (
+        "{missing} is missing: sudo apt install {apt}  # or: sudo dnf install {dnf}",
+        "{missing} não encontrado: sudo apt install {apt}  # ou: sudo dnf install {dnf}",
+    ),
+    (
+        "{missing} for {target} is missing: sudo dpkg --add-architecture {arch} && sudo apt update && sudo apt install {apt}:{arch}",
+        "{missing} para {target} não encontrado: sudo dpkg --add-architecture {arch} && sudo apt update && sudo apt install {apt}:{arch}",
+    ),
+    (
+        "{missing} is missing: brew install {brew}",
+        "{missing} não encontrado: brew install {brew}",
+    ),
+    (
+        "{missing} is missing; install its development files",
+        "{missing} não encontrado; instale os arquivos de desenvolvimento",
+    ),
+    (
+        "{missing} for {target} is missing; build in a container with --container, where the target's packages can be installed",
+        "{missing} para {target} não encontrado; compile em um contêiner com --container, onde os pacotes do target podem ser instalados",
+    ),
+    (
+        "Or build OpenSSL from source with the openssl crate's \"vendored\" feature",
+        "Ou compile o OpenSSL a partir do código-fonte com a feature \"vendored\" do crate openssl",
+    ),
+    (
+        "Or bundle SQLite with rusqlite's \"bundled\" feature",
+        "Ou embuta o SQLite com a feature \"bundled\" do rusqlite",
+    ),
+];
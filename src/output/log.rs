@@ -0,0 +1,321 @@
+//! Verbosity levels and the log file
+//!
+//! At the default level xcargo prints its usual output. `-q` keeps only
+//! warnings and errors, `-v` adds details such as every command xcargo runs,
+//! and `-vv` adds the environment those commands run with. With
+//! `--log-file`, every message and command is also appended to a file with a
+//! timestamp, whatever the level, so a failed CI run can be looked at later.
+
+use crate::error::Result;
+use std::ffi::OsStr;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::theme;
+
+/// How much xcargo prints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Verbosity {
+    /// Only warnings and errors (`-q`)
+    Quiet,
+    /// xcargo's usual output
+    #[default]
+    Normal,
+    /// Details, and each command xcargo runs (`-v`)
+    Verbose,
+    /// Commands with their environment and working directory (`-vv`)
+    Debug,
+}
+
+impl Verbosity {
+    /// Level for `-q` and the number of `-v` flags
+    #[must_use]
+    pub fn from_flags(quiet: bool, verbose: u8) -> Self {
+        match (quiet, verbose) {
+            (true, _) => Self::Quiet,
+            (false, 0) => Self::Normal,
+            (false, 1) => Self::Verbose,
+            (false, _) => Self::Debug,
+        }
+    }
+}
+
+/// Level in use; see [`set_verbosity`]
+static VERBOSITY: AtomicU8 = AtomicU8::new(Verbosity::Normal as u8);
+
+/// File every message and command is appended to; see [`open`]
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+/// Set how much xcargo prints
+pub fn set_verbosity(verbosity: Verbosity) {
+    VERBOSITY.store(verbosity as u8, Ordering::Relaxed);
+}
+
+/// Level in use
+#[must_use]
+pub fn verbosity() -> Verbosity {
+    match VERBOSITY.load(Ordering::Relaxed) {
+        0 => Verbosity::Quiet,
+        2 => Verbosity::Verbose,
+        3 => Verbosity::Debug,
+        _ => Verbosity::Normal,
+    }
+}
+
+/// Whether output meant for `level` is printed
+#[must_use]
+pub fn enabled(level: Verbosity) -> bool {
+    verbosity() >= level
+}
+
+/// Append every message and command from now on to `path`
+///
+/// The file is created if needed. Each run starts with a line giving xcargo's
+/// version, its arguments, and the working directory.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened for appending.
+pub fn open(path: &Path) -> Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    *LOG_FILE
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(file);
+
+    let args: Vec<String> = std::env::args()
+        .map(|arg| quote(OsStr::new(&arg)))
+        .collect();
+    write(
+        "Start",
+        &format!("xcargo {} {}", env!("CARGO_PKG_VERSION"), args.join(" ")),
+    );
+    if let Ok(dir) = std::env::current_dir() {
+        write("Start", &format!("in {}", dir.display()));
+    }
+    Ok(())
+}
+
+/// Append `message` to the log file, if one is open
+///
+/// Lines after the first are indented under the first.
+pub fn write(label: &str, message: &str) {
+    let mut file = LOG_FILE
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    let Some(file) = file.as_mut() else {
+        return;
+    };
+    // Past the timestamp and the label
+    let message = message.replace('\n', &format!("\n{:34}", ""));
+    // Logging must never fail the build
+    let _ = writeln!(
+        file,
+        "{} {label:<8} {message}",
+        timestamp(SystemTime::now())
+    );
+}
+
+/// Record a command about to run
+///
+/// The command line is printed with `-v` and always written to the log file,
+/// with the environment variables it sets and its working directory; `-vv`
+/// prints those too.
+pub fn command(cmd: &Command) {
+    let line = redacted_command_line(cmd);
+    let mut details: Vec<String> = cmd
+        .get_envs()
+        .map(|(key, value)| match value {
+            Some(_) if is_secret(&key.to_string_lossy()) => {
+                format!("{}={REDACTED}", key.to_string_lossy())
+            }
+            Some(value) => format!("{}={}", key.to_string_lossy(), quote(value)),
+            None => format!("unset {}", key.to_string_lossy()),
+        })
+        .collect();
+    if let Some(dir) = cmd.get_current_dir() {
+        details.push(format!("in {}", dir.display()));
+    }
+
+    write("Command", &line);
+    for detail in &details {
+        write("Env", detail);
+    }
+
+    if !enabled(Verbosity::Verbose) {
+        return;
+    }
+    let show = |text: String| println!("{}", theme::paint(theme::palette().dim, text));
+    show(format!("$ {line}"));
+    if enabled(Verbosity::Debug) {
+        for detail in details {
+            show(format!("  {detail}"));
+        }
+    }
+}
+
+/// Shown instead of the value of a secret
+const REDACTED: &str = "<redacted>";
+
+/// Whether an environment variable holds a secret, such as
+/// `CARGO_REGISTRIES_MY_REGISTRY_TOKEN`
+#[must_use]
+pub fn is_secret(name: &str) -> bool {
+    let name = name.to_ascii_uppercase();
+    ["TOKEN", "PASSWORD", "SECRET"]
+        .iter()
+        .any(|word| name.contains(word))
+}
+
+/// [`command_line`] with the values of secret `NAME=value` arguments, and
+/// of password options such as `--password <value>`, replaced
+fn redacted_command_line(cmd: &Command) -> String {
+    let mut after_secret_flag = false;
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(|arg| {
+            let text = arg.to_string_lossy();
+            let redact = std::mem::replace(&mut after_secret_flag, is_secret_flag(&text));
+            match text.split_once('=') {
+                _ if redact => REDACTED.to_string(),
+                Some((name, _)) if is_secret(name) => {
+                    format!("{}={REDACTED}", quote(name.as_ref()))
+                }
+                _ => quote(arg),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether an option's value is a secret: `--password`, `--token`, or the
+/// password options of signtool (`/p`) and osslsigncode (`-pass`)
+fn is_secret_flag(arg: &str) -> bool {
+    match arg {
+        "/p" | "-pass" => true,
+        _ => arg.starts_with("--") && !arg.contains('=') && is_secret(arg),
+    }
+}
+
+/// `cmd` as it would be typed in a shell
+#[must_use]
+pub fn command_line(cmd: &Command) -> String {
+    std::iter::once(cmd.get_program())
+        .chain(cmd.get_args())
+        .map(quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `arg` in single quotes if a shell would split or expand it
+pub(crate) fn quote(arg: &OsStr) -> String {
+    let arg = arg.to_string_lossy();
+    let plain = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c));
+    if plain {
+        arg.into_owned()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+/// `time` in UTC as RFC 3339 with milliseconds, e.g. `2024-05-01T09:30:00.250Z`
+fn timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = i64::try_from(days).unwrap_or(0) + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_from_flags() {
+        assert_eq!(Verbosity::from_flags(false, 0), Verbosity::Normal);
+        assert_eq!(Verbosity::from_flags(false, 1), Verbosity::Verbose);
+        assert_eq!(Verbosity::from_flags(false, 4), Verbosity::Debug);
+        assert_eq!(Verbosity::from_flags(true, 0), Verbosity::Quiet);
+        assert!(Verbosity::Debug > Verbosity::Verbose);
+    }
+
+    #[test]
+    fn test_timestamp() {
+        assert_eq!(timestamp(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        let leap_day = UNIX_EPOCH + Duration::from_millis(1_709_210_096_250);
+        assert_eq!(timestamp(leap_day), "2024-02-29T12:34:56.250Z");
+        let new_year = UNIX_EPOCH + Duration::from_secs(946_684_799);
+        assert_eq!(timestamp(new_year), "1999-12-31T23:59:59.000Z");
+    }
+
+    #[test]
+    fn test_redact_secrets() {
+        assert!(is_secret("CARGO_REGISTRIES_ACME_TOKEN"));
+        assert!(is_secret("xcargo_registry_password"));
+        assert!(!is_secret("CARGO_REGISTRIES_ACME_INDEX"));
+
+        let mut cmd = Command::new("docker");
+        cmd.args([
+            "run",
+            "-e",
+            "CARGO_REGISTRY_TOKEN=a b",
+            "-e",
+            "RUSTFLAGS=-g",
+            "image",
+        ]);
+        assert_eq!(
+            redacted_command_line(&cmd),
+            "docker run -e CARGO_REGISTRY_TOKEN=<redacted> -e RUSTFLAGS=-g image"
+        );
+
+        let mut cmd = Command::new("xcrun");
+        cmd.args(["notarytool", "submit", "--password", "hunter2", "app.zip"]);
+        assert_eq!(
+            redacted_command_line(&cmd),
+            "xcrun notarytool submit --password <redacted> app.zip"
+        );
+    }
+
+    #[test]
+    fn test_command_line() {
+        let mut cmd = Command::new("cargo");
+        cmd.args(["build", "--target", "x86_64-pc-windows-gnu"])
+            .arg("--config=build.rustflags=['-C', 'lto']")
+            .arg("");
+        assert_eq!(
+            command_line(&cmd),
+            r"cargo build --target x86_64-pc-windows-gnu '--config=build.rustflags=['\''-C'\'', '\''lto'\'']' ''"
+        );
+    }
+}
@@ -4,9 +4,56 @@
 //! and progress to users in a delightful and informative way.
 
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use log::Verbosity;
 use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
+pub mod i18n;
+pub mod log;
+pub mod table;
+pub mod terminal;
+pub mod theme;
+
+/// Whether plain output is on; see [`set_plain`]
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Switch to plain output for screen readers and simple log processors
+///
+/// Plain output has no colors, icons, box drawing, or spinners. Every message
+/// is a complete line, labeled where the icon would have carried its meaning,
+/// and progress is reported as one line when a target starts and one when it
+/// finishes.
+pub fn set_plain(plain: bool) {
+    PLAIN.store(plain, Ordering::Relaxed);
+}
+
+/// Whether plain output is on
+#[must_use]
+pub fn is_plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
+
+/// Whether messages go to stderr; see [`set_messages_to_stderr`]
+static MESSAGES_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Print messages, section headers, and output lines to stderr
+///
+/// Used when stdout carries machine-readable output, such as cargo's JSON
+/// messages read by rust-analyzer.
+pub fn set_messages_to_stderr(on: bool) {
+    MESSAGES_TO_STDERR.store(on, Ordering::Relaxed);
+}
+
+/// Print a line to stdout, or to stderr after [`set_messages_to_stderr`]
+fn print_line(text: impl fmt::Display) {
+    if MESSAGES_TO_STDERR.load(Ordering::Relaxed) {
+        eprintln!("{text}");
+    } else {
+        println!("{text}");
+    }
+}
+
 /// Color codes for terminal output
 pub mod colors {
     /// Reset to default color
@@ -64,17 +111,39 @@ impl MessageType {
         }
     }
 
-    /// Get the color for this message type
+    /// Get the icon for this message type on terminals without Unicode
+    #[must_use]
+    pub fn ascii_icon(&self) -> &'static str {
+        match self {
+            Self::Success => "+",
+            Self::Error => "x",
+            Self::Warning => "!",
+            Self::Info => "i",
+            Self::Tip => "*",
+            Self::Hint => "->",
+            Self::Progress => ">",
+        }
+    }
+
+    /// Get the icon the terminal can show: [`icon`](Self::icon), or
+    /// [`ascii_icon`](Self::ascii_icon) if it cannot show Unicode
+    #[must_use]
+    pub fn symbol(&self) -> &'static str {
+        terminal::symbol(self.icon(), self.ascii_icon())
+    }
+
+    /// Get the color for this message type in the active theme
     #[must_use]
     pub fn color(&self) -> &'static str {
+        let palette = theme::palette();
         match self {
-            Self::Success => colors::GREEN,
-            Self::Error => colors::RED,
-            Self::Warning => colors::YELLOW,
-            Self::Info => colors::BLUE,
-            Self::Tip => colors::CYAN,
-            Self::Hint => colors::CYAN,
-            Self::Progress => colors::BLUE,
+            Self::Success => palette.success,
+            Self::Error => palette.error,
+            Self::Warning => palette.warning,
+            Self::Info => palette.info,
+            Self::Tip => palette.tip,
+            Self::Hint => palette.hint,
+            Self::Progress => palette.progress,
         }
     }
 
@@ -145,30 +214,40 @@ impl Message {
         Self::new(MessageType::Progress, content)
     }
 
-    /// Print the message to stdout
+    /// Print the message to stdout, unless `-q` hides it, and record it in
+    /// the log file
     pub fn print(&self) {
-        println!("{self}");
+        log::write(self.msg_type.label(), &self.content);
+        let shown = match self.msg_type {
+            MessageType::Error | MessageType::Warning => true,
+            _ => log::enabled(Verbosity::Normal),
+        };
+        if shown {
+            print_line(self);
+        }
     }
 }
 
 impl fmt::Display for Message {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "{}{}{} {}{}{}",
-            colors::BOLD,
-            self.msg_type.color(),
-            self.msg_type.icon(),
-            colors::RESET,
-            self.content,
-            colors::RESET
-        )
+        if is_plain() {
+            return match self.msg_type {
+                MessageType::Success | MessageType::Info | MessageType::Progress => {
+                    f.write_str(&self.content)
+                }
+                _ => write!(f, "{}: {}", i18n::tr(self.msg_type.label()), self.content),
+            };
+        }
+        let icon = theme::paint(self.msg_type.color(), self.msg_type.symbol());
+        write!(f, "{} {}", theme::paint(colors::BOLD, icon), self.content)
     }
 }
 
 /// Helper functions for common output patterns
 pub mod helpers {
-    use super::{colors, Message};
+    use super::log::{self, Verbosity};
+    use super::{colors, terminal, theme, Message};
+    use std::fmt::Display;
 
     /// Print a success message
     pub fn success(message: impl Into<String>) {
@@ -205,51 +284,158 @@ pub mod helpers {
         Message::progress(message).print();
     }
 
+    /// Print a line of output as it is, unless `-q` hides it
+    ///
+    /// Use this instead of `println!` for output other than messages, such as
+    /// tables and configuration snippets, so it is quiet with `-q` and ends up
+    /// in the log file. An empty line is only printed.
+    pub fn line(text: impl Display) {
+        let text = text.to_string();
+        if !text.trim().is_empty() {
+            log::write("Output", &text);
+        }
+        if log::enabled(Verbosity::Normal) {
+            super::print_line(text);
+        }
+    }
+
     /// Print a section header
     pub fn section(title: impl Into<String>) {
         let title = title.into();
-        println!(
-            "\n{}{}{}{}",
-            colors::BOLD,
-            colors::CYAN,
-            title,
-            colors::RESET
-        );
-        println!("{}", "─".repeat(title.len()));
+        log::write("Section", &title);
+        if !log::enabled(Verbosity::Normal) {
+            return;
+        }
+        if super::is_plain() {
+            super::print_line(format_args!("\n{title}"));
+            return;
+        }
+        let heading = theme::paint(theme::palette().heading, &title);
+        super::print_line(format_args!("\n{}", theme::paint(colors::BOLD, heading)));
+        super::print_line(terminal::symbol("─", "-").repeat(title.chars().count()));
+    }
+
+    /// Print a horizontal rule between targets; nothing in plain output
+    pub fn rule() {
+        if !super::is_plain() && log::enabled(Verbosity::Normal) {
+            super::print_line(terminal::symbol("─", "-").repeat(50));
+        }
     }
 }
 
 /// Progress bar utilities for build operations
 pub mod progress {
-    use super::{colors, Duration, Instant, MultiProgress, ProgressBar, ProgressStyle};
+    use super::log::{self, Verbosity};
+    use super::{
+        colors, i18n, is_plain, terminal, theme, Duration, Instant, MessageType, MultiProgress,
+        ProgressBar, ProgressStyle,
+    };
+
+    /// Spinner style for `indicatif` templates, colored by the active theme
+    fn spinner_placeholder() -> String {
+        match theme::palette().spinner {
+            "" => "{spinner}".to_string(),
+            color => format!("{{spinner:.{color}}}"),
+        }
+    }
+
+    /// `indicatif` placeholder for `key`, in bold if colors are on
+    fn bold_placeholder(key: &str) -> String {
+        if terminal::colors_enabled() {
+            format!("{{{key}:.bold}}")
+        } else {
+            format!("{{{key}}}")
+        }
+    }
+
+    /// Spinner frames, the last shown once finished
+    fn tick_chars() -> &'static str {
+        terminal::symbol("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏", "|/-\\|")
+    }
+
+    /// Template of the spinner shown while `operation` runs
+    fn spinner_template(operation: &str) -> ProgressStyle {
+        ProgressStyle::default_spinner()
+            .tick_chars(tick_chars())
+            .template(&format!(
+                "{} {} {} [{{elapsed_precise}}]",
+                spinner_placeholder(),
+                operation_name(operation),
+                bold_placeholder("msg")
+            ))
+            .unwrap()
+    }
+
+    /// Template of the bar shown once cargo reports how many units it builds
+    fn units_template(operation: &str) -> ProgressStyle {
+        ProgressStyle::default_bar()
+            .tick_chars(tick_chars())
+            .template(&format!(
+                "{} {} {} [{{elapsed_precise}}] [{{bar:25}}] {{pos}}/{{len}} {{wide_msg}}",
+                spinner_placeholder(),
+                operation_name(operation),
+                bold_placeholder("prefix")
+            ))
+            .unwrap()
+            .progress_chars("=> ")
+    }
 
     /// A timed build progress tracker
+    ///
+    /// Starts as a spinner and turns into a bar of compiled units once
+    /// [`set_units`](Self::set_units) reports cargo's progress. Clones
+    /// update the same bar.
+    #[derive(Clone)]
     pub struct BuildProgress {
         bar: ProgressBar,
         start_time: Instant,
         target: String,
+        operation: String,
     }
 
     impl BuildProgress {
         /// Create a new build progress spinner
+        ///
+        /// In plain output, a line saying the operation started is printed
+        /// instead; with `-q`, nothing is.
         #[must_use]
         pub fn new(target: &str, operation: &str) -> Self {
-            let bar = ProgressBar::new_spinner();
-            bar.set_style(
-                ProgressStyle::default_spinner()
-                    .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏")
-                    .template(&format!(
-                        "{{spinner:.cyan}} {operation} {{msg:.bold}} [{{elapsed_precise}}]"
-                    ))
-                    .unwrap(),
-            );
-            bar.set_message(target.to_string());
+            Self::start(target, operation, |bar| bar)
+        }
+
+        /// Start tracking, placing the spinner with `add`
+        fn start(
+            target: &str,
+            operation: &str,
+            add: impl FnOnce(ProgressBar) -> ProgressBar,
+        ) -> Self {
+            let start_time = Instant::now();
+            let target = target.to_string();
+            let operation = operation.to_lowercase();
+            log::write("Started", &format!("{operation} {target}"));
+            let quiet = !log::enabled(Verbosity::Normal);
+            if quiet || is_plain() {
+                if !quiet {
+                    println!("{}", started_line(&operation, &target));
+                }
+                return Self {
+                    bar: ProgressBar::hidden(),
+                    start_time,
+                    target,
+                    operation,
+                };
+            }
+
+            let bar = add(ProgressBar::new_spinner());
+            bar.set_style(spinner_template(&operation));
+            bar.set_message(target.clone());
             bar.enable_steady_tick(Duration::from_millis(80));
 
             Self {
                 bar,
-                start_time: Instant::now(),
-                target: target.to_string(),
+                start_time,
+                target,
+                operation,
             }
         }
 
@@ -271,39 +457,126 @@ pub mod progress {
             Self::new(target, "Testing")
         }
 
+        /// Create a build progress for benchmarking
+        #[must_use]
+        pub fn benchmarking(target: &str) -> Self {
+            Self::new(target, "Benchmarking")
+        }
+
         /// Update the message
         pub fn set_message(&self, msg: &str) {
             self.bar.set_message(msg.to_string());
         }
 
+        /// Show that `done` of `total` units are compiled and which are
+        /// compiling now
+        pub fn set_units(&self, done: u64, total: u64, current: &str) {
+            if is_plain() {
+                return;
+            }
+            if self.bar.length() != Some(total) {
+                self.bar.set_style(units_template(&self.operation));
+                self.bar.set_prefix(self.target.clone());
+                self.bar.set_length(total);
+            }
+            self.bar.set_position(done);
+            self.bar.set_message(current.to_string());
+        }
+
+        /// Print a line to stderr without garbling the progress bar
+        pub fn eprintln(&self, line: &str) {
+            self.bar.suspend(|| eprintln!("{line}"));
+        }
+
+        /// Run `f`, printing output, while the progress bar is hidden
+        pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+            self.bar.suspend(f)
+        }
+
+        /// Go back to the spinner for the closing message
+        fn reset_style(&self) {
+            if self.bar.length().is_some() {
+                self.bar.set_style(spinner_template(&self.operation));
+            }
+        }
+
         /// Mark as finished with success
         pub fn finish_success(&self) {
             let elapsed = self.start_time.elapsed();
+            log::write(
+                "Finished",
+                &format!(
+                    "{} {} in {}",
+                    self.operation,
+                    self.target,
+                    format_duration(elapsed)
+                ),
+            );
+            if !log::enabled(Verbosity::Normal) {
+                return;
+            }
+            if is_plain() {
+                println!(
+                    "{}",
+                    i18n::trf(
+                        "Finished {operation} {target} in {duration}.",
+                        &[
+                            ("operation", &operation_name(&self.operation).to_lowercase()),
+                            ("target", &self.target),
+                            ("duration", &format_duration(elapsed)),
+                        ]
+                    )
+                );
+                return;
+            }
+            let palette = theme::palette();
+            self.reset_style();
             self.bar.finish_with_message(format!(
-                "{}{}{} {} {}({}){}",
-                colors::GREEN,
-                "✓",
-                colors::RESET,
+                "{} {} {}",
+                theme::paint(palette.success, MessageType::Success.symbol()),
                 self.target,
-                colors::DIM,
-                format_duration(elapsed),
-                colors::RESET
+                theme::paint(palette.dim, format!("({})", format_duration(elapsed)))
             ));
         }
 
         /// Mark as finished with error
         pub fn finish_error(&self, error: &str) {
             let elapsed = self.start_time.elapsed();
+            log::write(
+                "Failed",
+                &format!(
+                    "{} {} after {}: {error}",
+                    self.operation,
+                    self.target,
+                    format_duration(elapsed)
+                ),
+            );
+            if !log::enabled(Verbosity::Normal) {
+                return;
+            }
+            if is_plain() {
+                println!(
+                    "{}",
+                    i18n::trf(
+                        "Failed {operation} {target} after {duration}: {error}.",
+                        &[
+                            ("operation", &operation_name(&self.operation).to_lowercase()),
+                            ("target", &self.target),
+                            ("duration", &format_duration(elapsed)),
+                            ("error", &error),
+                        ]
+                    )
+                );
+                return;
+            }
+            let palette = theme::palette();
+            self.reset_style();
             self.bar.finish_with_message(format!(
-                "{}{}{} {} - {} {}({}){}",
-                colors::RED,
-                "✗",
-                colors::RESET,
+                "{} {} - {} {}",
+                theme::paint(palette.error, MessageType::Error.symbol()),
                 self.target,
                 error,
-                colors::DIM,
-                format_duration(elapsed),
-                colors::RESET
+                theme::paint(palette.dim, format!("({})", format_duration(elapsed)))
             ));
         }
 
@@ -315,6 +588,9 @@ pub mod progress {
     }
 
     /// Multi-target progress tracker for parallel builds
+    ///
+    /// Clones share the same display.
+    #[derive(Clone)]
     pub struct MultiTargetProgress {
         multi: MultiProgress,
         start_time: Instant,
@@ -331,14 +607,27 @@ pub mod progress {
         }
 
         /// Add a target progress bar
+        ///
+        /// In plain output, the bar is hidden and a line saying the operation
+        /// started is printed instead; with `-q`, the bar is just hidden.
         #[must_use]
         pub fn add_target(&self, target: &str, operation: &str) -> ProgressBar {
+            log::write("Started", &format!("{} {target}", operation.to_lowercase()));
+            if is_plain() && log::enabled(Verbosity::Normal) {
+                println!("{}", started_line(&operation.to_lowercase(), target));
+            }
+            if is_plain() || !log::enabled(Verbosity::Normal) {
+                return ProgressBar::hidden();
+            }
             let bar = self.multi.add(ProgressBar::new_spinner());
             bar.set_style(
                 ProgressStyle::default_spinner()
-                    .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏")
+                    .tick_chars(tick_chars())
                     .template(&format!(
-                        "  {{spinner:.cyan}} {operation} {{msg:.bold}} [{{elapsed_precise}}]"
+                        "  {} {} {} [{{elapsed_precise}}]",
+                        spinner_placeholder(),
+                        operation_name(operation),
+                        bold_placeholder("msg")
                     ))
                     .unwrap(),
             );
@@ -347,6 +636,15 @@ pub mod progress {
             bar
         }
 
+        /// Add a target's build progress, drawn below the other targets'
+        ///
+        /// In plain output, a line saying the operation started is printed
+        /// instead.
+        #[must_use]
+        pub fn add_build(&self, target: &str, operation: &str) -> BuildProgress {
+            BuildProgress::start(target, operation, |bar| self.multi.add(bar))
+        }
+
         /// Get total elapsed time
         #[must_use]
         pub fn elapsed(&self) -> Duration {
@@ -356,26 +654,43 @@ pub mod progress {
         /// Print summary
         pub fn finish_summary(&self, successes: usize, failures: usize) {
             let elapsed = self.elapsed();
-            println!();
-            if failures == 0 {
-                println!(
-                    "{}{}✓{} All {} targets completed in {}",
-                    colors::BOLD,
-                    colors::GREEN,
-                    colors::RESET,
-                    successes,
+            log::write(
+                "Finished",
+                &format!(
+                    "{successes} succeeded and {failures} failed in {}",
                     format_duration(elapsed)
-                );
+                ),
+            );
+            if !log::enabled(Verbosity::Normal) {
+                return;
+            }
+            println!();
+            let duration = format_duration(elapsed);
+            let summary = if failures == 0 {
+                i18n::trf(
+                    "All {count} targets completed in {duration}",
+                    &[("count", &successes), ("duration", &duration)],
+                )
             } else {
-                println!(
-                    "{}{}⚠{} {} succeeded, {} failed in {}",
-                    colors::BOLD,
-                    colors::YELLOW,
-                    colors::RESET,
-                    successes,
-                    failures,
-                    format_duration(elapsed)
-                );
+                i18n::trf(
+                    "{succeeded} succeeded, {failed} failed in {duration}",
+                    &[
+                        ("succeeded", &successes),
+                        ("failed", &failures),
+                        ("duration", &duration),
+                    ],
+                )
+            };
+            if is_plain() {
+                println!("{summary}.");
+            } else {
+                let (color, symbol) = if failures == 0 {
+                    (theme::palette().success, MessageType::Success.symbol())
+                } else {
+                    (theme::palette().warning, MessageType::Warning.symbol())
+                };
+                let icon = theme::paint(color, symbol);
+                println!("{} {summary}", theme::paint(colors::BOLD, icon));
             }
         }
     }
@@ -386,6 +701,31 @@ pub mod progress {
         }
     }
 
+    /// Plain output line for a target whose `operation` started
+    fn started_line(operation: &str, target: &str) -> String {
+        i18n::trf(
+            "Started {operation} {target}.",
+            &[
+                ("operation", &operation_name(operation).to_lowercase()),
+                ("target", &target),
+            ],
+        )
+    }
+
+    /// Name of `operation` for the spinner, e.g. "compiling" -> "Compiling",
+    /// in the current language
+    fn operation_name(operation: &str) -> String {
+        i18n::tr(&capitalize(operation)).to_string()
+    }
+
+    /// First letter in upper case, e.g. "compiling" -> "Compiling"
+    fn capitalize(word: &str) -> String {
+        let mut chars = word.chars();
+        chars.next().map_or_else(String::new, |first| {
+            first.to_uppercase().chain(chars).collect()
+        })
+    }
+
     /// Format a duration in a human-readable way
     #[must_use]
     pub fn format_duration(duration: Duration) -> String {
@@ -427,16 +767,25 @@ pub mod progress {
 
         /// Print elapsed time
         pub fn print_elapsed(&self) {
-            println!(
-                "{}{}⏱{} {} completed in {}{}{}",
-                colors::BOLD,
-                colors::CYAN,
-                colors::RESET,
-                self.label,
-                colors::DIM,
-                format_duration(self.elapsed()),
-                colors::RESET
+            let duration = format_duration(self.elapsed());
+            if is_plain() {
+                let line = i18n::trf(
+                    "{label} completed in {duration}",
+                    &[("label", &self.label), ("duration", &duration)],
+                );
+                println!("{line}.");
+                return;
+            }
+            let palette = theme::palette();
+            let icon = theme::paint(palette.heading, terminal::symbol("⏱", "*"));
+            let line = i18n::trf(
+                "{label} completed in {duration}",
+                &[
+                    ("label", &self.label),
+                    ("duration", &theme::paint(palette.dim, duration)),
+                ],
             );
+            println!("{} {line}", theme::paint(colors::BOLD, icon));
         }
     }
 }
@@ -485,6 +834,7 @@ mod tests {
         assert_eq!(MessageType::Info.icon(), "ℹ");
         assert_eq!(MessageType::Tip.icon(), "💡");
         assert_eq!(MessageType::Hint.icon(), "→");
+        assert!(MessageType::Tip.ascii_icon().is_ascii());
     }
 
     #[test]
@@ -503,4 +853,15 @@ mod tests {
         let output = format!("{msg}");
         assert!(output.contains("Testing message"));
     }
+
+    #[test]
+    fn test_plain_message_display() {
+        set_plain(true);
+        let warning = Message::warning("Linker not found").to_string();
+        let info = Message::info("Using toolchain stable").to_string();
+        set_plain(false);
+
+        assert_eq!(warning, "Warning: Linker not found");
+        assert_eq!(info, "Using toolchain stable");
+    }
 }
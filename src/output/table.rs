@@ -0,0 +1,54 @@
+//! Plain-text tables for terminal output
+
+/// Left-align the columns of `rows`, two spaces apart
+///
+/// Widths count characters, not bytes, so names with `…` or other non-ASCII
+/// characters line up. Rows may have fewer cells than others, and trailing
+/// padding is trimmed.
+#[must_use]
+pub fn align_columns(rows: &[Vec<String>]) -> Vec<String> {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..columns)
+        .map(|col| {
+            rows.iter()
+                .filter_map(|row| row.get(col))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_columns() {
+        let rows = vec![
+            vec!["TARGET".to_string(), "SIZE".to_string(), "NOTE".to_string()],
+            vec!["wasm32-unknown-unknown".to_string(), "1.2 MB".to_string()],
+            vec!["x86_64…".to_string(), "12 KB".to_string(), "-".to_string()],
+        ];
+        assert_eq!(
+            align_columns(&rows),
+            [
+                "TARGET                  SIZE    NOTE",
+                "wasm32-unknown-unknown  1.2 MB",
+                "x86_64…                 12 KB   -",
+            ]
+        );
+        assert!(align_columns(&[]).is_empty());
+    }
+}
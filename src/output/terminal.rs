@@ -0,0 +1,307 @@
+//! What the terminal can show: colors and Unicode
+//!
+//! Colors follow `--color`: `always`, `never`, or `auto`, which turns them off
+//! when `NO_COLOR` is set, `TERM` is `dumb`, or stdout is not a terminal (a CI
+//! log, a pipe), unless `CLICOLOR_FORCE` asks for them. Icons, spinners, and
+//! lines fall back to ASCII when the terminal is dumb or the locale is not
+//! UTF-8, as on many CI agents.
+//!
+//! Output other programs draw for a terminal, with colors and progress
+//! redraws, is read back with [`strip_ansi`] and [`read_segment`].
+
+use std::fmt;
+use std::io::{BufRead, IsTerminal};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// When to use colors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Colors on a terminal that supports them, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    /// Always, even when writing to a file or pipe
+    Always,
+    /// Never
+    Never,
+}
+
+impl ColorChoice {
+    /// Name used on the command line
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auto => "auto",
+            Self::Always => "always",
+            Self::Never => "never",
+        }
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "always" => Ok(Self::Always),
+            "never" => Ok(Self::Never),
+            other => Err(format!(
+                "Unknown color choice '{other}' (expected auto, always, or never)"
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Capabilities in use: `UNSET` until [`init`] runs, then 0 or 1
+static COLORS: AtomicU8 = AtomicU8::new(UNSET);
+static UNICODE: AtomicU8 = AtomicU8::new(UNSET);
+static CHOICE: AtomicU8 = AtomicU8::new(0);
+const UNSET: u8 = u8::MAX;
+
+/// Detect what the terminal can show, with colors as `choice` asks
+pub fn init(choice: ColorChoice) {
+    let env = |name: &str| std::env::var(name).ok();
+    let colors = detect_colors(choice, env, std::io::stdout().is_terminal());
+    COLORS.store(u8::from(colors), Ordering::Relaxed);
+    UNICODE.store(u8::from(detect_unicode(env)), Ordering::Relaxed);
+    CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+/// Color choice passed to [`init`]
+#[must_use]
+pub fn color_choice() -> ColorChoice {
+    match CHOICE.load(Ordering::Relaxed) {
+        1 => ColorChoice::Always,
+        2 => ColorChoice::Never,
+        _ => ColorChoice::Auto,
+    }
+}
+
+/// Whether output may contain colors and other escape codes
+///
+/// Never in plain output. Before [`init`] runs, colors are detected as for
+/// `--color auto`.
+#[must_use]
+pub fn colors_enabled() -> bool {
+    if super::is_plain() {
+        return false;
+    }
+    match COLORS.load(Ordering::Relaxed) {
+        UNSET => detect_colors(
+            ColorChoice::Auto,
+            |name| std::env::var(name).ok(),
+            std::io::stdout().is_terminal(),
+        ),
+        colors => colors == 1,
+    }
+}
+
+/// Whether icons, spinners, and box drawing may use Unicode
+#[must_use]
+pub fn unicode_enabled() -> bool {
+    match UNICODE.load(Ordering::Relaxed) {
+        UNSET => detect_unicode(|name| std::env::var(name).ok()),
+        unicode => unicode == 1,
+    }
+}
+
+/// `unicode` if the terminal can show it, else `ascii`
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::output::terminal::symbol;
+///
+/// println!("Stripped app (4.2 MiB {} 1.1 MiB)", symbol("→", "->"));
+/// ```
+#[must_use]
+pub fn symbol(unicode: &'static str, ascii: &'static str) -> &'static str {
+    if unicode_enabled() {
+        unicode
+    } else {
+        ascii
+    }
+}
+
+/// Whether to use colors, given the environment and whether stdout is a
+/// terminal
+fn detect_colors(choice: ColorChoice, env: impl Fn(&str) -> Option<String>, tty: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            // https://no-color.org: any non-empty value
+            if env("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+                return false;
+            }
+            if env("CLICOLOR_FORCE").is_some_and(|value| !value.is_empty() && value != "0") {
+                return true;
+            }
+            tty && env("TERM").as_deref() != Some("dumb")
+        }
+    }
+}
+
+/// Whether the terminal can show Unicode, from `TERM` and the locale
+fn detect_unicode(env: impl Fn(&str) -> Option<String>) -> bool {
+    if matches!(env("TERM").as_deref(), Some("dumb" | "linux")) {
+        return false;
+    }
+    if cfg!(windows) {
+        return true;
+    }
+    // The first of these that is set decides, as in the C library
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| env(name).filter(|value| !value.is_empty()));
+    locale.is_some_and(|locale| {
+        let locale = locale.to_ascii_lowercase();
+        locale.contains("utf-8") || locale.contains("utf8")
+    })
+}
+
+/// `line` without terminal escape sequences (colors, clearing the line)
+#[must_use]
+pub fn strip_ansi(line: &str) -> String {
+    let mut plain = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // ESC [ parameters, ended by a letter
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+/// Read up to and including the next '\r' or '\n'
+///
+/// Programs drawing a progress line end each redraw in '\r' instead of
+/// '\n', so a redraw is read as soon as it is drawn.
+///
+/// # Errors
+/// Returns an error if reading fails
+pub fn read_segment(reader: &mut impl BufRead, segment: &mut Vec<u8>) -> std::io::Result<usize> {
+    let mut read = 0;
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(read);
+        }
+        if let Some(end) = buffer.iter().position(|&b| b == b'\r' || b == b'\n') {
+            segment.extend_from_slice(&buffer[..=end]);
+            reader.consume(end + 1);
+            return Ok(read + end + 1);
+        }
+        let len = buffer.len();
+        segment.extend_from_slice(buffer);
+        reader.consume(len);
+        read += len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<String> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value).to_string())
+        }
+    }
+
+    #[test]
+    fn test_parse_color_choice() {
+        assert_eq!("Always".parse::<ColorChoice>(), Ok(ColorChoice::Always));
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+        for choice in [ColorChoice::Auto, ColorChoice::Always, ColorChoice::Never] {
+            assert_eq!(choice.as_str().parse::<ColorChoice>(), Ok(choice));
+        }
+    }
+
+    #[test]
+    fn test_detect_colors() {
+        let term = [("TERM", "xterm-256color")];
+        assert!(detect_colors(ColorChoice::Auto, env(&term), true));
+        // Piped to a file or a CI log
+        assert!(!detect_colors(ColorChoice::Auto, env(&term), false));
+        assert!(!detect_colors(
+            ColorChoice::Auto,
+            env(&[("TERM", "xterm"), ("NO_COLOR", "1")]),
+            true
+        ));
+        assert!(!detect_colors(
+            ColorChoice::Auto,
+            env(&[("TERM", "dumb")]),
+            true
+        ));
+        assert!(detect_colors(
+            ColorChoice::Auto,
+            env(&[("CLICOLOR_FORCE", "1")]),
+            false
+        ));
+        // An explicit choice wins over the environment
+        assert!(detect_colors(
+            ColorChoice::Always,
+            env(&[("NO_COLOR", "1")]),
+            false
+        ));
+        assert!(!detect_colors(ColorChoice::Never, env(&term), true));
+    }
+
+    #[test]
+    fn test_detect_unicode() {
+        assert!(!detect_unicode(env(&[
+            ("TERM", "dumb"),
+            ("LANG", "en_US.UTF-8")
+        ])));
+        if cfg!(windows) {
+            return;
+        }
+        assert!(detect_unicode(env(&[("LANG", "en_US.UTF-8")])));
+        assert!(detect_unicode(env(&[("LC_ALL", "C.utf8"), ("LANG", "C")])));
+        assert!(!detect_unicode(env(&[
+            ("LC_ALL", "POSIX"),
+            ("LANG", "en_US.UTF-8")
+        ])));
+        // Jenkins agents often have no locale at all
+        assert!(!detect_unicode(env(&[])));
+    }
+
+    #[test]
+    fn test_read_segment() {
+        let mut reader =
+            BufReader::new(&b"   Compiling a\n    Building [] 0/2: a\r    Finished"[..]);
+        let mut segments = Vec::new();
+        let mut segment = Vec::new();
+        while read_segment(&mut reader, &mut segment).unwrap() > 0 {
+            segments.push(String::from_utf8(std::mem::take(&mut segment)).unwrap());
+        }
+        assert_eq!(
+            segments,
+            [
+                "   Compiling a\n",
+                "    Building [] 0/2: a\r",
+                "    Finished"
+            ]
+        );
+    }
+}
@@ -0,0 +1,288 @@
+//! Output color themes
+//!
+//! Message types map to colors through the active theme's [`Palette`]
+//! instead of fixed ANSI codes. The theme is chosen with `XCARGO_THEME` or
+//! `[output] theme` in `xcargo.toml`, in that order of precedence.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use super::colors;
+
+/// Environment variable selecting the output theme
+pub const THEME_ENV: &str = "XCARGO_THEME";
+
+/// Output color theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// Green for success, red for errors, yellow for warnings
+    #[default]
+    Default,
+    /// Blue, orange, and yellow, distinguishable with red-green color blindness
+    #[serde(alias = "colorblind")]
+    ColorblindSafe,
+    /// No colors; bold text only
+    Monochrome,
+    /// Bright, bold colors without dimmed text
+    HighContrast,
+}
+
+/// ANSI escape codes used for each kind of output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Success messages
+    pub success: &'static str,
+    /// Error messages
+    pub error: &'static str,
+    /// Warning messages
+    pub warning: &'static str,
+    /// Info messages
+    pub info: &'static str,
+    /// Tips
+    pub tip: &'static str,
+    /// Hints
+    pub hint: &'static str,
+    /// Progress messages
+    pub progress: &'static str,
+    /// Section headers
+    pub heading: &'static str,
+    /// De-emphasized text such as timings
+    pub dim: &'static str,
+    /// Color name for progress spinners (an `indicatif` style), empty for none
+    pub spinner: &'static str,
+}
+
+impl Theme {
+    /// All themes, in display order
+    pub const ALL: [Theme; 4] = [
+        Self::Default,
+        Self::ColorblindSafe,
+        Self::Monochrome,
+        Self::HighContrast,
+    ];
+
+    /// Name used in `xcargo.toml` and `XCARGO_THEME`
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::ColorblindSafe => "colorblind-safe",
+            Self::Monochrome => "monochrome",
+            Self::HighContrast => "high-contrast",
+        }
+    }
+
+    /// Colors used by this theme
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::output::theme::Theme;
+    ///
+    /// assert_eq!(Theme::Monochrome.palette().error, "");
+    /// ```
+    #[must_use]
+    pub fn palette(&self) -> Palette {
+        match self {
+            Self::Default => Palette {
+                success: colors::GREEN,
+                error: colors::RED,
+                warning: colors::YELLOW,
+                info: colors::BLUE,
+                tip: colors::CYAN,
+                hint: colors::CYAN,
+                progress: colors::BLUE,
+                heading: colors::CYAN,
+                dim: colors::DIM,
+                spinner: "cyan",
+            },
+            // Okabe-Ito colors: blue, vermillion, yellow, sky blue, reddish purple
+            Self::ColorblindSafe => Palette {
+                success: "\x1b[38;5;32m",
+                error: "\x1b[38;5;202m",
+                warning: "\x1b[38;5;220m",
+                info: "\x1b[38;5;117m",
+                tip: "\x1b[38;5;175m",
+                hint: "\x1b[38;5;175m",
+                progress: "\x1b[38;5;117m",
+                heading: "\x1b[38;5;117m",
+                dim: colors::DIM,
+                spinner: "blue",
+            },
+            Self::Monochrome => Palette {
+                success: "",
+                error: "",
+                warning: "",
+                info: "",
+                tip: "",
+                hint: "",
+                progress: "",
+                heading: "",
+                dim: "",
+                spinner: "",
+            },
+            Self::HighContrast => Palette {
+                success: "\x1b[92m",
+                error: "\x1b[91m",
+                warning: "\x1b[93m",
+                info: "\x1b[96m",
+                tip: "\x1b[95m",
+                hint: "\x1b[96m",
+                progress: "\x1b[97m",
+                heading: "\x1b[97m",
+                dim: "",
+                spinner: "white",
+            },
+        }
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "default" => Ok(Self::Default),
+            "colorblind-safe" | "colorblind" => Ok(Self::ColorblindSafe),
+            "monochrome" => Ok(Self::Monochrome),
+            "high-contrast" => Ok(Self::HighContrast),
+            other => Err(format!(
+                "Unknown theme '{other}' (expected one of: {})",
+                Theme::ALL.map(|t| t.as_str()).join(", ")
+            )),
+        }
+    }
+}
+
+/// Theme in use, stored as its index in [`Theme::ALL`]; `UNSET` until
+/// [`init`] or [`set_theme`] runs
+static ACTIVE: AtomicU8 = AtomicU8::new(UNSET);
+const UNSET: u8 = u8::MAX;
+
+/// Select the theme from `XCARGO_THEME`, falling back to `configured`
+///
+/// Returns an error message if `XCARGO_THEME` names an unknown theme; the
+/// configured theme is used in that case.
+pub fn init(configured: Option<Theme>) -> std::result::Result<Theme, String> {
+    let (theme, result) = match std::env::var(THEME_ENV) {
+        Ok(name) if !name.trim().is_empty() => match name.parse::<Theme>() {
+            Ok(theme) => (theme, Ok(theme)),
+            Err(e) => {
+                let theme = configured.unwrap_or_default();
+                (theme, Err(format!("{THEME_ENV}: {e}")))
+            }
+        },
+        _ => {
+            let theme = configured.unwrap_or_default();
+            (theme, Ok(theme))
+        }
+    };
+    set_theme(theme);
+    result
+}
+
+/// Use `theme` for all further output
+pub fn set_theme(theme: Theme) {
+    let index = Theme::ALL.iter().position(|t| *t == theme).unwrap_or(0);
+    ACTIVE.store(u8::try_from(index).unwrap_or(0), Ordering::Relaxed);
+}
+
+/// Theme in use
+///
+/// Before [`init`] runs, this is the theme named by `XCARGO_THEME`, or the
+/// default theme.
+#[must_use]
+pub fn current() -> Theme {
+    match ACTIVE.load(Ordering::Relaxed) {
+        UNSET => std::env::var(THEME_ENV)
+            .ok()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or_default(),
+        index => Theme::ALL
+            .get(usize::from(index))
+            .copied()
+            .unwrap_or_default(),
+    }
+}
+
+/// Palette of the theme in use; without colors, as in plain output, it is
+/// empty
+#[must_use]
+pub fn palette() -> Palette {
+    if !super::terminal::colors_enabled() {
+        return Theme::Monochrome.palette();
+    }
+    current().palette()
+}
+
+/// Wrap `text` in an escape code, leaving it plain if the code is empty or
+/// colors are off
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::output::terminal::{self, ColorChoice};
+/// use xcargo::output::theme::{paint, Theme};
+///
+/// terminal::init(ColorChoice::Always);
+/// assert_eq!(paint(Theme::Monochrome.palette().success, "ok"), "ok");
+/// assert_eq!(paint("\x1b[32m", "ok"), "\x1b[32mok\x1b[0m");
+/// ```
+#[must_use]
+pub fn paint(code: &str, text: impl fmt::Display) -> String {
+    if code.is_empty() || !super::terminal::colors_enabled() {
+        text.to_string()
+    } else {
+        format!("{code}{text}{}", colors::RESET)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_theme() {
+        assert_eq!("colorblind".parse::<Theme>(), Ok(Theme::ColorblindSafe));
+        assert_eq!("High-Contrast".parse::<Theme>(), Ok(Theme::HighContrast));
+        assert!("neon".parse::<Theme>().unwrap_err().contains("monochrome"));
+
+        for theme in Theme::ALL {
+            assert_eq!(theme.as_str().parse::<Theme>(), Ok(theme));
+        }
+    }
+
+    #[test]
+    fn test_theme_from_toml() {
+        #[derive(Deserialize)]
+        struct Output {
+            theme: Theme,
+        }
+        let output: Output = toml::from_str("theme = \"colorblind-safe\"").unwrap();
+        assert_eq!(output.theme, Theme::ColorblindSafe);
+        assert!(toml::from_str::<Output>("theme = \"neon\"").is_err());
+    }
+
+    #[test]
+    fn test_palettes_distinguish_messages() {
+        // Success and error must differ in every colored theme
+        for theme in [Theme::Default, Theme::ColorblindSafe, Theme::HighContrast] {
+            let palette = theme.palette();
+            assert_ne!(palette.success, palette.error, "{theme}");
+            assert_ne!(palette.warning, palette.error, "{theme}");
+        }
+        // The colorblind-safe theme avoids red and green
+        let palette = Theme::ColorblindSafe.palette();
+        assert_ne!(palette.success, colors::GREEN);
+        assert_ne!(palette.error, colors::RED);
+    }
+}
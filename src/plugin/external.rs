@@ -0,0 +1,223 @@
+//! Plugins shipped as separate `xcargo-<name>` executables
+//!
+//! Like cargo's `cargo-<name>` subcommands, any executable named
+//! `xcargo-<name>` on `PATH` becomes `xcargo <name>`. The plugin receives the
+//! remaining arguments on its command line and a JSON [`ExternalContext`] on
+//! stdin, so it doesn't have to find and parse xcargo.toml itself.
+
+use serde::Serialize;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::output::log;
+
+use super::handshake::PLUGIN_API_VERSION;
+
+/// Prefix of external plugin executables
+pub const EXTERNAL_PREFIX: &str = "xcargo-";
+
+/// An `xcargo-<name>` executable found on `PATH`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalPlugin {
+    /// Subcommand name, e.g. "deploy" for `xcargo-deploy`
+    pub name: String,
+
+    /// Path of the executable
+    pub path: PathBuf,
+}
+
+/// What xcargo tells an external plugin on stdin
+#[derive(Debug, Clone, Serialize)]
+pub struct ExternalContext {
+    /// Plugin API version of this xcargo
+    pub api_version: u32,
+
+    /// Version of the running xcargo
+    pub xcargo_version: String,
+
+    /// Effective configuration, with every layer applied
+    pub config: Config,
+
+    /// Targets the project builds by default
+    pub targets: Vec<String>,
+
+    /// Whether `--verbose` was given
+    pub verbose: bool,
+}
+
+impl ExternalContext {
+    /// Context for the project's effective configuration
+    #[must_use]
+    pub fn new(config: Config, verbose: bool) -> Self {
+        Self {
+            api_version: PLUGIN_API_VERSION,
+            xcargo_version: env!("CARGO_PKG_VERSION").to_string(),
+            targets: config.targets.default.clone(),
+            config,
+            verbose,
+        }
+    }
+}
+
+impl ExternalPlugin {
+    /// Find the plugin providing `xcargo <name>` on `PATH`
+    #[must_use]
+    pub fn find(name: &str) -> Option<Self> {
+        let path = which::which(format!("{EXTERNAL_PREFIX}{name}")).ok()?;
+        Some(Self {
+            name: name.to_string(),
+            path,
+        })
+    }
+
+    /// Run the plugin with `args`, writing `context` to its stdin
+    ///
+    /// Returns the plugin's exit code.
+    ///
+    /// # Errors
+    /// Returns an error if the plugin cannot be started
+    pub fn run(&self, args: &[String], context: &ExternalContext) -> Result<i32> {
+        let json = serde_json::to_string(context)
+            .map_err(|e| Error::Config(format!("Failed to serialize plugin context: {e}")))?;
+
+        let mut cmd = Command::new(&self.path);
+        cmd.args(args)
+            .env("XCARGO", std::env::current_exe().unwrap_or_default());
+        log::command(&cmd);
+        let mut child = cmd.stdin(Stdio::piped()).spawn().map_err(|e| {
+            Error::Config(format!("Failed to run plugin {}: {e}", self.path.display()))
+        })?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // Plugins that don't need the context may exit without reading it
+            let _ = stdin.write_all(json.as_bytes());
+        }
+        let status = child.wait()?;
+        Ok(status.code().unwrap_or(1))
+    }
+}
+
+/// Every external plugin on `PATH`, by name
+///
+/// When several directories provide the same plugin, the first one wins, as
+/// it does when running it.
+#[must_use]
+pub fn discover_external() -> Vec<ExternalPlugin> {
+    let Some(path) = std::env::var_os("PATH") else {
+        return Vec::new();
+    };
+    let mut plugins: Vec<ExternalPlugin> = Vec::new();
+    for dir in std::env::split_paths(&path) {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut found: Vec<ExternalPlugin> = entries
+            .filter_map(std::result::Result::ok)
+            .filter_map(|entry| plugin_name(&entry.path()).map(|name| (name, entry.path())))
+            .filter(|(_, path)| is_executable(path))
+            .map(|(name, path)| ExternalPlugin { name, path })
+            .collect();
+        found.retain(|plugin| !plugins.iter().any(|p| p.name == plugin.name));
+        plugins.extend(found);
+    }
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+/// Subcommand name of an `xcargo-<name>` executable
+fn plugin_name(path: &Path) -> Option<String> {
+    let file_name = path.file_name()?.to_str()?;
+    let file_name = if cfg!(windows) {
+        file_name.strip_suffix(".exe")?
+    } else {
+        file_name
+    };
+    let name = file_name.strip_prefix(EXTERNAL_PREFIX)?;
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plugin_name() {
+        let exe = if cfg!(windows) { ".exe" } else { "" };
+        assert_eq!(
+            plugin_name(Path::new(&format!("/usr/bin/xcargo-deploy{exe}"))).as_deref(),
+            Some("deploy")
+        );
+        assert_eq!(plugin_name(Path::new("/usr/bin/xcargo-")), None);
+        assert_eq!(plugin_name(Path::new("/usr/bin/cargo-deploy")), None);
+    }
+
+    #[test]
+    fn test_context_json() {
+        let mut config = Config::default();
+        config.targets.default = vec!["aarch64-unknown-linux-gnu".to_string()];
+        let context = ExternalContext::new(config, true);
+
+        let json: serde_json::Value = serde_json::to_value(&context).unwrap();
+        assert_eq!(json["api_version"], PLUGIN_API_VERSION);
+        assert_eq!(json["targets"][0], "aarch64-unknown-linux-gnu");
+        assert_eq!(json["verbose"], true);
+        assert!(json["config"]["build"].is_object());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_passes_args_and_context() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::TempDir::new().unwrap();
+        let script = temp.path().join("xcargo-echo");
+        let out = temp.path().join("out.txt");
+        std::fs::write(
+            &script,
+            format!(
+                "#!/bin/sh\necho \"$@\" > {0}\ncat >> {0}\nexit 7\n",
+                out.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(is_executable(&script));
+
+        // Run the script through sh: executing a file this process just
+        // wrote can fail with ETXTBSY while other tests spawn processes
+        let plugin = ExternalPlugin {
+            name: "echo".to_string(),
+            path: PathBuf::from("sh"),
+        };
+        let context = ExternalContext::new(Config::default(), false);
+        let args = [
+            script.display().to_string(),
+            "--fast".to_string(),
+            "now".to_string(),
+        ];
+        let code = plugin.run(&args, &context).unwrap();
+        assert_eq!(code, 7);
+
+        let output = std::fs::read_to_string(&out).unwrap();
+        let (args, json) = output.split_once('\n').unwrap();
+        assert_eq!(args, "--fast now");
+        let json: serde_json::Value = serde_json::from_str(json).unwrap();
+        assert_eq!(json["verbose"], false);
+    }
+}
@@ -0,0 +1,207 @@
+//! Version and capability handshake performed before a plugin is registered
+//!
+//! Every plugin declares the plugin API version it was written against, the
+//! hooks it relies on, and the permissions it needs. The registry refuses
+//! plugins that cannot work with this xcargo instead of letting them fail
+//! halfway through a build.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::error::{Error, Result};
+
+use super::hooks::PluginHook;
+
+/// Plugin API version implemented by this xcargo
+pub const PLUGIN_API_VERSION: u32 = 1;
+
+/// Oldest plugin API version this xcargo still accepts
+pub const MIN_PLUGIN_API_VERSION: u32 = 1;
+
+/// Permission a plugin can request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PluginPermission {
+    /// Read files in the project
+    ReadProject,
+    /// Write or delete files in the project
+    WriteProject,
+    /// Read environment variables
+    Environment,
+    /// Make network requests
+    Network,
+    /// Spawn external commands
+    ExecuteCommands,
+}
+
+impl PluginPermission {
+    /// Permissions granted to every plugin unless revoked
+    pub const DEFAULT_GRANTED: [PluginPermission; 2] =
+        [PluginPermission::ReadProject, PluginPermission::Environment];
+
+    /// Get the permission name
+    #[must_use]
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReadProject => "read-project",
+            Self::WriteProject => "write-project",
+            Self::Environment => "environment",
+            Self::Network => "network",
+            Self::ExecuteCommands => "execute-commands",
+        }
+    }
+}
+
+/// What a plugin declares about itself during the handshake
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginCapabilities {
+    /// Plugin API version the plugin was written against
+    pub api_version: u32,
+
+    /// Hooks the plugin requires, by name (e.g. "pre-build")
+    #[serde(default)]
+    pub hooks: Vec<String>,
+
+    /// Permissions the plugin needs
+    #[serde(default)]
+    pub permissions: Vec<PluginPermission>,
+}
+
+impl Default for PluginCapabilities {
+    fn default() -> Self {
+        Self {
+            api_version: PLUGIN_API_VERSION,
+            hooks: Vec::new(),
+            permissions: Vec::new(),
+        }
+    }
+}
+
+/// Check that a plugin's declared capabilities are compatible with this xcargo
+///
+/// # Errors
+/// Returns `Error::PluginIncompatible` describing the first incompatibility
+/// and how to resolve it.
+pub fn negotiate(
+    plugin: &str,
+    capabilities: &PluginCapabilities,
+    granted: &BTreeSet<PluginPermission>,
+) -> Result<()> {
+    let incompatible = |reason: String, upgrade: String| Error::PluginIncompatible {
+        plugin: plugin.to_string(),
+        reason,
+        upgrade,
+    };
+
+    let version = capabilities.api_version;
+    if version > PLUGIN_API_VERSION {
+        return Err(incompatible(
+            format!(
+                "it requires plugin API v{version}, but this xcargo {} supports v{MIN_PLUGIN_API_VERSION} to v{PLUGIN_API_VERSION}",
+                env!("CARGO_PKG_VERSION")
+            ),
+            "Upgrade xcargo: cargo install xcargo --force".to_string(),
+        ));
+    }
+    if version < MIN_PLUGIN_API_VERSION {
+        return Err(incompatible(
+            format!(
+                "it was written for plugin API v{version}, which is no longer supported (minimum v{MIN_PLUGIN_API_VERSION})"
+            ),
+            format!("Upgrade '{plugin}' to a release built for plugin API v{PLUGIN_API_VERSION}"),
+        ));
+    }
+
+    if let Some(hook) = capabilities
+        .hooks
+        .iter()
+        .find(|hook| PluginHook::from_name(hook).is_none())
+    {
+        return Err(incompatible(
+            format!("it requires the '{hook}' hook, which this xcargo does not provide"),
+            "Upgrade xcargo: cargo install xcargo --force".to_string(),
+        ));
+    }
+
+    if let Some(permission) = capabilities
+        .permissions
+        .iter()
+        .find(|permission| !granted.contains(permission))
+    {
+        return Err(incompatible(
+            format!(
+                "it needs the '{}' permission, which has not been granted",
+                permission.as_str()
+            ),
+            format!(
+                "Grant '{}' to the plugin registry before registering '{plugin}'",
+                permission.as_str()
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_granted() -> BTreeSet<PluginPermission> {
+        PluginPermission::DEFAULT_GRANTED.into_iter().collect()
+    }
+
+    #[test]
+    fn test_default_capabilities_are_compatible() {
+        assert!(negotiate("p", &PluginCapabilities::default(), &default_granted()).is_ok());
+    }
+
+    #[test]
+    fn test_api_version_out_of_range() {
+        let newer = PluginCapabilities {
+            api_version: PLUGIN_API_VERSION + 1,
+            ..PluginCapabilities::default()
+        };
+        let err = negotiate("p", &newer, &default_granted()).unwrap_err();
+        assert!(err.suggestion().unwrap().contains("Upgrade xcargo"));
+
+        let older = PluginCapabilities {
+            api_version: MIN_PLUGIN_API_VERSION - 1,
+            ..PluginCapabilities::default()
+        };
+        let err = negotiate("p", &older, &default_granted()).unwrap_err();
+        assert!(err.suggestion().unwrap().contains("Upgrade 'p'"));
+    }
+
+    #[test]
+    fn test_unknown_hook_rejected() {
+        let caps = PluginCapabilities {
+            hooks: vec!["pre-build".to_string(), "pre-link".to_string()],
+            ..PluginCapabilities::default()
+        };
+        let err = negotiate("p", &caps, &default_granted()).unwrap_err();
+        assert!(err.to_string().contains("pre-link"));
+    }
+
+    #[test]
+    fn test_permissions_must_be_granted() {
+        let caps = PluginCapabilities {
+            permissions: vec![PluginPermission::ReadProject, PluginPermission::Network],
+            ..PluginCapabilities::default()
+        };
+        assert!(negotiate("p", &caps, &default_granted()).is_err());
+
+        let mut granted = default_granted();
+        granted.insert(PluginPermission::Network);
+        assert!(negotiate("p", &caps, &granted).is_ok());
+    }
+
+    #[test]
+    fn test_capabilities_deserialize() {
+        let caps: PluginCapabilities = serde_json::from_str(
+            r#"{"api_version": 1, "hooks": ["post-build"], "permissions": ["write-project"]}"#,
+        )
+        .unwrap();
+        assert_eq!(caps.permissions, vec![PluginPermission::WriteProject]);
+    }
+}
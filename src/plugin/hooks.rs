@@ -39,6 +39,22 @@ impl PluginHook {
         }
     }
 
+    /// Look up a hook by its name (e.g. "pre-build")
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::PreBuild,
+            Self::PostBuild,
+            Self::BuildFailed,
+            Self::PreToolchainInstall,
+            Self::PostToolchainInstall,
+            Self::Init,
+            Self::Shutdown,
+        ]
+        .into_iter()
+        .find(|hook| hook.as_str() == name)
+    }
+
     /// Execute this hook on a plugin
     ///
     /// # Errors
@@ -8,6 +8,8 @@
 //! - **Toolchain Plugins**: Add support for new toolchains (e.g., custom linkers)
 //! - **Build Hooks**: Execute code before/after build steps
 //! - **Target Plugins**: Add support for new target platforms
+//! - **External Plugins**: `xcargo-<name>` executables on `PATH` that provide
+//!   `xcargo <name>`
 //!
 //! # Example
 //!
@@ -29,11 +31,17 @@
 //! ```
 
 mod context;
+mod external;
+mod handshake;
 mod hooks;
 mod registry;
 mod traits;
 
 pub use context::{PluginContext, PluginMetadata};
+pub use external::{discover_external, ExternalContext, ExternalPlugin, EXTERNAL_PREFIX};
+pub use handshake::{
+    negotiate, PluginCapabilities, PluginPermission, MIN_PLUGIN_API_VERSION, PLUGIN_API_VERSION,
+};
 pub use hooks::PluginHook;
 pub use registry::PluginRegistry;
 pub use traits::Plugin;
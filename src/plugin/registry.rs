@@ -1,11 +1,12 @@
 //! Plugin registry for managing registered plugins
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
 
 use crate::error::{Error, Result};
 
 use super::context::PluginContext;
+use super::handshake::{negotiate, PluginPermission};
 use super::hooks::PluginHook;
 use super::traits::Plugin;
 
@@ -33,6 +34,9 @@ pub struct PluginRegistry {
     /// Execution order for plugins (by name)
     /// If empty, plugins execute in arbitrary order
     execution_order: Vec<String>,
+
+    /// Permissions plugins may request during the handshake
+    granted: BTreeSet<PluginPermission>,
 }
 
 impl PluginRegistry {
@@ -42,13 +46,26 @@ impl PluginRegistry {
         Self {
             plugins: HashMap::new(),
             execution_order: Vec::new(),
+            granted: PluginPermission::DEFAULT_GRANTED.into_iter().collect(),
         }
     }
 
+    /// Allow plugins registered from now on to request a permission
+    pub fn grant(&mut self, permission: PluginPermission) {
+        self.granted.insert(permission);
+    }
+
+    /// Check whether a permission has been granted
+    #[must_use]
+    pub fn is_granted(&self, permission: PluginPermission) -> bool {
+        self.granted.contains(&permission)
+    }
+
     /// Register a plugin
     ///
     /// # Errors
-    /// Returns error if a plugin with the same name is already registered
+    /// Returns error if a plugin with the same name is already registered, or
+    /// if the plugin fails the version/capability handshake
     pub fn register(&mut self, plugin: Box<dyn Plugin>) -> Result<()> {
         let name = plugin.name().to_string();
 
@@ -58,6 +75,9 @@ impl PluginRegistry {
             )));
         }
 
+        // Refuse incompatible plugins before running any of their code
+        negotiate(&name, &plugin.capabilities(), &self.granted)?;
+
         // Initialize the plugin
         plugin.on_init()?;
 
@@ -166,6 +186,7 @@ impl PluginRegistry {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::plugin::PluginCapabilities;
 
     struct TestPlugin {
         name: String,
@@ -304,4 +325,32 @@ mod tests {
 
         assert!(result.is_ok());
     }
+
+    struct NetworkPlugin;
+
+    impl Plugin for NetworkPlugin {
+        fn name(&self) -> &'static str {
+            "network-plugin"
+        }
+
+        fn capabilities(&self) -> PluginCapabilities {
+            PluginCapabilities {
+                permissions: vec![PluginPermission::Network],
+                ..PluginCapabilities::default()
+            }
+        }
+    }
+
+    #[test]
+    fn test_register_requires_granted_permissions() {
+        let mut registry = PluginRegistry::new();
+
+        let err = registry.register(Box::new(NetworkPlugin)).unwrap_err();
+        assert!(matches!(err, Error::PluginIncompatible { .. }));
+        assert!(!registry.contains("network-plugin"));
+
+        registry.grant(PluginPermission::Network);
+        registry.register(Box::new(NetworkPlugin)).unwrap();
+        assert!(registry.contains("network-plugin"));
+    }
 }
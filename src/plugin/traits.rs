@@ -3,6 +3,7 @@
 use crate::error::Result;
 
 use super::context::PluginContext;
+use super::handshake::PluginCapabilities;
 
 /// Main plugin trait that all plugins must implement
 ///
@@ -54,6 +55,11 @@ pub trait Plugin: Send + Sync {
         ""
     }
 
+    /// API version, required hooks and permissions, checked before `on_init`
+    fn capabilities(&self) -> PluginCapabilities {
+        PluginCapabilities::default()
+    }
+
     /// Called before the build starts
     ///
     /// Return `Err` to abort the build.
@@ -0,0 +1,977 @@
+//! Target platform definitions and detection
+//!
+//! This module provides types and functions for working with Rust target triples,
+//! detecting available targets, and validating target configurations.
+mod rename;
+mod requirements;
+
+use crate::error::{Error, Result};
+use crate::toolchain::msvc::MsvcTools;
+use crate::toolchain::ToolchainManager;
+use std::fmt;
+use std::process::Command;
+
+pub use rename::{
+    normalize_triple, renamed_counterpart, NormalizedTriple, TargetRename, TARGET_RENAMES,
+};
+pub use requirements::{RequirementDatabase, RequirementSpec};
+
+/// Operating system names of the BSD targets
+const BSD_SYSTEMS: [&str; 4] = ["freebsd", "netbsd", "openbsd", "dragonfly"];
+
+/// Represents the requirements needed to build for a target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetRequirements {
+    /// Linker required for this target
+    pub linker: Option<String>,
+    /// Additional tools needed (e.g., "lld", "gcc-aarch64-linux-gnu")
+    pub tools: Vec<String>,
+    /// System libraries needed
+    pub system_libs: Vec<String>,
+    /// Environment variables that should be set
+    pub env_vars: Vec<(String, String)>,
+    /// Rustup components needed (e.g., "rust-src")
+    pub components: Vec<String>,
+}
+
+impl TargetRequirements {
+    /// Create empty requirements
+    #[must_use]
+    pub fn none() -> Self {
+        Self {
+            linker: None,
+            tools: Vec::new(),
+            system_libs: Vec::new(),
+            env_vars: Vec::new(),
+            components: Vec::new(),
+        }
+    }
+
+    /// Check if all requirements are satisfied
+    #[must_use]
+    pub fn are_satisfied(&self) -> bool {
+        // Check if linker is available
+        if let Some(ref linker) = self.linker {
+            if !Self::is_command_available(linker) {
+                return false;
+            }
+        }
+
+        // Check if all tools are available
+        for tool in &self.tools {
+            if !Self::is_command_available(tool) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// Check if a command is available in PATH
+    fn is_command_available(cmd: &str) -> bool {
+        which::which(cmd).is_ok()
+    }
+}
+
+/// Represents a target platform for cross-compilation
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Target {
+    /// The full target triple (e.g., "x86_64-unknown-linux-gnu")
+    pub triple: String,
+    /// Target architecture (e.g., "`x86_64`", "aarch64")
+    pub arch: String,
+    /// Target vendor (e.g., "unknown", "apple", "pc")
+    pub vendor: String,
+    /// Target operating system (e.g., "linux", "windows", "darwin")
+    pub os: String,
+    /// Target environment/ABI (e.g., "gnu", "musl", "msvc")
+    pub env: Option<String>,
+    /// glibc version to link against (e.g., "2.17"), from a
+    /// `x86_64-unknown-linux-gnu.2.17` style triple; needs Zig
+    pub glibc: Option<String>,
+    /// Target tier (1 = native, 2 = container, 3 = specialized)
+    pub tier: TargetTier,
+}
+
+/// Classification of target support levels
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TargetTier {
+    /// Tier 1: Native compilation (fast, no containers)
+    Native,
+    /// Tier 2: Container-based (automatic fallback)
+    Container,
+    /// Tier 3: Specialized (mobile, embedded, etc.)
+    Specialized,
+}
+
+impl Target {
+    /// Parse a target triple string into a Target struct
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::target::Target;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let target = Target::from_triple("x86_64-unknown-linux-gnu")?;
+    /// assert_eq!(target.arch, "x86_64");
+    /// assert_eq!(target.os, "linux");
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the target triple is invalid (fewer than 3 parts).
+    /// WASI triples such as `wasm32-wasip1` are accepted with an implied
+    /// `unknown` vendor.
+    ///
+    /// As with cargo-zigbuild, a glibc version can be appended to a
+    /// `linux-gnu` triple; `triple` is then the triple without it:
+    ///
+    /// ```
+    /// use xcargo::target::Target;
+    ///
+    /// let target = Target::from_triple("x86_64-unknown-linux-gnu.2.17").unwrap();
+    /// assert_eq!(target.triple, "x86_64-unknown-linux-gnu");
+    /// assert_eq!(target.glibc.as_deref(), Some("2.17"));
+    /// ```
+    pub fn from_triple(triple: &str) -> Result<Self> {
+        let (triple, glibc) = Self::split_glibc_version(triple)?;
+        let mut parts: Vec<&str> = triple.split('-').collect();
+
+        // wasm32-wasi, wasm32-wasip1, wasm32-wasip2: os "wasi", env "p1"/"p2"
+        if parts.len() == 2 && parts[0].starts_with("wasm") {
+            if let Some(env) = parts[1].strip_prefix("wasi") {
+                parts = vec![parts[0], "unknown", "wasi"];
+                if !env.is_empty() {
+                    parts.push(env);
+                }
+            }
+        }
+
+        if parts.len() < 3 {
+            return Err(Error::TargetNotFound(format!(
+                "Invalid target triple: {triple}. Expected format: arch-vendor-os[-env]"
+            )));
+        }
+
+        let arch = parts[0].to_string();
+        let vendor = parts[1].to_string();
+        let os = parts[2].to_string();
+        let env = if parts.len() >= 4 {
+            Some(parts[3..].join("-"))
+        } else {
+            None
+        };
+
+        let tier = Self::classify_tier(triple);
+
+        Ok(Target {
+            triple: triple.to_string(),
+            arch,
+            vendor,
+            os,
+            env,
+            glibc,
+            tier,
+        })
+    }
+
+    /// Split a `<triple>.<major>.<minor>` glibc suffix off a `linux-gnu` triple
+    fn split_glibc_version(triple: &str) -> Result<(&str, Option<String>)> {
+        // Dots elsewhere are part of the triple, e.g. thumbv8m.main-none-eabi
+        let env_start = triple.rfind('-').map_or(0, |i| i + 1);
+        let Some(dot) = triple[env_start..].find('.').map(|i| env_start + i) else {
+            return Ok((triple, None));
+        };
+        let (base, version) = (&triple[..dot], &triple[dot + 1..]);
+
+        if !base.contains("-linux-gnu") {
+            return Err(Error::TargetNotFound(format!(
+                "Invalid target triple: {triple}. A glibc version can only follow a linux-gnu triple"
+            )));
+        }
+        let valid = version.split_once('.').is_some_and(|(major, minor)| {
+            major == "2" && !minor.is_empty() && minor.bytes().all(|b| b.is_ascii_digit())
+        });
+        if !valid {
+            return Err(Error::TargetNotFound(format!(
+                "Invalid glibc version '{version}' in {triple}. Expected e.g. {base}.2.17"
+            )));
+        }
+        Ok((base, Some(version.to_string())))
+    }
+
+    /// The triple with its glibc version, as written by the user
+    #[must_use]
+    pub fn versioned_triple(&self) -> String {
+        match &self.glibc {
+            Some(glibc) => format!("{}.{glibc}", self.triple),
+            None => self.triple.clone(),
+        }
+    }
+
+    /// Detect the current host target platform
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::target::Target;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let host = Target::detect_host()?;
+    /// println!("Host platform: {}", host.triple);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn detect_host() -> Result<Self> {
+        // Use rustc to get the host target
+        let output = Command::new("rustc")
+            .args(["-vV"])
+            .output()
+            .map_err(|e| Error::Toolchain(format!("Failed to run rustc: {e}")))?;
+
+        if !output.status.success() {
+            return Err(Error::Toolchain("rustc command failed".to_string()));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+
+        // Parse the "host: " line from rustc -vV output
+        for line in stdout.lines() {
+            if let Some(host) = line.strip_prefix("host: ") {
+                return Self::from_triple(host.trim());
+            }
+        }
+
+        Err(Error::Toolchain(
+            "Could not detect host target from rustc".to_string(),
+        ))
+    }
+
+    /// Detect all installed Rust targets via rustup
+    ///
+    /// Without rustup, these are the targets whose standard library is in
+    /// the sysroot of the `rustc` on PATH.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xcargo::target::Target;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let installed = Target::detect_installed()?;
+    /// for target in installed {
+    ///     println!("Installed: {}", target.triple);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn detect_installed() -> Result<Vec<Self>> {
+        let manager = ToolchainManager::new()?;
+        if let Some(standalone) = manager.standalone_toolchain() {
+            return Ok(Self::from_triples(&standalone.installed_targets()));
+        }
+
+        let output = Command::new("rustup")
+            .args(["target", "list", "--installed"])
+            .output()
+            .map_err(|e| Error::Toolchain(format!("Failed to run rustup: {e}")))?;
+
+        if !output.status.success() {
+            return Err(Error::Toolchain(
+                "rustup target list command failed".to_string(),
+            ));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut targets = Vec::new();
+
+        for line in stdout.lines() {
+            let triple = line.trim();
+            if !triple.is_empty() {
+                if let Ok(target) = Self::from_triple(triple) {
+                    targets.push(target);
+                }
+            }
+        }
+
+        Ok(targets)
+    }
+
+    /// List all available Rust targets via rustup
+    ///
+    /// Without rustup, these are the targets `rustc --print target-list`
+    /// names.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xcargo::target::Target;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let available = Target::list_available()?;
+    /// println!("Available targets: {}", available.len());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_available() -> Result<Vec<Self>> {
+        let manager = ToolchainManager::new()?;
+        if let Some(standalone) = manager.standalone_toolchain() {
+            return Ok(Self::from_triples(&standalone.supported_targets()?));
+        }
+
+        let output = Command::new("rustup")
+            .args(["target", "list"])
+            .output()
+            .map_err(|e| Error::Toolchain(format!("Failed to run rustup: {e}")))?;
+
+        if !output.status.success() {
+            return Err(Error::Toolchain(
+                "rustup target list command failed".to_string(),
+            ));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut targets = Vec::new();
+
+        for line in stdout.lines() {
+            // Remove " (installed)" suffix if present
+            let triple = line
+                .trim()
+                .strip_suffix(" (installed)")
+                .unwrap_or(line.trim());
+
+            if !triple.is_empty() {
+                if let Ok(target) = Self::from_triple(triple) {
+                    targets.push(target);
+                }
+            }
+        }
+
+        Ok(targets)
+    }
+
+    /// Targets for the triples that parse, skipping the rest
+    fn from_triples(triples: &[String]) -> Vec<Self> {
+        triples
+            .iter()
+            .filter_map(|triple| Self::from_triple(triple).ok())
+            .collect()
+    }
+
+    /// Check if this target is currently installed
+    pub fn is_installed(&self) -> Result<bool> {
+        let installed = Self::detect_installed()?;
+        Ok(installed.iter().any(|t| t.triple == self.triple))
+    }
+
+    /// Install this target via rustup
+    ///
+    /// # Errors
+    /// Returns an error without rustup, naming what to install instead
+    pub fn install(&self) -> Result<()> {
+        let manager = ToolchainManager::new()?;
+        if let Some(standalone) = manager.standalone_toolchain() {
+            return Err(standalone.install_target_error(&self.triple));
+        }
+        crate::toolchain::require_network(&format!("install target '{}'", self.triple))?;
+
+        let output = Command::new("rustup")
+            .args(["target", "add", &self.triple])
+            .output()
+            .map_err(|e| Error::Toolchain(format!("Failed to run rustup: {e}")))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(Error::Toolchain(format!(
+                "Failed to install target {}: {}",
+                self.triple, stderr
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Resolve a target alias to a full target triple
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::target::Target;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let triple = Target::resolve_alias("linux")?;
+    /// assert_eq!(triple, "x86_64-unknown-linux-gnu");
+    ///
+    /// let triple = Target::resolve_alias("windows")?;
+    /// assert_eq!(triple, "x86_64-pc-windows-gnu");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resolve_alias(alias: &str) -> Result<String> {
+        let alias_lower = alias.to_lowercase();
+        let triple = match alias_lower.as_str() {
+            // Platform aliases
+            "linux" => "x86_64-unknown-linux-gnu",
+            "windows" => "x86_64-pc-windows-gnu",
+            "macos" => {
+                // Detect if we're on Apple Silicon
+                if let Ok(host) = Self::detect_host() {
+                    if host.arch == "aarch64" && host.os == "darwin" {
+                        "aarch64-apple-darwin"
+                    } else {
+                        "x86_64-apple-darwin"
+                    }
+                } else {
+                    "x86_64-apple-darwin"
+                }
+            }
+
+            // Architecture variants
+            "linux-arm64" | "linux-aarch64" => "aarch64-unknown-linux-gnu",
+            "linux-armv7" => "armv7-unknown-linux-gnueabihf",
+            "linux-musl" => "x86_64-unknown-linux-musl",
+            "linux-arm64-musl" => "aarch64-unknown-linux-musl",
+            "riscv" | "riscv64" | "linux-riscv64" => "riscv64gc-unknown-linux-gnu",
+            "s390x" | "linux-s390x" => "s390x-unknown-linux-gnu",
+
+            // Raspberry Pi OS: 64-bit, 32-bit, and the ARMv6 Pi 1 and Zero
+            "rpi" | "rpi64" => "aarch64-unknown-linux-gnu",
+            "rpi32" => "armv7-unknown-linux-gnueabihf",
+            "rpi-zero" | "rpi1" => "arm-unknown-linux-gnueabihf",
+
+            "windows-msvc" => "x86_64-pc-windows-msvc",
+            "windows-gnu" => "x86_64-pc-windows-gnu",
+            "windows-32" => "i686-pc-windows-gnu",
+
+            // BSDs
+            "freebsd" | "freebsd-x64" => "x86_64-unknown-freebsd",
+            "freebsd-arm64" => "aarch64-unknown-freebsd",
+            "netbsd" => "x86_64-unknown-netbsd",
+            "openbsd" => "x86_64-unknown-openbsd",
+
+            // Mobile platforms
+            "android" | "android-arm64" => "aarch64-linux-android",
+            "android-armv7" => "armv7-linux-androideabi",
+            "android-x86" => "x86_64-linux-android",
+
+            "ios" | "ios-arm64" => "aarch64-apple-ios",
+            "ios-sim" => "aarch64-apple-ios-sim",
+
+            // WebAssembly
+            "wasm" | "wasm32" => "wasm32-unknown-unknown",
+            "wasi" => "wasm32-wasi",
+
+            // If not an alias, assume it's a full triple (use original case)
+            _ => alias,
+        };
+
+        Ok(triple.to_string())
+    }
+
+    /// Classify a target into a tier based on its triple
+    fn classify_tier(triple: &str) -> TargetTier {
+        // Tier 1: Native compilation targets
+        let tier1 = [
+            "x86_64-unknown-linux-gnu",
+            "x86_64-unknown-linux-musl",
+            "x86_64-pc-windows-gnu",
+            "x86_64-apple-darwin",
+            "aarch64-apple-darwin",
+            "i686-pc-windows-gnu",
+            "i686-unknown-linux-gnu",
+        ];
+
+        // WebAssembly builds on any host with rustup's std and rust-lld
+        if tier1.contains(&triple) || triple.starts_with("wasm") {
+            return TargetTier::Native;
+        }
+
+        // BSDs link against their own libc, which a container image (or Zig,
+        // for FreeBSD) provides
+        if BSD_SYSTEMS
+            .iter()
+            .any(|os| triple.ends_with(&format!("-{os}")))
+        {
+            return TargetTier::Container;
+        }
+
+        // Tier 3: Specialized targets (mobile, embedded)
+        if triple.contains("android")
+            || triple.contains("ios")
+            || triple.starts_with("thumb")
+            || triple.starts_with("riscv")
+        {
+            return TargetTier::Specialized;
+        }
+
+        // Tier 2: Container-based (everything else)
+        TargetTier::Container
+    }
+
+    /// Whether this is a WebAssembly target, e.g. `wasm32-unknown-unknown` or `wasm32-wasip1`
+    #[must_use]
+    pub fn is_wasm(&self) -> bool {
+        self.arch.starts_with("wasm")
+    }
+
+    /// Check if native compilation is likely possible for this target
+    #[must_use]
+    pub fn supports_native_build(&self) -> bool {
+        matches!(self.tier, TargetTier::Native)
+    }
+
+    /// Check if this target requires container-based compilation
+    #[must_use]
+    pub fn requires_container(&self) -> bool {
+        matches!(self.tier, TargetTier::Container | TargetTier::Specialized)
+    }
+
+    /// Get the requirements needed to build for this target
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::target::Target;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let target = Target::from_triple("aarch64-unknown-linux-gnu")?;
+    /// let reqs = target.get_requirements();
+    /// if !reqs.are_satisfied() {
+    ///     println!("Missing tools for {}", target.triple);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn get_requirements(&self) -> TargetRequirements {
+        let mut reqs = RequirementDatabase::global()
+            .lookup(&self.triple)
+            .requirements();
+
+        // MSVC requires special setup (xwin or native Windows). rustc finds
+        // Visual Studio's tools itself, so they need not be on PATH; the
+        // linker found through vswhere stands in for them.
+        if self.os == "windows" && self.env.as_deref() == Some("msvc") {
+            if let Some(msvc) = MsvcTools::detect(&self.arch) {
+                reqs.tools = vec![msvc.linker().to_string_lossy().to_string()];
+            }
+        }
+
+        reqs
+    }
+
+    /// Whether this is a FreeBSD, NetBSD, OpenBSD or `DragonFly` BSD target
+    #[must_use]
+    pub fn is_bsd(&self) -> bool {
+        BSD_SYSTEMS.contains(&self.os.as_str())
+    }
+
+    /// Whether this is a bare-metal target without an operating system
+    /// (e.g., `thumbv7em-none-eabihf`, `riscv32imac-unknown-none-elf`)
+    #[must_use]
+    pub fn is_bare_metal(&self) -> bool {
+        self.vendor == "none" || self.os == "none"
+    }
+
+    /// Detect the linker that will be used for this target
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::target::Target;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let target = Target::from_triple("x86_64-unknown-linux-gnu")?;
+    /// if let Some(linker) = target.detect_linker() {
+    ///     println!("Linker: {}", linker);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn detect_linker(&self) -> Option<String> {
+        let reqs = self.get_requirements();
+
+        if let Some(linker) = reqs.linker {
+            // Check if the required linker is available
+            if TargetRequirements::is_command_available(&linker) {
+                return Some(linker);
+            }
+        }
+
+        // Try to detect alternative linkers
+        let mut alternatives = RequirementDatabase::global()
+            .lookup(&self.triple)
+            .alternatives;
+        if alternatives.is_empty() {
+            alternatives = vec!["gcc".into(), "clang".into(), "cc".into()];
+        }
+
+        alternatives
+            .into_iter()
+            .find(|linker| TargetRequirements::is_command_available(linker))
+    }
+
+    /// Check if we can build for this target without containers
+    ///
+    /// This checks both the target tier and whether required tools are available
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::target::Target;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let target = Target::from_triple("x86_64-unknown-linux-gnu")?;
+    /// let host = Target::detect_host()?;
+    ///
+    /// if target.can_cross_compile_from(&host) {
+    ///     println!("Can build natively!");
+    /// } else {
+    ///     println!("Need container or missing tools");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn can_cross_compile_from(&self, host: &Target) -> bool {
+        // Same target - can always build
+        if self.triple == host.triple {
+            return true;
+        }
+
+        // Check if it's a native-tier target
+        if !self.supports_native_build() {
+            return false;
+        }
+
+        // Check if required tools are available
+        let reqs = self.get_requirements();
+        reqs.are_satisfied()
+    }
+
+    /// Get installation instructions for missing requirements
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::target::Target;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let target = Target::from_triple("aarch64-unknown-linux-gnu")?;
+    /// let instructions = target.get_install_instructions();
+    /// for instruction in instructions {
+    ///     println!("  {}", instruction);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn get_install_instructions(&self) -> Vec<String> {
+        if self.get_requirements().are_satisfied() {
+            return Vec::new();
+        }
+
+        RequirementDatabase::global()
+            .lookup(&self.triple)
+            .install_instructions(&self.triple, std::env::consts::OS)
+            .unwrap_or_else(|| {
+                vec![
+                    format!(
+                        "# No automatic installation instructions available for {}",
+                        self.triple
+                    ),
+                    "# Consider using container-based build".to_string(),
+                ]
+            })
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.triple)
+    }
+}
+
+impl fmt::Display for TargetTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TargetTier::Native => write!(f, "Tier 1 (Native)"),
+            TargetTier::Container => write!(f, "Tier 2 (Container)"),
+            TargetTier::Specialized => write!(f, "Tier 3 (Specialized)"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_linux_target() {
+        let target = Target::from_triple("x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(target.arch, "x86_64");
+        assert_eq!(target.vendor, "unknown");
+        assert_eq!(target.os, "linux");
+        assert_eq!(target.env, Some("gnu".to_string()));
+        assert_eq!(target.tier, TargetTier::Native);
+    }
+
+    #[test]
+    fn test_parse_windows_target() {
+        let target = Target::from_triple("x86_64-pc-windows-msvc").unwrap();
+        assert_eq!(target.arch, "x86_64");
+        assert_eq!(target.vendor, "pc");
+        assert_eq!(target.os, "windows");
+        assert_eq!(target.env, Some("msvc".to_string()));
+    }
+
+    #[test]
+    fn test_parse_macos_target() {
+        let target = Target::from_triple("aarch64-apple-darwin").unwrap();
+        assert_eq!(target.arch, "aarch64");
+        assert_eq!(target.vendor, "apple");
+        assert_eq!(target.os, "darwin");
+        assert_eq!(target.env, None);
+        assert_eq!(target.tier, TargetTier::Native);
+    }
+
+    #[test]
+    fn test_parse_invalid_target() {
+        let result = Target::from_triple("invalid");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_parse_wasi_targets() {
+        let target = Target::from_triple("wasm32-wasip1").unwrap();
+        assert_eq!(target.vendor, "unknown");
+        assert_eq!(target.os, "wasi");
+        assert_eq!(target.env.as_deref(), Some("p1"));
+
+        let target = Target::from_triple("wasm32-wasi").unwrap();
+        assert_eq!(target.triple, "wasm32-wasi");
+        assert_eq!(target.env, None);
+        assert_eq!(target.tier, TargetTier::Native);
+    }
+
+    #[test]
+    fn test_parse_glibc_version() {
+        let target = Target::from_triple("aarch64-unknown-linux-gnu.2.17").unwrap();
+        assert_eq!(target.triple, "aarch64-unknown-linux-gnu");
+        assert_eq!(target.env.as_deref(), Some("gnu"));
+        assert_eq!(target.glibc.as_deref(), Some("2.17"));
+        assert_eq!(target.tier, TargetTier::Container);
+        assert_eq!(target.versioned_triple(), "aarch64-unknown-linux-gnu.2.17");
+
+        let target = Target::from_triple("armv7-unknown-linux-gnueabihf.2.28").unwrap();
+        assert_eq!(target.triple, "armv7-unknown-linux-gnueabihf");
+
+        let target = Target::from_triple("thumbv8m.main-none-eabi").unwrap();
+        assert_eq!(target.arch, "thumbv8m.main");
+        assert_eq!(target.glibc, None);
+
+        assert!(Target::from_triple("x86_64-unknown-linux-musl.2.17").is_err());
+        assert!(Target::from_triple("x86_64-unknown-linux-gnu.2").is_err());
+        assert!(Target::from_triple("x86_64-unknown-linux-gnu.3.1").is_err());
+        assert!(Target::from_triple("x86_64-unknown-linux-gnu.2.x").is_err());
+    }
+
+    #[test]
+    fn test_resolve_alias_linux() {
+        assert_eq!(
+            Target::resolve_alias("linux").unwrap(),
+            "x86_64-unknown-linux-gnu"
+        );
+    }
+
+    #[test]
+    fn test_resolve_alias_windows() {
+        assert_eq!(
+            Target::resolve_alias("windows").unwrap(),
+            "x86_64-pc-windows-gnu"
+        );
+    }
+
+    #[test]
+    fn test_resolve_alias_linux_arm64() {
+        assert_eq!(
+            Target::resolve_alias("linux-arm64").unwrap(),
+            "aarch64-unknown-linux-gnu"
+        );
+    }
+
+    #[test]
+    fn test_resolve_alias_riscv_s390x() {
+        assert_eq!(
+            Target::resolve_alias("riscv").unwrap(),
+            "riscv64gc-unknown-linux-gnu"
+        );
+        assert_eq!(
+            Target::resolve_alias("s390x").unwrap(),
+            "s390x-unknown-linux-gnu"
+        );
+    }
+
+    #[test]
+    fn test_resolve_alias_raspberry_pi() {
+        assert_eq!(
+            Target::resolve_alias("rpi").unwrap(),
+            "aarch64-unknown-linux-gnu"
+        );
+        assert_eq!(
+            Target::resolve_alias("rpi32").unwrap(),
+            "armv7-unknown-linux-gnueabihf"
+        );
+        assert_eq!(
+            Target::resolve_alias("rpi-zero").unwrap(),
+            "arm-unknown-linux-gnueabihf"
+        );
+    }
+
+    #[test]
+    fn test_resolve_alias_passthrough() {
+        assert_eq!(
+            Target::resolve_alias("x86_64-unknown-linux-gnu").unwrap(),
+            "x86_64-unknown-linux-gnu"
+        );
+    }
+
+    #[test]
+    fn test_tier_classification() {
+        let native = Target::from_triple("x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(native.tier, TargetTier::Native);
+        assert!(native.supports_native_build());
+        assert!(!native.requires_container());
+
+        let container = Target::from_triple("aarch64-unknown-linux-gnu").unwrap();
+        assert_eq!(container.tier, TargetTier::Container);
+        assert!(container.requires_container());
+
+        let specialized = Target::from_triple("thumbv7em-none-eabihf").unwrap();
+        assert_eq!(specialized.tier, TargetTier::Specialized);
+        assert!(specialized.requires_container());
+
+        let wasm = Target::from_triple("wasm32-unknown-unknown").unwrap();
+        assert_eq!(wasm.tier, TargetTier::Native);
+        assert!(wasm.is_wasm());
+        assert!(!wasm.requires_container());
+
+        for triple in ["x86_64-unknown-freebsd", "riscv64gc-unknown-freebsd"] {
+            let bsd = Target::from_triple(triple).unwrap();
+            assert!(bsd.is_bsd());
+            assert_eq!(bsd.tier, TargetTier::Container);
+        }
+    }
+
+    #[test]
+    fn test_target_display() {
+        let target = Target::from_triple("x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(format!("{target}"), "x86_64-unknown-linux-gnu");
+    }
+
+    #[test]
+    fn test_detect_host() {
+        // This test requires rustc to be installed
+        let result = Target::detect_host();
+        assert!(result.is_ok());
+        let host = result.unwrap();
+        assert!(!host.triple.is_empty());
+        assert!(!host.arch.is_empty());
+        assert!(!host.os.is_empty());
+    }
+
+    #[test]
+    fn test_target_requirements() {
+        let target = Target::from_triple("aarch64-unknown-linux-gnu").unwrap();
+        let reqs = target.get_requirements();
+
+        // Should have a linker requirement
+        assert!(reqs.linker.is_some());
+        assert_eq!(reqs.linker.unwrap(), "aarch64-linux-gnu-gcc");
+    }
+
+    #[test]
+    fn test_native_target_requirements() {
+        let target = Target::from_triple("x86_64-unknown-linux-gnu").unwrap();
+        let reqs = target.get_requirements();
+
+        // Native targets may not require special linkers
+        // Requirements should still be created
+        assert_eq!(reqs.tools.len(), 0);
+    }
+
+    #[test]
+    fn test_windows_target_requirements() {
+        let target = Target::from_triple("x86_64-pc-windows-gnu").unwrap();
+        let reqs = target.get_requirements();
+
+        assert!(reqs.linker.is_some());
+        assert_eq!(reqs.linker.unwrap(), "x86_64-w64-mingw32-gcc");
+    }
+
+    #[test]
+    fn test_can_cross_compile_same_target() {
+        let target1 = Target::from_triple("x86_64-unknown-linux-gnu").unwrap();
+        let target2 = Target::from_triple("x86_64-unknown-linux-gnu").unwrap();
+
+        assert!(target1.can_cross_compile_from(&target2));
+    }
+
+    #[test]
+    fn test_get_install_instructions() {
+        let target = Target::from_triple("aarch64-unknown-linux-gnu").unwrap();
+        let _instructions = target.get_install_instructions();
+
+        // Just verify the method works without panicking
+        // Instructions will vary based on whether tools are installed
+    }
+
+    #[test]
+    fn test_detect_linker() {
+        let target = Target::from_triple("x86_64-unknown-linux-gnu").unwrap();
+
+        // Should be able to detect some linker (gcc, clang, or cc)
+        // This test might fail if no compiler is installed, but that's expected
+        let linker = target.detect_linker();
+        // Just verify the method works without panicking
+        assert!(linker.is_some() || linker.is_none());
+    }
+
+    #[test]
+    fn test_requirements_none() {
+        let reqs = TargetRequirements::none();
+        assert!(reqs.linker.is_none());
+        assert_eq!(reqs.tools.len(), 0);
+        assert_eq!(reqs.system_libs.len(), 0);
+        assert_eq!(reqs.env_vars.len(), 0);
+    }
+
+    #[test]
+    fn test_android_requirements() {
+        let target = Target::from_triple("aarch64-linux-android").unwrap();
+        let reqs = target.get_requirements();
+
+        // Android should require NDK
+        assert!(!reqs.tools.is_empty());
+        assert!(reqs.tools.contains(&"ndk-build".to_string()));
+    }
+
+    #[test]
+    fn test_bare_metal_requirements() {
+        let target = Target::from_triple("thumbv7em-none-eabihf").unwrap();
+        assert!(target.is_bare_metal());
+        let reqs = target.get_requirements();
+        assert!(reqs.components.contains(&"rust-src".to_string()));
+
+        let target = Target::from_triple("riscv32imac-unknown-none-elf").unwrap();
+        assert!(target.is_bare_metal());
+
+        let target = Target::from_triple("x86_64-unknown-linux-gnu").unwrap();
+        assert!(!target.is_bare_metal());
+        assert!(target.get_requirements().components.is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+//! Target triples that were renamed between Rust releases
+//!
+//! Rust renamed `wasm32-wasi` to `wasm32-wasip1` in 1.78 and removed the old
+//! name in 1.84. Configurations written for one side of the rename break on
+//! the other, so triples are translated to whichever name the active
+//! toolchain provides.
+
+/// A target triple renamed in a Rust release
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetRename {
+    /// Name used by older toolchains
+    pub old: &'static str,
+
+    /// Name used by newer toolchains
+    pub new: &'static str,
+
+    /// First Rust release providing the new name, as (major, minor)
+    pub introduced: (u32, u32),
+
+    /// First Rust release no longer providing the old name, as (major, minor)
+    pub removed: (u32, u32),
+}
+
+/// Known target renames
+pub const TARGET_RENAMES: &[TargetRename] = &[TargetRename {
+    old: "wasm32-wasi",
+    new: "wasm32-wasip1",
+    introduced: (1, 78),
+    removed: (1, 84),
+}];
+
+/// A triple translated for a specific toolchain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NormalizedTriple {
+    /// Triple the toolchain provides
+    pub triple: String,
+
+    /// Triple that was requested, if it had to be translated
+    pub renamed_from: Option<String>,
+
+    /// Explanation of the translation, for display
+    pub warning: Option<String>,
+}
+
+/// The other name of a renamed triple, if `triple` was part of a rename
+#[must_use]
+pub fn renamed_counterpart(triple: &str) -> Option<&'static str> {
+    TARGET_RENAMES.iter().find_map(|rename| {
+        if rename.old == triple {
+            Some(rename.new)
+        } else if rename.new == triple {
+            Some(rename.old)
+        } else {
+            None
+        }
+    })
+}
+
+/// Translate a triple to the name provided by a rustc release
+///
+/// `rustc_release` is the release reported by `rustc -vV` (e.g. "1.84.0" or
+/// "1.80.0-nightly"). Triples are left unchanged when the release is unknown
+/// or provides the requested name.
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::target::normalize_triple;
+///
+/// let normalized = normalize_triple("wasm32-wasi", Some("1.84.0"));
+/// assert_eq!(normalized.triple, "wasm32-wasip1");
+/// assert_eq!(normalized.renamed_from.as_deref(), Some("wasm32-wasi"));
+///
+/// let normalized = normalize_triple("wasm32-wasip1", Some("1.75.0"));
+/// assert_eq!(normalized.triple, "wasm32-wasi");
+/// ```
+#[must_use]
+pub fn normalize_triple(triple: &str, rustc_release: Option<&str>) -> NormalizedTriple {
+    let unchanged = || NormalizedTriple {
+        triple: triple.to_string(),
+        renamed_from: None,
+        warning: None,
+    };
+
+    let Some(version) = rustc_release.and_then(parse_release) else {
+        return unchanged();
+    };
+    let release = rustc_release.unwrap_or_default();
+
+    for rename in TARGET_RENAMES {
+        if triple == rename.old && version >= rename.removed {
+            return NormalizedTriple {
+                triple: rename.new.to_string(),
+                renamed_from: Some(triple.to_string()),
+                warning: Some(format!(
+                    "Target '{}' was renamed to '{}' in Rust {}.{}; using '{}' for rustc {release}. Update your configuration to the new name",
+                    rename.old, rename.new, rename.introduced.0, rename.introduced.1, rename.new
+                )),
+            };
+        }
+        if triple == rename.new && version < rename.introduced {
+            return NormalizedTriple {
+                triple: rename.old.to_string(),
+                renamed_from: Some(triple.to_string()),
+                warning: Some(format!(
+                    "Target '{}' is not available before Rust {}.{}; using its former name '{}' for rustc {release}",
+                    rename.new, rename.introduced.0, rename.introduced.1, rename.old
+                )),
+            };
+        }
+    }
+
+    unchanged()
+}
+
+/// Parse the (major, minor) version from a rustc release string
+fn parse_release(release: &str) -> Option<(u32, u32)> {
+    let mut parts = release.split(['.', '-']);
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_release() {
+        assert_eq!(parse_release("1.78.0"), Some((1, 78)));
+        assert_eq!(parse_release("1.80.0-nightly"), Some((1, 80)));
+        assert_eq!(parse_release("nightly"), None);
+    }
+
+    #[test]
+    fn test_old_name_on_new_toolchain() {
+        let normalized = normalize_triple("wasm32-wasi", Some("1.84.1"));
+        assert_eq!(normalized.triple, "wasm32-wasip1");
+        assert!(normalized.warning.unwrap().contains("renamed"));
+    }
+
+    #[test]
+    fn test_new_name_on_old_toolchain() {
+        let normalized = normalize_triple("wasm32-wasip1", Some("1.77.2"));
+        assert_eq!(normalized.triple, "wasm32-wasi");
+        assert_eq!(normalized.renamed_from.as_deref(), Some("wasm32-wasip1"));
+    }
+
+    #[test]
+    fn test_both_names_available() {
+        // 1.78 to 1.83 provide both names
+        for triple in ["wasm32-wasi", "wasm32-wasip1"] {
+            let normalized = normalize_triple(triple, Some("1.80.0"));
+            assert_eq!(normalized.triple, triple);
+            assert!(normalized.warning.is_none());
+        }
+    }
+
+    #[test]
+    fn test_unknown_release_or_unrelated_triple() {
+        assert_eq!(normalize_triple("wasm32-wasi", None).triple, "wasm32-wasi");
+        assert!(normalize_triple("x86_64-unknown-linux-gnu", Some("1.84.0"))
+            .renamed_from
+            .is_none());
+    }
+
+    #[test]
+    fn test_renamed_counterpart() {
+        assert_eq!(renamed_counterpart("wasm32-wasi"), Some("wasm32-wasip1"));
+        assert_eq!(renamed_counterpart("wasm32-wasip1"), Some("wasm32-wasi"));
+        assert_eq!(renamed_counterpart("wasm32-unknown-unknown"), None);
+    }
+}
@@ -0,0 +1,385 @@
+//! Database of what each target needs to build
+//!
+//! The linker, tools and installation instructions of each target come from
+//! `requirements.toml`, which is built into xcargo, followed by the user's
+//! `targets.d/*.toml` files next to the user configuration (e.g.
+//! `~/.config/xcargo/targets.d`). Entries match triples with `*` patterns
+//! and are applied in order, so a user file can add a target or override
+//! part of a built-in one.
+
+use super::TargetRequirements;
+use crate::config::ConfigDiscovery;
+use crate::output::helpers;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The built-in database
+const BUILTIN: &str = include_str!("requirements.toml");
+
+/// Directory of user database files, next to the user configuration file
+pub const USER_DIR: &str = "targets.d";
+
+/// Host key of installation instructions that apply on any host
+const ANY_HOST: &str = "any";
+
+/// A database file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DatabaseFile {
+    #[serde(default)]
+    target: Vec<RequirementEntry>,
+}
+
+/// One or more triple patterns
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Patterns {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Patterns {
+    fn matches(&self, triple: &str) -> bool {
+        match self {
+            Self::One(pattern) => glob_match(pattern, triple),
+            Self::Many(patterns) => patterns.iter().any(|p| glob_match(p, triple)),
+        }
+    }
+}
+
+/// Requirements of the triples matching `triple`; unset fields leave what
+/// earlier entries set
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RequirementEntry {
+    triple: Patterns,
+    linker: Option<String>,
+    alternatives: Option<Vec<String>>,
+    tools: Option<Vec<String>>,
+    system_libs: Option<Vec<String>>,
+    env: Option<BTreeMap<String, String>>,
+    components: Option<Vec<String>>,
+    runner: Option<String>,
+    #[serde(default)]
+    install: BTreeMap<String, Vec<String>>,
+}
+
+/// Everything the database knows about one target
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequirementSpec {
+    /// Linker to link the target with
+    pub linker: Option<String>,
+    /// Linkers to look for when `linker` is not installed
+    pub alternatives: Vec<String>,
+    /// Programs other than the linker that must be on PATH
+    pub tools: Vec<String>,
+    /// Libraries the target's C toolchain must provide
+    pub system_libs: Vec<String>,
+    /// Environment variables the build needs, as (name, value)
+    pub env_vars: Vec<(String, String)>,
+    /// Rustup components the target needs
+    pub components: Vec<String>,
+    /// Command running test binaries on hosts that cannot execute them
+    /// (e.g. "qemu-s390x -L /usr/s390x-linux-gnu")
+    pub runner: Option<String>,
+    /// Installation instructions by host OS, or `"any"` for every host
+    pub install: BTreeMap<String, Vec<String>>,
+}
+
+impl RequirementSpec {
+    /// The requirements to check before building; the linker counts as a
+    /// required tool
+    #[must_use]
+    pub fn requirements(&self) -> TargetRequirements {
+        let mut tools: Vec<String> = self.linker.iter().cloned().collect();
+        tools.extend(
+            self.tools
+                .iter()
+                .filter(|tool| self.linker.as_ref() != Some(*tool))
+                .cloned(),
+        );
+        TargetRequirements {
+            linker: self.linker.clone(),
+            tools,
+            system_libs: self.system_libs.clone(),
+            env_vars: self.env_vars.clone(),
+            components: self.components.clone(),
+        }
+    }
+
+    /// Installation instructions for building `triple` on `host_os` (as in
+    /// `std::env::consts::OS`), if the database has any
+    #[must_use]
+    pub fn install_instructions(&self, triple: &str, host_os: &str) -> Option<Vec<String>> {
+        let lines = self
+            .install
+            .get(host_os)
+            .or_else(|| self.install.get(ANY_HOST))?;
+        Some(
+            lines
+                .iter()
+                .map(|line| line.replace("{triple}", triple))
+                .collect(),
+        )
+    }
+
+    fn apply(&mut self, entry: &RequirementEntry) {
+        if let Some(linker) = &entry.linker {
+            self.linker = Some(linker.clone()).filter(|l| !l.is_empty());
+        }
+        if let Some(alternatives) = &entry.alternatives {
+            self.alternatives = alternatives.clone();
+        }
+        if let Some(tools) = &entry.tools {
+            self.tools = tools.clone();
+        }
+        if let Some(libs) = &entry.system_libs {
+            self.system_libs = libs.clone();
+        }
+        if let Some(env) = &entry.env {
+            self.env_vars = env.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        }
+        if let Some(components) = &entry.components {
+            self.components = components.clone();
+        }
+        if let Some(runner) = &entry.runner {
+            self.runner = Some(runner.clone()).filter(|r| !r.is_empty());
+        }
+        for (host, lines) in &entry.install {
+            self.install.insert(host.clone(), lines.clone());
+        }
+    }
+}
+
+/// The target requirement database
+#[derive(Debug, Clone)]
+pub struct RequirementDatabase {
+    entries: Vec<RequirementEntry>,
+    sources: Vec<PathBuf>,
+    errors: Vec<String>,
+}
+
+impl RequirementDatabase {
+    /// The database built into xcargo, without user files
+    #[must_use]
+    pub fn builtin() -> Self {
+        let file: DatabaseFile =
+            toml::from_str(BUILTIN).expect("the built-in target database is valid");
+        Self {
+            entries: file.target,
+            sources: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// The built-in database extended with the user's files, loaded once
+    ///
+    /// Files that cannot be read or parsed are skipped with a warning and
+    /// listed in [`errors`](Self::errors).
+    pub fn global() -> &'static Self {
+        static DATABASE: OnceLock<RequirementDatabase> = OnceLock::new();
+        DATABASE.get_or_init(|| {
+            let mut database = Self::builtin();
+            if let Some(dir) = Self::user_dir().filter(|dir| dir.is_dir()) {
+                database.extend_from_dir(&dir);
+            }
+            for error in &database.errors {
+                helpers::warning(format!("Ignoring target database file {error}"));
+            }
+            database
+        })
+    }
+
+    /// Directory of the user's database files, whether or not it exists
+    #[must_use]
+    pub fn user_dir() -> Option<PathBuf> {
+        ConfigDiscovery::user_config_path()
+            .and_then(|path| path.parent().map(|dir| dir.join(USER_DIR)))
+    }
+
+    /// Add the entries of every `*.toml` file in `dir`, in file name order
+    pub fn extend_from_dir(&mut self, dir: &Path) {
+        let mut paths: Vec<PathBuf> = match std::fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                .collect(),
+            Err(e) => {
+                self.errors.push(format!("{}: {e}", dir.display()));
+                return;
+            }
+        };
+        paths.sort();
+
+        for path in paths {
+            let parsed = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| {
+                    toml::from_str::<DatabaseFile>(&text).map_err(|e| e.message().to_string())
+                });
+            match parsed {
+                Ok(file) => {
+                    self.entries.extend(file.target);
+                    self.sources.push(path);
+                }
+                Err(e) => self.errors.push(format!("{}: {e}", path.display())),
+            }
+        }
+    }
+
+    /// User files whose entries were added
+    #[must_use]
+    pub fn sources(&self) -> &[PathBuf] {
+        &self.sources
+    }
+
+    /// User files that were skipped, with the reason
+    #[must_use]
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// Number of entries, built-in and from user files
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the database has no entries
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Everything known about `triple`, merged from all matching entries
+    #[must_use]
+    pub fn lookup(&self, triple: &str) -> RequirementSpec {
+        let mut spec = RequirementSpec::default();
+        for entry in self.entries.iter().filter(|e| e.triple.matches(triple)) {
+            spec.apply(entry);
+        }
+        spec
+    }
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = text.strip_prefix(parts.next().unwrap_or_default()) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*-linux-android*", "armv7-linux-androideabi"));
+        assert!(glob_match("*-linux-android*", "aarch64-linux-android"));
+        assert!(glob_match(
+            "armv7-unknown-linux-*eabi",
+            "armv7-unknown-linux-musleabi"
+        ));
+        assert!(!glob_match(
+            "armv7-unknown-linux-*eabi",
+            "armv7-unknown-linux-gnueabihf"
+        ));
+        assert!(glob_match("x86_64-pc-windows-gnu", "x86_64-pc-windows-gnu"));
+        assert!(!glob_match(
+            "x86_64-pc-windows-gnu",
+            "x86_64-pc-windows-gnullvm"
+        ));
+        assert!(!glob_match("a*a", "a"));
+    }
+
+    #[test]
+    fn test_builtin_lookup() {
+        let database = RequirementDatabase::builtin();
+
+        let spec = database.lookup("armv7-linux-androideabi");
+        assert_eq!(spec.tools, ["ndk-build"]);
+        assert_eq!(spec.env_vars[0].0, "ANDROID_NDK_HOME");
+
+        let spec = database.lookup("riscv64gc-unknown-linux-gnu");
+        assert_eq!(spec.linker.as_deref(), Some("riscv64-linux-gnu-gcc"));
+        assert_eq!(
+            spec.runner.as_deref(),
+            Some("qemu-riscv64 -L /usr/riscv64-linux-gnu")
+        );
+        assert_eq!(spec.requirements().tools, ["riscv64-linux-gnu-gcc"]);
+
+        let spec = database.lookup("x86_64-unknown-freebsd");
+        let lines = spec
+            .install_instructions("x86_64-unknown-freebsd", "linux")
+            .unwrap();
+        assert!(lines.contains(&"xcargo build --target x86_64-unknown-freebsd --container".into()));
+        assert!(database
+            .lookup("x86_64-unknown-illumos")
+            .install_instructions("x86_64-unknown-illumos", "windows")
+            .is_none());
+
+        assert_eq!(
+            database.lookup("x86_64-unknown-linux-gnu"),
+            RequirementSpec::default()
+        );
+    }
+
+    #[test]
+    fn test_user_files_override() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("10-arm.toml"),
+            r##"
+                [[target]]
+                triple = "armv7-unknown-linux-gnueabihf"
+                linker = "armv7-custom-gcc"
+
+                [[target]]
+                triple = "x86_64-unknown-haiku"
+                tools = ["haiku-gcc"]
+                install.any = ["# Build {triple} on Haiku"]
+            "##,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("20-broken.toml"),
+            "[[target]]\nlinker = 1\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a database file").unwrap();
+
+        let mut database = RequirementDatabase::builtin();
+        database.extend_from_dir(dir.path());
+        assert_eq!(database.sources(), [dir.path().join("10-arm.toml")]);
+        assert_eq!(database.errors().len(), 1);
+        assert!(database.errors()[0].contains("20-broken.toml"));
+
+        // The linker is replaced; the built-in install instructions remain
+        let spec = database.lookup("armv7-unknown-linux-gnueabihf");
+        assert_eq!(spec.linker.as_deref(), Some("armv7-custom-gcc"));
+        assert!(spec.install.contains_key("linux"));
+
+        let spec = database.lookup("x86_64-unknown-haiku");
+        assert_eq!(spec.tools, ["haiku-gcc"]);
+        assert_eq!(
+            spec.install_instructions("x86_64-unknown-haiku", "linux"),
+            Some(vec!["# Build x86_64-unknown-haiku on Haiku".to_string()])
+        );
+    }
+}
@@ -0,0 +1,224 @@
+//! C and C++ compilers for build scripts
+//!
+//! Build scripts compile C and C++ dependencies with the `cc` crate, which
+//! takes the compilers from `CC_<target>`, `CXX_<target>` and `AR_<target>`,
+//! and extra flags from `CFLAGS_<target>` and `CXXFLAGS_<target>`, before it
+//! falls back to `CC` and guesses of its own. Setting the target's variables
+//! rather than `CC` keeps the host compiler for the build scripts and
+//! proc-macros that run on the host.
+//!
+//! Build scripts that generate bindings with bindgen parse C headers with
+//! libclang, which knows only the host's headers. The target compiler's
+//! sysroot and include directories are passed to it in
+//! `BINDGEN_EXTRA_CLANG_ARGS_<target>`.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// The `cc` crate's variable `name` for a target, e.g.
+/// `CC_aarch64_unknown_linux_gnu`
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::toolchain::cc_var;
+///
+/// assert_eq!(cc_var("CXX", "x86_64-pc-windows-gnu"), "CXX_x86_64_pc_windows_gnu");
+/// ```
+#[must_use]
+pub fn cc_var(name: &str, triple: &str) -> String {
+    format!("{name}_{}", triple.replace('-', "_"))
+}
+
+/// Tool prefix of a GCC cross linker, e.g. "aarch64-linux-gnu-" for
+/// "aarch64-linux-gnu-gcc"
+#[must_use]
+pub fn gnu_prefix(linker: &str) -> Option<String> {
+    let name = Path::new(linker).file_name()?.to_str()?;
+    let prefix = name.strip_suffix("gcc")?;
+    prefix.ends_with('-').then(|| prefix.to_string())
+}
+
+/// C compiler, C++ compiler, archiver and flags of a target
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CcToolchain {
+    /// C compiler
+    pub cc: Option<String>,
+
+    /// C++ compiler
+    pub cxx: Option<String>,
+
+    /// Archiver for static libraries
+    pub ar: Option<String>,
+
+    /// Extra flags for the C compiler
+    pub cflags: Vec<String>,
+
+    /// Extra flags for the C++ compiler
+    pub cxxflags: Vec<String>,
+
+    /// Extra arguments for bindgen's libclang
+    pub bindgen_args: Vec<String>,
+}
+
+impl CcToolchain {
+    /// The GCC cross toolchain a prefixed gcc belongs to, e.g.
+    /// `aarch64-linux-gnu-g++` and `aarch64-linux-gnu-ar` next to
+    /// `aarch64-linux-gnu-gcc`
+    ///
+    /// Returns `None` for compilers without a target prefix.
+    #[must_use]
+    pub fn gnu(gcc: &str) -> Option<Self> {
+        let prefix = gnu_prefix(gcc)?;
+        let sibling = |tool: &str| {
+            Path::new(gcc)
+                .with_file_name(format!("{prefix}{tool}"))
+                .display()
+                .to_string()
+        };
+        Some(Self {
+            cc: Some(gcc.to_string()),
+            cxx: Some(sibling("g++")),
+            ar: Some(sibling("ar")),
+            ..Self::default()
+        })
+    }
+
+    /// The `cc` crate's variables for `triple`, leaving out tools that are
+    /// not known and flags that are not set
+    #[must_use]
+    pub fn env(&self, triple: &str) -> BTreeMap<String, String> {
+        let tools = [("CC", &self.cc), ("CXX", &self.cxx), ("AR", &self.ar)]
+            .into_iter()
+            .filter_map(|(name, tool)| Some((cc_var(name, triple), tool.clone()?)));
+        let flags = [
+            ("CFLAGS", &self.cflags),
+            ("CXXFLAGS", &self.cxxflags),
+            ("BINDGEN_EXTRA_CLANG_ARGS", &self.bindgen_args),
+        ]
+        .into_iter()
+        .filter(|(_, flags)| !flags.is_empty())
+        .map(|(name, flags)| (cc_var(name, triple), flags.join(" ")));
+        tools.chain(flags).collect()
+    }
+}
+
+/// Arguments that point libclang at the headers `compiler` builds against:
+/// its sysroot, if it has one, and its system include directories
+///
+/// Returns no arguments if `compiler` cannot be run.
+#[must_use]
+pub fn bindgen_clang_args(compiler: &str) -> Vec<String> {
+    let sysroot = Command::new(compiler)
+        .arg("-print-sysroot")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| PathBuf::from(String::from_utf8_lossy(&output.stdout).trim()))
+        .filter(|sysroot| !sysroot.as_os_str().is_empty() && sysroot.is_dir());
+
+    // GCC and Clang, and so `zig cc`, list the include directories with -v
+    let include_dirs = Command::new(compiler)
+        .args(["-E", "-v", "-x", "c", "-"])
+        .stdin(Stdio::null())
+        .output()
+        .map(|output| parse_include_dirs(&String::from_utf8_lossy(&output.stderr)))
+        .unwrap_or_default();
+
+    sysroot
+        .map(|sysroot| format!("--sysroot={}", sysroot.display()))
+        .into_iter()
+        .chain(
+            include_dirs
+                .iter()
+                .map(|dir| format!("-isystem{}", dir.display())),
+        )
+        .map(|arg| {
+            // bindgen splits the variable like a shell does
+            if arg.contains(char::is_whitespace) {
+                format!("\"{arg}\"")
+            } else {
+                arg
+            }
+        })
+        .collect()
+}
+
+/// The `#include <...>` search list of a compiler's `-v` output
+fn parse_include_dirs(output: &str) -> Vec<PathBuf> {
+    output
+        .lines()
+        .skip_while(|line| !line.starts_with("#include <...> search starts here:"))
+        .skip(1)
+        .take_while(|line| !line.starts_with("End of search list."))
+        .map(|line| line.trim().trim_end_matches(" (framework directory)"))
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gnu_prefix() {
+        assert_eq!(
+            gnu_prefix("aarch64-linux-gnu-gcc").as_deref(),
+            Some("aarch64-linux-gnu-")
+        );
+        assert_eq!(
+            gnu_prefix("/opt/x-tools/bin/arm-linux-gnueabihf-gcc").as_deref(),
+            Some("arm-linux-gnueabihf-")
+        );
+        assert_eq!(gnu_prefix("gcc"), None);
+        assert_eq!(gnu_prefix("clang"), None);
+    }
+
+    #[test]
+    fn test_gnu_toolchain_env() {
+        let mut toolchain = CcToolchain::gnu("/opt/x-tools/bin/aarch64-linux-gnu-gcc").unwrap();
+        toolchain.cflags = vec!["-O2".to_string(), "-march=armv8-a".to_string()];
+        let env = toolchain.env("aarch64-unknown-linux-gnu");
+
+        assert_eq!(
+            env.get("CXX_aarch64_unknown_linux_gnu").map(String::as_str),
+            Some("/opt/x-tools/bin/aarch64-linux-gnu-g++")
+        );
+        assert_eq!(
+            env.get("AR_aarch64_unknown_linux_gnu").map(String::as_str),
+            Some("/opt/x-tools/bin/aarch64-linux-gnu-ar")
+        );
+        assert_eq!(
+            env.get("CFLAGS_aarch64_unknown_linux_gnu")
+                .map(String::as_str),
+            Some("-O2 -march=armv8-a")
+        );
+        assert!(!env.contains_key("CXXFLAGS_aarch64_unknown_linux_gnu"));
+        assert!(CcToolchain::gnu("clang").is_none());
+    }
+
+    #[test]
+    fn test_parse_include_dirs() {
+        let output = "\
+ignoring nonexistent directory \"/usr/aarch64-linux-gnu/sys-include\"
+#include \"...\" search starts here:
+#include <...> search starts here:
+ /usr/lib/gcc-cross/aarch64-linux-gnu/12/include
+ /usr/aarch64-linux-gnu/include
+ /Library/Frameworks (framework directory)
+End of search list.
+";
+        assert_eq!(
+            parse_include_dirs(output),
+            [
+                PathBuf::from("/usr/lib/gcc-cross/aarch64-linux-gnu/12/include"),
+                PathBuf::from("/usr/aarch64-linux-gnu/include"),
+                PathBuf::from("/Library/Frameworks"),
+            ]
+        );
+        assert!(parse_include_dirs("gcc: not found").is_empty());
+        assert!(bindgen_clang_args("xcargo-no-such-compiler").is_empty());
+    }
+}
@@ -0,0 +1,103 @@
+//! Detailed toolchain information for `xcargo toolchain info`
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A target installed in a toolchain's sysroot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstalledTarget {
+    /// Target triple
+    pub triple: String,
+
+    /// Size of the target's standard library on disk, in bytes
+    pub size_bytes: u64,
+}
+
+/// Details about an installed toolchain
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ToolchainInfo {
+    /// Toolchain name (e.g., "stable-x86_64-unknown-linux-gnu")
+    pub name: String,
+
+    /// Output of `rustc --version`
+    pub rustc_version: Option<String>,
+
+    /// Toolchain sysroot
+    pub sysroot: Option<PathBuf>,
+
+    /// Whether this is the default toolchain
+    pub is_default: bool,
+
+    /// Installed components
+    pub components: Vec<String>,
+
+    /// Installed targets with their size on disk
+    pub targets: Vec<InstalledTarget>,
+}
+
+/// Total size of the files under `path`, in bytes
+#[must_use]
+pub fn dir_size(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map(std::result::Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(std::fs::Metadata::is_file)
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Format a byte count with a binary unit (e.g., "12.3 MiB")
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::toolchain::format_size;
+///
+/// assert_eq!(format_size(512), "512 B");
+/// assert_eq!(format_size(3 * 1024 * 1024), "3.0 MiB");
+/// ```
+#[must_use]
+#[allow(clippy::cast_precision_loss)]
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = UNITS[0];
+    for next in &UNITS[1..] {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    format!("{size:.1} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dir_size() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("lib")).unwrap();
+        std::fs::write(temp.path().join("a"), [0u8; 100]).unwrap();
+        std::fs::write(temp.path().join("lib/b"), [0u8; 24]).unwrap();
+
+        assert_eq!(dir_size(temp.path()), 124);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024 * 1024), "5.0 GiB");
+    }
+}
@@ -0,0 +1,128 @@
+//! Linking with rustc's bundled `rust-lld`
+//!
+//! Every Rust toolchain ships `rust-lld` in its sysroot. Targets whose startup
+//! objects and C library also ship with Rust (`*-linux-musl`), or that need
+//! none at all (bare metal), can link with it without any system cross
+//! linker. Which targets qualify is a fixed compatibility table; crates with
+//! C dependencies still need a C compiler for the target.
+
+use std::path::{Path, PathBuf};
+
+/// Linker name passed to rustc, found in the toolchain's own tools directory
+pub const RUST_LLD: &str = "rust-lld";
+
+/// How a target links with `rust-lld`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LldSupport {
+    /// Links against the CRT objects and libc that ship with the target
+    SelfContained,
+    /// Bare-metal target that needs no C runtime
+    BareMetal,
+}
+
+/// Targets known to link with `rust-lld` and nothing from the host
+const COMPATIBLE_TARGETS: &[(&str, LldSupport)] = &[
+    ("x86_64-unknown-linux-musl", LldSupport::SelfContained),
+    ("aarch64-unknown-linux-musl", LldSupport::SelfContained),
+    ("i686-unknown-linux-musl", LldSupport::SelfContained),
+    ("armv7-unknown-linux-musleabihf", LldSupport::SelfContained),
+    ("arm-unknown-linux-musleabihf", LldSupport::SelfContained),
+    ("thumbv6m-none-eabi", LldSupport::BareMetal),
+    ("thumbv7m-none-eabi", LldSupport::BareMetal),
+    ("thumbv7em-none-eabi", LldSupport::BareMetal),
+    ("thumbv7em-none-eabihf", LldSupport::BareMetal),
+    ("thumbv8m.main-none-eabihf", LldSupport::BareMetal),
+    ("riscv32imc-unknown-none-elf", LldSupport::BareMetal),
+    ("riscv32imac-unknown-none-elf", LldSupport::BareMetal),
+    ("aarch64-unknown-none", LldSupport::BareMetal),
+];
+
+impl LldSupport {
+    /// Look up a target in the compatibility table
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::toolchain::LldSupport;
+    ///
+    /// assert_eq!(
+    ///     LldSupport::for_target("aarch64-unknown-linux-musl"),
+    ///     Some(LldSupport::SelfContained)
+    /// );
+    /// assert_eq!(LldSupport::for_target("x86_64-pc-windows-msvc"), None);
+    /// ```
+    #[must_use]
+    pub fn for_target(triple: &str) -> Option<Self> {
+        COMPATIBLE_TARGETS
+            .iter()
+            .find(|(compatible, _)| *compatible == triple)
+            .map(|(_, support)| *support)
+    }
+
+    /// Rustc flags that make `rust-lld` link the target on its own
+    #[must_use]
+    pub fn rustflags(self) -> Vec<String> {
+        let mut flags = vec!["-C".to_string(), "linker-flavor=ld.lld".to_string()];
+        if self == Self::SelfContained {
+            flags.extend(["-C".to_string(), "link-self-contained=yes".to_string()]);
+        }
+        flags
+    }
+}
+
+/// Path of `rust-lld` in a toolchain sysroot, if the toolchain ships it
+#[must_use]
+pub fn find_rust_lld(sysroot: &Path, host: &str) -> Option<PathBuf> {
+    let path = sysroot
+        .join("lib/rustlib")
+        .join(host)
+        .join("bin")
+        .join(format!("{RUST_LLD}{}", std::env::consts::EXE_SUFFIX));
+    path.is_file().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compatibility_table() {
+        assert_eq!(
+            LldSupport::for_target("thumbv7em-none-eabihf"),
+            Some(LldSupport::BareMetal)
+        );
+        // glibc and MinGW targets need the system's C library
+        assert_eq!(LldSupport::for_target("aarch64-unknown-linux-gnu"), None);
+        assert_eq!(LldSupport::for_target("x86_64-pc-windows-gnu"), None);
+    }
+
+    #[test]
+    fn test_rustflags() {
+        assert_eq!(
+            LldSupport::SelfContained.rustflags(),
+            [
+                "-C",
+                "linker-flavor=ld.lld",
+                "-C",
+                "link-self-contained=yes"
+            ]
+        );
+        assert_eq!(
+            LldSupport::BareMetal.rustflags(),
+            ["-C", "linker-flavor=ld.lld"]
+        );
+    }
+
+    #[test]
+    fn test_find_rust_lld() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let host = "x86_64-unknown-linux-gnu";
+        assert!(find_rust_lld(temp.path(), host).is_none());
+
+        let bin = temp.path().join("lib/rustlib").join(host).join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        let lld = bin.join(format!("rust-lld{}", std::env::consts::EXE_SUFFIX));
+        std::fs::write(&lld, "").unwrap();
+        assert_eq!(find_rust_lld(temp.path(), host), Some(lld));
+    }
+}
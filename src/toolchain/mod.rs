@@ -1,16 +1,60 @@
 //! Toolchain detection and management
 //!
 //! This module handles Rust toolchain detection, installation, and management
-//! through rustup integration.
-
+//! through rustup integration. Without rustup, a standalone toolchain found on
+//! PATH is used instead; see [`StandaloneToolchain`].
+
+mod cc;
+mod info;
+mod lld;
+mod msrv;
+pub mod msvc;
+mod network;
+mod offline;
+mod pin;
+mod standalone;
+mod sync;
 pub mod zig;
+mod zig_dist;
 use crate::error::{Error, Result};
+use crate::output::log;
+use crate::output::progress::BuildProgress;
+use crate::output::terminal::{read_segment, strip_ansi};
 use crate::target::Target;
-use std::process::Command;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
 use std::str;
 
+pub use cc::{bindgen_clang_args, cc_var, gnu_prefix, CcToolchain};
+pub use info::{dir_size, format_size, InstalledTarget, ToolchainInfo};
+pub use lld::{find_rust_lld, LldSupport, RUST_LLD};
+pub use msrv::RustVersion;
+pub use network::{
+    ca_bundle, container_ca_env, is_untrusted_certificate, proxy_env, tls_advice,
+    use_network_config, CONTAINER_CA_BUNDLE,
+};
+pub use offline::{is_offline, require_network, set_offline, OFFLINE_ENV};
+pub use pin::{ToolchainFile, ToolchainSelection, ToolchainSource};
+pub use standalone::{StandaloneToolchain, NO_RUSTUP_ENV};
+pub use sync::TargetSync;
+pub use zig_dist::{
+    install_zig, installed_zig_versions, managed_zig, managed_zig_dir, ZIG_INDEX_ENV,
+    ZIG_INDEX_URL,
+};
+
+/// Cargo's home directory: `$CARGO_HOME`, or `.cargo` in the user's home
+/// directory (`%USERPROFILE%` on Windows)
+#[must_use]
+pub fn cargo_home() -> Option<PathBuf> {
+    std::env::var_os("CARGO_HOME")
+        .filter(|home| !home.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))
+}
+
 /// Represents a Rust toolchain
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
 pub struct Toolchain {
     /// Toolchain name (e.g., "stable", "nightly", "1.70.0")
     pub name: String,
@@ -24,13 +68,24 @@ pub struct Toolchain {
 
 /// Toolchain manager for rustup operations
 pub struct ToolchainManager {
-    /// Path to rustup binary
-    rustup_path: String,
+    backend: Backend,
+}
+
+/// How the toolchain manager reaches the toolchain
+enum Backend {
+    /// Through rustup, with the path to the rustup binary
+    Rustup(String),
+    /// A toolchain installed without rustup; install operations are disabled
+    Standalone(StandaloneToolchain),
 }
 
 impl ToolchainManager {
     /// Create a new toolchain manager
     ///
+    /// Uses rustup when it is installed, and otherwise falls back to a
+    /// standalone toolchain (see [`standalone`](Self::standalone)). Setting
+    /// `XCARGO_NO_RUSTUP` forces standalone mode.
+    ///
     /// # Examples
     ///
     /// ```no_run
@@ -42,14 +97,90 @@ impl ToolchainManager {
     /// # }
     /// ```
     pub fn new() -> Result<Self> {
-        let rustup_path = Self::find_rustup()?;
-        Ok(Self { rustup_path })
+        if std::env::var_os(NO_RUSTUP_ENV).is_some() {
+            return Self::standalone();
+        }
+        match Self::find_rustup() {
+            Ok(rustup_path) => Ok(Self {
+                backend: Backend::Rustup(rustup_path),
+            }),
+            Err(_) => Self::standalone(),
+        }
+    }
+
+    /// Create a toolchain manager for the `rustc` and `cargo` on PATH,
+    /// without rustup
+    ///
+    /// Targets are available when their standard library is in the sysroot.
+    /// Installing toolchains, targets, or components fails with an error
+    /// explaining what to install manually.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xcargo::toolchain::ToolchainManager;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let manager = ToolchainManager::standalone()?;
+    /// assert!(!manager.uses_rustup());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn standalone() -> Result<Self> {
+        Ok(Self {
+            backend: Backend::Standalone(StandaloneToolchain::detect()?),
+        })
+    }
+
+    /// Whether toolchains are managed through rustup
+    #[must_use]
+    pub fn uses_rustup(&self) -> bool {
+        matches!(self.backend, Backend::Rustup(_))
+    }
+
+    /// The standalone toolchain in use, if running without rustup
+    #[must_use]
+    pub fn standalone_toolchain(&self) -> Option<&StandaloneToolchain> {
+        match &self.backend {
+            Backend::Rustup(_) => None,
+            Backend::Standalone(standalone) => Some(standalone),
+        }
+    }
+
+    /// Program to invoke cargo with
+    ///
+    /// With rustup this is the `cargo` proxy, which understands `+toolchain`.
+    #[must_use]
+    pub fn cargo_program(&self) -> &Path {
+        match &self.backend {
+            Backend::Rustup(_) => Path::new("cargo"),
+            Backend::Standalone(standalone) => &standalone.cargo,
+        }
     }
 
-    /// Find rustup binary in PATH
+    /// Find rustup binary in PATH, or in Cargo's bin directory
+    ///
+    /// rustup-init adds Cargo's bin directory to PATH, but shells opened
+    /// before, as is common on Windows, don't have it yet. It is then added
+    /// to xcargo's PATH, so rustup, cargo, and rustc are all found.
     fn find_rustup() -> Result<String> {
+        let rustup = "rustup".to_string();
+        if which::which(&rustup).is_err() {
+            let binary = format!("rustup{}", std::env::consts::EXE_SUFFIX);
+            let bin = cargo_home()
+                .map(|home| home.join("bin"))
+                .filter(|bin| bin.join(&binary).is_file());
+            if let Some(bin) = bin {
+                let path = std::env::var_os("PATH").unwrap_or_default();
+                let paths = std::iter::once(bin).chain(std::env::split_paths(&path));
+                if let Ok(path) = std::env::join_paths(paths) {
+                    std::env::set_var("PATH", path);
+                }
+            }
+        }
+
         // Try to run rustup --version to verify it exists
-        let output = Command::new("rustup")
+        let output = Command::new(&rustup)
             .arg("--version")
             .output()
             .map_err(|e| {
@@ -65,7 +196,7 @@ impl ToolchainManager {
             ));
         }
 
-        Ok("rustup".to_string())
+        Ok(rustup)
     }
 
     /// List all installed toolchains
@@ -85,7 +216,18 @@ impl ToolchainManager {
     /// # }
     /// ```
     pub fn list_toolchains(&self) -> Result<Vec<Toolchain>> {
-        let output = Command::new(&self.rustup_path)
+        let rustup = match &self.backend {
+            Backend::Rustup(path) => path,
+            Backend::Standalone(standalone) => {
+                return Ok(vec![Toolchain {
+                    name: standalone.release.clone(),
+                    is_default: true,
+                    targets: standalone.installed_targets(),
+                }])
+            }
+        };
+
+        let output = Command::new(rustup)
             .args(["toolchain", "list"])
             .output()
             .map_err(|e| Error::Toolchain(format!("Failed to list toolchains: {e}")))?;
@@ -104,11 +246,13 @@ impl ToolchainManager {
                 continue;
             }
 
-            let is_default = line.contains("(default)");
-            let name = line.replace("(default)", "").trim().to_string();
+            // "stable-x86_64-unknown-linux-gnu (default)" on older rustup,
+            // "stable-x86_64-unknown-linux-gnu (active, default)" on newer
+            let (name, annotations) = line.split_once(' ').unwrap_or((line, ""));
+            let is_default = annotations.contains("default");
 
             toolchains.push(Toolchain {
-                name,
+                name: name.to_string(),
                 is_default,
                 targets: Vec::new(), // Will be populated if needed
             });
@@ -138,7 +282,15 @@ impl ToolchainManager {
     /// # }
     /// ```
     pub fn list_targets(&self, toolchain: &str) -> Result<Vec<String>> {
-        let output = Command::new(&self.rustup_path)
+        let rustup = match &self.backend {
+            Backend::Rustup(path) => path,
+            Backend::Standalone(standalone) => {
+                require_standalone(standalone, toolchain)?;
+                return Ok(standalone.installed_targets());
+            }
+        };
+
+        let output = Command::new(rustup)
             .args(["target", "list", "--installed", "--toolchain", toolchain])
             .output()
             .map_err(|e| Error::Toolchain(format!("Failed to list targets: {e}")))?;
@@ -161,8 +313,53 @@ impl ToolchainManager {
         Ok(targets)
     }
 
+    /// List the targets a toolchain can be used with, installed or not
+    ///
+    /// With rustup these are the targets rustup distributes a standard
+    /// library for. Without rustup they are the targets `rustc --print
+    /// target-list` names, which includes targets that need `-Z build-std`.
+    pub fn list_available_targets(&self, toolchain: &str) -> Result<Vec<String>> {
+        let rustup = match &self.backend {
+            Backend::Rustup(path) => path,
+            Backend::Standalone(standalone) => {
+                require_standalone(standalone, toolchain)?;
+                return standalone.supported_targets();
+            }
+        };
+
+        let output = Command::new(rustup)
+            .args(["target", "list", "--toolchain", toolchain])
+            .output()
+            .map_err(|e| Error::Toolchain(format!("Failed to list targets: {e}")))?;
+
+        if !output.status.success() {
+            return Err(Error::Toolchain(format!(
+                "Failed to list available targets for toolchain '{toolchain}'"
+            )));
+        }
+
+        let stdout = str::from_utf8(&output.stdout)
+            .map_err(|e| Error::Toolchain(format!("Invalid UTF-8 in rustup output: {e}")))?;
+
+        Ok(stdout
+            .lines()
+            .map(str::trim)
+            .map(|line| line.strip_suffix(" (installed)").unwrap_or(line))
+            .filter(|line| !line.is_empty())
+            .map(ToString::to_string)
+            .collect())
+    }
+
     /// Check if a target is installed for a specific toolchain
+    ///
+    /// Without rustup, this checks that the target's standard library is
+    /// present in the sysroot.
     pub fn is_target_installed(&self, toolchain: &str, target: &str) -> Result<bool> {
+        if let Backend::Standalone(standalone) = &self.backend {
+            require_standalone(standalone, toolchain)?;
+            return Ok(standalone.has_target(target));
+        }
+
         let targets = self.list_targets(toolchain)?;
         Ok(targets.iter().any(|t| t == target))
     }
@@ -181,23 +378,65 @@ impl ToolchainManager {
     /// # }
     /// ```
     pub fn install_target(&self, toolchain: &str, target: &str) -> Result<()> {
-        use crate::output::helpers;
+        let rustup = match &self.backend {
+            Backend::Rustup(path) => path,
+            Backend::Standalone(standalone) => return Err(standalone.install_target_error(target)),
+        };
+
+        require_network(&format!("install target '{target}'"))?;
+        let progress = BuildProgress::new(target, "Installing");
+
+        let mut cmd = Command::new(rustup);
+        cmd.args(["target", "add", target, "--toolchain", toolchain]);
+        let (status, stderr) = match run_with_progress(cmd, &progress) {
+            Ok(output) => output,
+            Err(e) => {
+                progress.finish_error("could not run rustup");
+                return Err(e);
+            }
+        };
+
+        if !status.success() {
+            progress.finish_error(&format!("rustup failed ({status})"));
+            return Err(Error::Toolchain(format!(
+                "Failed to install target '{target}' for toolchain '{toolchain}': {stderr}{}",
+                tls_advice(&stderr)
+            )));
+        }
 
-        helpers::progress(format!("Installing target {target} for toolchain {toolchain}"));
+        progress.finish_success();
+        Ok(())
+    }
 
-        let output = Command::new(&self.rustup_path)
-            .args(["target", "add", target, "--toolchain", toolchain])
+    /// Remove a target's standard library from a toolchain
+    ///
+    /// # Errors
+    /// Returns an error without rustup, or if rustup fails
+    pub fn remove_target(&self, toolchain: &str, target: &str) -> Result<()> {
+        let rustup = match &self.backend {
+            Backend::Rustup(path) => path,
+            Backend::Standalone(standalone) => {
+                return Err(standalone.unsupported(
+                    &format!("remove target '{target}'"),
+                    "Remove the target's standard library with your system package manager",
+                ))
+            }
+        };
+
+        let mut cmd = Command::new(rustup);
+        cmd.args(["target", "remove", target, "--toolchain", toolchain]);
+        log::command(&cmd);
+        let output = cmd
             .output()
-            .map_err(|e| Error::Toolchain(format!("Failed to install target: {e}")))?;
+            .map_err(|e| Error::Toolchain(format!("Failed to remove target: {e}")))?;
 
         if !output.status.success() {
             let stderr = str::from_utf8(&output.stderr).unwrap_or("<invalid UTF-8>");
             return Err(Error::Toolchain(format!(
-                "Failed to install target '{target}' for toolchain '{toolchain}': {stderr}"
+                "Failed to remove target '{target}' from toolchain '{toolchain}': {}",
+                stderr.trim()
             )));
         }
-
-        helpers::success(format!("Installed target {target}"));
         Ok(())
     }
 
@@ -225,17 +464,31 @@ impl ToolchainManager {
     pub fn install_toolchain(&self, toolchain: &str) -> Result<()> {
         use crate::output::helpers;
 
+        let rustup = match &self.backend {
+            Backend::Rustup(path) => path,
+            Backend::Standalone(standalone) => {
+                return Err(standalone.unsupported(
+                    &format!("install toolchain '{toolchain}'"),
+                    &format!("Set RUSTC and CARGO to a {toolchain} toolchain"),
+                ))
+            }
+        };
+
+        require_network(&format!("install toolchain '{toolchain}'"))?;
         helpers::progress(format!("Installing toolchain {toolchain}"));
 
-        let output = Command::new(&self.rustup_path)
-            .args(["toolchain", "install", toolchain])
+        let mut cmd = Command::new(rustup);
+        cmd.args(["toolchain", "install", toolchain]);
+        log::command(&cmd);
+        let output = cmd
             .output()
             .map_err(|e| Error::Toolchain(format!("Failed to install toolchain: {e}")))?;
 
         if !output.status.success() {
             let stderr = str::from_utf8(&output.stderr).unwrap_or("<invalid UTF-8>");
             return Err(Error::Toolchain(format!(
-                "Failed to install toolchain '{toolchain}': {stderr}"
+                "Failed to install toolchain '{toolchain}': {stderr}{}",
+                tls_advice(stderr)
             )));
         }
 
@@ -245,6 +498,10 @@ impl ToolchainManager {
 
     /// Check if a toolchain is installed
     pub fn is_toolchain_installed(&self, toolchain: &str) -> Result<bool> {
+        if let Backend::Standalone(standalone) = &self.backend {
+            return Ok(standalone.matches(toolchain));
+        }
+
         let toolchains = self.list_toolchains()?;
         Ok(toolchains.iter().any(|t| t.name.starts_with(toolchain)))
     }
@@ -257,11 +514,185 @@ impl ToolchainManager {
         self.install_toolchain(toolchain)
     }
 
+    /// List installed components for a specific toolchain
+    ///
+    /// Host-specific components are listed with their host suffix, e.g.
+    /// `llvm-tools-x86_64-unknown-linux-gnu`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xcargo::toolchain::ToolchainManager;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let manager = ToolchainManager::new()?;
+    /// let components = manager.list_components("stable")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn list_components(&self, toolchain: &str) -> Result<Vec<String>> {
+        let rustup = match &self.backend {
+            Backend::Rustup(path) => path,
+            Backend::Standalone(standalone) => {
+                require_standalone(standalone, toolchain)?;
+                return Ok(standalone.installed_components());
+            }
+        };
+
+        let output = Command::new(rustup)
+            .args(["component", "list", "--installed", "--toolchain", toolchain])
+            .output()
+            .map_err(|e| Error::Toolchain(format!("Failed to list components: {e}")))?;
+
+        if !output.status.success() {
+            let stderr = str::from_utf8(&output.stderr).unwrap_or("<invalid UTF-8>");
+            return Err(Error::Toolchain(format!(
+                "Failed to list components for toolchain '{toolchain}': {stderr}"
+            )));
+        }
+
+        let stdout = str::from_utf8(&output.stdout)
+            .map_err(|e| Error::Toolchain(format!("Invalid UTF-8 in rustup output: {e}")))?;
+
+        Ok(stdout
+            .lines()
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+
+    /// Check if a component is installed for a specific toolchain
+    pub fn is_component_installed(&self, toolchain: &str, component: &str) -> Result<bool> {
+        let installed = self.list_components(toolchain)?;
+        Ok(installed.iter().any(|c| component_matches(c, component)))
+    }
+
+    /// Components from `components` that are not installed for a toolchain
+    pub fn missing_components(
+        &self,
+        toolchain: &str,
+        components: &[String],
+    ) -> Result<Vec<String>> {
+        let installed = self.list_components(toolchain)?;
+        Ok(components
+            .iter()
+            .filter(|component| !installed.iter().any(|c| component_matches(c, component)))
+            .cloned()
+            .collect())
+    }
+
+    /// Install a component for a specific toolchain
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xcargo::toolchain::ToolchainManager;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let manager = ToolchainManager::new()?;
+    /// manager.install_component("nightly", "rust-src")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn install_component(&self, toolchain: &str, component: &str) -> Result<()> {
+        use crate::output::helpers;
+
+        let rustup = match &self.backend {
+            Backend::Rustup(path) => path,
+            Backend::Standalone(standalone) => {
+                return Err(standalone.unsupported(
+                    &format!("install component '{component}'"),
+                    &format!("Install {component} with your system package manager"),
+                ))
+            }
+        };
+
+        require_network(&format!("install component '{component}'"))?;
+        helpers::progress(format!(
+            "Installing component {component} for toolchain {toolchain}"
+        ));
+
+        let mut cmd = Command::new(rustup);
+        cmd.args(["component", "add", component, "--toolchain", toolchain]);
+        log::command(&cmd);
+        let output = cmd
+            .output()
+            .map_err(|e| Error::Toolchain(format!("Failed to install component: {e}")))?;
+
+        if !output.status.success() {
+            let stderr = str::from_utf8(&output.stderr).unwrap_or("<invalid UTF-8>");
+            return Err(Error::Toolchain(format!(
+                "Failed to install component '{component}' for toolchain '{toolchain}': {stderr}{}",
+                tls_advice(stderr)
+            )));
+        }
+
+        helpers::success(format!("Installed component {component}"));
+        Ok(())
+    }
+
+    /// Ensure a component is installed, installing it if necessary
+    pub fn ensure_component(&self, toolchain: &str, component: &str) -> Result<()> {
+        if self.is_component_installed(toolchain, component)? {
+            return Ok(());
+        }
+        self.install_component(toolchain, component)
+    }
+
+    /// Install missing components, asking for confirmation unless `assume_yes` is set
+    ///
+    /// Without a terminal to ask on, missing components are an error unless
+    /// `assume_yes` is set.
+    pub fn ensure_components(
+        &self,
+        toolchain: &str,
+        components: &[String],
+        assume_yes: bool,
+    ) -> Result<()> {
+        use std::io::IsTerminal;
+
+        let missing = self.missing_components(toolchain, components)?;
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        let list = missing.join(", ");
+        if let Backend::Standalone(standalone) = &self.backend {
+            return Err(standalone.unsupported(
+                &format!("install missing components for toolchain '{toolchain}': {list}"),
+                "Install them with your system package manager",
+            ));
+        }
+        if !assume_yes {
+            let confirmed = std::io::stdin().is_terminal()
+                && inquire::Confirm::new(&format!(
+                    "Toolchain '{toolchain}' is missing {list}. Install now?"
+                ))
+                .with_default(true)
+                .prompt()
+                .map_err(|e| Error::Prompt(e.to_string()))?;
+
+            if !confirmed {
+                return Err(Error::Toolchain(format!(
+                    "Missing components for toolchain '{toolchain}': {list}. Re-run with --yes to install them, or run: rustup component add {} --toolchain {toolchain}",
+                    missing.join(" ")
+                )));
+            }
+        }
+
+        for component in &missing {
+            self.install_component(toolchain, component)?;
+        }
+        Ok(())
+    }
+
     /// Prepare environment for cross-compilation to a target
     ///
     /// This ensures:
     /// 1. The specified toolchain is installed
     /// 2. The target is added to the toolchain
+    /// 3. Components the target needs (e.g. `rust-src`) are installed, after
+    ///    asking for confirmation
     ///
     /// # Examples
     ///
@@ -277,18 +708,207 @@ impl ToolchainManager {
     /// # }
     /// ```
     pub fn prepare_target(&self, toolchain: &str, target: &Target) -> Result<()> {
+        self.prepare_target_with_components(toolchain, target, &[], false)
+    }
+
+    /// Prepare a target like [`prepare_target`](Self::prepare_target), also
+    /// ensuring `extra_components`; missing components are installed without
+    /// asking if `assume_yes` is set
+    pub fn prepare_target_with_components(
+        &self,
+        toolchain: &str,
+        target: &Target,
+        extra_components: &[String],
+        assume_yes: bool,
+    ) -> Result<()> {
         // Ensure toolchain is installed
         self.ensure_toolchain(toolchain)?;
 
         // Ensure target is installed
         self.ensure_target(toolchain, &target.triple)?;
 
+        // Ensure components are installed
+        let mut components = target.get_requirements().components;
+        for component in extra_components {
+            if !components.contains(component) {
+                components.push(component.clone());
+            }
+        }
+        self.ensure_components(toolchain, &components, assume_yes)?;
+
         Ok(())
     }
 
+    /// Run a tool from a toolchain through `rustup run` and return its trimmed stdout
+    ///
+    /// Without rustup, `rustc` and `cargo` are run directly.
+    fn run_tool(&self, toolchain: &str, tool: &str, args: &[&str]) -> Option<String> {
+        let mut cmd = match &self.backend {
+            Backend::Rustup(rustup) => {
+                let mut cmd = Command::new(rustup);
+                cmd.args(["run", toolchain, tool]);
+                cmd
+            }
+            Backend::Standalone(standalone) => match tool {
+                "rustc" => Command::new(&standalone.rustc),
+                "cargo" => Command::new(&standalone.cargo),
+                _ => Command::new(tool),
+            },
+        };
+        let output = cmd.args(args).output().ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (!stdout.is_empty()).then_some(stdout)
+    }
+
+    /// Gather details about an installed toolchain: rustc version,
+    /// components, and installed targets with their size on disk
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xcargo::toolchain::ToolchainManager;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let manager = ToolchainManager::new()?;
+    /// let info = manager.toolchain_info("stable")?;
+    /// println!("{:?}", info.rustc_version);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn toolchain_info(&self, toolchain: &str) -> Result<ToolchainInfo> {
+        let installed = self.list_toolchains()?;
+        // "1.78" finds "1.78-x86_64-unknown-linux-gnu", but "1.7" doesn't
+        // find "1.78.0-x86_64-unknown-linux-gnu"
+        let prefix = format!("{toolchain}-");
+        let Some(found) = installed
+            .iter()
+            .find(|t| t.name == toolchain)
+            .or_else(|| installed.iter().find(|t| t.name.starts_with(&prefix)))
+        else {
+            let install_hint = if self.uses_rustup() {
+                format!("rustup toolchain install {toolchain}")
+            } else {
+                format!("set RUSTC and CARGO to a {toolchain} toolchain")
+            };
+            return Err(Error::ToolchainMissing {
+                toolchain: toolchain.to_string(),
+                install_hint,
+            });
+        };
+
+        let rustc_version = self.run_tool(&found.name, "rustc", &["--version"]);
+        let sysroot = self
+            .run_tool(&found.name, "rustc", &["--print", "sysroot"])
+            .map(std::path::PathBuf::from);
+
+        let targets = self
+            .list_targets(&found.name)?
+            .into_iter()
+            .map(|triple| {
+                let size_bytes = sysroot
+                    .as_ref()
+                    .map_or(0, |root| dir_size(&root.join("lib/rustlib").join(&triple)));
+                InstalledTarget { triple, size_bytes }
+            })
+            .collect();
+
+        Ok(ToolchainInfo {
+            name: found.name.clone(),
+            rustc_version,
+            sysroot,
+            is_default: found.is_default,
+            components: self.list_components(&found.name)?,
+            targets,
+        })
+    }
+
+    /// Release of a toolchain's rustc as reported by `rustc -vV`, e.g. "1.78.0"
+    ///
+    /// Returns `None` if the toolchain's rustc cannot be run.
+    #[must_use]
+    pub fn rustc_release(&self, toolchain: &str) -> Option<String> {
+        if let Backend::Standalone(standalone) = &self.backend {
+            return Some(standalone.release.clone());
+        }
+        let output = self.run_tool(toolchain, "rustc", &["-vV"])?;
+        standalone::parse_verbose_version(&output).map(|(release, _)| release)
+    }
+
+    /// A toolchain's rustc as reported by `rustc --version`, e.g.
+    /// "rustc 1.78.0 (9b00956e5 2024-04-29)"
+    ///
+    /// Returns `None` if the toolchain's rustc cannot be run.
+    #[must_use]
+    pub fn rustc_version(&self, toolchain: &str) -> Option<String> {
+        self.run_tool(toolchain, "rustc", &["--version"])
+    }
+
+    /// Sysroot of a toolchain as reported by `rustc --print sysroot`
+    ///
+    /// Returns `None` if the toolchain's rustc cannot be run.
+    #[must_use]
+    pub fn sysroot(&self, toolchain: &str) -> Option<std::path::PathBuf> {
+        if let Backend::Standalone(standalone) = &self.backend {
+            return Some(standalone.sysroot.clone());
+        }
+        self.run_tool(toolchain, "rustc", &["--print", "sysroot"])
+            .map(std::path::PathBuf::from)
+    }
+
+    /// Resolve the toolchain to build with in the current directory
+    ///
+    /// See [`ToolchainSelection::resolve`] for the precedence rules.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use xcargo::toolchain::ToolchainManager;
+    ///
+    /// # fn example() -> xcargo::Result<()> {
+    /// let manager = ToolchainManager::new()?;
+    /// let selection = manager.resolve_toolchain(None, None)?;
+    /// println!("Using {} (from {})", selection.name, selection.source);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resolve_toolchain(
+        &self,
+        requested: Option<&str>,
+        configured: Option<&str>,
+    ) -> Result<ToolchainSelection> {
+        let file = ToolchainFile::find(&std::env::current_dir()?)?;
+
+        Ok(ToolchainSelection::resolve(
+            requested,
+            configured,
+            file.as_ref(),
+            || {
+                self.get_default_toolchain()
+                    .ok()
+                    .flatten()
+                    .map(|tc| tc.name)
+            },
+        ))
+    }
+
     /// Get rustup home directory
     pub fn get_rustup_home(&self) -> Result<std::path::PathBuf> {
-        let output = Command::new(&self.rustup_path)
+        let rustup = match &self.backend {
+            Backend::Rustup(path) => path,
+            Backend::Standalone(standalone) => {
+                return Err(Error::Toolchain(format!(
+                    "No rustup home: xcargo is using the standalone toolchain at {}",
+                    standalone.sysroot.display()
+                )))
+            }
+        };
+
+        let output = Command::new(rustup)
             .args(["show", "home"])
             .output()
             .map_err(|e| Error::Toolchain(format!("Failed to get rustup home: {e}")))?;
@@ -308,7 +928,18 @@ impl ToolchainManager {
 
     /// Get information about the active toolchain
     pub fn show_active_toolchain(&self) -> Result<String> {
-        let output = Command::new(&self.rustup_path)
+        let rustup = match &self.backend {
+            Backend::Rustup(path) => path,
+            Backend::Standalone(standalone) => {
+                return Ok(format!(
+                    "{} (standalone, {})",
+                    standalone.release,
+                    standalone.sysroot.display()
+                ))
+            }
+        };
+
+        let output = Command::new(rustup)
             .args(["show", "active-toolchain"])
             .output()
             .map_err(|e| Error::Toolchain(format!("Failed to get active toolchain: {e}")))?;
@@ -326,11 +957,104 @@ impl ToolchainManager {
     }
 }
 
+/// Fail unless `toolchain` names the standalone toolchain
+fn require_standalone(standalone: &StandaloneToolchain, toolchain: &str) -> Result<()> {
+    if standalone.matches(toolchain) {
+        return Ok(());
+    }
+    Err(Error::Toolchain(format!(
+        "Toolchain '{toolchain}' is not available: rustup is not installed and the standalone toolchain at {} is rustc {} ({})",
+        standalone.sysroot.display(),
+        standalone.release,
+        standalone.channel()
+    )))
+}
+
+/// Whether an installed component (as listed by rustup) satisfies a requested one
+///
+/// Host-specific components are listed with a host suffix, and `-preview`
+/// components are listed without it (`llvm-tools-preview` is installed as
+/// `llvm-tools-x86_64-unknown-linux-gnu`).
+fn component_matches(installed: &str, requested: &str) -> bool {
+    let base = requested.strip_suffix("-preview").unwrap_or(requested);
+    installed == requested
+        || installed == base
+        || installed
+            .strip_prefix(base)
+            .is_some_and(|rest| rest.starts_with('-') && rest[1..].contains('-'))
+}
+
 // Note: ToolchainManager::new() can fail if rustup is not installed.
 // Users should call new() directly instead of relying on Default,
 // which is only provided for convenience in tests and examples where
 // rustup is guaranteed to be available.
 
+/// Run rustup, showing the download and install steps it reports on
+/// `progress`
+///
+/// Returns rustup's exit status and the lines it printed to stderr, without
+/// progress redraws, for error messages.
+fn run_with_progress(mut cmd: Command, progress: &BuildProgress) -> Result<(ExitStatus, String)> {
+    cmd.env("RUSTUP_TERM_PROGRESS_WHEN", "always")
+        .env("RUSTUP_TERM_COLOR", "never")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    log::command(&cmd);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| Error::Toolchain(format!("Failed to run rustup: {e}")))?;
+
+    let mut stderr = String::new();
+    if let Some(pipe) = child.stderr.take() {
+        let mut reader = BufReader::new(pipe);
+        let mut segment = Vec::new();
+        while read_segment(&mut reader, &mut segment)? > 0 {
+            let text = String::from_utf8_lossy(&segment);
+            let line = strip_ansi(text.trim_end_matches(['\r', '\n']));
+            if let Some(step) = rustup_step(&line) {
+                progress.set_message(&step);
+            }
+            if text.ends_with('\n') {
+                stderr.push_str(&line);
+                stderr.push('\n');
+            }
+            segment.clear();
+        }
+    }
+    let status = child
+        .wait()
+        .map_err(|e| Error::Toolchain(format!("Failed to run rustup: {e}")))?;
+    Ok((status, stderr))
+}
+
+/// What a line of rustup's output says it is doing, for a progress message
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::toolchain::rustup_step;
+///
+/// assert_eq!(
+///     rustup_step("info: downloading component 'rust-std'").as_deref(),
+///     Some("downloading component 'rust-std'")
+/// );
+/// assert_eq!(
+///     rustup_step(" 12.5 MiB /  25.0 MiB ( 50 %)   4.1 MiB/s in  3s").as_deref(),
+///     Some("12.5 MiB / 25.0 MiB ( 50 %) 4.1 MiB/s in 3s")
+/// );
+/// assert_eq!(rustup_step("   "), None);
+/// ```
+#[must_use]
+pub fn rustup_step(line: &str) -> Option<String> {
+    let line = line.trim();
+    let line = line.strip_prefix("info:").unwrap_or(line);
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if words.is_empty() {
+        return None;
+    }
+    Some(words.join(" "))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -343,6 +1067,20 @@ mod tests {
         assert_eq!(result.unwrap(), "rustup");
     }
 
+    #[test]
+    fn test_standalone_manager() {
+        let Ok(manager) = ToolchainManager::standalone() else {
+            return;
+        };
+        assert!(!manager.uses_rustup());
+
+        let toolchains = manager.list_toolchains().unwrap();
+        assert_eq!(toolchains.len(), 1);
+        assert!(toolchains[0].is_default);
+        assert!(manager.install_toolchain("no-such-toolchain").is_err());
+        assert!(manager.get_rustup_home().is_err());
+    }
+
     #[test]
     fn test_new_toolchain_manager() {
         let manager = ToolchainManager::new();
@@ -464,4 +1202,22 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_component_matches() {
+        assert!(component_matches("rust-src", "rust-src"));
+        assert!(component_matches(
+            "llvm-tools-x86_64-unknown-linux-gnu",
+            "llvm-tools-preview"
+        ));
+        assert!(component_matches(
+            "rust-std-x86_64-unknown-linux-gnu",
+            "rust-std"
+        ));
+        assert!(!component_matches("rust-src", "rust"));
+        assert!(!component_matches(
+            "rustfmt-x86_64-unknown-linux-gnu",
+            "rust-src"
+        ));
+    }
 }
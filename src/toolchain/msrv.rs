@@ -0,0 +1,180 @@
+//! Minimum supported Rust version from Cargo.toml
+//!
+//! The MSRV is the `rust-version` of the package manifest. With
+//! `rust-version.workspace = true`, or in a virtual workspace manifest, it
+//! comes from `[workspace.package]` of the workspace root.
+
+use crate::config::read_manifest;
+use crate::error::{Error, Result};
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// A `rust-version` declared in Cargo.toml
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RustVersion {
+    /// The version as written, e.g. "1.70"
+    pub version: String,
+
+    /// Manifest that declares it
+    pub manifest: PathBuf,
+}
+
+impl RustVersion {
+    /// Find the `rust-version` of the package or workspace containing `dir`
+    ///
+    /// Returns `None` if there is no Cargo.toml or it declares no
+    /// `rust-version`.
+    ///
+    /// # Errors
+    /// Returns an error if a manifest cannot be read or parsed, or the
+    /// version is malformed
+    pub fn find(dir: &Path) -> Result<Option<Self>> {
+        let Some(manifest) = dir
+            .ancestors()
+            .map(|ancestor| ancestor.join("Cargo.toml"))
+            .find(|path| path.is_file())
+        else {
+            return Ok(None);
+        };
+        let table = read_manifest(&manifest)?;
+
+        let declared = table
+            .get("package")
+            .and_then(|package| package.get("rust-version"));
+        let inherited = match declared {
+            Some(Value::String(version)) => return Self::parse(version, manifest).map(Some),
+            Some(value) => value.get("workspace").and_then(Value::as_bool) == Some(true),
+            // A virtual manifest only has the workspace's version
+            None => !table.contains_key("package"),
+        };
+        if !inherited {
+            return Ok(None);
+        }
+
+        let Some((root, workspace)) = workspace_root(&manifest, table)? else {
+            return Ok(None);
+        };
+        match workspace
+            .get("workspace")
+            .and_then(|workspace| workspace.get("package"))
+            .and_then(|package| package.get("rust-version"))
+            .and_then(Value::as_str)
+        {
+            Some(version) => Self::parse(version, root).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Check a `rust-version` value: one to three numeric components
+    ///
+    /// # Errors
+    /// Returns an error if the version is not of the form `1`, `1.70` or
+    /// `1.70.0`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::toolchain::RustVersion;
+    ///
+    /// let msrv = RustVersion::parse("1.70", "Cargo.toml".into()).unwrap();
+    /// assert_eq!(msrv.toolchain(), "1.70.0");
+    /// assert!(RustVersion::parse("1.70-beta", "Cargo.toml".into()).is_err());
+    /// ```
+    pub fn parse(version: &str, manifest: PathBuf) -> Result<Self> {
+        let parts: Vec<&str> = version.split('.').collect();
+        let valid = parts.len() <= 3
+            && parts
+                .iter()
+                .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+        if !valid {
+            return Err(Error::Config(format!(
+                "Invalid rust-version \"{version}\" in {}; expected a version like 1.70",
+                manifest.display()
+            )));
+        }
+        Ok(Self {
+            version: version.to_string(),
+            manifest,
+        })
+    }
+
+    /// Toolchain of the oldest release the version allows, e.g. "1.70.0"
+    /// for "1.70"
+    #[must_use]
+    pub fn toolchain(&self) -> String {
+        let mut parts: Vec<&str> = self.version.split('.').collect();
+        parts.resize(3, "0");
+        parts.join(".")
+    }
+}
+
+/// The workspace root manifest for `manifest`: itself if it has a
+/// `[workspace]` table, or else the nearest parent manifest with one
+fn workspace_root(manifest: &Path, table: Table) -> Result<Option<(PathBuf, Table)>> {
+    if table.contains_key("workspace") {
+        return Ok(Some((manifest.to_path_buf(), table)));
+    }
+    let Some(dir) = manifest.parent().and_then(Path::parent) else {
+        return Ok(None);
+    };
+    for ancestor in dir.ancestors() {
+        let path = ancestor.join("Cargo.toml");
+        if path.is_file() {
+            let table = read_manifest(&path)?;
+            if table.contains_key("workspace") {
+                return Ok(Some((path, table)));
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_package_and_workspace_versions() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"app\", \"tool\"]\n\n\
+             [workspace.package]\nrust-version = \"1.70\"\n",
+        )
+        .unwrap();
+        for (member, version) in [
+            ("app", "rust-version.workspace = true"),
+            ("tool", "rust-version = \"1.74.1\""),
+        ] {
+            std::fs::create_dir_all(root.join(member).join("src")).unwrap();
+            std::fs::write(
+                root.join(member).join("Cargo.toml"),
+                format!("[package]\nname = \"{member}\"\n{version}\n"),
+            )
+            .unwrap();
+        }
+
+        let app = RustVersion::find(&root.join("app").join("src"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(app.version, "1.70");
+        assert_eq!(app.manifest, root.join("Cargo.toml"));
+        assert_eq!(app.toolchain(), "1.70.0");
+
+        let tool = RustVersion::find(&root.join("tool")).unwrap().unwrap();
+        assert_eq!(tool.toolchain(), "1.74.1");
+        assert_eq!(tool.manifest, root.join("tool").join("Cargo.toml"));
+
+        // The virtual manifest has the workspace's version
+        let workspace = RustVersion::find(root).unwrap().unwrap();
+        assert_eq!(workspace.version, "1.70");
+
+        std::fs::write(
+            root.join("tool").join("Cargo.toml"),
+            "[package]\nname = \"tool\"\n",
+        )
+        .unwrap();
+        assert_eq!(RustVersion::find(&root.join("tool")).unwrap(), None);
+    }
+}
@@ -0,0 +1,150 @@
+//! Finding the MSVC build tools on Windows hosts
+//!
+//! `*-windows-msvc` targets link with Microsoft's `link.exe`. rustc finds it
+//! by itself, but outside a Developer Command Prompt it is not on PATH, and
+//! Git for Windows puts an unrelated `link.exe` there. Visual Studio 2017 and
+//! later, including the standalone Build Tools, install `vswhere.exe` in a
+//! fixed place, which reports where the C++ tools are.
+
+use crate::output::log;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Visual Studio component that provides the x86 and x64 C++ tools
+const VC_TOOLS_COMPONENT: &str = "Microsoft.VisualStudio.Component.VC.Tools.x86.x64";
+
+/// MSVC tools of one Visual Studio installation, for one target architecture
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MsvcTools {
+    /// Visual Studio installation directory
+    pub installation: PathBuf,
+    /// Version of the default C++ tools, e.g. "14.39.33519"
+    pub version: String,
+    /// Directory with `link.exe`, `cl.exe`, and `lib.exe` for the target
+    pub bin_dir: PathBuf,
+}
+
+impl MsvcTools {
+    /// Find the C++ tools building for `arch` (a Rust architecture such as
+    /// `x86_64`) in the newest Visual Studio installation
+    ///
+    /// Returns `None` on hosts other than Windows, and when Visual Studio,
+    /// its C++ tools, or the tools for `arch` are not installed.
+    #[must_use]
+    pub fn detect(arch: &str) -> Option<Self> {
+        let installation = find_installation()?;
+        Self::in_installation(installation, std::env::consts::ARCH, arch)
+    }
+
+    /// The tools building on `host_arch` for `target_arch` in `installation`
+    fn in_installation(installation: &Path, host_arch: &str, target_arch: &str) -> Option<Self> {
+        let version = std::fs::read_to_string(
+            installation.join("VC/Auxiliary/Build/Microsoft.VCToolsVersion.default.txt"),
+        )
+        .ok()?
+        .trim()
+        .to_string();
+        let bin_dir = installation
+            .join("VC/Tools/MSVC")
+            .join(&version)
+            .join("bin")
+            .join(format!("Host{}", msvc_arch(host_arch)?))
+            .join(msvc_arch(target_arch)?);
+        bin_dir.join("link.exe").is_file().then(|| Self {
+            installation: installation.to_path_buf(),
+            version,
+            bin_dir,
+        })
+    }
+
+    /// Path of Microsoft's linker
+    #[must_use]
+    pub fn linker(&self) -> PathBuf {
+        self.bin_dir.join("link.exe")
+    }
+}
+
+/// Visual Studio's name for a Rust architecture, e.g. `x64` for `x86_64`
+///
+/// # Examples
+///
+/// ```
+/// use xcargo::toolchain::msvc::msvc_arch;
+///
+/// assert_eq!(msvc_arch("x86_64"), Some("x64"));
+/// assert_eq!(msvc_arch("aarch64"), Some("arm64"));
+/// assert_eq!(msvc_arch("riscv64gc"), None);
+/// ```
+#[must_use]
+pub fn msvc_arch(arch: &str) -> Option<&'static str> {
+    match arch {
+        "x86_64" => Some("x64"),
+        "x86" | "i586" | "i686" => Some("x86"),
+        "aarch64" | "arm64ec" => Some("arm64"),
+        "thumbv7a" | "arm" => Some("arm"),
+        _ => None,
+    }
+}
+
+/// Path of `vswhere.exe`, if Visual Studio's installer is present
+#[must_use]
+pub fn vswhere_path() -> Option<PathBuf> {
+    ["ProgramFiles(x86)", "ProgramFiles"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(|dir| PathBuf::from(dir).join("Microsoft Visual Studio/Installer/vswhere.exe"))
+        .find(|path| path.is_file())
+}
+
+/// Newest Visual Studio installation with the C++ tools, asked of `vswhere`
+/// once per run
+fn find_installation() -> Option<&'static Path> {
+    static INSTALLATION: OnceLock<Option<PathBuf>> = OnceLock::new();
+    INSTALLATION
+        .get_or_init(|| {
+            if !cfg!(windows) {
+                return None;
+            }
+            let mut cmd = Command::new(vswhere_path()?);
+            cmd.args(["-latest", "-products", "*", "-requires", VC_TOOLS_COMPONENT])
+                .args(["-property", "installationPath", "-utf8"]);
+            log::command(&cmd);
+            let output = cmd.output().ok().filter(|o| o.status.success())?;
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(str::trim)
+                .find(|line| !line.is_empty())
+                .map(PathBuf::from)
+        })
+        .as_deref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tools_in_installation() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let vs = temp.path();
+        assert!(MsvcTools::in_installation(vs, "x86_64", "x86_64").is_none());
+
+        let build = vs.join("VC/Auxiliary/Build");
+        std::fs::create_dir_all(&build).unwrap();
+        std::fs::write(
+            build.join("Microsoft.VCToolsVersion.default.txt"),
+            "14.39.33519\r\n",
+        )
+        .unwrap();
+        let bin = vs.join("VC/Tools/MSVC/14.39.33519/bin/Hostx64/arm64");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(bin.join("link.exe"), "").unwrap();
+
+        let tools = MsvcTools::in_installation(vs, "x86_64", "aarch64").unwrap();
+        assert_eq!(tools.version, "14.39.33519");
+        assert_eq!(tools.linker(), bin.join("link.exe"));
+        // Only the ARM64 tools are installed
+        assert!(MsvcTools::in_installation(vs, "x86_64", "x86_64").is_none());
+    }
+}
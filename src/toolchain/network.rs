@@ -0,0 +1,153 @@
+//! Proxies and CA bundles for the tools xcargo runs
+//!
+//! rustup, cargo, curl, git, and the container runtimes all read proxies from
+//! `HTTP_PROXY`/`HTTPS_PROXY`/`NO_PROXY`, but disagree on where they look for
+//! a custom CA bundle. `[network]` in xcargo.toml sets all of these variables
+//! for xcargo and everything it starts, and build containers get the same
+//! proxy and a read-only copy of the bundle.
+
+use crate::config::NetworkConfig;
+use crate::output::helpers;
+use std::path::PathBuf;
+
+/// Proxy variables, in the two spellings tools read them in (curl only
+/// reads the lower-case `http_proxy`)
+const PROXY_VARS: [(&str, &str); 3] = [
+    ("HTTP_PROXY", "http_proxy"),
+    ("HTTPS_PROXY", "https_proxy"),
+    ("NO_PROXY", "no_proxy"),
+];
+
+/// Variables naming a CA bundle: OpenSSL (rustup, podman, most tools),
+/// curl, cargo, and git
+const CA_BUNDLE_VARS: [&str; 4] = [
+    "SSL_CERT_FILE",
+    "CURL_CA_BUNDLE",
+    "CARGO_HTTP_CAINFO",
+    "GIT_SSL_CAINFO",
+];
+
+/// Where build containers find the CA bundle
+pub const CONTAINER_CA_BUNDLE: &str = "/etc/xcargo/ca-bundle.pem";
+
+/// Apply `[network]` from xcargo.toml to the environment of xcargo and the
+/// tools it starts
+///
+/// Variables that are already set take precedence, in either spelling.
+pub fn use_network_config(network: &NetworkConfig) {
+    if let Some(proxy) = &network.proxy {
+        set_proxy_var(PROXY_VARS[0], proxy);
+        set_proxy_var(PROXY_VARS[1], proxy);
+    }
+    if let Some(no_proxy) = &network.no_proxy {
+        set_proxy_var(PROXY_VARS[2], no_proxy);
+    }
+    if let Some(bundle) = &network.ca_bundle {
+        let Ok(bundle) = bundle.canonicalize() else {
+            helpers::warning(format!(
+                "network.ca_bundle {} does not exist; using the system certificates",
+                bundle.display()
+            ));
+            return;
+        };
+        for var in CA_BUNDLE_VARS {
+            if std::env::var_os(var).is_none() {
+                std::env::set_var(var, &bundle);
+            }
+        }
+    }
+}
+
+fn set_proxy_var((upper, lower): (&str, &str), value: &str) {
+    if std::env::var_os(upper).is_none() && std::env::var_os(lower).is_none() {
+        std::env::set_var(upper, value);
+        std::env::set_var(lower, value);
+    }
+}
+
+/// Proxy variables to pass on to a build container, from xcargo's environment
+#[must_use]
+pub fn proxy_env() -> Vec<(String, String)> {
+    PROXY_VARS
+        .iter()
+        .flat_map(|(upper, lower)| [upper, lower])
+        .filter_map(|var| {
+            std::env::var(var)
+                .ok()
+                .filter(|value| !value.is_empty())
+                .map(|value| ((*var).to_string(), value))
+        })
+        .collect()
+}
+
+/// The CA bundle in use, from `[network] ca_bundle` or the first of the CA
+/// bundle variables that names an existing file
+#[must_use]
+pub fn ca_bundle() -> Option<PathBuf> {
+    CA_BUNDLE_VARS
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(PathBuf::from)
+        .find(|path| path.is_file())
+}
+
+/// Variables pointing the tools in a build container at
+/// [`CONTAINER_CA_BUNDLE`]
+#[must_use]
+pub fn container_ca_env() -> Vec<(String, String)> {
+    CA_BUNDLE_VARS
+        .iter()
+        .map(|var| ((*var).to_string(), CONTAINER_CA_BUNDLE.to_string()))
+        .collect()
+}
+
+/// Whether a tool's error output shows a certificate it does not trust,
+/// which behind a corporate proxy usually means TLS interception
+#[must_use]
+pub fn is_untrusted_certificate(output: &str) -> bool {
+    const MARKERS: [&str; 6] = [
+        "x509:",
+        "certificate signed by unknown authority",
+        "certificate verify failed",
+        "tls: failed to verify certificate",
+        "self-signed certificate in certificate chain",
+        "unable to get local issuer certificate",
+    ];
+    let output = output.to_ascii_lowercase();
+    MARKERS.iter().any(|marker| output.contains(marker))
+}
+
+/// Advice to append to a failed download's error when its output shows an
+/// untrusted certificate, or an empty string
+#[must_use]
+pub fn tls_advice(output: &str) -> String {
+    if !is_untrusted_certificate(output) {
+        return String::new();
+    }
+    let current = ca_bundle().map_or_else(String::new, |bundle| {
+        format!(" (currently {})", bundle.display())
+    });
+    format!(
+        "\nThe server's TLS certificate is not trusted; a proxy that intercepts TLS is the usual cause. \
+         Set network.ca_bundle in xcargo.toml to a PEM file with your system's and the proxy's CA certificates{current}."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_untrusted_certificate() {
+        assert!(is_untrusted_certificate(
+            "Error response from daemon: Get \"https://ghcr.io/v2/\": tls: failed to verify certificate: x509: certificate signed by unknown authority"
+        ));
+        assert!(is_untrusted_certificate(
+            "error: could not download file: SSL certificate problem: self-signed certificate in certificate chain"
+        ));
+        assert!(!is_untrusted_certificate(
+            "Error response from daemon: manifest unknown"
+        ));
+        assert!(tls_advice("connection refused").is_empty());
+    }
+}
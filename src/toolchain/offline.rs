@@ -0,0 +1,56 @@
+//! Offline mode for xcargo's own network operations
+//!
+//! `--offline` and `--frozen` are passed on to cargo, and also stop xcargo
+//! from installing toolchains, targets, and components or pulling container
+//! images. Like cargo, xcargo also honors `CARGO_NET_OFFLINE=true`.
+
+use crate::error::{Error, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Cargo's environment variable for offline mode
+pub const OFFLINE_ENV: &str = "CARGO_NET_OFFLINE";
+
+/// Set by `--offline`/`--frozen`
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Turn offline mode on or off for the rest of the process
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+/// Whether xcargo must not access the network
+///
+/// True after `set_offline(true)`, or if `CARGO_NET_OFFLINE` is `true`.
+#[must_use]
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed) || std::env::var(OFFLINE_ENV).is_ok_and(|value| is_true(&value))
+}
+
+/// Fail with a clear message if `action` would need the network while offline
+pub fn require_network(action: &str) -> Result<()> {
+    if is_offline() {
+        Err(Error::Toolchain(format!(
+            "Cannot {action} in offline mode (--offline, --frozen or {OFFLINE_ENV}=true)"
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+/// Cargo reads boolean environment config as `true`/`false`
+fn is_true(value: &str) -> bool {
+    value.trim().eq_ignore_ascii_case("true")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_true() {
+        assert!(is_true("true"));
+        assert!(is_true(" TRUE "));
+        assert!(!is_true("false"));
+        assert!(!is_true(""));
+    }
+}
@@ -0,0 +1,318 @@
+//! Toolchain pinning via `rust-toolchain.toml` and xcargo.toml
+//!
+//! The toolchain for a build is chosen in this order:
+//!
+//! 1. `--toolchain` on the command line
+//! 2. `[toolchain] channel` in xcargo.toml
+//! 3. `rust-toolchain.toml` or `rust-toolchain` in the project or a parent directory
+//! 4. The rustup default toolchain
+//! 5. `stable`
+
+use crate::error::{Error, Result};
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Toolchain file names, in the order rustup checks them within a directory
+const TOOLCHAIN_FILES: [&str; 2] = ["rust-toolchain", "rust-toolchain.toml"];
+
+/// Contents of a `rust-toolchain.toml` or legacy `rust-toolchain` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolchainFile {
+    /// Path of the file
+    pub path: PathBuf,
+
+    /// Toolchain channel, e.g. "1.78.0", "stable", or "nightly-2024-05-01"
+    pub channel: String,
+
+    /// Components to install with the toolchain
+    pub components: Vec<String>,
+
+    /// Targets to install with the toolchain
+    pub targets: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct ToolchainFileToml {
+    toolchain: ToolchainSection,
+}
+
+#[derive(Deserialize)]
+struct ToolchainSection {
+    channel: Option<String>,
+    path: Option<String>,
+    #[serde(default)]
+    components: Vec<String>,
+    #[serde(default)]
+    targets: Vec<String>,
+}
+
+impl ToolchainFile {
+    /// Find the toolchain file that applies to `dir`, searching parent directories
+    ///
+    /// # Errors
+    /// Returns an error if a toolchain file exists but cannot be read or parsed
+    pub fn find(dir: &Path) -> Result<Option<Self>> {
+        for ancestor in dir.ancestors() {
+            for name in TOOLCHAIN_FILES {
+                let path = ancestor.join(name);
+                if path.is_file() {
+                    let contents = std::fs::read_to_string(&path)?;
+                    return Self::parse(&contents, path).map(Some);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Parse toolchain file contents
+    ///
+    /// Accepts the TOML format as well as the legacy single-line format
+    /// containing only a channel name.
+    ///
+    /// # Errors
+    /// Returns an error if the file is malformed or uses a toolchain `path`,
+    /// which xcargo cannot install targets for
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::toolchain::ToolchainFile;
+    ///
+    /// let file = ToolchainFile::parse(
+    ///     "[toolchain]\nchannel = \"1.78.0\"\ncomponents = [\"rust-src\"]\n",
+    ///     "rust-toolchain.toml".into(),
+    /// ).unwrap();
+    /// assert_eq!(file.channel, "1.78.0");
+    /// assert_eq!(file.components, vec!["rust-src"]);
+    /// ```
+    pub fn parse(contents: &str, path: PathBuf) -> Result<Self> {
+        let trimmed = contents.trim();
+        let is_legacy = !trimmed.is_empty()
+            && !trimmed.contains('\n')
+            && !trimmed.contains('=')
+            && !trimmed.starts_with('[');
+
+        if is_legacy {
+            return Ok(Self {
+                path,
+                channel: trimmed.to_string(),
+                components: Vec::new(),
+                targets: Vec::new(),
+            });
+        }
+
+        let parsed: ToolchainFileToml =
+            toml::from_str(contents).map_err(|e| Error::ConfigParse {
+                path: path.display().to_string(),
+                line: None,
+                message: e.to_string(),
+            })?;
+        let section = parsed.toolchain;
+
+        let channel = match (section.channel, section.path) {
+            (Some(channel), _) => channel,
+            (None, Some(_)) => {
+                return Err(Error::Toolchain(format!(
+                    "{} uses a custom toolchain path, which xcargo does not support",
+                    path.display()
+                )))
+            }
+            (None, None) => {
+                return Err(Error::Toolchain(format!(
+                    "{} does not set [toolchain] channel",
+                    path.display()
+                )))
+            }
+        };
+
+        Ok(Self {
+            path,
+            channel,
+            components: section.components,
+            targets: section.targets,
+        })
+    }
+}
+
+/// Where the selected toolchain came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolchainSource {
+    /// `--toolchain` on the command line
+    CommandLine,
+    /// `[toolchain] channel` in xcargo.toml
+    Config,
+    /// A `rust-toolchain.toml` or `rust-toolchain` file
+    ToolchainFile(PathBuf),
+    /// The rustup default toolchain
+    RustupDefault,
+    /// Nothing configured; `stable` is used
+    Fallback,
+}
+
+impl fmt::Display for ToolchainSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CommandLine => write!(f, "--toolchain"),
+            Self::Config => write!(f, "xcargo.toml"),
+            Self::ToolchainFile(path) => write!(f, "{}", path.display()),
+            Self::RustupDefault => write!(f, "rustup default"),
+            Self::Fallback => write!(f, "fallback"),
+        }
+    }
+}
+
+/// The toolchain chosen for a build
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolchainSelection {
+    /// Toolchain name passed to rustup
+    pub name: String,
+
+    /// Components requested by the toolchain file
+    pub components: Vec<String>,
+
+    /// Where the toolchain came from
+    pub source: ToolchainSource,
+
+    /// Description of a conflict between xcargo.toml and the toolchain file
+    pub conflict: Option<String>,
+}
+
+impl ToolchainSelection {
+    /// Resolve the toolchain from the command line, xcargo.toml, and toolchain file
+    ///
+    /// `rustup_default` is only consulted when nothing else selects a toolchain.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::toolchain::{ToolchainSelection, ToolchainSource};
+    ///
+    /// let selection = ToolchainSelection::resolve(None, Some("1.78.0"), None, || None);
+    /// assert_eq!(selection.name, "1.78.0");
+    /// assert_eq!(selection.source, ToolchainSource::Config);
+    /// ```
+    #[must_use]
+    pub fn resolve(
+        requested: Option<&str>,
+        configured: Option<&str>,
+        file: Option<&ToolchainFile>,
+        rustup_default: impl FnOnce() -> Option<String>,
+    ) -> Self {
+        let conflict = match (configured, file) {
+            (Some(channel), Some(file)) if channel != file.channel => Some(format!(
+                "xcargo.toml pins toolchain '{channel}', overriding '{}' from {}",
+                file.channel,
+                file.path.display()
+            )),
+            _ => None,
+        };
+
+        let components = file.map(|f| f.components.clone()).unwrap_or_default();
+
+        let (name, source) = if let Some(name) = requested {
+            (name.to_string(), ToolchainSource::CommandLine)
+        } else if let Some(channel) = configured {
+            (channel.to_string(), ToolchainSource::Config)
+        } else if let Some(file) = file {
+            (
+                file.channel.clone(),
+                ToolchainSource::ToolchainFile(file.path.clone()),
+            )
+        } else if let Some(name) = rustup_default() {
+            (name, ToolchainSource::RustupDefault)
+        } else {
+            ("stable".to_string(), ToolchainSource::Fallback)
+        };
+
+        Self {
+            name,
+            components,
+            source,
+            conflict: conflict.filter(|_| requested.is_none()),
+        }
+    }
+
+    /// Whether cargo must be invoked with `+<toolchain>` to use this selection
+    ///
+    /// Toolchain files and the rustup default are already honored by rustup.
+    #[must_use]
+    pub fn needs_override(&self) -> bool {
+        matches!(
+            self.source,
+            ToolchainSource::CommandLine | ToolchainSource::Config
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn file(channel: &str) -> ToolchainFile {
+        ToolchainFile {
+            path: PathBuf::from("rust-toolchain.toml"),
+            channel: channel.to_string(),
+            components: vec!["rust-src".to_string()],
+            targets: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_legacy_file() {
+        let parsed = ToolchainFile::parse("nightly-2024-05-01\n", "rust-toolchain".into()).unwrap();
+        assert_eq!(parsed.channel, "nightly-2024-05-01");
+        assert!(parsed.components.is_empty());
+    }
+
+    #[test]
+    fn test_parse_path_toolchain_rejected() {
+        let result = ToolchainFile::parse(
+            "[toolchain]\npath = \"/opt/rust\"\n",
+            "rust-toolchain.toml".into(),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_find_in_parent_directory() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("rust-toolchain.toml"),
+            "[toolchain]\nchannel = \"1.78.0\"\ntargets = [\"wasm32-unknown-unknown\"]\n",
+        )
+        .unwrap();
+        let nested = temp.path().join("crates/app");
+        std::fs::create_dir_all(&nested).unwrap();
+
+        let found = ToolchainFile::find(&nested).unwrap().unwrap();
+        assert_eq!(found.channel, "1.78.0");
+        assert_eq!(found.targets, vec!["wasm32-unknown-unknown"]);
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let pinned = file("1.76.0");
+
+        let selection =
+            ToolchainSelection::resolve(Some("nightly"), Some("1.78.0"), Some(&pinned), || None);
+        assert_eq!(selection.source, ToolchainSource::CommandLine);
+        assert!(selection.conflict.is_none());
+
+        let selection = ToolchainSelection::resolve(None, Some("1.78.0"), Some(&pinned), || None);
+        assert_eq!(selection.name, "1.78.0");
+        assert!(selection.conflict.unwrap().contains("1.76.0"));
+
+        let selection = ToolchainSelection::resolve(None, None, Some(&pinned), || None);
+        assert_eq!(selection.name, "1.76.0");
+        assert_eq!(selection.components, vec!["rust-src"]);
+        assert!(!selection.needs_override());
+
+        let selection = ToolchainSelection::resolve(None, None, None, || Some("beta".to_string()));
+        assert_eq!(selection.source, ToolchainSource::RustupDefault);
+
+        let selection = ToolchainSelection::resolve(None, None, None, || None);
+        assert_eq!(selection.name, "stable");
+    }
+}
@@ -0,0 +1,335 @@
+//! Standalone toolchains used without rustup
+//!
+//! Distro packages and vendored toolchains ship `rustc` and `cargo` without
+//! rustup. xcargo can still build with them: the toolchain is detected from
+//! `rustc -vV`, and targets are considered installed when their standard
+//! library is present in the sysroot, and `rustc --print target-list` names
+//! the targets it can compile for. Nothing can be installed.
+
+use crate::error::{Error, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use which::which;
+
+/// Environment variable that forces standalone mode even if rustup is installed
+pub const NO_RUSTUP_ENV: &str = "XCARGO_NO_RUSTUP";
+
+/// A Rust toolchain found on the system without rustup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StandaloneToolchain {
+    /// Path to `rustc` (from `$RUSTC` or PATH)
+    pub rustc: PathBuf,
+
+    /// Path to `cargo` (from `$CARGO` or PATH)
+    pub cargo: PathBuf,
+
+    /// Release reported by `rustc -vV`, e.g. "1.78.0" or "1.80.0-nightly"
+    pub release: String,
+
+    /// Host triple reported by `rustc -vV`
+    pub host: String,
+
+    /// Toolchain sysroot
+    pub sysroot: PathBuf,
+}
+
+impl StandaloneToolchain {
+    /// Detect `rustc` and `cargo` without going through rustup
+    ///
+    /// `$RUSTC` and `$CARGO` take precedence over PATH.
+    ///
+    /// # Errors
+    /// Returns an error if `rustc` or `cargo` cannot be found, or `rustc`
+    /// does not report its version and sysroot
+    pub fn detect() -> Result<Self> {
+        let rustc = find_tool("rustc", "RUSTC")?;
+        let cargo = find_tool("cargo", "CARGO")?;
+
+        let verbose_version = run(&rustc, &["-vV"])?;
+        let (release, host) = parse_verbose_version(&verbose_version).ok_or_else(|| {
+            Error::Toolchain(format!(
+                "Could not parse the output of '{} -vV'",
+                rustc.display()
+            ))
+        })?;
+        let sysroot = PathBuf::from(run(&rustc, &["--print", "sysroot"])?);
+
+        Ok(Self {
+            rustc,
+            cargo,
+            release,
+            host,
+            sysroot,
+        })
+    }
+
+    /// Release channel: "stable", "beta", or "nightly"
+    #[must_use]
+    pub fn channel(&self) -> &'static str {
+        channel_of(&self.release)
+    }
+
+    /// Whether a requested toolchain name refers to this toolchain
+    ///
+    /// Accepts the release, also without its patch version ("1.78" for
+    /// "1.78.0"), the channel ("stable"), and either of those followed by the
+    /// host triple. A channel matches whichever release of it is installed,
+    /// as no other can be installed without rustup.
+    #[must_use]
+    pub fn matches(&self, toolchain: &str) -> bool {
+        let name = toolchain
+            .strip_suffix(self.host.as_str())
+            .and_then(|name| name.strip_suffix('-'))
+            .unwrap_or(toolchain);
+        if name == self.channel() {
+            return true;
+        }
+        let mut parts: Vec<&str> = name.split('.').collect();
+        if parts.len() < 3 {
+            parts.resize(3, "0");
+        }
+        parts.join(".") == self.release
+    }
+
+    /// Directory holding the libraries for `target`
+    fn target_lib_dir(&self, target: &str) -> PathBuf {
+        self.sysroot.join("lib/rustlib").join(target).join("lib")
+    }
+
+    /// Whether the standard library for `target` is present in the sysroot
+    #[must_use]
+    pub fn has_target(&self, target: &str) -> bool {
+        has_core_library(&self.target_lib_dir(target))
+    }
+
+    /// Targets whose standard library is present in the sysroot
+    #[must_use]
+    pub fn installed_targets(&self) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(self.sysroot.join("lib/rustlib")) else {
+            return Vec::new();
+        };
+
+        let mut targets: Vec<String> = entries
+            .filter_map(std::result::Result::ok)
+            .filter(|entry| has_core_library(&entry.path().join("lib")))
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        targets.sort();
+        targets
+    }
+
+    /// Targets this `rustc` can compile for, from `rustc --print target-list`
+    ///
+    /// Most of them have no standard library in the sysroot; see
+    /// [`installed_targets`](Self::installed_targets).
+    ///
+    /// # Errors
+    /// Returns an error if `rustc` fails to run
+    pub fn supported_targets(&self) -> Result<Vec<String>> {
+        let output = run(&self.rustc, &["--print", "target-list"])?;
+        Ok(parse_target_list(&output))
+    }
+
+    /// Components detectable in the sysroot, named as rustup lists them
+    #[must_use]
+    pub fn installed_components(&self) -> Vec<String> {
+        let rustlib = self.sysroot.join("lib/rustlib");
+        let mut components = vec![format!("rustc-{}", self.host)];
+
+        if rustlib.join("src/rust").is_dir() {
+            components.push("rust-src".to_string());
+        }
+        if rustlib.join(&self.host).join("bin/llvm-objcopy").exists() {
+            components.push(format!("llvm-tools-{}", self.host));
+        }
+        components.extend(
+            self.installed_targets()
+                .into_iter()
+                .map(|target| format!("rust-std-{target}")),
+        );
+        components
+    }
+
+    /// Error for an operation that needs rustup
+    #[must_use]
+    pub fn unsupported(&self, operation: &str, manual: &str) -> Error {
+        Error::Toolchain(format!(
+            "Cannot {operation}: rustup is not available and the standalone toolchain at {} (rustc {}) cannot be modified. {manual}, or install rustup from https://rustup.rs/",
+            self.sysroot.display(),
+            self.release
+        ))
+    }
+
+    /// Error for installing `target`, which needs rustup
+    #[must_use]
+    pub fn install_target_error(&self, target: &str) -> Error {
+        self.unsupported(
+            &format!("install target '{target}'"),
+            &format!(
+                "Install the standard library for {target} into {} with your system package manager",
+                self.sysroot.display()
+            ),
+        )
+    }
+}
+
+/// Release channel for a rustc release string
+fn channel_of(release: &str) -> &'static str {
+    if release.contains("nightly") || release.contains("-dev") {
+        "nightly"
+    } else if release.contains("beta") {
+        "beta"
+    } else {
+        "stable"
+    }
+}
+
+/// Whether `dir` contains `libcore`, which every target's standard library includes
+fn has_core_library(dir: &Path) -> bool {
+    std::fs::read_dir(dir).is_ok_and(|entries| {
+        entries.filter_map(std::result::Result::ok).any(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            name.starts_with("libcore-") && name.ends_with(".rlib")
+        })
+    })
+}
+
+/// Parse the release and host from `rustc -vV` output
+pub(super) fn parse_verbose_version(output: &str) -> Option<(String, String)> {
+    let field = |key: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(key))
+            .map(|value| value.trim().to_string())
+    };
+    Some((field("release:")?, field("host:")?))
+}
+
+/// Target triples from `rustc --print target-list` output
+fn parse_target_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn find_tool(name: &str, env: &str) -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os(env).filter(|path| !path.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    which(name).map_err(|_| {
+        Error::Toolchain(format!(
+            "Neither rustup nor a standalone {name} was found. Install Rust from https://rustup.rs/ or set {env} to the {name} binary"
+        ))
+    })
+}
+
+fn run(program: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| Error::Toolchain(format!("Failed to run {}: {e}", program.display())))?;
+
+    if !output.status.success() {
+        return Err(Error::Toolchain(format!(
+            "'{} {}' failed: {}",
+            program.display(),
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn toolchain(sysroot: &Path, release: &str) -> StandaloneToolchain {
+        StandaloneToolchain {
+            rustc: PathBuf::from("/usr/bin/rustc"),
+            cargo: PathBuf::from("/usr/bin/cargo"),
+            release: release.to_string(),
+            host: "x86_64-unknown-linux-gnu".to_string(),
+            sysroot: sysroot.to_path_buf(),
+        }
+    }
+
+    #[test]
+    fn test_parse_verbose_version() {
+        let output = "rustc 1.78.0 (9b00956e5 2024-04-29)\nbinary: rustc\nhost: x86_64-unknown-linux-gnu\nrelease: 1.78.0\nLLVM version: 18.1.2\n";
+        assert_eq!(
+            parse_verbose_version(output),
+            Some(("1.78.0".to_string(), "x86_64-unknown-linux-gnu".to_string()))
+        );
+        assert_eq!(parse_verbose_version("rustc 1.78.0"), None);
+    }
+
+    #[test]
+    fn test_parse_target_list() {
+        assert_eq!(
+            parse_target_list("aarch64-apple-darwin\n\nx86_64-unknown-linux-gnu\n"),
+            vec!["aarch64-apple-darwin", "x86_64-unknown-linux-gnu"]
+        );
+    }
+
+    #[test]
+    fn test_matches() {
+        let temp = TempDir::new().unwrap();
+        let stable = toolchain(temp.path(), "1.78.0");
+        assert!(stable.matches("stable"));
+        assert!(stable.matches("1.78.0"));
+        assert!(stable.matches("stable-x86_64-unknown-linux-gnu"));
+        assert!(!stable.matches("nightly"));
+        assert!(!stable.matches("1.79.0"));
+        // rust-toolchain.toml pins are often written without the patch version
+        assert!(stable.matches("1.78"));
+        assert!(stable.matches("1.78-x86_64-unknown-linux-gnu"));
+        assert!(!stable.matches("1.7"));
+
+        let nightly = toolchain(temp.path(), "1.80.0-nightly");
+        assert_eq!(nightly.channel(), "nightly");
+        assert!(nightly.matches("nightly"));
+    }
+
+    #[test]
+    fn test_targets_from_sysroot() {
+        let temp = TempDir::new().unwrap();
+        let rustlib = temp.path().join("lib/rustlib");
+        for target in ["x86_64-unknown-linux-gnu", "thumbv7em-none-eabihf"] {
+            let lib = rustlib.join(target).join("lib");
+            std::fs::create_dir_all(&lib).unwrap();
+            std::fs::write(lib.join("libcore-0123abcd.rlib"), b"").unwrap();
+        }
+        // Directories without a standard library are not targets
+        std::fs::create_dir_all(rustlib.join("etc")).unwrap();
+        std::fs::create_dir_all(rustlib.join("aarch64-unknown-linux-gnu/lib")).unwrap();
+
+        let standalone = toolchain(temp.path(), "1.78.0");
+        assert!(standalone.has_target("thumbv7em-none-eabihf"));
+        assert!(!standalone.has_target("aarch64-unknown-linux-gnu"));
+        assert_eq!(
+            standalone.installed_targets(),
+            vec!["thumbv7em-none-eabihf", "x86_64-unknown-linux-gnu"]
+        );
+        assert!(standalone
+            .installed_components()
+            .contains(&"rust-std-thumbv7em-none-eabihf".to_string()));
+    }
+
+    #[test]
+    fn test_unsupported_error_names_operation() {
+        let temp = TempDir::new().unwrap();
+        let err = toolchain(temp.path(), "1.78.0").unsupported(
+            "install target 'x'",
+            "Install the target with your package manager",
+        );
+        let message = err.to_string();
+        assert!(message.contains("install target 'x'"));
+        assert!(message.contains("rustc 1.78.0"));
+    }
+}
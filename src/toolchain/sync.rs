@@ -0,0 +1,172 @@
+//! Reconciling a toolchain's targets with the configuration
+//!
+//! `xcargo target sync` installs the targets xcargo.toml refers to that the
+//! toolchain is missing, so a fresh checkout is ready to build after one
+//! command. Installed targets nothing refers to are listed, and removed with
+//! `--prune`; the host's standard library is always kept.
+
+use std::fmt::Write;
+
+/// What syncing a toolchain's targets with the configuration changes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TargetSync {
+    /// Referenced targets the toolchain is missing
+    pub install: Vec<String>,
+
+    /// Installed targets nothing refers to
+    pub unreferenced: Vec<String>,
+
+    /// Referenced targets that are installed already
+    pub installed: Vec<String>,
+
+    /// Referenced targets rustup does not distribute, such as tier 3
+    /// targets built with `-Z build-std`
+    pub unavailable: Vec<String>,
+}
+
+impl TargetSync {
+    /// Compare the `referenced` targets with those `installed`
+    ///
+    /// `available` is rustup's list of targets, when it could be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::toolchain::TargetSync;
+    ///
+    /// let strings = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    /// let sync = TargetSync::plan(
+    ///     &strings(&["aarch64-unknown-linux-gnu", "x86_64-unknown-linux-gnu"]),
+    ///     &strings(&["x86_64-unknown-linux-gnu", "wasm32-unknown-unknown"]),
+    ///     None,
+    ///     "x86_64-unknown-linux-gnu",
+    /// );
+    /// assert_eq!(sync.install, ["aarch64-unknown-linux-gnu"]);
+    /// assert_eq!(sync.unreferenced, ["wasm32-unknown-unknown"]);
+    /// ```
+    #[must_use]
+    pub fn plan(
+        referenced: &[String],
+        installed: &[String],
+        available: Option<&[String]>,
+        host: &str,
+    ) -> Self {
+        let mut sync = Self::default();
+        for target in referenced {
+            if installed.contains(target) {
+                sync.installed.push(target.clone());
+            } else if available.is_some_and(|available| !available.contains(target)) {
+                sync.unavailable.push(target.clone());
+            } else {
+                sync.install.push(target.clone());
+            }
+        }
+        sync.unreferenced = installed
+            .iter()
+            .filter(|target| *target != host && !referenced.contains(target))
+            .cloned()
+            .collect();
+        for list in [
+            &mut sync.install,
+            &mut sync.unreferenced,
+            &mut sync.installed,
+            &mut sync.unavailable,
+        ] {
+            list.sort();
+            list.dedup();
+        }
+        sync
+    }
+
+    /// Whether syncing changes nothing; unreferenced targets only count
+    /// when they are being pruned
+    #[must_use]
+    pub fn is_current(&self, prune: bool) -> bool {
+        self.install.is_empty() && (!prune || self.unreferenced.is_empty())
+    }
+
+    /// The changes as a diff: `+` for targets to install, `-` for targets
+    /// to remove, and unchanged targets indented
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use xcargo::toolchain::TargetSync;
+    ///
+    /// let sync = TargetSync {
+    ///     install: vec!["aarch64-unknown-linux-gnu".to_string()],
+    ///     unreferenced: vec!["wasm32-unknown-unknown".to_string()],
+    ///     installed: vec!["x86_64-unknown-linux-gnu".to_string()],
+    ///     unavailable: Vec::new(),
+    /// };
+    /// assert_eq!(
+    ///     sync.diff(true),
+    ///     "+ aarch64-unknown-linux-gnu\n  x86_64-unknown-linux-gnu\n- wasm32-unknown-unknown\n"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn diff(&self, prune: bool) -> String {
+        let mut diff = String::new();
+        for target in &self.install {
+            let _ = writeln!(diff, "+ {target}");
+        }
+        for target in &self.installed {
+            let _ = writeln!(diff, "  {target}");
+        }
+        for target in &self.unreferenced {
+            if prune {
+                let _ = writeln!(diff, "- {target}");
+            } else {
+                let _ = writeln!(diff, "  {target} (not in xcargo.toml)");
+            }
+        }
+        for target in &self.unavailable {
+            let _ = writeln!(diff, "? {target} (not available from rustup)");
+        }
+        diff
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(list: &[&str]) -> Vec<String> {
+        list.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_plan_keeps_host_and_skips_unavailable() {
+        let sync = TargetSync::plan(
+            &strings(&["thumbv7em-none-eabihf", "riscv32imac-esp-espidf"]),
+            &strings(&["x86_64-unknown-linux-gnu", "x86_64-pc-windows-gnu"]),
+            Some(&strings(&[
+                "thumbv7em-none-eabihf",
+                "x86_64-unknown-linux-gnu",
+                "x86_64-pc-windows-gnu",
+            ])),
+            "x86_64-unknown-linux-gnu",
+        );
+        assert_eq!(sync.install, ["thumbv7em-none-eabihf"]);
+        assert_eq!(sync.unavailable, ["riscv32imac-esp-espidf"]);
+        assert_eq!(sync.unreferenced, ["x86_64-pc-windows-gnu"]);
+        assert!(sync.installed.is_empty());
+        assert!(!sync.is_current(false));
+    }
+
+    #[test]
+    fn test_is_current() {
+        let sync = TargetSync::plan(
+            &strings(&["x86_64-unknown-linux-gnu"]),
+            &strings(&["x86_64-unknown-linux-gnu", "wasm32-unknown-unknown"]),
+            None,
+            "x86_64-unknown-linux-gnu",
+        );
+        assert!(sync.is_current(false));
+        assert!(!sync.is_current(true));
+        assert_eq!(
+            sync.diff(false),
+            "  x86_64-unknown-linux-gnu\n  wasm32-unknown-unknown (not in xcargo.toml)\n"
+        );
+    }
+}
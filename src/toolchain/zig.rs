@@ -1,12 +1,121 @@
 //! Zig-based cross-compilation support
 
+use crate::config::Config;
 use crate::error::{Error, Result};
+use crate::output::helpers;
 use crate::target::Target;
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use super::{cc_var, install_zig, managed_zig};
+
+/// A Rust target Zig can compile C code and link for
+struct ZigTarget {
+    /// Rust target triple
+    rust: &'static str,
+
+    /// Zig's `-target` for it
+    zig: &'static str,
+
+    /// Oldest Zig (major, minor) that supports it
+    since: (u32, u32),
+}
+
+/// Every Rust target xcargo builds with Zig; macOS and wasm are left out, as
+/// Zig can't link for macOS off macOS and wasm needs no C toolchain
+const ZIG_TARGETS: &[ZigTarget] = &[
+    ZigTarget {
+        rust: "x86_64-unknown-linux-gnu",
+        zig: "x86_64-linux-gnu",
+        since: (0, 9),
+    },
+    ZigTarget {
+        rust: "aarch64-unknown-linux-gnu",
+        zig: "aarch64-linux-gnu",
+        since: (0, 9),
+    },
+    ZigTarget {
+        rust: "armv7-unknown-linux-gnueabihf",
+        zig: "arm-linux-gnueabihf",
+        since: (0, 9),
+    },
+    ZigTarget {
+        rust: "arm-unknown-linux-gnueabihf",
+        zig: "arm-linux-gnueabihf",
+        since: (0, 9),
+    },
+    ZigTarget {
+        rust: "i686-unknown-linux-gnu",
+        zig: "i386-linux-gnu",
+        since: (0, 9),
+    },
+    ZigTarget {
+        rust: "riscv64gc-unknown-linux-gnu",
+        zig: "riscv64-linux-gnu",
+        since: (0, 10),
+    },
+    ZigTarget {
+        rust: "s390x-unknown-linux-gnu",
+        zig: "s390x-linux-gnu",
+        since: (0, 10),
+    },
+    ZigTarget {
+        rust: "loongarch64-unknown-linux-gnu",
+        zig: "loongarch64-linux-gnu",
+        since: (0, 12),
+    },
+    // musl works, but static linking may hit duplicate symbols
+    ZigTarget {
+        rust: "x86_64-unknown-linux-musl",
+        zig: "x86_64-linux-musl",
+        since: (0, 9),
+    },
+    ZigTarget {
+        rust: "aarch64-unknown-linux-musl",
+        zig: "aarch64-linux-musl",
+        since: (0, 9),
+    },
+    ZigTarget {
+        rust: "x86_64-pc-windows-gnu",
+        zig: "x86_64-windows-gnu",
+        since: (0, 9),
+    },
+    ZigTarget {
+        rust: "i686-pc-windows-gnu",
+        zig: "i686-windows-gnu",
+        since: (0, 9),
+    },
+    ZigTarget {
+        rust: "aarch64-pc-windows-gnullvm",
+        zig: "aarch64-windows-gnu",
+        since: (0, 11),
+    },
+    // Zig ships FreeBSD's libc stubs and headers since 0.14
+    ZigTarget {
+        rust: "x86_64-unknown-freebsd",
+        zig: "x86_64-freebsd",
+        since: (0, 14),
+    },
+    ZigTarget {
+        rust: "aarch64-unknown-freebsd",
+        zig: "aarch64-freebsd",
+        since: (0, 14),
+    },
+];
+
+/// The [`ZIG_TARGETS`] entry for a Rust target
+fn zig_target_entry(triple: &str) -> Option<&'static ZigTarget> {
+    ZIG_TARGETS.iter().find(|t| t.rust == triple)
+}
+
+/// Parse "0.13.0" or "0.14.0-dev.1+abc" into (0, 13)
+fn parse_zig_version(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split(['.', '-', '+']);
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+}
+
 /// Zig toolchain for cross-compilation
 pub struct ZigToolchain {
     /// Path to zig binary
@@ -36,11 +145,14 @@ impl ZigToolchain {
     /// ```
     pub fn detect() -> Result<Option<Self>> {
         // Try to find zig in PATH
-        let zig_path = match which::which("zig") {
-            Ok(path) => path,
-            Err(_) => return Ok(None),
-        };
+        match which::which("zig") {
+            Ok(path) => Self::from_path(path),
+            Err(_) => Ok(None),
+        }
+    }
 
+    /// Use the Zig binary at `zig_path`, if it runs
+    pub fn from_path(zig_path: PathBuf) -> Result<Option<Self>> {
         // Get version
         let output = Command::new(&zig_path)
             .arg("version")
@@ -53,11 +165,8 @@ impl ZigToolchain {
 
         let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
-        // Use ~/.xcargo/zig-wrappers as cache directory
-        let cache_dir = dirs::home_dir()
-            .ok_or_else(|| Error::Toolchain("Could not determine home directory".to_string()))?
-            .join(".xcargo")
-            .join("zig-wrappers");
+        let cache_dir = Self::wrapper_cache_dir()
+            .ok_or_else(|| Error::Toolchain("Could not determine home directory".to_string()))?;
 
         Ok(Some(Self {
             zig_path,
@@ -66,6 +175,40 @@ impl ZigToolchain {
         }))
     }
 
+    /// Find the Zig a project builds with
+    ///
+    /// Without a `zig.version` pin this is [`detect`](Self::detect). With one,
+    /// it's the managed release in `~/.xcargo/zig/<version>`, else the Zig on
+    /// PATH if it's that version, else the release is downloaded and installed.
+    ///
+    /// # Errors
+    /// Returns an error if the pinned release is not installed and can't be
+    /// installed, e.g. offline
+    pub fn for_config(config: &Config) -> Result<Option<Self>> {
+        let Some(pinned) = &config.zig.version else {
+            return Self::detect();
+        };
+        if let Some(path) = managed_zig(pinned) {
+            return Self::from_path(path);
+        }
+        if let Some(zig) = Self::detect()?.filter(|zig| zig.version == *pinned) {
+            return Ok(Some(zig));
+        }
+
+        helpers::info(format!(
+            "xcargo.toml pins Zig {pinned}; installing it into ~/.xcargo/zig"
+        ));
+        let path = install_zig(pinned)?;
+        helpers::success(format!("Installed Zig {pinned}"));
+        Self::from_path(path)
+    }
+
+    /// Directory of the generated compiler wrappers, `~/.xcargo/zig-wrappers`
+    #[must_use]
+    pub fn wrapper_cache_dir() -> Option<PathBuf> {
+        dirs::home_dir().map(|home| home.join(".xcargo").join("zig-wrappers"))
+    }
+
     /// Get the Zig version
     #[must_use]
     pub fn version(&self) -> &str {
@@ -80,77 +223,56 @@ impl ZigToolchain {
 
     /// Check if Zig supports a target by triple name (static method)
     ///
-    /// This can be called without having a `ZigToolchain` instance.
+    /// This can be called without having a `ZigToolchain` instance, so it
+    /// doesn't check the Zig version; see [`supports_target`](Self::supports_target).
     #[must_use]
     pub fn supports_target_name(triple: &str) -> bool {
-        match triple {
-            // Linux targets (well-supported)
-            "x86_64-unknown-linux-gnu" => true,
-            "aarch64-unknown-linux-gnu" => true,
-            "armv7-unknown-linux-gnueabihf" => true,
-            "i686-unknown-linux-gnu" => true,
-            "arm-unknown-linux-gnueabihf" => true,
-
-            // musl targets (known issues with static linking)
-            "x86_64-unknown-linux-musl" => true, // Works but may have duplicate symbol issues
-            "aarch64-unknown-linux-musl" => true,
-
-            // Windows targets
-            "x86_64-pc-windows-gnu" => true,
-            "i686-pc-windows-gnu" => true,
-
-            // macOS targets (not supported - Zig can't build for macOS on non-macOS)
-            triple if triple.contains("apple-darwin") => false,
-
-            // WebAssembly (may work but untested)
-            triple if triple.contains("wasm32") => false,
-
-            // Unknown target
-            _ => false,
-        }
+        zig_target_entry(triple).is_some()
+    }
+
+    /// Rust targets Zig can cross-compile to, with a recent enough Zig
+    pub fn supported_target_names() -> impl Iterator<Item = &'static str> {
+        ZIG_TARGETS.iter().map(|t| t.rust)
+    }
+
+    /// Oldest Zig that supports a target, e.g. "0.12", if Zig supports it at all
+    #[must_use]
+    pub fn required_version(triple: &str) -> Option<String> {
+        zig_target_entry(triple).map(|t| format!("{}.{}", t.since.0, t.since.1))
     }
 
     /// Check if Zig can cross-compile to a target
     ///
-    /// Zig supports many targets out of the box. This function checks if the
-    /// target is supported by Zig.
+    /// Zig supports many targets out of the box; this checks the target is
+    /// in [`ZIG_TARGETS`] and that this Zig is new enough for it.
     #[must_use]
     pub fn supports_target(&self, target: &Target) -> bool {
-        // Zig supports most Linux targets
-        // Known supported targets:
-        // - x86_64-unknown-linux-gnu
-        // - x86_64-unknown-linux-musl (with caveats)
-        // - aarch64-unknown-linux-gnu
-        // - aarch64-unknown-linux-musl
-        // - armv7-unknown-linux-gnueabihf
-        // - i686-unknown-linux-gnu
-
-        Self::supports_target_name(&target.triple)
+        zig_target_entry(&target.triple).is_some_and(|t| self.is_at_least(t.since))
+    }
+
+    /// Whether this Zig is `version` or newer; unparseable versions are assumed new
+    fn is_at_least(&self, version: (u32, u32)) -> bool {
+        parse_zig_version(&self.version).map_or(true, |v| v >= version)
     }
 
     /// Get the Zig target triple for a Rust target
     ///
-    /// Converts Rust target triple to Zig target triple format
+    /// Converts Rust target triple to Zig target triple format, keeping a
+    /// requested glibc version (`x86_64-linux-gnu.2.17`)
     fn zig_target_for_rust_target(target: &Target) -> Option<String> {
-        match target.triple.as_str() {
-            "x86_64-unknown-linux-gnu" => Some("x86_64-linux-gnu".to_string()),
-            "x86_64-unknown-linux-musl" => Some("x86_64-linux-musl".to_string()),
-            "aarch64-unknown-linux-gnu" => Some("aarch64-linux-gnu".to_string()),
-            "aarch64-unknown-linux-musl" => Some("aarch64-linux-musl".to_string()),
-            "armv7-unknown-linux-gnueabihf" => Some("arm-linux-gnueabihf".to_string()),
-            "arm-unknown-linux-gnueabihf" => Some("arm-linux-gnueabihf".to_string()),
-            "i686-unknown-linux-gnu" => Some("i386-linux-gnu".to_string()),
-            "x86_64-pc-windows-gnu" => Some("x86_64-windows-gnu".to_string()),
-            "i686-pc-windows-gnu" => Some("i686-windows-gnu".to_string()),
-            _ => None,
-        }
+        let zig_target = zig_target_entry(&target.triple)?.zig;
+        Some(match &target.glibc {
+            Some(glibc) => format!("{zig_target}.{glibc}"),
+            None => zig_target.to_string(),
+        })
     }
 
     /// Create wrapper scripts for a target
     ///
-    /// Creates executable wrapper scripts that invoke `zig cc -target <target>` and `zig ar`.
-    /// These wrappers are needed because Cargo expects a single executable path for CC/AR,
-    /// not a command with arguments.
+    /// Creates executable wrapper scripts that invoke `zig cc -target <target>`,
+    /// `zig c++ -target <target>` and `zig ar`. These wrappers are needed
+    /// because Cargo expects a single executable path for CC/AR, not a
+    /// command with arguments.
     pub fn create_wrappers(&self, target: &Target) -> Result<HashMap<String, PathBuf>> {
         let zig_target = Self::zig_target_for_rust_target(target).ok_or_else(|| {
             Error::Toolchain(format!("Target {} not supported by Zig", target.triple))
@@ -162,57 +284,32 @@ impl ZigToolchain {
         })?;
 
         let mut wrappers = HashMap::new();
-
-        // Create CC wrapper
-        let cc_wrapper_path = self.cache_dir.join(format!("{}-cc", target.triple));
-        let cc_wrapper_content = if cfg!(windows) {
-            format!("@echo off\nzig cc -target {zig_target} %*\n")
-        } else {
-            format!("#!/bin/sh\nexec zig cc -target {zig_target} \"$@\"\n")
-        };
-
-        fs::write(&cc_wrapper_path, cc_wrapper_content)
-            .map_err(|e| Error::Toolchain(format!("Failed to create CC wrapper: {e}")))?;
-
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(&cc_wrapper_path)
-                .map_err(|e| Error::Toolchain(format!("Failed to get wrapper permissions: {e}")))?
-                .permissions();
-            perms.set_mode(0o755);
-            fs::set_permissions(&cc_wrapper_path, perms)
-                .map_err(|e| Error::Toolchain(format!("Failed to set wrapper permissions: {e}")))?;
-        }
-
-        wrappers.insert("CC".to_string(), cc_wrapper_path.clone());
-        wrappers.insert("LINKER".to_string(), cc_wrapper_path);
-
-        // Create AR wrapper (same for all targets)
-        let ar_wrapper_path = self.cache_dir.join("zig-ar");
-        if !ar_wrapper_path.exists() {
-            let ar_wrapper_content = if cfg!(windows) {
-                "@echo off\nzig ar %*\n"
+        let zig = self.zig_path.display();
+
+        // Create CC and CXX wrappers
+        for (name, command, path) in [
+            ("CC", "cc", self.cc_wrapper_path(target)),
+            ("CXX", "c++", self.cxx_wrapper_path(target)),
+        ] {
+            let content = if cfg!(windows) {
+                format!("@echo off\n\"{zig}\" {command} -target {zig_target} %*\n")
             } else {
-                "#!/bin/sh\nexec zig ar \"$@\"\n"
+                format!("#!/bin/sh\nexec \"{zig}\" {command} -target {zig_target} \"$@\"\n")
             };
+            write_wrapper(&path, &content, name)?;
+            wrappers.insert(name.to_string(), path);
+        }
+        wrappers.insert("LINKER".to_string(), self.cc_wrapper_path(target));
 
-            fs::write(&ar_wrapper_path, ar_wrapper_content)
-                .map_err(|e| Error::Toolchain(format!("Failed to create AR wrapper: {e}")))?;
-
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                let mut perms = fs::metadata(&ar_wrapper_path)
-                    .map_err(|e| {
-                        Error::Toolchain(format!("Failed to get AR wrapper permissions: {e}"))
-                    })?
-                    .permissions();
-                perms.set_mode(0o755);
-                fs::set_permissions(&ar_wrapper_path, perms).map_err(|e| {
-                    Error::Toolchain(format!("Failed to set AR wrapper permissions: {e}"))
-                })?;
-            }
+        // Create AR wrapper (same for all targets of this Zig)
+        let ar_wrapper_path = self.ar_wrapper_path();
+        let ar_wrapper_content = if cfg!(windows) {
+            format!("@echo off\n\"{zig}\" ar %*\n")
+        } else {
+            format!("#!/bin/sh\nexec \"{zig}\" ar \"$@\"\n")
+        };
+        if fs::read_to_string(&ar_wrapper_path).ok().as_deref() != Some(&ar_wrapper_content) {
+            write_wrapper(&ar_wrapper_path, &ar_wrapper_content, "AR")?;
         }
 
         wrappers.insert("AR".to_string(), ar_wrapper_path);
@@ -250,29 +347,84 @@ impl ZigToolchain {
             )));
         }
 
-        // Create wrapper scripts
-        let wrappers = self.create_wrappers(target)?;
+        if let Some(glibc) = &target.glibc {
+            self.check_glibc(target, glibc)?;
+        }
 
-        let mut env = HashMap::new();
+        self.create_wrappers(target)?;
+        Ok(self.planned_environment(target))
+    }
 
-        // Set CC and AR
-        if let Some(cc) = wrappers.get("CC") {
-            env.insert("CC".to_string(), cc.clone());
-        }
-        if let Some(ar) = wrappers.get("AR") {
-            env.insert("AR".to_string(), ar.clone());
-        }
+    /// Environment variables for a target, without creating the wrappers
+    ///
+    /// Used to describe a build before running it; the paths only exist once
+    /// [`Self::environment_for_target`] has been called for the target.
+    #[must_use]
+    pub fn planned_environment(&self, target: &Target) -> HashMap<String, PathBuf> {
+        let cc = self.cc_wrapper_path(target);
+        let linker_env_var = format!(
+            "CARGO_TARGET_{}_LINKER",
+            target.triple.to_uppercase().replace('-', "_")
+        );
+        HashMap::from([
+            (cc_var("CC", &target.triple), cc.clone()),
+            (cc_var("CXX", &target.triple), self.cxx_wrapper_path(target)),
+            (cc_var("AR", &target.triple), self.ar_wrapper_path()),
+            (linker_env_var, cc),
+        ])
+    }
+
+    /// Path of the `zig cc` wrapper for a target
+    fn cc_wrapper_path(&self, target: &Target) -> PathBuf {
+        self.cache_dir.join(wrapper_file_name(&format!(
+            "{}-cc",
+            target.versioned_triple()
+        )))
+    }
+
+    /// Path of the `zig c++` wrapper for a target
+    fn cxx_wrapper_path(&self, target: &Target) -> PathBuf {
+        self.cache_dir.join(wrapper_file_name(&format!(
+            "{}-c++",
+            target.versioned_triple()
+        )))
+    }
 
-        // Set CARGO_TARGET_*_LINKER
-        if let Some(linker) = wrappers.get("LINKER") {
-            let linker_env_var = format!(
-                "CARGO_TARGET_{}_LINKER",
-                target.triple.to_uppercase().replace('-', "_")
-            );
-            env.insert(linker_env_var, linker.clone());
+    /// Path of the `zig ar` wrapper, shared by all targets
+    fn ar_wrapper_path(&self) -> PathBuf {
+        self.cache_dir.join(wrapper_file_name("zig-ar"))
+    }
+
+    /// Check that this Zig can link `target` against glibc `glibc`
+    ///
+    /// # Errors
+    /// Returns an error if the version predates glibc's support for the
+    /// architecture or is newer than the glibc versions Zig ships
+    pub fn check_glibc(&self, target: &Target, glibc: &str) -> Result<()> {
+        let requested = parse_glibc(glibc)
+            .ok_or_else(|| Error::Toolchain(format!("Invalid glibc version '{glibc}'")))?;
+
+        let minimum = min_glibc_for_arch(&target.arch);
+        if requested < minimum {
+            return Err(Error::Toolchain(format!(
+                "glibc {glibc} predates {} support; the oldest glibc for {} is 2.{}",
+                target.arch, target.triple, minimum.1
+            )));
         }
 
-        Ok(env)
+        // `zig targets` lists the glibc versions Zig can link against
+        let output = Command::new(&self.zig_path)
+            .arg("targets")
+            .output()
+            .map_err(|e| Error::Toolchain(format!("Failed to run zig targets: {e}")))?;
+        let versions = glibc_versions(&String::from_utf8_lossy(&output.stdout));
+        match versions.last() {
+            Some(&newest) if requested > newest => Err(Error::Toolchain(format!(
+                "Zig {} supports glibc up to 2.{}, not {glibc}; upgrade Zig to target it",
+                self.version, newest.1
+            ))),
+            _ => Ok(()),
+        }
     }
 
     /// Clean up wrapper scripts cache
@@ -287,14 +439,92 @@ impl ZigToolchain {
     /// Get a summary of Zig's capabilities
     #[must_use]
     pub fn info(&self) -> String {
+        let supported: Vec<&str> = ZIG_TARGETS
+            .iter()
+            .filter(|t| self.is_at_least(t.since))
+            .map(|t| t.rust)
+            .collect();
         format!(
-            "Zig {} ({})\nSupports: Linux (x86_64, aarch64, armv7), Windows (x86_64, i686)\nLimitations: musl may have linking issues, macOS/wasm not supported",
+            "Zig {} ({})\nSupports: {}\nLimitations: musl may have linking issues, macOS/wasm not supported",
             self.version,
-            self.zig_path.display()
+            self.zig_path.display(),
+            supported.join(", ")
         )
     }
 }
 
+/// Write an executable wrapper script
+fn write_wrapper(path: &Path, content: &str, name: &str) -> Result<()> {
+    fs::write(path, content)
+        .map_err(|e| Error::Toolchain(format!("Failed to create {name} wrapper: {e}")))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut perms = fs::metadata(path)
+            .map_err(|e| {
+                Error::Toolchain(format!("Failed to get {name} wrapper permissions: {e}"))
+            })?
+            .permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(path, perms).map_err(|e| {
+            Error::Toolchain(format!("Failed to set {name} wrapper permissions: {e}"))
+        })?;
+    }
+    Ok(())
+}
+
+/// File name of a wrapper script; Windows only runs batch files with a
+/// `.cmd` or `.bat` extension
+fn wrapper_file_name(stem: &str) -> String {
+    if cfg!(windows) {
+        format!("{stem}.cmd")
+    } else {
+        stem.to_string()
+    }
+}
+
+/// Parse "2.17" or "2.17.0" into (2, 17)
+fn parse_glibc(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    Some((major, minor))
+}
+
+/// Oldest glibc release that supports an architecture
+fn min_glibc_for_arch(arch: &str) -> (u32, u32) {
+    match arch {
+        "aarch64" => (2, 17),
+        "riscv64gc" | "riscv64" => (2, 27),
+        "loongarch64" => (2, 36),
+        _ => (2, 0),
+    }
+}
+
+/// glibc versions in `zig targets` output, oldest first
+///
+/// Older Zig prints JSON (`"glibc": ["2.0.0", ...]`), newer Zig prints ZON
+/// (`.glibc = .{ "2.0.0", ... }`); both list the versions as strings.
+fn glibc_versions(targets: &str) -> Vec<(u32, u32)> {
+    let Some(start) = targets.find("\"glibc\"").or_else(|| targets.find(".glibc")) else {
+        return Vec::new();
+    };
+    let rest = &targets[start..];
+    let Some(open) = rest.find(['[', '{']) else {
+        return Vec::new();
+    };
+    let Some(close) = rest[open..].find([']', '}']) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<(u32, u32)> = rest[open + 1..open + close]
+        .split(',')
+        .filter_map(|item| parse_glibc(item.trim().trim_matches('"')))
+        .collect();
+    versions.sort_unstable();
+    versions
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -332,6 +562,70 @@ mod tests {
         assert_eq!(zig_target, Some("aarch64-linux-gnu".to_string()));
     }
 
+    #[test]
+    fn test_zig_targets_gated_on_version() {
+        let zig = |version: &str| ZigToolchain {
+            zig_path: PathBuf::from("zig"),
+            version: version.to_string(),
+            cache_dir: PathBuf::new(),
+        };
+        let loongarch = Target::from_triple("loongarch64-unknown-linux-gnu").unwrap();
+        assert!(ZigToolchain::supports_target_name(&loongarch.triple));
+        assert!(!zig("0.11.0").supports_target(&loongarch));
+        assert!(zig("0.12.0").supports_target(&loongarch));
+        assert!(zig("0.14.0-dev.2+abc123").supports_target(&loongarch));
+        assert!(!zig("0.11.0").info().contains("loongarch64"));
+        assert_eq!(
+            ZigToolchain::required_version(&loongarch.triple).as_deref(),
+            Some("0.12")
+        );
+
+        let windows_arm = Target::from_triple("aarch64-pc-windows-gnullvm").unwrap();
+        assert_eq!(
+            ZigToolchain::zig_target_for_rust_target(&windows_arm).as_deref(),
+            Some("aarch64-windows-gnu")
+        );
+        assert!(!ZigToolchain::supports_target_name("x86_64-apple-darwin"));
+        assert!(ZigToolchain::supported_target_names().any(|t| t == "s390x-unknown-linux-gnu"));
+
+        let freebsd = Target::from_triple("x86_64-unknown-freebsd").unwrap();
+        assert_eq!(
+            ZigToolchain::zig_target_for_rust_target(&freebsd).as_deref(),
+            Some("x86_64-freebsd")
+        );
+        assert!(!zig("0.13.0").supports_target(&freebsd));
+        assert!(zig("0.14.0").supports_target(&freebsd));
+        assert!(!ZigToolchain::supports_target_name(
+            "x86_64-unknown-openbsd"
+        ));
+    }
+
+    #[test]
+    fn test_zig_target_with_glibc() {
+        let target = Target::from_triple("x86_64-unknown-linux-gnu.2.17").unwrap();
+        assert_eq!(
+            ZigToolchain::zig_target_for_rust_target(&target).as_deref(),
+            Some("x86_64-linux-gnu.2.17")
+        );
+    }
+
+    #[test]
+    fn test_glibc_versions() {
+        let json = r#"{ "abi": ["gnu"], "glibc": [
+            "2.0.0",
+            "2.17.0",
+            "2.38.0"
+        ], "cpus": {} }"#;
+        assert_eq!(glibc_versions(json), [(2, 0), (2, 17), (2, 38)]);
+
+        let zon = ".{ .libc = .{ \"x86_64-linux-gnu\" }, .glibc = .{\n    \"2.0.0\",\n    \"2.39.0\",\n}, }";
+        assert_eq!(glibc_versions(zon), [(2, 0), (2, 39)]);
+
+        assert!(glibc_versions("no glibc list").is_empty());
+        assert_eq!(min_glibc_for_arch("aarch64"), (2, 17));
+        assert_eq!(parse_glibc("2.17"), Some((2, 17)));
+    }
+
     #[test]
     fn test_create_wrappers() {
         if let Ok(Some(zig)) = ZigToolchain::detect() {
@@ -0,0 +1,248 @@
+//! Zig releases managed by xcargo
+//!
+//! `xcargo toolchain zig install <version>` and projects that pin
+//! `zig.version` get Zig from ziglang.org's release index instead of PATH.
+//! Releases are unpacked into `~/.xcargo/zig/<version>`, after their SHA-256
+//! is checked against the index. Like the container support, downloading
+//! goes through the `curl` and `tar` commands rather than an HTTP client.
+
+use crate::cache::sha256_file;
+use crate::error::{Error, Result};
+use crate::output::helpers;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::network::tls_advice;
+use super::offline::require_network;
+
+/// Release index listing every Zig release and its downloads
+pub const ZIG_INDEX_URL: &str = "https://ziglang.org/download/index.json";
+
+/// Environment variable to fetch the release index from a mirror instead
+pub const ZIG_INDEX_ENV: &str = "XCARGO_ZIG_INDEX";
+
+/// One platform's archive of a Zig release
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ZigDownload {
+    /// Archive URL
+    tarball: String,
+
+    /// Expected SHA-256 of the archive, hex encoded
+    shasum: String,
+}
+
+/// Directory holding the managed Zig releases, `~/.xcargo/zig`
+#[must_use]
+pub fn managed_zig_dir() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".xcargo").join("zig"))
+}
+
+/// The `zig` binary of a managed release, if it is installed
+#[must_use]
+pub fn managed_zig(version: &str) -> Option<PathBuf> {
+    let zig = managed_zig_dir()?
+        .join(version)
+        .join(format!("zig{}", std::env::consts::EXE_SUFFIX));
+    zig.is_file().then_some(zig)
+}
+
+/// Versions of the installed managed releases, sorted
+#[must_use]
+pub fn installed_zig_versions() -> Vec<String> {
+    let Some(Ok(entries)) = managed_zig_dir().map(fs::read_dir) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<String> = entries
+        .filter_map(std::result::Result::ok)
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.') && managed_zig(name).is_some())
+        .collect();
+    versions.sort();
+    versions
+}
+
+/// Download, verify, and unpack a Zig release into `~/.xcargo/zig/<version>`
+///
+/// Returns the path of its `zig` binary. An installed release is reused.
+///
+/// # Errors
+/// Returns an error if offline, if the index has no such release for this
+/// host, or if downloading, verifying, or unpacking fails
+pub fn install_zig(version: &str) -> Result<PathBuf> {
+    if let Some(zig) = managed_zig(version) {
+        return Ok(zig);
+    }
+    require_network(&format!("download Zig {version}"))?;
+    let root = managed_zig_dir()
+        .ok_or_else(|| Error::Toolchain("Could not determine home directory".to_string()))?;
+    fs::create_dir_all(&root)?;
+
+    let platform = host_platform();
+    let index_url = std::env::var(ZIG_INDEX_ENV).unwrap_or_else(|_| ZIG_INDEX_URL.to_string());
+    helpers::progress(format!("Looking up Zig {version} for {platform}..."));
+    let index = String::from_utf8_lossy(&fetch(&index_url, None)?).into_owned();
+    let download = find_download(&index, version, &platform)?;
+
+    // Work in dot directories, so a failed install never looks installed
+    let archive_name = download.tarball.rsplit('/').next().unwrap_or("zig.tar.xz");
+    let archive = root.join(format!(".download-{archive_name}"));
+    let staging = root.join(format!(".unpack-{version}"));
+    let result = (|| {
+        helpers::progress(format!("Downloading {}...", download.tarball));
+        fetch(&download.tarball, Some(&archive))?;
+
+        let actual = sha256_file(&archive)?;
+        if !actual.eq_ignore_ascii_case(&download.shasum) {
+            return Err(Error::Toolchain(format!(
+                "Checksum mismatch for {archive_name}: expected {}, got {actual}",
+                download.shasum
+            )));
+        }
+        helpers::success(format!("Verified SHA-256 {actual}"));
+
+        unpack(&archive, &staging)?;
+        let unpacked = single_subdir(&staging)?;
+        let dest = root.join(version);
+        fs::rename(&unpacked, &dest)?;
+        managed_zig(version).ok_or_else(|| {
+            Error::Toolchain(format!("{archive_name} does not contain a zig binary"))
+        })
+    })();
+
+    let _ = fs::remove_file(&archive);
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+/// Zig's name for the host platform, e.g. "x86_64-linux" or "aarch64-macos"
+fn host_platform() -> String {
+    let arch = match std::env::consts::ARCH {
+        "x86" => "x86",
+        "arm" => "armv7a",
+        "powerpc64" => "powerpc64le",
+        arch => arch,
+    };
+    format!("{arch}-{}", std::env::consts::OS)
+}
+
+/// The archive for `version` on `platform` in the release index
+fn find_download(index: &str, version: &str, platform: &str) -> Result<ZigDownload> {
+    let index: serde_json::Value = serde_json::from_str(index)
+        .map_err(|e| Error::Toolchain(format!("Invalid Zig release index: {e}")))?;
+    let release = index.get(version).ok_or_else(|| {
+        Error::Toolchain(format!(
+            "Zig {version} is not a Zig release; see https://ziglang.org/download/"
+        ))
+    })?;
+    let entry = release
+        .get(platform)
+        .ok_or_else(|| Error::Toolchain(format!("Zig {version} has no download for {platform}")))?;
+    let field = |name: &str| {
+        entry
+            .get(name)
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| {
+                Error::Toolchain(format!(
+                    "Zig release index has no {name} for {version} on {platform}"
+                ))
+            })
+    };
+    Ok(ZigDownload {
+        tarball: field("tarball")?,
+        shasum: field("shasum")?,
+    })
+}
+
+/// Fetch `url` with curl, into `dest` or returning the body
+fn fetch(url: &str, dest: Option<&Path>) -> Result<Vec<u8>> {
+    let mut cmd = Command::new("curl");
+    cmd.args(["--fail", "--silent", "--show-error", "--location"]);
+    if let Some(dest) = dest {
+        cmd.arg("--output").arg(dest);
+    }
+    let output = cmd.arg(url).output().map_err(|e| {
+        Error::Toolchain(format!("Failed to run curl (needed to download Zig): {e}"))
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Toolchain(format!(
+            "Failed to download {url}: {}{}",
+            stderr.trim(),
+            tls_advice(&stderr)
+        )));
+    }
+    Ok(output.stdout)
+}
+
+/// Unpack a .tar.xz or .zip archive into `dest`
+fn unpack(archive: &Path, dest: &Path) -> Result<()> {
+    let _ = fs::remove_dir_all(dest);
+    fs::create_dir_all(dest)?;
+    // bsdtar (macOS, Windows) reads zip files too
+    let status = Command::new("tar")
+        .arg("-xf")
+        .arg(archive)
+        .arg("-C")
+        .arg(dest)
+        .status()
+        .map_err(|e| Error::Toolchain(format!("Failed to run tar: {e}")))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(Error::Toolchain(format!(
+            "Failed to unpack {}",
+            archive.display()
+        )))
+    }
+}
+
+/// The single top-level directory of an unpacked release
+fn single_subdir(dir: &Path) -> Result<PathBuf> {
+    let mut dirs = fs::read_dir(dir)?
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir());
+    match (dirs.next(), dirs.next()) {
+        (Some(only), None) => Ok(only),
+        _ => Err(Error::Toolchain(
+            "Unexpected Zig archive layout: expected a single top-level directory".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX: &str = r#"{
+        "master": { "version": "0.15.0-dev.1+abc" },
+        "0.13.0": {
+            "date": "2024-06-07",
+            "x86_64-linux": {
+                "tarball": "https://ziglang.org/download/0.13.0/zig-linux-x86_64-0.13.0.tar.xz",
+                "shasum": "d45312e61ebcc48032b77bc4cf7fd6915c11fa16e4aad116b66c9468211230ea",
+                "size": "47082308"
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_find_download() {
+        let download = find_download(INDEX, "0.13.0", "x86_64-linux").unwrap();
+        assert!(download.tarball.ends_with("zig-linux-x86_64-0.13.0.tar.xz"));
+        assert_eq!(download.shasum.len(), 64);
+
+        let err = find_download(INDEX, "0.13.0", "aarch64-macos").unwrap_err();
+        assert!(err.to_string().contains("no download for aarch64-macos"));
+        assert!(find_download(INDEX, "0.99.0", "x86_64-linux").is_err());
+    }
+
+    #[test]
+    fn test_host_platform() {
+        let platform = host_platform();
+        assert!(platform.starts_with(std::env::consts::ARCH) || platform.contains('-'));
+        assert!(platform.ends_with(std::env::consts::OS));
+    }
+}
@@ -22,6 +22,7 @@ async fn test_parallel_build_empty_targets() -> Result<()> {
 async fn test_parallel_build_single_target() -> Result<()> {
     let builder = Builder::new()?;
     let mut options = BuildOptions::default();
+    options.lock = false;
     options.operation = CargoOperation::Check;
 
     // Use the current host target
@@ -82,6 +83,7 @@ async fn test_parallel_build_operations() -> Result<()> {
 
     for operation in operations {
         let mut options = BuildOptions::default();
+        options.lock = false;
         options.operation = operation.clone();
 
         let targets: Vec<String> = vec![];
@@ -100,6 +102,7 @@ async fn test_parallel_build_concurrent_execution() -> Result<()> {
 
     let builder = Builder::new()?;
     let mut options = BuildOptions::default();
+    options.lock = false;
     options.operation = CargoOperation::Check;
 
     let targets: Vec<String> = vec![];
@@ -122,6 +125,7 @@ async fn test_parallel_build_concurrent_execution() -> Result<()> {
 async fn test_parallel_build_with_release_flag() -> Result<()> {
     let builder = Builder::new()?;
     let mut options = BuildOptions::default();
+    options.lock = false;
     options.operation = CargoOperation::Check;
     options.release = true;
 
@@ -137,6 +141,7 @@ async fn test_parallel_build_with_release_flag() -> Result<()> {
 async fn test_parallel_build_options_cloning() -> Result<()> {
     // Verify that BuildOptions can be cloned for parallel builds
     let mut options = BuildOptions::default();
+    options.lock = false;
     options.operation = CargoOperation::Build;
     options.release = true;
     options.target = Some("test-target".to_string());
@@ -155,6 +160,7 @@ async fn test_parallel_build_error_collection() -> Result<()> {
     // Test that errors are collected properly
     let builder = Builder::new()?;
     let mut options = BuildOptions::default();
+    options.lock = false;
     options.operation = CargoOperation::Check;
 
     // Use a completely invalid target
@@ -70,6 +70,73 @@ fn test_test_help() {
         .stdout(predicate::str::contains("--release"));
 }
 
+#[test]
+fn test_bench_help() {
+    xcargo()
+        .args(["bench", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--target"))
+        .stdout(predicate::str::contains("--all"));
+}
+
+#[test]
+fn test_build_unknown_profile() {
+    let temp = TempDir::new().unwrap();
+    fs::write(
+        temp.path().join("xcargo.toml"),
+        "[profiles.release-all]\ntargets = [\"x86_64-unknown-linux-gnu\"]\n",
+    )
+    .unwrap();
+
+    xcargo()
+        .current_dir(temp.path())
+        .args(["build", "--profile", "release"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains("release-all"));
+}
+
+#[test]
+fn test_profile_list() {
+    let temp = TempDir::new().unwrap();
+    fs::write(
+        temp.path().join("xcargo.toml"),
+        "[profiles.ci]\ntargets = [\"x86_64-pc-windows-gnu\"]\n",
+    )
+    .unwrap();
+
+    xcargo()
+        .current_dir(temp.path())
+        .args(["profile", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ci"))
+        .stdout(predicate::str::contains("x86_64-pc-windows-gnu"));
+}
+
+// ============================================================================
+// Toolchain Commands
+// ============================================================================
+
+#[test]
+fn test_toolchain_info_help() {
+    xcargo()
+        .args(["toolchain", "info", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--format"))
+        .stdout(predicate::str::contains("json"));
+}
+
+#[test]
+fn test_toolchain_info_rejects_unknown_format() {
+    xcargo()
+        .args(["toolchain", "info", "stable", "--format", "yaml"])
+        .assert()
+        .failure();
+}
+
 // ============================================================================
 // Target Commands
 // ============================================================================
@@ -151,6 +218,400 @@ fn test_config_no_file() {
         .stdout(predicate::str::contains("No xcargo.toml found"));
 }
 
+#[test]
+fn test_config_plain_output() {
+    let temp_dir = TempDir::new().unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["--plain", "config"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No xcargo.toml found"))
+        .stdout(predicate::str::contains("\x1b").not())
+        .stdout(predicate::str::contains("─").not());
+}
+
+#[test]
+fn test_color_choice() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Piped output has no colors, and a dumb terminal gets ASCII icons
+    xcargo()
+        .current_dir(temp_dir.path())
+        .env("TERM", "dumb")
+        .arg("config")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("i No xcargo.toml found"))
+        .stdout(predicate::str::contains("\x1b").not());
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["--color", "always", "config"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b["));
+
+    xcargo()
+        .args(["--color", "sometimes", "config"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("expected auto, always, or never"));
+}
+
+#[test]
+fn test_quiet_keeps_output_and_errors() {
+    let temp_dir = TempDir::new().unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["-q", "config"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No xcargo.toml found").not())
+        .stdout(predicate::str::contains("[build]"));
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["--quiet", "clean"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Nothing selected to clean"));
+
+    xcargo()
+        .args(["-q", "-v", "config"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_log_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let log = temp_dir.path().join("xcargo.log");
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .arg("--log-file")
+        .arg(&log)
+        .args(["-q", "config"])
+        .assert()
+        .success();
+
+    // The log file gets every message, whatever the verbosity
+    let contents = fs::read_to_string(&log).unwrap();
+    let first = contents.lines().next().unwrap();
+    assert_eq!(&first[4..5], "-");
+    assert_eq!(&first[10..11], "T");
+    assert!(first.contains("Start    xcargo"));
+    assert!(contents.contains("Info     No xcargo.toml found"));
+    assert!(contents.contains("Exit     exit code 0"));
+}
+
+#[test]
+fn test_clean_requires_selection() {
+    let temp_dir = TempDir::new().unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .arg("clean")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Nothing selected to clean"));
+}
+
+#[test]
+fn test_clean_single_target() {
+    let temp_dir = TempDir::new().unwrap();
+    for triple in ["aarch64-unknown-linux-gnu", "x86_64-pc-windows-gnu"] {
+        let profile_dir = temp_dir.path().join("target").join(triple).join("debug");
+        fs::create_dir_all(&profile_dir).unwrap();
+        fs::write(profile_dir.join("app"), "binary").unwrap();
+    }
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["clean", "--target", "aarch64-unknown-linux-gnu", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would remove 1 directory"));
+    assert!(temp_dir.path().join("target/aarch64-unknown-linux-gnu").exists());
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["clean", "--target", "aarch64-unknown-linux-gnu"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 1 directory"));
+    assert!(!temp_dir.path().join("target/aarch64-unknown-linux-gnu").exists());
+    assert!(temp_dir.path().join("target/x86_64-pc-windows-gnu").exists());
+}
+
+#[test]
+fn test_install_requires_target() {
+    xcargo()
+        .args(["install", "ripgrep"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--target <TARGET>"));
+}
+
+#[test]
+fn test_export_tasks() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("xcargo.toml"),
+        "[targets]\ndefault = [\"aarch64-unknown-linux-gnu\"]\n",
+    )
+    .unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["export", "tasks", "--format", "just"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote just tasks for 1 target"));
+    let justfile = fs::read_to_string(temp_dir.path().join("justfile")).unwrap();
+    assert!(justfile.contains("xcargo build --target aarch64-unknown-linux-gnu"));
+
+    // An existing file is only replaced with --force
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["export", "tasks", "--format", "just"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("justfile already exists"));
+}
+
+#[test]
+fn test_from_collected_without_tests() {
+    let temp_dir = TempDir::new().unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["test", "--from-collected", "collected"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("No collected tests in collected"));
+
+    // Collecting only makes sense for tests that aren't run
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["test", "--collect", "collected"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--no-run"));
+}
+
+#[test]
+fn test_config_from_cargo_metadata() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n\
+         [package.metadata.xcargo.targets]\ndefault = [\"riscv64gc-unknown-linux-gnu\"]\n",
+    )
+    .unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["config", "--show-origin"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("riscv64gc-unknown-linux-gnu"))
+        .stdout(predicate::str::contains("xcargo metadata in"));
+}
+
+#[test]
+fn test_required_version_rejects_old_xcargo() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("xcargo.toml"),
+        "required_version = \">=99.0\"\n\n[some_future_section]\nenabled = true\n",
+    )
+    .unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .arg("config")
+        .assert()
+        .failure()
+        .code(2)
+        .stdout(predicate::str::contains("requires xcargo >=99.0"))
+        .stdout(predicate::str::contains("cargo install xcargo"));
+}
+
+#[test]
+fn test_init_from_template() {
+    let temp_dir = TempDir::new().unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["init", "--template", "wasm"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("from the wasm template"))
+        .stdout(predicate::str::contains("wasm-bindgen"));
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .arg("config")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("wasm32-wasip1"));
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["init", "--template", "desktop"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown template 'desktop'"));
+}
+
+#[test]
+fn test_unknown_subcommand_without_plugin() {
+    xcargo()
+        .arg("buidl")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("unrecognized subcommand 'buidl'"))
+        .stderr(predicate::str::contains("'build'"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_plugins_lists_path_executables() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let plugin = temp_dir.path().join("xcargo-deploy");
+    fs::write(&plugin, "#!/bin/sh\n").unwrap();
+    fs::set_permissions(&plugin, fs::Permissions::from_mode(0o755)).unwrap();
+
+    xcargo()
+        .env("PATH", temp_dir.path())
+        .arg("plugins")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("xcargo deploy"));
+}
+
+#[test]
+fn test_build_wasm_rejects_container() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["build", "--target", "wasm32-unknown-unknown", "--container"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(
+            "--container does not apply to wasm32-unknown-unknown",
+        ));
+}
+
+#[test]
+fn test_test_container_conflicts_with_from_collected() {
+    xcargo()
+        .args(["test", "--container", "--from-collected", "collected"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_toolchain_zig_install_needs_version() {
+    let temp_dir = TempDir::new().unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["toolchain", "zig", "install"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(
+            "xcargo.toml does not pin zig.version",
+        ));
+}
+
+#[test]
+fn test_build_plan_reports_errors_as_json() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("Cargo.toml"),
+        "[package]\nname = \"app\"\nversion = \"0.1.0\"\n",
+    )
+    .unwrap();
+
+    let output = xcargo()
+        .current_dir(temp_dir.path())
+        .args([
+            "build",
+            "--plan=json",
+            "--target",
+            "wasm32-unknown-unknown",
+            "--container",
+        ])
+        .output()
+        .unwrap();
+    let plans: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(plans[0]["target"], "wasm32-unknown-unknown");
+    assert!(plans[0]["errors"][0]
+        .as_str()
+        .unwrap()
+        .contains("--container does not apply"));
+    assert!(!temp_dir.path().join("target").exists());
+}
+
+#[test]
+fn test_build_unknown_target_group() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(
+        temp_dir.path().join("xcargo.toml"),
+        "[target-groups]\ndesktop = [\"x86_64-unknown-linux-gnu\"]\n",
+    )
+    .unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["build", "--target", "@mobile"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains(
+            "Unknown target group @mobile. Defined groups: @desktop",
+        ));
+}
+
+#[test]
+fn test_update_lock_without_lock_file() {
+    let temp_dir = TempDir::new().unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .arg("update-lock")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No xcargo.lock yet"));
+}
+
+#[test]
+fn test_update_lock_rejects_newer_format() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("xcargo.lock"), "version = 99\n").unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .arg("update-lock")
+        .assert()
+        .failure();
+}
+
 // ============================================================================
 // Init Command
 // ============================================================================
@@ -217,6 +678,24 @@ fn test_build_zig_conflicting_args() {
         .stderr(predicate::str::contains("cannot be used with"));
 }
 
+#[test]
+fn test_build_since_requires_all() {
+    xcargo()
+        .args(["build", "--since", "main"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--all"));
+}
+
+#[test]
+fn test_build_full_conflicts_with_since() {
+    xcargo()
+        .args(["build", "--all", "--full", "--since", "main"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
 // ============================================================================
 // Check Command (Basic Tests)
 // ============================================================================
@@ -231,6 +710,32 @@ fn test_check_conflicting_args() {
         .stderr(predicate::str::contains("cannot be used with"));
 }
 
+#[test]
+fn test_check_rejects_passthrough_target() {
+    // xcargo injects --target itself, so a second one after -- is an error
+    xcargo()
+        .args([
+            "check",
+            "--target",
+            "x86_64-unknown-linux-gnu",
+            "--",
+            "--target",
+            "x86_64-pc-windows-gnu",
+        ])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains("--target cannot be passed through"));
+}
+
+#[test]
+fn test_check_exclude_requires_workspace() {
+    xcargo()
+        .args(["check", "--", "--exclude", "xtask"])
+        .assert()
+        .code(2)
+        .stdout(predicate::str::contains("--exclude"));
+}
+
 // ============================================================================
 // Exit Codes
 // ============================================================================
@@ -258,3 +763,70 @@ fn test_verbose_flag_global() {
 fn test_verbose_flag_long() {
     xcargo().args(["--verbose", "version"]).assert().success();
 }
+
+#[test]
+fn test_no_first_run_flag_global() {
+    xcargo()
+        .args(["target", "list", "--no-first-run"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_yes_flag_global() {
+    xcargo()
+        .args(["build", "--help"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--yes"));
+}
+
+#[test]
+fn test_config_validate() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::write(
+        temp_dir.path().join("xcargo.toml"),
+        "[build]\njobs = 0\n\n[container]\nuse_when = \"sometimes\"\n",
+    )
+    .unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["config", "validate"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("xcargo.toml:2: build.jobs"))
+        .stdout(predicate::str::contains("xcargo.toml:5: container.use_when"));
+}
+
+#[test]
+fn test_stats() {
+    let temp_dir = TempDir::new().unwrap();
+    std::fs::create_dir(temp_dir.path().join(".xcargo")).unwrap();
+    std::fs::write(
+        temp_dir.path().join(".xcargo/builds.jsonl"),
+        concat!(
+            r#"{"finished_at":1,"target":"aarch64-unknown-linux-gnu","operation":"build","profile":"release","duration_ms":2000,"success":true}"#,
+            "\n",
+            r#"{"finished_at":2,"target":"aarch64-unknown-linux-gnu","operation":"build","profile":"release","duration_ms":9000,"success":false}"#,
+            "\n",
+        ),
+    )
+    .unwrap();
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .arg("stats")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("aarch64-unknown-linux-gnu"))
+        .stdout(predicate::str::contains("50%"))
+        .stdout(predicate::str::contains("2.00s"));
+
+    xcargo()
+        .current_dir(temp_dir.path())
+        .args(["stats", "--clear"])
+        .assert()
+        .success();
+    assert!(!temp_dir.path().join(".xcargo/builds.jsonl").exists());
+}
@@ -161,6 +161,20 @@ fn test_config_target_specific_settings() -> Result<()> {
         force_container: None,
         env: std::collections::HashMap::new(),
         rustflags: None,
+        cflags: None,
+        cxxflags: None,
+        runner: None,
+        features: None,
+        no_default_features: None,
+        components: None,
+        strip: None,
+        objcopy: None,
+        upx: None,
+        split_debuginfo: None,
+        hooks: None,
+        wasm: None,
+        strategies: None,
+        timeout: None,
     };
 
     config.targets.custom.insert(target_triple.clone(), target_config);
@@ -16,6 +16,7 @@ fn test_exit_code_values() {
     assert_eq!(ExitCode::BuildError as i32, 5);
     assert_eq!(ExitCode::ContainerError as i32, 6);
     assert_eq!(ExitCode::IoError as i32, 7);
+    assert_eq!(ExitCode::PolicyError as i32, 8);
     assert_eq!(ExitCode::UserCancelled as i32, 130);
 }
 
@@ -87,6 +88,14 @@ fn test_error_to_exit_code_build_failed() {
     assert_eq!(error.exit_code(), 5);
 }
 
+#[test]
+fn test_error_to_exit_code_cancelled() {
+    let error = Error::Cancelled {
+        target: "x86_64-unknown-linux-gnu".to_string(),
+    };
+    assert_eq!(error.exit_code(), 130);
+}
+
 #[test]
 fn test_error_to_exit_code_config() {
     let error = Error::Config("test".to_string());
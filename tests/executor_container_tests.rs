@@ -7,7 +7,7 @@
 
 use std::fs;
 use tempfile::TempDir;
-use xcargo::build::{BuildOptions, CargoOperation, Builder};
+use xcargo::build::{ArtifactSelection, BuildOptions, CargoOperation, Builder};
 use xcargo::target::Target;
 use xcargo::Result;
 
@@ -48,6 +48,14 @@ fn test_container_flag_explicit() -> Result<()> {
         use_container: true, // Explicitly request container
         use_zig: Some(false),
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -77,6 +85,14 @@ fn test_container_not_requested() -> Result<()> {
         use_container: false, // No container
         use_zig: Some(false),
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -111,6 +127,14 @@ fn test_container_with_cross_target() -> Result<()> {
         use_container: true, // Use container for cross-compilation
         use_zig: Some(false),
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -154,6 +178,14 @@ fn test_container_with_release_mode() -> Result<()> {
         use_container: true,
         use_zig: Some(false),
         operation: CargoOperation::Build,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -182,6 +214,14 @@ fn test_container_with_cargo_args() -> Result<()> {
         use_container: true,
         use_zig: Some(false),
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -209,6 +249,14 @@ fn test_container_priority_over_zig() -> Result<()> {
         use_container: true, // Container should take priority
         use_zig: Some(true), // Even if Zig requested
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -237,6 +285,14 @@ fn test_native_build_fallback() -> Result<()> {
         use_container: false, // No container
         use_zig: Some(false), // No Zig
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -1,7 +1,7 @@
 // Additional coverage tests for build executor
 // These tests focus on code paths not covered by existing tests
 
-use xcargo::build::{BuildOptions, Builder, CargoOperation};
+use xcargo::build::{ArtifactSelection, BuildOptions, Builder, CargoOperation};
 use xcargo::config::Config;
 use xcargo::error::Result;
 
@@ -26,6 +26,14 @@ fn test_build_options_with_all_fields() {
         use_container: true,
         use_zig: Some(true),
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     assert_eq!(options.target, Some("x86_64-unknown-linux-musl".to_string()));
@@ -113,6 +121,14 @@ fn test_build_options_partial_eq() {
         use_container: false,
         use_zig: None,
         operation: CargoOperation::Build,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let options2 = BuildOptions {
@@ -124,6 +140,14 @@ fn test_build_options_partial_eq() {
         use_container: false,
         use_zig: None,
         operation: CargoOperation::Build,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     // Verify they have the same values (manual comparison since BuildOptions doesn't derive PartialEq)
@@ -7,7 +7,7 @@
 
 use std::fs;
 use tempfile::TempDir;
-use xcargo::build::{BuildOptions, CargoOperation, Builder};
+use xcargo::build::{ArtifactSelection, BuildOptions, CargoOperation, Builder};
 use xcargo::target::Target;
 use xcargo::Result;
 
@@ -47,6 +47,14 @@ fn test_build_with_host_linker() -> Result<()> {
         use_container: false,
         use_zig: Some(false),
         operation: CargoOperation::Check, // Use check for faster test
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -74,6 +82,14 @@ fn test_linker_detection_verbose_mode() -> Result<()> {
         use_container: false,
         use_zig: Some(false),
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -110,6 +126,14 @@ fn test_cross_compile_without_linker() -> Result<()> {
         use_container: false,
         use_zig: Some(false), // Disable Zig to test linker detection
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -173,6 +197,14 @@ fn test_build_with_explicit_toolchain() -> Result<()> {
         use_container: false,
         use_zig: Some(false),
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -200,6 +232,14 @@ fn test_build_with_nightly_toolchain() -> Result<()> {
         use_container: false,
         use_zig: Some(false),
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -248,6 +288,14 @@ fn test_multiple_targets_with_linkers() -> Result<()> {
         use_container: false,
         use_zig: Some(false),
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build_all(&targets, &options);
@@ -273,6 +321,14 @@ fn test_target_preparation_with_toolchain() -> Result<()> {
         use_container: false,
         use_zig: Some(false),
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
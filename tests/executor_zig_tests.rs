@@ -8,7 +8,7 @@
 
 use std::fs;
 use tempfile::TempDir;
-use xcargo::build::{BuildOptions, CargoOperation, Builder};
+use xcargo::build::{ArtifactSelection, BuildOptions, CargoOperation, Builder};
 use xcargo::target::Target;
 use xcargo::toolchain::zig::ZigToolchain;
 use xcargo::Result;
@@ -49,6 +49,14 @@ fn test_zig_disabled_via_flag() -> Result<()> {
         use_container: false,
         use_zig: Some(false), // Explicitly disable Zig
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -76,6 +84,14 @@ fn test_zig_auto_mode_same_os() -> Result<()> {
         use_container: false,
         use_zig: None, // Auto mode - should NOT use Zig for same OS
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -103,6 +119,14 @@ fn test_zig_forced_flag() -> Result<()> {
         use_container: false,
         use_zig: Some(true), // Force Zig even for same OS
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -140,6 +164,14 @@ fn test_zig_cross_os_auto_detection() -> Result<()> {
         use_container: false,
         use_zig: None, // Auto mode - should TRY to use Zig for cross-OS
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -206,6 +238,14 @@ fn test_zig_with_verbose_output() -> Result<()> {
         use_container: false,
         use_zig: Some(true), // Try to use Zig
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -234,6 +274,14 @@ fn test_zig_unsupported_target_with_force() -> Result<()> {
         use_container: false,
         use_zig: Some(true), // Force Zig for unsupported target
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -264,6 +312,14 @@ fn test_zig_with_release_build() -> Result<()> {
         use_container: false,
         use_zig: Some(true),
         operation: CargoOperation::Build, // Full build
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -298,6 +354,14 @@ fn test_zig_detection_with_different_operations() -> Result<()> {
             use_container: false,
             use_zig: None, // Auto mode
             operation: op,
+            affected_since: None,
+            assume_yes: false,
+            collect_tests: None,
+            attest: false,
+            selection: ArtifactSelection::default(),
+            fail_fast: None,
+            output: None,
+            lock: false,
         };
 
         let result = builder.build(&options);
@@ -3,7 +3,7 @@
 
 use std::fs;
 use tempfile::TempDir;
-use xcargo::build::{BuildOptions, Builder, CargoOperation};
+use xcargo::build::{ArtifactSelection, BuildOptions, Builder, CargoOperation};
 use xcargo::error::Result;
 use xcargo::target::Target;
 
@@ -95,6 +95,14 @@ fn test_build_host_target() -> Result<()> {
         use_container: false,
         use_zig: None,
         operation: CargoOperation::Build,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     // This should succeed for the host target
@@ -127,6 +135,14 @@ fn test_check_operation() -> Result<()> {
         use_container: false,
         use_zig: None,
         operation: CargoOperation::Check,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -156,6 +172,14 @@ fn test_test_operation() -> Result<()> {
         use_container: false,
         use_zig: None,
         operation: CargoOperation::Test,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -185,6 +209,14 @@ fn test_release_build() -> Result<()> {
         use_container: false,
         use_zig: None,
         operation: CargoOperation::Build,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -222,6 +254,14 @@ fn test_verbose_build() -> Result<()> {
         use_container: false,
         use_zig: None,
         operation: CargoOperation::Build,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -251,6 +291,14 @@ fn test_build_with_cargo_args() -> Result<()> {
         use_container: false,
         use_zig: None,
         operation: CargoOperation::Build,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -281,6 +329,14 @@ fn test_missing_cargo_toml() -> Result<()> {
         use_container: false,
         use_zig: None,
         operation: CargoOperation::Build,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);
@@ -315,6 +371,14 @@ fn test_build_no_target_specified() -> Result<()> {
         use_container: false,
         use_zig: None,
         operation: CargoOperation::Build,
+        affected_since: None,
+        assume_yes: false,
+        collect_tests: None,
+        attest: false,
+        selection: ArtifactSelection::default(),
+        fail_fast: None,
+        output: None,
+        lock: false,
     };
 
     let result = builder.build(&options);